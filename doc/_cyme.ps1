@@ -30,6 +30,9 @@ Register-ArgumentCompleter -Native -CommandName 'cyme' -ScriptBlock {
             [CompletionResult]::new('--filter-name', '--filter-name', [CompletionResultType]::ParameterName, 'Filter on string contained in name')
             [CompletionResult]::new('--filter-serial', '--filter-serial', [CompletionResultType]::ParameterName, 'Filter on string contained in serial')
             [CompletionResult]::new('--filter-class', '--filter-class', [CompletionResultType]::ParameterName, 'Filter on USB class code')
+            [CompletionResult]::new('--filter-location', '--filter-location', [CompletionResultType]::ParameterName, 'Filter on where devices are relative to the machine: ''internal'' hard-wired devices such as a laptop webcam, ''external'' plug-in peripherals or ''dock''; a heuristic from sysfs ''removable'', the hub descriptors and ACPI physical location so devices of unknown location are filtered out')
+            [CompletionResult]::new('--where', '--where', [CompletionResultType]::ParameterName, 'Filter on a boolean expression of ''field operator value'' conditions combined with ''&&'', ''||'', ''!'' and parentheses; e.g. ''class==hid && vid==0x046d || name~"Webcam"''. Fields are vid, pid, bus, number, name, serial, manufacturer and class; ''='' or ''~'' match like the --filter flags, ''=='' is exact and ''!='' or ''!~'' negate')
+            [CompletionResult]::new('--highlight', '--highlight', [CompletionResultType]::ParameterName, 'Mark devices matching an expression like --where rather than filtering, keeping the full tree for context; e.g. ''vid==0x1d50 && name~Probe''')
             [CompletionResult]::new('-b', '-b', [CompletionResultType]::ParameterName, 'Specify the blocks which will be displayed for each device and in what order. Supply arg multiple times to specify multiple blocks')
             [CompletionResult]::new('--blocks', '--blocks', [CompletionResultType]::ParameterName, 'Specify the blocks which will be displayed for each device and in what order. Supply arg multiple times to specify multiple blocks')
             [CompletionResult]::new('--bus-blocks', '--bus-blocks', [CompletionResultType]::ParameterName, 'Specify the blocks which will be displayed for each bus and in what order. Supply arg multiple times to specify multiple blocks')
@@ -38,17 +41,38 @@ Register-ArgumentCompleter -Native -CommandName 'cyme' -ScriptBlock {
             [CompletionResult]::new('--endpoint-blocks', '--endpoint-blocks', [CompletionResultType]::ParameterName, 'Specify the blocks which will be displayed for each endpoint and in what order. Supply arg multiple times to specify multiple blocks')
             [CompletionResult]::new('--sort-devices', '--sort-devices', [CompletionResultType]::ParameterName, 'Sort devices operation')
             [CompletionResult]::new('--group-devices', '--group-devices', [CompletionResultType]::ParameterName, 'Group devices by value when listing')
+            [CompletionResult]::new('--max-depth', '--max-depth', [CompletionResultType]::ParameterName, 'Maximum depth of devices to print with --tree: 0 for buses only, 1 for devices directly on the bus and so on. Hidden devices are counted on the last printed line')
             [CompletionResult]::new('--color', '--color', [CompletionResultType]::ParameterName, 'Output coloring mode')
             [CompletionResult]::new('--encoding', '--encoding', [CompletionResultType]::ParameterName, 'Output character encoding')
             [CompletionResult]::new('--icon', '--icon', [CompletionResultType]::ParameterName, 'When to print icon blocks')
+            [CompletionResult]::new('--separator', '--separator', [CompletionResultType]::ParameterName, 'Separate list blocks with this string rather than a space, '' | '' for example')
+            [CompletionResult]::new('--json-fields', '--json-fields', [CompletionResultType]::ParameterName, 'Output only these fields for each device as json, supplied as comma separated block names. Implies --json and output is always a flat list of devices')
+            [CompletionResult]::new('--template', '--template', [CompletionResultType]::ParameterName, 'Render each device through this Handlebars-style template file, or the whole profile once with --tree; fields are those of the json output plus port_path')
+            [CompletionResult]::new('--get', '--get', [CompletionResultType]::ParameterName, 'Print only the raw value of this block for each matching device, one per line; for use in shell scripts')
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Write the output to a file with colour stripped rather than stdout; a summary is printed to stderr')
             [CompletionResult]::new('--from-json', '--from-json', [CompletionResultType]::ParameterName, 'Read from json output rather than profiling system')
+            [CompletionResult]::new('--from-descriptors', '--from-descriptors', [CompletionResultType]::ParameterName, 'Read a device from raw descriptor files written by `cyme export-descriptors` rather than profiling system')
+            [CompletionResult]::new('--from-usbview', '--from-usbview', [CompletionResultType]::ParameterName, 'Read a text or XML report saved by the Windows USBView tool rather than profiling system')
+            [CompletionResult]::new('--cache-ttl', '--cache-ttl', [CompletionResultType]::ParameterName, 'Maximum age in seconds of the profile used with --cached')
+            [CompletionResult]::new('--backend', '--backend', [CompletionResultType]::ParameterName, 'USB library to profile devices with when more than one is compiled in; defaults to nusb if available')
+            [CompletionResult]::new('--theme', '--theme', [CompletionResultType]::ParameterName, 'Built-in colour theme; cb-safe is distinguishable with red-green colour blindness and legible on dark and light backgrounds')
+            [CompletionResult]::new('--no-open-class', '--no-open-class', [CompletionResultType]::ParameterName, 'Do not open devices with this device or interface class for verbose data, since opening some devices, such as smartcard readers or printers, has side effects. Supply arg multiple times to specify multiple classes')
+            [CompletionResult]::new('--name-sources', '--name-sources', [CompletionResultType]::ParameterName, 'Order of sources for device names, comma separated; sources not listed are not used. lsusb prefers USB IDs: ''ids,os,descriptor''. Default ''user,os,descriptor,ids''')
             [CompletionResult]::new('-c', '-c', [CompletionResultType]::ParameterName, 'Path to user config file to use for custom icons, colours and default settings')
             [CompletionResult]::new('--config', '--config', [CompletionResultType]::ParameterName, 'Path to user config file to use for custom icons, colours and default settings')
-            [CompletionResult]::new('--mask-serials', '--mask-serials', [CompletionResultType]::ParameterName, 'Mask serial numbers with ''*'' or random chars')
+            [CompletionResult]::new('--log-file', '--log-file', [CompletionResultType]::ParameterName, 'Append logs to a file rather than stderr; logs at -zz level if --debug is not passed')
+            [CompletionResult]::new('--log-format', '--log-format', [CompletionResultType]::ParameterName, 'Format of logs')
+            [CompletionResult]::new('--log-filter', '--log-filter', [CompletionResultType]::ParameterName, 'Per-module log levels as ''module=level'' such as ''profiler=debug,udev=trace''; cyme modules include profiler, udev and display')
+            [CompletionResult]::new('--mask-serials', '--mask-serials', [CompletionResultType]::ParameterName, 'Mask serial numbers with ''*'', random chars or a hash which is the same for a device in every dump; Bluetooth addresses and paths containing the serial are masked too')
+            [CompletionResult]::new('--mask-salt', '--mask-salt', [CompletionResultType]::ParameterName, 'Salt for --mask-serials hash; set a private one so shared hashes cannot be matched to known serials')
+            [CompletionResult]::new('--speed-units', '--speed-units', [CompletionResultType]::ParameterName, 'Units of speeds in blocks and the lsusb tree; defaults to decimal for blocks and lsusb for the tree')
+            [CompletionResult]::new('--complete-candidates', '--complete-candidates', [CompletionResultType]::ParameterName, 'Print values of the connected devices for the shell completions to complete an argument with')
             [CompletionResult]::new('-l', '-l', [CompletionResultType]::ParameterName, 'Attempt to maintain compatibility with lsusb output')
             [CompletionResult]::new('--lsusb', '--lsusb', [CompletionResultType]::ParameterName, 'Attempt to maintain compatibility with lsusb output')
             [CompletionResult]::new('-t', '-t', [CompletionResultType]::ParameterName, 'Dump USB device hierarchy as a tree')
             [CompletionResult]::new('--tree', '--tree', [CompletionResultType]::ParameterName, 'Dump USB device hierarchy as a tree')
+            [CompletionResult]::new('--filter-unclaimed', '--filter-unclaimed', [CompletionResultType]::ParameterName, 'Filter on devices with interfaces of the active configuration no driver is bound to, marked with the ''tree-interface-unclaimed'' icon; Linux only as other platforms do not report bound drivers')
+            [CompletionResult]::new('--warn-duplicates', '--warn-duplicates', [CompletionResultType]::ParameterName, 'Colour serial numbers shared by more than one device, or missing, with the warning colour; duplicates are found before filtering. `cyme serials` reports them')
             [CompletionResult]::new('-v', '-v', [CompletionResultType]::ParameterName, 'Verbosity level (repeat provides count): 1 prints device configurations; 2 prints interfaces; 3 prints interface endpoints; 4 prints everything and more blocks')
             [CompletionResult]::new('--verbose', '--verbose', [CompletionResultType]::ParameterName, 'Verbosity level (repeat provides count): 1 prints device configurations; 2 prints interfaces; 3 prints interface endpoints; 4 prints everything and more blocks')
             [CompletionResult]::new('-m', '-m', [CompletionResultType]::ParameterName, 'Print more blocks by default at each verbosity')
@@ -56,6 +80,7 @@ Register-ArgumentCompleter -Native -CommandName 'cyme' -ScriptBlock {
             [CompletionResult]::new('--sort-buses', '--sort-buses', [CompletionResultType]::ParameterName, 'Sort devices by bus number. If using any sort-devices other than no-sort, this happens automatically')
             [CompletionResult]::new('--hide-buses', '--hide-buses', [CompletionResultType]::ParameterName, 'Hide empty buses when printing tree; those with no devices')
             [CompletionResult]::new('--hide-hubs', '--hide-hubs', [CompletionResultType]::ParameterName, 'Hide empty hubs when printing tree; those with no devices. When listing will hide hubs regardless of whether empty of not')
+            [CompletionResult]::new('--compact', '--compact', [CompletionResultType]::ParameterName, 'Collapse identical sibling devices, same VID:PID and name with nothing attached, into a single line with a ''xN'' count. Verbose output prints them all')
             [CompletionResult]::new('--list-root-hubs', '--list-root-hubs', [CompletionResultType]::ParameterName, 'Show root hubs when listing; Linux only')
             [CompletionResult]::new('--decimal', '--decimal', [CompletionResultType]::ParameterName, 'Show base16 values as base10 decimal instead')
             [CompletionResult]::new('--no-padding', '--no-padding', [CompletionResultType]::ParameterName, 'Disable padding to align blocks - will cause --headings to become maligned')
@@ -63,17 +88,249 @@ Register-ArgumentCompleter -Native -CommandName 'cyme' -ScriptBlock {
             [CompletionResult]::new('--ascii', '--ascii', [CompletionResultType]::ParameterName, 'Disables icons and utf-8 characters')
             [CompletionResult]::new('--no-icons', '--no-icons', [CompletionResultType]::ParameterName, 'Disables all Block icons by not using any IconTheme. Providing custom XxxxBlocks without any icons is a nicer way to do this')
             [CompletionResult]::new('--headings', '--headings', [CompletionResultType]::ParameterName, 'Show block headings')
+            [CompletionResult]::new('--stripe', '--stripe', [CompletionResultType]::ParameterName, 'Alternate the background of list rows with the ''stripe'' colour to make wide lines easier to follow')
+            [CompletionResult]::new('--grid', '--grid', [CompletionResultType]::ParameterName, 'Print the list as a table with a border around each block and a heading row; verbose configurations are not printed')
             [CompletionResult]::new('--json', '--json', [CompletionResultType]::ParameterName, 'Output as json format after sorting, filters and tree settings are applied; without -tree will be flattened dump of devices')
+            [CompletionResult]::new('--stub-parents', '--stub-parents', [CompletionResultType]::ParameterName, 'When filtering a tree, reduce non-matching parents of matched devices to slim stubs of location and identity only; keeps topology context of matches in --json output without the bulk')
+            [CompletionResult]::new('--xml', '--xml', [CompletionResultType]::ParameterName, 'Output as XML modeled on the Windows USBView export; a tree of devices with --tree and a list under each host controller otherwise')
+            [CompletionResult]::new('--count', '--count', [CompletionResultType]::ParameterName, 'Print only the number of devices after filters are applied; with --group-devices prints the count for each group')
+            [CompletionResult]::new('--list-icons', '--list-icons', [CompletionResultType]::ParameterName, 'Print the icon rule matched by each device from the user config icons or defaults; for checking icon theme rules')
+            [CompletionResult]::new('--bandwidth', '--bandwidth', [CompletionResultType]::ParameterName, 'Print the estimated bandwidth reserved by periodic (interrupt and isochronous) endpoints on each bus and by each device, for diagnosing ''not enough bandwidth'' errors; -v lists the endpoints')
+            [CompletionResult]::new('--tee', '--tee', [CompletionResultType]::ParameterName, 'With --output also print the output to stdout')
+            [CompletionResult]::new('--ansi-to-html', '--ansi-to-html', [CompletionResultType]::ParameterName, 'Convert coloured output to HTML spans styled with the theme colours, within a <pre> element, for reports and CI logs which strip ANSI colour codes; written to stdout or --output')
+            [CompletionResult]::new('--no-pager', '--no-pager', [CompletionResultType]::ParameterName, 'Do not pipe output longer than the terminal to $PAGER or ''less -R'' in interactive sessions')
+            [CompletionResult]::new('--fail-if-empty', '--fail-if-empty', [CompletionResultType]::ParameterName, 'Exit with code 1 and no output, other than the 0 of --count, if no devices match filters; errors then exit with code 2. Like grep, allows scripts to check a device is present')
+            [CompletionResult]::new('--inline-descriptors', '--inline-descriptors', [CompletionResultType]::ParameterName, 'With --tree and -v, print lsusb style descriptor sections inside the tree below each device rather than configuration, interface and endpoint blocks; -v prints configurations, -vv interfaces and -vvv endpoints')
+            [CompletionResult]::new('--dump-descriptors', '--dump-descriptors', [CompletionResultType]::ParameterName, 'Print the raw configuration, interface and endpoint descriptor bytes as an annotated hexdump below each, like `lsusb -D`; implies -vvv. Also included in --json')
+            [CompletionResult]::new('--active-config-only', '--active-config-only', [CompletionResultType]::ParameterName, 'Print only the configuration currently set on devices with more than one in verbose output, rather than all of them. The active configuration is marked with ''*''')
+            [CompletionResult]::new('--thunderbolt', '--thunderbolt', [CompletionResultType]::ParameterName, 'Include Thunderbolt/USB4 domain and router topology with --tree (Linux only)')
+            [CompletionResult]::new('--gadget', '--gadget', [CompletionResultType]::ParameterName, 'Include USB device controller (gadget) state and the configfs gadgets with their functions with --tree (Linux only)')
+            [CompletionResult]::new('--cached', '--cached', [CompletionResultType]::ParameterName, 'Use the profile cached by a previous run if younger than --cache-ttl and the connected devices have not changed, otherwise profile and update the cache')
             [CompletionResult]::new('-F', '-F ', [CompletionResultType]::ParameterName, 'Force pure libusb profiler on macOS rather than combining system_profiler output')
             [CompletionResult]::new('--force-libusb', '--force-libusb', [CompletionResultType]::ParameterName, 'Force pure libusb profiler on macOS rather than combining system_profiler output')
+            [CompletionResult]::new('--sudo', '--sudo', [CompletionResultType]::ParameterName, 'Re-run with sudo, prompting for the password, if devices could not be opened for verbose data due to permissions')
+            [CompletionResult]::new('--show-errors', '--show-errors', [CompletionResultType]::ParameterName, 'Check configuration descriptors against the lengths and counts they declare, showing mismatches after the output; useful to spot malformed descriptors in firmware')
+            [CompletionResult]::new('--strict', '--strict', [CompletionResultType]::ParameterName, 'Fail with the first descriptor which cannot be parsed rather than showing the device without it; for validating device firmware')
+            [CompletionResult]::new('--capabilities', '--capabilities', [CompletionResultType]::ParameterName, 'Print the data sources available to the profiler, such as udev and the USB backends, and the fields missing without them then exit; json with --json')
             [CompletionResult]::new('-z', '-z', [CompletionResultType]::ParameterName, 'Turn debugging information on. Alternatively can use RUST_LOG env: INFO, DEBUG, TRACE')
             [CompletionResult]::new('--debug', '--debug', [CompletionResultType]::ParameterName, 'Turn debugging information on. Alternatively can use RUST_LOG env: INFO, DEBUG, TRACE')
+            [CompletionResult]::new('--redact', '--redact', [CompletionResultType]::ParameterName, 'Redact serials, Bluetooth addresses, the hostname and user name and volume names in every output format so dumps are safe to share; the ''redact'' of the config sets what is redacted. Subcommands redact what they print, except rules, capture and the device actions which error')
             [CompletionResult]::new('--gen', '--gen', [CompletionResultType]::ParameterName, 'Generate cli completions and man page')
             [CompletionResult]::new('--system-profiler', '--system-profiler', [CompletionResultType]::ParameterName, 'Use the system_profiler command on macOS to get USB data')
             [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
             [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
             [CompletionResult]::new('-V', '-V ', [CompletionResultType]::ParameterName, 'Print version')
             [CompletionResult]::new('--version', '--version', [CompletionResultType]::ParameterName, 'Print version')
+            [CompletionResult]::new('diff', 'diff', [CompletionResultType]::ParameterValue, 'Compare two json dumps, or a dump with the live system, reporting added, removed and changed devices')
+            [CompletionResult]::new('assert', 'assert', [CompletionResultType]::ParameterValue, 'Assert that the live system matches an expected json dump, exiting non-zero and printing the discrepancies if not')
+            [CompletionResult]::new('export-descriptors', 'export-descriptors', [CompletionResultType]::ParameterValue, 'Export the raw descriptors of a device as binary files for replay in emulators or fuzzers, or to attach to bug reports')
+            [CompletionResult]::new('capture', 'capture', [CompletionResultType]::ParameterValue, 'Print how to capture the traffic of a device with Linux usbmon, or with --output capture it to a pcap')
+            [CompletionResult]::new('wait', 'wait', [CompletionResultType]::ParameterValue, 'Wait for a device matching the filters to connect, or with --gone for all matching to disconnect, then print it and exit')
+            [CompletionResult]::new('watch', 'watch', [CompletionResultType]::ParameterValue, 'Print devices matching the filters as they connect and disconnect, optionally running a command for each')
+            [CompletionResult]::new('history', 'history', [CompletionResultType]::ParameterValue, 'Show when devices recorded by `cyme watch --history` were first and last seen and how many times they re-enumerated')
+            [CompletionResult]::new('rules', 'rules', [CompletionResultType]::ParameterValue, 'Generate udev rules granting access to devices matching the filters without root, for the usb device node and its serial or HID nodes')
+            [CompletionResult]::new('lint', 'lint', [CompletionResultType]::ParameterValue, 'Check the descriptors of devices matching the filters, or all, against the USB specification like shellcheck for device firmware')
+            [CompletionResult]::new('audit', 'audit', [CompletionResultType]::ParameterValue, 'Flag potentially suspicious devices matching the filters, or all, for a quick endpoint USB security audit')
+            [CompletionResult]::new('serials', 'serials', [CompletionResultType]::ParameterValue, 'Report devices matching the filters, or all, which share a serial number or are without one; the bane of udev rules and provisioning keyed on serial')
+            [CompletionResult]::new('serve', 'serve', [CompletionResultType]::ParameterValue, 'Serve the system profile as a JSON HTTP API for dashboards and remote tooling')
+            [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
+            break
+        }
+        'cyme;diff' {
+            [CompletionResult]::new('--json', '--json', [CompletionResultType]::ParameterName, 'Output the differences as json')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help')
+            break
+        }
+        'cyme;assert' {
+            [CompletionResult]::new('--expect', '--expect', [CompletionResultType]::ParameterName, 'Expected json dump such as from `cyme --json --tree` on the provisioned system')
+            [CompletionResult]::new('--actual', '--actual', [CompletionResultType]::ParameterName, 'Compare with a json dump rather than profiling the live system')
+            [CompletionResult]::new('--ignore', '--ignore', [CompletionResultType]::ParameterName, 'Fields to ignore such as serial_num')
+            [CompletionResult]::new('--exact', '--exact', [CompletionResultType]::ParameterName, 'Also fail if there are devices which are not in the expected dump')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'cyme;export-descriptors' {
+            [CompletionResult]::new('-o', '-o', [CompletionResultType]::ParameterName, 'Directory to write the descriptor files to; created if it does not exist')
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Directory to write the descriptor files to; created if it does not exist')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'cyme;capture' {
+            [CompletionResult]::new('-o', '-o', [CompletionResultType]::ParameterName, 'Write a pcap of the device traffic to this file, or stdout if ''-'', until interrupted or --count packets')
+            [CompletionResult]::new('--output', '--output', [CompletionResultType]::ParameterName, 'Write a pcap of the device traffic to this file, or stdout if ''-'', until interrupted or --count packets')
+            [CompletionResult]::new('--count', '--count', [CompletionResultType]::ParameterName, 'Stop after capturing this many packets')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'cyme;wait' {
+            [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--vidpid', '--vidpid', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--name', '--name', [CompletionResultType]::ParameterName, 'Device name containing this string')
+            [CompletionResult]::new('--serial', '--serial', [CompletionResultType]::ParameterName, 'Device serial containing this string')
+            [CompletionResult]::new('--where', '--where', [CompletionResultType]::ParameterName, 'Boolean filter expression like the main --where')
+            [CompletionResult]::new('--timeout', '--timeout', [CompletionResultType]::ParameterName, 'Seconds to wait before exiting with an error; waits indefinitely if not supplied')
+            [CompletionResult]::new('--interval', '--interval', [CompletionResultType]::ParameterName, 'Milliseconds between each profile of the system')
+            [CompletionResult]::new('--gone', '--gone', [CompletionResultType]::ParameterName, 'Wait for all matching devices to disconnect rather than one to connect')
+            [CompletionResult]::new('--json', '--json', [CompletionResultType]::ParameterName, 'Print the devices as json rather than lsusb style lines')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'cyme;watch' {
+            [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--vidpid', '--vidpid', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--name', '--name', [CompletionResultType]::ParameterName, 'Device name containing this string')
+            [CompletionResult]::new('--serial', '--serial', [CompletionResultType]::ParameterName, 'Device serial containing this string')
+            [CompletionResult]::new('--where', '--where', [CompletionResultType]::ParameterName, 'Boolean filter expression like the main --where')
+            [CompletionResult]::new('--exec', '--exec', [CompletionResultType]::ParameterName, 'Command to spawn with the shell for each event; ''{field}'' is replaced with the shell quoted value of the device, fields are event, port_path, vidpid, vid, pid, name, serial, manufacturer, bus, number, dev_path and class. Each is also in the environment as CYME_FIELD, CYME_PORT_PATH for example, and name, serial and manufacturer are only passed that way since the device reports them. e.g. ''notify-send {event} {name}''')
+            [CompletionResult]::new('--interval', '--interval', [CompletionResultType]::ParameterName, 'Milliseconds between each profile of the system')
+            [CompletionResult]::new('--history-file', '--history-file', [CompletionResultType]::ParameterName, 'History file to record to rather than the default; implies --history')
+            [CompletionResult]::new('--policy', '--policy', [CompletionResultType]::ParameterName, 'Audit policy json file, as `cyme audit --policy`, to check devices which connect and those connected when starting against, reporting violations')
+            [CompletionResult]::new('--existing', '--existing', [CompletionResultType]::ParameterName, 'Also report devices already connected when starting')
+            [CompletionResult]::new('--notify', '--notify', [CompletionResultType]::ParameterName, 'Send a desktop notification for each event of devices matching the filters; requires the ''notify'' feature')
+            [CompletionResult]::new('--json', '--json', [CompletionResultType]::ParameterName, 'Print each event as a line of json rather than lsusb style lines')
+            [CompletionResult]::new('--history', '--history', [CompletionResultType]::ParameterName, 'Record events of devices matching the filters to the history in the user data directory, or --history-file, for `cyme history`')
+            [CompletionResult]::new('--deauthorize', '--deauthorize', [CompletionResultType]::ParameterName, 'Deauthorize devices with --policy errors so the kernel disconnects them, not for heuristics such as cloned serials; Linux only and requires the ''actions'' feature and root')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'cyme;history' {
+            [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--vidpid', '--vidpid', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--name', '--name', [CompletionResultType]::ParameterName, 'Device name containing this string')
+            [CompletionResult]::new('--serial', '--serial', [CompletionResultType]::ParameterName, 'Device serial containing this string')
+            [CompletionResult]::new('--where', '--where', [CompletionResultType]::ParameterName, 'Boolean filter expression like the main --where')
+            [CompletionResult]::new('--history-file', '--history-file', [CompletionResultType]::ParameterName, 'History file to read rather than the default in the user data directory')
+            [CompletionResult]::new('--events', '--events', [CompletionResultType]::ParameterName, 'Print each recorded event rather than a summary of each device')
+            [CompletionResult]::new('--json', '--json', [CompletionResultType]::ParameterName, 'Output as json')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'cyme;rules' {
+            [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--vidpid', '--vidpid', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--name', '--name', [CompletionResultType]::ParameterName, 'Device name containing this string')
+            [CompletionResult]::new('--serial', '--serial', [CompletionResultType]::ParameterName, 'Device serial containing this string')
+            [CompletionResult]::new('--where', '--where', [CompletionResultType]::ParameterName, 'Boolean filter expression like the main --where')
+            [CompletionResult]::new('--group', '--group', [CompletionResultType]::ParameterName, 'Group given access, such as plugdev; the user logged in at the seat is given access with the uaccess tag if not supplied')
+            [CompletionResult]::new('--mode', '--mode', [CompletionResultType]::ParameterName, 'File mode of the device nodes')
+            [CompletionResult]::new('--match-serial', '--match-serial', [CompletionResultType]::ParameterName, 'Also match the serial number so only that device is given access')
+            [CompletionResult]::new('--install', '--install', [CompletionResultType]::ParameterName, 'Write the rules to /etc/udev/rules.d and reload udev after confirmation; requires root')
+            [CompletionResult]::new('-y', '-y', [CompletionResultType]::ParameterName, 'Do not ask for confirmation before installing')
+            [CompletionResult]::new('--yes', '--yes', [CompletionResultType]::ParameterName, 'Do not ask for confirmation before installing')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'cyme;lint' {
+            [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--vidpid', '--vidpid', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--name', '--name', [CompletionResultType]::ParameterName, 'Device name containing this string')
+            [CompletionResult]::new('--serial', '--serial', [CompletionResultType]::ParameterName, 'Device serial containing this string')
+            [CompletionResult]::new('--where', '--where', [CompletionResultType]::ParameterName, 'Boolean filter expression like the main --where')
+            [CompletionResult]::new('--allow', '--allow', [CompletionResultType]::ParameterName, 'Checks to skip')
+            [CompletionResult]::new('--from-json', '--from-json', [CompletionResultType]::ParameterName, 'Lint a json dump rather than profiling the live system')
+            [CompletionResult]::new('--json', '--json', [CompletionResultType]::ParameterName, 'Output the findings as json')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'cyme;audit' {
+            [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--vidpid', '--vidpid', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--name', '--name', [CompletionResultType]::ParameterName, 'Device name containing this string')
+            [CompletionResult]::new('--serial', '--serial', [CompletionResultType]::ParameterName, 'Device serial containing this string')
+            [CompletionResult]::new('--where', '--where', [CompletionResultType]::ParameterName, 'Boolean filter expression like the main --where')
+            [CompletionResult]::new('--policy', '--policy', [CompletionResultType]::ParameterName, 'Policy json file of what is expected of this endpoint')
+            [CompletionResult]::new('--allow', '--allow', [CompletionResultType]::ParameterName, 'Checks to skip in addition to those allowed by the policy')
+            [CompletionResult]::new('--from-json', '--from-json', [CompletionResultType]::ParameterName, 'Audit a json dump rather than profiling the live system')
+            [CompletionResult]::new('--deauthorize', '--deauthorize', [CompletionResultType]::ParameterName, 'Deauthorize devices with policy errors so the kernel disconnects them until authorized again; not for cloned serials or storage HID which are heuristics. Linux only and requires the ''actions'' feature and root')
+            [CompletionResult]::new('--json', '--json', [CompletionResultType]::ParameterName, 'Output the findings as json')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'cyme;serials' {
+            [CompletionResult]::new('-d', '-d', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--vidpid', '--vidpid', [CompletionResultType]::ParameterName, 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]')
+            [CompletionResult]::new('--name', '--name', [CompletionResultType]::ParameterName, 'Device name containing this string')
+            [CompletionResult]::new('--serial', '--serial', [CompletionResultType]::ParameterName, 'Device serial containing this string')
+            [CompletionResult]::new('--where', '--where', [CompletionResultType]::ParameterName, 'Boolean filter expression like the main --where')
+            [CompletionResult]::new('--from-json', '--from-json', [CompletionResultType]::ParameterName, 'Report a json dump rather than profiling the live system')
+            [CompletionResult]::new('--json', '--json', [CompletionResultType]::ParameterName, 'Output the report as json')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'cyme;serve' {
+            [CompletionResult]::new('--http', '--http', [CompletionResultType]::ParameterName, 'Address to listen on; '':8080'' listens on localhost only. ''0.0.0.0:8080'' listens on all interfaces, exposing device serials and names to the network without authentication')
+            [CompletionResult]::new('--cors', '--cors', [CompletionResultType]::ParameterName, 'Allow web pages from ORIGIN, or ''*'' for any, to read the API with the Access-Control-Allow-Origin header; no other origin can by default')
+            [CompletionResult]::new('--interval', '--interval', [CompletionResultType]::ParameterName, 'Milliseconds between each profile of the system')
+            [CompletionResult]::new('--from-json', '--from-json', [CompletionResultType]::ParameterName, 'Serve a json dump rather than profiling the system, for developing dashboards without devices')
+            [CompletionResult]::new('--extra', '--extra', [CompletionResultType]::ParameterName, 'Include configurations, interfaces and endpoints; requires opening devices each profile')
+            [CompletionResult]::new('-h', '-h', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            [CompletionResult]::new('--help', '--help', [CompletionResultType]::ParameterName, 'Print help (see more with ''--help'')')
+            break
+        }
+        'cyme;help' {
+            [CompletionResult]::new('diff', 'diff', [CompletionResultType]::ParameterValue, 'Compare two json dumps, or a dump with the live system, reporting added, removed and changed devices')
+            [CompletionResult]::new('assert', 'assert', [CompletionResultType]::ParameterValue, 'Assert that the live system matches an expected json dump, exiting non-zero and printing the discrepancies if not')
+            [CompletionResult]::new('export-descriptors', 'export-descriptors', [CompletionResultType]::ParameterValue, 'Export the raw descriptors of a device as binary files for replay in emulators or fuzzers, or to attach to bug reports')
+            [CompletionResult]::new('capture', 'capture', [CompletionResultType]::ParameterValue, 'Print how to capture the traffic of a device with Linux usbmon, or with --output capture it to a pcap')
+            [CompletionResult]::new('wait', 'wait', [CompletionResultType]::ParameterValue, 'Wait for a device matching the filters to connect, or with --gone for all matching to disconnect, then print it and exit')
+            [CompletionResult]::new('watch', 'watch', [CompletionResultType]::ParameterValue, 'Print devices matching the filters as they connect and disconnect, optionally running a command for each')
+            [CompletionResult]::new('history', 'history', [CompletionResultType]::ParameterValue, 'Show when devices recorded by `cyme watch --history` were first and last seen and how many times they re-enumerated')
+            [CompletionResult]::new('rules', 'rules', [CompletionResultType]::ParameterValue, 'Generate udev rules granting access to devices matching the filters without root, for the usb device node and its serial or HID nodes')
+            [CompletionResult]::new('lint', 'lint', [CompletionResultType]::ParameterValue, 'Check the descriptors of devices matching the filters, or all, against the USB specification like shellcheck for device firmware')
+            [CompletionResult]::new('audit', 'audit', [CompletionResultType]::ParameterValue, 'Flag potentially suspicious devices matching the filters, or all, for a quick endpoint USB security audit')
+            [CompletionResult]::new('serials', 'serials', [CompletionResultType]::ParameterValue, 'Report devices matching the filters, or all, which share a serial number or are without one; the bane of udev rules and provisioning keyed on serial')
+            [CompletionResult]::new('serve', 'serve', [CompletionResultType]::ParameterValue, 'Serve the system profile as a JSON HTTP API for dashboards and remote tooling')
+            [CompletionResult]::new('help', 'help', [CompletionResultType]::ParameterValue, 'Print this message or the help of the given subcommand(s)')
+            break
+        }
+        'cyme;help;diff' {
+            break
+        }
+        'cyme;help;assert' {
+            break
+        }
+        'cyme;help;export-descriptors' {
+            break
+        }
+        'cyme;help;capture' {
+            break
+        }
+        'cyme;help;wait' {
+            break
+        }
+        'cyme;help;watch' {
+            break
+        }
+        'cyme;help;history' {
+            break
+        }
+        'cyme;help;rules' {
+            break
+        }
+        'cyme;help;lint' {
+            break
+        }
+        'cyme;help;audit' {
+            break
+        }
+        'cyme;help;serials' {
+            break
+        }
+        'cyme;help;serve' {
+            break
+        }
+        'cyme;help;help' {
             break
         }
     })
@@ -1,138 +1,226 @@
-complete -c cyme -s d -l vidpid -d 'Show only devices with the specified vendor and product ID numbers (in hexadecimal) in format VID:[PID]' -r
-complete -c cyme -s s -l show -d 'Show only devices with specified device and/or bus numbers (in decimal) in format [[bus]:][devnum]' -r
-complete -c cyme -s D -l device -d 'Selects which device lsusb will examine - supplied as Linux /dev/bus/usb/BBB/DDD style path' -r
-complete -c cyme -l filter-name -d 'Filter on string contained in name' -r
-complete -c cyme -l filter-serial -d 'Filter on string contained in serial' -r
-complete -c cyme -l filter-class -d 'Filter on USB class code' -r -f -a "use-interface-descriptor\t'Device class is unspecified, interface descriptors are used to determine needed drivers'
-audio\t'Speaker, microphone, sound card, MIDI'
-cdc-communications\t'The modern serial interface; appears as a UART/RS232 port on most systems'
-hid\t'Human Interface Device; game controllers, keyboards, mice etc. Also commonly used as a device data interface rather then creating something from scratch'
-physical\t'Force feedback joystick'
-image\t'Still imaging device; scanners, cameras'
-printer\t'Laser printer, inkjet printer, CNC machine'
-mass-storage\t'Mass storage devices (MSD): USB flash drive, memory card reader, digital audio player, digital camera, external drive'
-hub\t'High speed USB hub'
-cdc-data\t'Used together with class 02h (Communications and CDC Control) above'
-smart-card\t'USB smart card reader'
-content-security\t'Fingerprint reader'
-video\t'Webcam'
-personal-healthcare\t'Pulse monitor (watch)'
-audio-video\t'Webcam, TV'
-billboard\t'Describes USB-C alternate modes supported by device'
-usb-type-c-bridge\t'An interface to expose and configure the USB Type-C capabilities of Connectors on USB Hubs or Alternate Mode Adapters'
-bdp\t'This base class is defined for devices that conform to the “VESA USB BDP Device Specification” found at the VESA website. This specification defines the usable set of SubClass and Protocol values. Values outside of this defined spec are reserved. These class codes can only be used in Interface Descriptors'
-mctp\t'This base class is defined for devices that conform to the “MCTP over USB” found at the DMTF website as DSP0283. This specification defines the usable set of SubClass and Protocol values. Values outside of this defined spec are reserved. These class codes can only be used in Interface Descriptors'
-i3c-device\t'An interface to expose and configure I3C function within a USB device to allow interaction between host software and the I3C device, to drive transaction on the I3C bus to/from target devices'
-diagnostic\t'Trace and debugging equipment'
-wireless-controller\t'Wireless controllers: Bluetooth adaptors, Microsoft RNDIS'
-miscellaneous\t'This base class is defined for miscellaneous device definitions. Some matching SubClass and Protocols are defined on the USB-IF website'
-application-specific-interface\t'This base class is defined for devices that conform to several class specifications found on the USB-IF website'
-vendor-specific-class\t'This base class is defined for vendors to use as they please'"
-complete -c cyme -s b -l blocks -d 'Specify the blocks which will be displayed for each device and in what order. Supply arg multiple times to specify multiple blocks' -r -f -a "bus-number\t'Number of bus device is attached'
-device-number\t'Bus issued device number'
-branch-position\t'Position of device in parent branch'
-port-path\t'Linux style port path'
-sys-path\t'Linux udev reported syspath'
-driver\t'Linux udev reported driver loaded for device'
-icon\t'Icon based on VID/PID'
-vendor-id\t'Unique vendor identifier - purchased from USB IF'
-product-id\t'Vendor unique product identifier'
-name\t'The device name as reported in descriptor or using usb_ids if None'
-manufacturer\t'The device manufacturer as provided in descriptor or using usb_ids if None'
-product-name\t'The device product name as reported by usb_ids vidpid lookup'
-vendor-name\t'The device vendor name as reported by usb_ids vid lookup'
-serial\t'Device serial string as reported by descriptor'
-speed\t'Advertised device capable speed'
-tree-positions\t'Position along all branches back to trunk device'
-bus-power\t'macOS system_profiler only - actually bus current in mA not power!'
-bus-power-used\t'macOS system_profiler only - actually bus current used in mA not power!'
-extra-current-used\t'macOS system_profiler only - actually bus current used in mA not power!'
-bcd-device\t'The device version'
-bcd-usb\t'The supported USB version'
-base-class\t'Base class enum of interface provided by USB IF - only available when using libusb'
-sub-class\t'Sub-class value of interface provided by USB IF - only available when using libusb'
-protocol\t'Prototol value for interface provided by USB IF - only available when using libusb'
-uid-class\t'Class name from USB IDs repository'
-uid-sub-class\t'Sub-class name from USB IDs repository'
-uid-protocol\t'Protocol name from USB IDs repository'
-class\t'Fully defined USB Class Code enum based on BaseClass/SubClass/Protocol triplet'
-base-value\t'Base class as number value rather than enum'"
-complete -c cyme -l bus-blocks -d 'Specify the blocks which will be displayed for each bus and in what order. Supply arg multiple times to specify multiple blocks' -r -f -a "bus-number\t'System bus number identifier'
-icon\t'Icon based on VID/PID'
-name\t'System internal bus name based on Root Hub device name'
-host-controller\t'System internal bus provider name'
-host-controller-vendor\t'Vendor name of PCI Host Controller from pci.ids'
-host-controller-device\t'Device name of PCI Host Controller from pci.ids'
-pci-vendor\t'PCI vendor ID (VID)'
-pci-device\t'PCI device ID (PID)'
-pci-revision\t'PCI Revsision ID'
-port-path\t'syspath style port path to bus, applicable to Linux only'"
-complete -c cyme -l config-blocks -d 'Specify the blocks which will be displayed for each configuration and in what order. Supply arg multiple times to specify multiple blocks' -r -f -a "name\t'Name from string descriptor'
-number\t'Number of config, bConfigurationValue; value to set to enable to configuration'
-num-interfaces\t'Interfaces available for this configuruation'
-attributes\t'Attributes of configuration, bmAttributes'
-icon-attributes\t'Icon representation of bmAttributes'
-max-power\t'Maximum current consumption in mA'"
-complete -c cyme -l interface-blocks -d 'Specify the blocks which will be displayed for each interface and in what order. Supply arg multiple times to specify multiple blocks' -r -f -a "name\t'Name from string descriptor'
-number\t'Interface number'
-port-path\t'Interface port path, applicable to Linux'
-base-class\t'Base class enum of interface provided by USB IF'
-sub-class\t'Sub-class value of interface provided by USB IF'
-protocol\t'Prototol value for interface provided by USB IF'
-alt-setting\t'Interfaces can have the same number but an alternate settings defined here'
-driver\t'Driver obtained from udev on Linux only'
-sys-path\t'syspath obtained from udev on Linux only'
-num-endpoints\t'An interface can have many endpoints'
-icon\t'Icon based on BaseClass/SubCode/Protocol'
-uid-class\t'Class name from USB IDs repository'
-uid-sub-class\t'Sub-class name from USB IDs repository'
-uid-protocol\t'Protocol name from USB IDs repository'
-class\t'Fully defined USB Class Code based on BaseClass/SubClass/Protocol triplet'
-base-value\t'Base class as number value rather than enum'"
-complete -c cyme -l endpoint-blocks -d 'Specify the blocks which will be displayed for each endpoint and in what order. Supply arg multiple times to specify multiple blocks' -r -f -a "number\t'Endpoint number on interface'
-direction\t'Direction of data into endpoint'
-transfer-type\t'Type of data transfer endpoint accepts'
-sync-type\t'Synchronisation type (Iso mode)'
-usage-type\t'Usage type (Iso mode)'
-max-packet-size\t'Maximum packet size in bytes endpoint can send/recieve'
-interval\t'Interval for polling endpoint data transfers. Value in frame counts. Ignored for Bulk & Control Endpoints. Isochronous must equal 1 and field may range from 1 to 255 for interrupt endpoints'"
-complete -c cyme -l sort-devices -d 'Sort devices operation' -r -f -a "device-number\t'Sort by bus device number'
-branch-position\t'Sort by position in parent branch'
-no-sort\t'No sorting; whatever order it was parsed'"
-complete -c cyme -l group-devices -d 'Group devices by value when listing' -r -f -a "no-group\t'No grouping'
-bus\t'Group into buses with bus info as heading - like a flat tree'"
-complete -c cyme -l color -d 'Output coloring mode' -r -f -a "auto\t'Show colours if the output goes to an interactive console'
-always\t'Always apply colouring to the output'
-never\t'Never apply colouring to the output'"
-complete -c cyme -l encoding -d 'Output character encoding' -r -f -a "glyphs\t'Use UTF-8 private use area characters such as those used by NerdFont to show glyph icons'
-utf8\t'Use only standard UTF-8 characters for the output; no private use area glyph icons'
-ascii\t'Use only ASCII characters for the output; 0x00 - 0x7F (127 chars)'"
-complete -c cyme -l icon -d 'When to print icon blocks' -r -f -a "auto\t'Show icon blocks if the [`Encoding`] supports icons matched in the [`icon::IconTheme`]'
-always\t'Always print icon blocks if included in configured blocks'
-never\t'Never print icon blocks'"
-complete -c cyme -l from-json -d 'Read from json output rather than profiling system' -r
-complete -c cyme -s c -l config -d 'Path to user config file to use for custom icons, colours and default settings' -r
-complete -c cyme -l mask-serials -d 'Mask serial numbers with \'*\' or random chars' -r -f -a "hide\t'Hide with \'*\' char'
-scramble\t'Mask by randomising existing chars'
-replace\t'Mask by replacing length with random chars'"
-complete -c cyme -s l -l lsusb -d 'Attempt to maintain compatibility with lsusb output'
-complete -c cyme -s t -l tree -d 'Dump USB device hierarchy as a tree'
-complete -c cyme -s v -l verbose -d 'Verbosity level (repeat provides count): 1 prints device configurations; 2 prints interfaces; 3 prints interface endpoints; 4 prints everything and more blocks'
-complete -c cyme -s m -l more -d 'Print more blocks by default at each verbosity'
-complete -c cyme -l sort-buses -d 'Sort devices by bus number. If using any sort-devices other than no-sort, this happens automatically'
-complete -c cyme -l hide-buses -d 'Hide empty buses when printing tree; those with no devices'
-complete -c cyme -l hide-hubs -d 'Hide empty hubs when printing tree; those with no devices. When listing will hide hubs regardless of whether empty of not'
-complete -c cyme -l list-root-hubs -d 'Show root hubs when listing; Linux only'
-complete -c cyme -l decimal -d 'Show base16 values as base10 decimal instead'
-complete -c cyme -l no-padding -d 'Disable padding to align blocks - will cause --headings to become maligned'
-complete -c cyme -l no-color -d 'Disable coloured output, can also use NO_COLOR environment variable'
-complete -c cyme -l ascii -d 'Disables icons and utf-8 characters'
-complete -c cyme -l no-icons -d 'Disables all Block icons by not using any IconTheme. Providing custom XxxxBlocks without any icons is a nicer way to do this'
-complete -c cyme -l headings -d 'Show block headings'
-complete -c cyme -l json -d 'Output as json format after sorting, filters and tree settings are applied; without -tree will be flattened dump of devices'
-complete -c cyme -s F -l force-libusb -d 'Force pure libusb profiler on macOS rather than combining system_profiler output'
-complete -c cyme -s z -l debug -d 'Turn debugging information on. Alternatively can use RUST_LOG env: INFO, DEBUG, TRACE'
-complete -c cyme -l gen -d 'Generate cli completions and man page'
-complete -c cyme -l system-profiler -d 'Use the system_profiler command on macOS to get USB data'
-complete -c cyme -s h -l help -d 'Print help (see more with \'--help\')'
-complete -c cyme -s V -l version -d 'Print version'
+# Print an optspec for argparse to handle cmd's options that are independent of any subcommand.
+function __fish_cyme_global_optspecs
+	string join \n l/lsusb t/tree d/vidpid= s/show= D/device= filter-name= filter-serial= filter-class= filter-unclaimed filter-location= where= highlight= warn-duplicates v/verbose b/blocks= bus-blocks= config-blocks= interface-blocks= endpoint-blocks= m/more sort-devices= sort-buses group-devices= hide-buses hide-hubs compact max-depth= list-root-hubs decimal no-padding color= no-color encoding= ascii no-icons icon= headings stripe separator= grid json json-fields= stub-parents xml template= get= count list-icons bandwidth output= tee ansi-to-html no-pager fail-if-empty inline-descriptors dump-descriptors active-config-only thunderbolt gadget from-json= from-descriptors= from-usbview= cached cache-ttl= F/force-libusb backend= theme= sudo show-errors strict capabilities no-open-class= name-sources= c/config= z/debug log-file= log-format= log-filter= mask-serials= mask-salt= redact speed-units= gen complete-candidates= system-profiler h/help V/version
+end
+
+function __fish_cyme_needs_command
+	# Figure out if the current invocation already has a command.
+	set -l cmd (commandline -opc)
+	set -e cmd[1]
+	argparse -s (__fish_cyme_global_optspecs) -- $cmd 2>/dev/null
+	or return
+	if set -q argv[1]
+		# Also print the command, so this can be used to figure out what it is.
+		echo $argv[1]
+		return 1
+	end
+	return 0
+end
+
+function __fish_cyme_using_subcommand
+	set -l cmd (__fish_cyme_needs_command)
+	test -z "$cmd"
+	and return 1
+	contains -- $cmd[1] $argv
+end
+
+complete -c cyme -n "__fish_cyme_needs_command" -s d -l vidpid -d 'Show only devices with the specified vendor and product ID numbers (in hexadecimal) in format VID:[PID]' -r
+complete -c cyme -n "__fish_cyme_needs_command" -s s -l show -d 'Show only devices with specified device and/or bus numbers (in decimal) in format [[bus]:][devnum]' -r
+complete -c cyme -n "__fish_cyme_needs_command" -s D -l device -d 'Selects which device lsusb will examine - supplied as Linux /dev/bus/usb/BBB/DDD style path' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l filter-name -d 'Filter on string contained in name' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l filter-serial -d 'Filter on string contained in serial' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l filter-class -d 'Filter on USB class code' -r -f -a "{use-interface-descriptor\t'Device class is unspecified, interface descriptors are used to determine needed drivers',audio\t'Speaker, microphone, sound card, MIDI',cdc-communications\t'The modern serial interface; appears as a UART/RS232 port on most systems',hid\t'Human Interface Device; game controllers, keyboards, mice etc. Also commonly used as a device data interface rather then creating something from scratch',physical\t'Force feedback joystick',image\t'Still imaging device; scanners, cameras',printer\t'Laser printer, inkjet printer, CNC machine',mass-storage\t'Mass storage devices (MSD): USB flash drive, memory card reader, digital audio player, digital camera, external drive',hub\t'High speed USB hub',cdc-data\t'Used together with class 02h (Communications and CDC Control) above',smart-card\t'USB smart card reader',content-security\t'Fingerprint reader',video\t'Webcam',personal-healthcare\t'Pulse monitor (watch)',audio-video\t'Webcam, TV',billboard\t'Describes USB-C alternate modes supported by device',usb-type-c-bridge\t'An interface to expose and configure the USB Type-C capabilities of Connectors on USB Hubs or Alternate Mode Adapters',bdp\t'This base class is defined for devices that conform to the “VESA USB BDP Device Specification” found at the VESA website. This specification defines the usable set of SubClass and Protocol values. Values outside of this defined spec are reserved. These class codes can only be used in Interface Descriptors',mctp\t'This base class is defined for devices that conform to the “MCTP over USB” found at the DMTF website as DSP0283. This specification defines the usable set of SubClass and Protocol values. Values outside of this defined spec are reserved. These class codes can only be used in Interface Descriptors',i3c-device\t'An interface to expose and configure I3C function within a USB device to allow interaction between host software and the I3C device, to drive transaction on the I3C bus to/from target devices',diagnostic\t'Trace and debugging equipment',wireless-controller\t'Wireless controllers: Bluetooth adaptors, Microsoft RNDIS',miscellaneous\t'This base class is defined for miscellaneous device definitions. Some matching SubClass and Protocols are defined on the USB-IF website',application-specific-interface\t'This base class is defined for devices that conform to several class specifications found on the USB-IF website',vendor-specific-class\t'This base class is defined for vendors to use as they please'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l filter-location -d 'Filter on where devices are relative to the machine: \'internal\' hard-wired devices such as a laptop webcam, \'external\' plug-in peripherals or \'dock\'; a heuristic from sysfs \'removable\', the hub descriptors and ACPI physical location so devices of unknown location are filtered out' -r -f -a "{internal\t'Hard-wired inside the machine such as a laptop webcam, fingerprint reader or Bluetooth adapter',external\t'Plugged into a port of the machine or an external hub',dock\t'Part of or connected through a dock: hard-wired to an external hub, on a port ACPI reports as docking or on a bus tunnelled over USB4/Thunderbolt'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l where -d 'Filter on a boolean expression of \'field operator value\' conditions combined with \'&&\', \'||\', \'!\' and parentheses; e.g. \'class==hid && vid==0x046d || name~"Webcam"\'. Fields are vid, pid, bus, number, name, serial, manufacturer and class; \'=\' or \'~\' match like the --filter flags, \'==\' is exact and \'!=\' or \'!~\' negate' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l highlight -d 'Mark devices matching an expression like --where rather than filtering, keeping the full tree for context; e.g. \'vid==0x1d50 && name~Probe\'' -r
+complete -c cyme -n "__fish_cyme_needs_command" -s b -l blocks -d 'Specify the blocks which will be displayed for each device and in what order. Supply arg multiple times to specify multiple blocks' -r -f -a "{bus-number\t'Number of bus device is attached',device-number\t'Bus issued device number',branch-position\t'Position of device in parent branch',port-path\t'Linux style port path',sys-path\t'Linux udev reported syspath',dev-path\t'Path to open device with OS tools: /dev/bus/usb/BBB/DDD on Linux, IOKit locationID on macOS',driver\t'Linux udev reported driver loaded for device',icon\t'Icon based on VID/PID',vendor-id\t'Unique vendor identifier - purchased from USB IF',product-id\t'Vendor unique product identifier',name\t'The device name as reported in descriptor or using usb_ids if None',manufacturer\t'The device manufacturer as provided in descriptor or using usb_ids if None',product-name\t'The device product name as reported by usb_ids vidpid lookup',vendor-name\t'The device vendor name as reported by usb_ids vid lookup',serial\t'Device serial string as reported by descriptor',speed\t'Device speed as reported by the profiler; system_profiler description on macOS',negotiated-speed\t'Speed the device is connected at, highlighted if below the capable speed',capable-speed\t'Maximum speed the device is capable of from BOS descriptor, bcdUSB or device qualifier',tree-positions\t'Position along all branches back to trunk device',bus-power\t'macOS system_profiler only - actually bus current in mA not power!',bus-power-used\t'macOS system_profiler only - actually bus current used in mA not power!',extra-current-used\t'macOS system_profiler only - actually bus current used in mA not power!',bcd-device\t'The device version',bcd-usb\t'The supported USB version',base-class\t'Base class enum of interface provided by USB IF - only available when using libusb',sub-class\t'Sub-class value of interface provided by USB IF - only available when using libusb',protocol\t'Prototol value for interface provided by USB IF - only available when using libusb',uid-class\t'Class name from USB IDs repository',uid-sub-class\t'Sub-class name from USB IDs repository',uid-protocol\t'Protocol name from USB IDs repository',class\t'Fully defined USB Class Code enum based on BaseClass/SubClass/Protocol triplet',base-value\t'Base class as number value rather than enum',bluetooth-name\t'Bluetooth HCI adapter name of a Bluetooth interface, Linux only',bluetooth-address\t'Bluetooth HCI adapter address (BD_ADDR) of a Bluetooth interface, Linux only',power-state\t'Runtime power management state and autosuspend delay on Linux, IOKit power state on macOS',connected-since\t'Time since the device was connected, Linux only',lanes\t'Number of USB 3.2 receive/transmit lanes in use; 2/2 for Gen 2x2, Linux only',link-state\t'State of the hub port the device is connected to, Linux only',physical-location\t'Physical location of the port the device is connected to from ACPI: panel, position and whether on a dock or lid, Linux only',removable\t'Whether the device is on a \'removable\' port or \'fixed\', hard-wired to it like an internal webcam or Bluetooth adapter; sysfs on Linux, the hub descriptor DeviceRemovable bitmap or macOS system_profiler built-in otherwise',location-kind\t'Whether the device is \'internal\', \'external\' or on a \'dock\'; a heuristic from removability, ACPI physical location and the hubs it is connected through',vendor-fields\t'Vendor specific details such as the chip and channel configuration of FTDI, CP210x and CH34x USB-UART bridges',battery\t'Charge and charging state of device batteries such as \'85% Discharging\', Linux only',video-format\t'Largest frame size with its frame rate, highest frame rate and formats of UVC cameras such as \'3840x2160@30fps, MJPEG/H264, raw YUY2\'; shown for video devices at max verbosity',media\t'Storage media of mass storage devices with disk name, size and whether removable; macOS system_profiler and Linux sysfs',capacity\t'Capacity of the storage media of mass storage devices, \'-\' for empty card reader slots; shown for mass storage devices at max verbosity',scsi-inquiry\t'SCSI INQUIRY vendor, model and revision of mass storage devices from the kernel, Linux only; shown for mass storage devices at max verbosity',quirks\t'Known-broken behaviours of the device from the quirk database, such as not reading the BOS descriptor because it stalls',name-source\t'Where the name came from: user override, OS, device descriptor or USB IDs; the manufacturer source follows if different',otg\t'On-The-Go protocols the device supports from its OTG descriptor: SRP, HNP and ADP; only devices for OTG connectors have one'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l bus-blocks -d 'Specify the blocks which will be displayed for each bus and in what order. Supply arg multiple times to specify multiple blocks' -r -f -a "{bus-number\t'System bus number identifier',icon\t'Icon based on VID/PID',name\t'System internal bus name based on Root Hub device name',host-controller\t'System internal bus provider name',host-controller-vendor\t'Vendor name of PCI Host Controller from pci.ids',host-controller-device\t'Device name of PCI Host Controller from pci.ids',pci-vendor\t'PCI vendor ID (VID)',pci-device\t'PCI device ID (PID)',pci-revision\t'PCI Revsision ID',port-path\t'syspath style port path to bus, applicable to Linux only',role\t'Current role of a dual-role port: \'host\', \'device\' or \'none\' from the USB role switch, Linux only'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l config-blocks -d 'Specify the blocks which will be displayed for each configuration and in what order. Supply arg multiple times to specify multiple blocks' -r -f -a "{name\t'Name from string descriptor',number\t'Number of config, bConfigurationValue; value to set to enable to configuration',num-interfaces\t'Interfaces available for this configuruation',attributes\t'Attributes of configuration, bmAttributes',icon-attributes\t'Icon representation of bmAttributes',max-power\t'Maximum current consumption in mA',raw-descriptor\t'Raw descriptor bytes as hex, when profiled with raw descriptors',active\t'\'*\' if the configuration is the one currently set on the device'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l interface-blocks -d 'Specify the blocks which will be displayed for each interface and in what order. Supply arg multiple times to specify multiple blocks' -r -f -a "{name\t'Name from string descriptor',number\t'Interface number',port-path\t'Interface port path, applicable to Linux',base-class\t'Base class enum of interface provided by USB IF',sub-class\t'Sub-class value of interface provided by USB IF',protocol\t'Prototol value for interface provided by USB IF',alt-setting\t'Interfaces can have the same number but an alternate settings defined here',driver\t'Driver obtained from udev on Linux only',sys-path\t'syspath obtained from udev on Linux only',module\t'Kernel module providing the bound driver from sysfs or udev, Linux only',module-parameters\t'Parameters of the kernel module such as \'mousepoll=0\', Linux only',num-endpoints\t'An interface can have many endpoints',icon\t'Icon based on BaseClass/SubCode/Protocol',uid-class\t'Class name from USB IDs repository',uid-sub-class\t'Sub-class name from USB IDs repository',uid-protocol\t'Protocol name from USB IDs repository',class\t'Fully defined USB Class Code based on BaseClass/SubClass/Protocol triplet',base-value\t'Base class as number value rather than enum',bluetooth-name\t'Bluetooth HCI adapter name if Bluetooth interface, Linux only',bluetooth-address\t'Bluetooth HCI adapter address (BD_ADDR) if Bluetooth interface, Linux only',audio-format\t'Summary of sample rates, bit depths and channels if UAC streaming interface',audio-controls\t'UAC2/UAC3 controls and whether they are read-only or read/write if UAC interface',video-format\t'Largest frame size with its frame rate, highest frame rate and formats if UVC streaming interface',vendor-fields\t'Fields decoded from vendor-specific class descriptors by a registered decoder',raw-descriptor\t'Raw descriptor bytes as hex including class descriptors, when profiled with raw descriptors'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l endpoint-blocks -d 'Specify the blocks which will be displayed for each endpoint and in what order. Supply arg multiple times to specify multiple blocks' -r -f -a "{number\t'Endpoint number on interface',direction\t'Direction of data into endpoint',transfer-type\t'Type of data transfer endpoint accepts',sync-type\t'Synchronisation type (Iso mode)',usage-type\t'Usage type (Iso mode)',max-packet-size\t'Maximum packet size in bytes endpoint can send/recieve',interval\t'Interval for polling endpoint data transfers. Value in frame counts. Ignored for Bulk & Control Endpoints. Isochronous must equal 1 and field may range from 1 to 255 for interrupt endpoints',raw-descriptor\t'Raw descriptor bytes as hex including companion descriptors, when profiled with raw descriptors'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l sort-devices -d 'Sort devices operation' -r -f -a "{device-number\t'Sort by bus device number',branch-position\t'Sort by position in parent branch',no-sort\t'No sorting; whatever order it was parsed'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l group-devices -d 'Group devices by value when listing' -r -f -a "{no-group\t'No grouping',bus\t'Group into buses with bus info as heading - like a flat tree',class\t'Group by device base class with class as heading'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l max-depth -d 'Maximum depth of devices to print with --tree: 0 for buses only, 1 for devices directly on the bus and so on. Hidden devices are counted on the last printed line' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l color -d 'Output coloring mode' -r -f -a "{auto\t'Show colours if the output goes to an interactive console',always\t'Always apply colouring to the output',never\t'Never apply colouring to the output'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l encoding -d 'Output character encoding' -r -f -a "{glyphs\t'Use UTF-8 private use area characters such as those used by NerdFont to show glyph icons',utf8\t'Use only standard UTF-8 characters for the output; no private use area glyph icons',emoji\t'Use standard UTF-8 characters and emoji icons for terminals with emoji but not NerdFont glyphs',ascii\t'Use only ASCII characters for the output; 0x00 - 0x7F (127 chars)',auto\t'Detect the encoding supported by the terminal from the locale, TERM and terminal emulator; treated as [`Encoding::Utf8`] if not resolved with [`Encoding::resolve`]'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l icon -d 'When to print icon blocks' -r -f -a "{auto\t'Show icon blocks if the [`Encoding`] supports icons matched in the [`icon::IconTheme`]',always\t'Always print icon blocks if included in configured blocks',never\t'Never print icon blocks'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l separator -d 'Separate list blocks with this string rather than a space, \' | \' for example' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l json-fields -d 'Output only these fields for each device as json, supplied as comma separated block names. Implies --json and output is always a flat list of devices' -r -f -a "{bus-number\t'Number of bus device is attached',device-number\t'Bus issued device number',branch-position\t'Position of device in parent branch',port-path\t'Linux style port path',sys-path\t'Linux udev reported syspath',dev-path\t'Path to open device with OS tools: /dev/bus/usb/BBB/DDD on Linux, IOKit locationID on macOS',driver\t'Linux udev reported driver loaded for device',icon\t'Icon based on VID/PID',vendor-id\t'Unique vendor identifier - purchased from USB IF',product-id\t'Vendor unique product identifier',name\t'The device name as reported in descriptor or using usb_ids if None',manufacturer\t'The device manufacturer as provided in descriptor or using usb_ids if None',product-name\t'The device product name as reported by usb_ids vidpid lookup',vendor-name\t'The device vendor name as reported by usb_ids vid lookup',serial\t'Device serial string as reported by descriptor',speed\t'Device speed as reported by the profiler; system_profiler description on macOS',negotiated-speed\t'Speed the device is connected at, highlighted if below the capable speed',capable-speed\t'Maximum speed the device is capable of from BOS descriptor, bcdUSB or device qualifier',tree-positions\t'Position along all branches back to trunk device',bus-power\t'macOS system_profiler only - actually bus current in mA not power!',bus-power-used\t'macOS system_profiler only - actually bus current used in mA not power!',extra-current-used\t'macOS system_profiler only - actually bus current used in mA not power!',bcd-device\t'The device version',bcd-usb\t'The supported USB version',base-class\t'Base class enum of interface provided by USB IF - only available when using libusb',sub-class\t'Sub-class value of interface provided by USB IF - only available when using libusb',protocol\t'Prototol value for interface provided by USB IF - only available when using libusb',uid-class\t'Class name from USB IDs repository',uid-sub-class\t'Sub-class name from USB IDs repository',uid-protocol\t'Protocol name from USB IDs repository',class\t'Fully defined USB Class Code enum based on BaseClass/SubClass/Protocol triplet',base-value\t'Base class as number value rather than enum',bluetooth-name\t'Bluetooth HCI adapter name of a Bluetooth interface, Linux only',bluetooth-address\t'Bluetooth HCI adapter address (BD_ADDR) of a Bluetooth interface, Linux only',power-state\t'Runtime power management state and autosuspend delay on Linux, IOKit power state on macOS',connected-since\t'Time since the device was connected, Linux only',lanes\t'Number of USB 3.2 receive/transmit lanes in use; 2/2 for Gen 2x2, Linux only',link-state\t'State of the hub port the device is connected to, Linux only',physical-location\t'Physical location of the port the device is connected to from ACPI: panel, position and whether on a dock or lid, Linux only',removable\t'Whether the device is on a \'removable\' port or \'fixed\', hard-wired to it like an internal webcam or Bluetooth adapter; sysfs on Linux, the hub descriptor DeviceRemovable bitmap or macOS system_profiler built-in otherwise',location-kind\t'Whether the device is \'internal\', \'external\' or on a \'dock\'; a heuristic from removability, ACPI physical location and the hubs it is connected through',vendor-fields\t'Vendor specific details such as the chip and channel configuration of FTDI, CP210x and CH34x USB-UART bridges',battery\t'Charge and charging state of device batteries such as \'85% Discharging\', Linux only',video-format\t'Largest frame size with its frame rate, highest frame rate and formats of UVC cameras such as \'3840x2160@30fps, MJPEG/H264, raw YUY2\'; shown for video devices at max verbosity',media\t'Storage media of mass storage devices with disk name, size and whether removable; macOS system_profiler and Linux sysfs',capacity\t'Capacity of the storage media of mass storage devices, \'-\' for empty card reader slots; shown for mass storage devices at max verbosity',scsi-inquiry\t'SCSI INQUIRY vendor, model and revision of mass storage devices from the kernel, Linux only; shown for mass storage devices at max verbosity',quirks\t'Known-broken behaviours of the device from the quirk database, such as not reading the BOS descriptor because it stalls',name-source\t'Where the name came from: user override, OS, device descriptor or USB IDs; the manufacturer source follows if different',otg\t'On-The-Go protocols the device supports from its OTG descriptor: SRP, HNP and ADP; only devices for OTG connectors have one'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l template -d 'Render each device through this Handlebars-style template file, or the whole profile once with --tree; fields are those of the json output plus port_path' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l get -d 'Print only the raw value of this block for each matching device, one per line; for use in shell scripts' -r -f -a "{bus-number\t'Number of bus device is attached',device-number\t'Bus issued device number',branch-position\t'Position of device in parent branch',port-path\t'Linux style port path',sys-path\t'Linux udev reported syspath',dev-path\t'Path to open device with OS tools: /dev/bus/usb/BBB/DDD on Linux, IOKit locationID on macOS',driver\t'Linux udev reported driver loaded for device',icon\t'Icon based on VID/PID',vendor-id\t'Unique vendor identifier - purchased from USB IF',product-id\t'Vendor unique product identifier',name\t'The device name as reported in descriptor or using usb_ids if None',manufacturer\t'The device manufacturer as provided in descriptor or using usb_ids if None',product-name\t'The device product name as reported by usb_ids vidpid lookup',vendor-name\t'The device vendor name as reported by usb_ids vid lookup',serial\t'Device serial string as reported by descriptor',speed\t'Device speed as reported by the profiler; system_profiler description on macOS',negotiated-speed\t'Speed the device is connected at, highlighted if below the capable speed',capable-speed\t'Maximum speed the device is capable of from BOS descriptor, bcdUSB or device qualifier',tree-positions\t'Position along all branches back to trunk device',bus-power\t'macOS system_profiler only - actually bus current in mA not power!',bus-power-used\t'macOS system_profiler only - actually bus current used in mA not power!',extra-current-used\t'macOS system_profiler only - actually bus current used in mA not power!',bcd-device\t'The device version',bcd-usb\t'The supported USB version',base-class\t'Base class enum of interface provided by USB IF - only available when using libusb',sub-class\t'Sub-class value of interface provided by USB IF - only available when using libusb',protocol\t'Prototol value for interface provided by USB IF - only available when using libusb',uid-class\t'Class name from USB IDs repository',uid-sub-class\t'Sub-class name from USB IDs repository',uid-protocol\t'Protocol name from USB IDs repository',class\t'Fully defined USB Class Code enum based on BaseClass/SubClass/Protocol triplet',base-value\t'Base class as number value rather than enum',bluetooth-name\t'Bluetooth HCI adapter name of a Bluetooth interface, Linux only',bluetooth-address\t'Bluetooth HCI adapter address (BD_ADDR) of a Bluetooth interface, Linux only',power-state\t'Runtime power management state and autosuspend delay on Linux, IOKit power state on macOS',connected-since\t'Time since the device was connected, Linux only',lanes\t'Number of USB 3.2 receive/transmit lanes in use; 2/2 for Gen 2x2, Linux only',link-state\t'State of the hub port the device is connected to, Linux only',physical-location\t'Physical location of the port the device is connected to from ACPI: panel, position and whether on a dock or lid, Linux only',removable\t'Whether the device is on a \'removable\' port or \'fixed\', hard-wired to it like an internal webcam or Bluetooth adapter; sysfs on Linux, the hub descriptor DeviceRemovable bitmap or macOS system_profiler built-in otherwise',location-kind\t'Whether the device is \'internal\', \'external\' or on a \'dock\'; a heuristic from removability, ACPI physical location and the hubs it is connected through',vendor-fields\t'Vendor specific details such as the chip and channel configuration of FTDI, CP210x and CH34x USB-UART bridges',battery\t'Charge and charging state of device batteries such as \'85% Discharging\', Linux only',video-format\t'Largest frame size with its frame rate, highest frame rate and formats of UVC cameras such as \'3840x2160@30fps, MJPEG/H264, raw YUY2\'; shown for video devices at max verbosity',media\t'Storage media of mass storage devices with disk name, size and whether removable; macOS system_profiler and Linux sysfs',capacity\t'Capacity of the storage media of mass storage devices, \'-\' for empty card reader slots; shown for mass storage devices at max verbosity',scsi-inquiry\t'SCSI INQUIRY vendor, model and revision of mass storage devices from the kernel, Linux only; shown for mass storage devices at max verbosity',quirks\t'Known-broken behaviours of the device from the quirk database, such as not reading the BOS descriptor because it stalls',name-source\t'Where the name came from: user override, OS, device descriptor or USB IDs; the manufacturer source follows if different',otg\t'On-The-Go protocols the device supports from its OTG descriptor: SRP, HNP and ADP; only devices for OTG connectors have one'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l output -d 'Write the output to a file with colour stripped rather than stdout; a summary is printed to stderr' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l from-json -d 'Read from json output rather than profiling system' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l from-descriptors -d 'Read a device from raw descriptor files written by `cyme export-descriptors` rather than profiling system' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l from-usbview -d 'Read a text or XML report saved by the Windows USBView tool rather than profiling system' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l cache-ttl -d 'Maximum age in seconds of the profile used with --cached' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l backend -d 'USB library to profile devices with when more than one is compiled in; defaults to nusb if available' -r -f -a "{nusb\t'Pure Rust nusb; \'nusb\' feature',libusb\t'libusb C library with rusb bindings; \'libusb\' feature'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l theme -d 'Built-in colour theme; cb-safe is distinguishable with red-green colour blindness and legible on dark and light backgrounds' -r -f -a "{default\t'Default ANSI colours, which follow the terminal palette',cb-safe\t'Okabe-Ito palette distinguishable with deuteranopia and protanopia and legible on dark and light backgrounds; approximated with ANSI colours if the terminal does not support true colour'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l no-open-class -d 'Do not open devices with this device or interface class for verbose data, since opening some devices, such as smartcard readers or printers, has side effects. Supply arg multiple times to specify multiple classes' -r -f -a "{use-interface-descriptor\t'Device class is unspecified, interface descriptors are used to determine needed drivers',audio\t'Speaker, microphone, sound card, MIDI',cdc-communications\t'The modern serial interface; appears as a UART/RS232 port on most systems',hid\t'Human Interface Device; game controllers, keyboards, mice etc. Also commonly used as a device data interface rather then creating something from scratch',physical\t'Force feedback joystick',image\t'Still imaging device; scanners, cameras',printer\t'Laser printer, inkjet printer, CNC machine',mass-storage\t'Mass storage devices (MSD): USB flash drive, memory card reader, digital audio player, digital camera, external drive',hub\t'High speed USB hub',cdc-data\t'Used together with class 02h (Communications and CDC Control) above',smart-card\t'USB smart card reader',content-security\t'Fingerprint reader',video\t'Webcam',personal-healthcare\t'Pulse monitor (watch)',audio-video\t'Webcam, TV',billboard\t'Describes USB-C alternate modes supported by device',usb-type-c-bridge\t'An interface to expose and configure the USB Type-C capabilities of Connectors on USB Hubs or Alternate Mode Adapters',bdp\t'This base class is defined for devices that conform to the “VESA USB BDP Device Specification” found at the VESA website. This specification defines the usable set of SubClass and Protocol values. Values outside of this defined spec are reserved. These class codes can only be used in Interface Descriptors',mctp\t'This base class is defined for devices that conform to the “MCTP over USB” found at the DMTF website as DSP0283. This specification defines the usable set of SubClass and Protocol values. Values outside of this defined spec are reserved. These class codes can only be used in Interface Descriptors',i3c-device\t'An interface to expose and configure I3C function within a USB device to allow interaction between host software and the I3C device, to drive transaction on the I3C bus to/from target devices',diagnostic\t'Trace and debugging equipment',wireless-controller\t'Wireless controllers: Bluetooth adaptors, Microsoft RNDIS',miscellaneous\t'This base class is defined for miscellaneous device definitions. Some matching SubClass and Protocols are defined on the USB-IF website',application-specific-interface\t'This base class is defined for devices that conform to several class specifications found on the USB-IF website',vendor-specific-class\t'This base class is defined for vendors to use as they please'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l name-sources -d 'Order of sources for device names, comma separated; sources not listed are not used. lsusb prefers USB IDs: \'ids,os,descriptor\'. Default \'user,os,descriptor,ids\'' -r -f -a "{user\t'User name from the \'names\' of the config, or set with `set_override`',os\t'String descriptor cached by the OS when the device was enumerated',descriptor\t'String descriptor read from the device when profiling with extra',ids\t'USB IDs repository, or the udev hwdb on Linux if enabled'}"
+complete -c cyme -n "__fish_cyme_needs_command" -s c -l config -d 'Path to user config file to use for custom icons, colours and default settings' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l log-file -d 'Append logs to a file rather than stderr; logs at -zz level if --debug is not passed' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l log-format -d 'Format of logs' -r -f -a "{pretty\t'Human readable with timestamp, level and module',json\t'Line delimited json objects with timestamp, level, target and message fields'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l log-filter -d 'Per-module log levels as \'module=level\' such as \'profiler=debug,udev=trace\'; cyme modules include profiler, udev and display' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l mask-serials -d 'Mask serial numbers with \'*\', random chars or a hash which is the same for a device in every dump; Bluetooth addresses and paths containing the serial are masked too' -r -f -a "{hide\t'Hide with \'*\' char',scramble\t'Mask by randomising existing chars',replace\t'Mask by replacing length with random chars',hash\t'Replace with a hash of the serial and salt so a device has the same mask in every dump; the hash is not cryptographic so set a private salt when sharing'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l mask-salt -d 'Salt for --mask-serials hash; set a private one so shared hashes cannot be matched to known serials' -r
+complete -c cyme -n "__fish_cyme_needs_command" -l speed-units -d 'Units of speeds in blocks and the lsusb tree; defaults to decimal for blocks and lsusb for the tree' -r -f -a "{decimal\t'Bits per second to one decimal place: \'480.0 Mb/s\'; default of blocks',bits\t'Bits per second in Mbps or Gbps: \'480 Mbps\'',bytes\t'Bytes per second: \'60 MB/s\'',raw\t'Mb/s without units as in Linux sysfs: \'480\'',lsusb\t'Mb/s with an M suffix like lsusb: \'480M\'; default of the lsusb tree'}"
+complete -c cyme -n "__fish_cyme_needs_command" -l complete-candidates -d 'Print values of the connected devices for the shell completions to complete an argument with' -r -f -a "{vidpid\t'--vidpid VID:PID',show\t'--show BUS:DEVNUM',device\t'--device /dev/bus/usb/BBB/DDD'}"
+complete -c cyme -n "__fish_cyme_needs_command" -s l -l lsusb -d 'Attempt to maintain compatibility with lsusb output'
+complete -c cyme -n "__fish_cyme_needs_command" -s t -l tree -d 'Dump USB device hierarchy as a tree'
+complete -c cyme -n "__fish_cyme_needs_command" -l filter-unclaimed -d 'Filter on devices with interfaces of the active configuration no driver is bound to, marked with the \'tree-interface-unclaimed\' icon; Linux only as other platforms do not report bound drivers'
+complete -c cyme -n "__fish_cyme_needs_command" -l warn-duplicates -d 'Colour serial numbers shared by more than one device, or missing, with the warning colour; duplicates are found before filtering. `cyme serials` reports them'
+complete -c cyme -n "__fish_cyme_needs_command" -s v -l verbose -d 'Verbosity level (repeat provides count): 1 prints device configurations; 2 prints interfaces; 3 prints interface endpoints; 4 prints everything and more blocks'
+complete -c cyme -n "__fish_cyme_needs_command" -s m -l more -d 'Print more blocks by default at each verbosity'
+complete -c cyme -n "__fish_cyme_needs_command" -l sort-buses -d 'Sort devices by bus number. If using any sort-devices other than no-sort, this happens automatically'
+complete -c cyme -n "__fish_cyme_needs_command" -l hide-buses -d 'Hide empty buses when printing tree; those with no devices'
+complete -c cyme -n "__fish_cyme_needs_command" -l hide-hubs -d 'Hide empty hubs when printing tree; those with no devices. When listing will hide hubs regardless of whether empty of not'
+complete -c cyme -n "__fish_cyme_needs_command" -l compact -d 'Collapse identical sibling devices, same VID:PID and name with nothing attached, into a single line with a \'xN\' count. Verbose output prints them all'
+complete -c cyme -n "__fish_cyme_needs_command" -l list-root-hubs -d 'Show root hubs when listing; Linux only'
+complete -c cyme -n "__fish_cyme_needs_command" -l decimal -d 'Show base16 values as base10 decimal instead'
+complete -c cyme -n "__fish_cyme_needs_command" -l no-padding -d 'Disable padding to align blocks - will cause --headings to become maligned'
+complete -c cyme -n "__fish_cyme_needs_command" -l no-color -d 'Disable coloured output, can also use NO_COLOR environment variable'
+complete -c cyme -n "__fish_cyme_needs_command" -l ascii -d 'Disables icons and utf-8 characters'
+complete -c cyme -n "__fish_cyme_needs_command" -l no-icons -d 'Disables all Block icons by not using any IconTheme. Providing custom XxxxBlocks without any icons is a nicer way to do this'
+complete -c cyme -n "__fish_cyme_needs_command" -l headings -d 'Show block headings'
+complete -c cyme -n "__fish_cyme_needs_command" -l stripe -d 'Alternate the background of list rows with the \'stripe\' colour to make wide lines easier to follow'
+complete -c cyme -n "__fish_cyme_needs_command" -l grid -d 'Print the list as a table with a border around each block and a heading row; verbose configurations are not printed'
+complete -c cyme -n "__fish_cyme_needs_command" -l json -d 'Output as json format after sorting, filters and tree settings are applied; without -tree will be flattened dump of devices'
+complete -c cyme -n "__fish_cyme_needs_command" -l stub-parents -d 'When filtering a tree, reduce non-matching parents of matched devices to slim stubs of location and identity only; keeps topology context of matches in --json output without the bulk'
+complete -c cyme -n "__fish_cyme_needs_command" -l xml -d 'Output as XML modeled on the Windows USBView export; a tree of devices with --tree and a list under each host controller otherwise'
+complete -c cyme -n "__fish_cyme_needs_command" -l count -d 'Print only the number of devices after filters are applied; with --group-devices prints the count for each group'
+complete -c cyme -n "__fish_cyme_needs_command" -l list-icons -d 'Print the icon rule matched by each device from the user config icons or defaults; for checking icon theme rules'
+complete -c cyme -n "__fish_cyme_needs_command" -l bandwidth -d 'Print the estimated bandwidth reserved by periodic (interrupt and isochronous) endpoints on each bus and by each device, for diagnosing \'not enough bandwidth\' errors; -v lists the endpoints'
+complete -c cyme -n "__fish_cyme_needs_command" -l tee -d 'With --output also print the output to stdout'
+complete -c cyme -n "__fish_cyme_needs_command" -l ansi-to-html -d 'Convert coloured output to HTML spans styled with the theme colours, within a <pre> element, for reports and CI logs which strip ANSI colour codes; written to stdout or --output'
+complete -c cyme -n "__fish_cyme_needs_command" -l no-pager -d 'Do not pipe output longer than the terminal to $PAGER or \'less -R\' in interactive sessions'
+complete -c cyme -n "__fish_cyme_needs_command" -l fail-if-empty -d 'Exit with code 1 and no output, other than the 0 of --count, if no devices match filters; errors then exit with code 2. Like grep, allows scripts to check a device is present'
+complete -c cyme -n "__fish_cyme_needs_command" -l inline-descriptors -d 'With --tree and -v, print lsusb style descriptor sections inside the tree below each device rather than configuration, interface and endpoint blocks; -v prints configurations, -vv interfaces and -vvv endpoints'
+complete -c cyme -n "__fish_cyme_needs_command" -l dump-descriptors -d 'Print the raw configuration, interface and endpoint descriptor bytes as an annotated hexdump below each, like `lsusb -D`; implies -vvv. Also included in --json'
+complete -c cyme -n "__fish_cyme_needs_command" -l active-config-only -d 'Print only the configuration currently set on devices with more than one in verbose output, rather than all of them. The active configuration is marked with \'*\''
+complete -c cyme -n "__fish_cyme_needs_command" -l thunderbolt -d 'Include Thunderbolt/USB4 domain and router topology with --tree (Linux only)'
+complete -c cyme -n "__fish_cyme_needs_command" -l gadget -d 'Include USB device controller (gadget) state and the configfs gadgets with their functions with --tree (Linux only)'
+complete -c cyme -n "__fish_cyme_needs_command" -l cached -d 'Use the profile cached by a previous run if younger than --cache-ttl and the connected devices have not changed, otherwise profile and update the cache'
+complete -c cyme -n "__fish_cyme_needs_command" -s F -l force-libusb -d 'Force pure libusb profiler on macOS rather than combining system_profiler output'
+complete -c cyme -n "__fish_cyme_needs_command" -l sudo -d 'Re-run with sudo, prompting for the password, if devices could not be opened for verbose data due to permissions'
+complete -c cyme -n "__fish_cyme_needs_command" -l show-errors -d 'Check configuration descriptors against the lengths and counts they declare, showing mismatches after the output; useful to spot malformed descriptors in firmware'
+complete -c cyme -n "__fish_cyme_needs_command" -l strict -d 'Fail with the first descriptor which cannot be parsed rather than showing the device without it; for validating device firmware'
+complete -c cyme -n "__fish_cyme_needs_command" -l capabilities -d 'Print the data sources available to the profiler, such as udev and the USB backends, and the fields missing without them then exit; json with --json'
+complete -c cyme -n "__fish_cyme_needs_command" -s z -l debug -d 'Turn debugging information on. Alternatively can use RUST_LOG env: INFO, DEBUG, TRACE'
+complete -c cyme -n "__fish_cyme_needs_command" -l redact -d 'Redact serials, Bluetooth addresses, the hostname and user name and volume names in every output format so dumps are safe to share; the \'redact\' of the config sets what is redacted. Subcommands redact what they print, except rules, capture and the device actions which error'
+complete -c cyme -n "__fish_cyme_needs_command" -l gen -d 'Generate cli completions and man page'
+complete -c cyme -n "__fish_cyme_needs_command" -l system-profiler -d 'Use the system_profiler command on macOS to get USB data'
+complete -c cyme -n "__fish_cyme_needs_command" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c cyme -n "__fish_cyme_needs_command" -s V -l version -d 'Print version'
+complete -c cyme -n "__fish_cyme_needs_command" -f -a "diff" -d 'Compare two json dumps, or a dump with the live system, reporting added, removed and changed devices'
+complete -c cyme -n "__fish_cyme_needs_command" -f -a "assert" -d 'Assert that the live system matches an expected json dump, exiting non-zero and printing the discrepancies if not'
+complete -c cyme -n "__fish_cyme_needs_command" -f -a "export-descriptors" -d 'Export the raw descriptors of a device as binary files for replay in emulators or fuzzers, or to attach to bug reports'
+complete -c cyme -n "__fish_cyme_needs_command" -f -a "capture" -d 'Print how to capture the traffic of a device with Linux usbmon, or with --output capture it to a pcap'
+complete -c cyme -n "__fish_cyme_needs_command" -f -a "wait" -d 'Wait for a device matching the filters to connect, or with --gone for all matching to disconnect, then print it and exit'
+complete -c cyme -n "__fish_cyme_needs_command" -f -a "watch" -d 'Print devices matching the filters as they connect and disconnect, optionally running a command for each'
+complete -c cyme -n "__fish_cyme_needs_command" -f -a "history" -d 'Show when devices recorded by `cyme watch --history` were first and last seen and how many times they re-enumerated'
+complete -c cyme -n "__fish_cyme_needs_command" -f -a "rules" -d 'Generate udev rules granting access to devices matching the filters without root, for the usb device node and its serial or HID nodes'
+complete -c cyme -n "__fish_cyme_needs_command" -f -a "lint" -d 'Check the descriptors of devices matching the filters, or all, against the USB specification like shellcheck for device firmware'
+complete -c cyme -n "__fish_cyme_needs_command" -f -a "audit" -d 'Flag potentially suspicious devices matching the filters, or all, for a quick endpoint USB security audit'
+complete -c cyme -n "__fish_cyme_needs_command" -f -a "serials" -d 'Report devices matching the filters, or all, which share a serial number or are without one; the bane of udev rules and provisioning keyed on serial'
+complete -c cyme -n "__fish_cyme_needs_command" -f -a "serve" -d 'Serve the system profile as a JSON HTTP API for dashboards and remote tooling'
+complete -c cyme -n "__fish_cyme_needs_command" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c cyme -n "__fish_cyme_using_subcommand diff" -l json -d 'Output the differences as json'
+complete -c cyme -n "__fish_cyme_using_subcommand diff" -s h -l help -d 'Print help'
+complete -c cyme -n "__fish_cyme_using_subcommand assert" -l expect -d 'Expected json dump such as from `cyme --json --tree` on the provisioned system' -r
+complete -c cyme -n "__fish_cyme_using_subcommand assert" -l actual -d 'Compare with a json dump rather than profiling the live system' -r
+complete -c cyme -n "__fish_cyme_using_subcommand assert" -l ignore -d 'Fields to ignore such as serial_num' -r
+complete -c cyme -n "__fish_cyme_using_subcommand assert" -l exact -d 'Also fail if there are devices which are not in the expected dump'
+complete -c cyme -n "__fish_cyme_using_subcommand assert" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c cyme -n "__fish_cyme_using_subcommand export-descriptors" -s o -l output -d 'Directory to write the descriptor files to; created if it does not exist' -r
+complete -c cyme -n "__fish_cyme_using_subcommand export-descriptors" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c cyme -n "__fish_cyme_using_subcommand capture" -s o -l output -d 'Write a pcap of the device traffic to this file, or stdout if \'-\', until interrupted or --count packets' -r
+complete -c cyme -n "__fish_cyme_using_subcommand capture" -l count -d 'Stop after capturing this many packets' -r
+complete -c cyme -n "__fish_cyme_using_subcommand capture" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c cyme -n "__fish_cyme_using_subcommand wait" -s d -l vidpid -d 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]' -r
+complete -c cyme -n "__fish_cyme_using_subcommand wait" -l name -d 'Device name containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand wait" -l serial -d 'Device serial containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand wait" -l where -d 'Boolean filter expression like the main --where' -r
+complete -c cyme -n "__fish_cyme_using_subcommand wait" -l timeout -d 'Seconds to wait before exiting with an error; waits indefinitely if not supplied' -r
+complete -c cyme -n "__fish_cyme_using_subcommand wait" -l interval -d 'Milliseconds between each profile of the system' -r
+complete -c cyme -n "__fish_cyme_using_subcommand wait" -l gone -d 'Wait for all matching devices to disconnect rather than one to connect'
+complete -c cyme -n "__fish_cyme_using_subcommand wait" -l json -d 'Print the devices as json rather than lsusb style lines'
+complete -c cyme -n "__fish_cyme_using_subcommand wait" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -s d -l vidpid -d 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]' -r
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -l name -d 'Device name containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -l serial -d 'Device serial containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -l where -d 'Boolean filter expression like the main --where' -r
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -l exec -d 'Command to spawn with the shell for each event; \'{field}\' is replaced with the shell quoted value of the device, fields are event, port_path, vidpid, vid, pid, name, serial, manufacturer, bus, number, dev_path and class. Each is also in the environment as CYME_FIELD, CYME_PORT_PATH for example, and name, serial and manufacturer are only passed that way since the device reports them. e.g. \'notify-send {event} {name}\'' -r
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -l interval -d 'Milliseconds between each profile of the system' -r
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -l history-file -d 'History file to record to rather than the default; implies --history' -r
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -l policy -d 'Audit policy json file, as `cyme audit --policy`, to check devices which connect and those connected when starting against, reporting violations' -r
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -l existing -d 'Also report devices already connected when starting'
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -l notify -d 'Send a desktop notification for each event of devices matching the filters; requires the \'notify\' feature'
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -l json -d 'Print each event as a line of json rather than lsusb style lines'
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -l history -d 'Record events of devices matching the filters to the history in the user data directory, or --history-file, for `cyme history`'
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -l deauthorize -d 'Deauthorize devices with --policy errors so the kernel disconnects them, not for heuristics such as cloned serials; Linux only and requires the \'actions\' feature and root'
+complete -c cyme -n "__fish_cyme_using_subcommand watch" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c cyme -n "__fish_cyme_using_subcommand history" -s d -l vidpid -d 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]' -r
+complete -c cyme -n "__fish_cyme_using_subcommand history" -l name -d 'Device name containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand history" -l serial -d 'Device serial containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand history" -l where -d 'Boolean filter expression like the main --where' -r
+complete -c cyme -n "__fish_cyme_using_subcommand history" -l history-file -d 'History file to read rather than the default in the user data directory' -r
+complete -c cyme -n "__fish_cyme_using_subcommand history" -l events -d 'Print each recorded event rather than a summary of each device'
+complete -c cyme -n "__fish_cyme_using_subcommand history" -l json -d 'Output as json'
+complete -c cyme -n "__fish_cyme_using_subcommand history" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c cyme -n "__fish_cyme_using_subcommand rules" -s d -l vidpid -d 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]' -r
+complete -c cyme -n "__fish_cyme_using_subcommand rules" -l name -d 'Device name containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand rules" -l serial -d 'Device serial containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand rules" -l where -d 'Boolean filter expression like the main --where' -r
+complete -c cyme -n "__fish_cyme_using_subcommand rules" -l group -d 'Group given access, such as plugdev; the user logged in at the seat is given access with the uaccess tag if not supplied' -r
+complete -c cyme -n "__fish_cyme_using_subcommand rules" -l mode -d 'File mode of the device nodes' -r
+complete -c cyme -n "__fish_cyme_using_subcommand rules" -l match-serial -d 'Also match the serial number so only that device is given access'
+complete -c cyme -n "__fish_cyme_using_subcommand rules" -l install -d 'Write the rules to /etc/udev/rules.d and reload udev after confirmation; requires root'
+complete -c cyme -n "__fish_cyme_using_subcommand rules" -s y -l yes -d 'Do not ask for confirmation before installing'
+complete -c cyme -n "__fish_cyme_using_subcommand rules" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c cyme -n "__fish_cyme_using_subcommand lint" -s d -l vidpid -d 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]' -r
+complete -c cyme -n "__fish_cyme_using_subcommand lint" -l name -d 'Device name containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand lint" -l serial -d 'Device serial containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand lint" -l where -d 'Boolean filter expression like the main --where' -r
+complete -c cyme -n "__fish_cyme_using_subcommand lint" -l allow -d 'Checks to skip' -r -f -a "{missing-serial\t'Mass storage devices must have a serial number',bus-power\t'Bus-powered configurations must not request more current than the port can supply',endpoint-interval\t'Interrupt and isochronous endpoint bInterval must be in range for the speed',bcd-usb\t'bcdUSB must be consistent with the speed and descriptors of the device',string-descriptor\t'String descriptors referenced by an index must be readable'}"
+complete -c cyme -n "__fish_cyme_using_subcommand lint" -l from-json -d 'Lint a json dump rather than profiling the live system' -r
+complete -c cyme -n "__fish_cyme_using_subcommand lint" -l json -d 'Output the findings as json'
+complete -c cyme -n "__fish_cyme_using_subcommand lint" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c cyme -n "__fish_cyme_using_subcommand audit" -s d -l vidpid -d 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]' -r
+complete -c cyme -n "__fish_cyme_using_subcommand audit" -l name -d 'Device name containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand audit" -l serial -d 'Device serial containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand audit" -l where -d 'Boolean filter expression like the main --where' -r
+complete -c cyme -n "__fish_cyme_using_subcommand audit" -l policy -d 'Policy json file of what is expected of this endpoint' -r
+complete -c cyme -n "__fish_cyme_using_subcommand audit" -l allow -d 'Checks to skip in addition to those allowed by the policy' -r -f -a "{unexpected-keyboard\t'HID boot keyboard on a port not in [`Policy::keyboard_ports`]; only checked if the policy has some',cloned-serial\t'More than one device with the same VID:PID and serial number; a warning since many cheap devices share a factory serial',known-bad-usb\t'VID:PID of a known BadUSB device or a board commonly used to build one',storage-hid\t'Composite device with both mass storage and HID interfaces',denied\t'Device matches a [`Policy::denylist`] rule',not-allowed\t'Device matches no [`Policy::allowlist`] rule; only checked if the policy has some and hubs with only hub interfaces are exempt'}"
+complete -c cyme -n "__fish_cyme_using_subcommand audit" -l from-json -d 'Audit a json dump rather than profiling the live system' -r
+complete -c cyme -n "__fish_cyme_using_subcommand audit" -l deauthorize -d 'Deauthorize devices with policy errors so the kernel disconnects them until authorized again; not for cloned serials or storage HID which are heuristics. Linux only and requires the \'actions\' feature and root'
+complete -c cyme -n "__fish_cyme_using_subcommand audit" -l json -d 'Output the findings as json'
+complete -c cyme -n "__fish_cyme_using_subcommand audit" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c cyme -n "__fish_cyme_using_subcommand serials" -s d -l vidpid -d 'Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]' -r
+complete -c cyme -n "__fish_cyme_using_subcommand serials" -l name -d 'Device name containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand serials" -l serial -d 'Device serial containing this string' -r
+complete -c cyme -n "__fish_cyme_using_subcommand serials" -l where -d 'Boolean filter expression like the main --where' -r
+complete -c cyme -n "__fish_cyme_using_subcommand serials" -l from-json -d 'Report a json dump rather than profiling the live system' -r
+complete -c cyme -n "__fish_cyme_using_subcommand serials" -l json -d 'Output the report as json'
+complete -c cyme -n "__fish_cyme_using_subcommand serials" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c cyme -n "__fish_cyme_using_subcommand serve" -l http -d 'Address to listen on; \':8080\' listens on localhost only. \'0.0.0.0:8080\' listens on all interfaces, exposing device serials and names to the network without authentication' -r
+complete -c cyme -n "__fish_cyme_using_subcommand serve" -l cors -d 'Allow web pages from ORIGIN, or \'*\' for any, to read the API with the Access-Control-Allow-Origin header; no other origin can by default' -r
+complete -c cyme -n "__fish_cyme_using_subcommand serve" -l interval -d 'Milliseconds between each profile of the system' -r
+complete -c cyme -n "__fish_cyme_using_subcommand serve" -l from-json -d 'Serve a json dump rather than profiling the system, for developing dashboards without devices' -r
+complete -c cyme -n "__fish_cyme_using_subcommand serve" -l extra -d 'Include configurations, interfaces and endpoints; requires opening devices each profile'
+complete -c cyme -n "__fish_cyme_using_subcommand serve" -s h -l help -d 'Print help (see more with \'--help\')'
+complete -c cyme -n "__fish_cyme_using_subcommand help; and not __fish_seen_subcommand_from diff assert export-descriptors capture wait watch history rules lint audit serials serve help" -f -a "diff" -d 'Compare two json dumps, or a dump with the live system, reporting added, removed and changed devices'
+complete -c cyme -n "__fish_cyme_using_subcommand help; and not __fish_seen_subcommand_from diff assert export-descriptors capture wait watch history rules lint audit serials serve help" -f -a "assert" -d 'Assert that the live system matches an expected json dump, exiting non-zero and printing the discrepancies if not'
+complete -c cyme -n "__fish_cyme_using_subcommand help; and not __fish_seen_subcommand_from diff assert export-descriptors capture wait watch history rules lint audit serials serve help" -f -a "export-descriptors" -d 'Export the raw descriptors of a device as binary files for replay in emulators or fuzzers, or to attach to bug reports'
+complete -c cyme -n "__fish_cyme_using_subcommand help; and not __fish_seen_subcommand_from diff assert export-descriptors capture wait watch history rules lint audit serials serve help" -f -a "capture" -d 'Print how to capture the traffic of a device with Linux usbmon, or with --output capture it to a pcap'
+complete -c cyme -n "__fish_cyme_using_subcommand help; and not __fish_seen_subcommand_from diff assert export-descriptors capture wait watch history rules lint audit serials serve help" -f -a "wait" -d 'Wait for a device matching the filters to connect, or with --gone for all matching to disconnect, then print it and exit'
+complete -c cyme -n "__fish_cyme_using_subcommand help; and not __fish_seen_subcommand_from diff assert export-descriptors capture wait watch history rules lint audit serials serve help" -f -a "watch" -d 'Print devices matching the filters as they connect and disconnect, optionally running a command for each'
+complete -c cyme -n "__fish_cyme_using_subcommand help; and not __fish_seen_subcommand_from diff assert export-descriptors capture wait watch history rules lint audit serials serve help" -f -a "history" -d 'Show when devices recorded by `cyme watch --history` were first and last seen and how many times they re-enumerated'
+complete -c cyme -n "__fish_cyme_using_subcommand help; and not __fish_seen_subcommand_from diff assert export-descriptors capture wait watch history rules lint audit serials serve help" -f -a "rules" -d 'Generate udev rules granting access to devices matching the filters without root, for the usb device node and its serial or HID nodes'
+complete -c cyme -n "__fish_cyme_using_subcommand help; and not __fish_seen_subcommand_from diff assert export-descriptors capture wait watch history rules lint audit serials serve help" -f -a "lint" -d 'Check the descriptors of devices matching the filters, or all, against the USB specification like shellcheck for device firmware'
+complete -c cyme -n "__fish_cyme_using_subcommand help; and not __fish_seen_subcommand_from diff assert export-descriptors capture wait watch history rules lint audit serials serve help" -f -a "audit" -d 'Flag potentially suspicious devices matching the filters, or all, for a quick endpoint USB security audit'
+complete -c cyme -n "__fish_cyme_using_subcommand help; and not __fish_seen_subcommand_from diff assert export-descriptors capture wait watch history rules lint audit serials serve help" -f -a "serials" -d 'Report devices matching the filters, or all, which share a serial number or are without one; the bane of udev rules and provisioning keyed on serial'
+complete -c cyme -n "__fish_cyme_using_subcommand help; and not __fish_seen_subcommand_from diff assert export-descriptors capture wait watch history rules lint audit serials serve help" -f -a "serve" -d 'Serve the system profile as a JSON HTTP API for dashboards and remote tooling'
+complete -c cyme -n "__fish_cyme_using_subcommand help; and not __fish_seen_subcommand_from diff assert export-descriptors capture wait watch history rules lint audit serials serve help" -f -a "help" -d 'Print this message or the help of the given subcommand(s)'
+complete -c cyme -s d -l vidpid -x -a '(cyme --complete-candidates vidpid 2>/dev/null)'
+complete -c cyme -s s -l show -x -a '(cyme --complete-candidates show 2>/dev/null)'
+complete -c cyme -s D -l device -x -a '(cyme --complete-candidates device 2>/dev/null)'
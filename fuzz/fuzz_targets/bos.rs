@@ -0,0 +1,8 @@
+//! Binary Object Store descriptor with device capabilities
+#![no_main]
+use cyme::usb::descriptors::bos::BinaryObjectStoreDescriptor;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = BinaryObjectStoreDescriptor::try_from(data);
+});
@@ -0,0 +1,12 @@
+//! Single descriptor with the class code triplet of the interface as the first three bytes
+#![no_main]
+use cyme::usb::Descriptor;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let [class, sub_class, protocol, desc @ ..] = data {
+        if let Ok(mut d) = Descriptor::try_from(desc) {
+            let _ = d.update_with_class_context((*class, *sub_class, *protocol));
+        }
+    }
+});
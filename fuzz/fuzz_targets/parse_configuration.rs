@@ -0,0 +1,7 @@
+//! Whole configuration descriptor blob as returned by GET_DESCRIPTOR
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = cyme::usb::descriptors::parse_configuration(data);
+});
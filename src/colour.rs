@@ -1,4 +1,12 @@
 //! Colouring of cyme output
+//!
+//! [`colored`] has the API of the parts of the `colored` crate used by cyme. With the 'colours' feature it writes ANSI escape codes for any styled string, leaving whether to colour to the caller rather than a process-global override; without it is a stand-in which leaves output plain.
+use clap::ValueEnum;
+#[cfg(feature = "colours")]
+#[path = "colour/ansi.rs"]
+pub mod colored;
+#[cfg(not(feature = "colours"))]
+pub mod colored;
 use colored::*;
 use serde::ser::SerializeSeq;
 use serde::{Deserialize, Deserializer, Serialize};
@@ -178,6 +186,27 @@ pub struct ColourTheme {
         deserialize_with = "deserialize_option_color_from_string"
     )]
     pub tree_endpoint_out: Option<Color>,
+    /// Colour for values flagged as a potential problem, such as a device connected below its capable speed
+    #[serde(
+        default,
+        serialize_with = "color_serializer",
+        deserialize_with = "deserialize_option_color_from_string"
+    )]
+    pub warning: Option<Color>,
+    /// Colour for the marker of devices matching `--highlight`
+    #[serde(
+        default,
+        serialize_with = "color_serializer",
+        deserialize_with = "deserialize_option_color_from_string"
+    )]
+    pub highlight: Option<Color>,
+    /// Background colour of alternate rows with `--stripe`
+    #[serde(
+        default,
+        serialize_with = "color_serializer",
+        deserialize_with = "deserialize_option_color_from_string"
+    )]
+    pub stripe: Option<Color>,
 }
 
 fn deserialize_option_color_from_string<'de, D>(deserializer: D) -> Result<Option<Color>, D::Error>
@@ -314,6 +343,106 @@ impl Default for ColourTheme {
 }
 
 impl ColourTheme {
+    /// Theme using the Okabe-Ito palette; see [`Theme::CbSafe`]
+    pub fn cb_safe() -> Self {
+        let rgb = |r, g, b| Some(Color::TrueColor { r, g, b });
+        let orange = rgb(230, 159, 0);
+        let sky_blue = rgb(86, 180, 233);
+        let bluish_green = rgb(0, 158, 115);
+        let blue = rgb(0, 114, 178);
+        let vermillion = rgb(213, 94, 0);
+        let reddish_purple = rgb(204, 121, 167);
+        let grey = rgb(128, 128, 128);
+        ColourTheme {
+            name: sky_blue,
+            serial: bluish_green,
+            manufacturer: blue,
+            driver: reddish_purple,
+            string: blue,
+            icon: None,
+            location: reddish_purple,
+            path: sky_blue,
+            number: blue,
+            speed: reddish_purple,
+            vid: orange,
+            pid: vermillion,
+            class_code: orange,
+            sub_code: vermillion,
+            protocol: vermillion,
+            attributes: reddish_purple,
+            power: vermillion,
+            tree: grey,
+            tree_bus_start: grey,
+            tree_bus_terminator: grey,
+            tree_configuration_terminator: grey,
+            tree_interface_terminator: grey,
+            tree_endpoint_in: orange,
+            tree_endpoint_out: sky_blue,
+            warning: vermillion,
+            highlight: orange,
+            stripe: Some(Color::TrueColor {
+                r: 40,
+                g: 40,
+                b: 40,
+            }),
+        }
+    }
+
+    /// Names and colours of the fields coloured text; stripe is a background so not included
+    fn text_colours(&self) -> [(&'static str, Option<Color>); 26] {
+        [
+            ("name", self.name),
+            ("serial", self.serial),
+            ("manufacturer", self.manufacturer),
+            ("driver", self.driver),
+            ("string", self.string),
+            ("icon", self.icon),
+            ("location", self.location),
+            ("path", self.path),
+            ("number", self.number),
+            ("speed", self.speed),
+            ("vid", self.vid),
+            ("pid", self.pid),
+            ("class_code", self.class_code),
+            ("sub_code", self.sub_code),
+            ("protocol", self.protocol),
+            ("attributes", self.attributes),
+            ("power", self.power),
+            ("tree", self.tree),
+            ("tree_bus_start", self.tree_bus_start),
+            ("tree_bus_terminator", self.tree_bus_terminator),
+            (
+                "tree_configuration_terminator",
+                self.tree_configuration_terminator,
+            ),
+            ("tree_interface_terminator", self.tree_interface_terminator),
+            ("tree_endpoint_in", self.tree_endpoint_in),
+            ("tree_endpoint_out", self.tree_endpoint_out),
+            ("warning", self.warning),
+            ("highlight", self.highlight),
+        ]
+    }
+
+    /// Names and colours of fields with a [`contrast_ratio`] below [`MIN_CONTRAST`] on `background`
+    ///
+    /// ```
+    /// use cyme::colour::{Background, ColourTheme};
+    ///
+    /// assert!(ColourTheme::new().illegible(Background::Dark).is_empty());
+    /// assert!(ColourTheme::new()
+    ///     .illegible(Background::Light)
+    ///     .iter()
+    ///     .any(|(name, _)| *name == "vid"));
+    /// assert!(ColourTheme::cb_safe().illegible(Background::Light).is_empty());
+    /// ```
+    pub fn illegible(&self, background: Background) -> Vec<(&'static str, Color)> {
+        self.text_colours()
+            .into_iter()
+            .filter_map(|(name, colour)| colour.map(|c| (name, c)))
+            .filter(|(_, c)| contrast_ratio(color_rgb(*c), background.rgb()) < MIN_CONTRAST)
+            .collect()
+    }
+
     /// New theme with defaults
     pub fn new() -> Self {
         ColourTheme {
@@ -341,10 +470,140 @@ impl ColourTheme {
             tree_interface_terminator: Some(Color::BrightBlack),
             tree_endpoint_in: Some(Color::Yellow),
             tree_endpoint_out: Some(Color::Magenta),
+            warning: Some(Color::BrightRed),
+            highlight: Some(Color::BrightYellow),
+            stripe: Some(Color::TrueColor {
+                r: 40,
+                g: 40,
+                b: 40,
+            }),
+        }
+    }
+}
+
+/// Built-in [`ColourTheme`]s
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    /// Default ANSI colours, which follow the terminal palette
+    #[default]
+    Default,
+    /// Okabe-Ito palette distinguishable with deuteranopia and protanopia and legible on dark and light backgrounds; approximated with ANSI colours if the terminal does not support true colour
+    CbSafe,
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Theme::Default => write!(f, "default"),
+            Theme::CbSafe => write!(f, "cb-safe"),
+        }
+    }
+}
+
+impl Theme {
+    /// The [`ColourTheme`] of the theme
+    pub fn colours(&self) -> ColourTheme {
+        match self {
+            Theme::Default => ColourTheme::new(),
+            Theme::CbSafe => ColourTheme::cb_safe(),
+        }
+    }
+}
+
+/// Terminal background used to check the contrast of a [`ColourTheme`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Background {
+    /// Dark background, taken as black
+    Dark,
+    /// Light background, taken as white
+    Light,
+}
+
+impl fmt::Display for Background {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Background::Dark => write!(f, "dark"),
+            Background::Light => write!(f, "light"),
+        }
+    }
+}
+
+impl Background {
+    /// From the COLORFGBG environment variable set by some terminals, '15;0' is white on black for example; `None` if not set
+    pub fn detect() -> Option<Self> {
+        std::env::var("COLORFGBG")
+            .ok()
+            .and_then(|v| Self::from_colorfgbg(&v))
+    }
+
+    /// From a COLORFGBG value, where the last field is the background ANSI colour index
+    ///
+    /// ```
+    /// use cyme::colour::Background;
+    ///
+    /// assert_eq!(Background::from_colorfgbg("15;0"), Some(Background::Dark));
+    /// assert_eq!(Background::from_colorfgbg("0;default;15"), Some(Background::Light));
+    /// assert_eq!(Background::from_colorfgbg("default"), None);
+    /// ```
+    pub fn from_colorfgbg(value: &str) -> Option<Self> {
+        match value.rsplit(';').next()?.parse::<u8>().ok()? {
+            0..=6 | 8 => Some(Background::Dark),
+            _ => Some(Background::Light),
+        }
+    }
+
+    fn rgb(&self) -> (u8, u8, u8) {
+        match self {
+            Background::Dark => (0, 0, 0),
+            Background::Light => (255, 255, 255),
         }
     }
 }
 
+/// Minimum [`contrast_ratio`] of a colour with the [`Background`] below which it is considered illegible
+pub const MIN_CONTRAST: f32 = 2.0;
+
+/// RGB of `color`, using the xterm default palette for the ANSI colours
+pub fn color_rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::Black => (0, 0, 0),
+        Color::Red => (205, 0, 0),
+        Color::Green => (0, 205, 0),
+        Color::Yellow => (205, 205, 0),
+        Color::Blue => (0, 0, 238),
+        Color::Magenta => (205, 0, 205),
+        Color::Cyan => (0, 205, 205),
+        Color::White => (229, 229, 229),
+        Color::BrightBlack => (127, 127, 127),
+        Color::BrightRed => (255, 0, 0),
+        Color::BrightGreen => (0, 255, 0),
+        Color::BrightYellow => (255, 255, 0),
+        Color::BrightBlue => (92, 92, 255),
+        Color::BrightMagenta => (255, 0, 255),
+        Color::BrightCyan => (0, 255, 255),
+        Color::BrightWhite => (255, 255, 255),
+        Color::TrueColor { r, g, b } => (r, g, b),
+    }
+}
+
+/// WCAG contrast ratio of two RGB colours, from 1 for the same to 21 for black and white
+pub fn contrast_ratio(a: (u8, u8, u8), b: (u8, u8, u8)) -> f32 {
+    fn luminance((r, g, b): (u8, u8, u8)) -> f32 {
+        let linear = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+    }
+    let (la, lb) = (luminance(a), luminance(b));
+    (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -368,4 +627,16 @@ mod tests {
         let ctrt: ColourTheme = serde_json::from_str(&ser).unwrap();
         assert_eq!(ct, ctrt);
     }
+
+    #[test]
+    fn test_cb_safe_theme() {
+        let ct = Theme::CbSafe.colours();
+        let ser = serde_json::to_string_pretty(&ct).unwrap();
+        let ctrt: ColourTheme = serde_json::from_str(&ser).unwrap();
+        assert_eq!(ct, ctrt);
+        assert!(ct.illegible(Background::Dark).is_empty());
+        assert!(ct.illegible(Background::Light).is_empty());
+        assert!((contrast_ratio((0, 0, 0), (255, 255, 255)) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio((128, 128, 128), (128, 128, 128)) - 1.0).abs() < 0.01);
+    }
 }
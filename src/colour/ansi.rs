@@ -0,0 +1,191 @@
+//! ANSI styling with the 'colours' feature, as the parts of the `colored` crate used by cyme
+//!
+//! Unlike `colored` there is no global override or check of the environment: a styled string is always written with escape codes, so whether output is coloured is decided by the caller, such as [`crate::display::PrintSettings::colours`] being set.
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Deref;
+
+pub use ::colored::Color;
+
+const RESET: &str = "\x1B[0m";
+
+const BOLD: u8 = 1 << 0;
+const DIMMED: u8 = 1 << 1;
+const ITALIC: u8 = 1 << 2;
+const UNDERLINE: u8 = 1 << 3;
+
+/// String with a foreground colour, background colour and style, as `colored::ColoredString`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColoredString {
+    input: String,
+    fgcolor: Option<Color>,
+    bgcolor: Option<Color>,
+    style: u8,
+}
+
+impl ColoredString {
+    fn is_plain(&self) -> bool {
+        self.fgcolor.is_none() && self.bgcolor.is_none() && self.style == 0
+    }
+
+    /// Escape sequence setting the style then background and foreground colours
+    fn escape(&self) -> String {
+        let mut codes: Vec<Cow<'static, str>> =
+            [(BOLD, "1"), (DIMMED, "2"), (ITALIC, "3"), (UNDERLINE, "4")]
+                .iter()
+                .filter(|(s, _)| self.style & s != 0)
+                .map(|(_, c)| Cow::Borrowed(*c))
+                .collect();
+        codes.extend(self.bgcolor.map(|c| c.to_bg_str()));
+        codes.extend(self.fgcolor.map(|c| c.to_fg_str()));
+        format!("\x1B[{}m", codes.join(";"))
+    }
+
+    fn with_style(mut self, style: u8) -> ColoredString {
+        self.style |= style;
+        self
+    }
+}
+
+impl Deref for ColoredString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.input
+    }
+}
+
+impl fmt::Display for ColoredString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_plain() {
+            return f.pad(&self.input);
+        }
+
+        let escape = self.escape();
+        // resets of nested styled strings would otherwise end this style early
+        let input = self.input.replace(RESET, &format!("{}{}", RESET, escape));
+        f.write_str(&escape)?;
+        f.pad(&input)?;
+        f.write_str(RESET)
+    }
+}
+
+impl From<String> for ColoredString {
+    fn from(s: String) -> Self {
+        ColoredString {
+            input: s,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&str> for ColoredString {
+    fn from(s: &str) -> Self {
+        ColoredString::from(s.to_string())
+    }
+}
+
+/// Styling methods of `colored::Colorize`
+#[allow(missing_docs)]
+pub trait Colorize: Sized {
+    fn plain(self) -> ColoredString;
+
+    fn color<S: Into<Color>>(self, color: S) -> ColoredString {
+        ColoredString {
+            fgcolor: Some(color.into()),
+            ..self.plain()
+        }
+    }
+    fn on_color<S: Into<Color>>(self, color: S) -> ColoredString {
+        ColoredString {
+            bgcolor: Some(color.into()),
+            ..self.plain()
+        }
+    }
+    fn normal(self) -> ColoredString {
+        self.clear()
+    }
+    fn clear(self) -> ColoredString {
+        ColoredString::from(self.plain().input)
+    }
+    fn bold(self) -> ColoredString {
+        self.plain().with_style(BOLD)
+    }
+    fn dimmed(self) -> ColoredString {
+        self.plain().with_style(DIMMED)
+    }
+    fn italic(self) -> ColoredString {
+        self.plain().with_style(ITALIC)
+    }
+    fn underline(self) -> ColoredString {
+        self.plain().with_style(UNDERLINE)
+    }
+    fn black(self) -> ColoredString {
+        self.color(Color::Black)
+    }
+    fn red(self) -> ColoredString {
+        self.color(Color::Red)
+    }
+    fn green(self) -> ColoredString {
+        self.color(Color::Green)
+    }
+    fn yellow(self) -> ColoredString {
+        self.color(Color::Yellow)
+    }
+    fn blue(self) -> ColoredString {
+        self.color(Color::Blue)
+    }
+    fn magenta(self) -> ColoredString {
+        self.color(Color::Magenta)
+    }
+    fn purple(self) -> ColoredString {
+        self.color(Color::Magenta)
+    }
+    fn cyan(self) -> ColoredString {
+        self.color(Color::Cyan)
+    }
+    fn white(self) -> ColoredString {
+        self.color(Color::White)
+    }
+    fn bright_black(self) -> ColoredString {
+        self.color(Color::BrightBlack)
+    }
+}
+
+impl Colorize for &str {
+    fn plain(self) -> ColoredString {
+        ColoredString::from(self)
+    }
+}
+
+impl Colorize for ColoredString {
+    fn plain(self) -> ColoredString {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        assert_eq!("a".plain().to_string(), "a");
+        assert_eq!("a".blue().to_string(), "\x1B[34ma\x1B[0m");
+        assert_eq!(
+            "a".blue().bold().on_color(Color::Blue).to_string(),
+            "\x1B[1;44;34ma\x1B[0m"
+        );
+        assert_eq!("a".red().normal().to_string(), "a");
+        assert_eq!(format!("{:3}|", "a".red()), "\x1B[31ma  \x1B[0m|");
+    }
+
+    #[test]
+    fn test_nested_reset() {
+        let inner = format!("a{}b", "c".red());
+        assert_eq!(
+            inner.as_str().bold().to_string(),
+            "\x1B[1ma\x1B[31mc\x1B[0m\x1B[1mb\x1B[0m"
+        );
+    }
+}
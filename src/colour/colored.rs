@@ -0,0 +1,160 @@
+//! Stand-in for the `colored` crate without the 'colours' feature
+//!
+//! The same API as the parts of `colored` used by cyme but strings are left unstyled, so the display code is the same with or without colours.
+use std::fmt;
+use std::ops::Deref;
+
+/// Terminal colour, as `colored::Color`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    TrueColor { r: u8, g: u8, b: u8 },
+}
+
+impl From<&str> for Color {
+    fn from(src: &str) -> Self {
+        match src.to_lowercase().replace('_', " ").as_str() {
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" | "purple" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "bright black" => Color::BrightBlack,
+            "bright red" => Color::BrightRed,
+            "bright green" => Color::BrightGreen,
+            "bright yellow" => Color::BrightYellow,
+            "bright blue" => Color::BrightBlue,
+            "bright magenta" | "bright purple" => Color::BrightMagenta,
+            "bright cyan" => Color::BrightCyan,
+            "bright white" => Color::BrightWhite,
+            _ => Color::White,
+        }
+    }
+}
+
+impl From<String> for Color {
+    fn from(src: String) -> Self {
+        Color::from(src.as_str())
+    }
+}
+
+/// String that would be styled, as `colored::ColoredString`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColoredString(String);
+
+impl Deref for ColoredString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ColoredString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.0)
+    }
+}
+
+impl From<String> for ColoredString {
+    fn from(s: String) -> Self {
+        ColoredString(s)
+    }
+}
+
+impl From<&str> for ColoredString {
+    fn from(s: &str) -> Self {
+        ColoredString(s.to_string())
+    }
+}
+
+/// Styling methods of `colored::Colorize` which return the string unstyled
+#[allow(missing_docs)]
+pub trait Colorize: Sized {
+    fn plain(self) -> ColoredString;
+
+    fn color<S: Into<Color>>(self, _color: S) -> ColoredString {
+        self.plain()
+    }
+    fn on_color<S: Into<Color>>(self, _color: S) -> ColoredString {
+        self.plain()
+    }
+    fn normal(self) -> ColoredString {
+        self.plain()
+    }
+    fn clear(self) -> ColoredString {
+        self.plain()
+    }
+    fn bold(self) -> ColoredString {
+        self.plain()
+    }
+    fn dimmed(self) -> ColoredString {
+        self.plain()
+    }
+    fn italic(self) -> ColoredString {
+        self.plain()
+    }
+    fn underline(self) -> ColoredString {
+        self.plain()
+    }
+    fn black(self) -> ColoredString {
+        self.plain()
+    }
+    fn red(self) -> ColoredString {
+        self.plain()
+    }
+    fn green(self) -> ColoredString {
+        self.plain()
+    }
+    fn yellow(self) -> ColoredString {
+        self.plain()
+    }
+    fn blue(self) -> ColoredString {
+        self.plain()
+    }
+    fn magenta(self) -> ColoredString {
+        self.plain()
+    }
+    fn purple(self) -> ColoredString {
+        self.plain()
+    }
+    fn cyan(self) -> ColoredString {
+        self.plain()
+    }
+    fn white(self) -> ColoredString {
+        self.plain()
+    }
+    fn bright_black(self) -> ColoredString {
+        self.plain()
+    }
+}
+
+impl Colorize for &str {
+    fn plain(self) -> ColoredString {
+        ColoredString(self.to_string())
+    }
+}
+
+impl Colorize for ColoredString {
+    fn plain(self) -> ColoredString {
+        self
+    }
+}
@@ -1,5 +1,6 @@
 //! Config for cyme binary
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
@@ -9,6 +10,7 @@ use crate::display;
 use crate::display::Block;
 use crate::error::{Error, ErrorKind, Result};
 use crate::icon;
+use crate::usb;
 
 const CONF_DIR: &str = "cyme";
 const CONF_NAME: &str = "cyme.json";
@@ -21,6 +23,8 @@ pub struct Config {
     pub icons: icon::IconTheme,
     /// User supplied [`crate::colour::ColourTheme`] - overrides default
     pub colours: colour::ColourTheme,
+    /// Built-in [`crate::colour::Theme`] used if `colours` are not set
+    pub theme: Option<colour::Theme>,
     /// Default [`crate::display::DeviceBlocks`] to use for displaying devices
     pub blocks: Option<Vec<display::DeviceBlocks>>,
     /// Default [`crate::display::BusBlocks`] to use for displaying buses
@@ -31,8 +35,16 @@ pub struct Config {
     pub interface_blocks: Option<Vec<display::InterfaceBlocks>>,
     /// Default [`crate::display::EndpointBlocks`] to use for device endpoints
     pub endpoint_blocks: Option<Vec<display::EndpointBlocks>>,
+    /// Additional [`crate::display::DeviceBlocks`] for devices of a [`crate::usb::BaseClass`], either the device class or that of an interface; [`crate::display::default_class_blocks`] at max verbosity if not set
+    pub class_blocks: Option<HashMap<usb::BaseClass, Vec<display::DeviceBlocks>>>,
     /// Whether to hide device serial numbers by default
     pub mask_serials: Option<display::MaskSerial>,
+    /// Salt for [`crate::display::MaskSerial::Hash`]
+    pub mask_salt: Option<String>,
+    /// What `--redact` redacts; see [`crate::redact::Redaction`]
+    pub redact: Option<crate::redact::Redaction>,
+    /// [`crate::usb::SpeedUnits`] of speeds; blocks and the lsusb tree have their own default if not set
+    pub speed_units: Option<usb::SpeedUnits>,
     /// Max variable string length to display before truncating - descriptors and classes for example
     pub max_variable_string_len: Option<usize>,
     /// Disable auto generation of max_variable_string_len based on terminal width
@@ -56,6 +68,8 @@ pub struct Config {
     pub decimal: bool,
     /// Disable padding to align blocks
     pub no_padding: bool,
+    /// Never pipe output longer than the terminal to a pager
+    pub no_pager: bool,
     // /// Output coloring mode
     // pub color: display::ColorWhen,
     /// Disables icons and utf-8 characters
@@ -68,6 +82,16 @@ pub struct Config {
     pub headings: bool,
     /// Force nusb/libusb profiler on macOS rather than using/combining system_profiler output
     pub force_libusb: bool,
+    /// USB library [`crate::profiler::backend::BackendKind`] to profile with when more than one is compiled in
+    pub backend: Option<crate::profiler::backend::BackendKind>,
+    /// Device or interface [`crate::usb::BaseClass`]es not to open for verbose data
+    pub no_open_classes: Option<Vec<usb::BaseClass>>,
+    /// [`crate::profiler::quirks::Quirk`]s of devices in addition to the built-in database, keyed by 'vid:pid' or 'vid' for all products of the vendor
+    pub quirks: Option<HashMap<String, Vec<crate::profiler::quirks::Quirk>>>,
+    /// Order of [`crate::profiler::names::NameSource`]s for device names; sources not listed are not used
+    pub name_sources: Option<Vec<crate::profiler::names::NameSource>>,
+    /// User names of devices keyed by 'vid:pid' for the product name or 'vid' for the manufacturer of all products
+    pub names: Option<HashMap<String, String>>,
     /// Print non-critical errors (normally due to permissions) during USB profiler to stderr
     pub print_non_critical_profiler_stderr: bool,
 }
@@ -145,9 +169,12 @@ impl Config {
     }
 
     /// This provides the path for a configuration file, specific to OS
-    /// return None if error like PermissionDenied
+    /// return None if error like PermissionDenied or without the 'config' feature
     pub fn config_file_path() -> Option<PathBuf> {
-        dirs::config_dir().map(|x| x.join(CONF_DIR))
+        #[cfg(feature = "config")]
+        return dirs::config_dir().map(|x| x.join(CONF_DIR));
+        #[cfg(not(feature = "config"))]
+        return None;
     }
 }
 
@@ -173,4 +200,36 @@ mod tests {
         let path = PathBuf::from("./tests/data").join("config_missing_args.json");
         assert!(Config::from_file(path).is_ok());
     }
+
+    #[test]
+    fn test_deserialize_class_blocks() {
+        let config: Config = serde_json::from_str(
+            r#"{"class-blocks": {"audio": ["vendor-fields"], "mass-storage": ["media"]}}"#,
+        )
+        .unwrap();
+        let class_blocks = config.class_blocks.unwrap();
+        assert_eq!(
+            class_blocks.get(&usb::BaseClass::Audio),
+            Some(&vec![display::DeviceBlocks::VendorFields])
+        );
+        assert_eq!(
+            class_blocks.get(&usb::BaseClass::MassStorage),
+            Some(&vec![display::DeviceBlocks::Media])
+        );
+    }
+
+    #[test]
+    fn test_deserialize_quirks() {
+        use crate::profiler::quirks::Quirk;
+        let config: Config = serde_json::from_str(
+            r#"{"quirks": {"1234:5678": ["no-bos", "fake-serial"], "abcd": ["bad-string-length"]}}"#,
+        )
+        .unwrap();
+        let quirks = config.quirks.unwrap();
+        assert_eq!(
+            quirks.get("1234:5678"),
+            Some(&vec![Quirk::NoBos, Quirk::FakeSerial])
+        );
+        assert_eq!(quirks.get("abcd"), Some(&vec![Quirk::BadStringLength]));
+    }
 }
@@ -1,24 +1,72 @@
 //! Provides the main utilities to display USB types within this crate - primarily used by `cyme` binary.
 //!
 //! TODO: There is some repeat code that could probably be made into functions/generics
+use crate::colour::colored::*;
 use clap::ValueEnum;
-use colored::*;
 use itertools::Itertools;
 use rand::{distributions::Alphanumeric, seq::IteratorRandom, Rng};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cell::RefCell;
 use std::cmp;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
+use std::io::{self, IsTerminal, Write};
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 use terminal_size::{Height, Width};
 use unicode_width::UnicodeWidthStr;
 
 use crate::colour;
+use crate::i18n;
 use crate::icon;
-use crate::profiler::{Bus, Device, Filter, SystemProfile};
+use crate::lsusb;
+use crate::profiler::capabilities::Hint;
+use crate::profiler::gadget::{Gadget, GadgetProfile};
+use crate::profiler::quirks::Quirk;
+use crate::profiler::thunderbolt::{ThunderboltDomain, ThunderboltRouter};
+use crate::profiler::{Battery, Bus, Device, Filter, Media, SystemProfile};
+use crate::types::format_duration;
+use crate::usb::vendor::VendorField;
 use crate::usb::DeviceExtra;
-use crate::usb::{ConfigAttributes, Configuration, Direction, Endpoint, Interface};
+use crate::usb::{
+    BaseClass, ConfigAttributes, Configuration, Direction, Endpoint, Interface, Speed, SpeedUnits,
+};
+
+pub mod template;
+pub mod xml;
+
+/// `print!` to the output set with [`set_output`], stdout by default
+macro_rules! output {
+    ($($arg:tt)*) => {
+        $crate::display::write_output(format_args!($($arg)*))
+    };
+}
+
+/// `println!` to the output set with [`set_output`], stdout by default
+macro_rules! outputln {
+    () => {
+        $crate::display::write_output(format_args!("\n"))
+    };
+    ($($arg:tt)*) => {
+        $crate::display::write_output(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+pub(crate) use output;
+pub(crate) use outputln;
+
+// output state is per thread so printing from different threads in the library does not interleave outputs or prefixes
+thread_local! {
+    /// Output set with [`set_output`]; `None` prints to stdout
+    static OUTPUT: RefCell<Option<DisplayWriter>> = const { RefCell::new(None) };
+
+    /// Prefix set with [`with_line_prefix`] and whether the next output starts a line
+    static LINE_PREFIX: RefCell<Option<(String, bool)>> = const { RefCell::new(None) };
+
+    /// Hints added with [`hint`] to be printed by [`finish_output`]
+    static HINTS: RefCell<Vec<Hint>> = const { RefCell::new(Vec::new()) };
+}
 
 const MAX_VERBOSITY: u8 = 4;
 const ICON_HEADING: &str = "I";
@@ -45,6 +93,46 @@ impl std::fmt::Display for ColorWhen {
     }
 }
 
+impl ColorWhen {
+    /// Resolve [`ColorWhen::Auto`] to [`ColorWhen::Always`] or [`ColorWhen::Never`] from the environment variables `var` and whether the output is a `terminal`
+    ///
+    /// `NO_COLOR` or `CLICOLOR=0` disable colour and `CLICOLOR_FORCE` enables it, otherwise colour is for a `terminal` that is not 'dumb'.
+    ///
+    /// ```
+    /// use cyme::display::ColorWhen;
+    ///
+    /// let dumb = |k: &str| (k == "TERM").then(|| String::from("dumb"));
+    /// assert_eq!(ColorWhen::Auto.resolve_with(dumb, true), ColorWhen::Never);
+    /// assert_eq!(ColorWhen::Always.resolve_with(dumb, true), ColorWhen::Always);
+    /// assert_eq!(ColorWhen::Auto.resolve_with(|_| None, true), ColorWhen::Always);
+    /// assert_eq!(ColorWhen::Auto.resolve_with(|_| None, false), ColorWhen::Never);
+    /// let force = |k: &str| (k == "CLICOLOR_FORCE").then(|| String::from("1"));
+    /// assert_eq!(ColorWhen::Auto.resolve_with(force, false), ColorWhen::Always);
+    /// ```
+    pub fn resolve_with<F: Fn(&str) -> Option<String>>(self, var: F, terminal: bool) -> ColorWhen {
+        let set = |k: &str| var(k).is_some_and(|v| !v.is_empty() && v != "0");
+        match self {
+            ColorWhen::Auto if set("NO_COLOR") => ColorWhen::Never,
+            ColorWhen::Auto if set("CLICOLOR_FORCE") => ColorWhen::Always,
+            ColorWhen::Auto if var("CLICOLOR").is_some_and(|v| v == "0") => ColorWhen::Never,
+            ColorWhen::Auto if var("TERM").is_some_and(|t| t == "dumb") => ColorWhen::Never,
+            ColorWhen::Auto if terminal => ColorWhen::Always,
+            ColorWhen::Auto => ColorWhen::Never,
+            c => c,
+        }
+    }
+
+    /// [`ColorWhen::resolve_with`] the process environment for output to stdout
+    pub fn resolve(self) -> ColorWhen {
+        self.resolve_with(|k| std::env::var(k).ok(), io::stdout().is_terminal())
+    }
+
+    /// Whether output to stdout is coloured once [`ColorWhen::resolve`]d
+    pub fn colours(self) -> bool {
+        self.resolve() == ColorWhen::Always
+    }
+}
+
 /// Icon control for the output
 #[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize, ValueEnum, Default)]
 #[serde(rename_all = "kebab-case")]
@@ -137,8 +225,12 @@ pub enum Encoding {
     Glyphs,
     /// Use only standard UTF-8 characters for the output; no private use area glyph icons
     Utf8,
+    /// Use standard UTF-8 characters and emoji icons for terminals with emoji but not NerdFont glyphs
+    Emoji,
     /// Use only ASCII characters for the output; 0x00 - 0x7F (127 chars)
     Ascii,
+    /// Detect the encoding supported by the terminal from the locale, TERM and terminal emulator; treated as [`Encoding::Utf8`] if not resolved with [`Encoding::resolve`]
+    Auto,
 }
 
 impl std::fmt::Display for Encoding {
@@ -148,6 +240,85 @@ impl std::fmt::Display for Encoding {
 }
 
 impl Encoding {
+    /// Detect the encoding supported by the terminal from the environment variables `var`
+    ///
+    /// The locale (LC_ALL, LC_CTYPE then LANG) must be UTF-8 and TERM not dumb or a VT for anything but [`Encoding::Ascii`]; Windows has no locale variables so is assumed UTF-8. Terminals known to bundle NerdFont symbols are [`Encoding::Glyphs`], those known to show emoji [`Encoding::Emoji`] and others [`Encoding::Utf8`].
+    ///
+    /// ```
+    /// use cyme::display::Encoding;
+    ///
+    /// let detect = |vars: &[(&str, &str)]| {
+    ///     Encoding::detect_with(|k| vars.iter().find(|(n, _)| *n == k).map(|(_, v)| v.to_string()))
+    /// };
+    /// assert_eq!(detect(&[("LANG", "C"), ("TERM", "xterm")]), Encoding::Ascii);
+    /// assert_eq!(detect(&[("LANG", "en_GB.UTF-8"), ("TERM", "dumb")]), Encoding::Ascii);
+    /// assert_eq!(detect(&[("LANG", "en_GB.UTF-8"), ("TERM", "xterm")]), Encoding::Utf8);
+    /// assert_eq!(detect(&[("LC_ALL", "C.utf8"), ("TERM_PROGRAM", "WezTerm")]), Encoding::Glyphs);
+    /// assert_eq!(detect(&[("LANG", "C.UTF-8"), ("TERM_PROGRAM", "vscode")]), Encoding::Emoji);
+    /// ```
+    pub fn detect_with<F: Fn(&str) -> Option<String>>(var: F) -> Encoding {
+        let var = |k: &str| var(k).filter(|v| !v.is_empty());
+        let term = var("TERM").unwrap_or_default();
+        let program = var("TERM_PROGRAM").unwrap_or_default().to_lowercase();
+
+        if term == "dumb" || term.starts_with("vt") {
+            return Encoding::Ascii;
+        }
+
+        // first set is the one used
+        let utf8_locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|k| var(k))
+            .map(|l| {
+                let l = l.to_lowercase();
+                l.contains("utf-8") || l.contains("utf8")
+            });
+        match utf8_locale {
+            Some(false) => return Encoding::Ascii,
+            None if !cfg!(target_os = "windows") => return Encoding::Ascii,
+            _ => (),
+        }
+
+        if term == "linux" {
+            // console fonts have box drawing but not emoji
+            Encoding::Utf8
+        } else if term.contains("kitty")
+            || ["wezterm", "ghostty"].contains(&program.as_str())
+            || [
+                "KITTY_WINDOW_ID",
+                "WEZTERM_EXECUTABLE",
+                "GHOSTTY_RESOURCES_DIR",
+            ]
+            .iter()
+            .any(|k| var(k).is_some())
+        {
+            Encoding::Glyphs
+        } else if term == "foot"
+            || term.contains("alacritty")
+            || ["iterm.app", "apple_terminal", "vscode"].contains(&program.as_str())
+            || ["WT_SESSION", "VTE_VERSION", "KONSOLE_VERSION"]
+                .iter()
+                .any(|k| var(k).is_some())
+        {
+            Encoding::Emoji
+        } else {
+            Encoding::Utf8
+        }
+    }
+
+    /// [`Encoding::detect_with`] the process environment
+    pub fn detect() -> Encoding {
+        Encoding::detect_with(|k| std::env::var(k).ok())
+    }
+
+    /// Resolve [`Encoding::Auto`] with [`Encoding::detect`], others are returned as is
+    pub fn resolve(self) -> Encoding {
+        match self {
+            Encoding::Auto => Encoding::detect(),
+            e => e,
+        }
+    }
+
     /// Returns if a char is valid for the encoding for not
     ///
     /// ```
@@ -165,6 +336,10 @@ impl Encoding {
     /// assert!(!enc.char_is_valid('\u{e001}'));
     /// assert!(!enc.char_is_valid(''));
     ///
+    /// let enc = Encoding::Emoji;
+    /// assert!(enc.char_is_valid('\u{1F4BE}'));
+    /// assert!(!enc.char_is_valid('\u{f287}'));
+    ///
     /// let enc = Encoding::Glyphs;
     /// assert!(enc.char_is_valid('I'));
     /// assert!(enc.char_is_valid('\u{2000}'));
@@ -176,7 +351,7 @@ impl Encoding {
         match self {
             Encoding::Ascii if !c.is_ascii() => false,
             // not inside private use area
-            Encoding::Utf8 => !matches!(c,
+            Encoding::Utf8 | Encoding::Emoji | Encoding::Auto => !matches!(c,
                 '\u{E000}'..='\u{F8FF}' |
                 '\u{F0000}'..='\u{FFFFD}' |
                 '\u{100000}'..='\u{10FFFD}'),
@@ -237,6 +412,8 @@ pub enum DeviceBlocks {
     PortPath,
     /// Linux udev reported syspath
     SysPath,
+    /// Path to open device with OS tools: /dev/bus/usb/BBB/DDD on Linux, IOKit locationID on macOS
+    DevPath,
     /// Linux udev reported driver loaded for device
     Driver,
     /// Icon based on VID/PID
@@ -255,8 +432,12 @@ pub enum DeviceBlocks {
     VendorName,
     /// Device serial string as reported by descriptor
     Serial,
-    /// Advertised device capable speed
+    /// Device speed as reported by the profiler; system_profiler description on macOS
     Speed,
+    /// Speed the device is connected at, highlighted if below the capable speed
+    NegotiatedSpeed,
+    /// Maximum speed the device is capable of from BOS descriptor, bcdUSB or device qualifier
+    CapableSpeed,
     /// Position along all branches back to trunk device
     TreePositions,
     /// macOS system_profiler only - actually bus current in mA not power!
@@ -287,6 +468,42 @@ pub enum DeviceBlocks {
     /// Base class as number value rather than enum
     #[serde(alias = "class-value")] // was called ClassCode in previous versions
     BaseValue,
+    /// Bluetooth HCI adapter name of a Bluetooth interface, Linux only
+    BluetoothName,
+    /// Bluetooth HCI adapter address (BD_ADDR) of a Bluetooth interface, Linux only
+    BluetoothAddress,
+    /// Runtime power management state and autosuspend delay on Linux, IOKit power state on macOS
+    PowerState,
+    /// Time since the device was connected, Linux only
+    ConnectedSince,
+    /// Number of USB 3.2 receive/transmit lanes in use; 2/2 for Gen 2x2, Linux only
+    Lanes,
+    /// State of the hub port the device is connected to, Linux only
+    LinkState,
+    /// Physical location of the port the device is connected to from ACPI: panel, position and whether on a dock or lid, Linux only
+    PhysicalLocation,
+    /// Whether the device is on a 'removable' port or 'fixed', hard-wired to it like an internal webcam or Bluetooth adapter; sysfs on Linux, the hub descriptor DeviceRemovable bitmap or macOS system_profiler built-in otherwise
+    Removable,
+    /// Whether the device is 'internal', 'external' or on a 'dock'; a heuristic from removability, ACPI physical location and the hubs it is connected through
+    LocationKind,
+    /// Vendor specific details such as the chip and channel configuration of FTDI, CP210x and CH34x USB-UART bridges
+    VendorFields,
+    /// Charge and charging state of device batteries such as '85% Discharging', Linux only
+    Battery,
+    /// Largest frame size with its frame rate, highest frame rate and formats of UVC cameras such as '3840x2160@30fps, MJPEG/H264, raw YUY2'; shown for video devices at max verbosity
+    VideoFormat,
+    /// Storage media of mass storage devices with disk name, size and whether removable; macOS system_profiler and Linux sysfs
+    Media,
+    /// Capacity of the storage media of mass storage devices, '-' for empty card reader slots; shown for mass storage devices at max verbosity
+    Capacity,
+    /// SCSI INQUIRY vendor, model and revision of mass storage devices from the kernel, Linux only; shown for mass storage devices at max verbosity
+    ScsiInquiry,
+    /// Known-broken behaviours of the device from the quirk database, such as not reading the BOS descriptor because it stalls
+    Quirks,
+    /// Where the name came from: user override, OS, device descriptor or USB IDs; the manufacturer source follows if different
+    NameSource,
+    /// On-The-Go protocols the device supports from its OTG descriptor: SRP, HNP and ADP; only devices for OTG connectors have one
+    Otg,
 }
 
 /// Info that can be printed about a [`Bus`]
@@ -327,6 +544,8 @@ pub enum BusBlocks {
     PciRevision,
     /// syspath style port path to bus, applicable to Linux only
     PortPath,
+    /// Current role of a dual-role port: 'host', 'device' or 'none' from the USB role switch, Linux only
+    Role,
 }
 
 /// Info that can be printed about a [`Configuration`]
@@ -346,6 +565,10 @@ pub enum ConfigurationBlocks {
     IconAttributes,
     /// Maximum current consumption in mA
     MaxPower,
+    /// Raw descriptor bytes as hex, when profiled with raw descriptors
+    RawDescriptor,
+    /// '*' if the configuration is the one currently set on the device
+    Active,
 }
 
 /// Info that can be printed about a [`Interface`]
@@ -372,6 +595,10 @@ pub enum InterfaceBlocks {
     Driver,
     /// syspath obtained from udev on Linux only
     SysPath,
+    /// Kernel module providing the bound driver from sysfs or udev, Linux only
+    Module,
+    /// Parameters of the kernel module such as 'mousepoll=0', Linux only
+    ModuleParameters,
     /// An interface can have many endpoints
     NumEndpoints,
     /// Icon based on BaseClass/SubCode/Protocol
@@ -387,6 +614,20 @@ pub enum InterfaceBlocks {
     /// Base class as number value rather than enum
     #[serde(alias = "class-value")]
     BaseValue,
+    /// Bluetooth HCI adapter name if Bluetooth interface, Linux only
+    BluetoothName,
+    /// Bluetooth HCI adapter address (BD_ADDR) if Bluetooth interface, Linux only
+    BluetoothAddress,
+    /// Summary of sample rates, bit depths and channels if UAC streaming interface
+    AudioFormat,
+    /// UAC2/UAC3 controls and whether they are read-only or read/write if UAC interface
+    AudioControls,
+    /// Largest frame size with its frame rate, highest frame rate and formats if UVC streaming interface
+    VideoFormat,
+    /// Fields decoded from vendor-specific class descriptors by a registered decoder
+    VendorFields,
+    /// Raw descriptor bytes as hex including class descriptors, when profiled with raw descriptors
+    RawDescriptor,
 }
 
 /// Info that can be printed about a [`Endpoint`]
@@ -408,6 +649,8 @@ pub enum EndpointBlocks {
     MaxPacketSize,
     /// Interval for polling endpoint data transfers. Value in frame counts. Ignored for Bulk & Control Endpoints. Isochronous must equal 1 and field may range from 1 to 255 for interrupt endpoints.
     Interval,
+    /// Raw descriptor bytes as hex including companion descriptors, when profiled with raw descriptors
+    RawDescriptor,
 }
 
 /// Length of field printed by block
@@ -472,22 +715,37 @@ pub trait Block<B: Eq + Hash, T> {
     }
 
     /// Returns the length of block value given device data - like block_length but actual device field length rather than fixed/heading
-    fn len(&self, d: &[&T]) -> usize;
+    fn len(&self, d: &[&T], settings: &PrintSettings) -> usize;
 
     /// Returns length type and usize contained, [`BlockLength::Variable`] will be heading usize without actual device data
     fn block_length(&self) -> BlockLength;
 
     /// Creates a HashMap of B keys to usize of longest value for that key in the `d` Vec or heading if > this; values can then be padded to match this
-    fn generate_padding(d: &[&T]) -> HashMap<B, usize>;
+    fn generate_padding(d: &[&T], settings: &PrintSettings) -> HashMap<B, usize>;
 
     /// Colour the block String
     fn colour(&self, s: &str, ct: &colour::ColourTheme) -> ColoredString;
 
+    /// Whether the value of `d` should be coloured with the warning colour rather than [`Block::colour`], such as a device running below its capable speed
+    fn highlight(&self, _d: &T, _settings: &PrintSettings) -> bool {
+        false
+    }
+
     /// Creates the heading for the block value, for use with the heading flag
     fn heading(&self) -> &str;
 
+    /// [`Block::heading`] translated with [`PrintSettings::translator`]
+    fn localized_heading(&self, settings: &PrintSettings) -> Cow<'_, str>
+    where
+        Self: std::fmt::Debug + Sized,
+    {
+        settings
+            .translator
+            .translate(&i18n::message_id(self), self.heading())
+    }
+
     /// Pads the heading with provided padding block HashMap
-    fn heading_padded(&self, pad: &HashMap<B, usize>) -> String;
+    fn heading_padded(&self, pad: &HashMap<B, usize>, settings: &PrintSettings) -> String;
 
     /// Returns whether the value intended for the block is a variable length type (string descriptor)
     fn value_is_variable_length(&self) -> bool {
@@ -557,6 +815,92 @@ impl DeviceBlocks {
             ]
         }
     }
+
+    /// Name of the block as used for the key in json field projection - same as the kebab-case value used for the CLI and config
+    pub fn json_key(&self) -> String {
+        self.to_possible_value()
+            .map(|v| v.get_name().to_string())
+            .unwrap_or_else(|| format!("{:?}", self))
+    }
+
+    /// Gets the value of the block for `d` as a [`serde_json::Value`] for json field projection; `null` if the value is not available
+    pub fn json_value(&self, d: &Device, settings: &PrintSettings) -> serde_json::Value {
+        fn to_value<T: Serialize>(v: T) -> serde_json::Value {
+            serde_json::to_value(v).unwrap_or(serde_json::Value::Null)
+        }
+
+        match self {
+            DeviceBlocks::BusNumber => to_value(d.location_id.bus),
+            DeviceBlocks::DeviceNumber => to_value(d.location_id.number),
+            DeviceBlocks::BranchPosition => to_value(d.get_branch_position()),
+            DeviceBlocks::PortPath => to_value(d.port_path()),
+            DeviceBlocks::SysPath => to_value(d.extra.as_ref().and_then(|e| e.syspath.as_ref())),
+            DeviceBlocks::DevPath => to_value(dev_path(d, settings)),
+            DeviceBlocks::Driver => to_value(d.extra.as_ref().and_then(|e| e.driver.as_ref())),
+            DeviceBlocks::Icon => to_value(
+                settings
+                    .icons
+                    .as_ref()
+                    .map(|i| i.get_encoded_device_icon(d, &settings.encoding)),
+            ),
+            DeviceBlocks::VendorId => to_value(d.vendor_id),
+            DeviceBlocks::ProductId => to_value(d.product_id),
+            DeviceBlocks::Name => to_value(&d.name),
+            DeviceBlocks::Manufacturer => to_value(&d.manufacturer),
+            DeviceBlocks::ProductName => {
+                to_value(d.extra.as_ref().and_then(|e| e.product_name.as_ref()))
+            }
+            DeviceBlocks::VendorName => to_value(d.extra.as_ref().and_then(|e| e.vendor.as_ref())),
+            DeviceBlocks::Serial => to_value(&d.serial_num),
+            DeviceBlocks::Speed => to_value(&d.device_speed),
+            DeviceBlocks::NegotiatedSpeed => to_value(d.negotiated_speed()),
+            DeviceBlocks::CapableSpeed => to_value(d.capable_speed()),
+            DeviceBlocks::TreePositions => to_value(&d.location_id.tree_positions),
+            DeviceBlocks::BusPower => to_value(d.bus_power),
+            DeviceBlocks::BusPowerUsed => to_value(d.bus_power_used),
+            DeviceBlocks::ExtraCurrentUsed => to_value(d.extra_current_used),
+            DeviceBlocks::BcdDevice => to_value(d.bcd_device),
+            DeviceBlocks::BcdUsb => to_value(d.bcd_usb),
+            DeviceBlocks::BaseClass => to_value(d.class),
+            DeviceBlocks::SubClass => to_value(d.sub_class),
+            DeviceBlocks::Protocol => to_value(d.protocol),
+            DeviceBlocks::UidClass => to_value(d.class_name()),
+            DeviceBlocks::UidSubClass => to_value(d.sub_class_name()),
+            DeviceBlocks::UidProtocol => to_value(d.protocol_name()),
+            DeviceBlocks::Class => to_value(d.fully_defined_class()),
+            DeviceBlocks::BaseValue => to_value(d.class.map(u8::from)),
+            DeviceBlocks::BluetoothName => to_value(d.bluetooth_adapter().map(|b| &b.name)),
+            DeviceBlocks::BluetoothAddress => {
+                to_value(d.bluetooth_adapter().and_then(|b| b.address.as_ref()))
+            }
+            DeviceBlocks::PowerState => to_value(d.power_state.as_ref()),
+            DeviceBlocks::ConnectedSince => to_value(d.connected_since),
+            DeviceBlocks::Lanes => to_value(d.rx_lanes.zip(d.tx_lanes)),
+            DeviceBlocks::LinkState => to_value(&d.link_state),
+            DeviceBlocks::PhysicalLocation => to_value(d.physical_location.as_ref()),
+            DeviceBlocks::Removable => to_value(d.is_removable()),
+            DeviceBlocks::LocationKind => to_value(d.location_kind),
+            DeviceBlocks::VendorFields => {
+                to_value(d.extra.as_ref().and_then(|e| e.vendor_fields.as_ref()))
+            }
+            DeviceBlocks::Battery => to_value(&d.batteries),
+            DeviceBlocks::VideoFormat => to_value(d.video_format_summary()),
+            DeviceBlocks::Media => to_value(&d.media),
+            DeviceBlocks::Capacity => to_value(
+                d.media
+                    .as_ref()
+                    .map(|m| m.iter().map(|m| m.size_in_bytes).collect::<Vec<_>>()),
+            ),
+            DeviceBlocks::ScsiInquiry => to_value(
+                d.media
+                    .as_ref()
+                    .map(|m| m.iter().map(|m| m.scsi_inquiry()).collect::<Vec<_>>()),
+            ),
+            DeviceBlocks::Quirks => to_value(&d.quirks),
+            DeviceBlocks::NameSource => to_value(d.name_source),
+            DeviceBlocks::Otg => to_value(d.otg().map(|o| o.protocols())),
+        }
+    }
 }
 
 impl Block<DeviceBlocks, Device> for DeviceBlocks {
@@ -653,7 +997,7 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
         ]
     }
 
-    fn len(&self, d: &[&Device]) -> usize {
+    fn len(&self, d: &[&Device], settings: &PrintSettings) -> usize {
         match self {
             DeviceBlocks::Name => d.iter().map(|d| d.name.width()).max().unwrap_or(0),
             DeviceBlocks::Serial => d
@@ -671,13 +1015,18 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
                 .map(|d| d.location_id.tree_positions.len() * 2)
                 .max()
                 .unwrap_or(0),
-            DeviceBlocks::PortPath => d.iter().map(|d| d.port_path().len()).max().unwrap_or(0),
+            DeviceBlocks::PortPath => d.iter().map(|d| d.port_path().width()).max().unwrap_or(0),
+            DeviceBlocks::DevPath => d
+                .iter()
+                .flat_map(|d| dev_path(d, settings).map(|s| s.width()))
+                .max()
+                .unwrap_or(0),
             DeviceBlocks::SysPath => d
                 .iter()
                 .flat_map(|d| {
                     d.extra
                         .as_ref()
-                        .and_then(|e| e.syspath.as_ref().map(|s| s.len()))
+                        .and_then(|e| e.syspath.as_ref().map(|s| s.width()))
                 })
                 .max()
                 .unwrap_or(0),
@@ -686,7 +1035,7 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
                 .flat_map(|d| {
                     d.extra
                         .as_ref()
-                        .and_then(|e| e.driver.as_ref().map(|s| s.len()))
+                        .and_then(|e| e.driver.as_ref().map(|s| s.width()))
                 })
                 .max()
                 .unwrap_or(0),
@@ -710,36 +1059,141 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
                 .unwrap_or(0),
             DeviceBlocks::BaseClass => d
                 .iter()
-                .flat_map(|d| d.class.as_ref().map(|c| c.to_string().len()))
+                .flat_map(|d| {
+                    d.class
+                        .as_ref()
+                        .map(|c| settings.translator.translate_enum(c).width())
+                })
                 .max()
                 .unwrap_or(0),
             DeviceBlocks::UidClass => d
                 .iter()
-                .flat_map(|d| d.class_name().map(|s| s.len()))
+                .flat_map(|d| d.class_name().map(|s| s.width()))
                 .max()
                 .unwrap_or(0),
             DeviceBlocks::UidSubClass => d
                 .iter()
-                .flat_map(|d| d.sub_class_name().map(|s| s.len()))
+                .flat_map(|d| d.sub_class_name().map(|s| s.width()))
                 .max()
                 .unwrap_or(0),
             DeviceBlocks::UidProtocol => d
                 .iter()
-                .flat_map(|d| d.protocol_name().map(|s| s.len()))
+                .flat_map(|d| d.protocol_name().map(|s| s.width()))
                 .max()
                 .unwrap_or(0),
             DeviceBlocks::Class => d
                 .iter()
-                .map(|d| d.fully_defined_class().map_or(0, |c| c.to_string().len()))
+                .map(|d| d.fully_defined_class().map_or(0, |c| c.to_string().width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::BluetoothName => d
+                .iter()
+                .flat_map(|d| d.bluetooth_adapter().map(|b| b.name.width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::BluetoothAddress => d
+                .iter()
+                .flat_map(|d| {
+                    d.bluetooth_adapter()
+                        .and_then(|b| b.address.as_ref().map(|s| s.width()))
+                })
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::PowerState => d
+                .iter()
+                .flat_map(|d| d.power_state.as_ref().map(|p| p.to_string().width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::ConnectedSince => d
+                .iter()
+                .flat_map(|d| d.connected_duration().map(|s| format_duration(s).width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::LinkState => d
+                .iter()
+                .flat_map(|d| d.link_state.as_ref().map(|s| s.width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::PhysicalLocation => d
+                .iter()
+                .flat_map(|d| d.physical_location.as_ref().map(|p| p.to_string().width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::VendorFields => d
+                .iter()
+                .flat_map(|d| {
+                    d.extra
+                        .as_ref()
+                        .and_then(|e| e.vendor_fields.as_ref())
+                        .map(|v| vendor_fields_string(v).width())
+                })
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::Battery => d
+                .iter()
+                .flat_map(|d| d.batteries.as_ref().map(|b| batteries_string(b).width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::VideoFormat => d
+                .iter()
+                .flat_map(|d| d.video_format_summary().map(|v| v.to_string().width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::Media => d
+                .iter()
+                .flat_map(|d| d.media.as_ref().map(|m| media_string(m).width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::Capacity => d
+                .iter()
+                .flat_map(|d| d.media.as_ref().map(|m| capacity_string(m).width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::ScsiInquiry => d
+                .iter()
+                .flat_map(|d| d.media.as_ref().map(|m| scsi_inquiry_string(m).width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::Quirks => d
+                .iter()
+                .flat_map(|d| d.quirks.as_ref().map(|q| quirks_string(q).width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::Removable => d
+                .iter()
+                .flat_map(|d| removable_string(d, settings).map(|s| s.width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::LocationKind => d
+                .iter()
+                .flat_map(|d| {
+                    d.location_kind
+                        .map(|l| settings.translator.translate_enum(&l).width())
+                })
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::NameSource => d
+                .iter()
+                .flat_map(|d| name_source_string(d).map(|s| s.width()))
+                .max()
+                .unwrap_or(0),
+            DeviceBlocks::Otg => d
+                .iter()
+                .flat_map(|d| d.otg().map(|o| o.to_string().width()))
                 .max()
                 .unwrap_or(0),
             _ => self.block_length().len(),
         }
     }
 
-    fn generate_padding(d: &[&Device]) -> HashMap<Self, usize> {
+    fn generate_padding(d: &[&Device], settings: &PrintSettings) -> HashMap<Self, usize> {
         DeviceBlocks::iter()
-            .map(|b| (b, cmp::max(b.heading().len(), b.len(d))))
+            .map(|b| {
+                (
+                    b,
+                    cmp::max(b.localized_heading(settings).width(), b.len(d, settings)),
+                )
+            })
             .collect()
     }
 
@@ -755,17 +1209,17 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
             DeviceBlocks::BranchPosition => Some(format!("{:3}", d.get_branch_position())),
             DeviceBlocks::PortPath => Some(format!(
                 "{:pad$}",
-                d.port_path(),
+                WidthPad(d.port_path()),
                 pad = pad.get(self).unwrap_or(&0)
             )),
+            DeviceBlocks::DevPath => Some(match dev_path(d, settings) {
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
             DeviceBlocks::SysPath => Some(match d.extra.as_ref() {
                 Some(e) => format!(
                     "{:pad$}",
-                    e.syspath.as_ref().unwrap_or(&format!(
-                        "{:pad$}",
-                        "-",
-                        pad = pad.get(self).unwrap_or(&0)
-                    )),
+                    WidthPad(e.syspath.as_deref().unwrap_or("-")),
                     pad = pad.get(self).unwrap_or(&0)
                 ),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
@@ -773,11 +1227,7 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
             DeviceBlocks::Driver => Some(match d.extra.as_ref() {
                 Some(e) => format!(
                     "{:pad$}",
-                    e.driver.as_ref().unwrap_or(&format!(
-                        "{:pad$}",
-                        "-",
-                        pad = pad.get(self).unwrap_or(&0)
-                    )),
+                    WidthPad(e.driver.as_deref().unwrap_or("-")),
                     pad = pad.get(self).unwrap_or(&0)
                 ),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
@@ -785,11 +1235,7 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
             DeviceBlocks::ProductName => Some(match d.extra.as_ref() {
                 Some(e) => format!(
                     "{:pad$}",
-                    e.product_name.as_ref().unwrap_or(&format!(
-                        "{:pad$}",
-                        "-",
-                        pad = pad.get(self).unwrap_or(&0)
-                    )),
+                    WidthPad(e.product_name.as_deref().unwrap_or("-")),
                     pad = pad.get(self).unwrap_or(&0)
                 ),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
@@ -797,16 +1243,15 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
             DeviceBlocks::VendorName => Some(match d.extra.as_ref() {
                 Some(e) => format!(
                     "{:pad$}",
-                    e.vendor.as_ref().unwrap_or(&format!(
-                        "{:pad$}",
-                        "-",
-                        pad = pad.get(self).unwrap_or(&0)
-                    )),
+                    WidthPad(e.vendor.as_deref().unwrap_or("-")),
                     pad = pad.get(self).unwrap_or(&0)
                 ),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
-            DeviceBlocks::Icon => settings.icons.as_ref().map(|i| i.get_device_icon(d)),
+            DeviceBlocks::Icon => settings
+                .icons
+                .as_ref()
+                .map(|i| i.get_encoded_device_icon(d, &settings.encoding)),
             DeviceBlocks::VendorId => Some(match d.vendor_id {
                 Some(v) => Self::format_base_u16(v, settings),
                 None => format!("{:>6}", "-"),
@@ -817,19 +1262,39 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
             }),
             DeviceBlocks::Name => Some(format!(
                 "{:pad$}",
-                d.name,
+                WidthPad(&d.name),
                 pad = pad.get(self).unwrap_or(&0)
             )),
             DeviceBlocks::Manufacturer => Some(match d.manufacturer.as_ref() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             DeviceBlocks::Serial => Some(match d.serial_num.as_ref() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             DeviceBlocks::Speed => Some(match d.device_speed.as_ref() {
-                Some(v) => format!("{:>10}", v.to_string()),
+                Some(v) => format!(
+                    "{:>10}",
+                    settings
+                        .speed_units
+                        .and_then(|u| v.to_units_string(u))
+                        .unwrap_or_else(|| v.to_string())
+                ),
+                None => format!("{:>10}", "-"),
+            }),
+            DeviceBlocks::NegotiatedSpeed => Some(match d.negotiated_speed() {
+                Some(v) => format!(
+                    "{:>10}",
+                    v.to_units_string(settings.speed_units.unwrap_or(SpeedUnits::Decimal))
+                ),
+                None => format!("{:>10}", "-"),
+            }),
+            DeviceBlocks::CapableSpeed => Some(match d.capable_speed() {
+                Some(v) => format!(
+                    "{:>10}",
+                    v.to_units_string(settings.speed_units.unwrap_or(SpeedUnits::Decimal))
+                ),
                 None => format!("{:>10}", "-"),
             }),
             DeviceBlocks::TreePositions => Some(format!(
@@ -858,7 +1323,11 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
                 None => format!("{:>5}", "-"),
             }),
             DeviceBlocks::BaseClass => Some(match d.class.as_ref() {
-                Some(v) => format!("{:pad$}", v.to_string(), pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(settings.translator.translate_enum(v)),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             DeviceBlocks::SubClass => Some(match d.sub_class.as_ref() {
@@ -870,54 +1339,211 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
                 None => format!("{:>4}", "-"),
             }),
             DeviceBlocks::UidClass => Some(match d.class_name() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             DeviceBlocks::UidSubClass => Some(match d.sub_class_name() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             DeviceBlocks::UidProtocol => Some(match d.protocol_name() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             DeviceBlocks::Class => Some(match d.fully_defined_class() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             DeviceBlocks::BaseValue => Some(match d.class.as_ref() {
                 Some(v) => Self::format_base_u8((*v).into(), settings),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
+            DeviceBlocks::BluetoothName => Some(match d.bluetooth_adapter() {
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(&v.name),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            DeviceBlocks::BluetoothAddress => Some(
+                match d.bluetooth_adapter().and_then(|b| b.address.as_ref()) {
+                    Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
+                    None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+                },
+            ),
+            DeviceBlocks::PowerState => Some(match d.power_state.as_ref() {
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(v.to_string()),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            DeviceBlocks::Lanes => Some(match d.rx_lanes.zip(d.tx_lanes) {
+                Some((rx, tx)) => format!("{:>5}", format!("{}/{}", rx, tx)),
+                None => format!("{:>5}", "-"),
+            }),
+            DeviceBlocks::LinkState => Some(match d.link_state.as_ref() {
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            DeviceBlocks::PhysicalLocation => Some(match d.physical_location.as_ref() {
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(v.to_string()),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            DeviceBlocks::Removable => Some(format!(
+                "{:pad$}",
+                WidthPad(removable_string(d, settings).unwrap_or(Cow::Borrowed("-"))),
+                pad = pad.get(self).unwrap_or(&0)
+            )),
+            DeviceBlocks::LocationKind => Some(match d.location_kind {
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(settings.translator.translate_enum(&v)),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            DeviceBlocks::ConnectedSince => Some(match d.connected_duration() {
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(format_duration(v)),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            DeviceBlocks::VendorFields => Some(
+                match d.extra.as_ref().and_then(|e| e.vendor_fields.as_ref()) {
+                    Some(v) => format!(
+                        "{:pad$}",
+                        WidthPad(vendor_fields_string(v)),
+                        pad = pad.get(self).unwrap_or(&0)
+                    ),
+                    None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+                },
+            ),
+            DeviceBlocks::Battery => Some(match d.batteries.as_ref() {
+                Some(b) => format!(
+                    "{:pad$}",
+                    WidthPad(batteries_string(b)),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            DeviceBlocks::VideoFormat => Some(match d.video_format_summary() {
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(v.to_string()),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            DeviceBlocks::Media => Some(match d.media.as_ref() {
+                Some(m) => format!(
+                    "{:pad$}",
+                    WidthPad(media_string(m)),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            DeviceBlocks::Capacity => Some(match d.media.as_ref() {
+                Some(m) => format!(
+                    "{:pad$}",
+                    WidthPad(capacity_string(m)),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            DeviceBlocks::ScsiInquiry => Some(
+                match d
+                    .media
+                    .as_ref()
+                    .map(|m| scsi_inquiry_string(m))
+                    .filter(|s| !s.is_empty())
+                {
+                    Some(s) => format!("{:pad$}", WidthPad(s), pad = pad.get(self).unwrap_or(&0)),
+                    None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+                },
+            ),
+            DeviceBlocks::Quirks => Some(match d.quirks.as_ref() {
+                Some(q) => format!(
+                    "{:pad$}",
+                    WidthPad(quirks_string(q)),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            DeviceBlocks::NameSource => Some(format!(
+                "{:pad$}",
+                WidthPad(name_source_string(d).unwrap_or(String::from("-"))),
+                pad = pad.get(self).unwrap_or(&0)
+            )),
+            DeviceBlocks::Otg => Some(match d.otg() {
+                Some(o) => format!("{:pad$}", o.to_string(), pad = pad.get(self).unwrap_or(&0)),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+        }
+    }
+
+    fn highlight(&self, d: &Device, settings: &PrintSettings) -> bool {
+        match self {
+            DeviceBlocks::NegotiatedSpeed => d.is_below_capable_speed(),
+            DeviceBlocks::Serial => settings
+                .serial_warnings
+                .as_ref()
+                .is_some_and(|w| w.contains(&d.port_path())),
+            _ => false,
         }
     }
 
     fn colour(&self, s: &str, ct: &colour::ColourTheme) -> ColoredString {
         match self {
-            DeviceBlocks::BcdUsb | DeviceBlocks::BcdDevice | DeviceBlocks::DeviceNumber => {
-                ct.number.map_or(s.normal(), |c| s.color(c))
-            }
+            DeviceBlocks::BcdUsb
+            | DeviceBlocks::BcdDevice
+            | DeviceBlocks::DeviceNumber
+            | DeviceBlocks::ConnectedSince => ct.number.map_or(s.normal(), |c| s.color(c)),
             DeviceBlocks::BusNumber
             | DeviceBlocks::BranchPosition
-            | DeviceBlocks::TreePositions => ct.location.map_or(s.normal(), |c| s.color(c)),
+            | DeviceBlocks::TreePositions
+            | DeviceBlocks::PhysicalLocation
+            | DeviceBlocks::Removable
+            | DeviceBlocks::LocationKind => ct.location.map_or(s.normal(), |c| s.color(c)),
             DeviceBlocks::Icon => ct.icon.map_or(s.normal(), |c| s.color(c)),
-            DeviceBlocks::PortPath | DeviceBlocks::SysPath => {
+            DeviceBlocks::PortPath | DeviceBlocks::SysPath | DeviceBlocks::DevPath => {
                 ct.path.map_or(s.normal(), |c| s.color(c))
             }
             DeviceBlocks::VendorId => ct.vid.map_or(s.normal(), |c| s.color(c)),
             DeviceBlocks::ProductId => ct.pid.map_or(s.normal(), |c| s.color(c)),
-            DeviceBlocks::Name | DeviceBlocks::ProductName => {
+            DeviceBlocks::Name | DeviceBlocks::ProductName | DeviceBlocks::NameSource => {
                 ct.name.map_or(s.normal(), |c| s.color(c))
             }
-            DeviceBlocks::Serial => ct.serial.map_or(s.normal(), |c| s.color(c)),
-            DeviceBlocks::Manufacturer | DeviceBlocks::VendorName => {
+            DeviceBlocks::Serial | DeviceBlocks::BluetoothAddress => {
+                ct.serial.map_or(s.normal(), |c| s.color(c))
+            }
+            DeviceBlocks::Manufacturer | DeviceBlocks::VendorName | DeviceBlocks::VendorFields => {
                 ct.manufacturer.map_or(s.normal(), |c| s.color(c))
             }
-            DeviceBlocks::Driver => ct.driver.map_or(s.normal(), |c| s.color(c)),
-            DeviceBlocks::Speed => ct.speed.map_or(s.normal(), |c| s.color(c)),
+            DeviceBlocks::Driver
+            | DeviceBlocks::BluetoothName
+            | DeviceBlocks::Media
+            | DeviceBlocks::Capacity
+            | DeviceBlocks::ScsiInquiry
+            | DeviceBlocks::VideoFormat => ct.driver.map_or(s.normal(), |c| s.color(c)),
+            DeviceBlocks::Speed
+            | DeviceBlocks::NegotiatedSpeed
+            | DeviceBlocks::CapableSpeed
+            | DeviceBlocks::Lanes
+            | DeviceBlocks::LinkState => ct.speed.map_or(s.normal(), |c| s.color(c)),
             DeviceBlocks::BusPower
             | DeviceBlocks::BusPowerUsed
-            | DeviceBlocks::ExtraCurrentUsed => ct.power.map_or(s.normal(), |c| s.color(c)),
+            | DeviceBlocks::ExtraCurrentUsed
+            | DeviceBlocks::PowerState
+            | DeviceBlocks::Battery => ct.power.map_or(s.normal(), |c| s.color(c)),
             DeviceBlocks::BaseClass
             | DeviceBlocks::UidClass
             | DeviceBlocks::Class
@@ -925,9 +1551,10 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
             DeviceBlocks::SubClass | DeviceBlocks::UidSubClass => {
                 ct.sub_code.map_or(s.normal(), |c| s.color(c))
             }
-            DeviceBlocks::Protocol | DeviceBlocks::UidProtocol => {
+            DeviceBlocks::Protocol | DeviceBlocks::UidProtocol | DeviceBlocks::Otg => {
                 ct.protocol.map_or(s.normal(), |c| s.color(c))
             }
+            DeviceBlocks::Quirks => ct.warning.map_or(s.normal(), |c| s.color(c)),
         }
     }
 
@@ -938,6 +1565,7 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
             DeviceBlocks::BranchPosition => "Prt",
             DeviceBlocks::PortPath => "PPath",
             DeviceBlocks::SysPath => "SPath",
+            DeviceBlocks::DevPath => "DPath",
             DeviceBlocks::Driver => "Driver",
             DeviceBlocks::VendorId => "VID",
             DeviceBlocks::ProductId => "PID",
@@ -947,6 +1575,8 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
             DeviceBlocks::VendorName => "VName",
             DeviceBlocks::Serial => "Serial",
             DeviceBlocks::Speed => "Speed",
+            DeviceBlocks::NegotiatedSpeed => "NSpeed",
+            DeviceBlocks::CapableSpeed => "CSpeed",
             DeviceBlocks::TreePositions => "TPos",
             // will be 000 mA = 6
             DeviceBlocks::BusPower => "PBus",
@@ -963,14 +1593,32 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
             DeviceBlocks::UidProtocol => "UidPc",
             DeviceBlocks::Class => "Class",
             DeviceBlocks::BaseValue => "CVal",
+            DeviceBlocks::BluetoothName => "BtName",
+            DeviceBlocks::BluetoothAddress => "BtAddr",
+            DeviceBlocks::PowerState => "PwrSt",
+            DeviceBlocks::ConnectedSince => "Conn",
+            DeviceBlocks::Lanes => "Lanes",
+            DeviceBlocks::LinkState => "LState",
+            DeviceBlocks::PhysicalLocation => "PhysLoc",
+            DeviceBlocks::Removable => "Remov",
+            DeviceBlocks::LocationKind => "Location",
+            DeviceBlocks::VendorFields => "Vendor",
+            DeviceBlocks::Battery => "Battery",
+            DeviceBlocks::VideoFormat => "VFormat",
+            DeviceBlocks::Media => "Media",
+            DeviceBlocks::Capacity => "Capacity",
+            DeviceBlocks::ScsiInquiry => "SCSI",
+            DeviceBlocks::Quirks => "Quirks",
+            DeviceBlocks::NameSource => "NameSrc",
+            DeviceBlocks::Otg => "OTG",
             DeviceBlocks::Icon => ICON_HEADING,
         }
     }
 
-    fn heading_padded(&self, pad: &HashMap<Self, usize>) -> String {
+    fn heading_padded(&self, pad: &HashMap<Self, usize>, settings: &PrintSettings) -> String {
         format!(
             "{:^pad$}",
-            self.heading(),
+            WidthPad(self.localized_heading(settings)),
             pad = pad.get(self).unwrap_or(&0)
         )
     }
@@ -982,11 +1630,15 @@ impl Block<DeviceBlocks, Device> for DeviceBlocks {
                 BlockLength::Fixed(3)
             }
             DeviceBlocks::VendorId | DeviceBlocks::ProductId => BlockLength::Fixed(6),
-            DeviceBlocks::Speed => BlockLength::Fixed(10),
+            DeviceBlocks::Speed | DeviceBlocks::NegotiatedSpeed | DeviceBlocks::CapableSpeed => {
+                BlockLength::Fixed(10)
+            }
             DeviceBlocks::BusPower
             | DeviceBlocks::BusPowerUsed
             | DeviceBlocks::ExtraCurrentUsed => BlockLength::Fixed(6),
-            DeviceBlocks::BcdDevice | DeviceBlocks::BcdUsb => BlockLength::Fixed(5),
+            DeviceBlocks::BcdDevice | DeviceBlocks::BcdUsb | DeviceBlocks::Lanes => {
+                BlockLength::Fixed(5)
+            }
             DeviceBlocks::SubClass | DeviceBlocks::Protocol | DeviceBlocks::BaseValue => {
                 BlockLength::Fixed(4)
             }
@@ -1023,12 +1675,16 @@ impl Block<BusBlocks, Bus> for BusBlocks {
         }
     }
 
-    fn len(&self, d: &[&Bus]) -> usize {
+    fn len(&self, d: &[&Bus], _settings: &PrintSettings) -> usize {
         match self {
-            BusBlocks::Name => d.iter().map(|d| d.name.width()).max().unwrap_or(0),
-            BusBlocks::HostController => d
+            BusBlocks::Name => d
                 .iter()
-                .map(|d| d.host_controller.width())
+                .map(|d| d.display_name().width())
+                .max()
+                .unwrap_or(0),
+            BusBlocks::HostController => d
+                .iter()
+                .map(|d| d.host_controller.width())
                 .max()
                 .unwrap_or(0),
             BusBlocks::HostControllerVendor => d
@@ -1043,16 +1699,21 @@ impl Block<BusBlocks, Bus> for BusBlocks {
                 .unwrap_or(0),
             BusBlocks::PortPath => d
                 .iter()
-                .map(|d| d.path().unwrap_or("-".to_string()).len())
+                .map(|d| d.path().unwrap_or("-".to_string()).width())
                 .max()
                 .unwrap_or(0),
             _ => self.block_length().len(),
         }
     }
 
-    fn generate_padding(d: &[&Bus]) -> HashMap<Self, usize> {
+    fn generate_padding(d: &[&Bus], settings: &PrintSettings) -> HashMap<Self, usize> {
         BusBlocks::iter()
-            .map(|b| (b, cmp::max(b.heading().len(), b.len(d))))
+            .map(|b| {
+                (
+                    b,
+                    cmp::max(b.localized_heading(settings).width(), b.len(d, settings)),
+                )
+            })
             .collect()
     }
 
@@ -1068,6 +1729,7 @@ impl Block<BusBlocks, Bus> for BusBlocks {
             BusBlocks::PciRevision => ct.number.map_or(s.normal(), |c| s.color(c)),
             BusBlocks::Icon => ct.icon.map_or(s.normal(), |c| s.color(c)),
             BusBlocks::PortPath => ct.path.map_or(s.normal(), |c| s.color(c)),
+            BusBlocks::Role => ct.protocol.map_or(s.normal(), |c| s.color(c)),
         }
     }
 
@@ -1085,7 +1747,7 @@ impl Block<BusBlocks, Bus> for BusBlocks {
             BusBlocks::Icon => settings
                 .icons
                 .as_ref()
-                .map(|i| i.get_bus_icon(bus))
+                .map(|i| i.get_encoded_bus_icon(bus, &settings.encoding))
                 .or(Some(" ".to_string())),
             BusBlocks::PciVendor => Some(match bus.pci_vendor {
                 Some(v) => Self::format_base_u16(v, settings),
@@ -1101,24 +1763,28 @@ impl Block<BusBlocks, Bus> for BusBlocks {
             }),
             BusBlocks::Name => Some(format!(
                 "{:pad$}",
-                bus.name,
+                WidthPad(bus.display_name()),
                 pad = pad.get(self).unwrap_or(&0)
             )),
             BusBlocks::HostController => Some(format!(
                 "{:pad$}",
-                bus.host_controller,
+                WidthPad(&bus.host_controller),
                 pad = pad.get(self).unwrap_or(&0)
             )),
             BusBlocks::HostControllerVendor => Some(match bus.host_controller_vendor.as_ref() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             BusBlocks::HostControllerDevice => Some(match bus.host_controller_device.as_ref() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             BusBlocks::PortPath => Some(match bus.path() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            BusBlocks::Role => Some(match bus.role {
+                Some(v) => format!("{:pad$}", v.to_string(), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
         }
@@ -1135,14 +1801,15 @@ impl Block<BusBlocks, Bus> for BusBlocks {
             BusBlocks::HostController => "HostController",
             BusBlocks::HostControllerVendor => "HostVendor",
             BusBlocks::HostControllerDevice => "HostDevice",
+            BusBlocks::Role => "Role",
             BusBlocks::Icon => ICON_HEADING,
         }
     }
 
-    fn heading_padded(&self, pad: &HashMap<Self, usize>) -> String {
+    fn heading_padded(&self, pad: &HashMap<Self, usize>, settings: &PrintSettings) -> String {
         format!(
             "{:^pad$}",
-            self.heading(),
+            WidthPad(self.localized_heading(settings)),
             pad = pad.get(self).unwrap_or(&0)
         )
     }
@@ -1151,9 +1818,10 @@ impl Block<BusBlocks, Bus> for BusBlocks {
         match self {
             BusBlocks::Icon => BlockLength::Fixed(1),
             BusBlocks::BusNumber => BlockLength::Fixed(3),
-            BusBlocks::PciDevice | BusBlocks::PciVendor | BusBlocks::PciRevision => {
-                BlockLength::Fixed(6)
-            }
+            BusBlocks::PciDevice
+            | BusBlocks::PciVendor
+            | BusBlocks::PciRevision
+            | BusBlocks::Role => BlockLength::Fixed(6),
             _ => BlockLength::Variable(self.heading().len()),
         }
     }
@@ -1169,6 +1837,7 @@ impl Block<ConfigurationBlocks, Configuration> for ConfigurationBlocks {
     fn default_blocks(verbose: bool) -> Vec<Self> {
         if verbose {
             vec![
+                ConfigurationBlocks::Active,
                 ConfigurationBlocks::Number,
                 ConfigurationBlocks::IconAttributes,
                 ConfigurationBlocks::Attributes,
@@ -1178,6 +1847,7 @@ impl Block<ConfigurationBlocks, Configuration> for ConfigurationBlocks {
             ]
         } else {
             vec![
+                ConfigurationBlocks::Active,
                 ConfigurationBlocks::Number,
                 ConfigurationBlocks::IconAttributes,
                 ConfigurationBlocks::MaxPower,
@@ -1186,21 +1856,31 @@ impl Block<ConfigurationBlocks, Configuration> for ConfigurationBlocks {
         }
     }
 
-    fn len(&self, d: &[&Configuration]) -> usize {
+    fn len(&self, d: &[&Configuration], _settings: &PrintSettings) -> usize {
         match self {
-            ConfigurationBlocks::Name => d.iter().map(|d| d.name.len()).max().unwrap_or(0),
+            ConfigurationBlocks::Name => d.iter().map(|d| d.name.width()).max().unwrap_or(0),
             ConfigurationBlocks::Attributes => d
                 .iter()
-                .map(|d| d.attributes_string().len())
+                .map(|d| d.attributes_string().width())
+                .max()
+                .unwrap_or(0),
+            ConfigurationBlocks::RawDescriptor => d
+                .iter()
+                .flat_map(|d| d.raw.as_ref().map(|r| hex_string(r).width()))
                 .max()
                 .unwrap_or(0),
             _ => self.block_length().len(),
         }
     }
 
-    fn generate_padding(d: &[&Configuration]) -> HashMap<Self, usize> {
+    fn generate_padding(d: &[&Configuration], settings: &PrintSettings) -> HashMap<Self, usize> {
         ConfigurationBlocks::iter()
-            .map(|b| (b, cmp::max(b.heading().len(), b.len(d))))
+            .map(|b| {
+                (
+                    b,
+                    cmp::max(b.localized_heading(settings).width(), b.len(d, settings)),
+                )
+            })
             .collect()
     }
 
@@ -1212,6 +1892,8 @@ impl Block<ConfigurationBlocks, Configuration> for ConfigurationBlocks {
             ConfigurationBlocks::Name => ct.name.map_or(s.normal(), |c| s.color(c)),
             ConfigurationBlocks::Attributes => ct.attributes.map_or(s.normal(), |c| s.color(c)),
             ConfigurationBlocks::IconAttributes => ct.icon.map_or(s.normal(), |c| s.color(c)),
+            ConfigurationBlocks::RawDescriptor => ct.number.map_or(s.normal(), |c| s.color(c)),
+            ConfigurationBlocks::Active => ct.location.map_or(s.normal(), |c| s.color(c)),
         }
     }
 
@@ -1226,20 +1908,31 @@ impl Block<ConfigurationBlocks, Configuration> for ConfigurationBlocks {
             ConfigurationBlocks::NumInterfaces => Some(format!("{:2}", config.interfaces.len())),
             ConfigurationBlocks::Name => Some(format!(
                 "{:pad$}",
-                config.name,
+                WidthPad(&config.name),
                 pad = pad.get(self).unwrap_or(&0)
             )),
             ConfigurationBlocks::MaxPower => Some(format!("{:6}", config.max_power)),
             ConfigurationBlocks::Attributes => Some(format!(
                 "{:pad$}",
-                config.attributes_string(),
+                WidthPad(config.attributes_string()),
                 pad = pad.get(self).unwrap_or(&0)
             )),
             ConfigurationBlocks::IconAttributes => Some(format!(
                 "{:pad$}",
-                attributes_to_icons(&config.attributes, settings),
+                WidthPad(attributes_to_icons(&config.attributes, settings)),
                 pad = pad.get(self).unwrap_or(&0)
             )),
+            ConfigurationBlocks::RawDescriptor => Some(match config.raw.as_ref() {
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(hex_string(v)),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            ConfigurationBlocks::Active => {
+                Some(String::from(if config.active { "*" } else { " " }))
+            }
         }
     }
 
@@ -1251,13 +1944,15 @@ impl Block<ConfigurationBlocks, Configuration> for ConfigurationBlocks {
             ConfigurationBlocks::Name => "Name",
             ConfigurationBlocks::Attributes => "Attributes",
             ConfigurationBlocks::IconAttributes => ICON_HEADING,
+            ConfigurationBlocks::RawDescriptor => "Raw",
+            ConfigurationBlocks::Active => "*",
         }
     }
 
-    fn heading_padded(&self, pad: &HashMap<Self, usize>) -> String {
+    fn heading_padded(&self, pad: &HashMap<Self, usize>, settings: &PrintSettings) -> String {
         format!(
             "{:^pad$}",
-            self.heading(),
+            WidthPad(self.localized_heading(settings)),
             pad = pad.get(self).unwrap_or(&0)
         )
     }
@@ -1269,6 +1964,7 @@ impl Block<ConfigurationBlocks, Configuration> for ConfigurationBlocks {
             ConfigurationBlocks::MaxPower => BlockLength::Fixed(6),
             // two possible icons and a space between
             ConfigurationBlocks::IconAttributes => BlockLength::Fixed(3),
+            ConfigurationBlocks::Active => BlockLength::Fixed(1),
             _ => BlockLength::Variable(self.heading().len()),
         }
     }
@@ -1278,6 +1974,205 @@ impl Block<ConfigurationBlocks, Configuration> for ConfigurationBlocks {
     }
 }
 
+/// Decoded UAC controls of the interface as "Mute: read/write, Volume: read-only"
+fn audio_controls_string(interface: &Interface) -> String {
+    interface
+        .audio_controls()
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Decoded vendor fields as "name: value, name: value"
+fn vendor_fields_string(fields: &[VendorField]) -> String {
+    fields
+        .iter()
+        .map(|f| f.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Bytes as space separated hex such as "09 02 22 00"
+fn hex_string(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Name of a descriptor within a configuration descriptor from bDescriptorType
+fn descriptor_type_name(descriptor_type: u8) -> &'static str {
+    match descriptor_type {
+        0x02 => "CONFIGURATION",
+        0x04 => "INTERFACE",
+        0x05 => "ENDPOINT",
+        0x0b => "INTERFACE_ASSOCIATION",
+        0x21 => "HID/CS_DEVICE",
+        0x24 => "CS_INTERFACE",
+        0x25 => "CS_ENDPOINT",
+        0x30 => "SS_ENDPOINT_COMPANION",
+        0x31 => "SSP_ISOC_ENDPOINT_COMPANION",
+        _ => "UNKNOWN",
+    }
+}
+
+/// Annotated hexdump of raw descriptor bytes, such as [`Configuration`] `raw`, like `lsusb -D` or `usbhid-dump`
+///
+/// Each descriptor starts a new line with its offset, up to 16 bytes per line, and the first line is annotated with the descriptor type and bLength.
+///
+/// ```
+/// use cyme::display::descriptor_hexdump;
+///
+/// let lines = descriptor_hexdump(&[0x09, 0x02, 0x22, 0x00, 0x01, 0x01, 0x00, 0xa0, 0x32, 0x02, 0xff]);
+/// assert_eq!(lines[0], "0000: 09 02 22 00 01 01 00 a0 32                        CONFIGURATION bLength 9");
+/// assert_eq!(lines[1], "0009: 02 ff                                             UNKNOWN (0xff) bLength 2");
+/// ```
+pub fn descriptor_hexdump(data: &[u8]) -> Vec<String> {
+    let mut ret = Vec::new();
+    let mut offset = 0;
+
+    while offset < data.len() {
+        let length = data[offset] as usize;
+        // malformed so dump the remaining bytes as one
+        let desc = if length < 2 || offset + length > data.len() {
+            &data[offset..]
+        } else {
+            &data[offset..offset + length]
+        };
+        let annotation = match desc.get(1) {
+            Some(t) if descriptor_type_name(*t) == "UNKNOWN" => {
+                format!("UNKNOWN ({:#04x}) bLength {}", t, length)
+            }
+            Some(t) => format!("{} bLength {}", descriptor_type_name(*t), length),
+            None => format!("bLength {}", length),
+        };
+        let annotation = if desc.len() != length {
+            format!("{} (malformed, {} bytes remaining)", annotation, desc.len())
+        } else {
+            annotation
+        };
+
+        for (i, chunk) in desc.chunks(16).enumerate() {
+            let hex = hex_string(chunk);
+            if i == 0 {
+                ret.push(format!("{:04x}: {:<47}   {}", offset, hex, annotation));
+            } else {
+                ret.push(format!("{:04x}: {}", offset + i * 16, hex));
+            }
+        }
+
+        offset += desc.len();
+    }
+
+    ret
+}
+
+/// Print the [`descriptor_hexdump`] of `raw` below the block line `index` of `tree`, indented as a child in the tree or by `inset` in a list
+fn print_descriptor_hexdump(
+    raw: Option<&Vec<u8>>,
+    tree: &TreeData,
+    index: usize,
+    inset: u8,
+    settings: &PrintSettings,
+) {
+    let indent = if settings.tree {
+        generate_tree_data(tree, 0, index, settings).prefix
+    } else {
+        " ".repeat(((inset + 1) * LIST_INSET_SPACES) as usize)
+    };
+    let indent = match settings.colours.as_ref() {
+        Some(ct) => ct
+            .tree
+            .map_or(indent.normal(), |c| indent.color(c))
+            .to_string(),
+        None => indent.to_string(),
+    };
+
+    match raw {
+        Some(raw) => {
+            for line in descriptor_hexdump(raw) {
+                outputln!("{}  {}", indent, line);
+            }
+        }
+        None => log::debug!("No raw descriptors to dump, profile with raw descriptors"),
+    }
+}
+
+/// Name source followed by the manufacturer source in brackets if different: 'os (ids)'
+fn name_source_string(d: &Device) -> Option<String> {
+    match (d.name_source, d.manufacturer_source) {
+        (Some(n), Some(m)) if n != m => Some(format!("{} ({})", n, m)),
+        (Some(n), _) => Some(n.to_string()),
+        (None, Some(m)) => Some(format!("- ({})", m)),
+        (None, None) => None,
+    }
+}
+
+/// [`Device::platform_dev_path`] unless [`PrintSettings::from_dump`], when the platform the path is for is not known
+fn dev_path(d: &Device, settings: &PrintSettings) -> Option<String> {
+    if settings.from_dump {
+        None
+    } else {
+        d.platform_dev_path()
+    }
+}
+
+/// 'removable' or 'fixed' from [`Device::is_removable`], translated
+fn removable_string(d: &Device, settings: &PrintSettings) -> Option<Cow<'static, str>> {
+    d.is_removable().map(|r| {
+        if r {
+            settings
+                .translator
+                .translate("device-removable", "removable")
+        } else {
+            settings.translator.translate("device-fixed", "fixed")
+        }
+    })
+}
+
+fn quirks_string(quirks: &[Quirk]) -> String {
+    quirks
+        .iter()
+        .map(|q| q.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn batteries_string(batteries: &[Battery]) -> String {
+    batteries
+        .iter()
+        .map(|b| b.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn capacity_string(media: &[Media]) -> String {
+    media
+        .iter()
+        .map(|m| m.size.as_deref().unwrap_or("-"))
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+fn scsi_inquiry_string(media: &[Media]) -> String {
+    media
+        .iter()
+        .filter_map(|m| m.scsi_inquiry())
+        .unique()
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn media_string(media: &[Media]) -> String {
+    media
+        .iter()
+        .map(|m| m.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
 impl Block<InterfaceBlocks, Interface> for InterfaceBlocks {
     const INSET: u8 = 2;
 
@@ -1328,6 +2223,7 @@ impl Block<InterfaceBlocks, Interface> for InterfaceBlocks {
                 InterfaceBlocks::UidProtocol,
                 InterfaceBlocks::Name,
                 InterfaceBlocks::NumEndpoints,
+                InterfaceBlocks::AudioControls,
             ]
         } else {
             vec![
@@ -1355,7 +2251,7 @@ impl Block<InterfaceBlocks, Interface> for InterfaceBlocks {
         ]
     }
 
-    fn len(&self, d: &[&Interface]) -> usize {
+    fn len(&self, d: &[&Interface], settings: &PrintSettings) -> usize {
         match self {
             InterfaceBlocks::Name => d
                 .iter()
@@ -1364,47 +2260,105 @@ impl Block<InterfaceBlocks, Interface> for InterfaceBlocks {
                 .unwrap_or(0),
             InterfaceBlocks::BaseClass => d
                 .iter()
-                .map(|d| d.class.to_string().len())
+                .map(|d| settings.translator.translate_enum(&d.class).width())
                 .max()
                 .unwrap_or(0),
-            InterfaceBlocks::PortPath => d.iter().map(|d| d.path.len()).max().unwrap_or(0),
+            InterfaceBlocks::PortPath => d.iter().map(|d| d.path.width()).max().unwrap_or(0),
             InterfaceBlocks::SysPath => d
                 .iter()
-                .flat_map(|d| d.syspath.as_ref().map(|v| v.len()))
+                .flat_map(|d| d.syspath.as_ref().map(|v| v.width()))
                 .max()
                 .unwrap_or(0),
             InterfaceBlocks::Driver => d
                 .iter()
-                .flat_map(|d| d.driver.as_ref().map(|v| v.len()))
+                .flat_map(|d| d.driver.as_ref().map(|v| v.width()))
+                .max()
+                .unwrap_or(0),
+            InterfaceBlocks::Module => d
+                .iter()
+                .flat_map(|d| d.module.as_ref().map(|m| m.name.width()))
+                .max()
+                .unwrap_or(0),
+            InterfaceBlocks::ModuleParameters => d
+                .iter()
+                .flat_map(|d| d.module.as_ref().map(|m| m.parameters_string().width()))
                 .max()
                 .unwrap_or(0),
             InterfaceBlocks::UidClass => d
                 .iter()
-                .flat_map(|d| d.class_name().map(|s| s.len()))
+                .flat_map(|d| d.class_name().map(|s| s.width()))
                 .max()
                 .unwrap_or(0),
             InterfaceBlocks::UidSubClass => d
                 .iter()
-                .flat_map(|d| d.sub_class_name().map(|s| s.len()))
+                .flat_map(|d| d.sub_class_name().map(|s| s.width()))
                 .max()
                 .unwrap_or(0),
             InterfaceBlocks::UidProtocol => d
                 .iter()
-                .flat_map(|d| d.protocol_name().map(|s| s.len()))
+                .flat_map(|d| d.protocol_name().map(|s| s.width()))
                 .max()
                 .unwrap_or(0),
             InterfaceBlocks::Class => d
                 .iter()
-                .map(|d| d.fully_defined_class().to_string().len())
+                .map(|d| d.fully_defined_class().to_string().width())
+                .max()
+                .unwrap_or(0),
+            InterfaceBlocks::BluetoothName => d
+                .iter()
+                .flat_map(|d| d.bluetooth.as_ref().map(|b| b.name.width()))
+                .max()
+                .unwrap_or(0),
+            InterfaceBlocks::AudioFormat => d
+                .iter()
+                .flat_map(|d| d.audio_format_summary().map(|a| a.to_string().width()))
+                .max()
+                .unwrap_or(0),
+            InterfaceBlocks::VideoFormat => d
+                .iter()
+                .flat_map(|d| d.video_format_summary().map(|v| v.to_string().width()))
+                .max()
+                .unwrap_or(0),
+            InterfaceBlocks::AudioControls => d
+                .iter()
+                .map(|d| audio_controls_string(d).width())
+                .max()
+                .unwrap_or(0),
+            InterfaceBlocks::VendorFields => d
+                .iter()
+                .flat_map(|d| {
+                    d.vendor_fields
+                        .as_ref()
+                        .map(|v| vendor_fields_string(v).width())
+                })
+                .max()
+                .unwrap_or(0),
+            InterfaceBlocks::BluetoothAddress => d
+                .iter()
+                .flat_map(|d| {
+                    d.bluetooth
+                        .as_ref()
+                        .and_then(|b| b.address.as_ref().map(|s| s.width()))
+                })
+                .max()
+                .unwrap_or(0),
+            InterfaceBlocks::RawDescriptor => d
+                .iter()
+                .flat_map(|d| d.raw.as_ref().map(|r| hex_string(r).width()))
                 .max()
                 .unwrap_or(0),
             _ => self.block_length().len(),
         }
     }
 
-    fn generate_padding(d: &[&Interface]) -> HashMap<Self, usize> {
+    fn generate_padding(d: &[&Interface], settings: &PrintSettings) -> HashMap<Self, usize> {
         InterfaceBlocks::iter()
-            .map(|b| (b, cmp::max(b.heading().len(), b.len(d))))
+            .map(|b| {
+                (
+                    b,
+                    cmp::max(b.localized_heading(settings).width(), b.len(d, settings)),
+                )
+            })
             .collect()
     }
 
@@ -1426,10 +2380,18 @@ impl Block<InterfaceBlocks, Interface> for InterfaceBlocks {
             InterfaceBlocks::Protocol | InterfaceBlocks::UidProtocol => {
                 ct.protocol.map_or(s.normal(), |c| s.color(c))
             }
-            InterfaceBlocks::Driver => ct.driver.map_or(s.normal(), |c| s.color(c)),
-            InterfaceBlocks::AltSetting | InterfaceBlocks::NumEndpoints => {
-                ct.number.map_or(s.normal(), |c| s.color(c))
-            }
+            InterfaceBlocks::Driver
+            | InterfaceBlocks::Module
+            | InterfaceBlocks::ModuleParameters
+            | InterfaceBlocks::BluetoothName => ct.driver.map_or(s.normal(), |c| s.color(c)),
+            InterfaceBlocks::BluetoothAddress => ct.serial.map_or(s.normal(), |c| s.color(c)),
+            InterfaceBlocks::AudioFormat => ct.attributes.map_or(s.normal(), |c| s.color(c)),
+            InterfaceBlocks::AudioControls => ct.attributes.map_or(s.normal(), |c| s.color(c)),
+            InterfaceBlocks::VideoFormat => ct.attributes.map_or(s.normal(), |c| s.color(c)),
+            InterfaceBlocks::VendorFields => ct.attributes.map_or(s.normal(), |c| s.color(c)),
+            InterfaceBlocks::AltSetting
+            | InterfaceBlocks::NumEndpoints
+            | InterfaceBlocks::RawDescriptor => ct.number.map_or(s.normal(), |c| s.color(c)),
         }
     }
 
@@ -1442,26 +2404,45 @@ impl Block<InterfaceBlocks, Interface> for InterfaceBlocks {
         match self {
             InterfaceBlocks::Number => Some(format!("{:2}", interface.number)),
             InterfaceBlocks::Name => Some(match interface.name.as_ref() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             InterfaceBlocks::NumEndpoints => Some(format!("{:2}", interface.endpoints.len())),
             InterfaceBlocks::PortPath => Some(format!(
                 "{:pad$}",
-                interface.path,
+                WidthPad(&interface.path),
                 pad = pad.get(self).unwrap_or(&0)
             )),
             InterfaceBlocks::SysPath => Some(match interface.syspath.as_ref() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             InterfaceBlocks::Driver => Some(match interface.driver.as_ref() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            InterfaceBlocks::Module => Some(match interface.module.as_ref() {
+                Some(m) => format!(
+                    "{:pad$}",
+                    WidthPad(&m.name),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
+            InterfaceBlocks::ModuleParameters => Some(
+                match interface
+                    .module
+                    .as_ref()
+                    .map(|m| m.parameters_string())
+                    .filter(|p| !p.is_empty())
+                {
+                    Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
+                    None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+                },
+            ),
             InterfaceBlocks::BaseClass => Some(format!(
                 "{:pad$}",
-                interface.class.to_string(),
+                WidthPad(settings.translator.translate_enum(&interface.class)),
                 pad = pad.get(self).unwrap_or(&0)
             )),
             InterfaceBlocks::SubClass => Some(Self::format_base_u8(interface.sub_class, settings)),
@@ -1470,28 +2451,89 @@ impl Block<InterfaceBlocks, Interface> for InterfaceBlocks {
                 Some(Self::format_base_u8(interface.alt_setting, settings))
             }
             InterfaceBlocks::Icon => settings.icons.as_ref().map(|i| {
-                i.get_classifier_icon(&interface.class, interface.sub_class, interface.protocol)
+                i.get_encoded_classifier_icon(
+                    &interface.class,
+                    interface.sub_class,
+                    interface.protocol,
+                    &settings.encoding,
+                )
             }),
             InterfaceBlocks::UidClass => Some(match interface.class_name() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             InterfaceBlocks::UidSubClass => Some(match interface.sub_class_name() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             InterfaceBlocks::UidProtocol => Some(match interface.protocol_name() {
-                Some(v) => format!("{:pad$}", v, pad = pad.get(self).unwrap_or(&0)),
+                Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
                 None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
             }),
             InterfaceBlocks::Class => Some(format!(
                 "{:pad$}",
-                interface.fully_defined_class(),
+                WidthPad(interface.fully_defined_class()),
                 pad = pad.get(self).unwrap_or(&0)
             )),
             InterfaceBlocks::BaseValue => {
                 Some(Self::format_base_u8(interface.class.into(), settings))
             }
+            InterfaceBlocks::BluetoothName => Some(match interface.bluetooth.as_ref() {
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(&v.name),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            InterfaceBlocks::BluetoothAddress => Some(
+                match interface
+                    .bluetooth
+                    .as_ref()
+                    .and_then(|b| b.address.as_ref())
+                {
+                    Some(v) => format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0)),
+                    None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+                },
+            ),
+            InterfaceBlocks::AudioFormat => Some(match interface.audio_format_summary() {
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(v.to_string()),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            InterfaceBlocks::VideoFormat => Some(match interface.video_format_summary() {
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(v.to_string()),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            InterfaceBlocks::AudioControls => Some(match audio_controls_string(interface) {
+                v if !v.is_empty() => {
+                    format!("{:pad$}", WidthPad(v), pad = pad.get(self).unwrap_or(&0))
+                }
+                _ => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            InterfaceBlocks::VendorFields => Some(match interface.vendor_fields.as_ref() {
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(vendor_fields_string(v)),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
+            InterfaceBlocks::RawDescriptor => Some(match interface.raw.as_ref() {
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(hex_string(v)),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
         }
     }
 
@@ -1503,6 +2545,8 @@ impl Block<InterfaceBlocks, Interface> for InterfaceBlocks {
             InterfaceBlocks::PortPath => "PPath",
             InterfaceBlocks::SysPath => "SPath",
             InterfaceBlocks::Driver => "Driver",
+            InterfaceBlocks::Module => "Module",
+            InterfaceBlocks::ModuleParameters => "MParams",
             InterfaceBlocks::BaseClass => "BaseC",
             InterfaceBlocks::SubClass => "SubC",
             InterfaceBlocks::Protocol => "Pcol",
@@ -1512,14 +2556,21 @@ impl Block<InterfaceBlocks, Interface> for InterfaceBlocks {
             InterfaceBlocks::UidProtocol => "UidPc",
             InterfaceBlocks::Class => "Class",
             InterfaceBlocks::BaseValue => "CVal",
+            InterfaceBlocks::BluetoothName => "BtName",
+            InterfaceBlocks::BluetoothAddress => "BtAddr",
+            InterfaceBlocks::AudioFormat => "AFormat",
+            InterfaceBlocks::AudioControls => "AControls",
+            InterfaceBlocks::VideoFormat => "VFormat",
+            InterfaceBlocks::VendorFields => "Vendor",
+            InterfaceBlocks::RawDescriptor => "Raw",
             InterfaceBlocks::Icon => ICON_HEADING,
         }
     }
 
-    fn heading_padded(&self, pad: &HashMap<Self, usize>) -> String {
+    fn heading_padded(&self, pad: &HashMap<Self, usize>, settings: &PrintSettings) -> String {
         format!(
             "{:^pad$}",
-            self.heading(),
+            WidthPad(self.localized_heading(settings)),
             pad = pad.get(self).unwrap_or(&0)
         )
     }
@@ -1568,48 +2619,59 @@ impl Block<EndpointBlocks, Endpoint> for EndpointBlocks {
         }
     }
 
-    fn len(&self, d: &[&Endpoint]) -> usize {
+    fn len(&self, d: &[&Endpoint], _settings: &PrintSettings) -> usize {
         match self {
             EndpointBlocks::TransferType => d
                 .iter()
-                .map(|d| d.transfer_type.to_string().len())
+                .map(|d| d.transfer_type.to_string().width())
                 .max()
                 .unwrap_or(0),
             EndpointBlocks::SyncType => d
                 .iter()
-                .map(|d| d.sync_type.to_string().len())
+                .map(|d| d.sync_type.to_string().width())
                 .max()
                 .unwrap_or(0),
             EndpointBlocks::UsageType => d
                 .iter()
-                .map(|d| d.usage_type.to_string().len())
+                .map(|d| d.usage_type.to_string().width())
                 .max()
                 .unwrap_or(0),
             EndpointBlocks::Direction => d
                 .iter()
-                .map(|d| d.address.direction.to_string().len())
+                .map(|d| d.address.direction.to_string().width())
                 .max()
                 .unwrap_or(0),
             EndpointBlocks::MaxPacketSize => d
                 .iter()
-                .map(|d| d.max_packet_string().len())
+                .map(|d| d.max_packet_string().width())
+                .max()
+                .unwrap_or(0),
+            EndpointBlocks::RawDescriptor => d
+                .iter()
+                .flat_map(|d| d.raw.as_ref().map(|r| hex_string(r).width()))
                 .max()
                 .unwrap_or(0),
             _ => self.block_length().len(),
         }
     }
 
-    fn generate_padding(d: &[&Endpoint]) -> HashMap<Self, usize> {
+    fn generate_padding(d: &[&Endpoint], settings: &PrintSettings) -> HashMap<Self, usize> {
         EndpointBlocks::iter()
-            .map(|b| (b, cmp::max(b.heading().len(), b.len(d))))
+            .map(|b| {
+                (
+                    b,
+                    cmp::max(b.localized_heading(settings).width(), b.len(d, settings)),
+                )
+            })
             .collect()
     }
 
     fn colour(&self, s: &str, ct: &colour::ColourTheme) -> ColoredString {
         match self {
-            EndpointBlocks::Number | EndpointBlocks::Interval | EndpointBlocks::MaxPacketSize => {
-                ct.number.map_or(s.normal(), |c| s.color(c))
-            }
+            EndpointBlocks::Number
+            | EndpointBlocks::Interval
+            | EndpointBlocks::MaxPacketSize
+            | EndpointBlocks::RawDescriptor => ct.number.map_or(s.normal(), |c| s.color(c)),
             EndpointBlocks::Direction
             | EndpointBlocks::UsageType
             | EndpointBlocks::TransferType
@@ -1628,29 +2690,37 @@ impl Block<EndpointBlocks, Endpoint> for EndpointBlocks {
             EndpointBlocks::Interval => Some(format!("{:2}", end.interval)),
             EndpointBlocks::MaxPacketSize => Some(format!(
                 "{:pad$}",
-                end.max_packet_string(),
+                WidthPad(end.max_packet_string()),
                 pad = pad.get(self).unwrap_or(&0)
             )),
             EndpointBlocks::Direction => Some(format!(
                 "{:pad$}",
-                end.address.direction.to_string(),
+                WidthPad(end.address.direction.to_string()),
                 pad = pad.get(self).unwrap_or(&0)
             )),
             EndpointBlocks::TransferType => Some(format!(
                 "{:pad$}",
-                end.transfer_type.to_string(),
+                WidthPad(end.transfer_type.to_string()),
                 pad = pad.get(self).unwrap_or(&0)
             )),
             EndpointBlocks::SyncType => Some(format!(
                 "{:pad$}",
-                end.sync_type.to_string(),
+                WidthPad(end.sync_type.to_string()),
                 pad = pad.get(self).unwrap_or(&0)
             )),
             EndpointBlocks::UsageType => Some(format!(
                 "{:pad$}",
-                end.usage_type.to_string(),
+                WidthPad(end.usage_type.to_string()),
                 pad = pad.get(self).unwrap_or(&0)
             )),
+            EndpointBlocks::RawDescriptor => Some(match end.raw.as_ref() {
+                Some(v) => format!(
+                    "{:pad$}",
+                    WidthPad(hex_string(v)),
+                    pad = pad.get(self).unwrap_or(&0)
+                ),
+                None => format!("{:pad$}", "-", pad = pad.get(self).unwrap_or(&0)),
+            }),
         }
     }
 
@@ -1663,13 +2733,14 @@ impl Block<EndpointBlocks, Endpoint> for EndpointBlocks {
             EndpointBlocks::TransferType => "TranT",
             EndpointBlocks::SyncType => "SyncT",
             EndpointBlocks::UsageType => "UsgeT",
+            EndpointBlocks::RawDescriptor => "Raw",
         }
     }
 
-    fn heading_padded(&self, pad: &HashMap<Self, usize>) -> String {
+    fn heading_padded(&self, pad: &HashMap<Self, usize>, settings: &PrintSettings) -> String {
         format!(
             "{:^pad$}",
-            self.heading(),
+            WidthPad(self.localized_heading(settings)),
             pad = pad.get(self).unwrap_or(&0)
         )
     }
@@ -1760,6 +2831,8 @@ pub enum Group {
     NoGroup,
     /// Group into buses with bus info as heading - like a flat tree
     Bus,
+    /// Group by device base class with class as heading
+    Class,
 }
 
 /// Options for [`PrintSettings`] mask_serials
@@ -1773,6 +2846,95 @@ pub enum MaskSerial {
     Scramble,
     /// Mask by replacing length with random chars
     Replace,
+    /// Replace with a hash of the serial and salt so a device has the same mask in every dump; the hash is not cryptographic so set a private salt when sharing
+    Hash,
+}
+
+impl MaskSerial {
+    /// Mask `serial`; `salt` is hashed with the serial for [`MaskSerial::Hash`]
+    ///
+    /// ```
+    /// use cyme::display::MaskSerial;
+    /// let masked = MaskSerial::Hash.mask("0123456789", "salt");
+    /// assert_eq!(masked, MaskSerial::Hash.mask("0123456789", "salt"));
+    /// assert_ne!(masked, MaskSerial::Hash.mask("0123456789", "pepper"));
+    /// assert_eq!(MaskSerial::Hide.mask("0123", ""), "****");
+    /// ```
+    pub fn mask(&self, serial: &str, salt: &str) -> String {
+        match self {
+            MaskSerial::Hide => serial.chars().map(|_| '*').collect::<String>(),
+            MaskSerial::Scramble => serial
+                .chars()
+                .map(|_| {
+                    serial
+                        .chars()
+                        .choose(&mut rand::thread_rng())
+                        .unwrap_or('*')
+                })
+                .collect::<String>(),
+            MaskSerial::Replace => rand::thread_rng()
+                .sample_iter(Alphanumeric)
+                .take(serial.chars().count())
+                .map(char::from)
+                .collect::<String>()
+                .to_uppercase(),
+            MaskSerial::Hash => format!("{:016X}", stable_hash(salt, serial)),
+        }
+    }
+
+    /// Mask the device specific half of MAC or Bluetooth `address`, keeping the OUI and separators so the organisation is still known
+    ///
+    /// The device half is only 24 bits, so every value can be hashed to find the one matching a [`MaskSerial::Hash`] mask unless the `salt` is secret; it is hidden as [`MaskSerial::Hide`] if `salt` is empty.
+    ///
+    /// ```
+    /// use cyme::display::MaskSerial;
+    /// assert_eq!(MaskSerial::Hide.mask_address("00:1A:7D:DA:71:13", ""), "00:1A:7D:**:**:**");
+    /// assert_eq!(MaskSerial::Hash.mask_address("00:1A:7D:DA:71:13", ""), "00:1A:7D:**:**:**");
+    /// let masked = MaskSerial::Hash.mask_address("00:1A:7D:DA:71:13", "secret");
+    /// assert!(masked.starts_with("00:1A:7D:") && !masked.contains('*'));
+    /// ```
+    pub fn mask_address(&self, address: &str, salt: &str) -> String {
+        if *self == MaskSerial::Hash && salt.is_empty() {
+            return MaskSerial::Hide.mask_address(address, salt);
+        }
+        let digits: String = address.chars().filter(|c| c.is_ascii_hexdigit()).collect();
+        if digits.len() != 12 {
+            return self.mask(address, salt);
+        }
+        let masked = match self {
+            MaskSerial::Hash => format!(
+                "{:06X}",
+                stable_hash(salt, &digits.to_uppercase()) & 0xff_ffff
+            ),
+            _ => self.mask(&digits[6..], salt),
+        };
+        let mut masked = masked.chars();
+        let mut index = 0;
+        address
+            .chars()
+            .map(|c| {
+                if !c.is_ascii_hexdigit() {
+                    return c;
+                }
+                index += 1;
+                if index > 6 {
+                    masked.next().unwrap_or('*')
+                } else {
+                    c
+                }
+            })
+            .collect()
+    }
+}
+
+/// FNV-1a hash of `salt` then `value`; stable across builds and platforms unlike the std hasher
+fn stable_hash(salt: &str, value: &str) -> u64 {
+    salt.bytes()
+        .chain(std::iter::once(0))
+        .chain(value.bytes())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+        })
 }
 
 /// Passed to printing functions allows default args
@@ -1800,14 +2962,20 @@ pub struct PrintSettings {
     pub more: bool,
     /// Print as json
     pub json: bool,
+    /// Only serialize these [`DeviceBlocks`] fields for each device when printing json
+    pub json_fields: Option<Vec<DeviceBlocks>>,
     /// Character encoding to use
     pub encoding: Encoding,
     /// Scramble serial numbers, useful if sharing sensitive device dumps
     pub mask_serials: Option<MaskSerial>,
+    /// Salt for [`MaskSerial::Hash`]
+    pub mask_salt: Option<String>,
     /// [`DeviceBlocks`] to use for printing
     pub device_blocks: Option<Vec<DeviceBlocks>>,
     /// [`BusBlocks`] to use for printing
     pub bus_blocks: Option<Vec<BusBlocks>>,
+    /// Additional [`DeviceBlocks`] for devices of a [`BaseClass`] - see [`class_device_blocks`]
+    pub class_blocks: Option<HashMap<BaseClass, Vec<DeviceBlocks>>>,
     /// [`ConfigurationBlocks`] to use for printing
     pub config_blocks: Option<Vec<ConfigurationBlocks>>,
     /// [`InterfaceBlocks`] to use for printing
@@ -1826,61 +2994,289 @@ pub struct PrintSettings {
     pub terminal_size: Option<(Width, Height)>,
     /// When to print icon blocks
     pub icon_when: IconWhen,
+    /// Print the raw configuration, interface and endpoint descriptors as an annotated hexdump below each
+    pub dump_descriptors: bool,
+    /// Print only the configuration currently set on the device when it is known
+    pub active_config_only: bool,
+    /// Collapse identical sibling devices into a single line with a 'xN' count when not verbose
+    pub compact: bool,
+    /// Maximum depth of the tree to print: 0 for buses only, 1 for devices directly on the bus and so on
+    pub max_depth: Option<usize>,
+    /// Mark devices matching this filter rather than removing those that don't
+    pub highlight: Option<Filter>,
+    /// Port paths of devices with a duplicate or missing serial number, from [`crate::profiler::serials::SerialReport::port_paths`]; their serial is coloured with the `warning` colour
+    pub serial_warnings: Option<HashSet<String>>,
+    /// Alternate the background of list rows with the `stripe` colour
+    pub stripe: bool,
+    /// Separator between list blocks rather than a space
+    pub separator: Option<String>,
+    /// Print the list as a table with borders around each block
+    pub grid: bool,
+    /// Print lsusb style descriptor sections inside the tree below each verbose device rather than configuration, interface and endpoint blocks
+    pub inline_descriptors: bool,
+    /// Print as USBView style XML; see [`xml::to_xml`]
+    pub xml: bool,
+    /// Render with this [`template::Template`] rather than blocks or json
+    pub template: Option<template::Template>,
+    /// Units of speeds; each output has its own default if `None`: 'decimal' for blocks and 'lsusb' for the lsusb tree
+    pub speed_units: Option<SpeedUnits>,
+    /// Translations of headings and values; see [`i18n::Translator::new`]
+    pub translator: i18n::Translator,
+    /// Devices are from a dump such as `--from-json` rather than profiled on this host, so [`DeviceBlocks::DevPath`] is not shown since it depends on the platform of the dump
+    pub from_dump: bool,
 }
 
-/// Converts a HashSet of [`ConfigAttributes`] a String of nerd icons
-fn attributes_to_icons(attributes: &Vec<ConfigAttributes>, settings: &PrintSettings) -> String {
-    let mut icon_strs = Vec::new();
-    if settings.icons.is_some() {
-        for a in attributes {
-            match a {
-                ConfigAttributes::SelfPowered => icon_strs.push("\u{f06a5}"), // 󰚥
-                ConfigAttributes::RemoteWakeup => icon_strs.push("\u{f0155}"), // 󰅕
-                ConfigAttributes::BatteryPowered => icon_strs.push("\u{f244}"), // 
+/// `blocks` followed by the [`PrintSettings::class_blocks`] of each class `device` or one of its interfaces has, without duplicates
+pub fn class_device_blocks(
+    device: &Device,
+    blocks: &[DeviceBlocks],
+    settings: &PrintSettings,
+) -> Vec<DeviceBlocks> {
+    let mut ret = blocks.to_vec();
+    let class_blocks = class_blocks(settings);
+    for (class, extra) in class_blocks
+        .iter()
+        .flat_map(|c| c.iter())
+        .sorted_by_key(|(c, _)| u8::from(**c))
+    {
+        if device.class.as_ref() == Some(class) || device.has_interface_class(class) {
+            for b in extra {
+                if !ret.contains(b) {
+                    ret.push(*b);
+                }
             }
         }
     }
-    icon_strs.join(" ")
+    ret
 }
 
-/// Truncates and appends '...' to show string has been truncated
-///
-/// `len` is length of resulting String, with '...' so original `s` content will be len - 3
-///
-/// If `len` is less than 3, `s` truncated to this length
-///
-/// ```
-/// use cyme::display::truncate_string;
-/// let mut string = String::from("Hello world");
-/// truncate_string(&mut string, 8);
-/// assert_eq!(string, "Hello...");
-/// // emoji are 2 bytes so will be truncated correctly on char boundary
-/// let mut string = String::from("Hell😅 world");
-/// truncate_string(&mut string, 8);
-/// assert_eq!(string, "Hell😅...");
-/// let mut string = String::from("bl");
-/// truncate_string(&mut string, 2);
-/// assert_eq!(string, "bl");
-/// // don't shorten if already length
-/// let mut string = String::from("blah");
+/// Whether `k` is in `blocks` or any [`PrintSettings::class_blocks`], so padding is kept for it
+fn is_printed_block(k: &DeviceBlocks, blocks: &[DeviceBlocks], settings: &PrintSettings) -> bool {
+    blocks.contains(k)
+        || class_blocks(settings)
+            .iter()
+            .flat_map(|c| c.values())
+            .any(|b| b.contains(k))
+}
+
+/// Class blocks used when the config has none and devices are shown at max verbosity with the default blocks: capacity and SCSI inquiry of mass storage devices
+pub fn default_class_blocks() -> HashMap<BaseClass, Vec<DeviceBlocks>> {
+    HashMap::from([
+        (
+            BaseClass::MassStorage,
+            vec![DeviceBlocks::Capacity, DeviceBlocks::ScsiInquiry],
+        ),
+        (BaseClass::Video, vec![DeviceBlocks::VideoFormat]),
+    ])
+}
+
+/// [`PrintSettings::class_blocks`], or the [`default_class_blocks`] if not set and printing the verbose default blocks
+fn class_blocks(
+    settings: &PrintSettings,
+) -> Option<Cow<'_, HashMap<BaseClass, Vec<DeviceBlocks>>>> {
+    match settings.class_blocks.as_ref() {
+        Some(c) => Some(Cow::Borrowed(c)),
+        None if settings.device_blocks.is_none()
+            && (settings.verbosity >= MAX_VERBOSITY || settings.more) =>
+        {
+            Some(Cow::Owned(default_class_blocks()))
+        }
+        None => None,
+    }
+}
+
+/// [`icon::Icon::TreeInterfaceUnclaimed`] marker for an interface no driver is bound to in the warning colour
+fn unclaimed_icon(settings: &PrintSettings) -> String {
+    let marker = settings.icons.as_ref().map_or(
+        icon::get_default_tree_icon(&icon::Icon::TreeInterfaceUnclaimed, &settings.encoding),
+        |i| i.get_tree_icon(&icon::Icon::TreeInterfaceUnclaimed, &settings.encoding),
+    );
+    match settings.colours.as_ref().and_then(|ct| ct.warning) {
+        Some(c) => marker.color(c).bold().to_string(),
+        None => marker,
+    }
+}
+
+/// Whether `device` matches the `highlight` filter of `settings`
+fn is_highlighted(device: &Device, settings: &PrintSettings) -> bool {
+    settings
+        .highlight
+        .as_ref()
+        .is_some_and(|f| f.is_match(device))
+}
+
+/// [`icon::Icon::TreeDeviceHighlight`] marker for a highlighted device in the highlight colour
+fn highlight_icon(settings: &PrintSettings) -> String {
+    let marker = settings.icons.as_ref().map_or(
+        icon::get_default_tree_icon(&icon::Icon::TreeDeviceHighlight, &settings.encoding),
+        |i| i.get_tree_icon(&icon::Icon::TreeDeviceHighlight, &settings.encoding),
+    );
+    match settings.colours.as_ref().and_then(|ct| ct.highlight) {
+        Some(c) => marker.color(c).bold().to_string(),
+        None => marker,
+    }
+}
+
+/// Whether `a` and `b` are identical siblings which can be compacted: same parent, VID:PID and name with no devices attached
+fn is_identical_sibling(a: &Device, b: &Device) -> bool {
+    !a.has_devices()
+        && !b.has_devices()
+        && a.vendor_id == b.vendor_id
+        && a.product_id == b.product_id
+        && a.name == b.name
+        && a.location_id.path().parent() == b.location_id.path().parent()
+}
+
+/// Groups consecutive identical siblings in `devices` with `settings.compact` when not verbose; each device to print with the number it represents
+fn compact_siblings<'a, I: IntoIterator<Item = &'a Device>>(
+    devices: I,
+    settings: &PrintSettings,
+) -> Vec<(&'a Device, usize)> {
+    let compact = settings.compact && settings.verbosity == 0;
+    let mut ret: Vec<(&Device, usize)> = Vec::new();
+    for device in devices {
+        match ret.last_mut() {
+            Some((last, count))
+                if compact
+                    && is_identical_sibling(last, device)
+                    && is_highlighted(last, settings) == is_highlighted(device, settings) =>
+            {
+                *count += 1
+            }
+            _ => ret.push((device, 1)),
+        }
+    }
+    ret
+}
+
+/// Whether devices below a node at tree `depth` are hidden by `max_depth`
+fn depth_limited(depth: usize, settings: &PrintSettings) -> bool {
+    settings.tree && matches!(settings.max_depth, Some(m) if depth >= m)
+}
+
+/// ' xN' suffix for a line representing `count` compacted devices and ' (+N hidden)' for `hidden` devices below it; empty if neither
+fn line_suffix(count: usize, hidden: usize, settings: &PrintSettings) -> String {
+    let mut suffix = String::new();
+    if count > 1 {
+        suffix.push_str(&format!(" x{}", count));
+    }
+    if hidden > 0 {
+        suffix.push_str(&format!(" (+{} hidden)", hidden));
+    }
+    if suffix.is_empty() {
+        return suffix;
+    }
+    match settings.colours.as_ref().and_then(|ct| ct.number) {
+        Some(c) => suffix.color(c).to_string(),
+        None => suffix,
+    }
+}
+
+/// The [`Configuration`]s of `extra` to print with `settings`; only the active one with `active_config_only` if known
+fn printed_configurations<'a>(
+    extra: &'a DeviceExtra,
+    settings: &PrintSettings,
+) -> &'a [Configuration] {
+    match extra.active_configuration() {
+        Some(active) if settings.active_config_only => std::slice::from_ref(active),
+        _ => &extra.configurations,
+    }
+}
+
+/// Converts a HashSet of [`ConfigAttributes`] a String of nerd icons
+fn attributes_to_icons(attributes: &Vec<ConfigAttributes>, settings: &PrintSettings) -> String {
+    let mut icon_strs = Vec::new();
+    if settings.icons.is_some() {
+        for a in attributes {
+            match a {
+                ConfigAttributes::SelfPowered => icon_strs.push("\u{f06a5}"), // 󰚥
+                ConfigAttributes::RemoteWakeup => icon_strs.push("\u{f0155}"), // 󰅕
+                ConfigAttributes::BatteryPowered => icon_strs.push("\u{f244}"), // 
+            }
+        }
+    }
+    icon_strs.join(" ")
+}
+
+/// Display of `T` padded to the format width by its display width rather than its number of chars, so columns containing East Asian wide characters or combining marks align
+///
+/// The alignment and fill of the format are kept; `{:^pad$}` centres as with a `str`.
+///
+/// ```
+/// use cyme::display::WidthPad;
+/// // each CJK character is two columns wide
+/// assert_eq!(format!("[{:6}]", WidthPad("日本")), "[日本  ]");
+/// assert_eq!(format!("[{:>6}]", WidthPad("abc")), "[   abc]");
+/// // combining acute accent takes no columns
+/// assert_eq!(format!("[{:^4}]", WidthPad("e\u{301}")), "[ e\u{301}  ]");
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct WidthPad<T>(pub T);
+
+impl<T: std::fmt::Display> std::fmt::Display for WidthPad<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = self.0.to_string();
+        let padding = f.width().unwrap_or(0).saturating_sub(s.width());
+        let (left, right) = match f.align() {
+            Some(std::fmt::Alignment::Right) => (padding, 0),
+            Some(std::fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+            _ => (0, padding),
+        };
+        let fill = f.fill().to_string();
+        write!(f, "{}{}{}", fill.repeat(left), s, fill.repeat(right))
+    }
+}
+
+/// Truncates and appends '...' to show string has been truncated
+///
+/// `len` is the display width of the resulting String, with '...' so original `s` content will be len - 3 columns. Widths are those of [`UnicodeWidthStr`] so East Asian wide characters take two columns and combining marks none; a wide character which would straddle the limit is replaced by a space so the result is exactly `len` wide.
+///
+/// If `len` is less than 3, `s` truncated to this length
+///
+/// ```
+/// use cyme::display::truncate_string;
+/// let mut string = String::from("Hello world");
+/// truncate_string(&mut string, 8);
+/// assert_eq!(string, "Hello...");
+/// // emoji are 2 bytes so will be truncated correctly on char boundary
+/// let mut string = String::from("Hell😅 world");
+/// truncate_string(&mut string, 9);
+/// assert_eq!(string, "Hell😅...");
+/// let mut string = String::from("bl");
+/// truncate_string(&mut string, 2);
+/// assert_eq!(string, "bl");
+/// // don't shorten if already length
+/// let mut string = String::from("blah");
 /// truncate_string(&mut string, 4);
 /// assert_eq!(string, "blah");
 /// // just over length
 /// let mut string = String::from("blahx");
 /// truncate_string(&mut string, 4);
 /// assert_eq!(string, "b...");
+/// // wide characters count as two columns
+/// let mut string = String::from("日本語のデバイス");
+/// truncate_string(&mut string, 8);
+/// assert_eq!(string, "日本 ...");
+/// // combining marks stay with their character
+/// let mut string = String::from("Cafe\u{301} Webcam");
+/// truncate_string(&mut string, 7);
+/// assert_eq!(string, "Cafe\u{301}...");
 /// ```
 pub fn truncate_string(s: &mut String, len: usize) {
     // if already less than or equal to len, or len is less than 3, return
     if s.width() <= len || len <= 3 {
         return;
     }
-    // use char_indices to find last char boundary before len - 3
-    // not s.len() as this is the byte length and utf-8 chars can be multiple bytes
-    if let Some((i, _)) = s.char_indices().nth(len - 3) {
-        s.truncate(i);
-        s.push_str("...");
-    }
+    let budget = len - 3;
+    // byte index of the first char which takes the str over the budget; measured as a str rather than summing chars since sequences such as emoji presentation are wider than their chars; zero width chars after the last kept char are kept with it
+    let end = s
+        .char_indices()
+        .find(|(i, c)| s[..i + c.len_utf8()].width() > budget)
+        .map_or(s.len(), |(i, _)| i);
+    let kept = s[..end].width();
+    s.truncate(end);
+    s.push_str(&" ".repeat(budget - kept));
+    s.push_str("...");
 }
 
 /// Finds the maximum string size to truncate variable fields
@@ -2015,7 +3411,10 @@ pub fn render_value<B: Eq + Hash, T>(
                 }
             }
             match &settings.colours {
-                Some(c) => ret.push(format!("{}", b.colour(&string, c))),
+                Some(c) => match c.warning {
+                    Some(w) if b.highlight(d, settings) => ret.push(string.color(w).to_string()),
+                    _ => ret.push(format!("{}", b.colour(&string, c))),
+                },
                 None => ret.push(string.to_string()),
             };
         }
@@ -2029,11 +3428,12 @@ pub fn render_heading<B: Eq + Hash, T>(
     blocks: &[impl Block<B, T>],
     pad: &HashMap<B, usize>,
     max_string_length: Option<usize>,
+    settings: &PrintSettings,
 ) -> Vec<String> {
     let mut ret = Vec::new();
 
     for b in blocks {
-        let mut string = b.heading_padded(pad);
+        let mut string = b.heading_padded(pad, settings);
         if b.value_is_variable_length() {
             if let Some(ml) = max_string_length {
                 truncate_string(&mut string, ml)
@@ -2180,11 +3580,11 @@ pub fn print_flattened_devices(devices: &[&Device], settings: &PrintSettings) {
     }
 
     let mut pad = if !settings.no_padding {
-        DeviceBlocks::generate_padding(devices)
+        DeviceBlocks::generate_padding(devices, settings)
     } else {
         HashMap::new()
     };
-    pad.retain(|k, _| db.contains(k));
+    pad.retain(|k, _| is_printed_block(k, &db, settings));
     log::trace!("Flattened devices padding {:?}", pad);
 
     let max_variable_string_len: Option<usize> = if settings.auto_width {
@@ -2205,92 +3605,1039 @@ pub fn print_flattened_devices(devices: &[&Device], settings: &PrintSettings) {
         }
     }
 
-    if settings.headings {
-        let heading = render_heading(&db, &pad, max_variable_string_len).join(" ");
-        println!("{}", heading.bold().underline());
+    if settings.grid {
+        print_grid(devices, &db, &pad, settings, max_variable_string_len);
+        return;
+    }
+
+    let separator = settings.separator.as_deref().unwrap_or(" ");
+
+    if settings.headings {
+        let heading = render_heading(&db, &pad, max_variable_string_len, settings).join(separator);
+        outputln!("{}", style_heading(&heading, settings));
+    }
+
+    for (i, &(device, count)) in compact_siblings(devices.iter().copied(), settings)
+        .iter()
+        .enumerate()
+    {
+        let row = format!(
+            "{}{}{}",
+            render_value(
+                device,
+                &class_device_blocks(device, &db, settings),
+                &pad,
+                settings,
+                max_variable_string_len
+            )
+            .join(separator),
+            line_suffix(count, 0, settings),
+            if is_highlighted(device, settings) {
+                format!(" {}", highlight_icon(settings))
+            } else {
+                String::new()
+            }
+        );
+        outputln!("{}", stripe_row(row, i, settings));
+        // print the configurations
+        if let Some(extra) = device.extra.as_ref() {
+            if settings.verbosity >= 1 {
+                let blocks = generate_extra_blocks(extra, settings);
+
+                // pass branch length as number of configurations for this device plus devices still to print
+                print_configurations(
+                    printed_configurations(extra, settings),
+                    (&blocks.0, &blocks.1, &blocks.2),
+                    settings,
+                    &generate_tree_data(
+                        &Default::default(),
+                        printed_configurations(extra, settings).len()
+                            + device.devices.as_ref().map_or(0, |d| d.len()),
+                        i,
+                        settings,
+                    ),
+                );
+            }
+        } else if settings.verbosity >= 1 {
+            log::warn!(
+                "Unable to print verbose information for {} because libusb extra data is missing",
+                device
+            )
+        }
+    }
+}
+
+/// `heading` in bold and underlined when `settings` has colours, otherwise plain so output without colours is the same regardless of the terminal
+fn style_heading(heading: &str, settings: &PrintSettings) -> String {
+    match settings.colours {
+        Some(_) => heading.bold().underline().to_string(),
+        None => heading.to_string(),
+    }
+}
+
+/// `s` in bold when `settings` has colours; see [`style_heading`]
+fn style_bold(s: &str, settings: &PrintSettings) -> String {
+    match settings.colours {
+        Some(_) => s.bold().to_string(),
+        None => s.to_string(),
+    }
+}
+
+/// Applies the `stripe` background colour to odd `index` rows when `settings.stripe`
+fn stripe_row(row: String, index: usize, settings: &PrintSettings) -> String {
+    match settings.colours.as_ref().and_then(|c| c.stripe) {
+        Some(bg) if settings.stripe && index % 2 == 1 => row.on_color(bg).to_string(),
+        _ => row,
+    }
+}
+
+/// Printed width of `s` without any ANSI colour sequences
+fn display_width(s: &str) -> usize {
+    let mut writer = StripAnsi::new(Vec::new());
+    // writing to a Vec cannot fail
+    let _ = writer.write_all(s.as_bytes());
+    String::from_utf8_lossy(&writer.into_inner()).width()
+}
+
+/// Prints flattened `devices` as a table of `blocks` with a border around each cell
+///
+/// Borders are box drawing characters or ASCII with [`Encoding::Ascii`]. The heading row is always printed and configurations are not since they do not fit the columns.
+fn print_grid(
+    devices: &[&Device],
+    blocks: &[DeviceBlocks],
+    pad: &HashMap<DeviceBlocks, usize>,
+    settings: &PrintSettings,
+    max_variable_string_len: Option<usize>,
+) {
+    if settings.verbosity >= 1 {
+        log::warn!("Verbose configurations are not printed with --grid");
+    }
+
+    let headings = render_heading(blocks, pad, max_variable_string_len, settings);
+    // render each block on its own so a block without a value is an empty cell rather than shifting the columns
+    let rows: Vec<(&Device, Vec<String>)> = compact_siblings(devices.iter().copied(), settings)
+        .into_iter()
+        .map(|(device, count)| {
+            let mut cells: Vec<String> = blocks
+                .iter()
+                .map(|b| {
+                    render_value(
+                        device,
+                        std::slice::from_ref(b),
+                        pad,
+                        settings,
+                        max_variable_string_len,
+                    )
+                    .pop()
+                    .unwrap_or_default()
+                })
+                .collect();
+            if let Some(last) = cells.last_mut() {
+                last.push_str(&line_suffix(count, 0, settings));
+            }
+            (device, cells)
+        })
+        .collect();
+
+    let widths: Vec<usize> = headings
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            rows.iter()
+                .filter_map(|(_, cells)| cells.get(i))
+                .map(|c| display_width(c))
+                .chain(std::iter::once(display_width(h)))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let [h, v, tl, tm, tr, ml, mm, mr, bl, bm, br] = match settings.encoding {
+        Encoding::Ascii => ["-", "|", "+", "+", "+", "+", "+", "+", "+", "+", "+"],
+        _ => ["─", "│", "┌", "┬", "┐", "├", "┼", "┤", "└", "┴", "┘"],
+    };
+    let border = |s: &str| match settings.colours.as_ref().and_then(|c| c.tree) {
+        Some(c) => s.color(c).to_string(),
+        None => s.to_string(),
+    };
+    let rule = |left: &str, mid: &str, right: &str| {
+        border(&format!(
+            "{}{}{}",
+            left,
+            widths
+                .iter()
+                .map(|w| h.repeat(w + 2))
+                .collect::<Vec<_>>()
+                .join(mid),
+            right
+        ))
+    };
+    let line = |cells: &[String]| {
+        let cells: Vec<String> = widths
+            .iter()
+            .zip(cells)
+            .map(|(w, c)| format!(" {}{} ", c, " ".repeat(w - display_width(c))))
+            .collect();
+        format!("{}{}{}", border(v), cells.join(&border(v)), border(v))
+    };
+
+    outputln!("{}", rule(tl, tm, tr));
+    let headings: Vec<String> = headings.iter().map(|h| style_bold(h, settings)).collect();
+    outputln!("{}", line(&headings));
+    outputln!("{}", rule(ml, mm, mr));
+    for (i, (device, cells)) in rows.iter().enumerate() {
+        let row = line(cells);
+        outputln!(
+            "{}{}",
+            stripe_row(row, i, settings),
+            if is_highlighted(device, settings) {
+                format!(" {}", highlight_icon(settings))
+            } else {
+                String::new()
+            }
+        );
+    }
+    outputln!("{}", rule(bl, bm, br));
+}
+
+/// A way of printing a reference flattened [`SystemProfile`] rather than hard flatten
+///
+/// Prints each `&Bus` and tuple pair `Vec<&Device>`
+pub fn print_bus_grouped(bus_devices: Vec<(&Bus, Vec<&Device>)>, settings: &PrintSettings) {
+    let bb = settings
+        .bus_blocks
+        .to_owned()
+        .unwrap_or(Block::<BusBlocks, Bus>::default_blocks(
+            settings.verbosity >= MAX_VERBOSITY || settings.more,
+        ));
+    let mut pad: HashMap<BusBlocks, usize> = if !settings.no_padding {
+        let buses: Vec<&Bus> = bus_devices.iter().map(|bd| bd.0).collect();
+        BusBlocks::generate_padding(&buses, settings)
+    } else {
+        HashMap::new()
+    };
+    pad.retain(|k, _| bb.contains(k));
+
+    let max_variable_string_len: Option<usize> = if settings.auto_width {
+        let mut variable_lens = pad.clone();
+        variable_lens.retain(|k, _| k.value_is_variable_length());
+        auto_max_string_len(&bb, 0, &variable_lens.into_values().collect(), settings)
+            .or(settings.max_variable_string_len)
+    } else {
+        settings.max_variable_string_len
+    };
+
+    // if there is a max variable length, adjust padding to this if current > it
+    if let Some(ml) = max_variable_string_len.as_ref() {
+        for (k, v) in pad.iter_mut() {
+            if k.value_is_variable_length() {
+                *v = cmp::min(*v, *ml);
+            }
+        }
+    }
+
+    for (bus, devices) in bus_devices {
+        if settings.headings {
+            let heading = render_heading(&bb, &pad, max_variable_string_len, settings).join(" ");
+            outputln!("{}", style_heading(&heading, settings));
+        }
+        outputln!(
+            "{}",
+            render_value(bus, &bb, &pad, settings, max_variable_string_len).join(" ")
+        );
+        print_flattened_devices(&devices, settings);
+        // new line for each group
+        outputln!();
+    }
+}
+
+/// Groups `devices` by [`BaseClass`] in order of class code, devices without a class are last
+pub fn group_devices_by_class<'a>(
+    devices: &[&'a Device],
+) -> Vec<(Option<BaseClass>, Vec<&'a Device>)> {
+    devices
+        .iter()
+        .copied()
+        .into_group_map_by(|d| d.class)
+        .into_iter()
+        .sorted_by_key(|(c, _)| c.map_or(u16::MAX, |c| u8::from(c) as u16))
+        .collect()
+}
+
+/// Prints each [`BaseClass`] heading and tuple pair `Vec<&Device>` - see [`group_devices_by_class`]
+pub fn print_class_grouped(
+    class_devices: Vec<(Option<BaseClass>, Vec<&Device>)>,
+    settings: &PrintSettings,
+) {
+    for (class, devices) in class_devices {
+        let heading = class.map_or(String::from("-"), |c| c.to_string());
+        outputln!(
+            "{}",
+            settings
+                .colours
+                .as_ref()
+                .and_then(|ct| ct.class_code)
+                .map_or(heading.normal(), |c| heading.color(c))
+        );
+        print_flattened_devices(&devices, settings);
+        // new line for each group
+        outputln!();
+    }
+}
+
+/// Prints the number of devices in `sp_usb`, or the count for each group if [`PrintSettings`] `group_devices`
+///
+/// With `json` the grouped counts are an object of group name to count
+pub fn print_count(sp_usb: &SystemProfile, settings: &PrintSettings) {
+    let counts: Vec<(String, usize)> = match settings.group_devices {
+        Group::NoGroup => {
+            // a bare number is valid json too
+            outputln!("{}", sp_usb.flattened_devices().len());
+            return;
+        }
+        Group::Bus => sp_usb
+            .buses
+            .iter()
+            .map(|b| {
+                (
+                    b.get_bus_number()
+                        .map_or(b.name.to_owned(), |n| n.to_string()),
+                    b.flattened_devices().len(),
+                )
+            })
+            .collect(),
+        Group::Class => group_devices_by_class(&sp_usb.flattened_devices())
+            .into_iter()
+            .map(|(c, d)| (c.map_or(String::from("-"), |c| c.to_string()), d.len()))
+            .collect(),
+    };
+
+    if settings.json {
+        let map: serde_json::Map<String, serde_json::Value> = counts
+            .into_iter()
+            .map(|(g, c)| (g, serde_json::Value::from(c)))
+            .collect();
+        outputln!("{}", serde_json::to_string_pretty(&map).unwrap());
+    } else {
+        for (group, count) in counts {
+            outputln!("{} {}", group, count);
+        }
+    }
+}
+
+/// Prints the estimated periodic endpoint bandwidth reserved on each bus of `sp_usb` and by each device with periodic endpoints; see [`crate::profiler::bandwidth`]
+///
+/// With `verbosity` the endpoints of each device are listed too. Reservations of 90% or more of the capacity are coloured with the `warning` colour.
+pub fn print_bandwidth(sp_usb: &SystemProfile, settings: &PrintSettings) {
+    let buses = crate::profiler::bandwidth::bandwidth(sp_usb);
+    if settings.json {
+        outputln!("{}", serde_json::to_string_pretty(&buses).unwrap());
+        return;
+    }
+
+    // percentage padded to align device rows, coloured if near the limit
+    let percent = |percent: Option<f32>, pad: usize| {
+        let s = match percent {
+            Some(p) => format!("{:>pad$.1}%", p, pad = pad),
+            None => format!("{:>pad$} ", "-", pad = pad),
+        };
+        match settings.colours.as_ref().and_then(|c| c.warning) {
+            Some(c) if percent.is_some_and(|p| p >= 90.0) => s.color(c).to_string(),
+            _ => s,
+        }
+    };
+    let path_len = buses
+        .iter()
+        .flat_map(|b| b.devices.iter())
+        .map(|d| d.port_path.len())
+        .max()
+        .unwrap_or(0);
+
+    for bus in buses.iter() {
+        outputln!(
+            "Bus {}: {} of {} periodic ({}) {}",
+            bus.bus_number
+                .map_or(String::from("-"), |n| format!("{:03}", n)),
+            format_rate(bus.bytes_per_second),
+            bus.capacity.map_or(String::from("-"), format_rate),
+            percent(bus.percent, 0).trim_end(),
+            bus.name
+        );
+        for device in bus.devices.iter() {
+            outputln!(
+                "  {:path_len$} {:>10} {} {:>10} {}",
+                device.port_path,
+                format_rate(device.bytes_per_second),
+                percent(device.percent, 6),
+                device
+                    .speed
+                    .parse::<Speed>()
+                    .map_or(device.speed.to_owned(), |s| s.to_units_string(
+                        settings.speed_units.unwrap_or(SpeedUnits::Decimal)
+                    )),
+                device.name,
+                path_len = path_len
+            );
+            if settings.verbosity > 0 {
+                for endpoint in device.endpoints.iter() {
+                    outputln!(
+                        "    EP {:#04x} {}.{} {:<11} {:>5} B every {:>7} µs {:>10}",
+                        endpoint.address,
+                        endpoint.interface,
+                        endpoint.alt_setting,
+                        endpoint.transfer_type,
+                        endpoint.bytes_per_interval,
+                        endpoint.interval_us,
+                        format_rate(endpoint.bytes_per_second)
+                    );
+                }
+            }
+        }
+        if bus.unknown > 0 {
+            outputln!(
+                "  {} device(s) not estimated; not opened for descriptors or speed unknown",
+                bus.unknown
+            );
+        }
+    }
+}
+
+/// Format `bytes` per second with a decimal unit prefix: '64.0 kB/s'
+fn format_rate(bytes: u64) -> String {
+    match bytes {
+        0..1_000 => format!("{} B/s", bytes),
+        1_000..1_000_000 => format!("{:.1} kB/s", bytes as f64 / 1e3),
+        1_000_000..1_000_000_000 => format!("{:.1} MB/s", bytes as f64 / 1e6),
+        _ => format!("{:.1} GB/s", bytes as f64 / 1e9),
+    }
+}
+
+/// Raw value of `block` for `d`: strings as they are and other values formatted without padding; `None` if the device does not have the value rather than the '-' placeholder
+fn block_value(d: &Device, block: &DeviceBlocks, settings: &PrintSettings) -> Option<String> {
+    match block.json_value(d, settings) {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s),
+        _ => block
+            .format_value(d, &HashMap::new(), settings)
+            .map(|v| v.trim().to_string()),
+    }
+}
+
+/// Prints only the raw value of `block` for each device in `devices`, one per line without padding or colour; devices without the value are skipped
+///
+/// Intended for shell command substitution such as `$(cyme --get serial -d 1d50:6018)`
+pub fn print_block_values(devices: &[&Device], block: &DeviceBlocks, settings: &PrintSettings) {
+    for device in devices {
+        if let Some(v) = block_value(device, block, settings) {
+            outputln!("{}", v);
+        }
+    }
+}
+
+/// Prints the [`icon::IconTheme`] rule matched by each device in `devices` with its icon, for checking user icon rules
+///
+/// Uses the default theme if `settings.icons` is `None`
+pub fn print_icon_matches(devices: &[&Device], settings: &PrintSettings) {
+    let default_theme = icon::IconTheme::new();
+    let theme = settings.icons.as_ref().unwrap_or(&default_theme);
+    let rows: Vec<[String; 5]> = devices
+        .iter()
+        .map(|d| {
+            let vidpid = match (d.vendor_id, d.product_id) {
+                (Some(v), Some(p)) => format!("{:04x}:{:04x}", v, p),
+                _ => String::from("-"),
+            };
+            let (icon, rule) = match theme.get_device_icon_match(d) {
+                Some(m) => (
+                    m.icon,
+                    format!("{} ({})", m.rule, if m.user { "user" } else { "default" }),
+                ),
+                None => (String::from(" "), String::from("-")),
+            };
+            [d.port_path(), vidpid, icon, rule, d.name.to_owned()]
+        })
+        .collect();
+    let path_len = rows.iter().map(|r| r[0].len()).max().unwrap_or(0);
+    let rule_len = rows.iter().map(|r| r[3].len()).max().unwrap_or(0);
+
+    for [path, vidpid, icon, rule, name] in rows {
+        outputln!(
+            "{:path_len$} {:9} {} {:rule_len$} {}",
+            path,
+            vidpid,
+            icon,
+            rule,
+            name,
+            path_len = path_len,
+            rule_len = rule_len
+        );
+    }
+}
+
+/// Writes printed output to stdout, any [`Write`] such as a file with ANSI colour codes stripped or converted to HTML, or both with `tee`
+///
+/// Set as the destination of the print functions with [`set_output`]
+pub struct DisplayWriter {
+    writer: Option<Box<dyn Write + Send>>,
+    tee: bool,
+}
+
+impl DisplayWriter {
+    /// Writer to stdout, the default
+    pub fn stdout() -> Self {
+        DisplayWriter {
+            writer: None,
+            tee: false,
+        }
+    }
+
+    /// Writer to `writer` with colour stripped, also writing to stdout as is if `tee`
+    ///
+    /// ```no_run
+    /// use cyme::display::{self, DisplayWriter};
+    ///
+    /// let file = std::fs::File::create("cyme.txt").unwrap();
+    /// display::set_output(DisplayWriter::new(Box::new(file), true));
+    /// ```
+    pub fn new(writer: Box<dyn Write + Send>, tee: bool) -> Self {
+        DisplayWriter {
+            writer: Some(Box::new(StripAnsi::new(writer))),
+            tee,
+        }
+    }
+
+    /// Writer to `writer` with colour converted to HTML by [`AnsiToHtml`], also writing to stdout as is if `tee`
+    pub fn html(writer: Box<dyn Write + Send>, tee: bool) -> Self {
+        DisplayWriter {
+            writer: Some(Box::new(AnsiToHtml::new(writer))),
+            tee,
+        }
+    }
+
+    /// Writer to a [`Pager`] running `command` once the output is more than `height` lines
+    pub fn pager(command: &str, height: usize) -> Self {
+        DisplayWriter {
+            writer: Some(Box::new(Pager::new(command, height))),
+            tee: false,
+        }
+    }
+}
+
+impl Write for DisplayWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.writer.as_mut() {
+            Some(w) => {
+                w.write_all(buf)?;
+                if self.tee {
+                    io::stdout().write_all(buf)?;
+                }
+            }
+            None => io::stdout().write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(w) = self.writer.as_mut() {
+            w.flush()?;
+        }
+        io::stdout().flush()
+    }
+}
+
+/// State of [`StripAnsi`] within an escape sequence, which may be split across writes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AnsiState {
+    Text,
+    Escape,
+    Csi,
+}
+
+/// Writer that drops ANSI CSI escape sequences, such as colour codes, before writing to the inner writer
+pub struct StripAnsi<W: Write> {
+    inner: W,
+    state: AnsiState,
+}
+
+impl<W: Write> StripAnsi<W> {
+    /// Strip ANSI escape sequences written to `inner`
+    ///
+    /// ```
+    /// use cyme::display::StripAnsi;
+    /// use std::io::Write;
+    ///
+    /// let mut writer = StripAnsi::new(Vec::new());
+    /// write!(writer, "\x1b[1;3").unwrap();
+    /// write!(writer, "1mred\x1b[0m text").unwrap();
+    /// assert_eq!(writer.into_inner(), b"red text");
+    /// ```
+    pub fn new(inner: W) -> Self {
+        StripAnsi {
+            inner,
+            state: AnsiState::Text,
+        }
+    }
+
+    /// Returns the inner writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for StripAnsi<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut text = Vec::with_capacity(buf.len());
+        for &b in buf {
+            self.state = match (self.state, b) {
+                (AnsiState::Text, 0x1b) => AnsiState::Escape,
+                (AnsiState::Text, _) => {
+                    text.push(b);
+                    AnsiState::Text
+                }
+                (AnsiState::Escape, b'[') => AnsiState::Csi,
+                // two byte sequence
+                (AnsiState::Escape, _) => AnsiState::Text,
+                // final byte ends sequence
+                (AnsiState::Csi, 0x40..=0x7e) => AnsiState::Text,
+                (AnsiState::Csi, _) => AnsiState::Csi,
+            };
+        }
+        self.inner.write_all(&text)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Text style from ANSI SGR codes, written as the inline CSS of an HTML span by [`AnsiToHtml`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct HtmlStyle {
+    foreground: Option<(u8, u8, u8)>,
+    background: Option<(u8, u8, u8)>,
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+    strikethrough: bool,
+}
+
+impl HtmlStyle {
+    /// Colour of an ANSI colour code offset from its base, 0-7 normal and 8-15 bright, with the palette [`colour::color_rgb`] uses
+    fn ansi_rgb(code: u16) -> Option<(u8, u8, u8)> {
+        let color = match code {
+            0 => Color::Black,
+            1 => Color::Red,
+            2 => Color::Green,
+            3 => Color::Yellow,
+            4 => Color::Blue,
+            5 => Color::Magenta,
+            6 => Color::Cyan,
+            7 => Color::White,
+            8 => Color::BrightBlack,
+            9 => Color::BrightRed,
+            10 => Color::BrightGreen,
+            11 => Color::BrightYellow,
+            12 => Color::BrightBlue,
+            13 => Color::BrightMagenta,
+            14 => Color::BrightCyan,
+            15 => Color::BrightWhite,
+            _ => return None,
+        };
+        Some(colour::color_rgb(color))
+    }
+
+    /// Colour of an extended colour code following 38 or 48: `5;n` 256 colour or `2;r;g;b` true colour
+    fn extended_rgb<I: Iterator<Item = u16>>(codes: &mut I) -> Option<(u8, u8, u8)> {
+        match codes.next()? {
+            5 => {
+                let n = codes.next()?;
+                match n {
+                    0..=15 => Self::ansi_rgb(n),
+                    16..=231 => {
+                        let level = |c: u16| if c == 0 { 0 } else { (c * 40 + 55) as u8 };
+                        let n = n - 16;
+                        Some((level(n / 36), level((n / 6) % 6), level(n % 6)))
+                    }
+                    232..=255 => {
+                        let grey = ((n - 232) * 10 + 8) as u8;
+                        Some((grey, grey, grey))
+                    }
+                    _ => None,
+                }
+            }
+            2 => Some((
+                codes.next()? as u8,
+                codes.next()? as u8,
+                codes.next()? as u8,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Apply the `;` separated codes of an SGR sequence; unsupported codes are ignored
+    fn apply(&mut self, params: &str) {
+        let mut codes = params.split(';').map(|c| c.parse::<u16>().unwrap_or(0));
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = HtmlStyle::default(),
+                1 => self.bold = true,
+                2 => self.dimmed = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                9 => self.strikethrough = true,
+                22 => (self.bold, self.dimmed) = (false, false),
+                23 => self.italic = false,
+                24 => self.underline = false,
+                29 => self.strikethrough = false,
+                30..=37 => self.foreground = Self::ansi_rgb(code - 30),
+                90..=97 => self.foreground = Self::ansi_rgb(code - 90 + 8),
+                38 => self.foreground = Self::extended_rgb(&mut codes),
+                39 => self.foreground = None,
+                40..=47 => self.background = Self::ansi_rgb(code - 40),
+                100..=107 => self.background = Self::ansi_rgb(code - 100 + 8),
+                48 => self.background = Self::extended_rgb(&mut codes),
+                49 => self.background = None,
+                _ => (),
+            }
+        }
+    }
+
+    /// Inline CSS of the style, empty for the default style
+    fn css(&self) -> String {
+        let mut css = Vec::new();
+        if let Some((r, g, b)) = self.foreground {
+            css.push(format!("color:#{:02x}{:02x}{:02x}", r, g, b));
+        }
+        if let Some((r, g, b)) = self.background {
+            css.push(format!("background-color:#{:02x}{:02x}{:02x}", r, g, b));
+        }
+        if self.bold {
+            css.push(String::from("font-weight:bold"));
+        }
+        if self.dimmed {
+            css.push(String::from("opacity:0.7"));
+        }
+        if self.italic {
+            css.push(String::from("font-style:italic"));
+        }
+        match (self.underline, self.strikethrough) {
+            (true, true) => css.push(String::from("text-decoration:underline line-through")),
+            (true, false) => css.push(String::from("text-decoration:underline")),
+            (false, true) => css.push(String::from("text-decoration:line-through")),
+            (false, false) => (),
+        }
+        css.join(";")
+    }
+}
+
+/// Writer that converts ANSI colour codes into HTML spans with inline styles before writing to the inner writer, so coloured output can be shown in a web page or CI log which strips raw ANSI
+///
+/// Output is within a `<pre class="cyme">` element, closed when the writer is dropped, and text is HTML escaped. Colours are the RGB of the theme colours, with the xterm palette for named ANSI colours. Escape sequences other than SGR (colours and text style) are dropped as with [`StripAnsi`].
+pub struct AnsiToHtml<W: Write> {
+    inner: W,
+    state: AnsiState,
+    params: String,
+    style: HtmlStyle,
+    span: bool,
+    started: bool,
+}
+
+impl<W: Write> AnsiToHtml<W> {
+    /// Convert ANSI colour codes written to `inner` into HTML
+    ///
+    /// ```
+    /// use cyme::display::AnsiToHtml;
+    /// use std::io::Write;
+    ///
+    /// let mut html = Vec::new();
+    /// {
+    ///     let mut writer = AnsiToHtml::new(&mut html);
+    ///     write!(writer, "\x1b[1;3").unwrap();
+    ///     write!(writer, "1mred\x1b[0m <text>").unwrap();
+    /// }
+    /// assert_eq!(
+    ///     String::from_utf8(html).unwrap(),
+    ///     "<pre class=\"cyme\"><span style=\"color:#cd0000;font-weight:bold\">red</span> &lt;text&gt;</pre>\n"
+    /// );
+    /// ```
+    pub fn new(inner: W) -> Self {
+        AnsiToHtml {
+            inner,
+            state: AnsiState::Text,
+            params: String::new(),
+            style: HtmlStyle::default(),
+            span: false,
+            started: false,
+        }
+    }
+
+    /// Append `byte` of text to `html`, opening a span for the current style if not already
+    fn push_text(&mut self, html: &mut Vec<u8>, byte: u8) {
+        if !self.span && self.style != HtmlStyle::default() {
+            html.extend_from_slice(format!("<span style=\"{}\">", self.style.css()).as_bytes());
+            self.span = true;
+        }
+        match byte {
+            b'&' => html.extend_from_slice(b"&amp;"),
+            b'<' => html.extend_from_slice(b"&lt;"),
+            b'>' => html.extend_from_slice(b"&gt;"),
+            b'"' => html.extend_from_slice(b"&quot;"),
+            _ => html.push(byte),
+        }
+    }
+
+    /// Close any open span in `html` since the style has changed
+    fn close_span(&mut self, html: &mut Vec<u8>) {
+        if self.span {
+            html.extend_from_slice(b"</span>");
+            self.span = false;
+        }
+    }
+}
+
+impl<W: Write> Write for AnsiToHtml<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut html = Vec::with_capacity(buf.len());
+        if !self.started {
+            html.extend_from_slice(b"<pre class=\"cyme\">");
+            self.started = true;
+        }
+        for &b in buf {
+            self.state = match (self.state, b) {
+                (AnsiState::Text, 0x1b) => AnsiState::Escape,
+                (AnsiState::Text, _) => {
+                    self.push_text(&mut html, b);
+                    AnsiState::Text
+                }
+                (AnsiState::Escape, b'[') => {
+                    self.params.clear();
+                    AnsiState::Csi
+                }
+                // two byte sequence
+                (AnsiState::Escape, _) => AnsiState::Text,
+                (AnsiState::Csi, b'm') => {
+                    let mut style = self.style;
+                    style.apply(&self.params);
+                    if style != self.style {
+                        self.close_span(&mut html);
+                        self.style = style;
+                    }
+                    AnsiState::Text
+                }
+                // final byte of a sequence other than SGR
+                (AnsiState::Csi, 0x40..=0x7e) => AnsiState::Text,
+                (AnsiState::Csi, _) => {
+                    self.params.push(b as char);
+                    AnsiState::Csi
+                }
+            };
+        }
+        self.inner.write_all(&html)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Write> Drop for AnsiToHtml<W> {
+    fn drop(&mut self) {
+        if self.started {
+            let mut html = Vec::new();
+            self.close_span(&mut html);
+            html.extend_from_slice(b"</pre>\n");
+            if let Err(e) = self.inner.write_all(&html).and_then(|_| self.inner.flush()) {
+                log::error!("Failed to finish HTML output: {}", e);
+            }
+        }
+    }
+}
+
+/// Writer that buffers output until it is more than `height` lines then pipes it to a pager `command`, like git
+///
+/// Output within `height` lines is written to stdout when dropped, as is all output if the pager fails to start. `LESS` is set to 'FRX' if not already so less keeps colour and quits if the output fits on one screen
+pub struct Pager {
+    command: String,
+    height: usize,
+    lines: usize,
+    buffer: Vec<u8>,
+    child: Option<std::process::Child>,
+    failed: bool,
+}
+
+impl Pager {
+    /// Pager running `command`, split on whitespace into program and arguments, if output is more than `height` lines
+    pub fn new(command: &str, height: usize) -> Self {
+        Pager {
+            command: command.to_string(),
+            height,
+            lines: 0,
+            buffer: Vec::new(),
+            child: None,
+            failed: false,
+        }
+    }
+
+    fn spawn(&mut self) -> io::Result<()> {
+        let mut parts = self.command.split_whitespace();
+        let program = parts
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Empty pager command"))?;
+        let mut command = std::process::Command::new(program);
+        command.args(parts).stdin(std::process::Stdio::piped());
+        if std::env::var_os("LESS").is_none() {
+            command.env("LESS", "FRX");
+        }
+        self.child = Some(command.spawn()?);
+        Ok(())
+    }
+
+    fn write_pager(&mut self, buf: &[u8]) -> io::Result<()> {
+        match self.child.as_mut().and_then(|c| c.stdin.as_mut()) {
+            // user quit the pager so drop the rest
+            Some(stdin) => match stdin.write_all(buf) {
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+                    self.child.as_mut().map(|c| c.stdin.take());
+                    Ok(())
+                }
+                r => r,
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+impl Write for Pager {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.child.is_some() {
+            self.write_pager(buf)?;
+        } else if self.failed {
+            io::stdout().write_all(buf)?;
+        } else {
+            self.buffer.extend_from_slice(buf);
+            self.lines += buf.iter().filter(|&&b| b == b'\n').count();
+            if self.lines > self.height {
+                let buffer = std::mem::take(&mut self.buffer);
+                match self.spawn() {
+                    Ok(_) => self.write_pager(&buffer)?,
+                    Err(e) => {
+                        log::warn!("Failed to start pager '{}'; Error({})", self.command, e);
+                        self.failed = true;
+                        io::stdout().write_all(&buffer)?;
+                    }
+                }
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.child.as_mut().and_then(|c| c.stdin.as_mut()) {
+            Some(stdin) => stdin.flush(),
+            None => io::stdout().flush(),
+        }
     }
+}
 
-    for (i, device) in devices.iter().enumerate() {
-        println!(
-            "{}",
-            render_value(*device, &db, &pad, settings, max_variable_string_len).join(" ")
-        );
-        // print the configurations
-        if let Some(extra) = device.extra.as_ref() {
-            if settings.verbosity >= 1 {
-                let blocks = generate_extra_blocks(extra, settings);
-
-                // pass branch length as number of configurations for this device plus devices still to print
-                print_configurations(
-                    &extra.configurations,
-                    (&blocks.0, &blocks.1, &blocks.2),
-                    settings,
-                    &generate_tree_data(
-                        &Default::default(),
-                        extra.configurations.len() + device.devices.as_ref().map_or(0, |d| d.len()),
-                        i,
-                        settings,
-                    ),
-                );
+impl Drop for Pager {
+    fn drop(&mut self) {
+        match self.child.take() {
+            Some(mut child) => {
+                // close stdin so the pager sees the end of output
+                drop(child.stdin.take());
+                let _ = child.wait();
+            }
+            None => {
+                let _ = io::stdout().write_all(&self.buffer);
+                let _ = io::stdout().flush();
             }
-        } else if settings.verbosity >= 1 {
-            log::warn!(
-                "Unable to print verbose information for {} because libusb extra data is missing",
-                device
-            )
         }
     }
 }
 
-/// A way of printing a reference flattened [`SystemProfile`] rather than hard flatten
-///
-/// Prints each `&Bus` and tuple pair `Vec<&Device>`
-pub fn print_bus_grouped(bus_devices: Vec<(&Bus, Vec<&Device>)>, settings: &PrintSettings) {
-    let bb = settings
-        .bus_blocks
-        .to_owned()
-        .unwrap_or(Block::<BusBlocks, Bus>::default_blocks(
-            settings.verbosity >= MAX_VERBOSITY || settings.more,
-        ));
-    let mut pad: HashMap<BusBlocks, usize> = if !settings.no_padding {
-        let buses: Vec<&Bus> = bus_devices.iter().map(|bd| bd.0).collect();
-        BusBlocks::generate_padding(&buses)
-    } else {
-        HashMap::new()
-    };
-    pad.retain(|k, _| bb.contains(k));
+/// Set the [`DisplayWriter`] the print functions of the current thread write to, returning the previous if any
+pub fn set_output(writer: DisplayWriter) -> Option<DisplayWriter> {
+    OUTPUT.with_borrow_mut(|o| o.replace(writer))
+}
 
-    let max_variable_string_len: Option<usize> = if settings.auto_width {
-        let mut variable_lens = pad.clone();
-        variable_lens.retain(|k, _| k.value_is_variable_length());
-        auto_max_string_len(&bb, 0, &variable_lens.into_values().collect(), settings)
-            .or(settings.max_variable_string_len)
-    } else {
-        settings.max_variable_string_len
+/// Flush the [`DisplayWriter`] set with [`set_output`]
+pub fn flush_output() -> io::Result<()> {
+    OUTPUT.with_borrow_mut(|o| match o.as_mut() {
+        Some(w) => w.flush(),
+        None => io::stdout().flush(),
+    })
+}
+
+/// Add a [`Hint`] explaining missing data or an ignored argument; printed to stderr by [`finish_output`] on the same thread so it is not lost in the output or pager
+pub fn hint(hint: Hint) {
+    log::info!("{}", hint);
+    HINTS.with_borrow_mut(|hints| {
+        if !hints.contains(&hint) {
+            hints.push(hint);
+        }
+    });
+}
+
+/// Flush and drop the [`DisplayWriter`] set with [`set_output`], waiting for any [`Pager`] to exit; printing is then to stdout
+///
+/// Any [`hint`]s are then printed to stderr, in yellow if `colours`.
+pub fn finish_output(colours: bool) -> io::Result<()> {
+    let writer = OUTPUT.with_borrow_mut(Option::take);
+    let ret = match writer {
+        Some(mut w) => w.flush(),
+        None => io::stdout().flush(),
     };
+    for hint in HINTS.with_borrow_mut(std::mem::take) {
+        if colours {
+            eprintln!("{}", hint.to_string().yellow());
+        } else {
+            eprintln!("{}", hint);
+        }
+    }
+    ret
+}
 
-    // if there is a max variable length, adjust padding to this if current > it
-    if let Some(ml) = max_variable_string_len.as_ref() {
-        for (k, v) in pad.iter_mut() {
-            if k.value_is_variable_length() {
-                *v = cmp::min(*v, *ml);
+/// Write `args` to the [`DisplayWriter`] set with [`set_output`] or stdout, such as with `format_args!`; the `output!` and `outputln!` macros of the crate use this
+pub fn write_output(args: std::fmt::Arguments) {
+    let prefixed = LINE_PREFIX.with_borrow_mut(|p| {
+        p.as_mut().map(|(prefix, line_start)| {
+            let mut ret = String::new();
+            for line in args.to_string().split_inclusive('\n') {
+                if *line_start {
+                    ret.push_str(prefix);
+                }
+                ret.push_str(line);
+                *line_start = line.ends_with('\n');
             }
-        }
+            ret
+        })
+    });
+
+    match prefixed {
+        Some(s) => write_args(format_args!("{}", s)),
+        None => write_args(args),
     }
+}
 
-    for (bus, devices) in bus_devices {
-        if settings.headings {
-            let heading = render_heading(&bb, &pad, max_variable_string_len).join(" ");
-            println!("{}", heading.bold().underline());
+/// Runs `f` with `prefix` written at the start of each line it outputs
+fn with_line_prefix<F: FnOnce()>(prefix: String, f: F) {
+    LINE_PREFIX.set(Some((prefix, true)));
+    f();
+    LINE_PREFIX.set(None);
+}
+
+fn write_args(args: std::fmt::Arguments) {
+    OUTPUT.with_borrow_mut(|o| match o.as_mut() {
+        Some(w) => {
+            if let Err(e) = w.write_fmt(args) {
+                log::error!("Failed to write output: {}", e);
+            }
         }
-        println!(
-            "{}",
-            render_value(bus, &bb, &pad, settings, max_variable_string_len).join(" ")
-        );
-        print_flattened_devices(&devices, settings);
-        // new line for each group
-        println!();
-    }
+        None => print!("{}", args),
+    })
 }
 
 /// Passed to print functions to support tree building
@@ -2315,7 +4662,7 @@ pub fn print_endpoints(
 ) {
     let mut pad = if !settings.no_padding {
         let endpoints: Vec<&Endpoint> = endpoints.iter().collect();
-        EndpointBlocks::generate_padding(&endpoints)
+        EndpointBlocks::generate_padding(&endpoints, settings)
     } else {
         HashMap::new()
     };
@@ -2402,42 +4749,72 @@ pub fn print_endpoints(
 
             // maybe should just do once at start of bus
             if settings.headings && i == 0 {
-                let heading = render_heading(blocks, &pad, max_variable_string_len).join(" ");
-                println!("{}  {}", prefix, heading.bold().underline());
+                let heading =
+                    render_heading(blocks, &pad, max_variable_string_len, settings).join(" ");
+                outputln!("{}  {}", prefix, style_heading(&heading, settings));
             }
 
             // render and print tree if doing it
-            print!("{}{} ", prefix, terminator);
-            println!(
+            output!("{}{} ", prefix, terminator);
+            outputln!(
                 "{}",
                 render_value(endpoint, blocks, &pad, settings, max_variable_string_len).join(" ")
             );
         } else {
             if settings.headings && i == 0 {
-                let heading = render_heading(blocks, &pad, max_variable_string_len).join(" ");
-                println!("{:spaces$}{}", "", heading.bold().underline(), spaces = 6);
+                let heading =
+                    render_heading(blocks, &pad, max_variable_string_len, settings).join(" ");
+                outputln!(
+                    "{:spaces$}{}",
+                    "",
+                    style_heading(&heading, settings),
+                    spaces = 6
+                );
             }
 
-            println!(
+            outputln!(
                 "{:spaces$}{}",
                 "",
                 render_value(endpoint, blocks, &pad, settings, max_variable_string_len).join(" "),
                 spaces = (EndpointBlocks::INSET * LIST_INSET_SPACES) as usize
             );
         }
+
+        if settings.dump_descriptors {
+            print_descriptor_hexdump(
+                endpoint.raw.as_ref(),
+                tree,
+                i,
+                EndpointBlocks::INSET,
+                settings,
+            );
+        }
     }
 }
 
 /// All device [`Interface`]
+///
+/// Interfaces no driver is bound to are marked with [`icon::Icon::TreeInterfaceUnclaimed`]; see [`Interface::is_unclaimed`].
 pub fn print_interfaces(
     interfaces: &[Interface],
     blocks: (&Vec<InterfaceBlocks>, &Vec<EndpointBlocks>),
     settings: &PrintSettings,
     tree: &TreeData,
+) {
+    print_config_interfaces(interfaces, blocks, settings, tree, true)
+}
+
+/// [`print_interfaces`] of a configuration, only marking those unclaimed if `claimable` since only the active configuration can be claimed
+fn print_config_interfaces(
+    interfaces: &[Interface],
+    blocks: (&Vec<InterfaceBlocks>, &Vec<EndpointBlocks>),
+    settings: &PrintSettings,
+    tree: &TreeData,
+    claimable: bool,
 ) {
     let mut pad = if !settings.no_padding {
         let interfaces: Vec<&Interface> = interfaces.iter().collect();
-        InterfaceBlocks::generate_padding(&interfaces)
+        InterfaceBlocks::generate_padding(&interfaces, settings)
     } else {
         HashMap::new()
     };
@@ -2474,6 +4851,7 @@ pub fn print_interfaces(
     log::trace!("Print interfaces padding {:?}, tree {:?}", pad, tree);
 
     for (i, interface) in interfaces.iter().enumerate() {
+        let unclaimed = claimable && interface.is_unclaimed();
         // get current prefix based on if last in tree and whether we are within the tree
         if settings.tree {
             let mut prefix = if tree.depth > 0 {
@@ -2512,35 +4890,61 @@ pub fn print_interfaces(
                     .to_string();
             }
 
+            if unclaimed {
+                terminator = unclaimed_icon(settings);
+            }
+
             // maybe should just do once at start of bus
             if settings.headings && i == 0 {
-                let heading = render_heading(blocks.0, &pad, max_variable_string_len).join(" ");
-                println!("{}  {}", prefix, heading.bold().underline());
+                let heading =
+                    render_heading(blocks.0, &pad, max_variable_string_len, settings).join(" ");
+                outputln!("{}  {}", prefix, style_heading(&heading, settings));
             }
 
             // render and print tree if doing it
-            print!("{}{} ", prefix, terminator);
+            output!("{}{} ", prefix, terminator);
 
-            println!(
+            outputln!(
                 "{}",
                 render_value(interface, blocks.0, &pad, settings, max_variable_string_len)
                     .join(" ")
             );
         } else {
             if settings.headings && i == 0 {
-                let heading = render_heading(blocks.0, &pad, max_variable_string_len).join(" ");
-                println!("{:spaces$}{}", "", heading.bold().underline(), spaces = 4);
+                let heading =
+                    render_heading(blocks.0, &pad, max_variable_string_len, settings).join(" ");
+                outputln!(
+                    "{:spaces$}{}",
+                    "",
+                    style_heading(&heading, settings),
+                    spaces = 4
+                );
             }
 
-            println!(
-                "{:spaces$}{}",
+            outputln!(
+                "{:spaces$}{}{}",
                 "",
                 render_value(interface, blocks.0, &pad, settings, max_variable_string_len)
                     .join(" "),
+                if unclaimed {
+                    format!(" {}", unclaimed_icon(settings))
+                } else {
+                    String::new()
+                },
                 spaces = (InterfaceBlocks::INSET * LIST_INSET_SPACES) as usize
             );
         }
 
+        if settings.dump_descriptors {
+            print_descriptor_hexdump(
+                interface.raw.as_ref(),
+                tree,
+                i,
+                InterfaceBlocks::INSET,
+                settings,
+            );
+        }
+
         // print the endpoints
         if settings.verbosity >= 3 {
             print_endpoints(
@@ -2566,7 +4970,7 @@ pub fn print_configurations(
 ) {
     let mut pad = if !settings.no_padding {
         let configs: Vec<&Configuration> = configs.iter().collect();
-        ConfigurationBlocks::generate_padding(&configs)
+        ConfigurationBlocks::generate_padding(&configs, settings)
     } else {
         HashMap::new()
     };
@@ -2643,24 +5047,31 @@ pub fn print_configurations(
 
             // maybe should just do once at start of bus
             if settings.headings && i == 0 {
-                let heading = render_heading(blocks.0, &pad, max_variable_string_len).join(" ");
-                println!("{}  {}", prefix, heading.bold().underline());
+                let heading =
+                    render_heading(blocks.0, &pad, max_variable_string_len, settings).join(" ");
+                outputln!("{}  {}", prefix, style_heading(&heading, settings));
             }
 
             // render and print tree if doing it
-            print!("{}{} ", prefix, terminator);
+            output!("{}{} ", prefix, terminator);
 
-            println!(
+            outputln!(
                 "{}",
                 render_value(config, blocks.0, &pad, settings, max_variable_string_len).join(" ")
             );
         } else {
             if settings.headings && i == 0 {
-                let heading = render_heading(blocks.0, &pad, max_variable_string_len).join(" ");
-                println!("{:spaces$}{}", "", heading.bold().underline(), spaces = 2);
+                let heading =
+                    render_heading(blocks.0, &pad, max_variable_string_len, settings).join(" ");
+                outputln!(
+                    "{:spaces$}{}",
+                    "",
+                    style_heading(&heading, settings),
+                    spaces = 2
+                );
             }
 
-            println!(
+            outputln!(
                 "{:spaces$}{}",
                 "",
                 render_value(config, blocks.0, &pad, settings, max_variable_string_len).join(" "),
@@ -2668,18 +5079,61 @@ pub fn print_configurations(
             );
         }
 
+        if settings.dump_descriptors {
+            print_descriptor_hexdump(
+                config.raw.as_ref(),
+                tree,
+                i,
+                ConfigurationBlocks::INSET,
+                settings,
+            );
+        }
+
         // print the interfaces
         if settings.verbosity >= 2 {
-            print_interfaces(
+            print_config_interfaces(
                 &config.interfaces,
                 ((blocks.1), (blocks.2)),
                 settings,
                 &generate_tree_data(tree, config.interfaces.len(), i, settings),
+                config.active || configs.len() == 1,
             );
         }
     }
 }
 
+/// Print the [`lsusb::dump`] of `device` below its line `index` in `tree`, continuing the tree line down to any `children`
+fn print_inline_descriptors(
+    device: &Device,
+    tree: &TreeData,
+    index: usize,
+    children: bool,
+    settings: &PrintSettings,
+) {
+    let edge_icon = if children {
+        icon::Icon::TreeLine
+    } else {
+        icon::Icon::TreeBlank
+    };
+    let prefix = format!(
+        "{}{}",
+        generate_tree_data(tree, 0, index, settings).prefix,
+        settings.icons.as_ref().map_or(
+            icon::get_default_tree_icon(&edge_icon, &settings.encoding),
+            |i| i.get_tree_icon(&edge_icon, &settings.encoding)
+        )
+    );
+    let prefix = match settings.colours.as_ref() {
+        Some(ct) => ct
+            .tree
+            .map_or(prefix.normal(), |c| prefix.color(c))
+            .to_string(),
+        None => prefix,
+    };
+
+    with_line_prefix(prefix, || lsusb::dump(device, settings.verbosity));
+}
+
 /// Recursively print `devices`; will call for each `Device` devices if `Some`
 ///
 /// Will draw tree if `settings.tree`, otherwise it will be flat
@@ -2691,11 +5145,11 @@ pub fn print_devices(
 ) {
     let mut pad = if !settings.no_padding {
         let devices: Vec<&Device> = devices.iter().collect();
-        DeviceBlocks::generate_padding(&devices)
+        DeviceBlocks::generate_padding(&devices, settings)
     } else {
         HashMap::new()
     };
-    pad.retain(|k, _| db.contains(k));
+    pad.retain(|k, _| is_printed_block(k, db, settings));
 
     let max_variable_string_len: Option<usize> = if settings.auto_width {
         let mut variable_lens = pad.clone();
@@ -2721,7 +5175,7 @@ pub fn print_devices(
     //// sort so that can be ascending along branch
     //let sorted = settings.sort_devices.sort_devices(devices);
 
-    for (i, device) in devices.iter().enumerate() {
+    for (i, &(device, count)) in compact_siblings(devices, settings).iter().enumerate() {
         // get current prefix based on if last in tree and whether we are within the tree
         if settings.tree {
             let mut prefix = if tree.depth > 0 {
@@ -2757,39 +5211,66 @@ pub fn print_devices(
                     .to_string();
             }
 
+            if is_highlighted(device, settings) {
+                terminator = highlight_icon(settings);
+            }
+
             // maybe should just do once at start of bus
             if settings.headings && i == 0 {
-                let heading = render_heading(db, &pad, max_variable_string_len).join(" ");
-                println!("{}  {}", prefix, heading.bold().underline());
+                let heading = render_heading(db, &pad, max_variable_string_len, settings).join(" ");
+                outputln!("{}  {}", prefix, style_heading(&heading, settings));
             }
 
             // render and print tree if doing it
-            print!("{}{} ", prefix, terminator);
+            output!("{}{} ", prefix, terminator);
         } else if settings.headings && i == 0 {
-            let heading = render_heading(db, &pad, max_variable_string_len).join(" ");
-            println!("{}", heading.bold().underline());
+            let heading = render_heading(db, &pad, max_variable_string_len, settings).join(" ");
+            outputln!("{}", style_heading(&heading, settings));
         }
 
+        let limited = depth_limited(tree.depth, settings);
+        let hidden = if limited {
+            device.iter().count() - 1
+        } else {
+            0
+        };
+
         // print the device
-        println!(
-            "{}",
-            render_value(device, db, &pad, settings, max_variable_string_len).join(" ")
+        outputln!(
+            "{}{}",
+            render_value(
+                device,
+                &class_device_blocks(device, db, settings),
+                &pad,
+                settings,
+                max_variable_string_len
+            )
+            .join(" "),
+            line_suffix(count, hidden, settings)
         );
 
         // print the configurations
         if let Some(extra) = device.extra.as_ref() {
-            if settings.verbosity >= 1 {
+            if settings.verbosity >= 1 && settings.tree && settings.inline_descriptors {
+                let children = !limited && device.devices.as_ref().is_some_and(|d| !d.is_empty());
+                print_inline_descriptors(device, tree, i, children, settings);
+            } else if settings.verbosity >= 1 {
                 // generate extra blocks if not passed and drop icons if not supported by encoding
                 let blocks = generate_extra_blocks(extra, settings);
 
                 // pass branch length as number of configurations for this device plus devices still to print
                 print_configurations(
-                    &extra.configurations,
+                    printed_configurations(extra, settings),
                     (&blocks.0, &blocks.1, &blocks.2),
                     settings,
                     &generate_tree_data(
                         tree,
-                        extra.configurations.len() + device.devices.as_ref().map_or(0, |d| d.len()),
+                        printed_configurations(extra, settings).len()
+                            + if limited {
+                                0
+                            } else {
+                                device.devices.as_ref().map_or(0, |d| d.len())
+                            },
                         i,
                         settings,
                     ),
@@ -2802,13 +5283,13 @@ pub fn print_devices(
             )
         }
 
-        if let Some(d) = device.devices.as_ref() {
+        if let Some(d) = device.devices.as_ref().filter(|_| !limited) {
             // and then walk down devices printing them too
             print_devices(
                 d,
                 db,
                 settings,
-                &generate_tree_data(tree, d.len(), i, settings),
+                &generate_tree_data(tree, compact_siblings(d, settings).len(), i, settings),
             );
         }
     }
@@ -2862,7 +5343,7 @@ pub fn print_sp_usb(sp_usb: &SystemProfile, settings: &PrintSettings) {
     };
 
     let mut pad: HashMap<BusBlocks, usize> = if !settings.no_padding {
-        BusBlocks::generate_padding(&sp_usb.buses.iter().collect::<Vec<&Bus>>())
+        BusBlocks::generate_padding(&sp_usb.buses.iter().collect::<Vec<&Bus>>(), settings)
     } else {
         HashMap::new()
     };
@@ -2919,65 +5400,52 @@ pub fn print_sp_usb(sp_usb: &SystemProfile, settings: &PrintSettings) {
             }
 
             if settings.headings {
-                let heading = render_heading(&bb, &pad, max_variable_string_len).join(" ");
+                let heading =
+                    render_heading(&bb, &pad, max_variable_string_len, settings).join(" ");
                 // 2 spaces for bus start icon and space to info
-                println!("{:>spaces$}{}", "", heading.bold().underline(), spaces = 2);
+                outputln!(
+                    "{:>spaces$}{}",
+                    "",
+                    style_heading(&heading, settings),
+                    spaces = 2
+                );
             }
 
-            print!("{}{} ", prefix, start);
+            output!("{}{} ", prefix, start);
         } else if settings.headings {
-            let heading = render_heading(&bb, &pad, max_variable_string_len).join(" ");
+            let heading = render_heading(&bb, &pad, max_variable_string_len, settings).join(" ");
             // 2 spaces for bus start icon and space to info
-            println!("{}", heading.bold().underline());
+            outputln!("{}", style_heading(&heading, settings));
         }
-        println!(
-            "{}",
-            render_value(bus, &bb, &pad, settings, max_variable_string_len).join(" ")
+        let limited = depth_limited(base_tree.depth, settings);
+        outputln!(
+            "{}{}",
+            render_value(bus, &bb, &pad, settings, max_variable_string_len).join(" "),
+            line_suffix(1, if limited { bus.iter().count() } else { 0 }, settings)
         );
 
-        if let Some(d) = bus.devices.as_ref() {
+        if let Some(d) = bus.devices.as_ref().filter(|_| !limited) {
             // and then walk down devices printing them too
             print_devices(
                 d,
                 &db,
                 settings,
-                &generate_tree_data(&base_tree, d.len(), i, settings),
+                &generate_tree_data(&base_tree, compact_siblings(d, settings).len(), i, settings),
             );
         }
 
         // separate bus groups with line
-        println!();
+        outputln!();
     }
 }
 
-/// Mask the `device` serial if it has one using the [`MaskSerial`] method and recursively if `recursive`
-pub fn mask_serial(device: &mut Device, hide: &MaskSerial, recursive: bool) {
-    if let Some(serial) = device.serial_num.as_mut() {
-        *serial = match hide {
-            MaskSerial::Hide => serial.chars().map(|_| '*').collect::<String>(),
-            MaskSerial::Scramble => serial
-                .chars()
-                .map(|_| {
-                    serial
-                        .chars()
-                        .choose(&mut rand::thread_rng())
-                        .unwrap_or('*')
-                })
-                .collect::<String>(),
-            MaskSerial::Replace => rand::thread_rng()
-                .sample_iter(Alphanumeric)
-                .take(serial.chars().count())
-                .map(char::from)
-                .collect::<String>()
-                .to_uppercase(),
-        };
-    }
-
-    if recursive {
-        device
-            .devices
-            .iter_mut()
-            .for_each(|dd| dd.iter_mut().for_each(|d| mask_serial(d, hide, recursive)));
+/// Mask the `device` serial if it has one using the [`MaskSerial`] method, with `salt` for [`MaskSerial::Hash`], and recursively if `recursive`
+///
+/// Paths containing the serial, such as a syspath named after it, have it replaced with the same mask and Bluetooth adapter addresses are masked with [`MaskSerial::mask_address`].
+pub fn mask_serial(device: &mut Device, hide: &MaskSerial, salt: &str, recursive: bool) {
+    let redaction = crate::redact::Redaction::mask_serials(hide.to_owned(), Some(salt.to_owned()));
+    if let Err(e) = redaction.apply_device(device, recursive) {
+        log::error!("Failed to mask serials: {}", e);
     }
 }
 
@@ -3025,7 +5493,12 @@ pub fn prepare(sp_usb: &mut SystemProfile, filter: Option<Filter>, settings: &Pr
         for bus in &mut sp_usb.buses {
             bus.devices.iter_mut().for_each(|devices| {
                 for device in devices {
-                    mask_serial(device, hide, true);
+                    mask_serial(
+                        device,
+                        hide,
+                        settings.mask_salt.as_deref().unwrap_or_default(),
+                        true,
+                    );
                 }
             });
         }
@@ -3034,15 +5507,190 @@ pub fn prepare(sp_usb: &mut SystemProfile, filter: Option<Filter>, settings: &Pr
     log::trace!("sp_usb data post filter and bus sort\n\r{:#}", sp_usb);
 }
 
+/// Projects `devices` into json objects containing only the `fields` blocks, keyed by the block name
+pub fn devices_to_json_fields(
+    devices: &[&Device],
+    fields: &[DeviceBlocks],
+    settings: &PrintSettings,
+) -> Vec<serde_json::Map<String, serde_json::Value>> {
+    devices
+        .iter()
+        .map(|d| {
+            fields
+                .iter()
+                .map(|f| (f.json_key(), f.json_value(d, settings)))
+                .collect()
+        })
+        .collect()
+}
+
+/// Print the Thunderbolt/USB4 [`ThunderboltDomain`]s as a tree of routers
+pub fn print_thunderbolt(domains: &[ThunderboltDomain], settings: &PrintSettings) {
+    for domain in domains {
+        let heading = format!("Thunderbolt {}", domain);
+        outputln!("{}", style_bold(&heading, settings));
+        print_thunderbolt_routers(&domain.routers, settings, "");
+    }
+}
+
+fn print_thunderbolt_routers(
+    routers: &[ThunderboltRouter],
+    settings: &PrintSettings,
+    prefix: &str,
+) {
+    let tree_icon = |i: icon::Icon| {
+        settings.icons.as_ref().map_or(
+            icon::get_default_tree_icon(&i, &settings.encoding),
+            |icons| icons.get_tree_icon(&i, &settings.encoding),
+        )
+    };
+
+    for (i, router) in routers.iter().enumerate() {
+        let last = i + 1 == routers.len();
+        let edge = format!(
+            "{}{}",
+            prefix,
+            tree_icon(if last {
+                icon::Icon::TreeCorner
+            } else {
+                icon::Icon::TreeEdge
+            })
+        );
+        let value = router.to_string();
+        match settings.colours.as_ref() {
+            Some(ct) => outputln!(
+                "{} {}",
+                ct.tree.map_or(edge.normal(), |c| edge.color(c)),
+                ct.name.map_or(value.normal(), |c| value.color(c))
+            ),
+            None => outputln!("{} {}", edge, value),
+        }
+
+        let next_prefix = format!(
+            "{}{}",
+            prefix,
+            tree_icon(if last {
+                icon::Icon::TreeBlank
+            } else {
+                icon::Icon::TreeLine
+            })
+        );
+        print_thunderbolt_routers(&router.routers, settings, &next_prefix);
+    }
+}
+
+/// Print the USB device controllers of [`GadgetProfile`] as a tree of their bound gadget, its configurations and active functions, followed by gadgets not bound
+pub fn print_gadgets(profile: &GadgetProfile, settings: &PrintSettings) {
+    let tree_icon = |i: icon::Icon| {
+        settings.icons.as_ref().map_or(
+            icon::get_default_tree_icon(&i, &settings.encoding),
+            |icons| icons.get_tree_icon(&i, &settings.encoding),
+        )
+    };
+    let line = |prefix: &str, last: bool, value: String, colour: Option<Color>| {
+        let edge = format!(
+            "{}{}",
+            prefix,
+            tree_icon(if last {
+                icon::Icon::TreeCorner
+            } else {
+                icon::Icon::TreeEdge
+            })
+        );
+        match settings.colours.as_ref() {
+            Some(ct) => outputln!(
+                "{} {}",
+                ct.tree.map_or(edge.normal(), |c| edge.color(c)),
+                colour.map_or(value.normal(), |c| value.color(c))
+            ),
+            None => outputln!("{} {}", edge, value),
+        }
+        format!(
+            "{}{}",
+            prefix,
+            tree_icon(if last {
+                icon::Icon::TreeBlank
+            } else {
+                icon::Icon::TreeLine
+            })
+        )
+    };
+    let colours = settings.colours.as_ref();
+    let print_configurations = |gadget: &Gadget, prefix: &str| {
+        for (i, config) in gadget.configurations.iter().enumerate() {
+            let config_prefix = line(
+                prefix,
+                i + 1 == gadget.configurations.len(),
+                config.to_string(),
+                colours.and_then(|ct| ct.number),
+            );
+            for (j, function) in config.functions.iter().enumerate() {
+                line(
+                    &config_prefix,
+                    j + 1 == config.functions.len(),
+                    function.to_owned(),
+                    colours.and_then(|ct| ct.driver),
+                );
+            }
+        }
+    };
+
+    for controller in profile.controllers.iter() {
+        let heading = format!("Device controller {}", controller);
+        outputln!("{}", style_bold(&heading, settings));
+        if let Some(gadget) = controller.gadget.as_ref() {
+            let prefix = line("", true, gadget.to_string(), colours.and_then(|ct| ct.name));
+            print_configurations(gadget, &prefix);
+        }
+    }
+    for gadget in profile.unbound.iter() {
+        let heading = format!("Gadget {} (unbound)", gadget);
+        outputln!("{}", style_bold(&heading, settings));
+        print_configurations(gadget, "");
+    }
+}
+
+/// Render `template` once with the whole `sp_usb` when printing a tree or grouping by bus, otherwise once for each device
+pub fn print_template(
+    sp_usb: &SystemProfile,
+    template: &template::Template,
+    settings: &PrintSettings,
+) {
+    if settings.tree || settings.group_devices == Group::Bus {
+        output!("{}", template.render(&template::profile_value(sp_usb)));
+    } else {
+        for device in sp_usb.flattened_devices() {
+            output!("{}", template.render(&template::device_value(device)));
+        }
+    }
+}
+
 /// Main cyme bin print function
 pub fn print(sp_usb: &SystemProfile, settings: &PrintSettings) {
     log::trace!("Printing with {:?}", settings);
 
-    if settings.tree || settings.group_devices == Group::Bus {
+    if let Some(template) = settings.template.as_ref() {
+        print_template(sp_usb, template, settings);
+    } else if settings.xml {
+        output!("{}", xml::to_xml(sp_usb));
+    } else if let (true, Some(fields)) = (settings.json, settings.json_fields.as_ref()) {
+        // projection is always a flat list of devices since that is what scripts will want
+        let devs = sp_usb.flattened_devices();
+        outputln!(
+            "{}",
+            serde_json::to_string_pretty(&devices_to_json_fields(&devs, fields, settings)).unwrap()
+        );
+    } else if settings.tree || settings.group_devices == Group::Bus {
         if settings.json {
-            println!("{}", serde_json::to_string_pretty(&sp_usb).unwrap());
+            outputln!("{}", serde_json::to_string_pretty(&sp_usb).unwrap());
         } else {
             print_sp_usb(sp_usb, settings);
+            if let Some(domains) = sp_usb.thunderbolt.as_ref() {
+                print_thunderbolt(domains, settings);
+            }
+            if let Some(profile) = sp_usb.gadget.as_ref() {
+                print_gadgets(profile, settings);
+            }
         }
     } else {
         {
@@ -3050,10 +5698,203 @@ pub fn print(sp_usb: &SystemProfile, settings: &PrintSettings) {
             let devs = sp_usb.flattened_devices();
 
             if settings.json {
-                println!("{}", serde_json::to_string_pretty(&devs).unwrap());
+                outputln!("{}", serde_json::to_string_pretty(&devs).unwrap());
+            } else if settings.group_devices == Group::Class {
+                print_class_grouped(group_devices_by_class(&devs), settings);
             } else {
                 print_flattened_devices(&devs, settings);
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::{read_json_dump, DeviceSpeed};
+    use crate::usb::{Speed, Version};
+
+    #[test]
+    fn test_negotiated_speed_highlight() {
+        let mut device = Device {
+            device_speed: Some(DeviceSpeed::SpeedValue(Speed::HighSpeed)),
+            bcd_usb: Some(Version(3, 2, 0)),
+            ..Default::default()
+        };
+        let settings = PrintSettings {
+            colours: Some(colour::ColourTheme::new()),
+            ..Default::default()
+        };
+        let block = DeviceBlocks::NegotiatedSpeed;
+
+        // value is plain so it pads and truncates like the others
+        let value = block
+            .format_value(&device, &HashMap::new(), &settings)
+            .unwrap();
+        assert_eq!(
+            value,
+            format!(
+                "{:>10}",
+                Speed::HighSpeed.to_units_string(SpeedUnits::Decimal)
+            )
+        );
+        assert!(block.highlight(&device, &settings));
+        assert!(!DeviceBlocks::Speed.highlight(&device, &settings));
+
+        device.bcd_usb = Some(Version(2, 0, 0));
+        assert!(!block.highlight(&device, &settings));
+    }
+
+    /// Devices below the 'Virtual USB1.1 HUB' in the dump: the J-Link repeated at ports 1-3, Black Magic Probe at 8 then another J-Link at 9
+    fn hub_siblings() -> Vec<Device> {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let hub = spusb.get_node("2-2").unwrap();
+        let children = hub.devices.as_ref().unwrap();
+        let (jlink, bmp) = (&children[0], &children[1]);
+        [(jlink, 1), (jlink, 2), (jlink, 3), (bmp, 8), (jlink, 9)]
+            .into_iter()
+            .map(|(d, port)| {
+                let mut d = d.clone();
+                d.location_id.tree_positions = vec![2, port];
+                d.location_id.number = 20 + port;
+                d
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compact_siblings() {
+        let devices = hub_siblings();
+        let settings = PrintSettings {
+            compact: true,
+            ..Default::default()
+        };
+        let compacted: Vec<(u8, usize)> = compact_siblings(&devices, &settings)
+            .into_iter()
+            .map(|(d, count)| (d.location_id.number, count))
+            .collect();
+        // only consecutive siblings are grouped
+        assert_eq!(compacted, vec![(21, 3), (28, 1), (29, 1)]);
+
+        // not when verbose or not enabled
+        for settings in [
+            PrintSettings {
+                compact: true,
+                verbosity: 1,
+                ..Default::default()
+            },
+            PrintSettings::default(),
+        ] {
+            assert!(compact_siblings(&devices, &settings)
+                .iter()
+                .all(|(_, count)| *count == 1));
+        }
+
+        // a highlighted device is not grouped with ones that are not
+        let settings = PrintSettings {
+            compact: true,
+            highlight: Some(Filter {
+                number: Some(22),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let compacted: Vec<(u8, usize)> = compact_siblings(&devices, &settings)
+            .into_iter()
+            .map(|(d, count)| (d.location_id.number, count))
+            .collect();
+        assert_eq!(compacted, vec![(21, 1), (22, 1), (23, 1), (28, 1), (29, 1)]);
+    }
+
+    #[test]
+    fn test_depth_limited_hidden() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let hub = spusb.get_node("2-2").unwrap();
+        let settings = PrintSettings {
+            tree: true,
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        // hub is at depth 1 so its J-Link and Black Magic Probe are hidden
+        assert!(!depth_limited(0, &settings));
+        assert!(depth_limited(1, &settings));
+        assert!(depth_limited(2, &settings));
+        let hidden = hub.iter().count() - 1;
+        assert_eq!(hidden, 2);
+        assert_eq!(line_suffix(1, hidden, &settings), " (+2 hidden)");
+        assert_eq!(line_suffix(3, hidden, &settings), " x3 (+2 hidden)");
+        assert_eq!(line_suffix(1, 0, &settings), "");
+
+        // only applies to the tree
+        let settings = PrintSettings {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        assert!(!depth_limited(1, &settings));
+        assert!(!depth_limited(
+            1,
+            &PrintSettings {
+                tree: true,
+                ..Default::default()
+            }
+        ));
+    }
+
+    #[test]
+    fn test_dev_path_from_dump() {
+        let device = Device {
+            location_id: crate::profiler::DeviceLocation {
+                bus: 1,
+                number: 4,
+                tree_positions: vec![2],
+            },
+            ..Default::default()
+        };
+        let settings = PrintSettings::default();
+        assert_eq!(dev_path(&device, &settings), device.platform_dev_path());
+        let settings = PrintSettings {
+            from_dump: true,
+            ..Default::default()
+        };
+        assert_eq!(dev_path(&device, &settings), None);
+        assert_eq!(
+            DeviceBlocks::DevPath.json_value(&device, &settings),
+            serde_json::Value::Null
+        );
+    }
+
+    #[test]
+    fn test_block_value() {
+        let mut device = Device {
+            vendor_id: Some(0x1d50),
+            serial_num: Some(String::from("97B6 A11D")),
+            ..Default::default()
+        };
+        let settings = PrintSettings::default();
+        assert_eq!(
+            block_value(&device, &DeviceBlocks::Serial, &settings).as_deref(),
+            Some("97B6 A11D")
+        );
+        assert_eq!(
+            block_value(&device, &DeviceBlocks::VendorId, &settings).as_deref(),
+            Some("0x1d50")
+        );
+        // missing rather than the '-' placeholder
+        device.serial_num = None;
+        assert_eq!(block_value(&device, &DeviceBlocks::Serial, &settings), None);
+        assert_eq!(block_value(&device, &DeviceBlocks::Driver, &settings), None);
+    }
+
+    #[test]
+    fn test_truncate_string_emoji_presentation() {
+        // variation selector makes the str wider than the sum of its chars
+        let mut string = String::from("ab\u{2764}\u{FE0F}cdef");
+        truncate_string(&mut string, 6);
+        assert_eq!(string, "ab\u{2764}...");
+        assert_eq!(string.width(), 6);
+
+        let mut string = String::from("\u{2764}\u{FE0F}\u{2764}\u{FE0F}\u{2764}\u{FE0F}");
+        truncate_string(&mut string, 5);
+        assert_eq!(string.width(), 5);
+    }
+}
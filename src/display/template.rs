@@ -0,0 +1,509 @@
+//! User output templates with a subset of the Handlebars syntax, rendered with the json of devices; see [`Template`]
+//!
+//! The supported syntax is:
+//!
+//! * `{{path}}`: value at a '.' separated path such as `{{location_id.bus}}` or `{{extra.driver}}`; `this` is the current value, `../` the parent and `@index`, `@key`, `@first` and `@last` are set in `each`. Values are not escaped.
+//! * `{{helper path}}`: value passed through a helper: `xml` escapes it for XML/HTML, `json` outputs it as json and `hex` formats a number as 4 digit hex.
+//! * `{{#each path}}..{{else}}..{{/each}}`: render the block for each item of an array or object, the else block if empty.
+//! * `{{#if path}}..{{else}}..{{/if}}` and `{{#unless path}}..{{/unless}}`: render the block if the value is truthy; false, null, 0, "" and [] are not.
+//! * `{{#*inline "name"}}..{{/inline}}` and `{{> name}}`: define and render a partial with the current value, which can be recursive to render the device tree.
+//! * `{{! comment }}` and `~` inside the braces, such as `{{~path~}}`, to trim whitespace before or after the tag.
+//!
+//! ```
+//! use cyme::display::template::Template;
+//!
+//! let template: Template = "{{#each devices}}{{hex vendor_id}}:{{hex product_id}} {{name}}\n{{/each}}".parse().unwrap();
+//! let value = serde_json::json!({"devices": [{"name": "Probe", "vendor_id": 7504, "product_id": 24600}]});
+//! assert_eq!(template.render(&value), "1d50:6018 Probe\n");
+//! ```
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::error::{Error, ErrorKind, Result};
+use crate::profiler::{Device, SystemProfile};
+
+/// Maximum depth of partials rendering partials; the device tree is far shallower
+const MAX_PARTIAL_DEPTH: usize = 64;
+
+/// Helper applied to a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Helper {
+    Xml,
+    Json,
+    Hex,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Text(String),
+    Value {
+        helper: Option<Helper>,
+        path: String,
+    },
+    Each {
+        path: String,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    If {
+        path: String,
+        negate: bool,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    Partial(String),
+}
+
+/// Tag between `{{` and `}}` or text between them
+#[derive(Debug)]
+enum Token<'a> {
+    Text(String),
+    Tag(&'a str),
+}
+
+/// A parsed output template
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Template {
+    nodes: Vec<Node>,
+    partials: HashMap<String, Vec<Node>>,
+}
+
+impl FromStr for Template {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let tokens = tokenize(s)?;
+        let mut partials = HashMap::new();
+        let mut iter = tokens.into_iter();
+        let (nodes, end) = parse(&mut iter, &mut partials)?;
+        if let Some(end) = end {
+            return Err(parse_error(&format!("unexpected {{{{{}}}}}", end)));
+        }
+
+        let used = std::iter::once(&nodes)
+            .chain(partials.values())
+            .flat_map(|n| partial_names(n));
+        for name in used {
+            if !partials.contains_key(name) {
+                return Err(parse_error(&format!("partial '{}' is not defined", name)));
+            }
+        }
+
+        Ok(Template { nodes, partials })
+    }
+}
+
+impl Template {
+    /// Read and parse the template at `file_path`
+    pub fn from_file<P: AsRef<Path>>(file_path: P) -> Result<Self> {
+        let data = fs::read_to_string(&file_path).map_err(|e| {
+            Error::new(
+                ErrorKind::Io,
+                &format!(
+                    "Failed to read template {:?}; Error({})",
+                    file_path.as_ref(),
+                    e
+                ),
+            )
+        })?;
+        data.parse().map_err(|e: Error| {
+            Error::new(
+                ErrorKind::Parsing,
+                &format!(
+                    "Failed to parse template {:?}; {}",
+                    file_path.as_ref(),
+                    e.message()
+                ),
+            )
+        })
+    }
+
+    /// Render the template with `context` as the root value
+    pub fn render(&self, context: &Value) -> String {
+        let mut out = String::new();
+        let mut stack = vec![Frame::new(context)];
+        self.render_nodes(&self.nodes, &mut stack, 0, &mut out);
+        out
+    }
+
+    fn render_nodes<'a>(
+        &'a self,
+        nodes: &'a [Node],
+        stack: &mut Vec<Frame<'a>>,
+        depth: usize,
+        out: &mut String,
+    ) {
+        for node in nodes {
+            match node {
+                Node::Text(t) => out.push_str(t),
+                Node::Value { helper, path } => {
+                    if let Some(value) = lookup(stack, path) {
+                        out.push_str(&format_value(&value, *helper));
+                    }
+                }
+                Node::If {
+                    path,
+                    negate,
+                    body,
+                    otherwise,
+                } => {
+                    let truthy = lookup(stack, path).is_some_and(|v| is_truthy(&v));
+                    let nodes = if truthy != *negate { body } else { otherwise };
+                    self.render_nodes(nodes, stack, depth, out);
+                }
+                Node::Each {
+                    path,
+                    body,
+                    otherwise,
+                } => {
+                    let items: Vec<(Option<&'a str>, &'a Value)> = match lookup_ref(stack, path) {
+                        Some(Value::Array(a)) => a.iter().map(|v| (None, v)).collect(),
+                        Some(Value::Object(o)) => {
+                            o.iter().map(|(k, v)| (Some(k.as_str()), v)).collect()
+                        }
+                        _ => Vec::new(),
+                    };
+                    if items.is_empty() {
+                        self.render_nodes(otherwise, stack, depth, out);
+                    }
+                    let len = items.len();
+                    for (i, (key, value)) in items.into_iter().enumerate() {
+                        stack.push(Frame {
+                            value,
+                            index: Some(i),
+                            key,
+                            last: i + 1 == len,
+                        });
+                        self.render_nodes(body, stack, depth, out);
+                        stack.pop();
+                    }
+                }
+                Node::Partial(name) => {
+                    if depth >= MAX_PARTIAL_DEPTH {
+                        log::warn!(
+                            "Partial '{}' exceeds the maximum depth {}",
+                            name,
+                            MAX_PARTIAL_DEPTH
+                        );
+                        continue;
+                    }
+                    if let Some(nodes) = self.partials.get(name) {
+                        self.render_nodes(nodes, stack, depth + 1, out);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Json value of `device` for templates: the serialized device with a 'port_path' added to it and the devices connected to it
+pub fn device_value(device: &Device) -> Value {
+    let mut value = serde_json::to_value(device).unwrap_or_default();
+    add_port_paths(device, &mut value);
+    value
+}
+
+/// Json value of `spusb` for templates; devices are those of [`device_value`]
+pub fn profile_value(spusb: &SystemProfile) -> Value {
+    let mut value = serde_json::to_value(spusb).unwrap_or_default();
+    let buses = value.get_mut("buses").and_then(|b| b.as_array_mut());
+    for (bus, bus_value) in spusb.buses.iter().zip(buses.into_iter().flatten()) {
+        if let (Some(devices), Some(Value::Array(values))) =
+            (bus.devices.as_ref(), bus_value.get_mut("devices"))
+        {
+            for (d, v) in devices.iter().zip(values.iter_mut()) {
+                add_port_paths(d, v);
+            }
+        }
+    }
+    value
+}
+
+fn add_port_paths(device: &Device, value: &mut Value) {
+    if let Some(object) = value.as_object_mut() {
+        object.insert("port_path".to_string(), Value::from(device.port_path()));
+        if let (Some(devices), Some(Value::Array(values))) =
+            (device.devices.as_ref(), object.get_mut("devices"))
+        {
+            for (d, v) in devices.iter().zip(values.iter_mut()) {
+                add_port_paths(d, v);
+            }
+        }
+    }
+}
+
+/// Value being rendered and its position in an `each`
+struct Frame<'a> {
+    value: &'a Value,
+    index: Option<usize>,
+    key: Option<&'a str>,
+    last: bool,
+}
+
+impl<'a> Frame<'a> {
+    fn new(value: &'a Value) -> Self {
+        Frame {
+            value,
+            index: None,
+            key: None,
+            last: false,
+        }
+    }
+}
+
+/// Names of partials rendered by `nodes`
+fn partial_names(nodes: &[Node]) -> Vec<&str> {
+    nodes
+        .iter()
+        .flat_map(|n| match n {
+            Node::Partial(name) => vec![name.as_str()],
+            Node::Each {
+                body, otherwise, ..
+            }
+            | Node::If {
+                body, otherwise, ..
+            } => {
+                let mut names = partial_names(body);
+                names.extend(partial_names(otherwise));
+                names
+            }
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+fn parse_error(message: &str) -> Error {
+    Error::new(
+        ErrorKind::Parsing,
+        &format!("Invalid template: {}", message),
+    )
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest = s;
+    let mut trim_next = false;
+    while !rest.is_empty() {
+        let (text, tag) = match rest.find("{{") {
+            Some(i) => (&rest[..i], Some(&rest[i..])),
+            None => (rest, None),
+        };
+        let mut text = if trim_next { text.trim_start() } else { text };
+        trim_next = false;
+
+        let Some(tag) = tag else {
+            tokens.push(Token::Text(text.to_string()));
+            break;
+        };
+        // triple braces are the same as double since values are never escaped
+        let (open, close) = if tag.starts_with("{{{") {
+            ("{{{", "}}}")
+        } else {
+            ("{{", "}}")
+        };
+        let end = tag
+            .find(close)
+            .ok_or_else(|| parse_error(&format!("unclosed '{}'", open)))?;
+        let mut inner = &tag[open.len()..end];
+        if let Some(i) = inner.strip_prefix('~') {
+            inner = i;
+            text = text.trim_end();
+        }
+        if let Some(i) = inner.strip_suffix('~') {
+            inner = i;
+            trim_next = true;
+        }
+
+        if !text.is_empty() {
+            tokens.push(Token::Text(text.to_string()));
+        }
+        let inner = inner.trim();
+        if !inner.starts_with('!') {
+            tokens.push(Token::Tag(inner));
+        }
+        rest = &tag[end + close.len()..];
+    }
+    Ok(tokens)
+}
+
+/// Parse nodes until a `/` or `else` tag, which is returned
+fn parse<'a, I: Iterator<Item = Token<'a>>>(
+    tokens: &mut I,
+    partials: &mut HashMap<String, Vec<Node>>,
+) -> Result<(Vec<Node>, Option<&'a str>)> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        let tag = match token {
+            Token::Text(t) => {
+                nodes.push(Node::Text(t));
+                continue;
+            }
+            Token::Tag(tag) => tag,
+        };
+
+        if tag == "else" || tag.starts_with('/') {
+            return Ok((nodes, Some(tag)));
+        } else if let Some(name) = tag.strip_prefix('>') {
+            nodes.push(Node::Partial(name.trim().to_string()));
+        } else if let Some(block) = tag.strip_prefix('#') {
+            let (helper, arg) = block
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| parse_error(&format!("{{{{#{}}}}} requires an argument", block)))?;
+            let arg = arg.trim();
+            let (body, end) = parse(tokens, partials)?;
+            let (otherwise, end) = match end {
+                Some("else") => parse(tokens, partials)?,
+                end => (Vec::new(), end),
+            };
+            let name = helper.trim_start_matches('*');
+            if end.and_then(|e| e.strip_prefix('/')).map(str::trim) != Some(name) {
+                return Err(parse_error(&format!(
+                    "{{{{#{}}}}} is not closed with {{{{/{}}}}}",
+                    helper, name
+                )));
+            }
+            let path = arg.to_string();
+            match helper {
+                "each" => nodes.push(Node::Each {
+                    path,
+                    body,
+                    otherwise,
+                }),
+                "if" | "unless" => nodes.push(Node::If {
+                    path,
+                    negate: helper == "unless",
+                    body,
+                    otherwise,
+                }),
+                "*inline" => {
+                    partials.insert(arg.trim_matches(['"', '\'']).to_string(), body);
+                }
+                _ => return Err(parse_error(&format!("unknown block helper '{}'", helper))),
+            }
+        } else {
+            let node = match tag.split_once(char::is_whitespace) {
+                Some((helper, path)) => Node::Value {
+                    helper: Some(match helper {
+                        "xml" => Helper::Xml,
+                        "json" => Helper::Json,
+                        "hex" => Helper::Hex,
+                        _ => return Err(parse_error(&format!("unknown helper '{}'", helper))),
+                    }),
+                    path: path.trim().to_string(),
+                },
+                None => Node::Value {
+                    helper: None,
+                    path: tag.to_string(),
+                },
+            };
+            nodes.push(node);
+        }
+    }
+    Ok((nodes, None))
+}
+
+/// Value at `path` from the top of `stack`, including the `@` data of the `each` frame
+fn lookup(stack: &[Frame], path: &str) -> Option<Value> {
+    let frame = stack.last()?;
+    match path {
+        "@index" => frame.index.map(Value::from),
+        "@key" => frame.key.map(Value::from),
+        "@first" => frame.index.map(|i| Value::from(i == 0)),
+        "@last" => frame.index.map(|_| Value::from(frame.last)),
+        _ => lookup_ref(stack, path).cloned(),
+    }
+}
+
+/// Value at `path` from the top of `stack`, moving up a frame for each leading '../'
+fn lookup_ref<'a>(stack: &[Frame<'a>], path: &str) -> Option<&'a Value> {
+    let mut path = path;
+    let mut up = 0;
+    while let Some(p) = path.strip_prefix("../") {
+        path = p;
+        up += 1;
+    }
+    let frame = stack.get(stack.len().checked_sub(up + 1)?)?;
+    let path = path
+        .strip_prefix("this")
+        .map(|p| p.trim_start_matches(['.', '/']))
+        .unwrap_or(path);
+
+    path.split(['.', '/'])
+        .filter(|p| !p.is_empty())
+        .try_fold(frame.value, |value, key| match value {
+            Value::Object(o) => o.get(key),
+            Value::Array(a) => key.parse::<usize>().ok().and_then(|i| a.get(i)),
+            _ => None,
+        })
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|f| f != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+fn format_value(value: &Value, helper: Option<Helper>) -> String {
+    match (helper, value) {
+        (Some(Helper::Json), v) => v.to_string(),
+        (Some(Helper::Hex), Value::Number(n)) => n
+            .as_u64()
+            .map_or_else(|| n.to_string(), |n| format!("{:04x}", n)),
+        (Some(Helper::Xml), v) => super::xml::escape(&format_value(v, None)),
+        (_, Value::Null) => String::new(),
+        (_, Value::String(s)) => s.to_owned(),
+        (_, v) => v.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::read_json_dump;
+
+    #[test]
+    fn test_render_template() {
+        let value = serde_json::json!({
+            "name": "Bus",
+            "devices": [
+                {"name": "Hub & Co", "vendor_id": 1, "devices": [{"name": "Probe", "vendor_id": 7504}]},
+                {"name": "Mouse", "devices": []}
+            ]
+        });
+        let template: Template = r#"{{#*inline "device"}}{{../name}}/{{xml name}}{{#if vendor_id}} {{hex vendor_id}}{{/if}}{{#unless @last}},{{/unless}}
+{{~#each devices}} [{{> device}}]{{/each}}{{/inline}}
+{{~#each devices}}{{@index}}: {{> device}}{{else}}none{{/each}}"#
+            .parse()
+            .unwrap();
+        assert_eq!(
+            template.render(&value),
+            "0: Bus/Hub &amp; Co 0001, [Hub & Co/Probe 1d50]1: Bus/Mouse"
+        );
+        assert_eq!(template.render(&serde_json::json!({})), "none");
+
+        assert!("{{#each devices}}".parse::<Template>().is_err());
+        assert!("{{#if a}}{{/each}}".parse::<Template>().is_err());
+        assert!("{{> device}}".parse::<Template>().is_err());
+        assert!("{{upper name}}".parse::<Template>().is_err());
+    }
+
+    #[test]
+    fn test_device_value() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let template: Template = "{{port_path}} {{location_id.bus}} {{extra.driver}}"
+            .parse()
+            .unwrap();
+        let device = spusb.get_node("2-2.8").unwrap();
+        assert!(template
+            .render(&device_value(device))
+            .starts_with("2-2.8 2 "));
+    }
+}
@@ -0,0 +1,217 @@
+//! XML output modeled on the export of the Windows USBView and USB Device Tree Viewer tools so cyme output can be used with workflows built around them; see [`to_xml`]
+//!
+//! Host controllers contain the devices connected to them, nested by hub when the [`SystemProfile`] is a tree. Descriptors are elements with their fields as attributes, named as in the USB specification like the USBView report, and values in hex. Device, configuration and interface strings and other details cyme has are added as capitalised attributes.
+//!
+//! ```
+//! use cyme::display::xml::to_xml;
+//! use cyme::profiler::read_json_dump;
+//!
+//! let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+//! let xml = to_xml(&spusb);
+//! assert!(xml.contains(r#"idVendor="0x1D50" idProduct="0x6018""#));
+//! ```
+use std::fmt::Write;
+
+use crate::profiler::{Bus, Device, SystemProfile};
+use crate::usb::{Configuration, Endpoint, Interface};
+
+/// Root element
+pub const ROOT: &str = "UsbTree";
+
+/// Element with attributes being written
+struct Element {
+    name: &'static str,
+    attributes: Vec<(&'static str, String)>,
+}
+
+impl Element {
+    fn new(name: &'static str) -> Self {
+        Element {
+            name,
+            attributes: Vec::new(),
+        }
+    }
+
+    fn attr<T: ToString>(mut self, name: &'static str, value: T) -> Self {
+        self.attributes.push((name, value.to_string()));
+        self
+    }
+
+    fn attr_opt<T: ToString>(self, name: &'static str, value: Option<T>) -> Self {
+        match value {
+            Some(v) => self.attr(name, v),
+            None => self,
+        }
+    }
+
+    /// Write the opening tag, self-closing if `empty`
+    fn open(&self, out: &mut String, indent: usize, empty: bool) {
+        let _ = write!(out, "{:indent$}<{}", "", self.name, indent = indent);
+        for (name, value) in self.attributes.iter() {
+            let _ = write!(out, " {}=\"{}\"", name, escape(value));
+        }
+        out.push_str(if empty { "/>\n" } else { ">\n" });
+    }
+
+    fn close(&self, out: &mut String, indent: usize) {
+        let _ = writeln!(out, "{:indent$}</{}>", "", self.name, indent = indent);
+    }
+}
+
+/// Serialize `spusb` to the XML document
+pub fn to_xml(spusb: &SystemProfile) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    let root = Element::new(ROOT).attr(
+        "Generator",
+        format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")),
+    );
+    root.open(&mut out, 0, false);
+    for bus in spusb.buses.iter() {
+        write_bus(bus, &mut out, 2);
+    }
+    root.close(&mut out, 0);
+    out
+}
+
+fn write_bus(bus: &Bus, out: &mut String, indent: usize) {
+    let element = Element::new("HostController")
+        .attr("Name", &bus.name)
+        .attr_opt("BusNumber", bus.usb_bus_number)
+        .attr_opt("VendorId", bus.pci_vendor.map(hex16))
+        .attr_opt("DeviceId", bus.pci_device.map(hex16))
+        .attr_opt("Revision", bus.pci_revision.map(hex16))
+        .attr("Controller", &bus.host_controller);
+    let devices = bus.devices.as_deref().unwrap_or_default();
+    element.open(out, indent, devices.is_empty());
+    if !devices.is_empty() {
+        for device in devices {
+            write_device(device, out, indent + 2);
+        }
+        element.close(out, indent);
+    }
+}
+
+fn write_device(device: &Device, out: &mut String, indent: usize) {
+    let extra = device.extra.as_ref();
+    let element = Element::new("UsbDevice")
+        .attr("Port", device.port_path())
+        .attr("DeviceAddress", hex8(device.location_id.number))
+        .attr_opt("Speed", device.device_speed.as_ref())
+        .attr_opt("Manufacturer", device.manufacturer.as_ref())
+        .attr("Product", &device.name)
+        .attr_opt("SerialNumber", device.serial_num.as_ref())
+        .attr_opt("Driver", extra.and_then(|e| e.driver.as_ref()));
+    element.open(out, indent, false);
+
+    let descriptor = Element::new("DeviceDescriptor")
+        .attr("bLength", hex8(18))
+        .attr("bDescriptorType", hex8(1))
+        .attr_opt("bcdUSB", device.bcd_usb.map(|v| hex16(v.into())))
+        .attr_opt("bDeviceClass", device.base_class_code().map(hex8))
+        .attr_opt("bDeviceSubClass", device.sub_class.map(hex8))
+        .attr_opt("bDeviceProtocol", device.protocol.map(hex8))
+        .attr_opt("bMaxPacketSize0", extra.map(|e| hex8(e.max_packet_size)))
+        .attr_opt("idVendor", device.vendor_id.map(hex16))
+        .attr_opt("idProduct", device.product_id.map(hex16))
+        .attr_opt("bcdDevice", device.bcd_device.map(|v| hex16(v.into())))
+        .attr_opt("iManufacturer", extra.map(|e| hex8(e.string_indexes.0)))
+        .attr_opt("iProduct", extra.map(|e| hex8(e.string_indexes.1)))
+        .attr_opt("iSerialNumber", extra.map(|e| hex8(e.string_indexes.2)))
+        .attr_opt(
+            "bNumConfigurations",
+            extra.map(|e| hex8(e.configurations.len() as u8)),
+        );
+    descriptor.open(out, indent + 2, true);
+
+    for config in extra.iter().flat_map(|e| e.configurations.iter()) {
+        write_configuration(config, out, indent + 2);
+    }
+    for child in device.devices.iter().flatten() {
+        write_device(child, out, indent + 2);
+    }
+    element.close(out, indent);
+}
+
+fn write_configuration(config: &Configuration, out: &mut String, indent: usize) {
+    let element = Element::new("ConfigurationDescriptor")
+        .attr("bLength", hex8(config.length))
+        .attr("bDescriptorType", hex8(2))
+        .attr("wTotalLength", hex16(config.total_length))
+        .attr("bNumInterfaces", hex8(config.interfaces.len() as u8))
+        .attr("bConfigurationValue", hex8(config.number))
+        .attr("iConfiguration", hex8(config.string_index))
+        .attr("bmAttributes", hex8(config.attributes_value()))
+        .attr("MaxPower", &config.max_power)
+        .attr("Configuration", &config.name)
+        .attr("Active", config.active);
+    element.open(out, indent, config.interfaces.is_empty());
+    if !config.interfaces.is_empty() {
+        for interface in config.interfaces.iter() {
+            write_interface(interface, out, indent + 2);
+        }
+        element.close(out, indent);
+    }
+}
+
+fn write_interface(interface: &Interface, out: &mut String, indent: usize) {
+    let element = Element::new("InterfaceDescriptor")
+        .attr("bLength", hex8(interface.length))
+        .attr("bDescriptorType", hex8(4))
+        .attr("bInterfaceNumber", hex8(interface.number))
+        .attr("bAlternateSetting", hex8(interface.alt_setting))
+        .attr("bNumEndpoints", hex8(interface.endpoints.len() as u8))
+        .attr("bInterfaceClass", hex8(u8::from(interface.class)))
+        .attr("bInterfaceSubClass", hex8(interface.sub_class))
+        .attr("bInterfaceProtocol", hex8(interface.protocol))
+        .attr("iInterface", hex8(interface.string_index))
+        .attr_opt("Interface", interface.name.as_ref())
+        .attr_opt("Driver", interface.driver.as_ref());
+    element.open(out, indent, interface.endpoints.is_empty());
+    if !interface.endpoints.is_empty() {
+        for endpoint in interface.endpoints.iter() {
+            write_endpoint(endpoint, out, indent + 2);
+        }
+        element.close(out, indent);
+    }
+}
+
+fn write_endpoint(endpoint: &Endpoint, out: &mut String, indent: usize) {
+    Element::new("EndpointDescriptor")
+        .attr("bLength", hex8(endpoint.length))
+        .attr("bDescriptorType", hex8(5))
+        .attr("bEndpointAddress", hex8(endpoint.address.address))
+        .attr("bmAttributes", hex8(endpoint.attributes()))
+        .attr("wMaxPacketSize", hex16(endpoint.max_packet_size))
+        .attr("bInterval", hex8(endpoint.interval))
+        .open(out, indent, true);
+}
+
+/// USBView style byte value such as '0x0A'
+fn hex8(v: u8) -> String {
+    format!("0x{:02X}", v)
+}
+
+/// USBView style word value such as '0x1D50'
+fn hex16(v: u16) -> String {
+    format!("0x{:04X}", v)
+}
+
+/// Escape `s` for XML text or attribute values
+///
+/// Control characters other than tab, newline and carriage return cannot be in an XML 1.0 document even as character references, so are replaced with U+FFFD; device strings can have them.
+pub(crate) fn escape(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => ret.push_str("&amp;"),
+            '<' => ret.push_str("&lt;"),
+            '>' => ret.push_str("&gt;"),
+            '"' => ret.push_str("&quot;"),
+            '\'' => ret.push_str("&apos;"),
+            '\t' | '\n' | '\r' => ret.push(c),
+            '\0'..='\x1f' => ret.push(char::REPLACEMENT_CHARACTER),
+            c => ret.push(c),
+        }
+    }
+    ret
+}
@@ -1,9 +1,16 @@
 //! Error type used within crate with From for commonly used crate errors
-use std::error;
-use std::{fmt, io};
+#[cfg(not(feature = "std"))]
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::{error, io};
 
 /// Result type used within crate
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 /// Contained with [`ErrorKind`] to provide more context
 #[derive(Debug, PartialEq, Clone)]
@@ -125,6 +132,7 @@ impl Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl error::Error for Error {}
 
 impl fmt::Display for Error {
@@ -137,6 +145,7 @@ impl fmt::Display for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<io::Error> for Error {
     fn from(error: io::Error) -> Self {
         Error {
@@ -146,6 +155,7 @@ impl From<io::Error> for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<serde_json::Error> for Error {
     fn from(error: serde_json::Error) -> Self {
         Error {
@@ -155,8 +165,8 @@ impl From<serde_json::Error> for Error {
     }
 }
 
-impl From<std::string::FromUtf8Error> for Error {
-    fn from(error: std::string::FromUtf8Error) -> Self {
+impl From<alloc::string::FromUtf8Error> for Error {
+    fn from(error: alloc::string::FromUtf8Error) -> Self {
         Error {
             kind: ErrorKind::Other("FromUtf8Error"),
             message: error.to_string(),
@@ -164,6 +174,7 @@ impl From<std::string::FromUtf8Error> for Error {
     }
 }
 
+#[cfg(feature = "std")]
 impl From<Error> for io::Error {
     fn from(val: Error) -> Self {
         io::Error::new(io::ErrorKind::Other, val.message)
@@ -0,0 +1,228 @@
+//! Translation of display headings, class names and enum strings so cyme output can be read in other languages
+//!
+//! With the 'i18n' feature the translations are [Fluent](https://projectfluent.org) resources built into cyme for each language, loaded for a locale by [`Translator::new`] and used by the display with [`crate::display::PrintSettings::translator`]; the cyme binary uses the locale of the environment from [`locale_from_env`]. Anything without a translation, and everything without the feature, is the English string. Only the human readable display is translated: json, lsusb compatible output and the block and class names used as arguments are not.
+//!
+//! Messages are identified by the kebab-case type and variant from [`message_id`], such as `device-blocks-bus-number` for the heading of [`crate::display::DeviceBlocks::BusNumber`] or `base-class-hid` for [`crate::usb::BaseClass::Hid`]:
+//!
+//! ```
+//! use cyme::i18n::{self, Translator};
+//! use cyme::usb::BaseClass;
+//!
+//! assert_eq!(i18n::message_id(&BaseClass::MassStorage), "base-class-mass-storage");
+//! // English has no translations so is always the Display string
+//! let translator = Translator::new("en-GB");
+//! assert_eq!(translator.translate_enum(&BaseClass::Hid), "Hid");
+//! ```
+use heck::ToKebabCase;
+use std::borrow::Cow;
+use std::fmt;
+
+#[cfg(feature = "i18n")]
+use fluent_bundle::{concurrent::FluentBundle, FluentResource};
+
+/// Fluent resource of each language with translations, by language code
+#[cfg(feature = "i18n")]
+pub const RESOURCES: &[(&str, &str)] = &[
+    ("de", include_str!("i18n/de.ftl")),
+    ("es", include_str!("i18n/es.ftl")),
+];
+
+#[cfg(feature = "i18n")]
+type Bundle = FluentBundle<FluentResource>;
+
+/// Locale of the environment from LC_ALL, LC_MESSAGES or LANG in that order, as gettext, in BCP 47 form such as 'de-DE'
+///
+/// `None` if none are set or the locale is 'C' or 'POSIX', which are English.
+pub fn locale_from_env() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|v| std::env::var(v).ok())
+        .find(|v| !v.is_empty())
+        .and_then(|v| {
+            // strip encoding and modifier: 'de_DE.UTF-8@euro'
+            let locale = v.split(['.', '@']).next().unwrap_or_default();
+            match locale {
+                "" | "C" | "POSIX" => None,
+                l => Some(l.replace('_', "-")),
+            }
+        })
+}
+
+/// Fluent message id of an enum `value`: the kebab-case type name then variant, such as `base-class-hid`
+pub fn message_id<T: fmt::Debug>(value: &T) -> String {
+    let type_name = std::any::type_name::<T>();
+    let type_name = type_name
+        .split('<')
+        .next()
+        .and_then(|t| t.rsplit("::").next())
+        .unwrap_or(type_name);
+    let debug = format!("{:?}", value);
+    // only the variant of tuple and struct variants
+    let variant = debug.split(['(', ' ', '{']).next().unwrap_or_default();
+    format!("{}-{}", type_name.to_kebab_case(), variant.to_kebab_case())
+}
+
+/// Translations of the language of a locale; the default translates nothing so is English
+#[derive(Default)]
+pub struct Translator {
+    #[cfg(feature = "i18n")]
+    bundle: Option<Bundle>,
+}
+
+impl fmt::Debug for Translator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Translator")
+            .field("translated", &self.is_translated())
+            .finish()
+    }
+}
+
+impl Translator {
+    /// Translate with the resource for the language of `locale` such as 'de-DE' or 'es'
+    ///
+    /// Output is English if there is not one, or without the 'i18n' feature.
+    #[cfg(feature = "i18n")]
+    pub fn new(locale: &str) -> Self {
+        let bundle = locale
+            .parse::<unic_langid::LanguageIdentifier>()
+            .ok()
+            .and_then(|langid| {
+                let (_, source) = RESOURCES
+                    .iter()
+                    .find(|(l, _)| *l == langid.language.as_str())?;
+                let resource =
+                    FluentResource::try_new(source.to_string()).unwrap_or_else(|(r, e)| {
+                        log::warn!("Errors parsing translations for {}: {:?}", langid, e);
+                        r
+                    });
+                let mut bundle = Bundle::new_concurrent(vec![langid]);
+                // unicode isolation marks would break the column widths
+                bundle.set_use_isolating(false);
+                bundle.add_resource_overriding(resource);
+                Some(bundle)
+            });
+        log::debug!("Locale {} translated: {}", locale, bundle.is_some());
+        Translator { bundle }
+    }
+
+    /// Translate with the resource for the language of `locale` such as 'de-DE' or 'es'
+    ///
+    /// Output is English if there is not one, or without the 'i18n' feature.
+    #[cfg(not(feature = "i18n"))]
+    pub fn new(locale: &str) -> Self {
+        log::debug!("Locale {} not translated without 'i18n' feature", locale);
+        Translator::default()
+    }
+
+    /// Whether there is a resource for the locale so output is translated
+    pub fn is_translated(&self) -> bool {
+        #[cfg(feature = "i18n")]
+        return self.bundle.is_some();
+        #[cfg(not(feature = "i18n"))]
+        return false;
+    }
+
+    /// Translation of message `id`, otherwise `default`
+    #[cfg(feature = "i18n")]
+    pub fn translate<'a>(&self, id: &str, default: &'a str) -> Cow<'a, str> {
+        let Some((bundle, pattern)) = self
+            .bundle
+            .as_ref()
+            .and_then(|b| b.get_message(id).and_then(|m| m.value()).map(|p| (b, p)))
+        else {
+            return Cow::Borrowed(default);
+        };
+        let mut errors = Vec::new();
+        let value = bundle
+            .format_pattern(pattern, None, &mut errors)
+            .to_string();
+        if !errors.is_empty() {
+            log::warn!("Errors translating {}: {:?}", id, errors);
+        }
+        Cow::Owned(value)
+    }
+
+    /// Translation of message `id`, otherwise `default`
+    #[cfg(not(feature = "i18n"))]
+    pub fn translate<'a>(&self, _id: &str, default: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(default)
+    }
+
+    /// Translation of an enum `value` with its [`message_id`], otherwise its Display string
+    pub fn translate_enum<T: fmt::Debug + fmt::Display>(&self, value: &T) -> String {
+        let default = value.to_string();
+        self.translate(&message_id(value), &default).into_owned()
+    }
+}
+
+#[cfg(all(test, feature = "i18n"))]
+mod tests {
+    use super::*;
+    use crate::display::{
+        Block, BusBlocks, ConfigurationBlocks, DeviceBlocks, EndpointBlocks, InterfaceBlocks,
+        PrintSettings,
+    };
+    use crate::profiler::LocationKind;
+    use crate::usb::BaseClass;
+    use clap::ValueEnum;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_resources_parse() {
+        for (language, source) in RESOURCES {
+            if let Err((_, e)) = FluentResource::try_new(source.to_string()) {
+                panic!("{}: {:?}", language, e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_resource_ids() {
+        let mut ids: Vec<String> = DeviceBlocks::iter()
+            .map(|b| message_id(&b))
+            .chain(BusBlocks::iter().map(|b| message_id(&b)))
+            .chain(ConfigurationBlocks::iter().map(|b| message_id(&b)))
+            .chain(InterfaceBlocks::iter().map(|b| message_id(&b)))
+            .chain(EndpointBlocks::iter().map(|b| message_id(&b)))
+            .chain((0..=u8::MAX).map(|c| message_id(&BaseClass::from(c))))
+            .chain(LocationKind::value_variants().iter().map(message_id))
+            .collect();
+        ids.extend(["device-removable".into(), "device-fixed".into()]);
+        for (language, source) in RESOURCES {
+            for id in source
+                .lines()
+                .filter(|l| !l.starts_with('#'))
+                .filter_map(|l| l.split_once(" = ").map(|(id, _)| id))
+            {
+                assert!(ids.iter().any(|i| i == id), "{}: unknown {}", language, id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_translate() {
+        let translator = Translator::new("de-DE");
+        assert!(translator.is_translated());
+        assert_eq!(
+            translator.translate_enum(&BaseClass::MassStorage),
+            "Massenspeicher"
+        );
+        assert_eq!(translator.translate("not-a-message", "Default"), "Default");
+        let settings = PrintSettings {
+            translator,
+            ..Default::default()
+        };
+        assert_eq!(DeviceBlocks::Speed.localized_heading(&settings), "Tempo");
+
+        let translator = Translator::new("en-US");
+        assert!(!translator.is_translated());
+        assert_eq!(
+            translator.translate_enum(&BaseClass::MassStorage),
+            "MassStorage"
+        );
+        assert_eq!(
+            DeviceBlocks::Speed.localized_heading(&PrintSettings::default()),
+            "Speed"
+        );
+    }
+}
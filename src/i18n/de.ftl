@@ -0,0 +1,83 @@
+# German translations of the cyme display; see the i18n module docs for the message ids
+
+## Device headings
+
+device-blocks-driver = Treiber
+device-blocks-manufacturer = Hersteller
+device-blocks-product-name = Produkt
+device-blocks-vendor-name = Anbieter
+device-blocks-serial = Seriennr.
+device-blocks-speed = Tempo
+device-blocks-base-class = BasisK
+device-blocks-sub-class = UntK
+device-blocks-protocol = Prot
+device-blocks-class = Klasse
+device-blocks-power-state = Energie
+device-blocks-connected-since = Verb.
+device-blocks-link-state = Link
+device-blocks-physical-location = Ort
+device-blocks-removable = Entf.
+device-blocks-location-kind = Lage
+device-blocks-vendor-fields = HerstInfo
+device-blocks-battery = Akku
+device-blocks-media = Medien
+device-blocks-capacity = Kapazität
+device-blocks-quirks = Eigenheiten
+device-blocks-name-source = NQuelle
+
+## Bus headings
+
+bus-blocks-host-controller = Hostcontroller
+bus-blocks-host-controller-vendor = HostHersteller
+bus-blocks-host-controller-device = HostGerät
+bus-blocks-role = Rolle
+
+## Configuration, interface and endpoint headings
+
+configuration-blocks-attributes = Attribute
+interface-blocks-driver = Treiber
+interface-blocks-module = Modul
+interface-blocks-module-parameters = MParam
+interface-blocks-base-class = BasisK
+interface-blocks-sub-class = UntK
+interface-blocks-protocol = Prot
+interface-blocks-class = Klasse
+endpoint-blocks-direction = Richt
+endpoint-blocks-transfer-type = ÜbTyp
+endpoint-blocks-usage-type = NutzT
+
+## Class names
+
+base-class-use-interface-descriptor = Schnittstellendeskriptor
+base-class-audio = Audio
+base-class-cdc-communications = CDC-Kommunikation
+base-class-hid = HID
+base-class-physical = Physisch
+base-class-image = Bild
+base-class-printer = Drucker
+base-class-mass-storage = Massenspeicher
+base-class-hub = Hub
+base-class-cdc-data = CDC-Daten
+base-class-smart-card = Chipkarte
+base-class-content-security = Inhaltsschutz
+base-class-video = Video
+base-class-personal-healthcare = Gesundheit
+base-class-audio-video = Audio/Video
+base-class-billboard = Billboard
+base-class-usb-type-c-bridge = USB-Typ-C-Brücke
+base-class-bdp = BDP
+base-class-mctp = MCTP
+base-class-i3c-device = I3C-Gerät
+base-class-diagnostic = Diagnose
+base-class-wireless-controller = Drahtlos-Controller
+base-class-miscellaneous = Verschiedenes
+base-class-application-specific-interface = Anwendungsspezifisch
+base-class-vendor-specific-class = Herstellerspezifisch
+
+## Device values
+
+location-kind-internal = intern
+location-kind-external = extern
+location-kind-dock = Dock
+device-removable = entfernbar
+device-fixed = fest
@@ -0,0 +1,85 @@
+# Spanish translations of the cyme display; see the i18n module docs for the message ids
+
+## Device headings
+
+device-blocks-driver = Controlador
+device-blocks-name = Nombre
+device-blocks-manufacturer = Fabricante
+device-blocks-product-name = Producto
+device-blocks-vendor-name = Proveedor
+device-blocks-serial = Serie
+device-blocks-speed = Velocidad
+device-blocks-base-class = ClaseB
+device-blocks-protocol = Prot
+device-blocks-class = Clase
+device-blocks-power-state = Energía
+device-blocks-connected-since = Conex
+device-blocks-lanes = Carriles
+device-blocks-link-state = Enlace
+device-blocks-physical-location = Ubic
+device-blocks-removable = Extr.
+device-blocks-location-kind = Ubicación
+device-blocks-vendor-fields = InfoFab
+device-blocks-battery = Batería
+device-blocks-media = Medios
+device-blocks-capacity = Capacidad
+device-blocks-quirks = Peculiaridades
+device-blocks-name-source = FuenteN
+
+## Bus headings
+
+bus-blocks-name = Nombre
+bus-blocks-host-controller = ControladorHost
+bus-blocks-host-controller-vendor = FabHost
+bus-blocks-host-controller-device = DispHost
+bus-blocks-role = Rol
+
+## Configuration, interface and endpoint headings
+
+configuration-blocks-name = Nombre
+configuration-blocks-attributes = Atributos
+interface-blocks-name = Nombre
+interface-blocks-driver = Controlador
+interface-blocks-module = Módulo
+interface-blocks-base-class = ClaseB
+interface-blocks-protocol = Prot
+interface-blocks-class = Clase
+endpoint-blocks-transfer-type = TipoT
+endpoint-blocks-sync-type = TipoS
+endpoint-blocks-usage-type = TipoU
+
+## Class names
+
+base-class-use-interface-descriptor = Descriptor de interfaz
+base-class-audio = Audio
+base-class-cdc-communications = Comunicaciones CDC
+base-class-hid = HID
+base-class-physical = Físico
+base-class-image = Imagen
+base-class-printer = Impresora
+base-class-mass-storage = Almacenamiento masivo
+base-class-hub = Hub
+base-class-cdc-data = Datos CDC
+base-class-smart-card = Tarjeta inteligente
+base-class-content-security = Seguridad de contenido
+base-class-video = Vídeo
+base-class-personal-healthcare = Salud personal
+base-class-audio-video = Audio/Vídeo
+base-class-billboard = Billboard
+base-class-usb-type-c-bridge = Puente USB Type-C
+base-class-bdp = BDP
+base-class-mctp = MCTP
+base-class-i3c-device = Dispositivo I3C
+base-class-diagnostic = Diagnóstico
+base-class-wireless-controller = Controlador inalámbrico
+base-class-miscellaneous = Misceláneo
+base-class-application-specific-interface = Específico de aplicación
+base-class-vendor-specific-class = Específico del fabricante
+
+## Device values
+
+location-kind-internal = interno
+location-kind-external = externo
+location-kind-dock = base
+device-removable = extraíble
+device-fixed = fijo
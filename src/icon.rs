@@ -37,6 +37,14 @@ pub enum Icon {
     ClassifierSubProtocol((BaseClass, u8, u8)),
     /// Pattern match device name icon
     Name(String),
+    /// Vendor ID within an inclusive range
+    VidRange((u16, u16)),
+    /// Vendor ID with Product ID within an inclusive range
+    VidPidRange((u16, u16, u16)),
+    /// Class classifier lookup with SubClass and Protocol where `None` matches any
+    ClassifierWildcard((BaseClass, Option<u8>, Option<u8>)),
+    /// Pattern match device serial number icon
+    Serial(String),
     /// Icon for unknown vendors
     UnknownVendor,
     /// Icon for undefined classifier
@@ -53,10 +61,14 @@ pub enum Icon {
     TreeBusStart,
     /// Icon printed at end of tree before printing `Device`
     TreeDeviceTerminator,
+    /// Icon printed at end of tree before printing a highlighted `Device` and after it when listing
+    TreeDeviceHighlight,
     /// Icon printed at end of tree before printing configuration
     TreeConfigurationTerminator,
     /// Icon printed at end of tree before printing interface
     TreeInterfaceTerminator,
+    /// Icon printed at end of tree before printing an interface no driver is bound to and after it when listing
+    TreeInterfaceUnclaimed,
     /// Icon for endpoint direction
     Endpoint(Direction),
 }
@@ -79,8 +91,10 @@ impl FromStr for Icon {
                 "tree-corner" => Ok(Icon::TreeCorner),
                 "tree-bus-start" => Ok(Icon::TreeBusStart),
                 "tree-device-terminator" => Ok(Icon::TreeDeviceTerminator),
+                "tree-device-highlight" => Ok(Icon::TreeDeviceHighlight),
                 "tree-configuration-terminator" => Ok(Icon::TreeConfigurationTerminator),
                 "tree-interface-terminator" => Ok(Icon::TreeInterfaceTerminator),
+                "tree-interface-unclaimed" => Ok(Icon::TreeInterfaceUnclaimed),
                 "endpoint_in" => Ok(Icon::Endpoint(Direction::In)),
                 "endpoint_out" => Ok(Icon::Endpoint(Direction::Out)),
                 _ => Err(Error::new(
@@ -88,15 +102,17 @@ impl FromStr for Icon {
                     "Invalid Icon enum name or valued enum without value",
                 )),
             }
-        // name#pattern
-        } else if matches!(enum_name, "name") {
+        // name#pattern or serial#pattern
+        } else if matches!(enum_name, "name" | "serial") {
             #[cfg(feature = "regex_icon")]
             match regex::Regex::new(value_split[1]) {
-                Ok(_) => Ok(Icon::Name(value_split[1].to_string())),
+                Ok(_) if enum_name == "name" => Ok(Icon::Name(value_split[1].to_string())),
+                Ok(_) => Ok(Icon::Serial(value_split[1].to_string())),
                 Err(_) => Err(Error::new(
                     ErrorKind::Parsing,
                     &format!(
-                        "Invalid regex pattern in Icon::Name enum string: {}",
+                        "Invalid regex pattern in Icon::{} enum string: {}",
+                        heck::AsUpperCamelCase(enum_name),
                         value_split[1]
                     ),
                 )),
@@ -104,15 +120,24 @@ impl FromStr for Icon {
             #[cfg(not(feature = "regex_icon"))]
             Err(Error::new(
                 ErrorKind::Parsing,
-                "regex_icon feature not enabled for Icon::Name matching",
+                &format!(
+                    "regex_icon feature not enabled for Icon::{} matching",
+                    heck::AsUpperCamelCase(enum_name)
+                ),
             ))
         // enum contains value
         } else {
-            let (parse_ints, errors): (Vec<Result<u32, _>>, Vec<_>) = value_split[1]
+            // '*' is None, a wildcard
+            let (parse_ints, errors): (Vec<Result<Option<u16>, _>>, Vec<_>) = value_split[1]
                 .split(':')
-                .map(|vs| u32::from_str_radix(vs.trim_start_matches("0x"), 16))
+                .map(|vs| match vs {
+                    "*" => Ok(None),
+                    _ => {
+                        u32::from_str_radix(vs.trim_start_matches("0x"), 16).map(|v| Some(v as u16))
+                    }
+                })
                 .partition(Result::is_ok);
-            let numbers: Vec<u16> = parse_ints.into_iter().map(|v| v.unwrap() as u16).collect();
+            let values: Vec<Option<u16>> = parse_ints.into_iter().map(|v| v.unwrap()).collect();
 
             if !errors.is_empty() {
                 return Err(Error::new(
@@ -121,6 +146,25 @@ impl FromStr for Icon {
                 ));
             }
 
+            // only the sub-class and protocol of classifier-sub-protocol can be wildcards
+            if enum_name == "classifier-sub-protocol"
+                && values.len() >= 3
+                && values[0].is_some()
+                && (values[1].is_none() || values[2].is_none())
+            {
+                return Ok(Icon::ClassifierWildcard((
+                    BaseClass::from(values[0].unwrap() as u8),
+                    values[1].map(|v| v as u8),
+                    values[2].map(|v| v as u8),
+                )));
+            } else if values.iter().any(Option::is_none) {
+                return Err(Error::new(
+                    ErrorKind::Parsing,
+                    "Wildcard '*' is only valid for classifier-sub-protocol sub-class and protocol",
+                ));
+            }
+            let numbers: Vec<u16> = values.into_iter().flatten().collect();
+
             match value_split[0] {
                 "vid" => match numbers.first() {
                     Some(i) => Ok(Icon::Vid(*i)),
@@ -134,6 +178,24 @@ impl FromStr for Icon {
                     Some(slice) => Ok(Icon::VidPidMsb((slice[0], slice[1] as u8))),
                     None => Err(Error::new(ErrorKind::Parsing, "No value for enum after $")),
                 },
+                "vid-range" => match numbers.get(0..2) {
+                    Some(slice) if slice[0] <= slice[1] => Ok(Icon::VidRange((slice[0], slice[1]))),
+                    Some(_) => Err(Error::new(
+                        ErrorKind::Parsing,
+                        "Range start is greater than end",
+                    )),
+                    None => Err(Error::new(ErrorKind::Parsing, "No value for enum after $")),
+                },
+                "vid-pid-range" => match numbers.get(0..3) {
+                    Some(slice) if slice[1] <= slice[2] => {
+                        Ok(Icon::VidPidRange((slice[0], slice[1], slice[2])))
+                    }
+                    Some(_) => Err(Error::new(
+                        ErrorKind::Parsing,
+                        "Range start is greater than end",
+                    )),
+                    None => Err(Error::new(ErrorKind::Parsing, "No value for enum after $")),
+                },
                 "classifier" => match numbers.first() {
                     Some(i) => Ok(Icon::Classifier(BaseClass::from(*i as u8))),
                     None => Err(Error::new(ErrorKind::Parsing, "No value for enum after $")),
@@ -182,6 +244,21 @@ impl fmt::Display for Icon {
                 c.2
             ),
             Icon::Name(s) => write!(f, "name#{}", s),
+            Icon::VidRange((s, e)) => write!(f, "vid-range#{:04x}:{:04x}", s, e),
+            Icon::VidPidRange((v, s, e)) => {
+                write!(f, "vid-pid-range#{:04x}:{:04x}:{:04x}", v, s, e)
+            }
+            Icon::ClassifierWildcard((c, s, p)) => {
+                let wildcard = |v: &Option<u8>| v.map_or("*".into(), |v| format!("{:02x}", v));
+                write!(
+                    f,
+                    "classifier-sub-protocol#{:02x}:{}:{}",
+                    u8::from(c.to_owned()),
+                    wildcard(s),
+                    wildcard(p)
+                )
+            }
+            Icon::Serial(s) => write!(f, "serial#{}", s),
             Icon::Endpoint(Direction::In) => write!(f, "endpoint_in"),
             Icon::Endpoint(Direction::Out) => write!(f, "endpoint_out"),
             _ => {
@@ -225,8 +302,10 @@ pub static DEFAULT_UTF8_TREE: LazyLock<HashMap<Icon, &'static str>> = LazyLock::
         (Icon::TreeBlank, "   "),                        // should be same char width as above
         (Icon::TreeBusStart, "\u{25CF}"),                // "●"
         (Icon::TreeDeviceTerminator, "\u{25CB}"),        // "○"
+        (Icon::TreeDeviceHighlight, "\u{25C9}"),         // "◉"
         (Icon::TreeConfigurationTerminator, "\u{2022}"), // "•"
         (Icon::TreeInterfaceTerminator, "\u{25E6}"),     // "◦"
+        (Icon::TreeInterfaceUnclaimed, "\u{2298}"),      // "⊘"
         (Icon::Endpoint(Direction::In), "\u{2192}"),     // →
         (Icon::Endpoint(Direction::Out), "\u{2190}"),    // ←
     ])
@@ -241,8 +320,10 @@ pub static DEFAULT_ASCII_TREE: LazyLock<HashMap<Icon, &'static str>> = LazyLock:
         (Icon::TreeBlank, "   "), // inset like line
         (Icon::TreeBusStart, "/: "),
         (Icon::TreeDeviceTerminator, "O"),        // null
+        (Icon::TreeDeviceHighlight, "*"),         // null
         (Icon::TreeConfigurationTerminator, "o"), // null
         (Icon::TreeInterfaceTerminator, "."),     // null
+        (Icon::TreeInterfaceUnclaimed, "!"),      // null
         (Icon::Endpoint(Direction::In), ">"),     //
         (Icon::Endpoint(Direction::Out), "<"),    //
     ])
@@ -250,8 +331,11 @@ pub static DEFAULT_ASCII_TREE: LazyLock<HashMap<Icon, &'static str>> = LazyLock:
 
 /// Default icon lookup can be overridden by user icons with IconTheme `icons`
 ///
-/// Should probably keep fairly short but I've added things I use like debuggers, mcus as examples
+/// Should probably keep fairly short but I've added things I use like debuggers, mcus as examples. Empty without the 'icons' feature
 pub static DEFAULT_ICONS: LazyLock<HashMap<Icon, &'static str>> = LazyLock::new(|| {
+    if !cfg!(feature = "icons") {
+        return HashMap::new();
+    }
     HashMap::from([
         (Icon::UnknownVendor, "\u{f287}"),             // usb plug default 
         (Icon::Vid(0x05ac), "\u{f179}"),               // apple 
@@ -317,6 +401,145 @@ pub static DEFAULT_ICONS: LazyLock<HashMap<Icon, &'static str>> = LazyLock::new(
     ])
 });
 
+/// Rule of an [`IconTheme`] that matched a device; see [`IconTheme::get_device_icon_match`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IconMatch {
+    /// [`Icon`] key of the rule
+    pub rule: Icon,
+    /// Icon of the rule
+    pub icon: String,
+    /// Rule is from the user theme rather than [`static@DEFAULT_ICONS`]
+    pub user: bool,
+}
+
+/// Rule in `icons` with the lowest `width`, the number of values it matches beyond the device, ignoring those `width` returns `None` for
+///
+/// Ties are broken by the [`Icon`] string so the match doesn't depend on `HashMap` order
+fn narrowest_icon<V, F: Fn(&Icon) -> Option<u32>>(
+    icons: &HashMap<Icon, V>,
+    width: F,
+) -> Option<(&Icon, &V)> {
+    icons
+        .iter()
+        .filter_map(|(k, v)| width(k).map(|w| (w, k.to_string(), k, v)))
+        .min_by(|a, b| (a.0, &a.1).cmp(&(b.0, &b.1)))
+        .map(|(_, _, k, v)| (k, v))
+}
+
+/// Find icon in `icons` for `vid` and `pid` looking for `VidPid` -> `VidPidRange` -> `VidPidMsb` -> `Vid` -> `VidRange` -> `UnknownVendor`
+fn find_vidpid_icon<V>(icons: &HashMap<Icon, V>, vid: u16, pid: u16) -> Option<(&Icon, &V)> {
+    icons
+        .get_key_value(&Icon::VidPid((vid, pid)))
+        .or_else(|| {
+            narrowest_icon(icons, |i| match i {
+                Icon::VidPidRange((v, s, e)) if *v == vid && (*s..=*e).contains(&pid) => {
+                    Some((e - s) as u32)
+                }
+                _ => None,
+            })
+        })
+        .or_else(|| icons.get_key_value(&Icon::VidPidMsb((vid, (pid >> 8) as u8))))
+        .or_else(|| icons.get_key_value(&Icon::Vid(vid)))
+        .or_else(|| {
+            narrowest_icon(icons, |i| match i {
+                Icon::VidRange((s, e)) if (*s..=*e).contains(&vid) => Some((e - s) as u32),
+                _ => None,
+            })
+        })
+        .or_else(|| icons.get_key_value(&Icon::UnknownVendor))
+}
+
+/// Find icon in `icons` for the class triplet looking for `ClassifierSubProtocol` -> `ClassifierWildcard` with fewest wildcards -> `Classifier`
+fn find_classifier_icon<'a, V>(
+    icons: &'a HashMap<Icon, V>,
+    class: &BaseClass,
+    sub: u8,
+    protocol: u8,
+) -> Option<(&'a Icon, &'a V)> {
+    icons
+        .get_key_value(&Icon::ClassifierSubProtocol((
+            class.to_owned(),
+            sub,
+            protocol,
+        )))
+        .or_else(|| {
+            narrowest_icon(icons, |i| match i {
+                Icon::ClassifierWildcard((c, s, p))
+                    if c == class
+                        && s.is_none_or(|s| s == sub)
+                        && p.is_none_or(|p| p == protocol) =>
+                {
+                    Some(s.is_none() as u32 + p.is_none() as u32)
+                }
+                _ => None,
+            })
+        })
+        .or_else(|| icons.get_key_value(&Icon::Classifier(class.to_owned())))
+}
+
+/// Find icon in `icons` for Device `d` looking for `Name` -> `Serial` patterns then [`find_vidpid_icon`] or `UnknownVendor` without Vendor ID and Product ID
+fn find_device_icon<'a, V>(icons: &'a HashMap<Icon, V>, d: &Device) -> Option<(&'a Icon, &'a V)> {
+    #[cfg(feature = "regex_icon")]
+    let pattern = {
+        let is_match = |p: &str, s: &str| regex::Regex::new(p).is_ok_and(|r| r.is_match(s));
+        narrowest_icon(icons, |i| match i {
+            Icon::Name(p) if is_match(p, &d.name) => Some(0),
+            Icon::Serial(p) if d.serial_num.as_ref().is_some_and(|s| is_match(p, s)) => Some(1),
+            _ => None,
+        })
+    };
+    #[cfg(not(feature = "regex_icon"))]
+    let pattern = None;
+
+    pattern.or_else(|| match (d.vendor_id, d.product_id) {
+        (Some(vid), Some(pid)) => find_vidpid_icon(icons, vid, pid),
+        _ => icons.get_key_value(&Icon::UnknownVendor),
+    })
+}
+
+/// Emoji icons used by [`Encoding::Emoji`] in place of [`static@DEFAULT_ICONS`] when NerdFont glyphs are not available
+///
+/// Mostly by class since there are few vendor emoji. User icons valid for the encoding are used before these
+pub static DEFAULT_EMOJI_ICONS: LazyLock<HashMap<Icon, &'static str>> = LazyLock::new(|| {
+    if !cfg!(feature = "icons") {
+        return HashMap::new();
+    }
+    HashMap::from([
+        (Icon::UnknownVendor, "\u{1F50C}"),                          // 🔌
+        (Icon::Vid(0x05ac), "\u{1F34E}"),                            // apple 🍎
+        (Icon::Vid(0x1D6B), "\u{1F427}"),                            // linux foundation 🐧
+        (Icon::Vid(0x1050), "\u{1F511}"),                            // yubikey 🔑
+        (Icon::Classifier(BaseClass::Audio), "\u{1F3A7}"),           // 🎧
+        (Icon::Classifier(BaseClass::Image), "\u{1F4F7}"),           // 📷
+        (Icon::Classifier(BaseClass::Video), "\u{1F4F7}"),           // 📷
+        (Icon::Classifier(BaseClass::Printer), "\u{1F5A8}\u{FE0F}"), // 🖨️
+        (Icon::Classifier(BaseClass::MassStorage), "\u{1F4BE}"),     // 💾
+        (Icon::Classifier(BaseClass::Hub), "\u{1F500}"),             // 🔀
+        (Icon::Classifier(BaseClass::ContentSecurity), "\u{1F511}"), // 🔑
+        (Icon::Classifier(BaseClass::SmartCard), "\u{1F4B3}"),       // 💳
+        (Icon::Classifier(BaseClass::PersonalHealthcare), "\u{1FA7A}"), // 🩺
+        (Icon::Classifier(BaseClass::AudioVideo), "\u{1F3AC}"),      // 🎬
+        (Icon::Classifier(BaseClass::Diagnostic), "\u{1F527}"),      // 🔧
+        (Icon::Classifier(BaseClass::WirelessController), "\u{1F4F6}"), // 📶
+        (Icon::Classifier(BaseClass::CdcCommunications), "\u{1F4DE}"), // 📞
+        (Icon::Classifier(BaseClass::CdcData), "\u{1F4DE}"),         // 📞
+        (Icon::Classifier(BaseClass::Hid), "\u{1F5AE}"),             // 🖮
+        (
+            Icon::ClassifierSubProtocol((BaseClass::Hid, 0x01, 0x01)),
+            "\u{2328}\u{FE0F}",
+        ), // boot keyboard ⌨️
+        (
+            Icon::ClassifierSubProtocol((BaseClass::Hid, 0x01, 0x02)),
+            "\u{1F5B1}\u{FE0F}",
+        ), // boot mouse 🖱️
+        (
+            Icon::ClassifierSubProtocol((BaseClass::ApplicationSpecificInterface, 0x01, 0x01)),
+            "\u{1F41E}",
+        ), // DFU 🐞
+        (Icon::UndefinedClassifier, "\u{2754}"),                     // ❔
+    ])
+});
+
 impl IconTheme {
     /// New theme with defaults
     pub fn new() -> Self {
@@ -341,44 +564,20 @@ impl IconTheme {
         }
     }
 
-    /// Drill through [`static@DEFAULT_ICONS`] first looking for `VidPid` -> `VidPidMsb` -> `Vid` -> `UnknownVendor` -> ""
+    /// Drill through [`static@DEFAULT_ICONS`] looking for `VidPid` -> `VidPidRange` -> `VidPidMsb` -> `Vid` -> `VidRange` -> `UnknownVendor` -> ""
     pub fn get_default_vidpid_icon(vid: u16, pid: u16) -> String {
-        // try vid pid first
-        DEFAULT_ICONS
-            .get(&Icon::VidPid((vid, pid)))
-            .unwrap_or(
-                DEFAULT_ICONS
-                    .get(&Icon::VidPidMsb((vid, (pid >> 8) as u8)))
-                    .unwrap_or(
-                        DEFAULT_ICONS
-                            .get(&Icon::Vid(vid))
-                            .unwrap_or(DEFAULT_ICONS.get(&Icon::UnknownVendor).unwrap_or(&"")),
-                    ),
-            )
-            .to_string()
+        find_vidpid_icon(&DEFAULT_ICONS, vid, pid)
+            .map(|(_, v)| v.to_string())
+            .unwrap_or_default()
     }
 
-    /// Drill through `Self` `icons` if present first looking for `VidPid` -> `VidPidMsb` -> `Vid` -> `UnknownVendor` -> `get_default_vidpid_icon`
+    /// Drill through `Self` `icons` if present looking for `VidPid` -> `VidPidRange` -> `VidPidMsb` -> `Vid` -> `VidRange` -> `UnknownVendor` -> `get_default_vidpid_icon`
     pub fn get_vidpid_icon(&self, vid: u16, pid: u16) -> String {
-        if let Some(user_icons) = self.user.as_ref() {
-            // try vid pid first
-            user_icons
-                .get(&Icon::VidPid((vid, pid)))
-                .unwrap_or(
-                    user_icons
-                        .get(&Icon::VidPidMsb((vid, (pid >> 8) as u8)))
-                        .unwrap_or(
-                            user_icons.get(&Icon::Vid(vid)).unwrap_or(
-                                user_icons
-                                    .get(&Icon::UnknownVendor)
-                                    .unwrap_or(&IconTheme::get_default_vidpid_icon(vid, pid)),
-                            ),
-                        ),
-                )
-                .to_owned()
-        } else {
-            IconTheme::get_default_vidpid_icon(vid, pid)
-        }
+        self.user
+            .as_ref()
+            .and_then(|u| find_vidpid_icon(u, vid, pid))
+            .map(|(_, v)| v.to_owned())
+            .unwrap_or_else(|| IconTheme::get_default_vidpid_icon(vid, pid))
     }
 
     /// Get icon for device from static default lookup
@@ -390,34 +589,32 @@ impl IconTheme {
         }
     }
 
-    /// Get icon for Device `d` by checking `Self` using Name, Vendor ID and Product ID
-    #[cfg(feature = "regex_icon")]
-    pub fn get_device_icon(&self, d: &Device) -> String {
-        // try name first since vidpid will return UnknownVendor default icon if not found
-        // does mean regex will be built/checked for every device
-        match self.get_name_icon(&d.name) {
-            s if !s.is_empty() => s,
-            _ => {
-                if let (Some(vid), Some(pid)) = (d.vendor_id, d.product_id) {
-                    self.get_vidpid_icon(vid, pid)
-                } else {
-                    String::new()
-                }
-            }
-        }
+    /// Get the rule of `Self` or [`static@DEFAULT_ICONS`] that matches Device `d` and its icon
+    ///
+    /// User icons are checked before the defaults, each looking for `Name` -> `Serial` patterns (with 'regex_icon' feature) then Vendor ID and Product ID as [`IconTheme::get_vidpid_icon`], or `UnknownVendor` if the device has none
+    pub fn get_device_icon_match(&self, d: &Device) -> Option<IconMatch> {
+        self.user
+            .as_ref()
+            .and_then(|u| find_device_icon(u, d))
+            .map(|(k, v)| IconMatch {
+                rule: k.to_owned(),
+                icon: v.to_owned(),
+                user: true,
+            })
+            .or_else(|| {
+                find_device_icon(&DEFAULT_ICONS, d).map(|(k, v)| IconMatch {
+                    rule: k.to_owned(),
+                    icon: v.to_string(),
+                    user: false,
+                })
+            })
     }
 
-    /// Get icon for Device `d` by checking `Self` using Vendor ID and Product ID
-    #[cfg(not(feature = "regex_icon"))]
+    /// Get icon for Device `d` by checking `Self` then defaults; see [`IconTheme::get_device_icon_match`]
     pub fn get_device_icon(&self, d: &Device) -> String {
-        if let (Some(vid), Some(pid)) = (d.vendor_id, d.product_id) {
-            self.get_vidpid_icon(vid, pid)
-        } else {
-            DEFAULT_ICONS
-                .get(&Icon::UnknownVendor)
-                .unwrap_or(&"")
-                .to_string()
-        }
+        self.get_device_icon_match(d)
+            .map(|m| m.icon)
+            .unwrap_or_default()
     }
 
     /// Get icon for Bus `d` by checking `Self` using PCI Vendor and PCI Device
@@ -432,45 +629,119 @@ impl IconTheme {
         }
     }
 
-    /// Drill through `DEFAULT_ICONS` first looking for `ClassifierSubProtocol` -> `Classifier` -> `UndefinedClassifier` -> ""
+    /// Drill through `DEFAULT_ICONS` looking for `ClassifierSubProtocol` -> `ClassifierWildcard` -> `Classifier` -> `UndefinedClassifier` -> ""
     pub fn get_default_classifier_icon(class: &BaseClass, sub: u8, protocol: u8) -> String {
-        // try vid pid first
-        DEFAULT_ICONS
-            .get(&Icon::ClassifierSubProtocol((
-                class.to_owned(),
-                sub,
-                protocol,
-            )))
-            .unwrap_or(
-                DEFAULT_ICONS
-                    .get(&Icon::Classifier(class.to_owned()))
-                    .unwrap_or(DEFAULT_ICONS.get(&Icon::UndefinedClassifier).unwrap_or(&"")),
-            )
-            .to_string()
+        find_classifier_icon(&DEFAULT_ICONS, class, sub, protocol)
+            .or_else(|| DEFAULT_ICONS.get_key_value(&Icon::UndefinedClassifier))
+            .map(|(_, v)| v.to_string())
+            .unwrap_or_default()
     }
 
-    /// Drill through `Self` icons first looking for `ClassifierSubProtocol` -> `Classifier` -> `UndefinedClassifier` -> get_default_classifier_icon
+    /// Drill through `Self` icons looking for `ClassifierSubProtocol` -> `ClassifierWildcard` -> `Classifier` -> get_default_classifier_icon
     pub fn get_classifier_icon(&self, class: &BaseClass, sub: u8, protocol: u8) -> String {
-        if let Some(user_icons) = self.user.as_ref() {
-            user_icons
-                .get(&Icon::ClassifierSubProtocol((
-                    class.to_owned(),
-                    sub,
-                    protocol,
-                )))
-                .unwrap_or(
-                    user_icons
-                        .get(&Icon::Classifier(class.to_owned()))
-                        .unwrap_or(&IconTheme::get_default_classifier_icon(
-                            class, sub, protocol,
-                        )),
-                )
-                .to_owned()
-        } else {
-            IconTheme::get_default_classifier_icon(class, sub, protocol)
+        self.user
+            .as_ref()
+            .and_then(|u| find_classifier_icon(u, class, sub, protocol))
+            .map(|(_, v)| v.to_owned())
+            .unwrap_or_else(|| IconTheme::get_default_classifier_icon(class, sub, protocol))
+    }
+
+    /// Get icon for Device `d` valid for `encoding`; [`Encoding::Emoji`] uses [`IconTheme::get_emoji_device_icon`]
+    pub fn get_encoded_device_icon(&self, d: &Device, encoding: &Encoding) -> String {
+        match encoding {
+            Encoding::Emoji => self.get_emoji_device_icon(d),
+            _ => self.get_device_icon(d),
+        }
+    }
+
+    /// Get icon for Bus `d` valid for `encoding`; [`Encoding::Emoji`] uses [`static@DEFAULT_EMOJI_ICONS`] by PCI Vendor and PCI Device
+    pub fn get_encoded_bus_icon(&self, d: &Bus, encoding: &Encoding) -> String {
+        match (encoding, d.pci_vendor, d.pci_device) {
+            (Encoding::Emoji, Some(vid), Some(pid)) => self.get_emoji_vidpid_icon(vid, pid),
+            (Encoding::Emoji, _, _) => DEFAULT_EMOJI_ICONS
+                .get(&Icon::UnknownVendor)
+                .unwrap_or(&"")
+                .to_string(),
+            _ => self.get_bus_icon(d),
+        }
+    }
+
+    /// Get icon for class triplet valid for `encoding`; [`Encoding::Emoji`] uses [`IconTheme::get_emoji_classifier_icon`]
+    pub fn get_encoded_classifier_icon(
+        &self,
+        class: &BaseClass,
+        sub: u8,
+        protocol: u8,
+        encoding: &Encoding,
+    ) -> String {
+        match encoding {
+            Encoding::Emoji => self.get_emoji_classifier_icon(class, sub, protocol),
+            _ => self.get_classifier_icon(class, sub, protocol),
         }
     }
 
+    /// Drill through `Self` icons valid for [`Encoding::Emoji`] then [`static@DEFAULT_EMOJI_ICONS`] as [`IconTheme::get_vidpid_icon`]
+    pub fn get_emoji_vidpid_icon(&self, vid: u16, pid: u16) -> String {
+        self.user
+            .as_ref()
+            .and_then(|u| find_vidpid_icon(u, vid, pid))
+            .map(|(_, v)| v.to_owned())
+            .filter(|v| Encoding::Emoji.str_is_valid(v))
+            .or_else(|| {
+                find_vidpid_icon(&DEFAULT_EMOJI_ICONS, vid, pid).map(|(_, v)| v.to_string())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Drill through `Self` icons valid for [`Encoding::Emoji`] then [`static@DEFAULT_EMOJI_ICONS`] as [`IconTheme::get_classifier_icon`]
+    pub fn get_emoji_classifier_icon(&self, class: &BaseClass, sub: u8, protocol: u8) -> String {
+        self.user
+            .as_ref()
+            .and_then(|u| find_classifier_icon(u, class, sub, protocol))
+            .map(|(_, v)| v.to_owned())
+            .filter(|v| Encoding::Emoji.str_is_valid(v))
+            .or_else(|| {
+                find_classifier_icon(&DEFAULT_EMOJI_ICONS, class, sub, protocol)
+                    .or_else(|| DEFAULT_EMOJI_ICONS.get_key_value(&Icon::UndefinedClassifier))
+                    .map(|(_, v)| v.to_string())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get emoji icon for Device `d`: a matching user icon valid for [`Encoding::Emoji`], otherwise from [`static@DEFAULT_EMOJI_ICONS`] by the device class, or the first interface class if defined there, then Vendor ID and Product ID
+    pub fn get_emoji_device_icon(&self, d: &Device) -> String {
+        if let Some(m) = self
+            .get_device_icon_match(d)
+            .filter(|m| m.user && Encoding::Emoji.str_is_valid(&m.icon))
+        {
+            return m.icon;
+        }
+
+        let class = match d.class {
+            Some(c) if c != BaseClass::UseInterfaceDescriptor => {
+                Some((c, d.sub_class.unwrap_or(0), d.protocol.unwrap_or(0)))
+            }
+            _ => d.extra.as_ref().and_then(|e| {
+                e.configurations
+                    .iter()
+                    .flat_map(|c| c.interfaces.iter())
+                    .next()
+                    .map(|i| (i.class, i.sub_class, i.protocol))
+            }),
+        };
+
+        class
+            .and_then(|(c, s, p)| find_classifier_icon(&DEFAULT_EMOJI_ICONS, &c, s, p))
+            .map(|(_, v)| v.to_string())
+            .unwrap_or_else(|| match (d.vendor_id, d.product_id) {
+                (Some(vid), Some(pid)) => self.get_emoji_vidpid_icon(vid, pid),
+                _ => DEFAULT_EMOJI_ICONS
+                    .get(&Icon::UnknownVendor)
+                    .unwrap_or(&"")
+                    .to_string(),
+            })
+    }
+
     /// Get default icon for device based on descriptor name pattern `[Icon::Name]` pattern match
     #[cfg(feature = "regex_icon")]
     pub fn get_default_name_icon(name: &str) -> String {
@@ -512,7 +783,9 @@ impl IconTheme {
 /// Gets tree icon from [`static@DEFAULT_UTF8_TREE`] or [`static@DEFAULT_ASCII_TREE`] (depanding on [`Encoding`]) as `String` with `unwrap` because should panic if missing from there
 pub fn get_default_tree_icon(i: &Icon, encoding: &Encoding) -> String {
     match encoding {
-        Encoding::Utf8 | Encoding::Glyphs => DEFAULT_UTF8_TREE.get(i).unwrap().to_string(),
+        Encoding::Utf8 | Encoding::Glyphs | Encoding::Emoji | Encoding::Auto => {
+            DEFAULT_UTF8_TREE.get(i).unwrap().to_string()
+        }
         Encoding::Ascii => DEFAULT_ASCII_TREE.get(i).unwrap().to_string(),
     }
 }
@@ -667,6 +940,128 @@ mod tests {
         }
     }
 
+    #[test]
+    fn icon_from_str_ranges_wildcards() {
+        let icon = Icon::from_str("vid-range#1d50:1d5f").unwrap();
+        assert_eq!(icon, Icon::VidRange((0x1d50, 0x1d5f)));
+        assert_eq!(icon.to_string(), "vid-range#1d50:1d5f");
+
+        let icon = Icon::from_str("vid-pid-range#0483:3740:374f").unwrap();
+        assert_eq!(icon, Icon::VidPidRange((0x0483, 0x3740, 0x374f)));
+        assert_eq!(icon.to_string(), "vid-pid-range#0483:3740:374f");
+
+        let icon = Icon::from_str("classifier-sub-protocol#03:01:*").unwrap();
+        assert_eq!(
+            icon,
+            Icon::ClassifierWildcard((BaseClass::Hid, Some(1), None))
+        );
+        assert_eq!(icon.to_string(), "classifier-sub-protocol#03:01:*");
+
+        assert!(Icon::from_str("vid-range#1d5f:1d50").is_err());
+        assert!(Icon::from_str("vid-pid#1d50:*").is_err());
+        assert!(Icon::from_str("classifier-sub-protocol#*:01:01").is_err());
+    }
+
+    #[test]
+    fn icon_match_ranges_wildcards() {
+        let theme = IconTheme {
+            user: Some(HashMap::from([
+                (Icon::VidRange((0x1d00, 0x1dff)), "wide".into()),
+                (Icon::VidRange((0x1d50, 0x1d5f)), "narrow".into()),
+                (Icon::VidPidRange((0x1d50, 0x6000, 0x60ff)), "pid".into()),
+                (
+                    Icon::ClassifierWildcard((BaseClass::Hid, None, None)),
+                    "any".into(),
+                ),
+                (
+                    Icon::ClassifierWildcard((BaseClass::Hid, Some(1), None)),
+                    "boot".into(),
+                ),
+            ])),
+            ..Default::default()
+        };
+
+        assert_eq!(theme.get_vidpid_icon(0x1d50, 0x6018), "pid");
+        assert_eq!(theme.get_vidpid_icon(0x1d50, 0x1234), "narrow");
+        assert_eq!(theme.get_vidpid_icon(0x1d01, 0x1234), "wide");
+        // falls through to defaults
+        assert_eq!(
+            theme.get_vidpid_icon(0x05ac, 0x1234),
+            IconTheme::get_default_vidpid_icon(0x05ac, 0x1234)
+        );
+        assert_eq!(theme.get_classifier_icon(&BaseClass::Hid, 1, 2), "boot");
+        assert_eq!(theme.get_classifier_icon(&BaseClass::Hid, 0, 0), "any");
+
+        let device = Device {
+            vendor_id: Some(0x1d50),
+            product_id: Some(0x1234),
+            ..Default::default()
+        };
+        let m = theme.get_device_icon_match(&device).unwrap();
+        assert_eq!(m.rule, Icon::VidRange((0x1d50, 0x1d5f)));
+        assert!(m.user);
+    }
+
+    #[test]
+    #[cfg(feature = "icons")]
+    fn icon_emoji() {
+        let theme = IconTheme {
+            user: Some(HashMap::from([
+                (Icon::Vid(0x1d50), "\u{e771}".into()),
+                (Icon::Vid(0x2e8a), "\u{1F353}".into()),
+            ])),
+            ..Default::default()
+        };
+        let mut device = Device {
+            vendor_id: Some(0x1d50),
+            product_id: Some(0x6018),
+            class: Some(BaseClass::MassStorage),
+            ..Default::default()
+        };
+
+        // glyph user icon is not valid so class emoji
+        assert_eq!(
+            theme.get_encoded_device_icon(&device, &Encoding::Emoji),
+            "\u{1F4BE}"
+        );
+        assert_eq!(
+            theme.get_encoded_device_icon(&device, &Encoding::Glyphs),
+            "\u{e771}"
+        );
+
+        device.vendor_id = Some(0x2e8a);
+        assert_eq!(theme.get_emoji_device_icon(&device), "\u{1F353}");
+
+        device.vendor_id = Some(0x1234);
+        device.class = None;
+        assert_eq!(theme.get_emoji_device_icon(&device), "\u{1F50C}");
+        assert_eq!(
+            theme.get_emoji_classifier_icon(&BaseClass::Hid, 1, 2),
+            "\u{1F5B1}\u{FE0F}"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "regex_icon")]
+    fn icon_match_serial() {
+        let mut device = Device {
+            vendor_id: Some(0x1d50),
+            product_id: Some(0x6018),
+            serial_num: Some("BMP-1234".into()),
+            ..Default::default()
+        };
+        let theme = IconTheme {
+            user: Some(HashMap::from([(Icon::Serial("^BMP-".into()), "S".into())])),
+            ..Default::default()
+        };
+        assert_eq!(theme.get_device_icon(&device), "S");
+
+        device.serial_num = None;
+        let m = theme.get_device_icon_match(&device).unwrap();
+        assert_eq!(m.rule, Icon::VidPid((0x1d50, 0x6018)));
+        assert!(!m.user);
+    }
+
     #[test]
     #[cfg(feature = "regex_icon")]
     fn icon_match_name() {
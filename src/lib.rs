@@ -41,17 +41,43 @@
 //! ```
 //!
 //! The [`profiler::SystemProfile`] struct contains system [`profiler::Bus`]s, which contain [`profiler::Device`]s as a USB tree.
+//!
+//! # no_std
+//!
+//! Without the default `std` feature only the [`usb`] descriptor structs and their parsing from bytes are built, requiring just `core` and `alloc`, so embedded USB tooling can reuse them:
+//!
+//! ```toml
+//! cyme = { version = "2", default-features = false }
+//! ```
 #![allow(dead_code)]
 #![warn(missing_docs)]
-use simple_logger::SimpleLogger;
+#![cfg_attr(not(feature = "std"), no_std)]
 
+extern crate alloc;
+
+#[cfg(feature = "std")]
 pub mod colour;
+#[cfg(feature = "std")]
 pub mod config;
+#[cfg(feature = "std")]
 pub mod display;
 pub mod error;
+#[cfg(feature = "std")]
+pub mod i18n;
+#[cfg(feature = "std")]
 pub mod icon;
+#[cfg(feature = "std")]
+pub mod logger;
+#[cfg(feature = "std")]
 pub mod lsusb;
+#[cfg(feature = "std")]
 pub mod profiler;
+#[cfg(feature = "std")]
+pub mod redact;
+#[cfg(feature = "std")]
+pub mod rules;
+#[cfg(feature = "watch")]
+pub mod serve;
 pub mod types;
 #[cfg(all(target_os = "linux", feature = "udev"))]
 pub mod udev;
@@ -60,48 +86,6 @@ pub mod udev;
 pub mod udev;
 pub mod usb;
 
-/// Set cyme module and binary log level
-pub fn set_log_level(debug: u8) -> crate::error::Result<()> {
-    match debug {
-        // just use env if not passed
-        0 => SimpleLogger::new()
-            .with_utc_timestamps()
-            .with_level(log::Level::Error.to_level_filter())
-            // even errors are off as can be noisy
-            .with_module_level("udevrs", log::LevelFilter::Off)
-            .with_module_level("nusb", log::LevelFilter::Off)
-            .env(),
-        1 => SimpleLogger::new()
-            .with_utc_timestamps()
-            .with_module_level("udevrs", log::Level::Warn.to_level_filter())
-            .with_module_level("nusb", log::Level::Warn.to_level_filter())
-            .with_module_level("cyme", log::Level::Info.to_level_filter()),
-        2 => SimpleLogger::new()
-            .with_utc_timestamps()
-            .with_module_level("udevrs", log::Level::Info.to_level_filter())
-            .with_module_level("nusb", log::Level::Info.to_level_filter())
-            .with_module_level("cyme", log::Level::Debug.to_level_filter()),
-        3 => SimpleLogger::new()
-            .with_utc_timestamps()
-            .with_module_level("udevrs", log::Level::Debug.to_level_filter())
-            .with_module_level("nusb", log::Level::Debug.to_level_filter())
-            .with_module_level("cyme", log::Level::Trace.to_level_filter()),
-        // all modules at Trace level
-        _ => SimpleLogger::new()
-            .with_utc_timestamps()
-            .with_level(log::Level::Trace.to_level_filter()),
-    }
-    .init()
-    .map_err(|e| {
-        crate::error::Error::new(
-            crate::error::ErrorKind::Other("simple_logger"),
-            &format!("Failed to set log level: {}", e),
-        )
-    })?;
-
-    Ok(())
-}
-
 // run any Rust code as doctest
 #[doc = include_str!("../README.md")]
 #[cfg(doctest)]
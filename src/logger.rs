@@ -0,0 +1,379 @@
+//! Logger for cyme with per-module levels, json or pretty formatting and optional output to a file.
+//!
+//! Logs are written to stderr by default so that they do not mix with `--json` output. Writing to a file allows issues to be diagnosed from captured logs without the terminal being flooded.
+//!
+//! ```no_run
+//! use cyme::logger::{LogFormat, Logger};
+//!
+//! Logger::new(0)
+//!     .env()
+//!     .with_module_level("profiler", log::LevelFilter::Debug)
+//!     .with_format(LogFormat::Json)
+//!     .with_file("cyme.log")
+//!     .unwrap()
+//!     .init()
+//!     .unwrap();
+//! ```
+use crate::colour::colored::*;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::error::{Error, ErrorKind, Result};
+
+/// cyme modules which can be given without the 'cyme::' prefix in module filters
+const CYME_MODULES: [&str; 9] = [
+    "colour", "config", "display", "icon", "lsusb", "profiler", "types", "udev", "usb",
+];
+
+/// Format of log records
+#[derive(Default, Debug, ValueEnum, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogFormat {
+    /// Human readable with timestamp, level and module
+    #[default]
+    Pretty,
+    /// Line delimited json objects with timestamp, level, target and message fields
+    Json,
+}
+
+/// Level filter for a module, parsed from 'module=level' such as 'profiler=debug'
+#[derive(Debug, Clone, PartialEq, Eq, SerializeDisplay, DeserializeFromStr)]
+pub struct ModuleFilter {
+    /// Module path; cyme modules can omit the 'cyme::' prefix
+    pub module: String,
+    /// Maximum level to log for the module
+    pub level: LevelFilter,
+}
+
+impl FromStr for ModuleFilter {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (module, level) = s.split_once('=').ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidArg,
+                &format!("Invalid module filter '{}', expected 'module=level'", s),
+            )
+        })?;
+        let level = LevelFilter::from_str(level.trim()).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidArg,
+                &format!(
+                    "Invalid log level '{}', expected off, error, warn, info, debug or trace",
+                    level
+                ),
+            )
+        })?;
+
+        Ok(ModuleFilter {
+            module: module.trim().to_string(),
+            level,
+        })
+    }
+}
+
+impl fmt::Display for ModuleFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.module, self.level.as_str().to_lowercase())
+    }
+}
+
+/// Full module path of `module`, adding the 'cyme::' prefix to cyme module names
+fn module_path(module: &str) -> String {
+    if CYME_MODULES.contains(&module) {
+        format!("cyme::{}", module)
+    } else {
+        module.to_string()
+    }
+}
+
+/// [`Log`] implementation configured with builder methods then installed with [`Logger::init`]
+pub struct Logger {
+    default_level: LevelFilter,
+    /// Module path and level; the longest matching path is used
+    module_levels: Vec<(String, LevelFilter)>,
+    format: LogFormat,
+    file: Option<Mutex<File>>,
+    /// Colour the level of pretty records to stderr
+    colours: bool,
+}
+
+impl Logger {
+    /// New logger with levels from the cyme `--debug` count
+    ///
+    /// 0 logs only errors from cyme, 1-3 increase cyme to info, debug then trace and 4 or more logs everything at trace.
+    pub fn new(debug: u8) -> Self {
+        let logger = Logger {
+            default_level: LevelFilter::Trace,
+            module_levels: Vec::new(),
+            format: LogFormat::default(),
+            file: None,
+            colours: false,
+        };
+
+        match debug {
+            0 => Logger {
+                default_level: LevelFilter::Error,
+                ..logger
+            }
+            // even errors are off as can be noisy
+            .with_module_level("udevrs", LevelFilter::Off)
+            .with_module_level("nusb", LevelFilter::Off),
+            1 => logger
+                .with_module_level("udevrs", LevelFilter::Warn)
+                .with_module_level("nusb", LevelFilter::Warn)
+                .with_module_level("cyme", LevelFilter::Info),
+            2 => logger
+                .with_module_level("udevrs", LevelFilter::Info)
+                .with_module_level("nusb", LevelFilter::Info)
+                .with_module_level("cyme", LevelFilter::Debug),
+            3 => logger
+                .with_module_level("udevrs", LevelFilter::Debug)
+                .with_module_level("nusb", LevelFilter::Debug)
+                .with_module_level("cyme", LevelFilter::Trace),
+            // all modules at Trace level
+            _ => logger,
+        }
+    }
+
+    /// Apply the RUST_LOG env: a comma separated list of a default level and/or 'module=level' filters
+    pub fn env(mut self) -> Self {
+        if let Ok(env) = std::env::var("RUST_LOG") {
+            for directive in env.split(',').filter(|d| !d.trim().is_empty()) {
+                if let Ok(level) = LevelFilter::from_str(directive.trim()) {
+                    self.default_level = level;
+                    // default applies to cyme too, as it did before module filters
+                    self = self.with_module_level("cyme", level);
+                } else if let Ok(filter) = ModuleFilter::from_str(directive) {
+                    self = self.with_module_level(&filter.module, filter.level);
+                } else {
+                    eprintln!("Ignoring invalid RUST_LOG directive '{}'", directive);
+                }
+            }
+        }
+
+        self
+    }
+
+    /// Set the level of `module`, replacing any existing level; cyme modules can omit the 'cyme::' prefix
+    pub fn with_module_level(mut self, module: &str, level: LevelFilter) -> Self {
+        let module = module_path(module);
+        self.module_levels.retain(|(m, _)| *m != module);
+        self.module_levels.push((module, level));
+        // longest first so that the most specific match is found first
+        self.module_levels
+            .sort_by(|a, b| b.0.len().cmp(&a.0.len()).then(a.0.cmp(&b.0)));
+        self
+    }
+
+    /// Set the format of log records
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Colour the level of [`LogFormat::Pretty`] records written to stderr; records to a file are never coloured
+    pub fn with_colours(mut self, colours: bool) -> Self {
+        self.colours = colours;
+        self
+    }
+
+    /// Append logs to the file at `path` rather than stderr
+    pub fn with_file<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        let file = File::options()
+            .create(true)
+            .append(true)
+            .open(path.as_ref())
+            .map_err(|e| {
+                Error::new(
+                    ErrorKind::Io,
+                    &format!("Failed to open log file {:?}: {}", path.as_ref(), e),
+                )
+            })?;
+        self.file = Some(Mutex::new(file));
+        Ok(self)
+    }
+
+    /// Level used for `target`
+    pub fn level(&self, target: &str) -> LevelFilter {
+        self.module_levels
+            .iter()
+            .find(|(m, _)| {
+                target == m
+                    || (target.starts_with(m.as_str()) && target[m.len()..].starts_with("::"))
+            })
+            .map(|(_, l)| *l)
+            .unwrap_or(self.default_level)
+    }
+
+    /// Install as the global logger
+    pub fn init(self) -> Result<()> {
+        let max_level = self
+            .module_levels
+            .iter()
+            .map(|(_, l)| *l)
+            .fold(self.default_level, std::cmp::max);
+        log::set_boxed_logger(Box::new(self)).map_err(|e| {
+            Error::new(
+                ErrorKind::Other("logger"),
+                &format!("Failed to set log level: {}", e),
+            )
+        })?;
+        log::set_max_level(max_level);
+
+        Ok(())
+    }
+
+    fn format_record(&self, record: &Record, colour: bool) -> String {
+        let timestamp = utc_timestamp(SystemTime::now());
+        match self.format {
+            LogFormat::Json => serde_json::json!({
+                "timestamp": timestamp,
+                "level": record.level().as_str(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            })
+            .to_string(),
+            LogFormat::Pretty => {
+                let level = format!("{:<5}", record.level());
+                let level = if colour {
+                    match record.level() {
+                        Level::Error => level.red().to_string(),
+                        Level::Warn => level.yellow().to_string(),
+                        Level::Info => level.cyan().to_string(),
+                        Level::Debug => level.purple().to_string(),
+                        Level::Trace => level.normal().to_string(),
+                    }
+                } else {
+                    level
+                };
+                format!(
+                    "{} {} [{}] {}",
+                    timestamp,
+                    level,
+                    record.target(),
+                    record.args()
+                )
+            }
+        }
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        // logging should not fail the program so write errors are ignored
+        match self.file.as_ref() {
+            Some(file) => {
+                if let Ok(mut f) = file.lock() {
+                    let _ = writeln!(f, "{}", self.format_record(record, false));
+                }
+            }
+            None => {
+                let _ = writeln!(io::stderr(), "{}", self.format_record(record, self.colours));
+            }
+        }
+    }
+
+    fn flush(&self) {
+        match self.file.as_ref() {
+            Some(file) => {
+                if let Ok(mut f) = file.lock() {
+                    let _ = f.flush();
+                }
+            }
+            None => {
+                let _ = io::stderr().flush();
+            }
+        }
+    }
+}
+
+/// RFC 3339 UTC timestamp with milliseconds such as '2024-12-01T09:30:00.000Z'
+pub(crate) fn utc_timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (hour, min, sec) = ((secs / 3600) % 24, (secs / 60) % 60, secs % 60);
+
+    // civil from days: http://howardhinnant.github.io/date_algorithms.html
+    let z = (secs / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        hour,
+        min,
+        sec,
+        since_epoch.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_module_levels() {
+        let logger = Logger::new(0)
+            .with_module_level("profiler", LevelFilter::Debug)
+            .with_module_level("cyme::profiler::nusb", LevelFilter::Trace);
+
+        assert_eq!(logger.level("cyme"), LevelFilter::Error);
+        assert_eq!(logger.level("cyme::display"), LevelFilter::Error);
+        assert_eq!(logger.level("cyme::profiler"), LevelFilter::Debug);
+        assert_eq!(logger.level("cyme::profiler::libusb"), LevelFilter::Debug);
+        assert_eq!(logger.level("cyme::profiler::nusb"), LevelFilter::Trace);
+        assert_eq!(logger.level("cyme::profilerx"), LevelFilter::Error);
+        assert_eq!(logger.level("nusb"), LevelFilter::Off);
+    }
+
+    #[test]
+    fn test_module_filter_from_str() {
+        assert_eq!(
+            ModuleFilter::from_str("udev=trace").unwrap(),
+            ModuleFilter {
+                module: String::from("udev"),
+                level: LevelFilter::Trace
+            }
+        );
+        assert!(ModuleFilter::from_str("udev").is_err());
+        assert!(ModuleFilter::from_str("udev=loud").is_err());
+    }
+
+    #[test]
+    fn test_utc_timestamp() {
+        assert_eq!(
+            utc_timestamp(UNIX_EPOCH + Duration::from_millis(1_733_045_400_123)),
+            "2024-12-01T09:30:00.123Z"
+        );
+        assert_eq!(utc_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+    }
+}
@@ -1,7 +1,7 @@
 //! Methods to print system USB information in lsusb style
 //!
 //! Originally based on [libusb list_devices.rs example](https://github.com/dcuddeback/libusb-rs/blob/master/examples/list_devices.rs), attempts to mimic lsusb output. The [lsusb source code](https://github.com/gregkh/usbutils/blob/master/lsusb.c) was used as a reference for the styling and content; even odities/inconsistencies were kept!
-use crate::display::PrintSettings;
+use crate::display::{output, outputln, PrintSettings};
 use crate::error::{Error, ErrorKind};
 use crate::profiler::{Device, SystemProfile};
 use uuid::Uuid;
@@ -57,14 +57,14 @@ fn dump_bitmap_array<T: std::fmt::LowerHex + Into<u64> + Copy>(
 
 /// Dump just indented string
 fn dump_string(field_name: &str, indent: usize) {
-    println!("{:indent$}{}", "", field_name);
+    outputln!("{:indent$}{}", "", field_name);
 }
 
 /// Dump a single value like lsusb
 fn dump_value<T: std::fmt::Display>(value: T, field_name: &str, indent: usize, width: usize) {
     let value = value.to_string();
     let spaces = get_spaces(value.len(), field_name.len(), width);
-    println!("{:indent$}{}{}{}", "", field_name, spaces, value);
+    outputln!("{:indent$}{}{}{}", "", field_name, spaces, value);
 }
 
 /// Dump a single hex value like lsusb
@@ -95,7 +95,7 @@ fn dump_name<T: std::fmt::Display>(
     let spaces = get_spaces(value_string.len(), field_name.len(), width);
     let dump = format!("{:indent$}{}{}{}", "", field_name, spaces, value_string,);
     if let Some(name) = names_f(value) {
-        println!("{} {}", dump, name);
+        outputln!("{} {}", dump, name);
     }
 }
 
@@ -109,9 +109,13 @@ fn dump_value_string<T: std::fmt::Display, S: std::fmt::Display>(
 ) {
     let value = value.to_string();
     let spaces = get_spaces(value.len(), field_name.len(), width);
-    println!(
+    outputln!(
         "{:indent$}{}{}{} {}",
-        "", field_name, spaces, value, value_string,
+        "",
+        field_name,
+        spaces,
+        value,
+        value_string,
     );
 }
 
@@ -119,7 +123,7 @@ fn dump_value_string<T: std::fmt::Display, S: std::fmt::Display>(
 fn dump_string_right<T: std::fmt::Display>(guid: T, field_name: &str, indent: usize, width: usize) {
     // 1 to account for space
     let spaces = get_spaces(1, field_name.len(), width);
-    println!("{:indent$}{}{}{}", "", field_name, spaces, guid);
+    outputln!("{:indent$}{}{}{}", "", field_name, spaces, guid);
 }
 
 /// Dumps GUID enclosed in braces like lsusb
@@ -130,7 +134,7 @@ fn dump_guid(guid: &Uuid, field_name: &str, indent: usize, width: usize) {
 /// Dumps junk descriptor bytes as hex like lsusb
 fn dump_junk(extra: &[u8], indent: usize, reported_len: usize, expected_len: usize) {
     if reported_len > expected_len && extra.len() >= reported_len {
-        println!(
+        outputln!(
             "{:^indent$}junk at descriptor end: {}",
             "",
             extra[expected_len..reported_len]
@@ -144,7 +148,7 @@ fn dump_junk(extra: &[u8], indent: usize, reported_len: usize, expected_len: usi
 
 /// Dumps unknown descriptor bytes as hex like lsusb
 fn dump_unrecognised(extra: &[u8], indent: usize) {
-    println!(
+    outputln!(
         "{:^indent$}** UNRECOGNIZED: {}",
         "",
         extra
@@ -165,7 +169,7 @@ where
     for index in 0..num_bits {
         if (bitmap_u64 >> index) & 0x1 != 0 {
             if let Some(string) = strings_f(index) {
-                println!("{:indent$}{}", "", string);
+                outputln!("{:indent$}{}", "", string);
             }
         }
     }
@@ -184,7 +188,7 @@ fn dump_bitmap_strings_invert<T>(
     for index in (0..num_bits).rev() {
         if (bitmap_u64 >> index) & 0x1 != 0 {
             if let Some(string) = strings_f(index) {
-                println!("{:indent$}{}", "", string);
+                outputln!("{:indent$}{}", "", string);
             }
         }
     }
@@ -204,17 +208,17 @@ fn dump_bitmap_strings_inline<T, V>(
 {
     let value = value.to_string();
     let spaces = get_spaces(value.len(), field_name.len(), width);
-    print!("{:indent$}{}{}{}", "", field_name, spaces, value,);
+    output!("{:indent$}{}{}{}", "", field_name, spaces, value,);
     let bitmap_u64: u64 = bitmap.into();
     let num_bits = std::mem::size_of::<T>() * 8;
     for index in 0..num_bits {
         if (bitmap_u64 >> index) & 0x1 != 0 {
             if let Some(string) = strings_f(index) {
-                print!(" {}", string);
+                output!(" {}", string);
             }
         }
     }
-    println!();
+    outputln!();
 }
 
 fn get_guid(buf: &[u8]) -> String {
@@ -240,15 +244,16 @@ pub fn print_tree(spusb: &SystemProfile, settings: &PrintSettings) {
             }
             // the const len should get compiled to const...
             let indent = (device.get_depth() * TREE_LSUSB_DEVICE.len()) + TREE_LSUSB_SPACE.len();
-            let device_tree_strings: Vec<(String, String, String)> = device.to_lsusb_tree_string();
+            let device_tree_strings: Vec<(String, String, String)> = device
+                .to_lsusb_tree_string_with_units(settings.speed_units.unwrap_or(SpeedUnits::Lsusb));
 
             for strings in device_tree_strings {
-                println!("{:>indent$}{}", TREE_LSUSB_DEVICE, strings.0);
+                outputln!("{:>indent$}{}", TREE_LSUSB_DEVICE, strings.0);
                 if settings.verbosity >= 1 {
-                    println!("{:>indent$}{}", TREE_LSUSB_SPACE, strings.1);
+                    outputln!("{:>indent$}{}", TREE_LSUSB_SPACE, strings.1);
                 }
                 if settings.verbosity >= 2 {
-                    println!("{:>indent$}{}", TREE_LSUSB_SPACE, strings.2);
+                    outputln!("{:>indent$}{}", TREE_LSUSB_SPACE, strings.2);
                 }
             }
             // print all devices with this device - if hub for example
@@ -260,14 +265,15 @@ pub fn print_tree(spusb: &SystemProfile, settings: &PrintSettings) {
     }
 
     for bus in &spusb.buses {
-        let bus_tree_strings: Vec<(String, String, String)> = bus.to_lsusb_tree_string();
+        let bus_tree_strings: Vec<(String, String, String)> =
+            bus.to_lsusb_tree_string_with_units(settings.speed_units.unwrap_or(SpeedUnits::Lsusb));
         for strings in bus_tree_strings {
-            println!("{}{}", TREE_LSUSB_BUS, strings.0);
+            outputln!("{}{}", TREE_LSUSB_BUS, strings.0);
             if settings.verbosity >= 1 {
-                println!("{}{}", TREE_LSUSB_SPACE, strings.1);
+                outputln!("{}{}", TREE_LSUSB_SPACE, strings.1);
             }
             if settings.verbosity >= 2 {
-                println!("{}{}", TREE_LSUSB_SPACE, strings.2);
+                outputln!("{}{}", TREE_LSUSB_SPACE, strings.2);
             }
         }
 
@@ -286,7 +292,7 @@ pub fn dump_one_device(devices: &Vec<&Device>, dev_path: &String) -> Result<(),
             if device.extra.is_none() {
                 return Err(Error::new(
                     ErrorKind::Opening,
-                    &format!("Unable to open {}", dev_path),
+                    &format!("Cannot open {}", dev_path),
                 ));
             }
 
@@ -295,9 +301,10 @@ pub fn dump_one_device(devices: &Vec<&Device>, dev_path: &String) -> Result<(),
         }
     }
 
+    // same message as lsusb, which does not distinguish a missing device
     Err(Error::new(
         ErrorKind::NotFound,
-        &format!("Unable to find {}", dev_path),
+        &format!("Cannot open {}", dev_path),
     ))
 }
 
@@ -318,12 +325,12 @@ fn find_otg(extra: &[Descriptor]) -> Option<&OnTheGoDescriptor> {
 pub fn print(devices: &Vec<&Device>, verbose: bool) {
     if !verbose {
         for device in devices {
-            println!("{}", device.to_lsusb_string());
+            outputln!("{}", device.to_lsusb_string());
         }
     } else {
         for device in devices {
-            println!(); // new lines separate in verbose lsusb
-            println!("{}", device.to_lsusb_string());
+            outputln!(); // new lines separate in verbose lsusb
+            outputln!("{}", device.to_lsusb_string());
             // print error regarding open if non-critical during probe like lsusb --verbose
             if device.profiler_error.is_some() {
                 eprintln!("Couldn't open device, some information will be missing");
@@ -333,57 +340,66 @@ pub fn print(devices: &Vec<&Device>, verbose: bool) {
                     "Device {} does not contain extra data required for verbose print",
                     device
                 ),
-                Some(device_extra) => {
-                    dump_device(device);
+                Some(_) => dump(device, 3),
+            }
+        }
+    }
+}
 
-                    let mut otg = None;
-                    for config in &device_extra.configurations {
-                        dump_config(config, LSUSB_DUMP_INDENT_BASE);
-                        otg = config.extra.as_ref().map(|e| find_otg(e));
+/// Dump the descriptors of a [`Device`] in style of lsusb --verbose
+///
+/// `verbosity` limits the sections like the cyme tree: 1 prints configurations, 2 interfaces and 3 endpoints. Nothing is printed if the device has no extra data.
+pub fn dump(device: &Device, verbosity: u8) {
+    let Some(device_extra) = device.extra.as_ref() else {
+        return;
+    };
 
-                        for interface in &config.interfaces {
-                            dump_interface(interface, LSUSB_DUMP_INDENT_BASE * 2);
-                            otg = config.extra.as_ref().map(|e| find_otg(e));
+    dump_device(device);
 
-                            for endpoint in &interface.endpoints {
-                                dump_endpoint(endpoint, LSUSB_DUMP_INDENT_BASE * 3);
-                                otg = config.extra.as_ref().map(|e| find_otg(e));
-                            }
-                        }
-                    }
+    let mut otg = None;
+    for config in &device_extra.configurations {
+        dump_config(config, LSUSB_DUMP_INDENT_BASE);
+        otg = config.extra.as_ref().map(|e| find_otg(e));
 
-                    let has_ssp = if let Some(bos) = &device_extra.binary_object_store {
-                        dump_bos_descriptor(bos, 0);
-                        bos.capabilities
-                            .iter()
-                            .any(|c| matches!(c, bos::BosCapability::SuperSpeedPlus(_)))
-                    } else {
-                        false
-                    };
-                    if let Some(hub) = &device_extra.hub {
-                        let bcd = device.bcd_usb.map_or(0x0100, |v| v.into());
-                        dump_hub(hub, device.protocol.unwrap_or(1), bcd, has_ssp, 0);
-                    }
-                    // lsusb do_dualspeed: dump_device_qualifier
-                    if let Some(qualifier) = &device_extra.qualifier {
-                        dump_device_qualifier(qualifier, 0);
-                    }
-                    if let Some(debug) = &device_extra.debug {
-                        dump_debug(debug, 0);
-                    }
+        for interface in config.interfaces.iter().filter(|_| verbosity >= 2) {
+            dump_interface(interface, LSUSB_DUMP_INDENT_BASE * 2);
+            otg = config.extra.as_ref().map(|e| find_otg(e));
 
-                    if let Some(status) = device_extra.status {
-                        dump_device_status(
-                            status,
-                            otg.is_some(),
-                            device.bcd_usb.is_some_and(|v| v.major() >= 3),
-                            0,
-                        );
-                    }
-                }
+            for endpoint in interface.endpoints.iter().filter(|_| verbosity >= 3) {
+                dump_endpoint(endpoint, LSUSB_DUMP_INDENT_BASE * 3);
+                otg = config.extra.as_ref().map(|e| find_otg(e));
             }
         }
     }
+
+    let has_ssp = if let Some(bos) = &device_extra.binary_object_store {
+        dump_bos_descriptor(bos, 0);
+        bos.capabilities
+            .iter()
+            .any(|c| matches!(c, bos::BosCapability::SuperSpeedPlus(_)))
+    } else {
+        false
+    };
+    if let Some(hub) = &device_extra.hub {
+        let bcd = device.bcd_usb.map_or(0x0100, |v| v.into());
+        dump_hub(hub, device.protocol.unwrap_or(1), bcd, has_ssp, 0);
+    }
+    // lsusb do_dualspeed: dump_device_qualifier
+    if let Some(qualifier) = &device_extra.qualifier {
+        dump_device_qualifier(qualifier, 0);
+    }
+    if let Some(debug) = &device_extra.debug {
+        dump_debug(debug, 0);
+    }
+
+    if let Some(status) = device_extra.status {
+        dump_device_status(
+            status,
+            otg.is_some(),
+            device.bcd_usb.is_some_and(|v| v.major() >= 3),
+            0,
+        );
+    }
 }
 
 /// Dump a [`Device`] in style of lsusb --verbose
@@ -406,7 +422,7 @@ fn dump_device(device: &Device) {
             _ => unreachable!(),
         };
 
-    println!("Device Descriptor:");
+    outputln!("Device Descriptor:");
     // These are constants - length is 18 bytes for descriptor, type is 1
     dump_value(18, "bLength", 2, LSUSB_DUMP_WIDTH);
     dump_value(1, "bDescriptorType", 2, LSUSB_DUMP_WIDTH);
@@ -762,19 +778,19 @@ fn dump_endpoint(endpoint: &Endpoint, indent: usize) {
         indent + 2,
         LSUSB_DUMP_WIDTH,
     );
-    println!(
+    outputln!(
         "{:indent$}Transfer Type          {:?}",
         "",
         endpoint.transfer_type,
         indent = indent + 4
     );
-    println!(
+    outputln!(
         "{:indent$}Sync Type              {:?}",
         "",
         endpoint.sync_type,
         indent = indent + 4
     );
-    println!(
+    outputln!(
         "{:indent$}Usage Type             {:?}",
         "",
         endpoint.usage_type,
@@ -824,7 +840,7 @@ fn dump_endpoint(endpoint: &Endpoint, indent: usize) {
                         dump_ccid_desc(ccid, indent);
                     }
                     _ => {
-                        println!(
+                        outputln!(
                             "{:indent$}DEVICE CLASS: {}",
                             "",
                             Vec::<u8>::from(cd.to_owned())
@@ -848,7 +864,7 @@ fn dump_endpoint(endpoint: &Endpoint, indent: usize) {
                             dump_pipe_desc(gd, indent + 2);
                         }
                         _ => {
-                            println!(
+                            outputln!(
                                 "{:indent$}INTERFACE CLASS: {}",
                                 "",
                                 Vec::<u8>::from(cd.to_owned())
@@ -862,7 +878,7 @@ fn dump_endpoint(endpoint: &Endpoint, indent: usize) {
                     },
                     ClassDescriptor::Communication(cd) => dump_comm_descriptor(cd, 6),
                     _ => {
-                        println!(
+                        outputln!(
                             "{:indent$}INTERFACE CLASS: {}",
                             "",
                             Vec::<u8>::from(cd.to_owned())
@@ -878,7 +894,7 @@ fn dump_endpoint(endpoint: &Endpoint, indent: usize) {
                     dump_interface_association(iad, indent + 2);
                 }
                 Descriptor::SsEndpointCompanion(ss) => {
-                    println!(
+                    outputln!(
                         "{:indent$}bMaxBurst {:>14}",
                         "",
                         ss.max_burst,
@@ -887,7 +903,7 @@ fn dump_endpoint(endpoint: &Endpoint, indent: usize) {
                     match endpoint.transfer_type {
                         TransferType::Bulk => {
                             if ss.attributes & 0x1f != 0 {
-                                println!(
+                                outputln!(
                                     "{:indent$}MaxStreams {:>13}",
                                     "",
                                     1 << ss.attributes,
@@ -897,7 +913,7 @@ fn dump_endpoint(endpoint: &Endpoint, indent: usize) {
                         }
                         TransferType::Isochronous => {
                             if ss.attributes & 0x03 != 0 {
-                                println!(
+                                outputln!(
                                     "{:indent$}Mult {:>19}",
                                     "",
                                     ss.attributes & 0x3,
@@ -1066,14 +1082,14 @@ fn dump_ccid_desc(ccid: &CcidDescriptor, indent: usize) {
         indent + 4,
     );
     if (ccid.features & (0x0040 | 0x0080)) != 0 {
-        println!(
+        outputln!(
             "{:indent$}WARNING: conflicting negotiation features",
             "",
             indent = indent + 2
         );
     }
     if ccid.features & 0x00070000 != 0 {
-        println!(
+        outputln!(
             "{:indent$}WARNING: conflicting exchange levels",
             "",
             indent = indent + 2
@@ -1179,7 +1195,7 @@ fn dump_printer_desc(pd: &PrinterDescriptor, indent: usize) {
                 indent + 2,
                 LSUSB_DUMP_WIDTH,
             );
-            print!(
+            output!(
                 "{:indent$}wBasicCapabilities   0x{:04x} ",
                 "",
                 desc.capabilities,
@@ -1188,30 +1204,30 @@ fn dump_printer_desc(pd: &PrinterDescriptor, indent: usize) {
 
             // capabilities
             if desc.capabilities & 0x0001 != 0 {
-                print!(" Print");
+                output!(" Print");
             }
             if desc.capabilities & 0x0002 != 0 {
-                print!(" Scan");
+                output!(" Scan");
             }
             if desc.capabilities & 0x0004 != 0 {
-                print!(" Fax");
+                output!(" Fax");
             }
             if desc.capabilities & 0x0008 != 0 {
-                print!(" Other");
+                output!(" Other");
             }
             if desc.capabilities & 0x0010 != 0 {
-                print!(" HTTP-over-USB");
+                output!(" HTTP-over-USB");
             }
             if (desc.capabilities & 0x0060) != 0 {
-                print!(" No-Auth");
+                output!(" No-Auth");
             } else if (desc.capabilities & 0x0060) != 0x20 {
-                print!(" Username-Auth");
+                output!(" Username-Auth");
             } else if (desc.capabilities & 0x0060) != 0x40 {
-                print!(" Reserved-Auth");
+                output!(" Reserved-Auth");
             } else if (desc.capabilities & 0x0060) != 0x60 {
-                print!(" Negotiable-Auth");
+                output!(" Negotiable-Auth");
             }
-            println!();
+            outputln!();
         // vendor specific
         } else {
             dump_value_string(
@@ -1227,7 +1243,7 @@ fn dump_printer_desc(pd: &PrinterDescriptor, indent: usize) {
 
 fn dump_bad_comm(cd: &cdc::CommunicationDescriptor, indent: usize) {
     let data = Into::<Vec<u8>>::into(cd.to_owned());
-    println!(
+    outputln!(
         "{:^indent$}INVALID CDC ({:#}): {}",
         "",
         cd.descriptor_subtype,
@@ -1290,7 +1306,7 @@ fn dump_comm_descriptor(cd: &cdc::CommunicationDescriptor, indent: usize) {
                 indent + 2,
                 LSUSB_DUMP_WIDTH,
             );
-            println!(
+            outputln!(
                 "{:indent$}bSlaveInterface      {}",
                 "",
                 cd.slave_interface
@@ -1414,7 +1430,7 @@ fn dump_comm_descriptor(cd: &cdc::CommunicationDescriptor, indent: usize) {
                 indent + 2,
                 LSUSB_DUMP_WIDTH,
             );
-            println!(
+            outputln!(
                 "{:indent$}bDetailData          {}",
                 "",
                 d.detail_data
@@ -1534,7 +1550,7 @@ fn dump_comm_descriptor(cd: &cdc::CommunicationDescriptor, indent: usize) {
             dump_bad_comm(cd, indent);
         }
         _ => {
-            println!(
+            outputln!(
                 "{:^indent$}UNRECOGNIZED CDC: {}",
                 "",
                 Vec::<u8>::from(cd.to_owned())
@@ -1564,31 +1580,31 @@ fn dump_dfu_interface(dfud: &DfuDescriptor, indent: usize) {
     dump_value(dfud.attributes, "bmAttributes", indent + 2, DFU_WIDTH);
 
     if dfud.attributes & 0xf0 != 0 {
-        println!("{:indent$}(unknown attributes!)", "", indent = indent + 4);
+        outputln!("{:indent$}(unknown attributes!)", "", indent = indent + 4);
     }
     if dfud.attributes & 0x08 != 0 {
-        println!("{:indent$}Will Detach", "", indent = indent + 4);
+        outputln!("{:indent$}Will Detach", "", indent = indent + 4);
     } else {
-        println!("{:indent$}Will Not Detach", "", indent = indent + 4);
+        outputln!("{:indent$}Will Not Detach", "", indent = indent + 4);
     }
     if dfud.attributes & 0x04 != 0 {
-        println!(
+        outputln!(
             "{:indent$}Manifestation Intolerant",
             "",
             indent = indent + 4
         );
     } else {
-        println!("{:indent$}Manifestation Tolerant", "", indent = indent + 4);
+        outputln!("{:indent$}Manifestation Tolerant", "", indent = indent + 4);
     }
     if dfud.attributes & 0x02 != 0 {
-        println!("{:indent$}Upload Supported", "", indent = indent + 4);
+        outputln!("{:indent$}Upload Supported", "", indent = indent + 4);
     } else {
-        println!("{:indent$}Upload Unsupported", "", indent = indent + 4);
+        outputln!("{:indent$}Upload Unsupported", "", indent = indent + 4);
     }
     if dfud.attributes & 0x01 != 0 {
-        println!("{:indent$}Download Supported", "", indent = indent + 4);
+        outputln!("{:indent$}Download Supported", "", indent = indent + 4);
     } else {
-        println!("{:indent$}Download Unsupported", "", indent = indent + 4);
+        outputln!("{:indent$}Download Unsupported", "", indent = indent + 4);
     }
 
     dump_value_string(
@@ -1621,7 +1637,7 @@ fn dump_pipe_desc(gd: &GenericDescriptor, indent: usize) {
             0xe0..=0xef => "Vendor-specific",
         };
 
-        println!(
+        outputln!(
             "{:indent$}{} (0x{:02x})",
             "",
             subtype_string,
@@ -1629,7 +1645,7 @@ fn dump_pipe_desc(gd: &GenericDescriptor, indent: usize) {
             indent = indent
         );
     } else {
-        println!(
+        outputln!(
             "{:indent$}INTERFACE CLASS: {}",
             "",
             Vec::<u8>::from(gd.to_owned())
@@ -1957,33 +1973,33 @@ fn dump_hub(hd: &HubDescriptor, protocol: u8, bcd: u16, has_ssp: bool, indent: u
         LSUSB_DUMP_WIDTH,
     );
     match hd.characteristics & 0x03 {
-        0 => println!("{:indent$}Ganged power switching", "", indent = indent + 4),
-        1 => println!(
+        0 => outputln!("{:indent$}Ganged power switching", "", indent = indent + 4),
+        1 => outputln!(
             "{:indent$}Per-port power switching",
             "",
             indent = indent + 4
         ),
-        _ => println!(
+        _ => outputln!(
             "{:indent$}No power switching (usb 1.0)",
             "",
             indent = indent + 4
         ),
     }
     if hd.characteristics & 0x04 != 0 {
-        println!("{:indent$}Compound device", "", indent = indent + 4);
+        outputln!("{:indent$}Compound device", "", indent = indent + 4);
     }
     match (hd.characteristics >> 3) & 0x03 {
-        0 => println!(
+        0 => outputln!(
             "{:indent$}Ganged overcurrent protection",
             "",
             indent = indent + 4
         ),
-        1 => println!(
+        1 => outputln!(
             "{:indent$}Per-port overcurrent protection",
             "",
             indent = indent + 4
         ),
-        _ => println!(
+        _ => outputln!(
             "{:indent$}No overcurrent protection",
             "",
             indent = indent + 4
@@ -2205,22 +2221,22 @@ fn dump_hub(hd: &HubDescriptor, protocol: u8, bcd: u16, has_ssp: bool, indent: u
 fn dump_device_status(status: u16, otg: bool, super_speed: bool, indent: usize) {
     dump_hex(status, "Device Status:", indent, LSUSB_DUMP_WIDTH);
     if status & 0x01 != 0 {
-        println!("{:indent$}Self Powered", "", indent = indent + 2);
+        outputln!("{:indent$}Self Powered", "", indent = indent + 2);
     } else {
-        println!("{:indent$}(Bus Powered)", "", indent = indent + 2);
+        outputln!("{:indent$}(Bus Powered)", "", indent = indent + 2);
     }
     if status & 0x02 != 0 {
-        println!("{:indent$}Remote Wakeup Enabled", "", indent = indent + 2);
+        outputln!("{:indent$}Remote Wakeup Enabled", "", indent = indent + 2);
     }
     if super_speed {
         if status & (1 << 2) != 0 {
-            println!("{:indent$}U1 Enabled", "", indent = indent + 2);
+            outputln!("{:indent$}U1 Enabled", "", indent = indent + 2);
         }
         if status & (1 << 3) != 0 {
-            println!("{:indent$}U2 Enabled", "", indent = indent + 2);
+            outputln!("{:indent$}U2 Enabled", "", indent = indent + 2);
         }
         if status & (1 << 4) != 0 {
-            println!(
+            outputln!(
                 "{:indent$}Latency Tolerance Messaging (LTM) Enabled",
                 "",
                 indent = indent + 2
@@ -2229,17 +2245,17 @@ fn dump_device_status(status: u16, otg: bool, super_speed: bool, indent: usize)
     }
     if otg {
         if status & (1 << 3) != 0 {
-            println!("{:indent$}HNP Enabled", "", indent = indent + 2);
+            outputln!("{:indent$}HNP Enabled", "", indent = indent + 2);
         }
         if status & (1 << 4) != 0 {
-            println!("{:indent$}HNP Capable", "", indent = indent + 2);
+            outputln!("{:indent$}HNP Capable", "", indent = indent + 2);
         }
         if status & (1 << 5) != 0 {
-            println!("{:indent$}ALT port is HNP Capable", "", indent = indent + 2);
+            outputln!("{:indent$}ALT port is HNP Capable", "", indent = indent + 2);
         }
     }
     if status & (1 << 6) != 0 {
-        println!("{:indent$}Debug Mode", "", indent = indent + 2);
+        outputln!("{:indent$}Debug Mode", "", indent = indent + 2);
     }
 }
 
@@ -2275,15 +2291,15 @@ fn dump_unit(mut data: u16, len: usize, indent: usize) {
 
     if sys > 4 {
         if sys == 0xf {
-            println!("{:indent$}System: Vendor defined, Unit: (unknown)", "");
+            outputln!("{:indent$}System: Vendor defined, Unit: (unknown)", "");
         } else {
-            println!("{:indent$}System: Reserved, Unit: (unknown)", "");
+            outputln!("{:indent$}System: Reserved, Unit: (unknown)", "");
         }
 
         return;
     }
 
-    print!("{:indent$}System: {}, Unit: ", "", systems(sys));
+    output!("{:indent$}System: {}, Unit: ", "", systems(sys));
 
     let mut earlier_unit = 0;
 
@@ -2292,9 +2308,9 @@ fn dump_unit(mut data: u16, len: usize, indent: usize) {
         data >>= 4;
         if nibble != 0 {
             if earlier_unit > 0 {
-                print!("*");
+                output!("*");
             }
-            print!("{}", units(sys, i));
+            output!("{}", units(sys, i));
             earlier_unit += 1;
             /* This is a _signed_ nibble(!) */
             if nibble != 1 {
@@ -2302,15 +2318,15 @@ fn dump_unit(mut data: u16, len: usize, indent: usize) {
                 if nibble & 0x08 != 0x00 {
                     val = -((0x7 & !val) + 1);
                 }
-                print!("^{}", val);
+                output!("^{}", val);
             }
         }
     }
 
     if earlier_unit == 0 {
-        print!("(None)");
+        output!("(None)");
     }
-    println!();
+    outputln!();
 }
 
 /// Dumps HID report data ported directly from lsusb - it's not pretty but works...
@@ -2342,7 +2358,7 @@ fn dump_report_desc(desc: &[u8], indent: usize) {
         let btag = b & !0x03;
 
         // Item Header
-        print!(
+        output!(
             "{:indent$}Item({:>6}): {}, data=",
             "",
             types(btype >> 2),
@@ -2352,20 +2368,20 @@ fn dump_report_desc(desc: &[u8], indent: usize) {
 
         // Check for descriptor bounds
         if i + bsize >= desc.len() {
-            println!("Error: Descriptor too short");
+            outputln!("Error: Descriptor too short");
             break;
         }
 
         if bsize > 0 {
-            print!(" [ ");
+            output!(" [ ");
             data = 0;
             for j in 0..bsize {
                 data |= (desc[i + 1 + j] as u32) << (j * 8);
-                print!("{:02x} ", desc[i + 1 + j]);
+                output!("{:02x} ", desc[i + 1 + j]);
             }
-            println!("] {}", data);
+            outputln!("] {}", data);
         } else {
-            println!("none");
+            outputln!("none");
         }
 
         match btag {
@@ -2373,18 +2389,18 @@ fn dump_report_desc(desc: &[u8], indent: usize) {
             0x04 => {
                 hut = data as u8;
                 if let Some(hut) = names::huts(hut) {
-                    println!("{:indent$}{}", "", hut, indent = REPORT_INDENT);
+                    outputln!("{:indent$}{}", "", hut, indent = REPORT_INDENT);
                 }
             }
             // usage, usage minimum, usage maximum
             0x08 | 0x18 | 0x28 => {
                 if let Some(hutus) = names::hutus(hut, data as u16) {
-                    println!("{:indent$}{}", "", hutus, indent = REPORT_INDENT);
+                    outputln!("{:indent$}{}", "", hutus, indent = REPORT_INDENT);
                 }
             }
             // unit exponent
             0x54 => {
-                println!(
+                outputln!(
                     "{:indent$}Unit Exponent: {}",
                     "",
                     data as u8,
@@ -2395,18 +2411,18 @@ fn dump_report_desc(desc: &[u8], indent: usize) {
             0x64 => dump_unit(data as u16, bsize, REPORT_INDENT),
             // collection
             0xa0 => match data {
-                0x00 => println!("{:indent$}Physical", "", indent = REPORT_INDENT),
-                0x01 => println!("{:indent$}Application", "", indent = REPORT_INDENT),
-                0x02 => println!("{:indent$}Logical", "", indent = REPORT_INDENT),
-                0x03 => println!("{:indent$}Report", "", indent = REPORT_INDENT),
-                0x04 => println!("{:indent$}Named Array", "", indent = REPORT_INDENT),
-                0x05 => println!("{:indent$}Usage Switch", "", indent = REPORT_INDENT),
-                0x06 => println!("{:indent$}Usage Modifier", "", indent = REPORT_INDENT),
+                0x00 => outputln!("{:indent$}Physical", "", indent = REPORT_INDENT),
+                0x01 => outputln!("{:indent$}Application", "", indent = REPORT_INDENT),
+                0x02 => outputln!("{:indent$}Logical", "", indent = REPORT_INDENT),
+                0x03 => outputln!("{:indent$}Report", "", indent = REPORT_INDENT),
+                0x04 => outputln!("{:indent$}Named Array", "", indent = REPORT_INDENT),
+                0x05 => outputln!("{:indent$}Usage Switch", "", indent = REPORT_INDENT),
+                0x06 => outputln!("{:indent$}Usage Modifier", "", indent = REPORT_INDENT),
                 _ => {
                     if (data & 0x80) == 0x80 {
-                        println!("{:indent$}Vendor defined", "", indent = REPORT_INDENT)
+                        outputln!("{:indent$}Vendor defined", "", indent = REPORT_INDENT)
                     } else {
-                        println!("{:indent$}Unknown", "", indent = REPORT_INDENT)
+                        outputln!("{:indent$}Unknown", "", indent = REPORT_INDENT)
                     }
                 }
             },
@@ -2460,8 +2476,8 @@ fn dump_report_desc(desc: &[u8], indent: usize) {
                     },
                     indent = REPORT_INDENT
                 );
-                println!("{}", attributes_1);
-                println!("{}", attributes_2);
+                outputln!("{}", attributes_1);
+                outputln!("{}", attributes_2);
             }
             _ => (),
         }
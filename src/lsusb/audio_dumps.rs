@@ -2,67 +2,6 @@ use crate::usb::descriptors::audio;
 
 use super::*;
 
-const UAC2_INTERFACE_HEADER_BMCONTROLS: [&str; 1] = ["Legacy"];
-const UAC2_INPUT_TERMINAL_BMCONTROLS: [&str; 6] = [
-    "Copy Protect",
-    "Connector",
-    "Overload",
-    "Cluster",
-    "Underflow",
-    "Overflow",
-];
-const UAC3_INPUT_TERMINAL_BMCONTROLS: [&str; 5] = [
-    "Insertion",
-    "Overload",
-    "Underflow",
-    "Overflow",
-    "Underflow",
-];
-const UAC2_OUTPUT_TERMINAL_BMCONTROLS: [&str; 5] = [
-    "Copy Protect",
-    "Connector",
-    "Overload",
-    "Underflow",
-    "Overflow",
-];
-const UAC3_OUTPUT_TERMINAL_BMCONTROLS: [&str; 4] =
-    ["Insertion", "Overload", "Underflow", "Overflow"];
-const UAC2_AS_INTERFACE_BMCONTROLS: [&str; 2] =
-    ["Active Alternate Setting", "Valid Alternate Setting"];
-const UAC3_AS_INTERFACE_BMCONTROLS: [&str; 3] = [
-    "Active Alternate Setting",
-    "Valid Alternate Setting",
-    "Audio Data Format Control",
-];
-const UAC2_AS_ISO_ENDPOINT_BMCONTROLS: [&str; 3] = ["Pitch", "Data Overrun", "Data Underrun"];
-const UAC2_MIXER_UNIT_BMCONTROLS: [&str; 4] = ["Cluster", "Underflow", "Overflow", "Overflow"];
-const UAC3_MIXER_UNIT_BMCONTROLS: [&str; 2] = ["Underflow", "Overflow"];
-const UAC2_SELECTOR_UNIT_BMCONTROLS: [&str; 1] = ["Selector"];
-const UAC1_FEATURE_UNIT_BMCONTROLS: [&str; 13] = [
-    "Mute",
-    "Volume",
-    "Bass",
-    "Mid",
-    "Treble",
-    "Graphic Equalizer",
-    "Automatic Gain",
-    "Delay",
-    "Bass Boost",
-    "Loudness",
-    "Input gain",
-    "Input gain pad",
-    "Phase invert",
-];
-const UAC2_EXTENSION_UNIT_BMCONTROLS: [&str; 4] = ["Enable", "Cluster", "Underflow", "Overflow"];
-const UAC3_EXTENSION_UNIT_BMCONTROLS: [&str; 2] = ["Underflow", "Overflow"];
-const UAC2_CLOCK_SOURCE_BMCONTROLS: [&str; 2] = ["Clock Frequency", "Clock Validity"];
-const UAC2_CLOCK_SELECTOR_BMCONTROLS: [&str; 1] = ["Clock Selector"];
-const UAC2_CLOCK_MULTIPLIER_BMCONTROLS: [&str; 2] = ["Clock Numerator", "Clock Denominator"];
-const UAC3_PROCESSING_UNIT_UP_DOWN_BMCONTROLS: [&str; 3] = ["Mode Select", "Underflow", "Overflow"];
-const UAC3_PROCESSING_UNIT_STEREO_EXTENDER_BMCONTROLS: [&str; 3] =
-    ["Width", "Underflow", "Overflow"];
-const UAC3_PROCESSING_UNIT_MULTI_FUNC_BMCONTROLS: [&str; 2] = ["Underflow", "Overflow"];
-
 fn dump_bitmap_controls<T: Into<u32>>(
     controls: T,
     control_descriptions: &[&'static str],
@@ -70,24 +9,23 @@ fn dump_bitmap_controls<T: Into<u32>>(
     indent: usize,
 ) {
     let controls: u32 = controls.into();
-    for (index, control) in control_descriptions.iter().enumerate() {
-        match desc_type {
-            audio::ControlType::BmControl1 => {
+    match desc_type {
+        audio::ControlType::BmControl1 => {
+            for (index, control) in control_descriptions.iter().enumerate() {
                 if (controls >> index) & 0x1 != 0 {
-                    println!("{:indent$}{} Control", "", control, indent = indent);
+                    outputln!("{:indent$}{} Control", "", control, indent = indent);
                 }
             }
-            audio::ControlType::BmControl2 => {
-                let c = ((controls >> (index * 2)) & 0x3) as u8;
-                if c != 0 {
-                    println!(
-                        "{:indent$}{} Control ({})",
-                        "",
-                        control,
-                        audio::ControlSetting::from(c),
-                        indent = indent
-                    )
-                }
+        }
+        audio::ControlType::BmControl2 => {
+            for c in audio::decode_bm_controls(controls, control_descriptions) {
+                outputln!(
+                    "{:indent$}{} Control ({})",
+                    "",
+                    c.name,
+                    c.setting,
+                    indent = indent
+                )
             }
         }
     }
@@ -120,7 +58,7 @@ fn dump_audio_mixer_unit1(mixer_unit: &audio::MixerUnit1, indent: usize, width:
         mixer_unit.channel_config as u32,
     );
     for name in channel_names.iter() {
-        println!("{:indent$}{}", "", name, indent = indent + 2);
+        outputln!("{:indent$}{}", "", name, indent = indent + 2);
     }
     dump_value(mixer_unit.channel_names, "iChannelNames", indent, width);
     dump_bitmap_array(&mixer_unit.controls, "bmControls", indent, width);
@@ -138,14 +76,14 @@ fn dump_audio_mixer_unit2(mixer_unit: &audio::MixerUnit2, indent: usize, width:
         mixer_unit.channel_config,
     );
     for name in channel_names.iter() {
-        println!("{:indent$}{}", "", name, indent = indent + 2);
+        outputln!("{:indent$}{}", "", name, indent = indent + 2);
     }
     dump_value(mixer_unit.channel_names, "iChannelNames", indent, width);
     dump_bitmap_array(&mixer_unit.mixer_controls, "bmMixerControls", indent, width);
     dump_hex(mixer_unit.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         mixer_unit.controls as u32,
-        &UAC2_MIXER_UNIT_BMCONTROLS,
+        &audio::UAC2_MIXER_UNIT_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -166,7 +104,7 @@ fn dump_audio_mixer_unit3(mixer_unit: &audio::MixerUnit3, indent: usize, width:
     dump_hex(mixer_unit.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         mixer_unit.controls,
-        &UAC3_MIXER_UNIT_BMCONTROLS,
+        &audio::UAC3_MIXER_UNIT_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -226,7 +164,7 @@ fn dump_audio_selector_unit2(selector_unit: &audio::SelectorUnit2, indent: usize
     dump_hex(selector_unit.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         selector_unit.controls,
-        &UAC2_SELECTOR_UNIT_BMCONTROLS,
+        &audio::UAC2_SELECTOR_UNIT_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -246,7 +184,7 @@ fn dump_audio_selector_unit3(selector_unit: &audio::SelectorUnit3, indent: usize
     dump_hex(selector_unit.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         selector_unit.controls,
-        &UAC2_SELECTOR_UNIT_BMCONTROLS,
+        &audio::UAC2_SELECTOR_UNIT_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -277,7 +215,7 @@ fn dump_audio_processing_unit1(unit: &audio::ProcessingUnit1, indent: usize, wid
         unit.channel_config as u32,
     );
     for name in channel_names.iter() {
-        println!("{:indent$}{}", "", name, indent = indent + 2);
+        outputln!("{:indent$}{}", "", name, indent = indent + 2);
     }
     dump_value_string(
         unit.channel_names_index,
@@ -320,7 +258,7 @@ fn dump_audio_processing_unit2(unit: &audio::ProcessingUnit2, indent: usize, wid
         unit.channel_config,
     );
     for name in channel_names.iter() {
-        println!("{:indent$}{}", "", name, indent = indent + 2);
+        outputln!("{:indent$}{}", "", name, indent = indent + 2);
     }
     dump_value_string(
         unit.channel_names_index,
@@ -375,7 +313,7 @@ fn dump_audio_processing_unit3(unit: &audio::ProcessingUnit3, indent: usize, wid
                 dump_hex(up_down_mix.controls, "bmControls", indent, width);
                 dump_bitmap_controls(
                     up_down_mix.controls,
-                    &UAC3_PROCESSING_UNIT_UP_DOWN_BMCONTROLS,
+                    &audio::UAC3_PROCESSING_UNIT_UP_DOWN_BMCONTROLS,
                     &audio::ControlType::BmControl2,
                     indent + 2,
                 );
@@ -391,7 +329,7 @@ fn dump_audio_processing_unit3(unit: &audio::ProcessingUnit3, indent: usize, wid
                 dump_hex(stereo_extender.controls, "bmControls", indent, width);
                 dump_bitmap_controls(
                     stereo_extender.controls,
-                    &UAC3_PROCESSING_UNIT_STEREO_EXTENDER_BMCONTROLS,
+                    &audio::UAC3_PROCESSING_UNIT_STEREO_EXTENDER_BMCONTROLS,
                     &audio::ControlType::BmControl2,
                     indent + 2,
                 );
@@ -400,7 +338,7 @@ fn dump_audio_processing_unit3(unit: &audio::ProcessingUnit3, indent: usize, wid
                 dump_hex(multi_function.controls, "bmControls", indent, width);
                 dump_bitmap_controls(
                     multi_function.controls,
-                    &UAC3_PROCESSING_UNIT_MULTI_FUNC_BMCONTROLS,
+                    &audio::UAC3_PROCESSING_UNIT_MULTI_FUNC_BMCONTROLS,
                     &audio::ControlType::BmControl2,
                     indent + 2,
                 );
@@ -413,7 +351,7 @@ fn dump_audio_processing_unit3(unit: &audio::ProcessingUnit3, indent: usize, wid
                 dump_value(multi_function.algorithms, "bmAlgorithms", indent, width);
                 if let Some(ref algorithms) = unit.algorithms() {
                     for algorithm in algorithms.iter() {
-                        println!("{:indent$}{}", "", algorithm, indent = indent + 2);
+                        outputln!("{:indent$}{}", "", algorithm, indent = indent + 2);
                     }
                 }
             }
@@ -454,7 +392,7 @@ fn dump_audio_feature_unit1(unit: &audio::FeatureUnit1, indent: usize, width: us
     dump_bitmap_controls_array(
         "bmaControls",
         &unit.controls,
-        &UAC1_FEATURE_UNIT_BMCONTROLS,
+        &audio::UAC1_FEATURE_UNIT_BMCONTROLS,
         &audio::ControlType::BmControl1,
         indent,
         width,
@@ -472,14 +410,19 @@ fn dump_audio_feature_unit1(unit: &audio::FeatureUnit1, indent: usize, width: us
 fn dump_audio_feature_unit2(unit: &audio::FeatureUnit2, indent: usize, width: usize) {
     dump_value(unit.unit_id, "bUnitID", indent, width);
     dump_value(unit.source_id, "bSourceID", indent, width);
-    dump_bitmap_controls_array(
-        "bmaControls",
-        &unit.controls,
-        &UAC1_FEATURE_UNIT_BMCONTROLS,
-        &audio::ControlType::BmControl1,
-        indent,
-        width,
-    );
+    for (i, controls) in core::iter::once(&unit.controls)
+        .chain(unit.channel_controls.iter())
+        .enumerate()
+    {
+        let controls = u32::from_le_bytes(*controls);
+        dump_hex(controls, &format!("bmaControls({:2})", i), indent, width);
+        dump_bitmap_controls(
+            controls,
+            &audio::UAC2_FEATURE_UNIT_BMCONTROLS,
+            &audio::ControlType::BmControl2,
+            indent + 2,
+        );
+    }
     dump_value_string(
         unit.feature_index,
         "iFeature",
@@ -493,14 +436,19 @@ fn dump_audio_feature_unit2(unit: &audio::FeatureUnit2, indent: usize, width: us
 fn dump_audio_feature_unit3(unit: &audio::FeatureUnit3, indent: usize, width: usize) {
     dump_value(unit.unit_id, "bUnitID", indent, width);
     dump_value(unit.source_id, "bSourceID", indent, width);
-    dump_bitmap_controls_array(
-        "bmaControls",
-        &unit.controls,
-        &UAC1_FEATURE_UNIT_BMCONTROLS,
-        &audio::ControlType::BmControl1,
-        indent,
-        width,
-    );
+    for (i, controls) in core::iter::once(&unit.controls)
+        .chain(unit.channel_controls.iter())
+        .enumerate()
+    {
+        let controls = u32::from_le_bytes(*controls);
+        dump_hex(controls, &format!("bmaControls({:2})", i), indent, width);
+        dump_bitmap_controls(
+            controls,
+            &audio::UAC2_FEATURE_UNIT_BMCONTROLS,
+            &audio::ControlType::BmControl2,
+            indent + 2,
+        );
+    }
     dump_value(unit.feature_descr_str, "wFeatureDescrStr", indent, width);
 }
 
@@ -517,7 +465,7 @@ fn dump_audio_extension_unit1(unit: &audio::ExtensionUnit1, indent: usize, width
         unit.channel_config as u32,
     );
     for name in channel_names.iter() {
-        println!("{:indent$}{}", "", name, indent = indent + 2);
+        outputln!("{:indent$}{}", "", name, indent = indent + 2);
     }
     dump_value(unit.channel_names_index, "iChannelNames", indent, width);
     dump_value_string(
@@ -551,7 +499,7 @@ fn dump_audio_extension_unit2(unit: &audio::ExtensionUnit2, indent: usize, width
         unit.channel_config,
     );
     for name in channel_names.iter() {
-        println!("{:indent$}{}", "", name, indent = indent + 2);
+        outputln!("{:indent$}{}", "", name, indent = indent + 2);
     }
     dump_value_string(
         unit.channel_names_index,
@@ -563,7 +511,7 @@ fn dump_audio_extension_unit2(unit: &audio::ExtensionUnit2, indent: usize, width
     dump_hex(unit.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         unit.controls,
-        &UAC2_EXTENSION_UNIT_BMCONTROLS,
+        &audio::UAC2_EXTENSION_UNIT_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -591,7 +539,7 @@ fn dump_audio_extension_unit3(unit: &audio::ExtensionUnit3, indent: usize, width
     dump_hex(unit.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         unit.controls,
-        &UAC3_EXTENSION_UNIT_BMCONTROLS,
+        &audio::UAC3_EXTENSION_UNIT_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -616,7 +564,7 @@ fn dump_audio_clock_source2(source: &audio::ClockSource2, indent: usize, width:
     dump_hex(source.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         source.controls,
-        &UAC2_CLOCK_SOURCE_BMCONTROLS,
+        &audio::UAC2_CLOCK_SOURCE_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -648,7 +596,7 @@ fn dump_audio_clock_source3(source: &audio::ClockSource3, indent: usize, width:
     dump_hex(source.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         source.controls,
-        &UAC2_CLOCK_SOURCE_BMCONTROLS,
+        &audio::UAC2_CLOCK_SOURCE_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -669,7 +617,7 @@ fn dump_audio_clock_selector2(selector: &audio::ClockSelector2, indent: usize, w
     dump_hex(selector.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         selector.controls,
-        &UAC2_CLOCK_SELECTOR_BMCONTROLS,
+        &audio::UAC2_CLOCK_SELECTOR_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -690,7 +638,7 @@ fn dump_audio_clock_selector3(selector: &audio::ClockSelector3, indent: usize, w
     dump_hex(selector.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         selector.controls,
-        &UAC2_CLOCK_SELECTOR_BMCONTROLS,
+        &audio::UAC2_CLOCK_SELECTOR_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -709,7 +657,7 @@ fn dump_audio_clock_multiplier2(multiplier: &audio::ClockMultiplier2, indent: us
     dump_hex(multiplier.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         multiplier.controls,
-        &UAC2_CLOCK_MULTIPLIER_BMCONTROLS,
+        &audio::UAC2_CLOCK_MULTIPLIER_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -729,7 +677,7 @@ fn dump_audio_clock_multiplier3(multiplier: &audio::ClockMultiplier3, indent: us
     dump_hex(multiplier.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         multiplier.controls,
-        &UAC2_CLOCK_MULTIPLIER_BMCONTROLS,
+        &audio::UAC2_CLOCK_MULTIPLIER_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -784,7 +732,7 @@ fn dump_audio_header2(header: &audio::Header2, indent: usize, width: usize) {
     dump_hex(header.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         header.controls as u32,
-        &UAC2_INTERFACE_HEADER_BMCONTROLS,
+        &audio::UAC2_INTERFACE_HEADER_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -796,7 +744,7 @@ fn dump_audio_header3(header: &audio::Header3, indent: usize, width: usize) {
     dump_hex(header.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         header.controls,
-        &UAC2_INTERFACE_HEADER_BMCONTROLS,
+        &audio::UAC2_INTERFACE_HEADER_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -804,7 +752,7 @@ fn dump_audio_header3(header: &audio::Header3, indent: usize, width: usize) {
 
 fn dump_audio_input_terminal1(ait: &audio::InputTerminal1, indent: usize, width: usize) {
     dump_value(ait.terminal_id, "bTerminalID", indent, width);
-    println!(
+    outputln!(
         "{:indent$}wTerminalType      {:5} {}",
         "",
         ait.terminal_type,
@@ -819,7 +767,7 @@ fn dump_audio_input_terminal1(ait: &audio::InputTerminal1, indent: usize, width:
         ait.channel_config as u32,
     );
     for name in channel_names.iter() {
-        println!("{:indent$}{}", "", name, indent = indent + 2);
+        outputln!("{:indent$}{}", "", name, indent = indent + 2);
     }
     dump_value_string(
         ait.channel_names_index,
@@ -854,7 +802,7 @@ fn dump_audio_input_terminal2(ait: &audio::InputTerminal2, indent: usize, width:
         ait.channel_config,
     );
     for name in channel_names.iter() {
-        println!("{:indent$}{}", "", name, indent = indent + 2);
+        outputln!("{:indent$}{}", "", name, indent = indent + 2);
     }
     dump_value_string(
         ait.channel_names_index,
@@ -866,7 +814,7 @@ fn dump_audio_input_terminal2(ait: &audio::InputTerminal2, indent: usize, width:
     dump_hex(ait.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         ait.controls,
-        &UAC2_INPUT_TERMINAL_BMCONTROLS,
+        &audio::UAC2_INPUT_TERMINAL_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -894,7 +842,7 @@ fn dump_audio_input_terminal3(ait: &audio::InputTerminal3, indent: usize, width:
     dump_hex(ait.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         ait.controls,
-        &UAC3_INPUT_TERMINAL_BMCONTROLS,
+        &audio::UAC3_INPUT_TERMINAL_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -943,7 +891,7 @@ fn dump_audio_output_terminal2(a: &audio::OutputTerminal2, indent: usize, width:
     dump_hex(a.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         a.controls,
-        &UAC2_OUTPUT_TERMINAL_BMCONTROLS,
+        &audio::UAC2_OUTPUT_TERMINAL_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -970,7 +918,7 @@ fn dump_audio_output_terminal3(a: &audio::OutputTerminal3, indent: usize, width:
     dump_hex(a.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         a.controls,
-        &UAC3_OUTPUT_TERMINAL_BMCONTROLS,
+        &audio::UAC3_OUTPUT_TERMINAL_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -995,7 +943,7 @@ fn dump_audio_streaming_interface2(asi: &audio::StreamingInterface2, indent: usi
     dump_hex(asi.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         asi.controls,
-        &UAC2_AS_INTERFACE_BMCONTROLS,
+        &audio::UAC2_AS_INTERFACE_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -1007,7 +955,7 @@ fn dump_audio_streaming_interface2(asi: &audio::StreamingInterface2, indent: usi
         asi.channel_config,
     );
     for name in channel_names.iter() {
-        println!("{:indent$}{}", "", name, indent = indent + 2);
+        outputln!("{:indent$}{}", "", name, indent = indent + 2);
     }
     dump_value_string(
         asi.channel_names_index,
@@ -1023,7 +971,7 @@ fn dump_audio_streaming_interface3(asi: &audio::StreamingInterface3, indent: usi
     dump_hex(asi.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         asi.controls,
-        &UAC3_AS_INTERFACE_BMCONTROLS,
+        &audio::UAC3_AS_INTERFACE_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -1067,7 +1015,7 @@ fn dump_audio_data_streaming_endpoint2(
     dump_hex(ads.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         ads.controls,
-        &UAC2_AS_ISO_ENDPOINT_BMCONTROLS,
+        &audio::UAC2_AS_ISO_ENDPOINT_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -1083,7 +1031,7 @@ fn dump_audio_data_streaming_endpoint3(
     dump_hex(ads.controls, "bmControls", indent, width);
     dump_bitmap_controls(
         ads.controls,
-        &UAC2_AS_ISO_ENDPOINT_BMCONTROLS,
+        &audio::UAC2_AS_ISO_ENDPOINT_BMCONTROLS,
         &audio::ControlType::BmControl2,
         indent + 2,
     );
@@ -1119,9 +1067,11 @@ fn dump_audio_streaming_format(af: &audio::StreamingFormat, indent: usize, width
         audio::StreamingFormatInterface::FormatTypeIII2(asi) => {
             dump_format_type_iii_uac2(asi, indent, width);
         }
+        // nothing beyond bFormatType
+        audio::StreamingFormatInterface::FormatTypeIV2 => (),
         i => {
             let data: Vec<u8> = i.to_owned().into();
-            println!(
+            outputln!(
                 "{:indent$}invalid desc format type: {}",
                 "",
                 data.iter()
@@ -1151,7 +1101,7 @@ fn dump_audio_streaming_format_specific(
         }
         i => {
             let data: Vec<u8> = i.to_owned().into();
-            println!(
+            outputln!(
                 "{:indent$}Invalid desc format type: {}",
                 "",
                 data.iter()
@@ -1300,7 +1250,7 @@ fn dump_audio_subtype(uacid: &audio::UacInterfaceDescriptor, indent: usize) {
         }
         audio::UacInterfaceDescriptor::Undefined(data)
         | audio::UacInterfaceDescriptor::Invalid(data) => {
-            println!(
+            outputln!(
                 "{:indent$}Invalid desc subtype: {}",
                 "",
                 data.iter()
@@ -1339,7 +1289,7 @@ pub(crate) fn dump_audiocontrol_interface(
 
     match &uacd.interface {
         audio::UacInterfaceDescriptor::Invalid(_) => {
-            println!(
+            outputln!(
                 "{:indent$}Warning: {:#} descriptors are illegal for {}",
                 "",
                 uacd.descriptor_subtype,
@@ -1527,7 +1477,7 @@ fn dump_format_specific_mpeg(fs: &audio::FormatSpecificMpeg, indent: usize, widt
         },
         indent + 2,
     );
-    println!(
+    outputln!(
         "{:indent$}MPEG-2 multilingual support: {}",
         "",
         match (fs.mpeg_capabilities >> 8) & 3 {
@@ -1539,7 +1489,7 @@ fn dump_format_specific_mpeg(fs: &audio::FormatSpecificMpeg, indent: usize, widt
         indent = indent + 2
     );
     dump_hex(fs.mpeg_features, "bmMPEGFeatures", indent, width);
-    println!(
+    outputln!(
         "{:indent$}Internal Dynamic Range Control: {}",
         "",
         match (fs.mpeg_features >> 4) & 3 {
@@ -1566,7 +1516,7 @@ fn dump_format_specific_ac3(fs: &audio::FormatSpecificAc3, indent: usize, width:
         },
         indent + 2,
     );
-    println!(
+    outputln!(
         "{:indent$}Internal Dynamic Range Control: {}",
         "",
         match (fs.ac3_features >> 4) & 3 {
@@ -1603,7 +1553,7 @@ pub(crate) fn dump_audiostreaming_interface(
 
     match &uacd.interface {
         audio::UacInterfaceDescriptor::Invalid(_) => {
-            println!(
+            outputln!(
                 "{:indent$}Warning: {:#} descriptors are illegal for {}",
                 "",
                 uacd.descriptor_subtype,
@@ -1810,7 +1760,7 @@ pub(crate) fn dump_midistreaming_interface(md: &audio::MidiDescriptor, indent: u
         audio::MidiInterfaceDescriptor::Undefined(_) => (),
         invalid => {
             let data: Vec<u8> = invalid.to_owned().into();
-            println!(
+            outputln!(
                 "{:indent$}Invalid desc subtype: {}",
                 "",
                 data.iter()
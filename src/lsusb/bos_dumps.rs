@@ -246,7 +246,12 @@ fn dump_billboard_capability(d: &bos::BillboardCapability, indent: usize) {
         .join(" ");
     dump_value(bytes_string, "bmConfigured", indent + 2, LSUSB_DUMP_WIDTH);
 
-    dump_value(d.version, "bcdVersion", indent + 2, LSUSB_DUMP_WIDTH);
+    // as lsusb, a zero major version is shown as 1
+    let version = match d.version {
+        crate::usb::Version(0, minor, sub_minor) => crate::usb::Version(1, minor, sub_minor),
+        v => v,
+    };
+    dump_value(version, "bcdVersion", indent + 2, LSUSB_DUMP_WIDTH);
     dump_value(
         d.additional_failure_info,
         "bAdditionalFailureInfo",
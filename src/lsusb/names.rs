@@ -1,79 +1,134 @@
 //! Port of names.c in usbutils that provides name lookups for USB data using udev, falling back to USB IDs repository.
 //!
-//! lsusb uses udev and the bundled hwdb (based on USB IDs) for name lookups. To attempt parity with lsusb, this module uses udev_hwdb if the feature is enabled, otherwise it will fall back to the USB IDs repository. Whilst they both get data from the same source, the bundled udev hwdb might be different due to release version/customisations.
+//! lsusb uses udev and the bundled hwdb (based on USB IDs) for name lookups. To attempt parity with lsusb, this module uses udev_hwdb if the feature is enabled, falling back to the USB IDs repository bundled by the usb-ids crate when the hwdb is unavailable or has no entry; see [`sources`]. Whilst they both get data from the same source, the bundled udev hwdb might be different due to release version/customisations.
 //!
 //! The function names match those found in the lsusb source code.
 #[allow(unused_imports)]
 use crate::error::{Error, ErrorKind};
 use usb_ids::{self, FromId};
 
-/// Get name of vendor from [`usb_ids::Vendor`] or `hwdb_get` if feature is enabled
+/// Source of names, looked up in the order of [`sources`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// udev hardware database (hwdb.bin), which is generated from USB IDs but may be a different release or customised by the distribution
+    Hwdb,
+    /// USB IDs repository data bundled at build time by the usb-ids crate
+    UsbIds,
+}
+
+/// Order in which [`Source`]s are tried for [`vendor`], [`product`], [`class`], [`subclass`] and [`protocol`]: the hwdb if the 'udevlib' and 'udev_hwdb' features are enabled, then the bundled USB IDs
+///
+/// A source which is unavailable, such as a system without hwdb.bin, or which has no entry falls through to the next so the result only depends on the data, not the system setup.
+///
+/// ```
+/// use cyme::lsusb::names::{self, Source};
+/// assert_eq!(names::sources().last(), Some(&Source::UsbIds));
+/// ```
+pub fn sources() -> &'static [Source] {
+    if HWDB_ENABLED {
+        &[Source::Hwdb, Source::UsbIds]
+    } else {
+        &[Source::UsbIds]
+    }
+}
+
+const HWDB_ENABLED: bool = cfg!(all(
+    target_os = "linux",
+    feature = "udev_hwdb",
+    not(feature = "udev")
+));
+
+/// Get name of vendor from the [`sources`]: `hwdb_get` then [`usb_ids::Vendor`]
 ///
 /// ```
 /// use cyme::lsusb::names;
 /// assert_eq!(names::vendor(0x1d6b), Some("Linux Foundation".to_owned()));
 /// ```
 pub fn vendor(vid: u16) -> Option<String> {
-    hwdb_get(&format!("usb:v{:04X}*", vid), "ID_VENDOR_FROM_DATABASE")
-        .unwrap_or_else(|_| usb_ids::Vendor::from_id(vid).map(|v| v.name().to_owned()))
+    lookup(
+        &format!("usb:v{:04X}*", vid),
+        "ID_VENDOR_FROM_DATABASE",
+        || usb_ids::Vendor::from_id(vid).map(|v| v.name()),
+    )
 }
 
-/// Get name of product from [`usb_ids::Device`] or `hwdb_get` if feature is enabled
+/// Get name of product from the [`sources`]: `hwdb_get` then [`usb_ids::Device`]
 ///
 /// ```
 /// use cyme::lsusb::names;
 /// assert_eq!(names::product(0x1d6b, 0x0003), Some("3.0 root hub".to_owned()));
 /// ```
 pub fn product(vid: u16, pid: u16) -> Option<String> {
-    hwdb_get(
+    lookup(
         &format!("usb:v{:04X}p{:04X}*", vid, pid),
         "ID_MODEL_FROM_DATABASE",
+        || usb_ids::Device::from_vid_pid(vid, pid).map(|v| v.name()),
     )
-    .unwrap_or_else(|_| usb_ids::Device::from_vid_pid(vid, pid).map(|v| v.name().to_owned()))
 }
 
-/// Get name of class from [`usb_ids::Class`] or `hwdb_get` if feature is enabled
+/// Get name of class from the [`sources`]: `hwdb_get` then [`usb_ids::Class`]
 ///
 /// ```
 /// use cyme::lsusb::names;
 /// assert_eq!(names::class(0x03), Some("Human Interface Device".to_owned()));
 /// ```
 pub fn class(id: u8) -> Option<String> {
-    hwdb_get(
+    lookup(
         &format!("usb:v*p*d*dc{:02X}*", id),
         "ID_USB_CLASS_FROM_DATABASE",
+        || usb_ids::Class::from_id(id).map(|v| v.name()),
     )
-    .unwrap_or_else(|_| usb_ids::Class::from_id(id).map(|v| v.name().to_owned()))
 }
 
-/// Get name of sub class from [`usb_ids::SubClass`] or `hwdb_get` if feature is enabled
+/// Get name of sub class from the [`sources`]: `hwdb_get` then [`usb_ids::SubClass`]
 ///
 /// ```
 /// use cyme::lsusb::names;
 /// assert_eq!(names::subclass(0x02, 0x02), Some("Abstract (modem)".to_owned()));
 /// ```
 pub fn subclass(cid: u8, scid: u8) -> Option<String> {
-    hwdb_get(
+    lookup(
         &format!("usb:v*p*d*dc{:02X}dsc{:02X}*", cid, scid),
         "ID_USB_SUBCLASS_FROM_DATABASE",
+        || usb_ids::SubClass::from_cid_scid(cid, scid).map(|v| v.name()),
     )
-    .unwrap_or_else(|_| usb_ids::SubClass::from_cid_scid(cid, scid).map(|v| v.name().to_owned()))
 }
 
-/// Get name of protocol from [`usb_ids::Protocol`] or `hwdb_get` if feature is enabled
+/// Get name of protocol from the [`sources`]: `hwdb_get` then [`usb_ids::Protocol`]
 ///
 /// ```
 /// use cyme::lsusb::names;
 /// assert_eq!(names::protocol(0x02, 0x02, 0x05), Some("AT-commands (3G)".to_owned()));
 /// ```
 pub fn protocol(cid: u8, scid: u8, pid: u8) -> Option<String> {
-    hwdb_get(
+    lookup(
         &format!("usb:v*p*d*dc{:02X}dsc{:02X}dp{:02X}*", cid, scid, pid),
         "ID_USB_PROTOCOL_FROM_DATABASE",
+        || usb_ids::Protocol::from_cid_scid_pid(cid, scid, pid).map(|v| v.name()),
     )
-    .unwrap_or_else(|_| {
-        usb_ids::Protocol::from_cid_scid_pid(cid, scid, pid).map(|v| v.name().to_owned())
-    })
+}
+
+/// Get the organisation assigned the IEEE OUI, the first three octets, of a MAC or Bluetooth `address` such as "00:1A:7D:DA:71:13" from the udev hwdb if a udev feature is enabled
+///
+/// The USB IDs repository has no OUI data so there is no fallback.
+pub fn oui(address: &str) -> Option<String> {
+    let hex: String = address
+        .chars()
+        .filter(|c| c.is_ascii_hexdigit())
+        .take(6)
+        .collect();
+    if hex.len() != 6 {
+        return None;
+    }
+    let modalias = format!("OUI:{}", hex.to_uppercase());
+
+    #[cfg(all(target_os = "linux", feature = "udev"))]
+    return crate::udev::hwdb::get(&modalias, "ID_OUI_FROM_DATABASE")
+        .ok()
+        .flatten();
+
+    #[cfg(not(all(target_os = "linux", feature = "udev")))]
+    return hwdb_get(&modalias, "ID_OUI_FROM_DATABASE").ok().flatten();
 }
 
 /// Get HID descriptor type name from [`usb_ids::Hid`]
@@ -121,6 +176,22 @@ pub fn videoterminal(id: u16) -> Option<String> {
     usb_ids::VideoTerminal::from_id(id).map(|v| v.name().to_owned())
 }
 
+/// Look up `key` of `modalias` in each of the [`sources`] in order, with `usb_ids` the [`Source::UsbIds`] lookup
+fn lookup<F: FnOnce() -> Option<&'static str>>(
+    modalias: &str,
+    key: &'static str,
+    usb_ids: F,
+) -> Option<String> {
+    match hwdb_get(modalias, key) {
+        Ok(Some(name)) => return Some(name),
+        Ok(None) => log::trace!("No hwdb {} for {}", key, modalias),
+        // expected without the feature so don't log unless it is enabled
+        Err(e) if HWDB_ENABLED => log::debug!("hwdb unavailable for {}: {}", modalias, e),
+        Err(_) => (),
+    }
+    usb_ids().map(|n| n.to_owned())
+}
+
 /// Wrapper around [`crate::udev::hwdb_get`] so that it can be 'used' without feature
 ///
 /// Returns `Err` not `None` if feature is not enabled so that with unwrap_or hwdb can still return `None` if no match in db
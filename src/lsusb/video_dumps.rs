@@ -207,7 +207,7 @@ pub(crate) fn dump_videocontrol_interface(
 
     match &vcd.interface {
         video::UvcInterfaceDescriptor::Invalid(_) => {
-            println!(
+            outputln!(
                 "{:indent$}Warning: {:#} descriptors are illegal for {}",
                 "",
                 vct,
@@ -726,7 +726,7 @@ fn dump_video_subtype(
         }
         video::UvcInterfaceDescriptor::Undefined(data)
         | video::UvcInterfaceDescriptor::Invalid(data) => {
-            println!(
+            outputln!(
                 "{:indent$}Invalid desc subtype: {}",
                 "",
                 data.iter()
@@ -767,7 +767,7 @@ pub(crate) fn dump_videostreaming_interface(
 
     match &vsd.interface {
         video::UvcInterfaceDescriptor::Invalid(_) => {
-            println!(
+            outputln!(
                 "{:indent$}Warning: {:#} descriptors are illegal for {}",
                 "",
                 vst,
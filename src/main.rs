@@ -1,17 +1,27 @@
 //! Where the magic happens for `cyme` binary!
 use clap::Parser;
-use colored::*;
+use clap::Subcommand;
+use clap::ValueEnum;
+use cyme::colour::colored::*;
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use std::env;
-use terminal_size::terminal_size;
+use std::io::IsTerminal;
+#[cfg(feature = "watch")]
+use std::time::Duration;
+use terminal_size::{terminal_size, Height};
 
 use cyme::config::Config;
 use cyme::display;
 use cyme::error::{Error, ErrorKind, Result};
+use cyme::i18n;
+use cyme::logger::{LogFormat, Logger, ModuleFilter};
 use cyme::lsusb;
 use cyme::profiler;
-use cyme::usb::BaseClass;
+use cyme::profiler::capabilities::{Capabilities, Hint};
+use cyme::profiler::expression::FilterExpression;
+use cyme::redact::Redaction;
+use cyme::usb::{BaseClass, ParseMode, PortPath};
 
 #[derive(Parser, Debug, Default, Serialize, Deserialize)]
 #[skip_serializing_none]
@@ -49,6 +59,26 @@ struct Args {
     #[arg(long)]
     filter_class: Option<BaseClass>,
 
+    /// Filter on devices with interfaces of the active configuration no driver is bound to, marked with the 'tree-interface-unclaimed' icon; Linux only as other platforms do not report bound drivers
+    #[arg(long, default_value_t = false)]
+    filter_unclaimed: bool,
+
+    /// Filter on where devices are relative to the machine: 'internal' hard-wired devices such as a laptop webcam, 'external' plug-in peripherals or 'dock'; a heuristic from sysfs 'removable', the hub descriptors and ACPI physical location so devices of unknown location are filtered out
+    #[arg(long, value_name = "LOCATION")]
+    filter_location: Option<profiler::LocationKind>,
+
+    /// Filter on a boolean expression of 'field operator value' conditions combined with '&&', '||', '!' and parentheses; e.g. 'class==hid && vid==0x046d || name~"Webcam"'. Fields are vid, pid, bus, number, name, serial, manufacturer and class; '=' or '~' match like the --filter flags, '==' is exact and '!=' or '!~' negate
+    #[arg(long = "where", value_name = "EXPRESSION")]
+    filter_expression: Option<FilterExpression>,
+
+    /// Mark devices matching an expression like --where rather than filtering, keeping the full tree for context; e.g. 'vid==0x1d50 && name~Probe'
+    #[arg(long, value_name = "EXPRESSION")]
+    highlight: Option<FilterExpression>,
+
+    /// Colour serial numbers shared by more than one device, or missing, with the warning colour; duplicates are found before filtering. `cyme serials` reports them
+    #[arg(long, default_value_t = false)]
+    warn_duplicates: bool,
+
     /// Verbosity level (repeat provides count): 1 prints device configurations; 2 prints interfaces; 3 prints interface endpoints; 4 prints everything and more blocks
     #[arg(short = 'v', long, default_value_t = 0, action = clap::ArgAction::Count)]
     verbose: u8,
@@ -98,6 +128,14 @@ struct Args {
     #[arg(long, default_value_t = false)]
     hide_hubs: bool,
 
+    /// Collapse identical sibling devices, same VID:PID and name with nothing attached, into a single line with a 'xN' count. Verbose output prints them all
+    #[arg(long, default_value_t = false)]
+    compact: bool,
+
+    /// Maximum depth of devices to print with --tree: 0 for buses only, 1 for devices directly on the bus and so on. Hidden devices are counted on the last printed line
+    #[arg(long)]
+    max_depth: Option<usize>,
+
     /// Show root hubs when listing; Linux only
     #[arg(long, default_value_t = false)]
     list_root_hubs: bool,
@@ -138,20 +176,157 @@ struct Args {
     #[arg(long, default_value_t = false)]
     headings: bool,
 
+    /// Alternate the background of list rows with the 'stripe' colour to make wide lines easier to follow
+    #[arg(long, default_value_t = false, conflicts_with_all = ["tree", "json", "lsusb"])]
+    stripe: bool,
+
+    /// Separate list blocks with this string rather than a space, ' | ' for example
+    #[arg(long, conflicts_with_all = ["tree", "json", "lsusb", "grid"])]
+    separator: Option<String>,
+
+    /// Print the list as a table with a border around each block and a heading row; verbose configurations are not printed
+    #[arg(long, default_value_t = false, conflicts_with_all = ["tree", "json", "lsusb"])]
+    grid: bool,
+
     /// Output as json format after sorting, filters and tree settings are applied; without -tree will be flattened dump of devices
     #[arg(long, default_value_t = false, overrides_with = "lsusb")]
     json: bool,
 
+    /// Output only these fields for each device as json, supplied as comma separated block names. Implies --json and output is always a flat list of devices
+    #[arg(long, value_enum, value_delimiter = ',')]
+    json_fields: Option<Vec<display::DeviceBlocks>>,
+
+    /// When filtering a tree, reduce non-matching parents of matched devices to slim stubs of location and identity only; keeps topology context of matches in --json output without the bulk
+    #[arg(long, default_value_t = false)]
+    stub_parents: bool,
+
+    /// Output as XML modeled on the Windows USBView export; a tree of devices with --tree and a list under each host controller otherwise
+    #[arg(long, default_value_t = false, conflicts_with_all = ["json", "json_fields", "get", "count", "lsusb"])]
+    xml: bool,
+
+    /// Render each device through this Handlebars-style template file, or the whole profile once with --tree; fields are those of the json output plus port_path
+    #[arg(long, conflicts_with_all = ["json", "json_fields", "get", "count", "lsusb", "xml"])]
+    template: Option<String>,
+
+    /// Print only the raw value of this block for each matching device, one per line; for use in shell scripts
+    #[arg(long, value_enum, conflicts_with_all = ["json", "json_fields", "count", "lsusb"])]
+    get: Option<display::DeviceBlocks>,
+
+    /// Print only the number of devices after filters are applied; with --group-devices prints the count for each group
+    #[arg(long, default_value_t = false)]
+    count: bool,
+
+    /// Print the icon rule matched by each device from the user config icons or defaults; for checking icon theme rules
+    #[arg(long, default_value_t = false, conflicts_with_all = ["json", "count", "get", "lsusb"])]
+    list_icons: bool,
+
+    /// Print the estimated bandwidth reserved by periodic (interrupt and isochronous) endpoints on each bus and by each device, for diagnosing 'not enough bandwidth' errors; -v lists the endpoints
+    #[arg(long, default_value_t = false, conflicts_with_all = ["json_fields", "count", "get", "lsusb", "list_icons", "template", "xml"])]
+    bandwidth: bool,
+
+    /// Write the output to a file with colour stripped rather than stdout; a summary is printed to stderr
+    #[arg(long, value_name = "FILE")]
+    output: Option<String>,
+
+    /// With --output also print the output to stdout
+    #[arg(long, default_value_t = false, requires = "output")]
+    tee: bool,
+
+    /// Convert coloured output to HTML spans styled with the theme colours, within a <pre> element, for reports and CI logs which strip ANSI colour codes; written to stdout or --output
+    #[arg(long, default_value_t = false)]
+    ansi_to_html: bool,
+
+    /// Do not pipe output longer than the terminal to $PAGER or 'less -R' in interactive sessions
+    #[arg(long, default_value_t = false)]
+    no_pager: bool,
+
+    /// Exit with code 1 and no output, other than the 0 of --count, if no devices match filters; errors then exit with code 2. Like grep, allows scripts to check a device is present
+    #[arg(long, default_value_t = false)]
+    fail_if_empty: bool,
+
+    /// With --tree and -v, print lsusb style descriptor sections inside the tree below each device rather than configuration, interface and endpoint blocks; -v prints configurations, -vv interfaces and -vvv endpoints
+    #[arg(long, default_value_t = false, conflicts_with_all = ["json", "lsusb"])]
+    inline_descriptors: bool,
+
+    /// Print the raw configuration, interface and endpoint descriptor bytes as an annotated hexdump below each, like `lsusb -D`; implies -vvv. Also included in --json
+    #[arg(long, default_value_t = false)]
+    dump_descriptors: bool,
+
+    /// Print only the configuration currently set on devices with more than one in verbose output, rather than all of them. The active configuration is marked with '*'
+    #[arg(long, default_value_t = false)]
+    active_config_only: bool,
+
+    /// Include Thunderbolt/USB4 domain and router topology with --tree (Linux only)
+    #[arg(long, default_value_t = false)]
+    thunderbolt: bool,
+
+    /// Include USB device controller (gadget) state and the configfs gadgets with their functions with --tree (Linux only)
+    #[arg(long, default_value_t = false)]
+    gadget: bool,
+
     /// Read from json output rather than profiling system
     #[arg(long)]
     from_json: Option<String>,
 
+    /// Read a device from raw descriptor files written by `cyme export-descriptors` rather than profiling system
+    #[arg(long, conflicts_with = "from_json")]
+    from_descriptors: Option<String>,
+
+    /// Read a text or XML report saved by the Windows USBView tool rather than profiling system
+    #[arg(long, conflicts_with_all = ["from_json", "from_descriptors"])]
+    from_usbview: Option<String>,
+
+    /// Use the profile cached by a previous run if younger than --cache-ttl and the connected devices have not changed, otherwise profile and update the cache
+    #[arg(long, default_value_t = false, conflicts_with_all = ["from_json", "from_descriptors", "from_usbview"])]
+    cached: bool,
+
+    /// Maximum age in seconds of the profile used with --cached
+    #[arg(long, default_value_t = 60)]
+    cache_ttl: u64,
+
     /// Force pure libusb profiler on macOS rather than combining system_profiler output
     ///
     /// Has no effect on other platforms or when using nusb
     #[arg(short = 'F', long, default_value_t = false)]
     force_libusb: bool,
 
+    /// USB library to profile devices with when more than one is compiled in; defaults to nusb if available
+    #[arg(long, value_enum)]
+    backend: Option<profiler::backend::BackendKind>,
+
+    /// Built-in colour theme; cb-safe is distinguishable with red-green colour blindness and legible on dark and light backgrounds
+    #[arg(long, value_enum)]
+    theme: Option<cyme::colour::Theme>,
+
+    /// Re-run with sudo, prompting for the password, if devices could not be opened for verbose data due to permissions
+    #[arg(long, default_value_t = false, conflicts_with_all = ["from_json", "from_descriptors", "from_usbview"])]
+    sudo: bool,
+
+    /// Check configuration descriptors against the lengths and counts they declare, showing mismatches after the output; useful to spot malformed descriptors in firmware
+    #[arg(long, default_value_t = false)]
+    show_errors: bool,
+
+    /// Fail with the first descriptor which cannot be parsed rather than showing the device without it; for validating device firmware
+    #[arg(long, default_value_t = false)]
+    strict: bool,
+
+    /// Print non-critical profiler errors to stderr rather than logging; config only
+    #[arg(skip)]
+    #[serde(skip)]
+    print_non_critical_profiler_stderr: bool,
+
+    /// Print the data sources available to the profiler, such as udev and the USB backends, and the fields missing without them then exit; json with --json
+    #[arg(long, default_value_t = false)]
+    capabilities: bool,
+
+    /// Do not open devices with this device or interface class for verbose data, since opening some devices, such as smartcard readers or printers, has side effects. Supply arg multiple times to specify multiple classes
+    #[arg(long)]
+    no_open_class: Option<Vec<BaseClass>>,
+
+    /// Order of sources for device names, comma separated; sources not listed are not used. lsusb prefers USB IDs: 'ids,os,descriptor'. Default 'user,os,descriptor,ids'
+    #[arg(long, value_enum, value_delimiter = ',', num_args = 1..)]
+    name_sources: Option<Vec<profiler::names::NameSource>>,
+
     /// Path to user config file to use for custom icons, colours and default settings
     #[arg(short = 'c', long)]
     config: Option<String>,
@@ -161,44 +336,512 @@ struct Args {
     // short -d taken by lsusb compat vid:pid
     debug: u8,
 
-    /// Mask serial numbers with '*' or random chars
+    /// Append logs to a file rather than stderr; logs at -zz level if --debug is not passed
+    #[arg(long)]
+    log_file: Option<String>,
+
+    /// Format of logs
+    #[arg(long, value_enum, default_value_t = LogFormat::Pretty)]
+    log_format: LogFormat,
+
+    /// Per-module log levels as 'module=level' such as 'profiler=debug,udev=trace'; cyme modules include profiler, udev and display
+    #[arg(long, value_delimiter = ',', num_args = 1..)]
+    log_filter: Vec<ModuleFilter>,
+
+    /// Mask serial numbers with '*', random chars or a hash which is the same for a device in every dump; Bluetooth addresses and paths containing the serial are masked too
     #[arg(long)]
     mask_serials: Option<display::MaskSerial>,
 
+    /// Salt for --mask-serials hash; set a private one so shared hashes cannot be matched to known serials
+    #[arg(long)]
+    mask_salt: Option<String>,
+
+    /// Redact serials, Bluetooth addresses, the hostname and user name and volume names in every output format so dumps are safe to share; the 'redact' of the config sets what is redacted. Subcommands redact what they print, except rules, capture and the device actions which error
+    #[arg(long, default_value_t = false)]
+    redact: bool,
+
+    /// Units of speeds in blocks and the lsusb tree; defaults to decimal for blocks and lsusb for the tree
+    #[arg(long, value_enum)]
+    speed_units: Option<cyme::usb::SpeedUnits>,
+
     /// Generate cli completions and man page
     #[arg(long, hide = true, exclusive = true)]
     gen: bool,
 
+    /// Print values of the connected devices for the shell completions to complete an argument with
+    #[arg(long, hide = true, exclusive = true, value_enum)]
+    complete_candidates: Option<CompleteCandidates>,
+
     /// Use the system_profiler command on macOS to get USB data
     ///
     /// If not using nusb this is the default for macOS, merging with libusb data for verbose output. nusb uses IOKit directly so does not use system_profiler by default
     #[arg(long, default_value_t = false)]
     system_profiler: bool,
+
+    /// Command to run rather than listing, such as comparing dumps or an action on a device
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Arguments completed with values of the connected devices by the shell completions
+#[derive(Debug, Clone, Copy, ValueEnum, Serialize, Deserialize)]
+enum CompleteCandidates {
+    /// --vidpid VID:PID
+    Vidpid,
+    /// --show BUS:DEVNUM
+    Show,
+    /// --device /dev/bus/usb/BBB/DDD
+    Device,
+}
+
+/// Filters of devices for `wait` and `watch`
+#[derive(clap::Args, Debug, Serialize, Deserialize)]
+struct DeviceFilterArgs {
+    /// Device vendor and product ID numbers (in hexadecimal) in format VID:[PID]
+    #[arg(short = 'd', long)]
+    vidpid: Option<String>,
+
+    /// Device name containing this string
+    #[arg(long)]
+    name: Option<String>,
+
+    /// Device serial containing this string
+    #[arg(long)]
+    serial: Option<String>,
+
+    /// Boolean filter expression like the main --where
+    #[arg(long = "where", value_name = "EXPRESSION")]
+    filter_expression: Option<FilterExpression>,
+}
+
+impl DeviceFilterArgs {
+    fn is_empty(&self) -> bool {
+        self.vidpid.is_none()
+            && self.name.is_none()
+            && self.serial.is_none()
+            && self.filter_expression.is_none()
+    }
+
+    fn to_filter(&self) -> Result<profiler::Filter> {
+        let mut filter = profiler::Filter {
+            name: self.name.clone(),
+            serial: self.serial.clone(),
+            expression: self.filter_expression.clone(),
+            ..Default::default()
+        };
+        if let Some(vidpid) = self.vidpid.as_ref() {
+            (filter.vid, filter.pid) = parse_vidpid(vidpid).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidArg,
+                    &format!("Failed to parse vidpid '{}'; Error({})", vidpid, e),
+                )
+            })?;
+        }
+        Ok(filter)
+    }
+
+    /// [`profiler::watch::Watcher`] of devices matching the filter profiled with `builder`, with extra data if `extra` or the filter requires it
+    ///
+    /// Only devices which connect are profiled each poll using a [`profiler::SharedProfile`].
+    #[cfg(feature = "watch")]
+    fn watcher(
+        &self,
+        builder: &profiler::ProfilerBuilder,
+        interval: u64,
+        extra: bool,
+    ) -> Result<profiler::watch::Watcher> {
+        let filter = self.to_filter()?;
+        // class of interfaces requires extra
+        let with_extra = extra
+            || filter
+                .expression
+                .as_ref()
+                .is_some_and(|e| e.contains_field(profiler::expression::Field::Class));
+        let builder = builder.clone().with_extra(with_extra);
+        let shared = profiler::SharedProfile::new(builder.profile()?);
+        Ok(
+            profiler::watch::Watcher::new(Duration::from_millis(interval))
+                .with_filter(filter)
+                .with_builder(builder)
+                .with_shared(shared),
+        )
+    }
+}
+
+/// Commands other than listing; those which modify device state are only available with the 'actions' feature
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+enum Command {
+    /// Compare two json dumps, or a dump with the live system, reporting added, removed and changed devices
+    Diff {
+        /// Old json dump such as from `cyme --json --tree`
+        old: String,
+
+        /// New json dump to compare with; profiles the live system if not supplied or 'live'
+        new: Option<String>,
+
+        /// Output the differences as json
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Assert that the live system matches an expected json dump, exiting non-zero and printing the discrepancies if not
+    ///
+    /// Only fields present in the expected dump are compared so it can be a subset; a dump without extra (verbose) data will not check drivers or configurations for example. Field names are those shown by `cyme diff`
+    Assert {
+        /// Expected json dump such as from `cyme --json --tree` on the provisioned system
+        #[arg(long)]
+        expect: String,
+
+        /// Compare with a json dump rather than profiling the live system
+        #[arg(long)]
+        actual: Option<String>,
+
+        /// Fields to ignore such as serial_num
+        #[arg(long, value_delimiter = ',', num_args = 1..)]
+        ignore: Vec<String>,
+
+        /// Also fail if there are devices which are not in the expected dump
+        #[arg(long, default_value_t = false)]
+        exact: bool,
+    },
+    /// Export the raw descriptors of a device as binary files for replay in emulators or fuzzers, or to attach to bug reports
+    ///
+    /// Writes device.bin, config-<index>.bin, bos.bin, hid-report-<interface>.bin and string-<index>.bin as read from the device. Requires permission to open the device
+    ExportDescriptors {
+        /// Device to export as port path (e.g. 1-1.2) or VID:PID; VID:PID must match only one device
+        target: String,
+
+        /// Directory to write the descriptor files to; created if it does not exist
+        #[arg(short, long)]
+        output: String,
+    },
+    /// Print how to capture the traffic of a device with Linux usbmon, or with --output capture it to a pcap
+    ///
+    /// usbmon captures a whole bus so the device is selected by its bus and address, found by profiling; the address changes if it re-enumerates. --output requires the 'capture' feature and read permission of /dev/usbmonN, generally root
+    Capture {
+        /// Device to capture as port path (e.g. 1-1.2) or VID:PID; VID:PID must match only one device
+        target: String,
+
+        /// Write a pcap of the device traffic to this file, or stdout if '-', until interrupted or --count packets
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Stop after capturing this many packets
+        #[arg(long, requires = "output")]
+        count: Option<usize>,
+    },
+    /// Wait for a device matching the filters to connect, or with --gone for all matching to disconnect, then print it and exit
+    ///
+    /// Exits non-zero if --timeout is reached first. The system is profiled every --interval so it works with all profilers; useful in scripts waiting for a bootloader to enumerate before flashing for example
+    #[cfg(feature = "watch")]
+    Wait {
+        #[command(flatten)]
+        filter: DeviceFilterArgs,
+
+        /// Wait for all matching devices to disconnect rather than one to connect
+        #[arg(long, default_value_t = false)]
+        gone: bool,
+
+        /// Seconds to wait before exiting with an error; waits indefinitely if not supplied
+        #[arg(long)]
+        timeout: Option<u64>,
+
+        /// Milliseconds between each profile of the system
+        #[arg(long, default_value_t = 250)]
+        interval: u64,
+
+        /// Print the devices as json rather than lsusb style lines
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Print devices matching the filters as they connect and disconnect, optionally running a command for each
+    ///
+    /// The system is profiled every --interval so it works on all platforms without udev rules; a device that connects and disconnects between profiles is missed
+    #[cfg(feature = "watch")]
+    Watch {
+        #[command(flatten)]
+        filter: DeviceFilterArgs,
+
+        /// Command to spawn with the shell for each event; '{field}' is replaced with the shell quoted value of the device, fields are event, port_path, vidpid, vid, pid, name, serial, manufacturer, bus, number, dev_path and class. Each is also in the environment as CYME_FIELD, CYME_PORT_PATH for example, and name, serial and manufacturer are only passed that way since the device reports them. e.g. 'notify-send {event} {name}'
+        #[arg(long)]
+        exec: Option<String>,
+
+        /// Also report devices already connected when starting
+        #[arg(long, default_value_t = false)]
+        existing: bool,
+
+        /// Send a desktop notification for each event of devices matching the filters; requires the 'notify' feature
+        #[arg(long, default_value_t = false)]
+        notify: bool,
+
+        /// Milliseconds between each profile of the system
+        #[arg(long, default_value_t = 250)]
+        interval: u64,
+
+        /// Print each event as a line of json rather than lsusb style lines
+        #[arg(long, default_value_t = false)]
+        json: bool,
+
+        /// Record events of devices matching the filters to the history in the user data directory, or --history-file, for `cyme history`
+        #[arg(long, default_value_t = false)]
+        history: bool,
+
+        /// History file to record to rather than the default; implies --history
+        #[arg(long, value_name = "FILE")]
+        history_file: Option<String>,
+
+        /// Audit policy json file, as `cyme audit --policy`, to check devices which connect and those connected when starting against, reporting violations
+        #[arg(long, value_name = "FILE")]
+        policy: Option<String>,
+
+        /// Deauthorize devices with --policy errors so the kernel disconnects them, not for heuristics such as cloned serials; Linux only and requires the 'actions' feature and root
+        #[arg(long, default_value_t = false, requires = "policy")]
+        deauthorize: bool,
+    },
+    /// Show when devices recorded by `cyme watch --history` were first and last seen and how many times they re-enumerated
+    ///
+    /// Devices are identified by VID:PID and serial, or port path if they have no serial. A device re-enumerating many times is often a sign of a flaky cable, hub or firmware
+    #[cfg(feature = "watch")]
+    History {
+        #[command(flatten)]
+        filter: DeviceFilterArgs,
+
+        /// History file to read rather than the default in the user data directory
+        #[arg(long, value_name = "FILE")]
+        history_file: Option<String>,
+
+        /// Print each recorded event rather than a summary of each device
+        #[arg(long, default_value_t = false)]
+        events: bool,
+
+        /// Output as json
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Generate udev rules granting access to devices matching the filters without root, for the usb device node and its serial or HID nodes
+    ///
+    /// Linux only. Devices are profiled for their name and interfaces; a rule for --vidpid alone is generated if no device is connected
+    Rules {
+        #[command(flatten)]
+        filter: DeviceFilterArgs,
+
+        /// Group given access, such as plugdev; the user logged in at the seat is given access with the uaccess tag if not supplied
+        #[arg(long)]
+        group: Option<String>,
+
+        /// File mode of the device nodes
+        #[arg(long, default_value = "0660")]
+        mode: String,
+
+        /// Also match the serial number so only that device is given access
+        #[arg(long, default_value_t = false)]
+        match_serial: bool,
+
+        /// Write the rules to /etc/udev/rules.d and reload udev after confirmation; requires root
+        #[arg(long, default_value_t = false)]
+        install: bool,
+
+        /// Do not ask for confirmation before installing
+        #[arg(short, long, default_value_t = false)]
+        yes: bool,
+    },
+    /// Check the descriptors of devices matching the filters, or all, against the USB specification like shellcheck for device firmware
+    ///
+    /// Checks are missing-serial, bus-power, endpoint-interval, bcd-usb and string-descriptor. Devices are profiled with extra so those which cannot be opened are only partially checked. Exits non-zero if there are errors
+    Lint {
+        #[command(flatten)]
+        filter: DeviceFilterArgs,
+
+        /// Checks to skip
+        #[arg(long, value_enum, value_delimiter = ',', num_args = 1..)]
+        allow: Vec<profiler::lint::Check>,
+
+        /// Lint a json dump rather than profiling the live system
+        #[arg(long)]
+        from_json: Option<String>,
+
+        /// Output the findings as json
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Flag potentially suspicious devices matching the filters, or all, for a quick endpoint USB security audit
+    ///
+    /// Checks are unexpected-keyboard, cloned-serial, known-bad-usb, storage-hid, denied and not-allowed. The --policy json file sets the ports keyboards are expected on, additional BadUSB and trusted VID:PIDs, checks to allow and an allowlist and denylist of rules matching vidpid, class and serial patterns. Devices are profiled with extra so interfaces of those which cannot be opened are not checked. Exits non-zero if there are errors
+    Audit {
+        #[command(flatten)]
+        filter: DeviceFilterArgs,
+
+        /// Policy json file of what is expected of this endpoint
+        #[arg(long, value_name = "FILE")]
+        policy: Option<String>,
+
+        /// Checks to skip in addition to those allowed by the policy
+        #[arg(long, value_enum, value_delimiter = ',', num_args = 1..)]
+        allow: Vec<profiler::audit::Check>,
+
+        /// Audit a json dump rather than profiling the live system
+        #[arg(long, conflicts_with = "deauthorize")]
+        from_json: Option<String>,
+
+        /// Deauthorize devices with policy errors so the kernel disconnects them until authorized again; not for cloned serials or storage HID which are heuristics. Linux only and requires the 'actions' feature and root
+        #[arg(long, default_value_t = false)]
+        deauthorize: bool,
+
+        /// Output the findings as json
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Report devices matching the filters, or all, which share a serial number or are without one; the bane of udev rules and provisioning keyed on serial
+    ///
+    /// Serials are compared regardless of VID:PID and hubs are not included. Exits non-zero if any are shared
+    Serials {
+        #[command(flatten)]
+        filter: DeviceFilterArgs,
+
+        /// Report a json dump rather than profiling the live system
+        #[arg(long)]
+        from_json: Option<String>,
+
+        /// Output the report as json
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Serve the system profile as a JSON HTTP API for dashboards and remote tooling
+    ///
+    /// Endpoints are GET /devices, /devices/{port_path}, /buses and /events, a server-sent event stream of devices connecting and disconnecting. The system is profiled every --interval
+    #[cfg(feature = "watch")]
+    Serve {
+        /// Address to listen on; ':8080' listens on localhost only. '0.0.0.0:8080' listens on all interfaces, exposing device serials and names to the network without authentication
+        #[arg(long, default_value = ":8080", value_name = "ADDR")]
+        http: String,
+
+        /// Allow web pages from ORIGIN, or '*' for any, to read the API with the Access-Control-Allow-Origin header; no other origin can by default
+        #[arg(long, value_name = "ORIGIN")]
+        cors: Option<String>,
+
+        /// Milliseconds between each profile of the system
+        #[arg(long, default_value_t = 1000)]
+        interval: u64,
+
+        /// Include configurations, interfaces and endpoints; requires opening devices each profile
+        #[arg(long, default_value_t = false)]
+        extra: bool,
+
+        /// Serve a json dump rather than profiling the system, for developing dashboards without devices
+        #[arg(long)]
+        from_json: Option<String>,
+    },
+    /// Reset a device with a USB port reset; the device will disconnect and re-enumerate
+    ///
+    /// On Linux, falls back to toggling the sysfs 'authorized' attribute if the USB library reset fails. Requires write permissions to the device
+    #[cfg(feature = "actions")]
+    Reset {
+        /// Device to reset as port path (e.g. 1-1.2) or VID:PID; VID:PID must match only one device
+        target: String,
+
+        /// Do not ask for confirmation before resetting
+        #[arg(short, long, default_value_t = false)]
+        yes: bool,
+    },
+    /// Authorize a device or interface so that the kernel will bind drivers to it (Linux only)
+    #[cfg(feature = "actions")]
+    Authorize {
+        /// Port path of device (e.g. 1-1.2) or interface (e.g. 1-1.2:1.0), or device VID:PID
+        target: String,
+
+        /// Print the sysfs write rather than performing it
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Deauthorize a device or interface; the kernel disconnects it until authorized again (Linux only)
+    #[cfg(feature = "actions")]
+    Deauthorize {
+        /// Port path of device (e.g. 1-1.2) or interface (e.g. 1-1.2:1.0), or device VID:PID
+        target: String,
+
+        /// Print the sysfs write rather than performing it
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Bind or unbind the driver of a device or interface (Linux only)
+    #[cfg(feature = "actions")]
+    Driver {
+        #[command(subcommand)]
+        action: DriverCommand,
+    },
+}
+
+/// Driver actions using sysfs on Linux
+#[cfg(feature = "actions")]
+#[derive(Subcommand, Debug, Serialize, Deserialize)]
+enum DriverCommand {
+    /// Bind a device or interface to a driver
+    Bind {
+        /// Port path of device (e.g. 1-1.2) or interface (e.g. 1-1.2:1.0), or device VID:PID
+        target: String,
+
+        /// Driver to bind to (e.g. usbhid); kernel probes for a matching driver if not supplied
+        #[arg(long)]
+        driver: Option<String>,
+
+        /// Print the sysfs write rather than performing it
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Unbind a device or interface from its current driver
+    Unbind {
+        /// Port path of device (e.g. 1-1.2) or interface (e.g. 1-1.2:1.0), or device VID:PID
+        target: String,
+
+        /// Print the sysfs write rather than performing it
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
 }
 
-/// Print in bold red and exit with error
+/// Print in bold red if `colours` and exit with error `code`
 macro_rules! eprintexit {
-    ($error:expr) => {
-        // `stringify!` will convert the expression *as it is* into a string.
-        eprintln!(
-            "{}\n{}",
-            "cyme encountered a runtime error:".bold().red(),
-            $error.to_string().bold().red()
-        );
-        std::process::exit(1);
+    ($colours:expr, $error:expr, $code:expr) => {
+        let heading = "cyme encountered a runtime error:";
+        if $colours {
+            eprintln!(
+                "{}\n{}",
+                heading.bold().red(),
+                $error.to_string().bold().red()
+            );
+        } else {
+            eprintln!("{}\n{}", heading, $error);
+        }
+        std::process::exit($code);
     };
 }
 
-/// Print in bold orange warning and log
+/// Print in bold orange warning if `colours` and log
 #[allow(unused_macros)]
 macro_rules! wprintln {
-    ($error:expr) => {
-        // `stringify!` will convert the expression *as it is* into a string.
-        println!("{}", $error.to_string().bold().yellow());
+    ($colours:expr, $error:expr) => {
+        if $colours {
+            println!("{}", $error.to_string().bold().yellow());
+        } else {
+            println!("{}", $error);
+        }
         log::warn!($error)
     };
 }
 
+/// `print!` to the output set with [`display::set_output`] so --output, --tee and --ansi-to-html apply
+macro_rules! output {
+    ($($arg:tt)*) => {
+        display::write_output(format_args!($($arg)*))
+    };
+}
+
+/// `println!` to the output set with [`display::set_output`] so --output, --tee and --ansi-to-html apply
+macro_rules! outputln {
+    ($($arg:tt)*) => {
+        display::write_output(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+
 /// Merges non-Option Config with passed `Args`
 fn merge_config(c: &Config, a: &mut Args) {
     a.lsusb |= c.lsusb;
@@ -209,10 +852,24 @@ fn merge_config(c: &Config, a: &mut Args) {
     a.list_root_hubs |= c.list_root_hubs;
     a.decimal |= c.decimal;
     a.no_padding |= c.no_padding;
+    a.no_pager |= c.no_pager;
     a.ascii |= c.ascii;
     a.headings |= c.headings;
     a.force_libusb |= c.force_libusb;
+    if a.backend.is_none() {
+        a.backend = c.backend;
+    }
+    if a.theme.is_none() {
+        a.theme = c.theme;
+    }
     a.no_icons |= c.no_icons;
+    a.print_non_critical_profiler_stderr |= c.print_non_critical_profiler_stderr;
+    if a.no_open_class.is_none() {
+        a.no_open_class = c.no_open_classes.clone();
+    }
+    if a.name_sources.is_none() {
+        a.name_sources = c.name_sources.clone();
+    }
     if a.verbose == 0 {
         a.verbose = c.verbose;
     }
@@ -312,196 +969,1544 @@ fn parse_devpath(s: &str) -> Result<(Option<u8>, Option<u8>)> {
     }
 }
 
+/// Hint that system_profiler failed with `e` so the cyme profiler was used
+#[cfg(target_os = "macos")]
+fn system_profiler_failed(e: &Error) -> Hint {
+    Hint::with_source(
+        profiler::capabilities::DataSource::SystemProfiler,
+        &format!(
+            "failed to run 'system_profiler -json SPUSBDataType', fallback to cyme profiler; Error({})",
+            e
+        ),
+    )
+}
+
 /// macOS can use system_profiler to get USB data and merge with libusb so separate function
 #[cfg(target_os = "macos")]
-fn get_system_profile_macos(args: &Args) -> Result<profiler::SystemProfile> {
-    // if requested or only have libusb, use system_profiler and merge with libusb
-    if args.system_profiler || !cfg!(feature = "nusb") {
+fn get_system_profile_macos(
+    args: &Args,
+    builder: &profiler::ProfilerBuilder,
+) -> Result<profiler::SystemProfile> {
+    // device path profiles just the device so system_profiler is not needed
+    if args.device.is_some() {
+        return get_system_profile(args, builder);
+    }
+
+    // if requested or using libusb, use system_profiler and merge with libusb
+    let nusb = builder.backend_kind() == Some(profiler::backend::BackendKind::Nusb);
+    if args.system_profiler || !nusb {
         if !args.force_libusb
-            && args.device.is_none() // device path requires extra
-                && args.filter_class.is_none() // class filter requires extra
-                && !((args.tree && args.lsusb) || args.verbose > 0 || args.more)
+            && args.filter_class.is_none() // class filter requires extra
+            && !args
+                .filter_expression
+                .iter()
+                .chain(args.highlight.iter())
+                .any(|e| e.contains_field(profiler::expression::Field::Class))
+            && !((args.tree && args.lsusb) || args.verbose > 0 || args.more)
         {
-            profiler::macos::get_spusb()
-                .map_or_else(|e| {
+            profiler::macos::get_spusb().map_or_else(
+                |e| {
                     // For non-zero return, report but continue in this case
                     if e.kind() == ErrorKind::SystemProfiler {
-                        eprintln!("Failed to run 'system_profiler -json SPUSBDataType', fallback to cyme profiler; Error({})", e);
-                        get_system_profile(args)
+                        display::hint(system_profiler_failed(&e));
+                        get_system_profile(args, builder)
                     } else {
                         Err(e)
                     }
-                }, Ok)
+                },
+                Ok,
+            )
         } else if !args.force_libusb {
-            if cfg!(feature = "libusb") {
-                log::warn!("Merging macOS system_profiler output with libusb for verbose data. Apple internal devices will not be obtained");
+            if !nusb {
+                display::hint(Hint::with_source(profiler::capabilities::DataSource::SystemProfiler, "merging system_profiler output with libusb for verbose data; Apple internal devices will not be obtained"));
             }
-            profiler::macos::get_spusb_with_extra().map_or_else(|e| {
-                // For non-zero return, report but continue in this case
-                if e.kind() == ErrorKind::SystemProfiler {
-                    eprintln!("Failed to run 'system_profiler -json SPUSBDataType', fallback to cyme profiler; Error({})", e);
-                    get_system_profile(args)
-                } else {
-                    Err(e)
-                }
-            }, Ok)
+            profiler::macos::get_spusb_with_extra(builder).map_or_else(
+                |e| {
+                    // For non-zero return, report but continue in this case
+                    if e.kind() == ErrorKind::SystemProfiler {
+                        display::hint(system_profiler_failed(&e));
+                        get_system_profile(args, builder)
+                    } else {
+                        Err(e)
+                    }
+                },
+                Ok,
+            )
         } else {
-            return get_system_profile(args);
+            return get_system_profile(args, builder);
         }
     } else {
-        get_system_profile(args)
+        get_system_profile(args, builder)
     }
 }
 
-/// Detects and switches between verbose profiler (extra) and normal profiler
-fn get_system_profile(args: &Args) -> Result<profiler::SystemProfile> {
-    if args.verbose > 0
-        || args.tree
-        || args.device.is_some()
-        || args.lsusb
-        || args.json
-        || args.more
-        || args.filter_class.is_none()
-    // class filter requires extra
-    {
-        profiler::get_spusb_with_extra()
-    } else {
-        profiler::get_spusb()
+/// Find the single device matching `target` as either a port path or VID:PID
+fn find_action_device<'a>(
+    spusb: &'a profiler::SystemProfile,
+    target: &str,
+) -> Result<&'a profiler::Device> {
+    // interface port paths contain ':' too so try port path before VID:PID
+    match target.parse::<PortPath>() {
+        Ok(path) => spusb.get_node_at(&path).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                &format!("No device found at port path '{}'", path),
+            )
+        }),
+        Err(e) if !target.contains(':') => Err(e),
+        Err(_) => {
+            let (vid, pid) = parse_vidpid(target)?;
+            let matches: Vec<&profiler::Device> = spusb
+                .flattened_devices()
+                .into_iter()
+                .filter(|d| {
+                    (vid.is_none() || d.vendor_id == vid) && (pid.is_none() || d.product_id == pid)
+                })
+                .collect();
+            match matches.as_slice() {
+                [d] => Ok(*d),
+                [] => Err(Error::new(
+                    ErrorKind::NotFound,
+                    &format!("No device found matching '{}'", target),
+                )),
+                _ => Err(Error::new(
+                    ErrorKind::InvalidArg,
+                    &format!(
+                        "{} devices match '{}', use a port path to select one",
+                        matches.len(),
+                        target
+                    ),
+                )),
+            }
+        }
     }
 }
 
-fn print_lsusb(
-    sp_usb: &profiler::SystemProfile,
-    device: &Option<String>,
-    settings: &display::PrintSettings,
-) -> Result<()> {
-    // device specific overrides tree on lsusb
-    if settings.tree && device.is_none() {
-        if !cfg!(target_os = "linux") {
-            log::warn!("Most of the data in a lsusb style tree is applicable to Linux only!");
-        }
-        lsusb::print_tree(sp_usb, settings)
+/// Resolve `target` to a sysfs name; port paths are used as is, otherwise profile with `builder` and find device by VID:PID
+#[cfg(feature = "actions")]
+fn resolve_sysfs_name(target: &str, builder: &profiler::ProfilerBuilder) -> Result<String> {
+    if let Ok(path) = target.parse::<PortPath>() {
+        Ok(path.sysfs_name())
     } else {
-        // can't print verbose if not using libusb
-        if !(cfg!(feature = "libusb") || cfg!(feature = "nusb"))
-            && (settings.verbosity > 0 || device.is_some())
-        {
-            return Err(Error::new(ErrorKind::Unsupported, "nusb or libusb feature is required to do this, install with `cargo install --features nusb/libusb`"));
-        }
-
-        let devices = sp_usb.flattened_devices();
-        // even though we filtered using filter.show and using prepare, keep this here because it will match the exact Linux dev path and exit error if it doesn't match like lsusb
-        if let Some(dev_path) = &device {
-            lsusb::dump_one_device(&devices, dev_path)?
-        } else {
-            lsusb::print(&devices, settings.verbosity > 0);
-        }
-    };
-
-    Ok(())
+        let spusb = get_spusb(builder)?;
+        Ok(find_action_device(&spusb, target)?.sysfs_name())
+    }
 }
 
-/// Generates extra CLI information for packaging
-#[cfg(feature = "cli_generate")]
-#[cold]
-fn print_man() -> Result<()> {
-    use clap::CommandFactory;
-    use clap_complete::generate_to;
-    use clap_complete::shells::*;
-    use std::fs;
-    use std::path::PathBuf;
-
-    let outdir = std::env::var_os("BUILD_SCRIPT_DIR")
-        .or_else(|| std::env::var_os("OUT_DIR"))
-        .unwrap_or_else(|| "./doc".into());
-    fs::create_dir_all(&outdir)?;
-    println!("Generating CLI info to {:?}", outdir);
-
-    let mut app = Args::command();
-
-    // completions
-    let bin_name = "cyme";
-    generate_to(Bash, &mut app, bin_name, &outdir).expect("Failed to generate Bash completions");
-    generate_to(Fish, &mut app, bin_name, &outdir).expect("Failed to generate Fish completions");
-    generate_to(Zsh, &mut app, bin_name, &outdir).expect("Failed to generate Zsh completions");
-    generate_to(PowerShell, &mut app, bin_name, &outdir)
-        .expect("Failed to generate PowerShell completions");
-
-    // man page
-    let man = clap_mangen::Man::new(app);
-    let mut buffer: Vec<u8> = Default::default();
-    man.render(&mut buffer)?;
-
-    std::fs::write(PathBuf::from(&outdir).join("cyme.1"), buffer)?;
-
-    // example config
-    std::fs::write(
-        PathBuf::from(&outdir).join("cyme_example_config.json"),
-        serde_json::to_string_pretty(&Config::example())?,
-    )?;
-
-    Ok(())
+/// Print the [`profiler::SysfsWrite`] if `dry_run` otherwise apply it
+#[cfg(feature = "actions")]
+fn run_sysfs_write(write: profiler::SysfsWrite, dry_run: bool) -> Result<()> {
+    if dry_run {
+        outputln!("{}", write);
+        Ok(())
+    } else {
+        log::info!("{}", write);
+        write.apply()
+    }
 }
 
-fn cyme() -> Result<()> {
-    let mut args = Args::parse();
-
-    #[cfg(feature = "cli_generate")]
-    if args.gen {
-        print_man()?;
-        std::process::exit(0);
+/// Path of the history for `watch --history` and `history`: `file` if supplied, otherwise the default if `default`
+#[cfg(feature = "watch")]
+fn history_path(file: Option<&str>, default: bool) -> Result<Option<std::path::PathBuf>> {
+    match file {
+        Some(f) => Ok(Some(std::path::PathBuf::from(f))),
+        None if default => profiler::history::default_path().map(Some).ok_or_else(|| {
+            Error::new(
+                ErrorKind::Unsupported,
+                "No user data directory for the history, supply one with --history-file",
+            )
+        }),
+        None => Ok(None),
     }
+}
 
-    // set the module debug level, will also check env if args.debug == 0
-    cyme::set_log_level(args.debug)?;
-
-    #[cfg(feature = "libusb")]
-    profiler::libusb::set_log_level(args.debug);
+/// Ask to confirm `question` on stderr, so it is not in the output, returning whether the answer was yes
+fn confirm(question: &str) -> Result<bool> {
+    eprint!("{} [y/N] ", question);
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    let yes = matches!(input.trim(), "y" | "Y" | "yes");
+    if !yes {
+        eprintln!("Aborted");
+    }
+    Ok(yes)
+}
 
-    let config = if let Some(path) = args.config.as_ref() {
-        let config = Config::from_file(path)?;
-        log::info!("Using user config {:?}", config);
-        config
+/// Check --deauthorize can be used with the features cyme was built with
+fn check_deauthorize(deauthorize: bool) -> Result<()> {
+    if deauthorize && !cfg!(feature = "actions") {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "actions feature is required for --deauthorize, install with `cargo install --features actions`",
+        ))
     } else {
-        Config::sys()?
-    };
-
-    // add any config ENV override
-    if config.print_non_critical_profiler_stderr {
-        std::env::set_var("CYME_PRINT_NON_CRITICAL_PROFILER_STDERR", "1");
+        Ok(())
     }
+}
 
-    merge_config(&config, &mut args);
+/// Whether the output of `command` can be redacted with `--redact`; udev rules must match the real serial and captures and actions are of the device itself
+fn supports_redact(command: &Command) -> bool {
+    match command {
+        Command::Rules { .. } | Command::Capture { .. } => false,
+        #[cfg(feature = "actions")]
+        Command::Reset { .. }
+        | Command::Authorize { .. }
+        | Command::Deauthorize { .. }
+        | Command::Driver { .. } => false,
+        _ => true,
+    }
+}
 
-    // legacy arg, hidden but still support with new format
-    if args.no_color {
-        args.color = display::ColorWhen::Never;
+/// Print the [`profiler::diff::ProfileDiff`] of `cyme diff` or `cyme assert`, coloured if `colours`
+fn print_diff(diff: &profiler::diff::ProfileDiff, colours: bool) {
+    if colours {
+        output!("{:#}", diff);
+    } else {
+        output!("{}", diff);
     }
+}
 
-    // set the output colouring
-    let colours = match args.color {
-        display::ColorWhen::Auto => {
-            // colored crate manages coloring
-            Some(config.colours)
-        }
-        display::ColorWhen::Always => {
-            env::set_var("NO_COLOR", "0");
-            colored::control::set_override(true);
-            Some(config.colours)
-        }
-        display::ColorWhen::Never => {
-            // set env to be sure too
-            env::set_var("NO_COLOR", "1");
-            colored::control::set_override(false);
-            None
+/// Print the [`profiler::lint::LintReport`] of `cyme lint` as JSON if `json`
+///
+/// Errors with [`ErrorKind::InvalidDescriptor`] if there are error findings.
+fn print_lint(report: &profiler::lint::LintReport, json: bool) -> Result<()> {
+    if json {
+        outputln!("{}", serde_json::to_string_pretty(&report.findings)?);
+    } else {
+        for finding in &report.findings {
+            outputln!("{}", finding);
+        }
+        if report.unopened > 0 {
+            display::hint(Hint::new(&format!(
+                "{} device(s) could not be opened so were only checked for bcd-usb",
+                report.unopened
+            )));
+        }
+    }
+
+    let errors = report.errors();
+    if errors > 0 {
+        Err(Error::new(
+            ErrorKind::InvalidDescriptor,
+            &format!(
+                "{} error(s) and {} warning(s) in {} device(s)",
+                errors,
+                report.findings.len() - errors,
+                report.devices
+            ),
+        ))
+    } else {
+        if !json {
+            eprintln!(
+                "{} warning(s) in {} device(s)",
+                report.findings.len(),
+                report.devices
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Print the [`profiler::serials::SerialReport`] of `cyme serials` as JSON if `json`, otherwise coloured if `colours`
+///
+/// Errors with [`ErrorKind::InvalidDevice`] if any devices share a serial.
+fn print_serials(
+    report: &profiler::serials::SerialReport,
+    json: bool,
+    colours: bool,
+) -> Result<()> {
+    if json {
+        outputln!("{}", serde_json::to_string_pretty(report)?);
+    } else {
+        for duplicate in &report.duplicates {
+            let line = duplicate.to_string();
+            if colours {
+                outputln!("{}", line.red());
+            } else {
+                outputln!("{}", line);
+            }
+        }
+        for device in &report.missing {
+            let line = format!("{}: no serial", device);
+            if colours {
+                outputln!("{}", line.yellow());
+            } else {
+                outputln!("{}", line);
+            }
+        }
+    }
+
+    if !report.duplicates.is_empty() {
+        Err(Error::new(
+            ErrorKind::InvalidDevice,
+            &format!(
+                "{} serial(s) shared by more than one device and {} device(s) without a serial",
+                report.duplicates.len(),
+                report.missing.len()
+            ),
+        ))
+    } else {
+        if !json {
+            eprintln!("{} device(s) without a serial", report.missing.len());
+        }
+        Ok(())
+    }
+}
+
+/// Print the [`profiler::audit::AuditReport`] of `cyme audit` as JSON if `json`, otherwise coloured if `colours`
+///
+/// Errors with the kind of the first failure if any device failed to deauthorize, otherwise with [`ErrorKind::InvalidDevice`] if there are error findings.
+fn print_audit(
+    report: &profiler::audit::AuditReport,
+    policy: &profiler::audit::Policy,
+    json: bool,
+    colours: bool,
+) -> Result<()> {
+    if json {
+        outputln!("{}", serde_json::to_string_pretty(&report.findings)?);
+    } else {
+        for finding in &report.findings {
+            if colours {
+                outputln!("{}", finding.to_coloured_string());
+            } else {
+                outputln!("{}", finding);
+            }
+        }
+        if report.unopened > 0 {
+            display::hint(Hint::new(&format!(
+                "{} device(s) could not be opened so their interfaces were not checked",
+                report.unopened
+            )));
+        }
+        if policy.keyboard_ports.is_empty() {
+            display::hint(Hint::new(
+                "Keyboards were not checked; add 'keyboard-ports' to a --policy to check they are only on expected ports",
+            ));
+        }
+    }
+    for port_path in &report.deauthorized {
+        eprintln!("Deauthorized {}", port_path);
+    }
+
+    let errors = report.errors();
+    let warnings = report.findings.len() - errors;
+    if let Some(first) = report.failed.first() {
+        Err(Error::new(
+            first.kind(),
+            &format!(
+                "failed to deauthorize {} device(s) with {} error(s) and {} warning(s) in {} device(s)",
+                report.failed.len(),
+                errors,
+                warnings,
+                report.devices
+            ),
+        ))
+    } else if errors > 0 {
+        Err(Error::new(
+            ErrorKind::InvalidDevice,
+            &format!(
+                "{} error(s) and {} warning(s) in {} device(s)",
+                errors, warnings, report.devices
+            ),
+        ))
+    } else {
+        if !json {
+            eprintln!("{} warning(s) in {} device(s)", warnings, report.devices);
+        }
+        Ok(())
+    }
+}
+
+/// Run a [`Command`], colouring its output if `colours` and redacting it with `redaction` if `--redact`
+fn run_command(
+    command: &Command,
+    builder: &profiler::ProfilerBuilder,
+    colours: bool,
+    redaction: Option<&Redaction>,
+) -> Result<()> {
+    if redaction.is_some() && !supports_redact(command) {
+        return Err(Error::new(
+            ErrorKind::InvalidArg,
+            "--redact is not supported by this command since its output must have the real serials and names",
+        ));
+    }
+
+    match command {
+        Command::Diff { old, new, json } => {
+            let old = read_json_dump(old)?;
+            let new = match new.as_deref() {
+                Some(path) if path != "live" => read_json_dump(path)?,
+                _ => builder.clone().with_extra(true).profile()?,
+            };
+            let diff = profiler::diff::run(old, new, redaction)?;
+            if *json {
+                outputln!("{}", serde_json::to_string_pretty(&diff)?);
+            } else {
+                print_diff(&diff, colours);
+            }
+        }
+        Command::Assert {
+            expect,
+            actual,
+            ignore,
+            exact,
+        } => {
+            let expected = read_json_dump(expect)?;
+            let actual = match actual.as_deref() {
+                Some(path) => read_json_dump(path)?,
+                None => builder.clone().with_extra(true).profile()?,
+            };
+            let diff = profiler::diff::run_assert(expected, actual, ignore, *exact, redaction)?;
+            if !diff.is_empty() {
+                print_diff(&diff, colours);
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    &format!(
+                        "{} device(s) do not match expected {}",
+                        diff.devices.len(),
+                        expect
+                    ),
+                ));
+            }
+        }
+        #[cfg(feature = "watch")]
+        Command::Wait {
+            filter,
+            gone,
+            timeout,
+            interval,
+            json,
+        } => {
+            if filter.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidArg,
+                    "Wait requires a device to wait for with --vidpid, --name, --serial or --where",
+                ));
+            }
+
+            let devices = profiler::watch::run_wait(
+                filter.watcher(builder, *interval, false)?,
+                *gone,
+                timeout.map(Duration::from_secs),
+                redaction,
+            )?;
+            if *json {
+                outputln!("{}", serde_json::to_string_pretty(&devices)?);
+            } else {
+                for device in devices {
+                    outputln!("{}", device.to_lsusb_string());
+                }
+            }
+        }
+        #[cfg(feature = "watch")]
+        Command::Watch {
+            filter,
+            exec,
+            existing,
+            notify,
+            interval,
+            json,
+            history,
+            history_file,
+            policy,
+            deauthorize,
+        } => {
+            if *notify && !cfg!(feature = "notify") {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "notify feature is required for --notify, install with `cargo install --features notify`",
+                ));
+            }
+            check_deauthorize(*deauthorize)?;
+            let history = history_path(history_file.as_deref(), *history)?;
+            let policy = policy
+                .as_deref()
+                .map(profiler::audit::Policy::from_file)
+                .transpose()?;
+            // interfaces are required for keyboard and class checks
+            let watcher = filter.watcher(builder, *interval, policy.is_some())?;
+            let settings = profiler::watch::WatchSettings {
+                exec: exec.to_owned(),
+                existing: *existing,
+                notify: *notify,
+                json: *json,
+                colours,
+                history,
+                policy,
+                deauthorize: *deauthorize,
+            };
+            profiler::watch::run(watcher, &settings, redaction)?;
+        }
+        #[cfg(feature = "watch")]
+        Command::History {
+            filter,
+            history_file,
+            events,
+            json,
+        } => {
+            let device_filter = filter.to_filter()?;
+            let path = history_path(history_file.as_deref(), true)?.unwrap_or_default();
+            if *events {
+                let records = profiler::history::run_events(&path, &device_filter, redaction)?;
+                if *json {
+                    outputln!("{}", serde_json::to_string_pretty(&records)?);
+                } else {
+                    for record in &records {
+                        outputln!("{}", record);
+                    }
+                }
+            } else {
+                let summary = profiler::history::run(&path, &device_filter, redaction)?;
+                if *json {
+                    outputln!("{}", serde_json::to_string_pretty(&summary)?);
+                } else {
+                    // re-enumerating is often a flaky cable, hub or firmware
+                    for device in &summary {
+                        if colours && device.re_enumerations() > 0 {
+                            outputln!("{}", device.to_string().yellow());
+                        } else {
+                            outputln!("{}", device);
+                        }
+                    }
+                }
+            }
+        }
+        Command::Lint {
+            filter,
+            allow,
+            from_json,
+            json,
+        } => {
+            let device_filter = filter.to_filter()?;
+            let spusb = match from_json.as_deref() {
+                Some(path) => read_json_dump(path)?,
+                None => builder.clone().with_extra(true).profile()?,
+            };
+            let report = profiler::lint::run(&spusb, &device_filter, allow, redaction)?;
+            print_lint(&report, *json)?;
+        }
+        Command::Audit {
+            filter,
+            policy,
+            allow,
+            from_json,
+            deauthorize,
+            json,
+        } => {
+            check_deauthorize(*deauthorize)?;
+            let device_filter = filter.to_filter()?;
+            let mut policy = match policy.as_deref() {
+                Some(path) => profiler::audit::Policy::from_file(path)?,
+                None => profiler::audit::Policy::default(),
+            };
+            policy.allow.extend(allow.iter().copied());
+            let spusb = match from_json.as_deref() {
+                Some(path) => read_json_dump(path)?,
+                None => builder.clone().with_extra(true).profile()?,
+            };
+            let report =
+                profiler::audit::run(&spusb, &device_filter, &policy, *deauthorize, redaction)?;
+            print_audit(&report, &policy, *json, colours)?;
+        }
+        Command::Serials {
+            filter,
+            from_json,
+            json,
+        } => {
+            let device_filter = filter.to_filter()?;
+            let spusb = match from_json.as_deref() {
+                Some(path) => read_json_dump(path)?,
+                None => get_spusb(builder)?,
+            };
+            let report = profiler::serials::run(&spusb, &device_filter, redaction)?;
+            print_serials(&report, *json, colours)?;
+        }
+        Command::Rules {
+            filter,
+            group,
+            mode,
+            match_serial,
+            install,
+            yes,
+        } => {
+            if filter.is_empty() {
+                return Err(Error::new(
+                    ErrorKind::InvalidArg,
+                    "Rules requires devices to generate rules for with --vidpid, --name, --serial or --where",
+                ));
+            }
+            let device_filter = filter.to_filter()?;
+            let spusb = builder.clone().with_extra(true).profile()?;
+            let rules = cyme::rules::run(
+                &spusb,
+                &device_filter,
+                mode,
+                group.as_deref(),
+                *match_serial,
+            )?;
+            if !spusb.iter().any(|d| device_filter.is_match(d)) {
+                display::hint(Hint::new(
+                    "no matching device connected; rule is for the usb device node only",
+                ));
+            }
+            for rule in &rules {
+                outputln!("{}", rule);
+            }
+
+            if *install
+                && (*yes
+                    || confirm(&format!(
+                        "Install {} rule(s) to {}?",
+                        rules.len(),
+                        cyme::rules::RULES_DIR
+                    ))?)
+            {
+                for path in cyme::rules::install_rules(&rules)? {
+                    outputln!("Installed {}", path.display());
+                }
+            }
+        }
+        #[cfg(feature = "watch")]
+        Command::Serve {
+            http,
+            cors,
+            interval,
+            extra,
+            from_json,
+        } => {
+            let mut server = cyme::serve::Server::bind(http)?
+                .with_builder(builder.clone().with_extra(*extra))
+                .with_interval(Duration::from_millis(*interval));
+            if let Some(origin) = cors {
+                server = server.with_cors(origin);
+            }
+            if let Some(redaction) = redaction {
+                server = server.with_redaction(redaction.to_owned())?;
+            }
+            eprintln!("Serving on http://{}", server.local_addr()?);
+
+            match from_json {
+                Some(file_path) => {
+                    let file_path = file_path.to_owned();
+                    server.run_with(move || read_json_dump(&file_path))?
+                }
+                None => server.run()?,
+            }
+        }
+        Command::ExportDescriptors { target, output } => {
+            let spusb = get_spusb(builder)?;
+            let device = find_action_device(&spusb, target)?;
+            let paths = match redaction {
+                Some(redaction) => profiler::export::export_redacted_descriptors(
+                    device, output, redaction, builder,
+                )?,
+                None => profiler::export::export_descriptors(device, output, builder)?,
+            };
+            for path in paths {
+                outputln!("{}", path.display());
+            }
+        }
+        Command::Capture {
+            target,
+            output,
+            count,
+        } => {
+            let spusb = get_spusb(builder)?;
+            let usbmon = profiler::capture::Usbmon::from(find_action_device(&spusb, target)?);
+            match output.as_deref() {
+                Some(output) => {
+                    eprintln!(
+                        "Capturing bus {} device {} to {}; Ctrl-C to stop",
+                        usbmon.bus, usbmon.address, output
+                    );
+                    let written = profiler::capture::run(usbmon, output, *count)?;
+                    eprintln!("Captured {} packets", written);
+                }
+                None => output!("{}", usbmon.instructions()),
+            }
+        }
+        #[cfg(feature = "actions")]
+        Command::Reset { target, yes } => {
+            let spusb = get_spusb(builder)?;
+            let device = find_action_device(&spusb, target)?;
+
+            if !yes && !confirm(&format!("Reset {}?", device))? {
+                return Ok(());
+            }
+
+            builder.reset_device(device)?;
+            outputln!("Reset {}", device);
+        }
+        #[cfg(feature = "actions")]
+        Command::Authorize { target, dry_run } => {
+            let sysfs_name = resolve_sysfs_name(target, builder)?;
+            run_sysfs_write(profiler::sysfs_authorize(&sysfs_name, true)?, *dry_run)?;
+        }
+        #[cfg(feature = "actions")]
+        Command::Deauthorize { target, dry_run } => {
+            let sysfs_name = resolve_sysfs_name(target, builder)?;
+            run_sysfs_write(profiler::sysfs_authorize(&sysfs_name, false)?, *dry_run)?;
+        }
+        #[cfg(feature = "actions")]
+        Command::Driver {
+            action:
+                DriverCommand::Bind {
+                    target,
+                    driver,
+                    dry_run,
+                },
+        } => {
+            let sysfs_name = resolve_sysfs_name(target, builder)?;
+            run_sysfs_write(
+                profiler::sysfs_driver_bind(&sysfs_name, driver.as_deref())?,
+                *dry_run,
+            )?;
+        }
+        #[cfg(feature = "actions")]
+        Command::Driver {
+            action: DriverCommand::Unbind { target, dry_run },
+        } => {
+            let sysfs_name = resolve_sysfs_name(target, builder)?;
+            run_sysfs_write(profiler::sysfs_driver_unbind(&sysfs_name)?, *dry_run)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a json dump, falling back to a flattened list of devices on a phony bus
+fn read_json_dump(file_path: &str) -> Result<profiler::SystemProfile> {
+    match profiler::read_json_dump(file_path) {
+        Ok(s) => Ok(s),
+        Err(e) => {
+            log::warn!(
+                "Failed to read json dump, attempting as flattened with phony bus: Error({})",
+                e
+            );
+            profiler::read_flat_json_to_phony_bus(file_path)
+        }
+    }
+}
+
+/// [`ParseMode`] of the profiler from `--strict`
+fn parse_mode(args: &Args) -> ParseMode {
+    if args.strict {
+        ParseMode::Strict
+    } else {
+        ParseMode::Lenient
+    }
+}
+
+/// Whether the args require the verbose profiler (extra)
+fn requires_extra(args: &Args) -> bool {
+    args.verbose > 0
+        || args.tree
+        || args.device.is_some()
+        || args.lsusb
+        || args.json
+        || args.more
+        || args.show_errors
+        || args.strict
+        || args.bandwidth
+        || args.filter_unclaimed
+        || args.filter_location.is_some() // hub DeviceRemovable if not in sysfs
+        || args.filter_class.is_none()
+    // class filter requires extra
+}
+
+/// Whether the args require the raw descriptor bytes to be kept when profiling
+fn requires_raw_descriptors(args: &Args) -> bool {
+    args.dump_descriptors
+        || args
+            .config_blocks
+            .as_ref()
+            .is_some_and(|b| b.contains(&display::ConfigurationBlocks::RawDescriptor))
+        || args
+            .interface_blocks
+            .as_ref()
+            .is_some_and(|b| b.contains(&display::InterfaceBlocks::RawDescriptor))
+        || args
+            .endpoint_blocks
+            .as_ref()
+            .is_some_and(|b| b.contains(&display::EndpointBlocks::RawDescriptor))
+}
+
+/// Whether the args require the kernel module parameters of interface drivers to be read when profiling
+fn requires_module_parameters(args: &Args) -> bool {
+    args.interface_blocks
+        .as_ref()
+        .is_some_and(|b| b.contains(&display::InterfaceBlocks::ModuleParameters))
+}
+
+/// Whether --cached can be used for the args: the cache holds a full profile without raw descriptors, module parameters or errors, so a profile of only the --device must not replace it
+fn use_cache(args: &Args) -> bool {
+    args.cached
+        && args.device.is_none()
+        && !requires_raw_descriptors(args)
+        && !requires_module_parameters(args)
+        && !args.show_errors
+        && !args.strict
+}
+
+/// Add a hint for each [`cyme::usb::descriptors::DescriptorWarning`] of the devices in `spusb`
+fn descriptor_warning_hints(spusb: &profiler::SystemProfile) {
+    let mut count = 0;
+    for device in spusb.iter() {
+        let configs = device.extra.iter().flat_map(|e| e.configurations.iter());
+        for config in configs {
+            for warning in &config.warnings {
+                count += 1;
+                display::hint(Hint::new(&format!(
+                    "{} {:04x}:{:04x} configuration {}: {}",
+                    device.port_path(),
+                    device.vendor_id.unwrap_or(0),
+                    device.product_id.unwrap_or(0),
+                    config.number,
+                    warning
+                )));
+            }
+        }
+    }
+    log::info!("{} descriptor warnings", count);
+}
+
+/// Hint at devices with quirks since what is profiled for them is adjusted
+fn quirk_hints(spusb: &profiler::SystemProfile) {
+    for device in spusb.iter() {
+        if let Some(quirks) = device.quirks.as_ref() {
+            display::hint(Hint::new(&format!(
+                "{} {:04x}:{:04x} has quirks {}; show them with --blocks quirks",
+                device.port_path(),
+                device.vendor_id.unwrap_or(0),
+                device.product_id.unwrap_or(0),
+                quirks
+                    .iter()
+                    .map(|q| q.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            )));
+        }
+    }
+}
+
+/// [`profiler::ProfilerBuilder`] which all profiling starts from: the backend of `args`, the name source order of `args` with the name overrides in `config` and the quirks in `config` added to the built-in database
+fn profiler_builder(config: &Config, args: &Args) -> Result<profiler::ProfilerBuilder> {
+    let mut quirks = profiler::quirks::Database::builtin();
+    for (key, entry) in config.quirks.iter().flatten() {
+        let (Some(vid), pid) = parse_vidpid(key)? else {
+            return Err(Error::new(
+                ErrorKind::Config,
+                &format!("Quirks key '{}' must be 'vid:pid' or 'vid'", key),
+            ));
+        };
+        quirks.add(profiler::quirks::QuirkEntry::new(vid, pid, entry.clone()));
+    }
+
+    let mut names = profiler::names::Resolver::new();
+    if let Some(order) = args.name_sources.as_ref() {
+        names.set_order(order.iter().copied());
+    }
+    for (key, name) in config.names.iter().flatten() {
+        let (Some(vid), pid) = parse_vidpid(key)? else {
+            return Err(Error::new(
+                ErrorKind::Config,
+                &format!("Names key '{}' must be 'vid:pid' or 'vid'", key),
+            ));
+        };
+        names.set_override(vid, pid, name);
+    }
+
+    let builder = profiler::ProfilerBuilder::new()
+        .quirks(quirks)
+        .names(names)
+        .libusb_log_level(args.debug);
+    match args.backend {
+        Some(backend) => {
+            // error now rather than when first profiling
+            backend.backend()?;
+            Ok(builder.backend(backend))
+        }
+        None => Ok(builder),
+    }
+}
+
+/// Profile with `builder` as [`profiler::get_spusb`] does, which is with extra when the backend is nusb
+fn get_spusb(builder: &profiler::ProfilerBuilder) -> Result<profiler::SystemProfile> {
+    builder
+        .clone()
+        .with_extra(builder.backend_kind() == Some(profiler::backend::BackendKind::Nusb))
+        .profile()
+}
+
+/// Detects and switches between verbose profiler (extra) and normal profiler, starting from `builder`
+fn get_system_profile(
+    args: &Args,
+    builder: &profiler::ProfilerBuilder,
+) -> Result<profiler::SystemProfile> {
+    // only the device at --device is shown so just profile that
+    if let Some((Some(bus), Some(number))) =
+        args.device.as_deref().and_then(|d| parse_devpath(d).ok())
+    {
+        match builder
+            .clone()
+            .with_extra(true)
+            .raw_descriptors(requires_raw_descriptors(args))
+            .module_parameters(requires_module_parameters(args))
+            .validate_descriptors(args.show_errors)
+            .parse_mode(parse_mode(args))
+            .print_errors(args.print_non_critical_profiler_stderr)
+            .no_open_classes(args.no_open_class.iter().flatten().copied())
+            .get_device(bus, number)
+        {
+            Ok(device) => {
+                let mut bus = profiler::Bus::from(bus);
+                bus.devices = Some(vec![device]);
+                return Ok(profiler::SystemProfile {
+                    buses: vec![bus],
+                    thunderbolt: None,
+                    gadget: None,
+                });
+            }
+            // root hubs are not profiled alone so fallback to full profile
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                log::debug!("{}, profiling all devices", e)
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    if requires_extra(args) {
+        builder
+            .clone()
+            .with_extra(true)
+            .raw_descriptors(requires_raw_descriptors(args))
+            .module_parameters(requires_module_parameters(args))
+            .validate_descriptors(args.show_errors)
+            .parse_mode(parse_mode(args))
+            .print_errors(args.print_non_critical_profiler_stderr)
+            .no_open_classes(args.no_open_class.iter().flatten().copied())
+            .profile()
+    } else {
+        get_spusb(builder)
+    }
+}
+
+fn print_lsusb(
+    sp_usb: &profiler::SystemProfile,
+    device: &Option<String>,
+    settings: &display::PrintSettings,
+) -> Result<()> {
+    // device specific overrides tree on lsusb
+    if settings.tree && device.is_none() {
+        if !cfg!(target_os = "linux") {
+            display::hint(Hint::new(
+                "most of the data in a lsusb style tree is applicable to Linux only",
+            ));
+        }
+        lsusb::print_tree(sp_usb, settings)
+    } else {
+        // can't print verbose if not using libusb
+        if !(cfg!(feature = "libusb") || cfg!(feature = "nusb"))
+            && (settings.verbosity > 0 || device.is_some())
+        {
+            return Err(Error::new(ErrorKind::Unsupported, "nusb or libusb feature is required to do this, install with `cargo install --features nusb/libusb`"));
+        }
+
+        let devices = sp_usb.flattened_devices();
+        // even though we filtered using filter.show and using prepare, keep this here because it will match the exact Linux dev path and exit error if it doesn't match like lsusb
+        if let Some(dev_path) = &device {
+            lsusb::dump_one_device(&devices, dev_path)?
+        } else {
+            lsusb::print(&devices, settings.verbosity > 0);
         }
     };
 
+    Ok(())
+}
+
+/// Generates extra CLI information for packaging
+#[cfg(feature = "cli_generate")]
+#[cold]
+fn print_man() -> Result<()> {
+    use clap::CommandFactory;
+    use clap_complete::generate_to;
+    use clap_complete::shells::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    let outdir = std::env::var_os("BUILD_SCRIPT_DIR")
+        .or_else(|| std::env::var_os("OUT_DIR"))
+        .unwrap_or_else(|| "./doc".into());
+    fs::create_dir_all(&outdir)?;
+    println!("Generating CLI info to {:?}", outdir);
+
+    let mut app = Args::command();
+
+    // completions
+    let bin_name = "cyme";
+    generate_to(Bash, &mut app, bin_name, &outdir).expect("Failed to generate Bash completions");
+    generate_to(Fish, &mut app, bin_name, &outdir).expect("Failed to generate Fish completions");
+    generate_to(Zsh, &mut app, bin_name, &outdir).expect("Failed to generate Zsh completions");
+    generate_to(PowerShell, &mut app, bin_name, &outdir)
+        .expect("Failed to generate PowerShell completions");
+    add_dynamic_completions(&PathBuf::from(&outdir))?;
+
+    // man page
+    let man = clap_mangen::Man::new(app);
+    let mut buffer: Vec<u8> = Default::default();
+    man.render(&mut buffer)?;
+
+    std::fs::write(PathBuf::from(&outdir).join("cyme.1"), buffer)?;
+
+    // config and theming man pages
+    std::fs::write(PathBuf::from(&outdir).join("cyme.5"), config_man_page()?)?;
+    std::fs::write(
+        PathBuf::from(&outdir).join("cyme-icons.5"),
+        icons_man_page(),
+    )?;
+
+    // example config
+    std::fs::write(
+        PathBuf::from(&outdir).join("cyme_example_config.json"),
+        serde_json::to_string_pretty(&Config::example())?,
+    )?;
+
+    Ok(())
+}
+
+/// Escape `s` for roff text, with non-ASCII characters such as Nerd Font icons as unicode escapes
+#[cfg(feature = "cli_generate")]
+fn roff_escape(s: &str) -> String {
+    let escaped: String = s
+        .chars()
+        .map(|c| match c {
+            '\\' => "\\e".to_string(),
+            '-' => "\\-".to_string(),
+            c if !c.is_ascii() => format!("\\[u{:04X}]", c as u32),
+            c => c.to_string(),
+        })
+        .collect();
+    // control characters at the start of a line
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{}", escaped)
+    } else {
+        escaped
+    }
+}
+
+/// Roff header of a section 5 man page `name`
+#[cfg(feature = "cli_generate")]
+fn man_page_header(name: &str, description: &str) -> String {
+    format!(
+        ".TH {} 5 \"\" \"cyme {}\"\n.SH NAME\n{} \\- {}\n",
+        name,
+        env!("CARGO_PKG_VERSION"),
+        name,
+        roff_escape(description)
+    )
+}
+
+/// `cyme.5` man page of the config file keys, generated from the serde [`Config`] defaults and the help and possible values of the matching CLI [`Args`]
+#[cfg(feature = "cli_generate")]
+#[cold]
+fn config_man_page() -> Result<String> {
+    use clap::CommandFactory;
+
+    let command = Args::command();
+    let mut page = man_page_header("cyme", "configuration file for cyme");
+    page.push_str(".SH DESCRIPTION\nJSON object of the keys below, read from cyme.json in the cyme config directory or the file passed with \\fB\\-\\-config\\fR. All keys are optional and CLI arguments take precedence; see \\fIcyme_example_config.json\\fR.\n.PP\nThe config directory is $XDG_CONFIG_HOME/cyme or $HOME/.config/cyme on Linux, $HOME/Library/Application Support/cyme on macOS and {FOLDERID_RoamingAppData}/cyme on Windows.\n.SH KEYS\n");
+
+    let config = serde_json::to_value(Config::default())?;
+    for (key, default) in config.as_object().into_iter().flatten() {
+        let id = key.replace('-', "_");
+        let arg = command.get_arguments().find(|a| {
+            a.get_id() == id.as_str() || Some(a.get_id().as_str()) == id.strip_suffix("es")
+        });
+        let help = match key.as_str() {
+            "icons" => {
+                "Icons and tree drawing characters merged with the defaults; see cyme\\-icons(5)"
+                    .to_string()
+            }
+            "colours" => "Colours of block values and the tree replacing the defaults; see COLOURS"
+                .to_string(),
+            // config only keys
+            "max-variable-string-len" => roff_escape(
+                "Max variable string length to display before truncating - descriptors and classes for example",
+            ),
+            "class-blocks" => roff_escape(
+                "Object of base class to a list of blocks added after the blocks of devices with that device or interface class, audio or mass-storage for example. If not set, mass-storage devices have capacity and scsi-inquiry at max verbosity",
+            ),
+            "no-auto-width" => roff_escape(
+                "Disable auto generation of max-variable-string-len based on terminal width",
+            ),
+            "quirks" => roff_escape(
+                "Object of 'vid:pid', or 'vid' for all products of a vendor, to a list of quirks in addition to the built-in database: no-bos, no-qualifier, bad-string-length or fake-serial",
+            ),
+            "names" => roff_escape(
+                "Object of 'vid:pid' to product name, or 'vid' to manufacturer name of all products of a vendor, used in place of the device strings with the 'user' name source",
+            ),
+            "redact" => roff_escape(
+                "Object of what --redact redacts: 'method' (default hash) and 'salt' of masks, a random salt for each run if not set, booleans 'serials', 'addresses', 'hostnames' and 'volumes' (default true) and 'patterns', regular expressions whose matches are replaced with '[redacted]'",
+            ),
+            "print-non-critical-profiler-stderr" => roff_escape(
+                "Print non-critical errors (normally due to permissions) during USB profiler to stderr",
+            ),
+            _ => arg
+                .and_then(|a| a.get_help())
+                .map_or(String::new(), |h| roff_escape(&h.to_string())),
+        };
+        page.push_str(&format!(".TP\n\\fB{}\\fR\n{}\n", roff_escape(key), help));
+        if !default.is_object() {
+            page.push_str(&format!(
+                ".RS\nDefault: {}\n.RE\n",
+                roff_escape(&default.to_string())
+            ));
+        }
+        // flags such as --redact have the same name as some config objects
+        let values: Vec<_> = arg
+            .filter(|a| a.get_action().takes_values())
+            .map(|a| a.get_possible_values())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|v| !v.is_hide_set() && !default.is_boolean())
+            .collect();
+        if !values.is_empty() {
+            page.push_str(".RS\nValues:\n");
+            for value in values {
+                page.push_str(&format!(
+                    ".IP \\(bu 2\n\\fI{}\\fR{}\n",
+                    roff_escape(value.get_name()),
+                    value.get_help().map_or(String::new(), |h| format!(
+                        ": {}",
+                        roff_escape(&h.to_string())
+                    ))
+                ));
+            }
+            page.push_str(".RE\n");
+        }
+    }
+
+    page.push_str(".SH COLOURS\nKeys of the \\fBcolours\\fR object with their default. Values are a colour name such as 'bright blue', an RGB array [r, g, b] or null for no colour.\n");
+    let colours = serde_json::to_value(cyme::colour::ColourTheme::new())?;
+    for (key, default) in colours.as_object().into_iter().flatten() {
+        page.push_str(&format!(
+            ".TP\n\\fB{}\\fR\n{}\n",
+            roff_escape(key),
+            roff_escape(&default.to_string())
+        ));
+    }
+
+    page.push_str(".SH SEE ALSO\ncyme(1), cyme\\-icons(5)\n");
+    Ok(page)
+}
+
+/// `cyme-icons.5` man page of the icon theme keys and defaults, generated from [`cyme::icon::Icon`] and the default icon and tree maps
+#[cfg(feature = "cli_generate")]
+#[cold]
+fn icons_man_page() -> String {
+    let mut page = man_page_header("cyme\\-icons", "icon theme for cyme");
+    page.push_str(".SH DESCRIPTION\n");
+    page.push_str(&roff_escape("The \"icons\" object of the cyme(5) config has \"user\" icons for devices and \"tree\" drawing characters, both objects of icon key to string which are merged with the defaults below. Device icons are looked up by name and serial patterns, VID:PID, VID:PID range, VID:PID MSB, VID then VID range in that order, user icons before the defaults; the narrowest matching range is used. Interface icons by class with sub-class and protocol, with wildcards then class. Use cyme --list-icons to show the rule matched by each device."));
+    page.push('\n');
+
+    page.push_str(".SH ICON KEYS\n");
+    for (key, help) in [
+        ("vid#VID", "Vendor ID in base16"),
+        ("vid-pid#VID:PID", "Vendor ID and Product ID in base16"),
+        (
+            "vid-range#START:END",
+            "Inclusive range of Vendor IDs in base16",
+        ),
+        (
+            "vid-pid-range#VID:START:END",
+            "Vendor ID and inclusive range of Product IDs in base16",
+        ),
+        (
+            "vid-pid-msb#VID:MSB",
+            "Vendor ID and the most significant byte of the Product ID in base16",
+        ),
+        (
+            "classifier#CLASS",
+            "Device or interface base class code in base16",
+        ),
+        (
+            "classifier-sub-protocol#CLASS:SUB:PROTOCOL",
+            "Class, sub-class and protocol codes in base16; sub-class and protocol can be '*' to match any",
+        ),
+        (
+            "name#PATTERN",
+            "Regex pattern matched against the device name; requires the 'regex_icon' feature",
+        ),
+        (
+            "serial#PATTERN",
+            "Regex pattern matched against the device serial number; requires the 'regex_icon' feature",
+        ),
+    ] {
+        page.push_str(&format!(
+            ".TP\n\\fB{}\\fR\n{}\n",
+            roff_escape(key),
+            roff_escape(help)
+        ));
+    }
+
+    let mut defaults: Vec<(String, &str)> = cyme::icon::defaults()
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+    defaults.sort();
+    page.push_str(".SH DEFAULT ICONS\n");
+    for (key, value) in defaults {
+        page.push_str(&format!(
+            ".TP\n\\fB{}\\fR\n{}\n",
+            roff_escape(&key),
+            roff_escape(value)
+        ));
+    }
+
+    let mut tree: Vec<(String, &str, &str)> = cyme::icon::DEFAULT_UTF8_TREE
+        .iter()
+        .map(|(k, v)| {
+            (
+                k.to_string(),
+                *v,
+                cyme::icon::DEFAULT_ASCII_TREE
+                    .get(k)
+                    .copied()
+                    .unwrap_or_default(),
+            )
+        })
+        .collect();
+    tree.sort();
+    page.push_str(".SH TREE\nKeys of the \"tree\" object with their default UTF\\-8 and ASCII strings; ASCII is used with \\fB\\-\\-encoding ascii\\fR or \\fB\\-\\-lsusb\\fR.\n");
+    for (key, utf8, ascii) in tree {
+        page.push_str(&format!(
+            ".TP\n\\fB{}\\fR\n\"{}\" \"{}\"\n",
+            roff_escape(&key),
+            roff_escape(utf8),
+            roff_escape(ascii)
+        ));
+    }
+
+    page.push_str(".SH SEE ALSO\ncyme(1), cyme(5)\n");
+    page
+}
+
+/// Print the `candidates` values of the connected devices profiled with `builder`, one per line, for the shell completions
+///
+/// Devices are not opened since only the VID:PID, bus and address are needed and this runs on each TAB press.
+fn print_complete_candidates(
+    candidates: CompleteCandidates,
+    builder: &profiler::ProfilerBuilder,
+) -> Result<()> {
+    let sp = builder
+        .clone()
+        .with_extra(false)
+        .strings(false)
+        .attributes(false)
+        .profile()?;
+    let mut values: Vec<String> = sp
+        .iter()
+        .filter_map(|d| match candidates {
+            CompleteCandidates::Vidpid => d
+                .vendor_id
+                .zip(d.product_id)
+                .map(|(v, p)| format!("{:04x}:{:04x}", v, p)),
+            CompleteCandidates::Show => {
+                Some(format!("{}:{}", d.location_id.bus, d.location_id.number))
+            }
+            CompleteCandidates::Device => Some(d.dev_path()),
+        })
+        .collect();
+    values.sort();
+    values.dedup();
+
+    for value in values {
+        println!("{}", value);
+    }
+
+    Ok(())
+}
+
+/// Arguments completed from the connected devices as (short, long, [`CompleteCandidates`] value)
+#[cfg(feature = "cli_generate")]
+const DYNAMIC_COMPLETIONS: [(char, &str, &str); 3] = [
+    ('d', "vidpid", "vidpid"),
+    ('s', "show", "show"),
+    ('D', "device", "device"),
+];
+
+/// Edit the generated Bash, Zsh and Fish completions in `outdir` to complete [`DYNAMIC_COMPLETIONS`] with `cyme --complete-candidates` rather than files
+#[cfg(feature = "cli_generate")]
+#[cold]
+fn add_dynamic_completions(outdir: &std::path::Path) -> Result<()> {
+    use std::fs;
+
+    // bash: replace the file completion following the argument in the main command case
+    let path = outdir.join("cyme.bash");
+    let mut lines: Vec<String> = Vec::new();
+    let mut main_command = false;
+    let mut candidates: Option<&str> = None;
+    for line in fs::read_to_string(&path)?.lines() {
+        let trimmed = line.trim();
+        if trimmed == "cyme)" {
+            main_command = true;
+        } else if trimmed.starts_with("cyme__") {
+            main_command = false;
+        }
+
+        if main_command {
+            if let Some((_, _, c)) = DYNAMIC_COMPLETIONS
+                .iter()
+                .find(|(s, l, _)| trimmed == format!("-{})", s) || trimmed == format!("--{})", l))
+            {
+                candidates = Some(*c);
+            } else if let Some(c) = candidates.take() {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                lines.push(format!(
+                    "{}COMPREPLY=($(compgen -W \"$(cyme --complete-candidates {} 2>/dev/null)\" -- \"${{cur}}\"))",
+                    indent, c
+                ));
+                continue;
+            }
+        }
+        lines.push(line.to_string());
+    }
+    fs::write(&path, lines.join("\n") + "\n")?;
+
+    // zsh: replace the default action of the argument values
+    let path = outdir.join("_cyme");
+    let mut zsh = fs::read_to_string(&path)?;
+    for (_, long, c) in DYNAMIC_COMPLETIONS {
+        zsh = zsh.replace(
+            &format!(":{}:_default'", long.to_uppercase()),
+            &format!(
+                ":{}:{{compadd -- ${{(f)\"$(cyme --complete-candidates {} 2>/dev/null)\"}}}}'",
+                long.to_uppercase(),
+                c
+            ),
+        );
+    }
+    fs::write(&path, zsh)?;
+
+    // fish: add the candidates to the argument completions
+    let path = outdir.join("cyme.fish");
+    let mut fish = fs::read_to_string(&path)?;
+    for (short, long, c) in DYNAMIC_COMPLETIONS {
+        fish.push_str(&format!(
+            "complete -c cyme -s {} -l {} -x -a '(cyme --complete-candidates {} 2>/dev/null)'\n",
+            short, long, c
+        ));
+    }
+    fs::write(&path, fish)?;
+
+    Ok(())
+}
+
+/// Pager command from $PAGER or 'less' if not set; `None` if $PAGER is empty or 'cat'
+fn pager_command() -> Option<String> {
+    match env::var("PAGER") {
+        Ok(p) if p.trim().is_empty() || p.trim() == "cat" => None,
+        Ok(p) => Some(p),
+        Err(_) => Some(String::from("less")),
+    }
+}
+
+/// Set the module log levels, format and output from args; will also check env if args.debug == 0
+fn init_logger(args: &Args) -> Result<()> {
+    let debug = match args.debug {
+        0 if args.log_file.is_some() => 2,
+        d => d,
+    };
+    let mut logger = Logger::new(debug);
+    if args.debug == 0 {
+        logger = logger.env();
+    }
+    for filter in args.log_filter.iter() {
+        logger = logger.with_module_level(&filter.module, filter.level);
+    }
+    logger = logger.with_format(args.log_format).with_colours(
+        args.color
+            .resolve_with(|k| env::var(k).ok(), std::io::stderr().is_terminal())
+            == display::ColorWhen::Always,
+    );
+    if let Some(path) = args.log_file.as_ref() {
+        logger = logger.with_file(path)?;
+    }
+
+    logger.init()
+}
+
+/// Re-run cyme with the same arguments under sudo since `denied` devices could not be opened, exiting with its status
+#[cfg(unix)]
+fn rerun_with_sudo(denied: usize) -> Result<()> {
+    eprintln!(
+        "{} device(s) could not be opened due to permissions, re-running with sudo",
+        denied
+    );
+    let status = std::process::Command::new("sudo")
+        .arg(env::current_exe()?)
+        .args(env::args_os().skip(1))
+        .status()?;
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+#[cfg(not(unix))]
+fn rerun_with_sudo(_denied: usize) -> Result<()> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "--sudo is only supported on Unix, run from an administrator terminal instead",
+    ))
+}
+
+/// Whether --lsusb is passed on the command line, alone or in a cluster of flags such as `-tlv`, before it can be parsed
+fn lsusb_arg() -> bool {
+    std::env::args().skip(1).any(|a| {
+        a == "--lsusb"
+            || (a.starts_with('-')
+                && !a.starts_with("--")
+                && a.contains('l')
+                && a[1..].chars().all(|c| "ltvmFz".contains(c)))
+    })
+}
+
+/// Set the [`display::DisplayWriter`] for --output, --tee and --ansi-to-html, otherwise a pager if the terminal has `pager_height` lines
+fn set_output(
+    output: Option<&str>,
+    tee: bool,
+    ansi_to_html: bool,
+    pager_height: Option<u16>,
+) -> Result<()> {
+    if let Some(path) = output {
+        let file = std::fs::File::create(path).map_err(|e| {
+            Error::new(
+                ErrorKind::Io,
+                &format!("Failed to create output file {}; Error({})", path, e),
+            )
+        })?;
+        let file = Box::new(std::io::BufWriter::new(file));
+        display::set_output(if ansi_to_html {
+            display::DisplayWriter::html(file, tee)
+        } else {
+            display::DisplayWriter::new(file, tee)
+        });
+    } else if ansi_to_html {
+        display::set_output(display::DisplayWriter::html(
+            Box::new(std::io::stdout()),
+            false,
+        ));
+    } else if let (Some(command), Some(height)) = (pager_command(), pager_height) {
+        display::set_output(display::DisplayWriter::pager(&command, height as usize));
+    }
+
+    Ok(())
+}
+
+fn cyme(mut args: Args) -> Result<()> {
+    #[cfg(feature = "cli_generate")]
+    if args.gen {
+        print_man()?;
+        std::process::exit(0);
+    }
+
+    // legacy arg, hidden but still support with new format
+    if args.no_color {
+        args.color = display::ColorWhen::Never;
+    }
+
+    init_logger(&args)?;
+
+    let config = if let Some(path) = args.config.as_ref() {
+        let config = Config::from_file(path)?;
+        log::info!("Using user config {:?}", config);
+        config
+    } else {
+        Config::sys()?
+    };
+
+    merge_config(&config, &mut args);
+    let builder = profiler_builder(&config, &args)?;
+
+    if let Some(candidates) = args.complete_candidates {
+        return print_complete_candidates(candidates, &builder);
+    }
+
+    if args.capabilities {
+        let capabilities = Capabilities::detect_for(&builder);
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&capabilities)?);
+        } else {
+            print!("{}", capabilities);
+        }
+        return Ok(());
+    }
+
+    // html is only useful with colours, which would be off with auto when piping
+    if args.ansi_to_html && args.color == display::ColorWhen::Auto {
+        args.color = display::ColorWhen::Always;
+    }
+
+    if let Some(command) = args.command.as_ref() {
+        let redaction = args
+            .redact
+            .then(|| config.redact.clone().unwrap_or_default());
+        // no pager since commands can prompt or run until interrupted
+        set_output(args.output.as_deref(), args.tee, args.ansi_to_html, None)?;
+        run_command(command, &builder, args.color.colours(), redaction.as_ref())?;
+        display::finish_output(args.color.colours())?;
+        return Ok(());
+    }
+
+    // descriptors are printed at each level so show them all
+    if args.dump_descriptors {
+        args.verbose = args.verbose.max(3);
+    }
+
+    // field projection is only for json
+    if args.json_fields.is_some() {
+        args.json = true;
+    }
+
+    // config colours are a full theme so take precedence
+    let theme_colours = match args.theme {
+        Some(theme) if config.colours == cyme::colour::ColourTheme::new() => theme.colours(),
+        Some(theme) if theme != cyme::colour::Theme::Default => {
+            display::hint(Hint::new(&format!(
+                "colours in the config are used rather than the {} theme, remove them to use it",
+                theme
+            )));
+            config.colours
+        }
+        _ => config.colours,
+    };
+
+    // set the output colouring
+    let colours = args.color.colours().then_some(theme_colours);
+
+    if let Some((background, colours)) = cyme::colour::Background::detect().zip(colours.as_ref()) {
+        let illegible = colours.illegible(background);
+        if !illegible.is_empty() {
+            display::hint(Hint::new(&format!(
+                "{} colours have low contrast on the {} terminal background, change them in the config or try `--theme cb-safe`",
+                illegible
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                background
+            )));
+        }
+    }
+
     // legacy arg, hidden but still support with new format
     if args.ascii {
         args.encoding = display::Encoding::Ascii;
     }
 
+    if args.encoding == display::Encoding::Auto {
+        args.encoding = display::Encoding::detect();
+        log::info!("Detected terminal encoding {:?}", args.encoding);
+    }
+
     // support hidden no_icons arg
     let icons = if args.no_icons {
         // For the tree, the display crate falls back to the static defaults for the encoding
@@ -511,29 +2516,119 @@ fn cyme() -> Result<()> {
         Some(config.icons)
     };
 
+    // explain data missing from a live profile due to sources not available
+    let from_dump =
+        args.from_json.is_some() || args.from_descriptors.is_some() || args.from_usbview.is_some();
+    if !from_dump {
+        Capabilities::detect_for(&builder)
+            .hints()
+            .into_iter()
+            .for_each(display::hint);
+    }
+
     let mut spusb = if let Some(file_path) = args.from_json {
-        match profiler::read_json_dump(file_path.as_str()) {
-            Ok(s) => s,
-            Err(e) => {
-                log::warn!(
-                    "Failed to read json dump, attempting as flattened with phony bus: Error({})",
-                    e
-                );
-                profiler::read_flat_json_to_phony_bus(file_path.as_str())?
-            }
-        }
+        read_json_dump(file_path.as_str())?
+    } else if let Some(dir) = args.from_descriptors.as_ref() {
+        profiler::replay::read_descriptors_to_phony_bus(
+            dir,
+            builder
+                .clone()
+                .with_extra(true)
+                .raw_descriptors(requires_raw_descriptors(&args))
+                .validate_descriptors(args.show_errors)
+                .parse_mode(parse_mode(&args))
+                .print_errors(args.print_non_critical_profiler_stderr),
+        )?
+    } else if let Some(file_path) = args.from_usbview.as_ref() {
+        profiler::usbview::read_report(file_path)?
+    } else if use_cache(&args) {
+        let path = profiler::cache::default_path().ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                "Unable to find user cache directory for --cached",
+            )
+        })?;
+        profiler::cache::get_or_profile(
+            &path,
+            std::time::Duration::from_secs(args.cache_ttl),
+            requires_extra(&args),
+            args.no_open_class.as_deref().unwrap_or_default(),
+            || {
+                #[cfg(target_os = "macos")]
+                return get_system_profile_macos(&args, &builder);
+                #[cfg(not(target_os = "macos"))]
+                return get_system_profile(&args, &builder);
+            },
+        )?
     } else {
         #[cfg(target_os = "macos")]
         {
-            get_system_profile_macos(&args)?
+            get_system_profile_macos(&args, &builder)?
         }
 
         #[cfg(not(target_os = "macos"))]
         {
-            get_system_profile(&args)?
+            get_system_profile(&args, &builder)?
         }
     };
 
+    let open_errors = profiler::capabilities::open_errors(&spusb);
+    let denied = open_errors.iter().filter(|e| e.permission_denied).count();
+    // SUDO_USER is set when already re-run so do not loop
+    if args.sudo && denied > 0 && env::var_os("SUDO_USER").is_none() {
+        return rerun_with_sudo(denied);
+    }
+    open_errors.iter().map(|e| e.hint()).for_each(display::hint);
+    if denied > 0 && !args.sudo {
+        display::hint(Hint::new(&format!(
+            "{} device(s) could not be opened due to permissions; re-run with --sudo or grant the user access with a udev rule",
+            denied
+        )));
+    }
+
+    if args.show_errors {
+        descriptor_warning_hints(&spusb);
+    }
+
+    if !args.json
+        && !args
+            .blocks
+            .as_ref()
+            .is_some_and(|b| b.contains(&display::DeviceBlocks::Quirks))
+    {
+        quirk_hints(&spusb);
+    }
+
+    if args.thunderbolt {
+        if !args.tree {
+            display::hint(Hint::new("--thunderbolt is only shown with --tree"));
+        }
+        // devices are still listed without the domains
+        spusb.thunderbolt = match profiler::thunderbolt::get_domains() {
+            Ok(domains) => Some(domains),
+            Err(e) => {
+                display::hint(Hint::new(&format!(
+                    "Failed to profile Thunderbolt domains: {}",
+                    e
+                )));
+                None
+            }
+        };
+    }
+
+    if args.gadget {
+        if !args.tree {
+            display::hint(Hint::new("--gadget is only shown with --tree"));
+        }
+        spusb.gadget = match profiler::gadget::get_gadgets() {
+            Ok(gadgets) => Some(gadgets),
+            Err(e) => {
+                display::hint(Hint::new(&format!("Failed to profile gadgets: {}", e)));
+                None
+            }
+        };
+    }
+
     log::trace!("Returned system_profiler data\n\r{:#?}", spusb);
 
     let filter = if args.hide_hubs
@@ -543,6 +2638,9 @@ fn cyme() -> Result<()> {
         || args.filter_name.is_some()
         || args.filter_serial.is_some()
         || args.filter_class.is_some()
+        || args.filter_unclaimed
+        || args.filter_location.is_some()
+        || args.filter_expression.is_some()
     {
         let mut f = profiler::Filter::new();
 
@@ -560,13 +2658,17 @@ fn cyme() -> Result<()> {
         // decode device devpath into the show filter since that is what it essentially will do
         if let Some(devpath) = &args.device {
             let (bus, number) = parse_devpath(devpath.as_str()).map_err(|e| {
-                Error::new(
-                    ErrorKind::InvalidArg,
-                    &format!(
-                        "Failed to parse devpath '{}', should end with 'BUS/DEVNO'; Error({})",
-                        devpath, e
-                    ),
-                )
+                if args.lsusb {
+                    Error::new(ErrorKind::InvalidArg, &format!("Cannot open {}", devpath))
+                } else {
+                    Error::new(
+                        ErrorKind::InvalidArg,
+                        &format!(
+                            "Failed to parse devpath '{}', should end with 'BUS/DEVNO'; Error({})",
+                            devpath, e
+                        ),
+                    )
+                }
             })?;
             f.bus = bus;
             f.number = number;
@@ -585,22 +2687,36 @@ fn cyme() -> Result<()> {
         f.name = args.filter_name;
         f.serial = args.filter_serial;
         f.class = args.filter_class;
+        f.unclaimed = args.filter_unclaimed;
+        f.location = args.filter_location;
+        f.expression = args.filter_expression;
         f.exclude_empty_hub = args.hide_hubs;
+        // only relevant to tree since list is flattened before filtering
+        f.stub_parents = args.stub_parents && args.tree;
         // exclude root hubs unless:
         // * lsusb compat (shows root_hubs)
         // * json - for --from-json support
+        // * xml - USBView shows the root hubs
+        // * bandwidth - root hub speed is the capacity of the bus
         // * list_root_hubs - user wants to see root hubs in list
-        f.no_exclude_root_hub = args.lsusb || args.json || args.list_root_hubs;
+        f.no_exclude_root_hub =
+            args.lsusb || args.json || args.xml || args.bandwidth || args.list_root_hubs;
 
         Some(f)
     } else {
         // exclude root hubs (on Linux) unless:
         // * lsusb compat (shows root_hubs)
         // * json - for --from-json support
+        // * xml - USBView shows the root hubs
+        // * bandwidth - root hub speed is the capacity of the bus
         // * list_root_hubs - user wants to see root hubs in list
         if cfg!(target_os = "linux") {
             Some(profiler::Filter {
-                no_exclude_root_hub: (args.lsusb || args.json || args.list_root_hubs),
+                no_exclude_root_hub: (args.lsusb
+                    || args.json
+                    || args.xml
+                    || args.bandwidth
+                    || args.list_root_hubs),
                 ..Default::default()
             })
         } else {
@@ -608,8 +2724,10 @@ fn cyme() -> Result<()> {
         }
     };
 
-    let group_devices = if args.group_devices == display::Group::Bus && args.tree {
-        eprintln!("--group-devices with --tree is ignored; will print as tree");
+    let group_devices = if args.group_devices != display::Group::NoGroup && args.tree {
+        display::hint(Hint::new(
+            "--group-devices with --tree is ignored; will print as tree",
+        ));
         display::Group::NoGroup
     } else {
         args.group_devices
@@ -624,27 +2742,90 @@ fn cyme() -> Result<()> {
         sort_buses: args.sort_buses,
         group_devices,
         json: args.json,
+        json_fields: args.json_fields,
+        xml: args.xml,
+        template: args
+            .template
+            .as_ref()
+            .map(display::template::Template::from_file)
+            .transpose()?,
         headings: args.headings,
         verbosity: args.verbose,
         more: args.more,
         encoding: args.encoding,
         mask_serials: args.mask_serials.map_or(config.mask_serials, Some),
+        mask_salt: args.mask_salt.or(config.mask_salt),
+        speed_units: args.speed_units.map_or(config.speed_units, Some),
         device_blocks: args.blocks.map_or(config.blocks, Some),
         bus_blocks: args.bus_blocks.map_or(config.bus_blocks, Some),
         config_blocks: args.config_blocks.map_or(config.config_blocks, Some),
         interface_blocks: args.interface_blocks.map_or(config.interface_blocks, Some),
         endpoint_blocks: args.endpoint_blocks.map_or(config.endpoint_blocks, Some),
+        class_blocks: config.class_blocks,
         icons,
         colours,
         max_variable_string_len: config.max_variable_string_len,
         auto_width: !config.no_auto_width,
         terminal_size: terminal_size(),
         icon_when: args.icon,
+        dump_descriptors: args.dump_descriptors,
+        active_config_only: args.active_config_only,
+        compact: args.compact,
+        max_depth: args.max_depth,
+        highlight: args.highlight.map(|e| profiler::Filter {
+            expression: Some(e),
+            ..Default::default()
+        }),
+        serial_warnings: args
+            .warn_duplicates
+            .then(|| profiler::serials::report(spusb.iter()).port_paths()),
+        stripe: args.stripe,
+        separator: args.separator,
+        grid: args.grid,
+        inline_descriptors: args.inline_descriptors,
+        from_dump,
+        translator: i18n::locale_from_env()
+            .map(|locale| i18n::Translator::new(&locale))
+            .unwrap_or_default(),
     };
 
     display::prepare(&mut spusb, filter, &settings);
 
-    if args.lsusb {
+    // after filtering so serials and names can still be matched
+    if args.redact {
+        config.redact.unwrap_or_default().apply(&mut spusb)?;
+    }
+
+    // like grep, no match exits 1 without output other than the count so scripts can tell it from an error
+    let no_match = args.fail_if_empty && spusb.flattened_devices().is_empty();
+    if no_match && !args.count {
+        display::finish_output(settings.colours.is_some())?;
+        std::process::exit(1);
+    }
+
+    let pager_height = match settings.terminal_size {
+        Some((_, Height(height)))
+            if !args.no_pager && !args.lsusb && std::io::stdout().is_terminal() =>
+        {
+            Some(height)
+        }
+        _ => None,
+    };
+    set_output(
+        args.output.as_deref(),
+        args.tee,
+        args.ansi_to_html,
+        pager_height,
+    )?;
+
+    if args.lsusb && !args.count {
+        // lsusb exits with an error and no output if -s or -d match nothing
+        if (args.vidpid.is_some() || args.show.is_some())
+            && !args.tree
+            && spusb.flattened_devices().is_empty()
+        {
+            std::process::exit(1);
+        }
         print_lsusb(&spusb, &args.device, &settings)?;
     } else {
         // check and report if was looking for args.device
@@ -654,15 +2835,59 @@ fn cyme() -> Result<()> {
                 &format!("Unable to find device at {:?}", args.device.unwrap()),
             ));
         }
-        display::print(&spusb, &settings);
+        if let Some(block) = args.get.as_ref() {
+            display::print_block_values(&spusb.flattened_devices(), block, &settings);
+        } else if args.count {
+            display::print_count(&spusb, &settings);
+        } else if args.list_icons {
+            display::print_icon_matches(&spusb.flattened_devices(), &settings);
+        } else if args.bandwidth {
+            display::print_bandwidth(&spusb, &settings);
+        } else {
+            display::print(&spusb, &settings);
+        }
+    }
+
+    display::finish_output(settings.colours.is_some())?;
+    if no_match {
+        std::process::exit(1);
+    }
+    if let Some(path) = args.output.as_ref() {
+        if !args.tee {
+            eprintln!(
+                "{} devices written to {}",
+                spusb.flattened_devices().len(),
+                path
+            );
+        }
     }
 
     Ok(())
 }
 
 fn main() {
-    cyme().unwrap_or_else(|e| {
-        eprintexit!(e);
+    let args = Args::try_parse().unwrap_or_else(|e| {
+        // lsusb exits 1 on usage errors rather than 2
+        if e.use_stderr() && lsusb_arg() {
+            let _ = e.print();
+            std::process::exit(1);
+        }
+        e.exit()
+    });
+    let lsusb = args.lsusb;
+    let colours = !args.no_color && args.color.colours();
+    // 1 is no devices matching with --fail-if-empty so errors are 2 like grep
+    let error_code = if args.fail_if_empty { 2 } else { 1 };
+
+    cyme(args).unwrap_or_else(|e| {
+        // anything printed before the error
+        let _ = display::finish_output(colours);
+        // plain message like lsusb so scripts matching its stderr keep working
+        if lsusb {
+            eprintln!("{}", e.message());
+            std::process::exit(1);
+        }
+        eprintexit!(colours, e, error_code);
     });
 }
 
@@ -680,6 +2905,22 @@ mod tests {
         println!("{}", serde_json::to_string_pretty(&args).unwrap());
     }
 
+    #[test]
+    fn test_use_cache() {
+        let args = Args {
+            cached: true,
+            ..Default::default()
+        };
+        assert!(use_cache(&args));
+        // only the device would be profiled, which would replace the full profile in the cache
+        let args = Args {
+            cached: true,
+            device: Some(String::from("001:005")),
+            ..Default::default()
+        };
+        assert!(!use_cache(&args));
+    }
+
     #[test]
     fn test_parse_vidpid() {
         assert_eq!(
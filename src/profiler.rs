@@ -13,30 +13,65 @@
 //! See [`types`] docs for what can be done with returned data, such as [`Filter`]
 use crate::error::Result;
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+use std::sync::Arc;
 
 use crate::error::{Error, ErrorKind};
 #[cfg(all(target_os = "linux", any(feature = "udev", feature = "udevlib")))]
 use crate::udev;
 use crate::usb;
+use usb_ids::FromId;
 
 const REQUEST_GET_DESCRIPTOR: u8 = 0x06;
 const REQUEST_GET_STATUS: u8 = 0x00;
+const REQUEST_GET_CONFIGURATION: u8 = 0x08;
 const REQUEST_WEBUSB_URL: u8 = 0x02;
 
 const SYSFS_USB_PREFIX: &str = "/sys/bus/usb/devices/";
 const SYSFS_PCI_PREFIX: &str = "/sys/bus/pci/devices/";
+#[cfg(feature = "actions")]
+const SYSFS_USB_DRIVERS_PREFIX: &str = "/sys/bus/usb/drivers/";
 
 // separate module but import all
 pub mod types;
+#[cfg(feature = "watch")]
+pub use shared::SharedProfile;
 pub use types::*;
 
+pub mod audit;
+pub mod backend;
+pub mod bandwidth;
+pub mod bridges;
+pub mod cache;
+pub mod capabilities;
+pub mod capture;
+pub mod diff;
+pub mod export;
+pub mod expression;
+pub mod gadget;
+#[cfg(feature = "watch")]
+pub mod history;
+pub mod iter;
 #[cfg(feature = "libusb")]
 pub mod libusb;
+pub mod lint;
 #[cfg(target_os = "macos")]
 pub mod macos;
+pub mod merge;
+pub mod names;
 #[cfg(feature = "nusb")]
 pub mod nusb;
+pub mod quirks;
+pub mod replay;
+pub mod serials;
+#[cfg(feature = "watch")]
+pub mod shared;
+pub mod thunderbolt;
+pub mod usbview;
+#[cfg(feature = "watch")]
+pub mod watch;
 
 /// Transfer direction
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -92,6 +127,49 @@ pub(crate) trait UsbOperations {
     fn get_control_msg(&self, control_request: ControlRequest) -> Result<Vec<u8>>;
 }
 
+/// Key of a string in the [`StringCache`]: bus, address, VID, PID, language and string index
+type StringKey = (u8, u8, u16, u16, u16, u8);
+
+/// String descriptors read from devices during a profiling run, keyed by device identity
+///
+/// The same string index is often used by more than one descriptor of a device, such as the product string re-used as the configuration or interface name, so this prevents a control transfer for each. Failed reads are cached too so a device which does not respond is not asked again. VID and PID are part of the key so a different device enumerated at the same address is not given stale strings.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(not(any(feature = "libusb", feature = "nusb")), allow(dead_code))]
+pub(crate) struct StringCache(Rc<RefCell<HashMap<StringKey, Option<String>>>>);
+
+#[cfg_attr(not(any(feature = "libusb", feature = "nusb")), allow(dead_code))]
+impl StringCache {
+    /// Get string `index` in `language` of the device at `location` from the cache, otherwise with `read`, caching the result
+    pub(crate) fn get_or_read<F>(
+        &self,
+        location: &DeviceLocation,
+        vidpid: (u16, u16),
+        language: u16,
+        index: u8,
+        read: F,
+    ) -> Option<String>
+    where
+        F: FnOnce() -> Option<String>,
+    {
+        let key = (
+            location.bus,
+            location.number,
+            vidpid.0,
+            vidpid.1,
+            language,
+            index,
+        );
+        if let Some(cached) = self.0.borrow().get(&key) {
+            log::trace!("Using cached string {} of {}", index, location.port_path());
+            return cached.clone();
+        }
+
+        let string = read();
+        self.0.borrow_mut().insert(key, string.clone());
+        string
+    }
+}
+
 /// OS level USB Profiler trait for profiling USB devices
 pub(crate) trait Profiler<T>
 where
@@ -113,6 +191,20 @@ where
         device.get_control_msg(control_request)
     }
 
+    /// Get the full USB Configuration Descriptor of `total_length` bytes, including interface, endpoint and class descriptors, with a Control request
+    fn get_config_descriptor(device: &T, index: u8, total_length: u16) -> Result<Vec<u8>> {
+        let control = ControlRequest {
+            control_type: ControlType::Standard,
+            request: REQUEST_GET_DESCRIPTOR,
+            value: ((u8::from(usb::DescriptorType::Config) as u16) << 8) | index as u16,
+            index: 0,
+            recipient: Recipient::Device,
+            length: total_length as usize,
+            claim_interface: false,
+        };
+        device.get_control_msg(control)
+    }
+
     /// Get the USB Hub Descriptor with a Control request, include hub port statuses
     fn get_hub_descriptor(
         device: &T,
@@ -132,7 +224,8 @@ where
             value,
             index: 0,
             recipient: Recipient::Device,
-            length: 9,
+            // base descriptor and DeviceRemovable/PortPwrCtrlMask for up to 23 ports, as lsusb
+            length: 13,
             claim_interface: false,
         };
         let data = device.get_control_msg(control)?;
@@ -190,6 +283,24 @@ where
         Ok(u16::from_le_bytes([data[0], data[1]]))
     }
 
+    /// Get the bConfigurationValue of the configuration currently set; from sysfs on Linux where the kernel has it cached, otherwise with a GET_CONFIGURATION Control request
+    fn get_active_configuration(device: &T, sysfs_name: &str) -> Option<u8> {
+        get_sysfs_string(sysfs_name, "bConfigurationValue")
+            .and_then(|s| s.parse::<u8>().ok())
+            .or_else(|| {
+                let control = ControlRequest {
+                    control_type: ControlType::Standard,
+                    request: REQUEST_GET_CONFIGURATION,
+                    value: 0,
+                    index: 0,
+                    recipient: Recipient::Device,
+                    length: 1,
+                    claim_interface: false,
+                };
+                device.get_control_msg(control).ok()?.first().copied()
+            })
+    }
+
     /// Get the USB Debug Descriptor with a Control request
     fn get_debug_descriptor(device: &T) -> Result<usb::DebugDescriptor> {
         let control = ControlRequest {
@@ -349,6 +460,13 @@ where
                     device,
                     e
                 );
+                // left as a generic descriptor when lenient
+                if self.options().parse_mode == usb::ParseMode::Strict {
+                    return Err(Error::new(
+                        ErrorKind::InvalidDescriptor,
+                        &format!("Class descriptor {:02x?}: {}", extra_bytes, e),
+                    ));
+                }
             }
         }
 
@@ -525,6 +643,16 @@ where
         // Iterate on chunks of the header length
         while taken < extra_len && extra_len >= 2 {
             let dt_len = raw[0] as usize;
+            if dt_len < 2 || dt_len > raw.len() {
+                return Err(Error::new(
+                    ErrorKind::InvalidDescriptor,
+                    &format!(
+                        "Extra descriptor bLength {} invalid with {} bytes remaining",
+                        dt_len,
+                        raw.len()
+                    ),
+                ));
+            }
             let dt = self.build_descriptor_extra::<u8>(
                 device,
                 None,
@@ -554,18 +682,36 @@ where
         // Iterate on chunks of the header length
         while taken < extra_len && extra_len >= 2 {
             let dt_len = raw[0] as usize;
+            if dt_len < 2 || dt_len > raw.len() {
+                return Err(Error::new(
+                    ErrorKind::InvalidDescriptor,
+                    &format!(
+                        "Extra descriptor bLength {} invalid with {} bytes remaining",
+                        dt_len,
+                        raw.len()
+                    ),
+                ));
+            }
             if let Some(b) = raw.get_mut(1) {
-                // Mask request type LIBUSB_REQUEST_TYPE_CLASS
-                *b &= !(0x01 << 5);
-                // if not Device or Interface, force it to Interface (like lsusb) but warn
-                if !(*b == 0x01 || *b == 0x04) {
+                // Mask request type LIBUSB_REQUEST_TYPE_CLASS; if not Device or Interface, force it to Interface (like lsusb) but warn
+                let dt = usb::interface_extra_descriptor_type(*b);
+                if dt != *b & !(0x01 << 5) {
                     log::warn!(
                         "{:?} Misplaced descriptor type in interfaces: {:02x}",
                         device,
-                        *b
+                        *b & !(0x01 << 5)
                     );
-                    *b = 0x04;
+                    if self.options().parse_mode == usb::ParseMode::Strict {
+                        return Err(Error::new(
+                            ErrorKind::InvalidDescriptor,
+                            &format!(
+                                "Misplaced descriptor type in interfaces: {:02x}",
+                                *b & !(0x01 << 5)
+                            ),
+                        ));
+                    }
                 }
+                *b = dt;
             }
 
             let dt = self.build_descriptor_extra(
@@ -598,11 +744,19 @@ where
         // Iterate on chunks of the header length
         while taken < extra_len && extra_len >= 2 {
             let dt_len = raw[0] as usize;
+            if dt_len < 2 || dt_len > raw.len() {
+                return Err(Error::new(
+                    ErrorKind::InvalidDescriptor,
+                    &format!(
+                        "Extra descriptor bLength {} invalid with {} bytes remaining",
+                        dt_len,
+                        raw.len()
+                    ),
+                ));
+            }
             if let Some(b) = raw.get_mut(1) {
                 // Mask request type LIBUSB_REQUEST_TYPE_CLASS for Endpoint: 0x25
-                if *b == 0x25 {
-                    *b &= !(0x01 << 5);
-                }
+                *b = usb::endpoint_extra_descriptor_type(*b);
             };
 
             let dt = self.build_descriptor_extra(
@@ -620,6 +774,9 @@ where
         Ok(Some(ret))
     }
 
+    /// Options for which steps of profiling to perform
+    fn options(&self) -> &ProfilerBuilder;
+
     /// Get [`Device`]s connected to the host, excluding root hubs
     fn get_devices(&mut self, with_extra: bool) -> Result<Vec<Device>>;
 
@@ -645,12 +802,23 @@ where
 
     /// Build the [`SystemProfile`] from the Profiler get_devices and get_root_hubs (for buses) functions
     fn get_spusb(&mut self, with_extra: bool) -> Result<SystemProfile> {
-        let mut spusb = SystemProfile { buses: Vec::new() };
+        let mut spusb = SystemProfile {
+            buses: Vec::new(),
+            thunderbolt: None,
+            gadget: None,
+        };
 
         log::info!("Building SystemProfile with {:?}", self);
 
         // temporary store of devices created when iterating through DeviceList
-        let mut cache = self.get_devices(with_extra)?;
+        let mut cache = if self.options().buses_only {
+            Vec::new()
+        } else {
+            self.get_devices(with_extra)?
+        };
+        for device in cache.iter_mut() {
+            self.options().apply(device, with_extra);
+        }
         cache.sort_by_key(|d| d.location_id.bus);
         log::trace!("Sorted devices {:#?}", cache);
         // get system buses
@@ -662,42 +830,30 @@ where
             // create the bus if missing, we'll add devices at next step
             let mut new_bus = buses.remove(&key).unwrap_or(Bus::from(key));
 
-            // group into parent groups with parent path as key or trunk devices so they end up in same place
-            let parent_groups = group.group_by(|d| d.parent_path().unwrap_or(d.trunk_path()));
+            // group into parent groups with parent path as key or the bus for trunk devices and root_hubs so they end up in same place
+            let parent_groups = group.group_by(|d| {
+                let path = d.location_id.path();
+                path.parent().unwrap_or(path)
+            });
 
             // now go through parent paths inserting devices owned by that parent
             // this is not perfect...if the sort of devices does not result in order of depth, it will panic because the parent of a device will not exist. But that won't happen, right...
-            // sort key - ends_with to ensure root_hubs, which will have same str length as trunk devices will still be ahead
-            for (parent_path, children) in parent_groups
-                .into_iter()
-                .sorted_by_key(|x| x.0.len() - x.0.ends_with("-0") as usize)
+            for (parent_path, children) in parent_groups.into_iter().sorted_by_key(|x| x.0.depth())
             {
                 // if root devices, add them to bus
-                if parent_path.ends_with("-0") {
-                    // if parent_path == "-" {
-                    let devices = std::mem::take(&mut new_bus.devices);
-                    if let Some(mut d) = devices {
-                        for new_device in children {
-                            d.push(new_device);
-                        }
-                        new_bus.devices = Some(d);
-                    } else {
-                        new_bus.devices = Some(children.collect());
-                    }
+                if parent_path.is_root() {
+                    new_bus
+                        .devices
+                        .get_or_insert_with(Vec::new)
+                        .extend(children);
                     // else find and add parent - this should work because we are sorted to accend the tree so parents should be created before their children
                 } else {
-                    let parent_node = new_bus
-                        .get_node_mut(&parent_path)
-                        .expect("Parent node does not exist in new bus!");
-                    let devices = std::mem::take(&mut parent_node.devices);
-                    if let Some(mut d) = devices {
-                        for new_device in children {
-                            d.push(new_device);
-                        }
-                        parent_node.devices = Some(d);
-                    } else {
-                        parent_node.devices = Some(children.collect());
-                    }
+                    new_bus
+                        .get_node_at_mut(&parent_path)
+                        .expect("Parent node does not exist in new bus!")
+                        .devices
+                        .get_or_insert_with(Vec::new)
+                        .extend(children);
                 }
             }
 
@@ -712,6 +868,26 @@ where
             spusb.buses.sort_by_key(|b| b.usb_bus_number);
         }
 
+        // bus attributes are read from sysfs so skipped with the device ones, such as when polling
+        let attributes = self.options().attributes;
+        let role_switches = if attributes {
+            gadget::get_role_switches().unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+        for bus in spusb.buses.iter_mut() {
+            if attributes {
+                bus.usb4_tunnelled = bus.get_bus_number().and_then(get_sysfs_usb4_tunnelled);
+                bus.role = bus
+                    .get_bus_number()
+                    .and_then(|n| get_sysfs_role(n, &role_switches));
+            }
+            if with_extra {
+                bus.fill_removable_from_hubs();
+            }
+            bus.fill_location_kinds();
+        }
+
         Ok(spusb)
     }
 
@@ -723,17 +899,8 @@ where
 
         // merge if passed has any buses
         if !spusb.buses.is_empty() {
-            for mut bus in libusb_spusb.buses {
-                if let Some(existing) = spusb
-                    .buses
-                    .iter_mut()
-                    .find(|b| b.get_bus_number() == bus.get_bus_number())
-                {
-                    // just take the devices and put them in since nusb/libusb will be more verbose
-                    // bus macOS profiler will have accurate bus information
-                    existing.devices = std::mem::take(&mut bus.devices);
-                }
-            }
+            // nusb/libusb devices are more verbose but macOS profiler has accurate bus information and fields only it has
+            spusb.merge(libusb_spusb, merge::MergeStrategy::ReplaceDevices);
         }
 
         Ok(())
@@ -752,6 +919,16 @@ fn get_sysfs_string(sysfs_name: &str, attr: &str) -> Option<String> {
     return None;
 }
 
+/// Write a USB device attribute String to sysfs on Linux; requires write permission, normally root
+#[cfg(feature = "actions")]
+fn set_sysfs_string(sysfs_name: &str, attr: &str, value: &str) -> Result<()> {
+    SysfsWrite {
+        path: format!("{}{}/{}", SYSFS_USB_PREFIX, sysfs_name, attr),
+        value: value.to_string(),
+    }
+    .apply()
+}
+
 #[allow(unused_variables)]
 fn get_sysfs_readlink(sysfs_name: &str, attr: &str) -> Option<String> {
     #[cfg(any(target_os = "linux", target_os = "android"))]
@@ -782,6 +959,49 @@ fn get_udev_driver_name(port_path: &str) -> Result<Option<String>> {
     return Ok(None);
 }
 
+/// Get the kernel module of the driver bound at `port_path` from udev on Linux if the feature is enabled
+#[allow(unused_variables)]
+fn get_udev_module_name(port_path: &str) -> Result<Option<String>> {
+    #[cfg(all(target_os = "linux", any(feature = "udev", feature = "udevlib")))]
+    return udev::get_udev_module_name(port_path);
+    #[cfg(not(all(target_os = "linux", any(feature = "udev", feature = "udevlib"))))]
+    return Ok(None);
+}
+
+/// Get the parameters of kernel module `module` from '/sys/module/<module>/parameters' on Linux; parameters which are write-only or fail to read are skipped
+#[allow(unused_variables)]
+fn get_sysfs_module_parameters(module: &str) -> Option<BTreeMap<String, String>> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let path = format!("/sys/module/{}/parameters", module);
+        log::trace!("Getting module parameters at {}", path);
+        let parameters: BTreeMap<String, String> = std::fs::read_dir(path)
+            .ok()?
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let value = std::fs::read_to_string(e.path()).ok()?;
+                Some((
+                    e.file_name().to_string_lossy().to_string(),
+                    value.trim().to_string(),
+                ))
+            })
+            .collect();
+
+        (!parameters.is_empty()).then_some(parameters)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    return None;
+}
+
+/// Look up `key` of `modalias` in the udev hwdb with the Rust udev on Linux if the feature is enabled; with libudev and 'udev_hwdb' the hwdb is already used by [`crate::lsusb::names`]
+#[allow(unused_variables)]
+fn get_udev_hwdb(modalias: &str, key: &'static str) -> Option<String> {
+    #[cfg(all(target_os = "linux", feature = "udev"))]
+    return udev::hwdb::get(modalias, key).ok().flatten();
+    #[cfg(not(all(target_os = "linux", feature = "udev")))]
+    return None;
+}
+
 /// Get the USB device syspath from udev on Linux if the feature is enabled
 #[allow(unused_variables)]
 fn get_udev_syspath(port_path: &str) -> Result<Option<String>> {
@@ -800,65 +1020,938 @@ fn get_syspath(port_path: &str) -> Option<String> {
     return None;
 }
 
-/// Build [`SystemProfile`] by profiling system. Does not source [`usb::DeviceExtra`] - use [`get_spusb_with_extra`] for that; the extra operation is mostly moving data around so the only hit is to stack.
+/// Get the Bluetooth HCI adapter registered under a USB interface on Linux; the kernel btusb driver creates a 'bluetooth/hciX' child of the interface
 ///
-/// Runs through [`Profiler::get_devices()`] creating a cache of [`Device`]. Then sorts into parent groups, where the [`Bus`] is created -  with root hub information if available from [`Profiler::get_root_hubs()`] - and the tree built.
+/// Kernels without the 'address' attribute in sysfs only have the address in debugfs, so it is `None` unless run as root.
+#[allow(unused_variables)]
+fn get_sysfs_bluetooth(
+    interface_path: &str,
+    options: &ProfilerBuilder,
+) -> Option<usb::BluetoothAdapter> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let bt_path = format!("{}{}/bluetooth", SYSFS_USB_PREFIX, interface_path);
+        log::trace!("Getting Bluetooth HCI at {}", bt_path);
+        let name = std::fs::read_dir(&bt_path)
+            .ok()?
+            .filter_map(|e| e.ok())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .filter(|n| n.starts_with("hci"))
+            .sorted()
+            .next()?;
+        // older kernels expose the address in sysfs, newer only in debugfs, which requires root
+        let address = [
+            format!("{}/{}/address", bt_path, name),
+            format!("/sys/kernel/debug/bluetooth/{}/identity", name),
+        ]
+        .iter()
+        .find_map(|p| match std::fs::read_to_string(p) {
+            Ok(s) => Some(s),
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                log::debug!("Bluetooth address at {} requires root: {}", p, e);
+                None
+            }
+            Err(_) => None,
+        })
+        .and_then(|s| s.split_whitespace().next().map(|a| a.to_uppercase()));
+
+        let vendor = address.as_deref().and_then(|a| options.oui_vendor(a));
+
+        Some(usb::BluetoothAdapter {
+            name,
+            address,
+            vendor,
+        })
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    return None;
+}
+
+/// Fill the attributes of `device` kept in sysfs on Linux, such as power state, link and media; see [`ProfilerBuilder::attributes`]
+#[cfg_attr(not(any(feature = "libusb", feature = "nusb")), allow(dead_code))]
+fn fill_sysfs_attributes(device: &mut Device) {
+    let sysfs_name = device.sysfs_name();
+    device.power_state = get_sysfs_power_state(&sysfs_name);
+    device.connected_since = get_sysfs_connected_since(&sysfs_name);
+    (device.rx_lanes, device.tx_lanes, device.link_state) = get_sysfs_link(&sysfs_name);
+    device.physical_location = get_sysfs_physical_location(&sysfs_name);
+    device.removable = get_sysfs_removable(&sysfs_name);
+    device.media = get_sysfs_media(&sysfs_name);
+    device.batteries = get_sysfs_batteries(&sysfs_name);
+}
+
+/// Get the device runtime power management state from sysfs on Linux
+fn get_sysfs_power_state(sysfs_name: &str) -> Option<PowerState> {
+    let status = get_sysfs_string(sysfs_name, "power/runtime_status")?;
+    Some(PowerState {
+        status,
+        autosuspend_delay_ms: get_sysfs_string(sysfs_name, "power/autosuspend_delay_ms")
+            .and_then(|s| s.parse().ok()),
+    })
+}
+
+/// Get the Unix time in seconds the device was connected from the sysfs 'power/connected_duration' in ms on Linux
+fn get_sysfs_connected_since(sysfs_name: &str) -> Option<u64> {
+    let duration_ms: u64 = get_sysfs_string(sysfs_name, "power/connected_duration")?
+        .parse()
+        .ok()?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    Some(now.as_secs().saturating_sub(duration_ms / 1000))
+}
+
+/// Get the USB 3.2 lane counts and port state of a device from sysfs on Linux; (rx_lanes, tx_lanes, state)
+fn get_sysfs_link(sysfs_name: &str) -> (Option<u8>, Option<u8>, Option<String>) {
+    (
+        get_sysfs_string(sysfs_name, "rx_lanes").and_then(|s| s.parse().ok()),
+        get_sysfs_string(sysfs_name, "tx_lanes").and_then(|s| s.parse().ok()),
+        // the device 'port' links to the hub port, which has a 'state' attribute on newer kernels
+        get_sysfs_string(sysfs_name, "port/state"),
+    )
+}
+
+/// Get the ACPI physical location of the hub port a device is connected to from sysfs 'port/physical_location' on Linux
+fn get_sysfs_physical_location(sysfs_name: &str) -> Option<PhysicalLocation> {
+    let attr =
+        |name: &str| get_sysfs_string(sysfs_name, &format!("port/physical_location/{}", name));
+    Some(PhysicalLocation {
+        panel: attr("panel")?,
+        vertical_position: attr("vertical_position"),
+        horizontal_position: attr("horizontal_position"),
+        dock: attr("dock").is_some_and(|s| s == "yes"),
+        lid: attr("lid").is_some_and(|s| s == "yes"),
+    })
+}
+
+/// Get whether a device is on a removable port from sysfs 'removable' on Linux; the kernel sets it from ACPI or the hub DeviceRemovable bitmap and reports 'unknown' if neither say
+fn get_sysfs_removable(sysfs_name: &str) -> Option<bool> {
+    match get_sysfs_string(sysfs_name, "removable")?.as_str() {
+        "removable" => Some(true),
+        "fixed" => Some(false),
+        _ => None,
+    }
+}
+
+/// Get the SCSI disks of the mass storage interfaces of a device from sysfs on Linux
 ///
-/// The function will call which library is available based on the features enabled: 'nusb' or 'libusb'. If neither are enabled, it will return an error.If both are enabled, it will default to 'nusb'.
+/// The vendor, model and revision are the SCSI INQUIRY strings and the size the READ CAPACITY cached by the kernel when it probed the disk, so no permission is required. Slots of card readers without a card have no size.
+#[allow(unused_variables)]
+fn get_sysfs_media(sysfs_name: &str) -> Option<Vec<Media>> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use std::path::Path;
+
+        // sorted names of the entries of `path` starting with `prefix`
+        let entries = |path: &Path, prefix: &str| -> Vec<String> {
+            std::fs::read_dir(path)
+                .map(|d| {
+                    d.filter_map(|e| e.ok())
+                        .map(|e| e.file_name().to_string_lossy().to_string())
+                        .filter(|n| n.starts_with(prefix))
+                        .sorted()
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let read = |path: &Path| {
+            std::fs::read_to_string(path)
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+        };
+
+        let usb_devices = Path::new(SYSFS_USB_PREFIX);
+        let mut ret = Vec::new();
+        for interface in entries(usb_devices, &format!("{}:", sysfs_name)) {
+            if get_sysfs_string(&interface, "bInterfaceClass").as_deref() != Some("08") {
+                continue;
+            }
+            let usb_interface = get_sysfs_string(&interface, "bInterfaceNumber")
+                .and_then(|n| u8::from_str_radix(&n, 16).ok());
+
+            // <interface>/host<H>/target<H:C:T>/<H:C:T:L>
+            let interface_path = usb_devices.join(&interface);
+            for host in entries(&interface_path, "host") {
+                let host_path = interface_path.join(host);
+                for target in entries(&host_path, "target") {
+                    let target_path = host_path.join(target);
+                    for lun in entries(&target_path, "")
+                        .into_iter()
+                        .filter(|n| n.split(':').count() == 4)
+                    {
+                        let lun_path = target_path.join(&lun);
+                        let disk = entries(&lun_path.join("block"), "").into_iter().next();
+                        let disk_attr = |attr: &str| {
+                            disk.as_ref()
+                                .and_then(|d| read(&lun_path.join("block").join(d).join(attr)))
+                        };
+                        // always in 512 byte sectors whatever the logical block size
+                        let size_in_bytes = disk_attr("size")
+                            .and_then(|s| s.parse::<u64>().ok())
+                            .map(|s| s * 512)
+                            .filter(|s| *s > 0);
+                        let scsi_vendor = read(&lun_path.join("vendor"));
+                        let scsi_model = read(&lun_path.join("model"));
+
+                        let name = [scsi_vendor.as_deref(), scsi_model.as_deref()]
+                            .into_iter()
+                            .flatten()
+                            .join(" ");
+
+                        ret.push(Media {
+                            name,
+                            bsd_name: disk.clone(),
+                            logical_unit: lun.rsplit(':').next().and_then(|l| l.parse().ok()),
+                            usb_interface,
+                            removable: disk_attr("removable").map(|r| r == "1"),
+                            size: size_in_bytes.map(format_capacity),
+                            size_in_bytes,
+                            scsi_vendor,
+                            scsi_model,
+                            scsi_revision: read(&lun_path.join("rev")),
+                            ..Default::default()
+                        });
+                    }
+                }
+            }
+        }
+
+        (!ret.is_empty()).then_some(ret)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    return None;
+}
+
+/// Get the batteries of a device from the sysfs power supplies on Linux
 ///
-/// Bus data on Windows is only available with 'nusb', and on this bus numbers are created in order of appearance since it is not a concept in the Windows USB stack.
-pub fn get_spusb() -> Result<SystemProfile> {
-    #[cfg(all(feature = "libusb", not(feature = "nusb")))]
+/// The kernel registers a power supply for HID devices with battery strength reports under the HID device, which is under the USB interface. Each is matched to the nearest USB device in its path so those of devices connected to a hub are not also batteries of the hub.
+#[allow(unused_variables)]
+fn get_sysfs_batteries(sysfs_name: &str) -> Option<Vec<Battery>> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
     {
-        let mut profiler = libusb::LibUsbProfiler;
-        <libusb::LibUsbProfiler as Profiler<libusb::UsbDevice<rusb::Context>>>::get_spusb(
-            &mut profiler,
-            false,
-        )
+        let supplies = std::fs::read_dir("/sys/class/power_supply").ok()?;
+        let mut ret: Vec<Battery> = supplies
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let path = std::fs::canonicalize(e.path()).ok()?;
+                // USB device names are like '1-2.3' where interfaces have a ':' and the PCI host controller starts '0000:'
+                let usb_device = path.iter().map(|c| c.to_string_lossy()).rfind(|c| {
+                    c.contains('-')
+                        && !c.contains(':')
+                        && c.starts_with(|ch: char| ch.is_ascii_digit())
+                })?;
+                if usb_device != sysfs_name {
+                    return None;
+                }
+                let read = |attr: &str| {
+                    std::fs::read_to_string(path.join(attr))
+                        .ok()
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                };
+                if !matches!(read("type").as_deref(), Some("Battery" | "UPS")) {
+                    return None;
+                }
+                Some(Battery {
+                    name: e.file_name().to_string_lossy().to_string(),
+                    capacity: read("capacity").and_then(|c| c.parse().ok()),
+                    capacity_level: read("capacity_level"),
+                    status: read("status"),
+                })
+            })
+            .collect();
+        ret.sort_by(|a, b| a.name.cmp(&b.name));
+
+        (!ret.is_empty()).then_some(ret)
     }
-    #[cfg(feature = "nusb")]
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    return None;
+}
+
+/// Format `bytes` with a decimal unit prefix as storage is sold: '32.0 GB'
+#[cfg_attr(not(any(target_os = "linux", target_os = "android")), allow(dead_code))]
+fn format_capacity(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "kB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1000.0 && unit < UNITS.len() - 1 {
+        value /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Check whether any root hub port of the bus is tunnelled over USB4/Thunderbolt; the thunderbolt driver links a 'usb4_port' to the USB3 port it tunnels
+#[allow(unused_variables)]
+fn get_sysfs_usb4_tunnelled(bus_number: u8) -> Option<bool> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
     {
-        let mut profiler = nusb::NusbProfiler::new();
-        profiler.get_spusb(true)
+        let iface_path = format!("{}usb{}/{}-0:1.0", SYSFS_USB_PREFIX, bus_number, bus_number);
+        let ports = std::fs::read_dir(iface_path).ok()?;
+        Some(ports.filter_map(|e| e.ok()).any(|e| {
+            e.file_name().to_string_lossy().contains("-port") && e.path().join("usb4_port").exists()
+        }))
     }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    return None;
+}
 
-    #[cfg(all(not(feature = "libusb"), not(feature = "nusb")))]
+/// Current role of the dual-role port of the bus host controller from `switches`; the root hub is below the controller the role switch belongs to
+#[allow(unused_variables)]
+fn get_sysfs_role(bus_number: u8, switches: &[gadget::RoleSwitch]) -> Option<gadget::UsbRole> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
     {
-        Err(crate::error::Error::new(
-            crate::error::ErrorKind::Unsupported,
-            "nusb or libusb feature is required to do this, install with `cargo install --features nusb/libusb`",
-        ))
+        let path = std::fs::canonicalize(format!("{}usb{}", SYSFS_USB_PREFIX, bus_number)).ok()?;
+        gadget::role_of(&path, switches)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    return None;
+}
+
+/// Builder for profiling the system with control over which of the more expensive steps are performed
+///
+/// By default devices are not opened for extra data but string descriptors, USB IDs names and udev lookups are included.
+///
+/// ```no_run
+/// use cyme::profiler::ProfilerBuilder;
+///
+/// // only the buses and root hubs, no device descriptors or name lookups
+/// let spusb = ProfilerBuilder::new()
+///     .with_extra(false)
+///     .ids(false)
+///     .buses_only(true)
+///     .profile()
+///     .unwrap();
+/// println!("{:#}", spusb);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ProfilerBuilder {
+    with_extra: bool,
+    strings: bool,
+    ids: bool,
+    udev: bool,
+    attributes: bool,
+    buses_only: bool,
+    raw_descriptors: bool,
+    validate_descriptors: bool,
+    parse_mode: usb::ParseMode,
+    module_parameters: bool,
+    print_errors: bool,
+    // bit set of base class codes not to open
+    no_open_classes: [u64; 4],
+    backend: Option<backend::BackendKind>,
+    #[cfg_attr(not(feature = "libusb"), allow(dead_code))]
+    libusb_log_level: u8,
+    // shared so the builder is cheap to clone for each profile
+    names: Arc<names::Resolver>,
+    quirks: Arc<quirks::Database>,
+    vendor_decoders: Arc<usb::vendor::Registry>,
+}
+
+impl Default for ProfilerBuilder {
+    fn default() -> Self {
+        ProfilerBuilder {
+            with_extra: false,
+            strings: true,
+            ids: true,
+            udev: true,
+            attributes: true,
+            buses_only: false,
+            raw_descriptors: false,
+            validate_descriptors: false,
+            parse_mode: usb::ParseMode::Lenient,
+            module_parameters: false,
+            print_errors: false,
+            no_open_classes: [0; 4],
+            backend: None,
+            libusb_log_level: 0,
+            names: Arc::new(names::Resolver::new()),
+            quirks: Arc::new(quirks::Database::builtin()),
+            vendor_decoders: Arc::new(usb::vendor::Registry::new()),
+        }
+    }
+}
+
+impl ProfilerBuilder {
+    /// New builder with the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open devices to read descriptors for [`usb::DeviceExtra`]
+    pub fn with_extra(mut self, with_extra: bool) -> Self {
+        self.with_extra = with_extra;
+        self
+    }
+
+    /// Read string descriptors from opened devices; names cached by the OS are still used when disabled
+    pub fn strings(mut self, strings: bool) -> Self {
+        self.strings = strings;
+        self
+    }
+
+    /// Look up vendor and product names from udev-hwdb/USB IDs
+    ///
+    /// Names from descriptors or cached by the OS are used first, then the udev hwdb on Linux with a udev feature, then USB IDs. The hwdb also provides the organisation of Bluetooth adapter address OUIs.
+    pub fn ids(mut self, ids: bool) -> Self {
+        self.ids = ids;
+        self
+    }
+
+    /// Fall back to udev for driver and syspath when sysfs does not have them; Linux with 'udev' feature only
+    pub fn udev(mut self, udev: bool) -> Self {
+        self.udev = udev;
+        self
+    }
+
+    /// Read the device attributes the OS keeps: power state, connected time, link lanes and state, physical location, removable, media and batteries, and the USB4 tunnelling and dual-role of buses, from sysfs on Linux and the power state from the IORegistry on macOS
+    ///
+    /// Enabled by default; disable to only profile what the USB library has, such as when polling.
+    pub fn attributes(mut self, attributes: bool) -> Self {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Only profile the [`Bus`]es and their root hubs, not the devices attached
+    pub fn buses_only(mut self, buses_only: bool) -> Self {
+        self.buses_only = buses_only;
+        self
+    }
+
+    /// Keep the raw bytes of configuration, interface and endpoint descriptors when profiling with extra; see [`usb::Configuration::set_raw_descriptors`]
+    pub fn raw_descriptors(mut self, raw_descriptors: bool) -> Self {
+        self.raw_descriptors = raw_descriptors;
+        self
+    }
+
+    /// Check each configuration descriptor against the lengths and counts it declares when profiling with extra, setting [`usb::Configuration::warnings`]; see [`usb::descriptors::validate_configuration`]
+    ///
+    /// With libusb this reads the configuration descriptor from the device again as it only has the parsed descriptors.
+    pub fn validate_descriptors(mut self, validate_descriptors: bool) -> Self {
+        self.validate_descriptors = validate_descriptors;
+        self
+    }
+
+    /// How descriptors which fail to parse when profiling with extra are handled; see [`usb::ParseMode`]
+    ///
+    /// Lenient, the default, leaves descriptors which fail to parse as generic or without them and sets [`Device::profiler_error`]. Strict returns the error from [`Self::profile`] and [`Self::get_device`], so it can be used to validate device firmware; errors reading descriptors, such as a device not supporting one, are still tolerated. [`Self::devices_iter`] prints the error and skips the device.
+    pub fn parse_mode(mut self, parse_mode: usb::ParseMode) -> Self {
+        self.parse_mode = parse_mode;
+        self
+    }
+
+    /// Read the parameters of the kernel module of each interface driver for [`usb::KernelModule::parameters`]; Linux only
+    pub fn module_parameters(mut self, module_parameters: bool) -> Self {
+        self.module_parameters = module_parameters;
+        self
+    }
+
+    /// Print non-critical errors during profiling, normally due to permissions opening devices, to stderr rather than logging them as warnings
+    pub fn print_errors(mut self, print_errors: bool) -> Self {
+        self.print_errors = print_errors;
+        self
+    }
+
+    /// Do not open devices with a device or interface base class in `classes` for extra data; opening some devices, such as smartcard readers or printers, has side effects on the device
+    ///
+    /// Devices not opened have the extra data available without opening, such as driver and syspath, but no descriptors.
+    pub fn no_open_classes<I: IntoIterator<Item = usb::BaseClass>>(mut self, classes: I) -> Self {
+        for class in classes {
+            let code = u8::from(class);
+            self.no_open_classes[(code / 64) as usize] |= 1 << (code % 64);
+        }
+        self
+    }
+
+    /// Profile with the USB library `backend` rather than the default; see [`backend::default_kind`]
+    ///
+    /// Profiling returns an [`ErrorKind::Unsupported`] error if the backend is not compiled in.
+    pub fn backend(mut self, backend: backend::BackendKind) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Log level of libusb as a verbosity count: 0 for none, 1 warnings, 2 info and 3 or more debug
+    ///
+    /// It is set on the libusb context created for each profile rather than the default context, so other users of libusb in the process are not affected.
+    pub fn libusb_log_level(mut self, level: u8) -> Self {
+        self.libusb_log_level = level;
+        self
+    }
+
+    /// Resolve device names with `resolver`, which has the [`names::NameSource`] order and user overrides; the default prefers the device strings
+    pub fn names(mut self, resolver: names::Resolver) -> Self {
+        self.names = Arc::new(resolver);
+        self
+    }
+
+    /// Apply the [`quirks::Quirk`]s of devices in `database`; the default is [`quirks::Database::builtin`] so start from that to add entries
+    pub fn quirks(mut self, database: quirks::Database) -> Self {
+        self.quirks = Arc::new(database);
+        self
+    }
+
+    /// Decode vendor-specific descriptors with the decoders in `registry` when profiling with extra; there are none by default
+    pub fn vendor_decoders(mut self, registry: usb::vendor::Registry) -> Self {
+        self.vendor_decoders = Arc::new(registry);
+        self
+    }
+
+    /// Kind of the [`backend::Backend`] selected or the default; `None` if neither 'nusb' or 'libusb' are compiled in
+    pub fn backend_kind(&self) -> Option<backend::BackendKind> {
+        self.backend.or_else(backend::default_kind)
+    }
+
+    /// The [`backend::Backend`] selected or the default
+    fn get_backend(&self) -> Result<&'static dyn backend::Backend> {
+        match self.backend {
+            Some(kind) => kind.backend(),
+            None => backend::default_backend(),
+        }
+    }
+
+    /// Reset the [`Device`] with a USB port reset using the selected or default [`backend::Backend`]
+    ///
+    /// On Linux, if the library reset fails the device is re-enumerated by toggling the sysfs 'authorized' attribute instead. The device will disconnect and reconnect so any existing handles will be invalid and the device number may change.
+    #[cfg(feature = "actions")]
+    pub fn reset_device(&self, device: &Device) -> Result<()> {
+        let ret = self
+            .get_backend()
+            .and_then(|b| b.reset_device(&device.location_id));
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        let ret = ret.or_else(|e| {
+            log::warn!(
+                "Failed to reset {} with USB library, falling back to sysfs authorized toggle: {}",
+                device,
+                e
+            );
+            let sysfs_name = device.sysfs_name();
+            set_sysfs_string(&sysfs_name, "authorized", "0")?;
+            set_sysfs_string(&sysfs_name, "authorized", "1")
+        });
+
+        ret
+    }
+
+    /// Decode the vendor-specific descriptors of a profiled `device` if `with_extra`, then resolve its names and apply its quirks
+    pub(crate) fn apply(&self, device: &mut Device, with_extra: bool) {
+        if with_extra && !self.vendor_decoders.is_empty() {
+            device.decode_vendor_descriptors(&self.vendor_decoders);
+        }
+        self.names.resolve(device, self);
+        quirks::apply(device, &self.quirks);
+    }
+
+    /// Build [`SystemProfile`] by profiling the system with the options
+    ///
+    /// Runs through [`Profiler::get_devices()`] creating a cache of [`Device`]. Then sorts into parent groups, where the [`Bus`] is created -  with root hub information if available from [`Profiler::get_root_hubs()`] - and the tree built.
+    ///
+    /// The library used is that selected with [`ProfilerBuilder::backend`], otherwise the [`backend::default_kind`]: 'nusb' if the feature is enabled, then 'libusb'. If neither are enabled, it will return an error.
+    ///
+    /// Bus data on Windows is only available with 'nusb', and on this bus numbers are created in order of appearance since it is not a concept in the Windows USB stack.
+    pub fn profile(&self) -> Result<SystemProfile> {
+        self.get_backend()?.profile(self.clone())
+    }
+
+    /// Iterate [`Device`]s with the options, profiling each as it is reached rather than building the full [`SystemProfile`] upfront
+    ///
+    /// Root hubs are not included and the devices are flat: `devices` is always `None`. `buses_only` has no effect.
+    ///
+    /// With 'libusb' the device list itself is enumerated eagerly when the iterator is created - libusb has no lazy enumeration - so only the per-device profiling (descriptors, strings, sysfs attributes) is deferred. With 'nusb' both are lazy.
+    pub fn devices_iter(&self) -> Result<DeviceIter> {
+        let inner = self.get_backend()?.devices_iter(self.clone())?;
+        Ok(DeviceIter::new(inner, self.clone()))
+    }
+
+    /// Profile only the [`Device`] at device `address` on `bus` with the options, rather than profiling every device to build the [`SystemProfile`]
+    ///
+    /// Much faster when only one device is wanted, like lsusb `--device`. Root hubs are not included; use [`ProfilerBuilder::profile`] for those.
+    pub fn get_device(&self, bus: u8, address: u8) -> Result<Device> {
+        self.get_selected_device(DeviceSelector::Address(bus, address))
+    }
+
+    /// Profile only the [`Device`] at `path` with the options; see [`ProfilerBuilder::get_device`]
+    ///
+    /// ```no_run
+    /// use cyme::profiler::ProfilerBuilder;
+    ///
+    /// let path = "1-1.2".parse().unwrap();
+    /// let device = ProfilerBuilder::new()
+    ///     .with_extra(true)
+    ///     .get_device_by_path(&path)
+    ///     .unwrap();
+    /// println!("{:#}", device);
+    /// ```
+    pub fn get_device_by_path(&self, path: &usb::PortPath) -> Result<Device> {
+        self.get_selected_device(DeviceSelector::Path(path))
+    }
+
+    fn get_selected_device(&self, selector: DeviceSelector) -> Result<Device> {
+        let mut device = self.get_backend()?.get_device(self.clone(), &selector)?;
+        self.apply(&mut device, self.with_extra);
+        Ok(device)
+    }
+
+    /// Whether a device with device and interface base class codes `classes` should be opened for extra data
+    #[cfg_attr(not(any(feature = "libusb", feature = "nusb")), allow(dead_code))]
+    pub(crate) fn opens<I: IntoIterator<Item = u8>>(&self, classes: I) -> bool {
+        !classes
+            .into_iter()
+            .any(|c| self.no_open_classes[(c / 64) as usize] & (1 << (c % 64)) != 0)
+    }
+
+    /// Vendor name from udev-hwdb or USB IDs if `ids` is enabled
+    pub(crate) fn vendor_name(&self, vendor_id: u16) -> Option<String> {
+        if !self.ids {
+            return None;
+        }
+        get_udev_hwdb(
+            &format!("usb:v{:04X}*", vendor_id),
+            "ID_VENDOR_FROM_DATABASE",
+        )
+        .or_else(|| crate::lsusb::names::vendor(vendor_id))
+        .or_else(|| usb_ids::Vendor::from_id(vendor_id).map(|v| v.name().to_owned()))
+    }
+
+    /// Product name from udev-hwdb or USB IDs if `ids` is enabled
+    pub(crate) fn product_name(&self, vendor_id: u16, product_id: u16) -> Option<String> {
+        if !self.ids {
+            return None;
+        }
+        get_udev_hwdb(
+            &format!("usb:v{:04X}p{:04X}*", vendor_id, product_id),
+            "ID_MODEL_FROM_DATABASE",
+        )
+        .or_else(|| crate::lsusb::names::product(vendor_id, product_id))
+        .or_else(|| {
+            usb_ids::Device::from_vid_pid(vendor_id, product_id).map(|d| d.name().to_owned())
+        })
+    }
+
+    /// Organisation of the OUI of a MAC or Bluetooth `address` from the udev hwdb if `ids` is enabled
+    pub(crate) fn oui_vendor(&self, address: &str) -> Option<String> {
+        if !self.ids {
+            return None;
+        }
+        crate::lsusb::names::oui(address)
+    }
+
+    /// Driver of the device or interface at `sysfs_name` from sysfs, falling back to udev if enabled
+    pub(crate) fn driver(&self, sysfs_name: &str) -> Option<String> {
+        get_sysfs_readlink(sysfs_name, "driver").or_else(|| {
+            if self.udev {
+                get_udev_driver_name(sysfs_name).ok().flatten()
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Kernel module of the driver bound to the interface at `sysfs_name` from sysfs, falling back to udev if enabled, with its parameters if `module_parameters`
+    pub(crate) fn module(&self, sysfs_name: &str) -> Option<usb::KernelModule> {
+        let name = get_sysfs_readlink(sysfs_name, "driver/module").or_else(|| {
+            if self.udev {
+                get_udev_module_name(sysfs_name).ok().flatten()
+            } else {
+                None
+            }
+        })?;
+        let parameters = if self.module_parameters {
+            get_sysfs_module_parameters(&name)
+        } else {
+            None
+        };
+
+        Some(usb::KernelModule { name, parameters })
+    }
+
+    /// Syspath of the device or interface at `sysfs_name`, falling back to udev if enabled
+    pub(crate) fn syspath(&self, sysfs_name: &str) -> Option<String> {
+        get_syspath(sysfs_name).or_else(|| {
+            if self.udev {
+                get_udev_syspath(sysfs_name).ok().flatten()
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// Device to profile with [`ProfilerBuilder::get_device`] or [`ProfilerBuilder::get_device_by_path`]
+#[derive(Debug, Clone, Copy)]
+pub enum DeviceSelector<'a> {
+    /// Bus number and device address
+    Address(u8, u8),
+    /// Port path of device
+    Path(&'a usb::PortPath),
+}
+
+#[cfg_attr(not(any(feature = "libusb", feature = "nusb")), allow(dead_code))]
+impl DeviceSelector<'_> {
+    /// Bus number of the device
+    pub(crate) fn bus(&self) -> u8 {
+        match self {
+            DeviceSelector::Address(bus, _) => *bus,
+            DeviceSelector::Path(path) => path.bus,
+        }
+    }
+
+    /// Whether the device on `bus` with `address` and `ports` is the one selected
+    pub(crate) fn is_match(&self, bus: u8, address: u8, ports: &[u8]) -> bool {
+        match self {
+            DeviceSelector::Address(b, a) => *b == bus && *a == address,
+            DeviceSelector::Path(path) => path.bus == bus && path.ports == ports,
+        }
+    }
+
+    /// Error for when no device matches
+    pub(crate) fn not_found(&self) -> Error {
+        let location = match self {
+            DeviceSelector::Address(bus, address) => usb::get_dev_path(*bus, Some(*address)),
+            DeviceSelector::Path(path) => path.to_string(),
+        };
+        Error::new(
+            ErrorKind::NotFound,
+            &format!("Unable to find device at {}", location),
+        )
+    }
+}
+
+/// Bus number of each of `bus_ids` where numbers are assigned in the order the buses are first seen, as the nusb profiler does on Windows where buses have no number
+///
+/// Devices are listed in the same order for a single device as for a full profile so it has the same bus number in both.
+#[cfg_attr(not(feature = "nusb"), allow(dead_code))]
+pub(crate) fn assign_bus_numbers<'a>(bus_ids: impl IntoIterator<Item = &'a str>) -> Vec<u8> {
+    let mut seen: Vec<&str> = Vec::new();
+    bus_ids
+        .into_iter()
+        .map(|id| match seen.iter().position(|s| *s == id) {
+            Some(i) => i as u8,
+            None => {
+                seen.push(id);
+                (seen.len() - 1) as u8
+            }
+        })
+        .collect()
+}
+
+/// Iterator of [`Device`]s which are profiled as they are reached; see [`ProfilerBuilder::devices_iter`]
+pub struct DeviceIter {
+    inner: Box<dyn Iterator<Item = Device>>,
+}
+
+impl DeviceIter {
+    /// Wrap backend `inner` so that each [`Device`] has vendor descriptors, names and quirks applied per `options` as it is reached
+    fn new(inner: impl Iterator<Item = Device> + 'static, options: ProfilerBuilder) -> Self {
+        DeviceIter {
+            inner: Box::new(inner.map(move |mut d| {
+                options.apply(&mut d, options.with_extra);
+                d
+            })),
+        }
+    }
+}
+
+impl std::fmt::Debug for DeviceIter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("DeviceIter").finish_non_exhaustive()
+    }
+}
+
+impl Iterator for DeviceIter {
+    type Item = Device;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
     }
 }
 
+/// Iterate [`Device`]s connected to the host, profiling each as it is reached so that consumers can stop early, such as when looking for the first match of a [`Filter`]
+///
+/// Uses the default [`ProfilerBuilder`] so does not include [`usb::DeviceExtra`]; use [`ProfilerBuilder::devices_iter`] for that.
+///
+/// ```no_run
+/// use cyme::profiler::{self, Filter};
+///
+/// let filter = Filter {
+///     vid: Some(0x1d50),
+///     ..Default::default()
+/// };
+/// let device = profiler::devices_iter()
+///     .unwrap()
+///     .find(|d| filter.is_match(d));
+/// ```
+pub fn devices_iter() -> Result<DeviceIter> {
+    ProfilerBuilder::new().devices_iter()
+}
+
+/// Profile only the [`Device`] at device `address` on `bus`, including [`usb::DeviceExtra`]; see [`ProfilerBuilder::get_device`]
+pub fn get_device(bus: u8, address: u8) -> Result<Device> {
+    ProfilerBuilder::new()
+        .with_extra(true)
+        .get_device(bus, address)
+}
+
+/// Profile only the [`Device`] at `path`, including [`usb::DeviceExtra`]; see [`ProfilerBuilder::get_device`]
+pub fn get_device_by_path(path: &usb::PortPath) -> Result<Device> {
+    ProfilerBuilder::new()
+        .with_extra(true)
+        .get_device_by_path(path)
+}
+
+/// Build [`SystemProfile`] by profiling system. Does not source [`usb::DeviceExtra`] - use [`get_spusb_with_extra`] for that; the extra operation is mostly moving data around so the only hit is to stack.
+///
+/// Uses the default [`ProfilerBuilder`]; see [`ProfilerBuilder::profile`] for more information and to control which steps are performed.
+pub fn get_spusb() -> Result<SystemProfile> {
+    // nusb has always profiled with extra here so keep that behaviour
+    ProfilerBuilder::new()
+        .with_extra(backend::default_kind() == Some(backend::BackendKind::Nusb))
+        .profile()
+}
+
 /// Build [`SystemProfile`] including [`usb::DeviceExtra`] - the main function to use for most use cases unless one does not want verbose data. The extra data requires opening the device to read device descriptors.
 ///
-/// See [`Profiler::get_spusb()`] for more information.
+/// Uses [`ProfilerBuilder`] `with_extra`; see [`ProfilerBuilder::profile`] for more information.
 pub fn get_spusb_with_extra() -> Result<SystemProfile> {
-    #[cfg(all(feature = "libusb", not(feature = "nusb")))]
-    {
-        let mut profiler = libusb::LibUsbProfiler;
-        <libusb::LibUsbProfiler as Profiler<libusb::UsbDevice<rusb::Context>>>::get_spusb(
-            &mut profiler,
-            true,
-        )
+    ProfilerBuilder::new().with_extra(true).profile()
+}
+
+/// Reset the [`Device`] with a USB port reset using the default [`backend::Backend`]; see [`ProfilerBuilder::reset_device`]
+#[cfg(feature = "actions")]
+pub fn reset_device(device: &Device) -> Result<()> {
+    ProfilerBuilder::new().reset_device(device)
+}
+
+/// A write to a sysfs file which modifies device state on Linux
+///
+/// Returned by the sysfs actions so that the write can be shown as a dry-run before being applied with [`SysfsWrite::apply`]. Display is the equivalent shell command.
+#[cfg(feature = "actions")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SysfsWrite {
+    /// Absolute path of the sysfs file
+    pub path: String,
+    /// Value to write
+    pub value: String,
+}
+
+#[cfg(feature = "actions")]
+impl std::fmt::Display for SysfsWrite {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "echo '{}' > {}", self.value, self.path)
     }
+}
 
-    #[cfg(feature = "nusb")]
-    {
-        let mut profiler = nusb::NusbProfiler::new();
-        profiler.get_spusb(true)
+#[cfg(feature = "actions")]
+impl SysfsWrite {
+    /// Write the value to the sysfs path; requires write permission, normally root
+    pub fn apply(&self) -> Result<()> {
+        log::trace!("Writing sysfs {}", self);
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        return std::fs::write(&self.path, &self.value).map_err(|e| {
+            Error::new(
+                ErrorKind::Io,
+                &format!(
+                    "Failed to write '{}' to {}, probably requires elevated permissions; Error({})",
+                    self.value, self.path, e
+                ),
+            )
+        });
+        #[cfg(not(any(target_os = "linux", target_os = "android")))]
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            "sysfs is only available on Linux",
+        ));
     }
+}
 
-    #[cfg(all(not(feature = "libusb"), not(feature = "nusb")))]
+/// Check `sysfs_name` is a USB device or interface in sysfs
+#[cfg(feature = "actions")]
+fn check_sysfs_name(sysfs_name: &str) -> Result<()> {
+    if cfg!(not(any(target_os = "linux", target_os = "android"))) {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "sysfs actions are only available on Linux",
+        ))
+    } else if sysfs_name.is_empty()
+        || sysfs_name.contains('/')
+        || !std::path::Path::new(&format!("{}{}", SYSFS_USB_PREFIX, sysfs_name)).exists()
     {
-        Err(crate::error::Error::new(
-            crate::error::ErrorKind::Unsupported,
-            "nusb or libusb feature is required to do this, install with `cargo install --features nusb/libusb`",
+        Err(Error::new(
+            ErrorKind::NotFound,
+            &format!("No USB device or interface '{}' in sysfs", sysfs_name),
         ))
+    } else {
+        Ok(())
     }
 }
 
+/// Get the [`SysfsWrite`] to authorize or deauthorize a USB device or interface by sysfs name (port path), e.g. '1-1.2' or '1-1.2:1.0'
+///
+/// A deauthorized device is disconnected by the kernel and its interfaces are not bound until authorized again.
+#[cfg(feature = "actions")]
+pub fn sysfs_authorize(sysfs_name: &str, authorize: bool) -> Result<SysfsWrite> {
+    check_sysfs_name(sysfs_name)?;
+    Ok(SysfsWrite {
+        path: format!("{}{}/authorized", SYSFS_USB_PREFIX, sysfs_name),
+        value: if authorize { "1" } else { "0" }.to_string(),
+    })
+}
+
+/// Get the [`SysfsWrite`] to unbind the driver currently bound to a USB device or interface by sysfs name (port path)
+#[cfg(feature = "actions")]
+pub fn sysfs_driver_unbind(sysfs_name: &str) -> Result<SysfsWrite> {
+    check_sysfs_name(sysfs_name)?;
+    // not get_sysfs_readlink as that returns the pci driver for root hubs
+    let driver = std::fs::read_link(format!("{}{}/driver", SYSFS_USB_PREFIX, sysfs_name))
+        .ok()
+        .and_then(|p| p.file_name().map(|f| f.to_string_lossy().to_string()))
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                &format!("No driver bound to '{}'", sysfs_name),
+            )
+        })?;
+
+    Ok(SysfsWrite {
+        path: format!("{}{}/unbind", SYSFS_USB_DRIVERS_PREFIX, driver),
+        value: sysfs_name.to_string(),
+    })
+}
+
+/// Get the [`SysfsWrite`] to bind a USB device or interface by sysfs name (port path) to `driver`
+///
+/// If `driver` is None, the kernel is asked to probe for any matching driver instead.
+#[cfg(feature = "actions")]
+pub fn sysfs_driver_bind(sysfs_name: &str, driver: Option<&str>) -> Result<SysfsWrite> {
+    check_sysfs_name(sysfs_name)?;
+    let path = match driver {
+        Some(d) => {
+            if d.is_empty()
+                || d.contains('/')
+                || !std::path::Path::new(&format!("{}{}", SYSFS_USB_DRIVERS_PREFIX, d)).exists()
+            {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    &format!("No USB driver '{}' loaded", d),
+                ));
+            }
+            format!("{}{}/bind", SYSFS_USB_DRIVERS_PREFIX, d)
+        }
+        None => "/sys/bus/usb/drivers_probe".to_string(),
+    };
+
+    Ok(SysfsWrite {
+        path,
+        value: sysfs_name.to_string(),
+    })
+}
+
 #[cfg(target_os = "windows")]
 mod platform {
     use super::*;
@@ -1195,3 +2288,192 @@ mod platform {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_select_device_by_assigned_bus() {
+        // devices on Windows buses listed in profile order, with the second device on the second bus seen
+        let bus_ids = [
+            "PCI\\VEN_8086&DEV_A36D",
+            "PCI\\VEN_1B21&DEV_2142",
+            "PCI\\VEN_8086&DEV_A36D",
+        ];
+        let buses = assign_bus_numbers(bus_ids);
+        assert_eq!(buses, vec![0, 1, 0]);
+
+        let devices = [(1u8, vec![1u8]), (1, vec![1]), (2, vec![2])];
+        let path = usb::PortPath::from_str("1-1").unwrap();
+        let selector = DeviceSelector::Path(&path);
+        let selected = devices
+            .iter()
+            .zip(buses.iter())
+            .position(|((address, ports), bus)| selector.is_match(*bus, *address, ports));
+        assert_eq!(selected, Some(1));
+        assert!(DeviceSelector::Address(0, 2).is_match(buses[2], 2, &[2]));
+    }
+
+    #[test]
+    fn test_string_cache() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let devices = spusb.flattened_devices();
+        let cache = StringCache::default();
+        let reads = Cell::new(0);
+        let read = |s: Option<&str>| {
+            reads.set(reads.get() + 1);
+            s.map(String::from)
+        };
+
+        let device = devices[0];
+        let vidpid = (device.vendor_id.unwrap(), device.product_id.unwrap());
+        let first = cache.get_or_read(&device.location_id, vidpid, 0x0409, 2, || {
+            read(Some(&device.name))
+        });
+        assert_eq!(first.as_deref(), Some(device.name.as_str()));
+        // re-used index is not read again, including through a clone of the cache
+        let again = cache
+            .clone()
+            .get_or_read(&device.location_id, vidpid, 0x0409, 2, || {
+                read(Some("other"))
+            });
+        assert_eq!(again, first);
+        assert_eq!(reads.get(), 1);
+
+        // failed reads are cached too
+        assert_eq!(
+            cache.get_or_read(&device.location_id, vidpid, 0x0409, 3, || read(None)),
+            None
+        );
+        assert_eq!(
+            cache.get_or_read(&device.location_id, vidpid, 0x0409, 3, || read(Some(
+                "late"
+            ))),
+            None
+        );
+        assert_eq!(reads.get(), 2);
+
+        // a different device at the same address is read
+        let replaced = cache.get_or_read(
+            &device.location_id,
+            (vidpid.0, vidpid.1 ^ 1),
+            0x0409,
+            2,
+            || read(Some("replaced")),
+        );
+        assert_eq!(replaced.as_deref(), Some("replaced"));
+        // as is a different language or a device at another location
+        cache.get_or_read(&device.location_id, vidpid, 0x0407, 2, || read(None));
+        let other = devices
+            .iter()
+            .find(|d| d.location_id != device.location_id)
+            .unwrap();
+        cache.get_or_read(&other.location_id, vidpid, 0x0409, 2, || read(None));
+        assert_eq!(reads.get(), 5);
+    }
+
+    #[test]
+    fn test_no_open_classes() {
+        let options = ProfilerBuilder::new()
+            .no_open_classes([usb::BaseClass::Printer, usb::BaseClass::VendorSpecificClass]);
+        assert!(ProfilerBuilder::new().opens([0x07, 0xff]));
+        // bits either side of the set ones and in the other words are not set
+        assert!(options.opens([0x06, 0x08, 0x47, 0xbf, 0xfe]));
+        assert!(!options.opens([0x07]));
+        assert!(!options.opens([0x00, 0xff]));
+
+        // a device is not opened if the device or any interface class is excluded
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let mut opened = 0;
+        for device in spusb.flattened_devices() {
+            let mut classes: Vec<u8> = device.class.map(u8::from).into_iter().collect();
+            if let Some(extra) = device.extra.as_ref() {
+                classes.extend(
+                    extra
+                        .configurations
+                        .iter()
+                        .flat_map(|c| c.interfaces.iter())
+                        .map(|i| u8::from(i.class)),
+                );
+            }
+            let excluded = device.class == Some(usb::BaseClass::Printer)
+                || device.has_interface_class(&usb::BaseClass::Printer)
+                || device.has_interface_class(&usb::BaseClass::VendorSpecificClass);
+            assert_eq!(options.opens(classes), !excluded, "{}", device);
+            opened += usize::from(!excluded);
+        }
+        assert!(opened > 0);
+        assert!(opened < spusb.flattened_devices().len());
+    }
+
+    #[test]
+    fn test_device_iter_lazy() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let devices: Vec<Device> = spusb
+            .flattened_devices()
+            .into_iter()
+            .map(|d| {
+                let mut d = d.clone();
+                d.devices = None;
+                d
+            })
+            .collect();
+        let total = devices.len();
+        let target = devices[total / 2].location_id.clone();
+
+        let reached = Rc::new(Cell::new(0));
+        let counter = reached.clone();
+        let backend = devices
+            .into_iter()
+            .inspect(move |_| counter.set(counter.get() + 1));
+        let mut iter = DeviceIter::new(backend, ProfilerBuilder::new());
+
+        let found = iter.find(|d| d.location_id == target).unwrap();
+        assert_eq!(found.location_id, target);
+        // stops at the match rather than profiling every device
+        assert_eq!(reached.get(), total / 2 + 1);
+        assert_eq!(iter.count(), total - total / 2 - 1);
+        assert_eq!(reached.get(), total);
+    }
+
+    #[test]
+    fn test_builder_apply_own_names_quirks() {
+        let device = Device {
+            name: String::from("Probe"),
+            vendor_id: Some(0x1d50),
+            product_id: Some(0x6018),
+            name_source: Some(names::NameSource::Descriptor),
+            ..Default::default()
+        };
+        let mut resolver = names::Resolver::new();
+        resolver.set_override(0x1d50, Some(0x6018), "My Probe");
+        let mut database = quirks::Database::new();
+        database.add(quirks::QuirkEntry::new(
+            0x1d50,
+            None,
+            vec![quirks::Quirk::FakeSerial],
+        ));
+        let options = ProfilerBuilder::new().names(resolver).quirks(database);
+        let other = ProfilerBuilder::new();
+
+        // each builder has its own, so applying one does not change the other in another thread
+        let handle = std::thread::spawn({
+            let mut device = device.clone();
+            move || {
+                other.apply(&mut device, false);
+                device
+            }
+        });
+        let mut applied = device.clone();
+        options.apply(&mut applied, false);
+        assert_eq!(applied.name, "My Probe");
+        assert_eq!(applied.quirks, Some(vec![quirks::Quirk::FakeSerial]));
+
+        let default = handle.join().unwrap();
+        assert_eq!(default.name, "Probe");
+        assert_eq!(default.quirks, None);
+    }
+}
@@ -0,0 +1,881 @@
+//! Security audit of connected devices for potentially suspicious ones; see [`audit`]
+//!
+//! Looks for devices commonly used in BadUSB attacks: boot keyboards on ports not expected to have one, devices with cloned serial numbers, VID:PIDs of boards used to build keystroke injectors and composite devices which are both mass storage and HID. A [`Policy`] adjusts what is expected for the endpoint and can lock it down with an allowlist and denylist of [`Rule`]s. Checks of interfaces require the [`crate::usb::DeviceExtra`] so the devices must be profiled with extra.
+//!
+//! Findings are indicators to investigate rather than proof: a BadUSB device can mimic any VID:PID and descriptors.
+//!
+//! ```
+//! use cyme::profiler::audit::{audit, Policy};
+//! use cyme::profiler::read_json_dump;
+//!
+//! let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+//! let policy = Policy {
+//!     keyboard_ports: vec![String::from("1-2")],
+//!     ..Default::default()
+//! };
+//! assert!(audit(&spusb, &policy).is_empty());
+//! ```
+use crate::colour::colored::*;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::lint::Severity;
+use super::quirks::Quirk;
+use super::{Device, Filter, SystemProfile};
+use crate::error::{Error, ErrorKind, Result};
+use crate::redact::Redaction;
+use crate::usb::BaseClass;
+
+/// VID, PID and serial of devices which are clones of each other if they share it
+type SerialKey<'a> = (Option<u16>, Option<u16>, &'a str);
+
+/// A check of [`audit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Check {
+    /// HID boot keyboard on a port not in [`Policy::keyboard_ports`]; only checked if the policy has some
+    UnexpectedKeyboard,
+    /// More than one device with the same VID:PID and serial number; a warning since many cheap devices share a factory serial
+    ClonedSerial,
+    /// VID:PID of a known BadUSB device or a board commonly used to build one
+    KnownBadUsb,
+    /// Composite device with both mass storage and HID interfaces
+    StorageHid,
+    /// Device matches a [`Policy::denylist`] rule
+    Denied,
+    /// Device matches no [`Policy::allowlist`] rule; only checked if the policy has some and hubs with only hub interfaces are exempt
+    NotAllowed,
+}
+
+impl Check {
+    /// Whether the check is of the [`Policy`] rather than a heuristic; a cloned serial is common for cheap devices and composite storage HID devices exist so those should not be acted on
+    pub fn is_policy(&self) -> bool {
+        matches!(
+            self,
+            Check::UnexpectedKeyboard | Check::KnownBadUsb | Check::Denied | Check::NotAllowed
+        )
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Check::UnexpectedKeyboard => write!(f, "unexpected-keyboard"),
+            Check::ClonedSerial => write!(f, "cloned-serial"),
+            Check::KnownBadUsb => write!(f, "known-bad-usb"),
+            Check::StorageHid => write!(f, "storage-hid"),
+            Check::Denied => write!(f, "denied"),
+            Check::NotAllowed => write!(f, "not-allowed"),
+        }
+    }
+}
+
+/// Devices matching a 'vid:pid' or 'vid' for all products of the vendor, such as in a [`Policy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct VidPid {
+    /// Vendor ID
+    pub vendor_id: u16,
+    /// Product ID or `None` for all products of the vendor
+    pub product_id: Option<u16>,
+}
+
+impl VidPid {
+    /// Whether `device` has the vendor and product ID
+    pub fn matches(&self, device: &Device) -> bool {
+        device.vendor_id == Some(self.vendor_id)
+            && self.product_id.is_none_or(|p| device.product_id == Some(p))
+    }
+}
+
+impl FromStr for VidPid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parse = |v: &str| {
+            u16::from_str_radix(v.trim().trim_start_matches("0x"), 16).map_err(|e| {
+                Error::new(
+                    ErrorKind::Parsing,
+                    &format!("Invalid 'vid:pid' or 'vid' '{}'; Error({})", s, e),
+                )
+            })
+        };
+        match s.split_once(':') {
+            Some((vid, pid)) => Ok(VidPid {
+                vendor_id: parse(vid)?,
+                product_id: Some(parse(pid)?),
+            }),
+            None => Ok(VidPid {
+                vendor_id: parse(s)?,
+                product_id: None,
+            }),
+        }
+    }
+}
+
+impl TryFrom<String> for VidPid {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<VidPid> for String {
+    fn from(v: VidPid) -> Self {
+        v.to_string()
+    }
+}
+
+impl fmt::Display for VidPid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.product_id {
+            Some(pid) => write!(f, "{:04x}:{:04x}", self.vendor_id, pid),
+            None => write!(f, "{:04x}", self.vendor_id),
+        }
+    }
+}
+
+/// Devices matched by a [`Policy::allowlist`] or [`Policy::denylist`] entry; every field which is set must match so an empty rule matches all devices
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Rule {
+    /// 'vid:pid' or 'vid' of the device
+    pub vidpid: Option<VidPid>,
+    /// Class of the device or any of its interfaces; interfaces require [`crate::usb::DeviceExtra`]
+    pub class: Option<BaseClass>,
+    /// Serial number pattern where '*' matches any characters and '?' any one
+    pub serial: Option<String>,
+}
+
+impl Rule {
+    /// Whether `device` matches every field of the rule which is set
+    ///
+    /// ```
+    /// use cyme::profiler::audit::Rule;
+    /// use cyme::profiler::Device;
+    ///
+    /// let device = Device {
+    ///     vendor_id: Some(0x1d50),
+    ///     product_id: Some(0x6018),
+    ///     serial_num: Some(String::from("LAB-0042")),
+    ///     ..Default::default()
+    /// };
+    /// let rule = Rule {
+    ///     vidpid: Some("1d50".parse().unwrap()),
+    ///     serial: Some(String::from("LAB-*")),
+    ///     ..Default::default()
+    /// };
+    /// assert!(rule.matches(&device));
+    /// ```
+    pub fn matches(&self, device: &Device) -> bool {
+        self.vidpid.is_none_or(|v| v.matches(device))
+            && self
+                .class
+                .is_none_or(|c| device.class == Some(c) || device.has_interface_class(&c))
+            && self.serial.as_ref().is_none_or(|p| {
+                device
+                    .serial_num
+                    .as_deref()
+                    .is_some_and(|s| glob_match(p, s))
+            })
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut fields = Vec::new();
+        if let Some(v) = self.vidpid.as_ref() {
+            fields.push(format!("vidpid {}", v));
+        }
+        if let Some(c) = self.class.as_ref() {
+            fields.push(format!("class {}", c));
+        }
+        if let Some(p) = self.serial.as_ref() {
+            fields.push(format!("serial '{}'", p));
+        }
+        if fields.is_empty() {
+            write!(f, "any device")
+        } else {
+            write!(f, "{}", fields.join(", "))
+        }
+    }
+}
+
+/// Whether `s` matches `pattern` where '*' matches any characters, including none, and '?' any one
+fn glob_match(pattern: &str, s: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let s: Vec<char> = s.chars().collect();
+    let (mut p, mut i) = (0, 0);
+    // position of the last '*' and the character of `s` it is matching up to
+    let mut star: Option<(usize, usize)> = None;
+    while i < s.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == s[i]) {
+            p += 1;
+            i += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, i));
+            p += 1;
+        } else if let Some((sp, si)) = star {
+            // let the '*' match one more character
+            star = Some((sp, si + 1));
+            p = sp + 1;
+            i = si + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Built-in [`Check::KnownBadUsb`] entries; boards commonly flashed as keystroke injectors which also have legitimate uses so are warnings
+const BUILTIN_BAD_USB: &[(u16, Option<u16>, &str)] = &[
+    // V-USB shared keyboard PID used by Digispark DigiKeyboard sketches
+    (
+        0x16c0,
+        Some(0x27db),
+        "V-USB keyboard, used by Digispark keystroke injectors",
+    ),
+    (0x16d0, Some(0x0753), "Digispark bootloader"),
+    // Pi Zero based tools such as P4wnP1 leave the libcomposite example IDs
+    (
+        0x1d6b,
+        Some(0x0104),
+        "Linux multifunction composite gadget, used by P4wnP1 and other Pi Zero tools",
+    ),
+    (
+        0x2341,
+        Some(0x8036),
+        "Arduino Leonardo, used by MalDuino and similar keystroke injectors",
+    ),
+    (
+        0x2341,
+        Some(0x8037),
+        "Arduino Micro, used by MalDuino and similar keystroke injectors",
+    ),
+    (
+        0x1b4f,
+        Some(0x9206),
+        "SparkFun Pro Micro, used by keystroke injectors",
+    ),
+];
+
+/// What is expected of the endpoint being audited; loaded from a json file with [`Policy::from_file`]
+///
+/// ```json
+/// {
+///   "keyboard-ports": ["1-2", "3-1.4"],
+///   "bad-usb": ["1234:5678"],
+///   "trusted": ["2341:8036"],
+///   "allow": ["storage-hid"],
+///   "allowlist": [{"class": "hid"}, {"vidpid": "0781", "serial": "LAB-*"}],
+///   "denylist": [{"class": "mass-storage"}]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Policy {
+    /// Port paths keyboards are expected on, including devices connected through a hub at the port; [`Check::UnexpectedKeyboard`] is not checked if empty
+    #[serde(default)]
+    pub keyboard_ports: Vec<String>,
+    /// VID:PIDs of BadUSB devices in addition to the built-in entries; these are errors rather than warnings
+    #[serde(default)]
+    pub bad_usb: Vec<VidPid>,
+    /// VID:PIDs of devices which are never flagged
+    #[serde(default)]
+    pub trusted: Vec<VidPid>,
+    /// Checks to skip
+    #[serde(default)]
+    pub allow: Vec<Check>,
+    /// Devices which are allowed; any other device other than a hub with only hub interfaces is [`Check::NotAllowed`] if not empty
+    #[serde(default)]
+    pub allowlist: Vec<Rule>,
+    /// Devices which are [`Check::Denied`], even if in the allowlist
+    #[serde(default)]
+    pub denylist: Vec<Rule>,
+}
+
+impl Policy {
+    /// Load a policy from a json file at `path`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let data = fs::read_to_string(&path).map_err(|e| {
+            Error::new(
+                ErrorKind::Io,
+                &format!("Failed to read policy at {:?}; Error({})", path.as_ref(), e),
+            )
+        })?;
+        serde_json::from_str(&data).map_err(|e| {
+            Error::new(
+                ErrorKind::Config,
+                &format!(
+                    "Failed to parse policy at {:?}; Error({})",
+                    path.as_ref(),
+                    e
+                ),
+            )
+        })
+    }
+
+    /// Whether a keyboard at `port_path` is expected
+    fn is_keyboard_port(&self, port_path: &str) -> bool {
+        self.keyboard_ports.iter().any(|p| {
+            port_path == p
+                || port_path
+                    .strip_prefix(p.as_str())
+                    .is_some_and(|r| r.starts_with('.'))
+        })
+    }
+
+    fn is_trusted(&self, device: &Device) -> bool {
+        self.trusted.iter().any(|t| t.matches(device))
+    }
+}
+
+/// A potentially suspicious device found by a [`Check`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    /// Check which flagged the device
+    pub check: Check,
+    /// How suspicious it is
+    pub severity: Severity,
+    /// Port path of the device
+    pub port_path: String,
+    /// Vendor and product ID as 'vid:pid'
+    pub vidpid: String,
+    /// Name of the device
+    pub name: String,
+    /// Why the device was flagged
+    pub message: String,
+}
+
+impl Finding {
+    fn new(device: &Device, check: Check, severity: Severity, message: String) -> Self {
+        Finding {
+            check,
+            severity,
+            port_path: device.port_path(),
+            vidpid: format!(
+                "{:04x}:{:04x}",
+                device.vendor_id.unwrap_or(0),
+                device.product_id.unwrap_or(0)
+            ),
+            name: device.name.to_owned(),
+            message,
+        }
+    }
+
+    /// Whether the finding is an error of a [`Check::is_policy`] check, which the device should be deauthorized for
+    pub fn is_policy_violation(&self) -> bool {
+        self.severity == Severity::Error && self.check.is_policy()
+    }
+
+    /// Display string coloured by severity: red for errors and yellow for warnings
+    pub fn to_coloured_string(&self) -> String {
+        match self.severity {
+            Severity::Error => self.to_string().red().to_string(),
+            Severity::Warning => self.to_string().yellow().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} '{}': {} [{}]: {}",
+            self.port_path, self.vidpid, self.name, self.severity, self.check, self.message
+        )
+    }
+}
+
+/// Whether `device` has a HID boot keyboard interface; requires [`crate::usb::DeviceExtra`]
+///
+/// Keyboards which do not support the boot protocol can only be identified from their report descriptor so are not found.
+pub fn has_boot_keyboard(device: &Device) -> bool {
+    device.extra.as_ref().is_some_and(|e| {
+        e.configurations
+            .iter()
+            .flat_map(|c| c.interfaces.iter())
+            .any(|i| i.class == BaseClass::Hid && i.sub_class == 1 && i.protocol == 1)
+    })
+}
+
+/// Run every [`Check`] not allowed by `policy` on the devices in `spusb`, other than root hubs
+pub fn audit(spusb: &SystemProfile, policy: &Policy) -> Vec<Finding> {
+    let devices: Vec<&Device> = spusb.iter().filter(|d| !d.is_root_hub()).collect();
+    audit_devices(&devices, policy)
+}
+
+/// Run every [`Check`] not allowed by `policy` on `devices`; [`Check::ClonedSerial`] is only found between devices in the slice
+pub fn audit_devices(devices: &[&Device], policy: &Policy) -> Vec<Finding> {
+    let devices: Vec<&Device> = devices
+        .iter()
+        .copied()
+        .filter(|d| !policy.is_trusted(d))
+        .collect();
+    let mut findings: Vec<Finding> = devices
+        .iter()
+        .flat_map(|d| audit_device(d, policy))
+        .collect();
+
+    if !policy.allow.contains(&Check::ClonedSerial) {
+        let mut serials: HashMap<SerialKey, Vec<&Device>> = HashMap::new();
+        for device in devices.iter().copied().filter(|d| {
+            // shared by every unit so not a clone
+            !d.quirks
+                .as_ref()
+                .is_some_and(|q| q.contains(&Quirk::FakeSerial))
+        }) {
+            if let Some(serial) = device
+                .serial_num
+                .as_deref()
+                .filter(|s| !s.trim().is_empty())
+            {
+                serials
+                    .entry((device.vendor_id, device.product_id, serial))
+                    .or_default()
+                    .push(device);
+            }
+        }
+        let mut clones: Vec<&Vec<&Device>> = serials.values().filter(|d| d.len() > 1).collect();
+        clones.sort_by_key(|d| d[0].port_path());
+        for clone in clones {
+            for device in clone {
+                let others: Vec<String> = clone
+                    .iter()
+                    .filter(|o| o.port_path() != device.port_path())
+                    .map(|o| o.port_path())
+                    .collect();
+                findings.push(Finding::new(
+                    device,
+                    Check::ClonedSerial,
+                    Severity::Warning,
+                    format!(
+                        "serial '{}' is also used by {}",
+                        device.serial_num.as_deref().unwrap_or_default(),
+                        others.join(", ")
+                    ),
+                ));
+            }
+        }
+    }
+
+    findings
+}
+
+/// Run every [`Check`] not allowed by `policy` which can be found from `device` alone; not [`Check::ClonedSerial`]
+///
+/// ```
+/// use cyme::profiler::audit::{audit_device, Check, Policy};
+/// use cyme::profiler::read_json_dump;
+///
+/// let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+/// let mut device = spusb.get_node("2-2.8").unwrap().clone();
+/// device.vendor_id = Some(0x16c0);
+/// device.product_id = Some(0x27db);
+/// let findings = audit_device(&device, &Policy::default());
+/// assert_eq!(findings[0].check, Check::KnownBadUsb);
+/// ```
+pub fn audit_device(device: &Device, policy: &Policy) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if policy.is_trusted(device) {
+        return findings;
+    }
+    let keyboard = has_boot_keyboard(device);
+
+    if !policy.allow.contains(&Check::KnownBadUsb) {
+        if let Some(v) = policy.bad_usb.iter().find(|v| v.matches(device)) {
+            findings.push(Finding::new(
+                device,
+                Check::KnownBadUsb,
+                Severity::Error,
+                format!("{} is a BadUSB device in the policy", v),
+            ));
+        } else if let Some((_, _, description)) = BUILTIN_BAD_USB.iter().find(|(vid, pid, _)| {
+            VidPid {
+                vendor_id: *vid,
+                product_id: *pid,
+            }
+            .matches(device)
+        }) {
+            findings.push(Finding::new(
+                device,
+                Check::KnownBadUsb,
+                // only a board until it presents a keyboard
+                if keyboard {
+                    Severity::Error
+                } else {
+                    Severity::Warning
+                },
+                description.to_string(),
+            ));
+        }
+    }
+
+    if keyboard
+        && !policy.keyboard_ports.is_empty()
+        && !policy.allow.contains(&Check::UnexpectedKeyboard)
+        && !policy.is_keyboard_port(&device.port_path())
+    {
+        findings.push(Finding::new(
+            device,
+            Check::UnexpectedKeyboard,
+            Severity::Error,
+            format!(
+                "keyboard is not on an expected port ({})",
+                policy.keyboard_ports.join(", ")
+            ),
+        ));
+    }
+
+    if !policy.allow.contains(&Check::Denied) {
+        if let Some(rule) = policy.denylist.iter().find(|r| r.matches(device)) {
+            findings.push(Finding::new(
+                device,
+                Check::Denied,
+                Severity::Error,
+                format!("matches denylist rule {}", rule),
+            ));
+        }
+    }
+
+    if !policy.allowlist.is_empty()
+        && !policy.allow.contains(&Check::NotAllowed)
+        && !is_only_hub(device)
+        && !policy.allowlist.iter().any(|r| r.matches(device))
+    {
+        findings.push(Finding::new(
+            device,
+            Check::NotAllowed,
+            Severity::Error,
+            String::from("matches no allowlist rule"),
+        ));
+    }
+
+    if !policy.allow.contains(&Check::StorageHid)
+        && device.has_interface_class(&BaseClass::MassStorage)
+        && device.has_interface_class(&BaseClass::Hid)
+    {
+        findings.push(Finding::new(
+            device,
+            Check::StorageHid,
+            if keyboard {
+                Severity::Error
+            } else {
+                Severity::Warning
+            },
+            format!(
+                "mass storage device also has a HID{} interface",
+                if keyboard { " keyboard" } else { "" }
+            ),
+        ));
+    }
+
+    findings
+}
+
+/// Whether `device` declares the hub class and every one of its interfaces is a hub, so it is exempt from [`Check::NotAllowed`]
+///
+/// Not [`Device::is_hub`] which also matches the name and the bDeviceClass is reported by the device, so a device could declare itself a hub whilst exposing a HID interface. A device which could not be opened has no interfaces to check so is not exempt.
+fn is_only_hub(device: &Device) -> bool {
+    let mut interfaces = device
+        .extra
+        .iter()
+        .flat_map(|e| e.configurations.iter())
+        .flat_map(|c| c.interfaces.iter())
+        .peekable();
+    device.class == Some(BaseClass::Hub)
+        && interfaces.peek().is_some()
+        && interfaces.all(|i| i.class == BaseClass::Hub)
+}
+
+/// Findings of [`run`] or [`enforce`] and the devices deauthorized
+#[derive(Debug, Default, PartialEq)]
+pub struct AuditReport {
+    /// Findings of the devices, redacted if a [`Redaction`] was supplied
+    pub findings: Vec<Finding>,
+    /// Number of devices audited
+    pub devices: usize,
+    /// Number of devices which could not be opened so their interfaces were not checked
+    pub unopened: usize,
+    /// Port paths of the devices deauthorized
+    pub deauthorized: Vec<String>,
+    /// Errors of the devices which failed to deauthorize; each is also logged
+    pub failed: Vec<Error>,
+}
+
+impl AuditReport {
+    /// Number of findings with [`Severity::Error`]
+    pub fn errors(&self) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+            .count()
+    }
+
+    /// [`deauthorize`] `device`, adding it to the deauthorized or failed
+    fn deauthorize(&mut self, device: &Device) {
+        match self::deauthorize(device) {
+            Ok(()) => self.deauthorized.push(device.port_path()),
+            Err(e) => {
+                log::error!("Failed to deauthorize {}: {}", device, e);
+                self.failed.push(e);
+            }
+        }
+    }
+}
+
+/// Audit the devices in `spusb` matching `filter` with `policy` for `cyme audit`, redacting the findings with `redaction`
+///
+/// Devices violating the policy are deauthorized if `deauthorize`; every device is attempted so one failing does not leave the rest connected, with failures in [`AuditReport::failed`].
+pub fn run(
+    spusb: &SystemProfile,
+    filter: &Filter,
+    policy: &Policy,
+    deauthorize: bool,
+    redaction: Option<&Redaction>,
+) -> Result<AuditReport> {
+    let devices: Vec<&Device> = spusb
+        .iter()
+        .filter(|d| !d.is_root_hub() && filter.is_match(d))
+        .collect();
+    let findings = audit_devices(&devices, policy);
+    let mut report = AuditReport {
+        devices: devices.len(),
+        unopened: devices.iter().filter(|d| d.extra.is_none()).count(),
+        ..Default::default()
+    };
+
+    if deauthorize {
+        for device in devices.iter().filter(|d| {
+            findings
+                .iter()
+                .any(|f| f.is_policy_violation() && f.port_path == d.port_path())
+        }) {
+            report.deauthorize(device);
+        }
+    }
+
+    report.findings = findings;
+    // after auditing so policy rules and cloned serials match the real serials
+    if let Some(redaction) = redaction {
+        redaction.apply_audit(&mut report.findings, &devices)?;
+    }
+
+    Ok(report)
+}
+
+/// Audit `device` with `policy` for `cyme watch --policy`, redacting the findings with `redaction` and deauthorizing the device if it violates the policy and `deauthorize`
+///
+/// Failing to deauthorize is in [`AuditReport::failed`] rather than returned so watching continues.
+pub fn enforce(
+    device: &Device,
+    policy: &Policy,
+    deauthorize: bool,
+    redaction: Option<&Redaction>,
+) -> Result<AuditReport> {
+    let mut report = AuditReport {
+        findings: audit_device(device, policy),
+        devices: 1,
+        unopened: usize::from(device.extra.is_none()),
+        ..Default::default()
+    };
+    if deauthorize && report.findings.iter().any(|f| f.is_policy_violation()) {
+        report.deauthorize(device);
+    }
+    if let Some(redaction) = redaction {
+        redaction.apply_audit(&mut report.findings, &[device])?;
+    }
+
+    Ok(report)
+}
+
+/// Deauthorize `device` so the kernel disconnects it until authorized again; requires the 'actions' feature and root
+pub fn deauthorize(device: &Device) -> Result<()> {
+    #[cfg(feature = "actions")]
+    {
+        let write = super::sysfs_authorize(&device.sysfs_name(), false)?;
+        log::info!("{}", write);
+        write.apply()
+    }
+    #[cfg(not(feature = "actions"))]
+    {
+        let _ = device;
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "actions feature is required for --deauthorize, install with `cargo install --features actions`",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::read_json_dump;
+
+    /// Virtual Mouse of the dump as a boot keyboard at `port` of bus 1
+    fn keyboard(port: u8) -> Device {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let mut device = spusb.get_node("1-2").unwrap().clone();
+        device.location_id.tree_positions = vec![port];
+        for interface in device
+            .extra
+            .as_mut()
+            .unwrap()
+            .configurations
+            .iter_mut()
+            .flat_map(|c| c.interfaces.iter_mut())
+        {
+            interface.sub_class = 1;
+            interface.protocol = 1;
+        }
+        device
+    }
+
+    #[test]
+    fn test_audit_devices() {
+        let mut storage = keyboard(3);
+        storage.serial_num = Some(String::from("ABC"));
+        let mut interface = storage.extra.as_ref().unwrap().configurations[0].interfaces[0].clone();
+        interface.class = BaseClass::MassStorage;
+        storage.extra.as_mut().unwrap().configurations[0]
+            .interfaces
+            .push(interface);
+        let mut clone = storage.clone();
+        clone.location_id.tree_positions = vec![4];
+        let devices = [keyboard(2), storage, clone];
+        let devices: Vec<&Device> = devices.iter().collect();
+
+        let policy = Policy {
+            keyboard_ports: vec![String::from("1-2"), String::from("1-4")],
+            ..Default::default()
+        };
+        let findings = audit_devices(&devices, &policy);
+        let found = |check: Check, port_path: &str| {
+            findings
+                .iter()
+                .any(|f| f.check == check && f.port_path == port_path)
+        };
+        assert!(!found(Check::UnexpectedKeyboard, "1-2"));
+        assert!(found(Check::UnexpectedKeyboard, "1-3"));
+        assert!(!found(Check::UnexpectedKeyboard, "1-4"));
+        assert!(found(Check::StorageHid, "1-3"));
+        assert!(found(Check::ClonedSerial, "1-3"));
+        assert!(found(Check::ClonedSerial, "1-4"));
+        // only policy errors are violations to deauthorize for
+        assert!(findings
+            .iter()
+            .filter(|f| f.is_policy_violation())
+            .all(|f| f.check == Check::UnexpectedKeyboard));
+
+        let policy = Policy {
+            allow: vec![Check::StorageHid, Check::ClonedSerial],
+            ..Default::default()
+        };
+        assert!(audit_devices(&devices, &policy).is_empty());
+    }
+
+    #[test]
+    fn test_run_audit() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let mut policy = Policy {
+            keyboard_ports: vec![String::from("1-2")],
+            ..Default::default()
+        };
+        let report = run(&spusb, &Filter::default(), &policy, false, None).unwrap();
+        assert_eq!(report.errors(), 0);
+        assert!(report.deauthorized.is_empty());
+
+        policy.bad_usb.push("1d50:6018".parse().unwrap());
+        let report = run(&spusb, &Filter::default(), &policy, false, None).unwrap();
+        assert!(report.errors() > 0);
+        assert!(report.findings.iter().any(|f| f.port_path == "2-2.8"));
+        // only devices matching the filter are audited
+        let filter = Filter {
+            vid: Some(0x1366),
+            ..Default::default()
+        };
+        let report = run(&spusb, &filter, &policy, false, None).unwrap();
+        assert_eq!(report.errors(), 0);
+        assert!(report.devices > 0);
+    }
+
+    #[test]
+    fn test_policy() {
+        let policy: Policy = serde_json::from_str(
+            r#"{"keyboard-ports": ["1-2"], "bad-usb": ["1234:5678", "abcd"], "allow": ["storage-hid"]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            policy.bad_usb,
+            vec![
+                VidPid {
+                    vendor_id: 0x1234,
+                    product_id: Some(0x5678)
+                },
+                VidPid {
+                    vendor_id: 0xabcd,
+                    product_id: None
+                }
+            ]
+        );
+        assert!(policy.is_keyboard_port("1-2.3"));
+        assert!(!policy.is_keyboard_port("1-23"));
+        assert!(serde_json::from_str::<Policy>(r#"{"bad-usb": ["xyz"]}"#).is_err());
+    }
+
+    #[test]
+    fn test_allowlist_denylist() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let policy: Policy = serde_json::from_str(
+            r#"{"allowlist": [{"class": "hid"}, {"vidpid": "1d50:6018", "serial": "97B6*"}], "denylist": [{"vidpid": "1366"}]}"#,
+        )
+        .unwrap();
+        let findings = audit(&spusb, &policy);
+        let checks = |port_path: &str| -> Vec<Check> {
+            findings
+                .iter()
+                .filter(|f| f.port_path == port_path)
+                .map(|f| f.check)
+                .collect()
+        };
+        // hid mouse and probe with matching serial are allowed, the hub is exempt
+        assert!(checks("1-2").is_empty());
+        assert!(checks("2-2.8").is_empty());
+        assert!(checks("2-2").is_empty());
+        assert_eq!(checks("1-6"), vec![Check::NotAllowed]);
+        assert_eq!(checks("2-2.1"), vec![Check::Denied, Check::NotAllowed]);
+
+        // a device declaring the hub class is not exempt if it has other interfaces or they are unknown
+        let mut device = spusb.get_node("1-6").unwrap().clone();
+        device.class = Some(BaseClass::Hub);
+        let policy: Policy =
+            serde_json::from_str(r#"{"allowlist": [{"vidpid": "1d50"}]}"#).unwrap();
+        assert_eq!(
+            audit_device(&device, &policy)
+                .iter()
+                .map(|f| f.check)
+                .collect::<Vec<_>>(),
+            vec![Check::NotAllowed]
+        );
+        device.extra = None;
+        assert_eq!(audit_device(&device, &policy).len(), 1);
+        let hub = spusb.get_node("2-2").unwrap();
+        assert!(audit_device(hub, &policy).is_empty());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("LAB-*", "LAB-0042"));
+        assert!(glob_match("*42", "LAB-0042"));
+        assert!(glob_match("L?B-*4*", "LAB-0042"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("LAB-?", "LAB-0042"));
+        assert!(!glob_match("lab-*", "LAB-0042"));
+    }
+}
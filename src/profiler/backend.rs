@@ -0,0 +1,219 @@
+//! USB libraries used to profile devices; see [`Backend`]
+//!
+//! The 'nusb' and 'libusb' features each compile in a backend. When both are, nusb is the default and libusb can be selected at runtime with [`ProfilerBuilder::backend`] or `cyme --backend libusb`. This allows packagers to ship one binary and users to work around a bug in one of the libraries.
+//!
+//! ```no_run
+//! use cyme::profiler::backend::BackendKind;
+//! use cyme::profiler::ProfilerBuilder;
+//!
+//! let spusb = ProfilerBuilder::new()
+//!     .backend(BackendKind::Libusb)
+//!     .profile()
+//!     .unwrap();
+//! println!("{:#}", spusb);
+//! ```
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::{Device, DeviceLocation, DeviceSelector, ProfilerBuilder, SystemProfile};
+use crate::error::{Error, ErrorKind, Result};
+
+/// A USB library which can profile the system and open devices
+pub trait Backend: Sync {
+    /// Which backend this is
+    fn kind(&self) -> BackendKind;
+
+    /// Build the [`SystemProfile`] with `options`; see [`ProfilerBuilder::profile`]
+    fn profile(&self, options: ProfilerBuilder) -> Result<SystemProfile>;
+
+    /// Iterate [`Device`]s with `options`, profiling each as it is reached; see [`ProfilerBuilder::devices_iter`]
+    fn devices_iter(&self, options: ProfilerBuilder) -> Result<Box<dyn Iterator<Item = Device>>>;
+
+    /// Profile only the [`Device`] matching `selector` with `options`
+    fn get_device(&self, options: ProfilerBuilder, selector: &DeviceSelector) -> Result<Device>;
+
+    /// Add the extra data of devices in `spusb`, such as one from macOS system_profiler
+    fn fill_spusb(&self, spusb: &mut SystemProfile) -> Result<()>;
+
+    /// Read the raw descriptors of the device at `location` as file name and bytes; see [`super::export`]
+    fn read_descriptors(&self, location: &DeviceLocation) -> Result<Vec<(String, Vec<u8>)>>;
+
+    /// Issue a USB port reset to the device at `location`
+    #[cfg(feature = "actions")]
+    fn reset_device(&self, location: &DeviceLocation) -> Result<()>;
+}
+
+/// Kind of [`Backend`], which may not be compiled in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// Pure Rust nusb; 'nusb' feature
+    Nusb,
+    /// libusb C library with rusb bindings; 'libusb' feature
+    Libusb,
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendKind::Nusb => write!(f, "nusb"),
+            BackendKind::Libusb => write!(f, "libusb"),
+        }
+    }
+}
+
+impl BackendKind {
+    /// All kinds in order of preference
+    pub const ALL: [BackendKind; 2] = [BackendKind::Nusb, BackendKind::Libusb];
+
+    /// Whether the backend is compiled in
+    pub fn is_available(&self) -> bool {
+        match self {
+            BackendKind::Nusb => cfg!(feature = "nusb"),
+            BackendKind::Libusb => cfg!(feature = "libusb"),
+        }
+    }
+
+    /// The [`Backend`]; error if it is not compiled in
+    pub fn backend(&self) -> Result<&'static dyn Backend> {
+        match self {
+            #[cfg(feature = "nusb")]
+            BackendKind::Nusb => Ok(&Nusb),
+            #[cfg(feature = "libusb")]
+            BackendKind::Libusb => Ok(&LibUsb),
+            #[allow(unreachable_patterns)]
+            kind => Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "{} backend is not compiled in, install with `cargo install --features {}`",
+                    kind, kind
+                ),
+            )),
+        }
+    }
+}
+
+/// Backends compiled in, in order of preference
+pub fn available() -> Vec<BackendKind> {
+    BackendKind::ALL
+        .into_iter()
+        .filter(|k| k.is_available())
+        .collect()
+}
+
+/// Kind of [`default_backend`], the first [`available`]
+pub fn default_kind() -> Option<BackendKind> {
+    available().first().copied()
+}
+
+/// The [`Backend`] used when none is selected; error if neither 'nusb' or 'libusb' are compiled in
+pub fn default_backend() -> Result<&'static dyn Backend> {
+    match default_kind() {
+        Some(kind) => kind.backend(),
+        None => Err(Error::new(
+            ErrorKind::Unsupported,
+            "nusb or libusb feature is required to do this, install with `cargo install --features nusb/libusb`",
+        )),
+    }
+}
+
+#[cfg(feature = "nusb")]
+struct Nusb;
+
+#[cfg(feature = "nusb")]
+impl Backend for Nusb {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Nusb
+    }
+
+    fn profile(&self, options: ProfilerBuilder) -> Result<SystemProfile> {
+        use super::Profiler;
+        let with_extra = options.with_extra;
+        let mut profiler = super::nusb::NusbProfiler::with_options(options);
+        profiler.get_spusb(with_extra)
+    }
+
+    fn devices_iter(&self, options: ProfilerBuilder) -> Result<Box<dyn Iterator<Item = Device>>> {
+        Ok(Box::new(super::nusb::devices_iter(options)?))
+    }
+
+    fn get_device(&self, options: ProfilerBuilder, selector: &DeviceSelector) -> Result<Device> {
+        super::nusb::get_device(options, selector)
+    }
+
+    fn fill_spusb(&self, spusb: &mut SystemProfile) -> Result<()> {
+        super::nusb::fill_spusb(spusb)
+    }
+
+    fn read_descriptors(&self, location: &DeviceLocation) -> Result<Vec<(String, Vec<u8>)>> {
+        super::nusb::open_device(location).and_then(|d| super::export::read_descriptors(&d))
+    }
+
+    #[cfg(feature = "actions")]
+    fn reset_device(&self, location: &DeviceLocation) -> Result<()> {
+        super::nusb::reset_device(location)
+    }
+}
+
+#[cfg(feature = "libusb")]
+struct LibUsb;
+
+#[cfg(feature = "libusb")]
+impl Backend for LibUsb {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Libusb
+    }
+
+    fn profile(&self, options: ProfilerBuilder) -> Result<SystemProfile> {
+        use super::libusb::{LibUsbProfiler, UsbDevice};
+        let with_extra = options.with_extra;
+        let mut profiler = LibUsbProfiler::new(options)?;
+        <LibUsbProfiler as super::Profiler<UsbDevice<rusb::Context>>>::get_spusb(
+            &mut profiler,
+            with_extra,
+        )
+    }
+
+    fn devices_iter(&self, options: ProfilerBuilder) -> Result<Box<dyn Iterator<Item = Device>>> {
+        Ok(Box::new(super::libusb::devices_iter(options)?))
+    }
+
+    fn get_device(&self, options: ProfilerBuilder, selector: &DeviceSelector) -> Result<Device> {
+        super::libusb::get_device(options, selector)
+    }
+
+    fn fill_spusb(&self, spusb: &mut SystemProfile) -> Result<()> {
+        super::libusb::fill_spusb(spusb)
+    }
+
+    fn read_descriptors(&self, location: &DeviceLocation) -> Result<Vec<(String, Vec<u8>)>> {
+        super::libusb::open_device(location).and_then(|d| super::export::read_descriptors(&d))
+    }
+
+    #[cfg(feature = "actions")]
+    fn reset_device(&self, location: &DeviceLocation) -> Result<()> {
+        super::libusb::reset_device(location)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_available() {
+        for kind in BackendKind::ALL {
+            assert_eq!(kind.backend().is_ok(), kind.is_available());
+            assert_eq!(
+                kind.backend().map(|b| b.kind()).ok(),
+                Some(kind).filter(|k| k.is_available())
+            );
+            assert_eq!(
+                serde_json::to_string(&kind).unwrap(),
+                format!("\"{}\"", kind)
+            );
+        }
+        assert_eq!(default_kind(), available().first().copied());
+    }
+}
@@ -0,0 +1,225 @@
+//! Estimates of the bus bandwidth reserved by the periodic (interrupt and isochronous) endpoints of devices; see [`bandwidth`]
+//!
+//! Hosts reserve bandwidth for the periodic endpoints of an interface when its alternate setting is selected and refuse it once the bus is at the limit, the 'not enough bandwidth' of cameras and audio interfaces sharing a bus. The alternate setting in use is not known so the most demanding of each interface is used, the reservation when streaming. Estimates are of payload only, excluding protocol overhead and the split transactions of full and low speed devices behind high speed hubs.
+//!
+//! Requires the [`crate::usb::DeviceExtra`] so devices must be profiled with extra.
+//!
+//! ```
+//! use cyme::profiler::bandwidth::bandwidth;
+//! use cyme::profiler::read_json_dump;
+//!
+//! let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+//! for bus in bandwidth(&spusb) {
+//!     println!("{:?} {} B/s {:?}%", bus.bus_number, bus.bytes_per_second, bus.percent);
+//! }
+//! ```
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::{Bus, Device, SystemProfile};
+use crate::usb::{Interface, Speed, TransferType};
+
+/// Reservation of a periodic endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EndpointBandwidth {
+    /// Interface number
+    pub interface: u8,
+    /// Alternate setting of the interface
+    pub alt_setting: u8,
+    /// Endpoint address
+    pub address: u8,
+    /// Interrupt or isochronous
+    pub transfer_type: TransferType,
+    /// Microseconds between each service
+    pub interval_us: u32,
+    /// Bytes transferred each service
+    pub bytes_per_interval: u32,
+    /// Bytes per second reserved
+    pub bytes_per_second: u64,
+}
+
+/// Periodic endpoint reservations of a device at the speed it is connected
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeviceBandwidth {
+    /// Port path of the device
+    pub port_path: String,
+    /// Name of the device
+    pub name: String,
+    /// Speed the device is connected at, such as 'high_speed'
+    pub speed: String,
+    /// Bytes per second reserved by all endpoints
+    pub bytes_per_second: u64,
+    /// Percentage of the [`Speed::periodic_capacity`] of the device
+    pub percent: Option<f32>,
+    /// Endpoints of the most demanding alternate setting of each interface in the active configuration
+    pub endpoints: Vec<EndpointBandwidth>,
+}
+
+/// Total periodic endpoint reservations of the devices on a bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusBandwidth {
+    /// Bus number
+    pub bus_number: Option<u8>,
+    /// Name of the bus
+    pub name: String,
+    /// Fastest speed of the devices on the bus, used for the capacity
+    pub speed: Option<String>,
+    /// Bytes per second that can be reserved for periodic endpoints at `speed`
+    pub capacity: Option<u64>,
+    /// Bytes per second reserved by all devices
+    pub bytes_per_second: u64,
+    /// Percentage of `capacity` reserved
+    pub percent: Option<f32>,
+    /// Devices with periodic endpoints
+    pub devices: Vec<DeviceBandwidth>,
+    /// Number of devices which could not be estimated because they were not opened for their descriptors or their speed is unknown
+    pub unknown: usize,
+}
+
+/// Estimate the reservations of each bus in `spusb`
+pub fn bandwidth(spusb: &SystemProfile) -> Vec<BusBandwidth> {
+    spusb.buses.iter().map(bus_bandwidth).collect()
+}
+
+/// Estimate the reservations of the devices on `bus`
+pub fn bus_bandwidth(bus: &Bus) -> BusBandwidth {
+    let flattened = bus.flattened_devices();
+    let estimates: Vec<Option<DeviceBandwidth>> =
+        flattened.iter().map(|d| device_bandwidth(d)).collect();
+    let unknown = estimates.iter().filter(|e| e.is_none()).count();
+    let devices: Vec<DeviceBandwidth> = estimates
+        .into_iter()
+        .flatten()
+        .filter(|d| !d.endpoints.is_empty())
+        .collect();
+    let speed = flattened
+        .iter()
+        .filter_map(|d| d.negotiated_speed())
+        .reduce(|a, b| if b > a { b } else { a });
+    let capacity = speed.as_ref().and_then(Speed::periodic_capacity);
+    let bytes_per_second = devices.iter().map(|d| d.bytes_per_second).sum();
+
+    BusBandwidth {
+        bus_number: bus.get_bus_number(),
+        name: bus.name.to_owned(),
+        speed: speed.map(|s| s.to_string()),
+        capacity,
+        bytes_per_second,
+        percent: capacity.map(|c| percent(bytes_per_second, c)),
+        devices,
+        unknown,
+    }
+}
+
+/// Estimate the reservations of `device`, not including devices connected to it
+///
+/// `None` if the device has no [`crate::usb::DeviceExtra`] or its speed is not known.
+///
+/// ```
+/// use cyme::profiler::bandwidth::device_bandwidth;
+/// use cyme::profiler::read_json_dump;
+///
+/// let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+/// let estimate = device_bandwidth(spusb.get_node("2-2.8").unwrap()).unwrap();
+/// assert!(estimate.endpoints.iter().all(|e| e.bytes_per_second > 0));
+/// ```
+pub fn device_bandwidth(device: &Device) -> Option<DeviceBandwidth> {
+    let speed = device.negotiated_speed()?;
+    let extra = device.extra.as_ref()?;
+    let endpoints = match extra
+        .configurations
+        .iter()
+        .find(|c| c.active)
+        .or(extra.configurations.first())
+    {
+        Some(config) => {
+            // alternate settings of each interface number, only one of which can be selected
+            let mut interfaces: BTreeMap<u8, Vec<&Interface>> = BTreeMap::new();
+            for interface in config.interfaces.iter() {
+                interfaces
+                    .entry(interface.number)
+                    .or_default()
+                    .push(interface);
+            }
+            interfaces
+                .into_values()
+                .filter_map(|alts| {
+                    alts.into_iter()
+                        .map(|i| interface_bandwidth(i, &speed))
+                        .max_by_key(|eps| eps.iter().map(|e| e.bytes_per_second).sum::<u64>())
+                })
+                .flatten()
+                .collect()
+        }
+        None => Vec::new(),
+    };
+    let bytes_per_second = endpoints.iter().map(|e| e.bytes_per_second).sum();
+    let percent = speed
+        .periodic_capacity()
+        .map(|c| percent(bytes_per_second, c));
+
+    Some(DeviceBandwidth {
+        port_path: device.port_path(),
+        name: device.name.to_owned(),
+        speed: speed.to_string(),
+        bytes_per_second,
+        percent,
+        endpoints,
+    })
+}
+
+/// Reservations of the periodic endpoints of `interface` at `speed`
+fn interface_bandwidth(interface: &Interface, speed: &Speed) -> Vec<EndpointBandwidth> {
+    interface
+        .endpoints
+        .iter()
+        .filter_map(|endpoint| {
+            Some(EndpointBandwidth {
+                interface: interface.number,
+                alt_setting: interface.alt_setting,
+                address: endpoint.address.address,
+                transfer_type: endpoint.transfer_type.to_owned(),
+                interval_us: endpoint.service_interval_us(speed)?,
+                bytes_per_interval: endpoint.bytes_per_interval(speed),
+                bytes_per_second: endpoint.periodic_bandwidth(speed)?,
+            })
+        })
+        .collect()
+}
+
+fn percent(bytes_per_second: u64, capacity: u64) -> f32 {
+    if capacity == 0 {
+        0.0
+    } else {
+        bytes_per_second as f32 * 100.0 / capacity as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bus_bandwidth() {
+        let spusb =
+            super::super::read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let buses = bandwidth(&spusb);
+        assert_eq!(buses.len(), spusb.buses.len());
+        for bus in buses {
+            assert_eq!(
+                bus.bytes_per_second,
+                bus.devices.iter().map(|d| d.bytes_per_second).sum::<u64>()
+            );
+            // one alternate setting per interface
+            for device in bus.devices {
+                let mut seen = std::collections::HashMap::new();
+                for e in device.endpoints {
+                    assert_eq!(
+                        *seen.entry(e.interface).or_insert(e.alt_setting),
+                        e.alt_setting
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Built-in vendor details for common USB-UART bridges: FTDI, Silicon Labs CP210x and WCH CH34x.
+//!
+//! These chips do not have class descriptors to decode so the details are taken from the bcdDevice and vendor control requests while the device is open for extra data. They are added to [`DeviceExtra`](crate::usb::DeviceExtra) as [`VendorField`]s.
+use super::{ControlRequest, ControlType, Recipient, UsbOperations};
+use crate::usb::vendor::VendorField;
+
+/// FTDI Future Technology Devices International
+pub const FTDI_VID: u16 = 0x0403;
+/// Silicon Labs
+pub const SILABS_VID: u16 = 0x10c4;
+/// WCH Nanjing Qinheng Microelectronics
+pub const WCH_VID: u16 = 0x1a86;
+/// Silicon Labs product IDs of CP210x bridges; other Silicon Labs devices do not support the part number request
+pub const CP210X_PIDS: [u16; 5] = [0xea60, 0xea61, 0xea63, 0xea70, 0xea71];
+
+/// FTDI SIO_READ_EEPROM_REQUEST
+const FTDI_READ_EEPROM: u8 = 0x90;
+/// CP210X_VENDOR_SPECIFIC with CP210X_GET_PARTNUM value
+const CP210X_VENDOR_SPECIFIC: u8 = 0xff;
+const CP210X_GET_PARTNUM: u16 = 0x370b;
+/// CH341_REQ_READ_VERSION
+const CH34X_READ_VERSION: u8 = 0x5f;
+
+/// FTDI chip from the bcdDevice, which FTDI set based on the chip rather than firmware release
+///
+/// ```
+/// use cyme::profiler::bridges::ftdi_chip;
+///
+/// assert_eq!(ftdi_chip(0x0600), Some("FT232R/FT245R"));
+/// assert_eq!(ftdi_chip(0x0900), Some("FT232H"));
+/// assert_eq!(ftdi_chip(0x1234), None);
+/// ```
+pub fn ftdi_chip(bcd_device: u16) -> Option<&'static str> {
+    match bcd_device {
+        0x0200 => Some("FT232AM"),
+        0x0400 => Some("FT232BM/FT245BM"),
+        0x0500 => Some("FT2232C/D"),
+        0x0600 => Some("FT232R/FT245R"),
+        0x0700 => Some("FT2232H"),
+        0x0800 => Some("FT4232H"),
+        0x0900 => Some("FT232H"),
+        0x1000 => Some("FT-X"),
+        _ => None,
+    }
+}
+
+fn ftdi_channel_type(t: u8) -> &'static str {
+    match t {
+        0x0 => "UART",
+        0x1 => "FIFO",
+        0x2 => "OPTO",
+        0x4 => "CPU",
+        0x8 => "FT1284",
+        _ => "unknown",
+    }
+}
+
+fn ftdi_channel(t: Option<u8>, vcp: bool) -> String {
+    format!(
+        "{} ({})",
+        ftdi_channel_type(t.unwrap_or(0)),
+        if vcp { "VCP" } else { "D2XX" }
+    )
+}
+
+/// Decode the channel configuration from the first EEPROM word of multi-channel and H series FTDI chips
+///
+/// ```
+/// use cyme::profiler::bridges::ftdi_channel_fields;
+///
+/// let fields = ftdi_channel_fields(0x0700, [0x08, 0x01]);
+/// assert_eq!(fields[0].to_string(), "channel A: UART (VCP)");
+/// assert_eq!(fields[1].to_string(), "channel B: FIFO (D2XX)");
+/// ```
+pub fn ftdi_channel_fields(bcd_device: u16, word: [u8; 2]) -> Vec<VendorField> {
+    let channels: Vec<(char, Option<u8>, bool)> = match bcd_device {
+        // FT2232C/D and FT2232H
+        0x0500 | 0x0700 => vec![
+            ('A', Some(word[0] & 0x07), word[0] & 0x08 != 0),
+            ('B', Some(word[1] & 0x07), word[1] & 0x08 != 0),
+        ],
+        // FT4232H channels are UART only
+        0x0800 => vec![
+            ('A', None, word[0] & 0x08 != 0),
+            ('B', None, word[1] & 0x08 != 0),
+            ('C', None, word[0] & 0x80 != 0),
+            ('D', None, word[1] & 0x80 != 0),
+        ],
+        // FT232H
+        0x0900 => vec![('A', Some(word[0] & 0x0f), word[0] & 0x10 != 0)],
+        _ => vec![],
+    };
+
+    channels
+        .into_iter()
+        .map(|(c, t, vcp)| VendorField::new(format!("channel {}", c), ftdi_channel(t, vcp)))
+        .collect()
+}
+
+/// CP210x part from the CP210X_GET_PARTNUM vendor request
+///
+/// ```
+/// use cyme::profiler::bridges::cp210x_part;
+///
+/// assert_eq!(cp210x_part(0x02), Some("CP2102"));
+/// assert_eq!(cp210x_part(0x20), Some("CP2102N (QFN28)"));
+/// ```
+pub fn cp210x_part(part_number: u8) -> Option<&'static str> {
+    match part_number {
+        0x01 => Some("CP2101"),
+        0x02 => Some("CP2102"),
+        0x03 => Some("CP2103"),
+        0x04 => Some("CP2104"),
+        0x05 => Some("CP2105"),
+        0x08 => Some("CP2108"),
+        0x20 => Some("CP2102N (QFN28)"),
+        0x21 => Some("CP2102N (QFN24)"),
+        0x22 => Some("CP2102N (QFN20)"),
+        _ => None,
+    }
+}
+
+/// CH34x variant from the product ID
+///
+/// ```
+/// use cyme::profiler::bridges::ch34x_variant;
+///
+/// assert_eq!(ch34x_variant(0x7523), Some("CH340"));
+/// assert_eq!(ch34x_variant(0x55d4), Some("CH9102"));
+/// ```
+pub fn ch34x_variant(product_id: u16) -> Option<&'static str> {
+    match product_id {
+        0x7523 => Some("CH340"),
+        0x7522 => Some("CH340K"),
+        0x5523 => Some("CH341"),
+        0x55d2 => Some("CH342"),
+        0x55d3 => Some("CH343"),
+        0x55d4 => Some("CH9102"),
+        0x55d5 => Some("CH344"),
+        0x55d8 => Some("CH9101"),
+        _ => None,
+    }
+}
+
+fn vendor_in(request: u8, value: u16, index: u16, length: usize) -> ControlRequest {
+    ControlRequest {
+        control_type: ControlType::Vendor,
+        recipient: Recipient::Device,
+        request,
+        value,
+        index,
+        length,
+        claim_interface: false,
+    }
+}
+
+/// Get the vendor details of a USB-UART bridge using vendor requests where required; `None` if not a known bridge
+pub(crate) fn get_bridge_fields<T: UsbOperations + std::fmt::Debug>(
+    device: &T,
+    vendor_id: u16,
+    product_id: u16,
+    bcd_device: u16,
+) -> Option<Vec<VendorField>> {
+    let mut fields = Vec::new();
+
+    match vendor_id {
+        FTDI_VID => {
+            fields.push(VendorField::new("chip", ftdi_chip(bcd_device)?));
+            match device.get_control_msg(vendor_in(FTDI_READ_EEPROM, 0, 0, 2)) {
+                Ok(word) => fields.extend(ftdi_channel_fields(bcd_device, [word[0], word[1]])),
+                Err(e) => log::debug!("{:?} Failed to read FTDI EEPROM: {}", device, e),
+            }
+        }
+        SILABS_VID if CP210X_PIDS.contains(&product_id) => {
+            match device.get_control_msg(vendor_in(
+                CP210X_VENDOR_SPECIFIC,
+                CP210X_GET_PARTNUM,
+                0,
+                1,
+            )) {
+                Ok(data) => fields.push(VendorField::new(
+                    "part",
+                    cp210x_part(data[0])
+                        .map(|p| p.to_string())
+                        .unwrap_or_else(|| format!("unknown ({:#04x})", data[0])),
+                )),
+                Err(e) => {
+                    log::debug!("{:?} Failed to read CP210x part number: {}", device, e);
+                    return None;
+                }
+            }
+        }
+        WCH_VID => {
+            fields.push(VendorField::new("variant", ch34x_variant(product_id)?));
+            match device.get_control_msg(vendor_in(CH34X_READ_VERSION, 0, 0, 2)) {
+                Ok(data) => fields.push(VendorField::new("version", format!("{:#04x}", data[0]))),
+                Err(e) => log::debug!("{:?} Failed to read CH34x version: {}", device, e),
+            }
+        }
+        _ => return None,
+    }
+
+    Some(fields)
+}
@@ -0,0 +1,282 @@
+//! On-disk cache of the last [`SystemProfile`] so that repeated invocations do not need to open devices again.
+//!
+//! The cache is used if it is younger than a time-to-live, has the [`crate::usb::DeviceExtra`] required and the devices connected have not changed. Changes are detected with a fingerprint of the device locations, addresses and IDs, which is cheap to get since the devices are not opened; a device being re-plugged will change address so also invalidates the cache. The fingerprint rather than hotplug events is the check since nothing may be watching between invocations, but `cyme watch` also removes the cache with [`invalidate`] when a device connects or disconnects.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use cyme::profiler::{self, cache};
+//!
+//! let path = cache::default_path().unwrap();
+//! let spusb = cache::get_or_profile(&path, Duration::from_secs(60), true, &[], || {
+//!     profiler::get_spusb_with_extra()
+//! })
+//! .unwrap();
+//! ```
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::{ProfilerBuilder, SystemProfile};
+use crate::error::{Error, ErrorKind, Result};
+use crate::usb::BaseClass;
+
+/// Name of the cache file within the cyme cache directory
+const CACHE_FILE: &str = "profile.json";
+
+/// Cached [`SystemProfile`] with the data required to check it is still valid
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProfileCache {
+    /// Unix time in seconds when the profile was cached
+    pub created: u64,
+    /// Whether the profile includes [`crate::usb::DeviceExtra`]
+    pub with_extra: bool,
+    /// Base classes of devices not opened for [`crate::usb::DeviceExtra`], as [`ProfilerBuilder::no_open_classes`]
+    #[serde(default)]
+    pub no_open_classes: Vec<BaseClass>,
+    /// Fingerprint of the connected devices when the profile was cached
+    pub fingerprint: String,
+    /// The cached profile
+    pub profile: SystemProfile,
+}
+
+impl ProfileCache {
+    /// New cache of `profile` created now with the current device fingerprint
+    pub fn new(
+        profile: SystemProfile,
+        with_extra: bool,
+        no_open_classes: &[BaseClass],
+    ) -> Result<Self> {
+        Ok(ProfileCache {
+            created: now(),
+            with_extra,
+            no_open_classes: no_open_classes.to_vec(),
+            fingerprint: fingerprint()?,
+            profile,
+        })
+    }
+
+    /// Read the cache from `path`
+    pub fn read(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)?;
+        serde_json::from_str(&data).map_err(|e| {
+            Error::new(
+                ErrorKind::Parsing,
+                &format!("Failed to parse cache at {:?}; Error({})", path, e),
+            )
+        })
+    }
+
+    /// Write the cache to `path`, creating parent directories if required
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Whether the cache is no older than `ttl`
+    pub fn is_fresh(&self, ttl: Duration) -> bool {
+        now().saturating_sub(self.created) <= ttl.as_secs()
+    }
+
+    /// Whether the cache is fresh, has extra data if `with_extra` for all but `no_open_classes` and the connected devices match the fingerprint
+    pub fn is_valid(&self, ttl: Duration, with_extra: bool, no_open_classes: &[BaseClass]) -> bool {
+        fingerprint().is_ok_and(|f| self.is_valid_for(ttl, with_extra, no_open_classes, &f))
+    }
+
+    /// [`ProfileCache::is_valid`] with the `fingerprint` of the connected devices already taken
+    ///
+    /// A cache with extra data is valid when extra is not required, so it is only upgraded and never downgraded. When extra is required, every class not opened for the cache must also not be opened for the request, otherwise those devices would be missing extra.
+    pub fn is_valid_for(
+        &self,
+        ttl: Duration,
+        with_extra: bool,
+        no_open_classes: &[BaseClass],
+        fingerprint: &str,
+    ) -> bool {
+        self.is_fresh(ttl)
+            && self.has_extra(with_extra, no_open_classes)
+            && self.fingerprint == fingerprint
+    }
+
+    fn has_extra(&self, with_extra: bool, no_open_classes: &[BaseClass]) -> bool {
+        !with_extra
+            || (self.with_extra
+                && self
+                    .no_open_classes
+                    .iter()
+                    .all(|c| no_open_classes.contains(c)))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Default cache path in the user cache directory: '$XDG_CACHE_HOME/cyme/profile.json' on Linux
+///
+/// `None` without the 'config' feature so a cache path must be supplied.
+pub fn default_path() -> Option<PathBuf> {
+    #[cfg(feature = "config")]
+    return dirs::cache_dir().map(|d| d.join("cyme").join(CACHE_FILE));
+    #[cfg(not(feature = "config"))]
+    return None;
+}
+
+/// Remove the cache at `path` so the next [`get_or_profile`] profiles again, such as when a device connects; a cache which does not exist is not an error
+pub fn invalidate(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Fingerprint of the connected devices from their port path, address and VID:PID, without opening them
+pub fn fingerprint() -> Result<String> {
+    let mut devices: Vec<String> = ProfilerBuilder::new()
+        .strings(false)
+        .ids(false)
+        .udev(false)
+        .devices_iter()?
+        .map(|d| {
+            format!(
+                "{}/{}:{:04x}:{:04x}",
+                d.port_path(),
+                d.location_id.number,
+                d.vendor_id.unwrap_or(0),
+                d.product_id.unwrap_or(0)
+            )
+        })
+        .collect();
+    devices.sort();
+    Ok(devices.join(","))
+}
+
+/// Get the [`SystemProfile`] from the cache at `path` if valid, otherwise run `profile` and update the cache
+///
+/// `no_open_classes` are the base classes `profile` does not open for extra, as [`ProfilerBuilder::no_open_classes`].
+///
+/// Failure to read or write the cache is not an error; the profile is returned regardless.
+pub fn get_or_profile<F>(
+    path: &Path,
+    ttl: Duration,
+    with_extra: bool,
+    no_open_classes: &[BaseClass],
+    profile: F,
+) -> Result<SystemProfile>
+where
+    F: FnOnce() -> Result<SystemProfile>,
+{
+    match ProfileCache::read(path) {
+        Ok(cache) if cache.is_valid(ttl, with_extra, no_open_classes) => {
+            log::info!("Using cached profile at {:?}", path);
+            return Ok(cache.profile);
+        }
+        Ok(_) => log::info!("Cached profile at {:?} is stale", path),
+        Err(e) => log::info!("No cached profile at {:?}: {}", path, e),
+    }
+
+    // taken before profiling so that any change during is caught next time
+    let fingerprint = fingerprint();
+    let spusb = profile()?;
+    let cache = match fingerprint {
+        Ok(fingerprint) => ProfileCache {
+            created: now(),
+            with_extra,
+            no_open_classes: no_open_classes.to_vec(),
+            fingerprint,
+            profile: spusb,
+        },
+        Err(e) => {
+            log::warn!("Failed to fingerprint devices, not caching profile: {}", e);
+            return Ok(spusb);
+        }
+    };
+    if let Err(e) = cache.write(path) {
+        log::warn!("Failed to write profile cache to {:?}: {}", path, e);
+    }
+
+    Ok(cache.profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::read_json_dump;
+
+    const FINGERPRINT: &str = "2-2/3:0bda:5411,2-2.8/24:1d50:6018";
+
+    fn cache(created: u64, with_extra: bool) -> ProfileCache {
+        ProfileCache {
+            created,
+            with_extra,
+            no_open_classes: Vec::new(),
+            fingerprint: String::from(FINGERPRINT),
+            profile: read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_write_read() {
+        let dir = std::env::temp_dir().join(format!("cyme-cache-{}", std::process::id()));
+        let path = dir.join("nested").join(CACHE_FILE);
+        let written = cache(now(), true);
+        written.write(&path).unwrap();
+
+        let read = ProfileCache::read(&path).unwrap();
+        assert_eq!(read.created, written.created);
+        assert!(read.with_extra);
+        assert_eq!(read.fingerprint, FINGERPRINT);
+        assert_eq!(read.profile.len(), written.profile.len());
+
+        invalidate(&path).unwrap();
+        assert!(ProfileCache::read(&path).is_err());
+        // already removed
+        invalidate(&path).unwrap();
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_is_valid_for() {
+        let ttl = Duration::from_secs(60);
+        let fresh = cache(now(), false);
+        assert!(fresh.is_valid_for(ttl, false, &[], FINGERPRINT));
+        // device connected or re-plugged
+        assert!(!fresh.is_valid_for(ttl, false, &[], "2-2/3:0bda:5411"));
+        // extra required but not cached
+        assert!(!fresh.is_valid_for(ttl, true, &[], FINGERPRINT));
+
+        let expired = cache(now() - 61, false);
+        assert!(!expired.is_fresh(ttl));
+        assert!(!expired.is_valid_for(ttl, false, &[], FINGERPRINT));
+        assert!(expired.is_valid_for(Duration::from_secs(120), false, &[], FINGERPRINT));
+    }
+
+    #[test]
+    fn test_with_extra_upgrade() {
+        let ttl = Duration::from_secs(60);
+        // a cache with extra can be used without
+        let extra = cache(now(), true);
+        assert!(extra.is_valid_for(ttl, false, &[], FINGERPRINT));
+        assert!(extra.is_valid_for(ttl, true, &[], FINGERPRINT));
+    }
+
+    #[test]
+    fn test_no_open_classes() {
+        let ttl = Duration::from_secs(60);
+        let mut skipped = cache(now(), true);
+        skipped.no_open_classes = vec![BaseClass::Hid];
+        // hid devices missing extra
+        assert!(!skipped.is_valid_for(ttl, true, &[], FINGERPRINT));
+        assert!(skipped.is_valid_for(ttl, true, &[BaseClass::Hid], FINGERPRINT));
+        assert!(skipped.is_valid_for(ttl, false, &[], FINGERPRINT));
+        // skipping more than cached is fine
+        let all = cache(now(), true);
+        assert!(all.is_valid_for(ttl, true, &[BaseClass::Hid], FINGERPRINT));
+    }
+}
@@ -0,0 +1,454 @@
+//! Data sources available to the profiler and the fields missing without them; see [`Capabilities`]
+//!
+//! Profiling degrades rather than failing when a source is not available, such as udev in a container or a build without a USB backend. [`Capabilities::detect`] reports which are available so that the missing data can be explained with [`Hint`]s or `cyme --capabilities`.
+//!
+//! ```
+//! use cyme::profiler::capabilities::Capabilities;
+//!
+//! let capabilities = Capabilities::detect();
+//! for hint in capabilities.hints() {
+//!     eprintln!("{}", hint);
+//! }
+//! println!("{}", capabilities);
+//! ```
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+use super::backend::BackendKind;
+use super::{Device, ProfilerBuilder, SystemProfile};
+
+/// A source of data for the profiler
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataSource {
+    /// nusb USB backend
+    Nusb,
+    /// libusb USB backend
+    Libusb,
+    /// Linux sysfs /sys/bus/usb
+    Sysfs,
+    /// Linux udev, used when sysfs does not have the data
+    Udev,
+    /// macOS system_profiler command
+    SystemProfiler,
+    /// Built in USB IDs database
+    UsbIds,
+}
+
+impl fmt::Display for DataSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataSource::Nusb => write!(f, "nusb"),
+            DataSource::Libusb => write!(f, "libusb"),
+            DataSource::Sysfs => write!(f, "sysfs"),
+            DataSource::Udev => write!(f, "udev"),
+            DataSource::SystemProfiler => write!(f, "system_profiler"),
+            DataSource::UsbIds => write!(f, "usb-ids"),
+        }
+    }
+}
+
+impl DataSource {
+    /// All sources, including those not used on this platform
+    pub const ALL: [DataSource; 6] = [
+        DataSource::Nusb,
+        DataSource::Libusb,
+        DataSource::Sysfs,
+        DataSource::Udev,
+        DataSource::SystemProfiler,
+        DataSource::UsbIds,
+    ];
+
+    /// Whether the source is used on the platform cyme is built for
+    pub fn is_platform(&self) -> bool {
+        match self {
+            DataSource::Sysfs | DataSource::Udev => {
+                cfg!(any(target_os = "linux", target_os = "android"))
+            }
+            DataSource::SystemProfiler => cfg!(target_os = "macos"),
+            _ => true,
+        }
+    }
+
+    /// Fields of [`super::Device`] and [`crate::usb::DeviceExtra`] the source provides
+    pub fn fields(&self) -> &'static [&'static str] {
+        match self {
+            DataSource::Nusb | DataSource::Libusb => &[
+                "configurations",
+                "binary_object_store",
+                "qualifier",
+                "hub",
+                "debug",
+                "status",
+            ],
+            DataSource::Sysfs | DataSource::Udev => &["driver", "syspath"],
+            DataSource::SystemProfiler => &["built_in", "media"],
+            DataSource::UsbIds => &["vendor", "product_name"],
+        }
+    }
+}
+
+/// Whether a [`DataSource`] is available and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceStatus {
+    /// The source
+    pub source: DataSource,
+    /// Whether the profiler can use it
+    pub available: bool,
+    /// Why the source is or is not available
+    pub detail: String,
+}
+
+/// [`DataSource`]s of the platform and whether they are available; see the [module docs](self)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capabilities {
+    /// Status of each source used on the platform
+    pub sources: Vec<SourceStatus>,
+}
+
+impl Capabilities {
+    /// Detect which sources are compiled in and usable on this system, with the default backend
+    pub fn detect() -> Self {
+        Self::detect_for(&ProfilerBuilder::new())
+    }
+
+    /// Detect which sources are compiled in and usable on this system, with the backend of `options`
+    pub fn detect_for(options: &ProfilerBuilder) -> Self {
+        let used = options.backend_kind();
+        Capabilities {
+            sources: DataSource::ALL
+                .into_iter()
+                .filter(|s| s.is_platform())
+                .map(|s| detect_source(s, used))
+                .collect(),
+        }
+    }
+
+    /// Whether `source` is available
+    pub fn is_available(&self, source: DataSource) -> bool {
+        self.sources
+            .iter()
+            .any(|s| s.source == source && s.available)
+    }
+
+    /// Fields which will be missing because no available source provides them
+    pub fn missing_fields(&self) -> Vec<&'static str> {
+        let provided: Vec<&str> = self
+            .sources
+            .iter()
+            .filter(|s| s.available)
+            .flat_map(|s| s.source.fields())
+            .copied()
+            .collect();
+        let mut ret: Vec<&'static str> = Vec::new();
+        for field in self
+            .sources
+            .iter()
+            .filter(|s| !s.available)
+            .flat_map(|s| s.source.fields())
+        {
+            if !provided.contains(field) && !ret.contains(field) {
+                ret.push(field);
+            }
+        }
+        ret
+    }
+
+    /// [`Hint`]s for the sources not available which leave fields missing
+    ///
+    /// ```
+    /// use cyme::profiler::capabilities::*;
+    ///
+    /// let capabilities = Capabilities {
+    ///     sources: vec![SourceStatus {
+    ///         source: DataSource::Udev,
+    ///         available: false,
+    ///         detail: String::from("udev is not running"),
+    ///     }],
+    /// };
+    /// assert_eq!(capabilities.missing_fields(), vec!["driver", "syspath"]);
+    /// assert_eq!(
+    ///     capabilities.hints()[0].to_string(),
+    ///     "hint (udev): udev is not running; driver and syspath will be missing"
+    /// );
+    /// ```
+    pub fn hints(&self) -> Vec<Hint> {
+        let missing = self.missing_fields();
+        self.sources
+            .iter()
+            .filter(|s| !s.available)
+            .filter_map(|s| {
+                let fields: Vec<&str> = s
+                    .source
+                    .fields()
+                    .iter()
+                    .copied()
+                    .filter(|f| missing.contains(f))
+                    .collect();
+                let fields = match fields.split_last() {
+                    None => return None,
+                    Some((last, [])) => last.to_string(),
+                    Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+                };
+                Some(Hint::with_source(
+                    s.source,
+                    &format!("{}; {} will be missing", s.detail, fields),
+                ))
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:<16} {:<10} Detail", "Source", "Available")?;
+        for s in &self.sources {
+            writeln!(
+                f,
+                "{:<16} {:<10} {}",
+                s.source.to_string(),
+                if s.available { "yes" } else { "no" },
+                s.detail
+            )?;
+        }
+        let missing = self.missing_fields();
+        if !missing.is_empty() {
+            writeln!(f, "\nMissing fields: {}", missing.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// An explanation of output which is missing data or ignored an argument, printed after the output rather than as it is encountered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hint {
+    /// Source the hint is about, if any
+    pub source: Option<DataSource>,
+    /// The explanation
+    pub message: String,
+}
+
+impl Hint {
+    /// New hint not about a [`DataSource`]
+    pub fn new(message: &str) -> Self {
+        Hint {
+            source: None,
+            message: message.to_string(),
+        }
+    }
+
+    /// New hint about `source`
+    pub fn with_source(source: DataSource, message: &str) -> Self {
+        Hint {
+            source: Some(source),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.source {
+            Some(source) => write!(f, "hint ({}): {}", source, self.message),
+            None => write!(f, "hint: {}", self.message),
+        }
+    }
+}
+
+/// A [`Device`] which could not be opened for extra data; see [`open_errors`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenError {
+    /// Port path of the device
+    pub port_path: String,
+    /// Vendor and product ID as 'vid:pid'
+    pub vidpid: String,
+    /// Name of the device
+    pub name: String,
+    /// Error recorded by the profiler
+    pub error: String,
+    /// Whether the user does not have permission to open the device; only determined on Linux, where the device node is checked
+    pub permission_denied: bool,
+}
+
+impl OpenError {
+    /// From the `profiler_error` of `device`; `None` if there was no error
+    pub fn from_device(device: &Device) -> Option<Self> {
+        let error = device.profiler_error.as_ref()?;
+        Some(OpenError {
+            port_path: device.port_path(),
+            vidpid: format!(
+                "{:04x}:{:04x}",
+                device.vendor_id.unwrap_or(0),
+                device.product_id.unwrap_or(0)
+            ),
+            name: device.name.to_owned(),
+            error: error.to_owned(),
+            permission_denied: is_permission_denied(device),
+        })
+    }
+
+    /// [`Hint`] explaining the missing data of the device
+    pub fn hint(&self) -> Hint {
+        Hint::new(&format!(
+            "{} {} '{}': {}{}",
+            self.port_path,
+            self.vidpid,
+            self.name,
+            if self.permission_denied {
+                "permission denied, "
+            } else {
+                ""
+            },
+            self.error
+        ))
+    }
+}
+
+/// [`OpenError`]s of devices in `spusb` which could not be opened for extra data; only profiles with extra will have any
+pub fn open_errors(spusb: &SystemProfile) -> Vec<OpenError> {
+    spusb.iter().filter_map(OpenError::from_device).collect()
+}
+
+/// Whether the user cannot open the usbfs node of `device` for writing, which the USB backends require
+fn is_permission_denied(device: &Device) -> bool {
+    if cfg!(any(target_os = "linux", target_os = "android")) {
+        std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(device.dev_path())
+            .is_err_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied)
+    } else {
+        false
+    }
+}
+
+fn detect_backend(kind: BackendKind, used: Option<BackendKind>) -> (bool, String) {
+    if !kind.is_available() {
+        return (
+            false,
+            format!(
+                "not compiled in, install with `cargo install --features {}`",
+                kind
+            ),
+        );
+    }
+    // devices are listed from sysfs but opened through usbfs
+    if cfg!(any(target_os = "linux", target_os = "android")) && !Path::new("/dev/bus/usb").is_dir()
+    {
+        return (
+            false,
+            String::from("/dev/bus/usb not found so devices cannot be opened"),
+        );
+    }
+    if used == Some(kind) {
+        (true, String::from("compiled in, used to profile"))
+    } else {
+        (true, format!("compiled in, use with `--backend {}`", kind))
+    }
+}
+
+fn detect_source(source: DataSource, backend: Option<BackendKind>) -> SourceStatus {
+    let (available, detail) = match source {
+        DataSource::Nusb => detect_backend(BackendKind::Nusb, backend),
+        DataSource::Libusb => detect_backend(BackendKind::Libusb, backend),
+        DataSource::Sysfs => {
+            if Path::new("/sys/bus/usb/devices").is_dir() {
+                (true, String::from("/sys/bus/usb/devices"))
+            } else {
+                (false, String::from("/sys/bus/usb/devices not found"))
+            }
+        }
+        DataSource::Udev => {
+            if !cfg!(any(feature = "udev", feature = "udevlib")) {
+                (
+                    false,
+                    String::from("not compiled in, install with `cargo install --features udev`"),
+                )
+            } else if Path::new("/run/udev").is_dir() {
+                (true, String::from("compiled in, /run/udev"))
+            } else {
+                (false, String::from("udev is not running"))
+            }
+        }
+        DataSource::SystemProfiler => {
+            if Path::new("/usr/sbin/system_profiler").exists() {
+                (
+                    true,
+                    String::from("/usr/sbin/system_profiler, use with `--system-profiler`"),
+                )
+            } else {
+                (false, String::from("/usr/sbin/system_profiler not found"))
+            }
+        }
+        DataSource::UsbIds => (true, String::from("built in database")),
+    };
+
+    SourceStatus {
+        source,
+        available,
+        detail,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_fields_covered() {
+        let status = |source, available| SourceStatus {
+            source,
+            available,
+            detail: String::new(),
+        };
+        let capabilities = Capabilities {
+            sources: vec![
+                status(DataSource::Nusb, true),
+                status(DataSource::Libusb, false),
+                status(DataSource::Sysfs, true),
+                status(DataSource::Udev, false),
+            ],
+        };
+        // the other backend and sysfs provide the fields
+        assert!(capabilities.missing_fields().is_empty());
+        assert!(capabilities.hints().is_empty());
+
+        let capabilities = Capabilities {
+            sources: vec![
+                status(DataSource::Nusb, false),
+                status(DataSource::Libusb, false),
+            ],
+        };
+        assert_eq!(
+            capabilities.missing_fields(),
+            DataSource::Nusb.fields().to_vec()
+        );
+        assert_eq!(capabilities.hints().len(), 2);
+    }
+
+    #[test]
+    fn test_open_error() {
+        let mut device = Device {
+            name: "Black Magic Probe".into(),
+            vendor_id: Some(0x1d50),
+            product_id: Some(0x6018),
+            location_id: crate::profiler::DeviceLocation {
+                bus: 200,
+                number: 24,
+                tree_positions: vec![2, 8],
+            },
+            ..Default::default()
+        };
+        assert!(OpenError::from_device(&device).is_none());
+
+        device.profiler_error = Some("Failed to open device for extra data".into());
+        let error = OpenError::from_device(&device).unwrap();
+        // device node does not exist so cannot be denied
+        assert!(!error.permission_denied);
+        assert_eq!(
+            error.hint().to_string(),
+            "hint: 200-2.8 1d50:6018 'Black Magic Probe': Failed to open device for extra data"
+        );
+    }
+}
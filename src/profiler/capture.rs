@@ -0,0 +1,301 @@
+//! Linux usbmon packet capture of a device, tying the bus and device address from profiling to capture workflows; see [`Usbmon`]
+//!
+//! usbmon captures all traffic on a bus so the device is selected by its address, which changes each time it enumerates. [`Usbmon::instructions`] describes capturing with the usbmon text interface, tcpdump or Wireshark; with the 'capture' feature [`Usbmon::capture`] writes a pcap of only the device traffic.
+//!
+//! ```
+//! use cyme::profiler::capture::Usbmon;
+//! use cyme::profiler::read_json_dump;
+//!
+//! let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+//! let usbmon = Usbmon::from(spusb.get_node("2-2.8").unwrap());
+//! assert_eq!(usbmon.interface(), "usbmon2");
+//! assert!(usbmon.display_filter().starts_with("usb.bus_id == 2"));
+//! ```
+use std::fmt::Write as _;
+use std::path::PathBuf;
+
+use super::Device;
+use crate::error::{Error, ErrorKind, Result};
+
+/// pcap link type of the 64 byte memory mapped usbmon header written by [`Usbmon::capture`]
+pub const LINKTYPE_USB_LINUX_MMAPPED: u32 = 220;
+
+/// usbmon bus and device address of a device to capture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usbmon {
+    /// Bus number; usbmon bus 0 captures all buses
+    pub bus: u8,
+    /// Device address on the bus
+    pub address: u8,
+}
+
+impl From<&Device> for Usbmon {
+    fn from(device: &Device) -> Self {
+        Usbmon {
+            bus: device.location_id.bus,
+            address: device.location_id.number,
+        }
+    }
+}
+
+impl Usbmon {
+    /// Binary usbmon character device of the bus
+    pub fn device_node(&self) -> PathBuf {
+        PathBuf::from(format!("/dev/usbmon{}", self.bus))
+    }
+
+    /// Text usbmon interface of the bus in debugfs
+    pub fn debugfs_path(&self) -> PathBuf {
+        PathBuf::from(format!("/sys/kernel/debug/usb/usbmon/{}u", self.bus))
+    }
+
+    /// libpcap interface name of the bus used by tcpdump and Wireshark
+    pub fn interface(&self) -> String {
+        format!("usbmon{}", self.bus)
+    }
+
+    /// Wireshark display filter matching only the device traffic
+    pub fn display_filter(&self) -> String {
+        format!(
+            "usb.bus_id == {} && usb.device_address == {}",
+            self.bus, self.address
+        )
+    }
+
+    /// Steps to capture the device traffic with the usbmon text interface, tcpdump and Wireshark
+    pub fn instructions(&self) -> String {
+        let mut ret = String::new();
+        let _ = writeln!(
+            ret,
+            "Bus {:03} Device {:03}; the address changes if the device re-enumerates",
+            self.bus, self.address
+        );
+        let _ = writeln!(ret, "\n# load usbmon if not built into the kernel");
+        let _ = writeln!(ret, "sudo modprobe usbmon");
+        let _ = writeln!(
+            ret,
+            "\n# text capture, device traffic has ':{}:{:03}:' after the type",
+            self.bus, self.address
+        );
+        let _ = writeln!(
+            ret,
+            "sudo grep ':{}:{:03}:' {}",
+            self.bus,
+            self.address,
+            self.debugfs_path().display()
+        );
+        let _ = writeln!(ret, "\n# pcap of the whole bus, filter in Wireshark");
+        let _ = writeln!(ret, "sudo tcpdump -i {} -w trace.pcap", self.interface());
+        let _ = writeln!(
+            ret,
+            "wireshark -r trace.pcap -Y '{}'",
+            self.display_filter()
+        );
+        let _ = writeln!(ret, "\n# live in Wireshark");
+        let _ = writeln!(
+            ret,
+            "wireshark -k -i {} -Y '{}'",
+            self.interface(),
+            self.display_filter()
+        );
+        ret
+    }
+
+    /// Capture the device traffic from the usbmon [`Self::device_node`] to `writer` as a pcap, stopping after `count` packets if supplied
+    ///
+    /// Packets are written and flushed as they arrive so the capture is valid if interrupted. Requires read permission of the device node, generally root. Returns the number of packets written.
+    #[cfg(all(feature = "capture", target_os = "linux"))]
+    pub fn capture<W: std::io::Write>(&self, mut writer: W, count: Option<usize>) -> Result<usize> {
+        use std::os::fd::AsRawFd;
+
+        let path = self.device_node();
+        let file = std::fs::File::open(&path).map_err(|e| {
+            Error::new(
+                ErrorKind::Io,
+                &format!(
+                    "Failed to open {}, is usbmon loaded and are you root? {}",
+                    path.display(),
+                    e
+                ),
+            )
+        })?;
+
+        writer.write_all(&pcap_header())?;
+        writer.flush()?;
+
+        let mut header = [0u8; MMAPPED_HEADER_LEN];
+        let mut data = vec![0u8; DATA_LEN];
+        let mut written = 0;
+        loop {
+            if count.is_some_and(|c| written >= c) {
+                break;
+            }
+            let mut arg = MonGetArg {
+                hdr: header.as_mut_ptr().cast(),
+                data: data.as_mut_ptr().cast(),
+                alloc: data.len(),
+            };
+            // SAFETY: the kernel writes at most MMAPPED_HEADER_LEN to hdr and alloc bytes to data, both valid for the call
+            let ret = unsafe { libc::ioctl(file.as_raw_fd(), MON_IOCX_GETX, &mut arg) };
+            if ret < 0 {
+                let e = std::io::Error::last_os_error();
+                if e.kind() == std::io::ErrorKind::Interrupted {
+                    continue;
+                }
+                return Err(e.into());
+            }
+
+            if header[HEADER_DEVNUM] != self.address {
+                continue;
+            }
+
+            // the header captured length is that of the event, which is truncated to our buffer
+            let len_cap = u32::from_ne_bytes(
+                header[HEADER_LEN_CAP..HEADER_LEN_CAP + 4]
+                    .try_into()
+                    .unwrap(),
+            )
+            .min(DATA_LEN as u32);
+            header[HEADER_LEN_CAP..HEADER_LEN_CAP + 4].copy_from_slice(&len_cap.to_ne_bytes());
+            let ts_sec =
+                i64::from_ne_bytes(header[HEADER_TS_SEC..HEADER_TS_SEC + 8].try_into().unwrap());
+            let ts_usec = i32::from_ne_bytes(
+                header[HEADER_TS_USEC..HEADER_TS_USEC + 4]
+                    .try_into()
+                    .unwrap(),
+            );
+            let length = MMAPPED_HEADER_LEN as u32 + len_cap;
+
+            writer.write_all(&(ts_sec as u32).to_ne_bytes())?;
+            writer.write_all(&(ts_usec as u32).to_ne_bytes())?;
+            writer.write_all(&length.to_ne_bytes())?;
+            writer.write_all(&length.to_ne_bytes())?;
+            writer.write_all(&header)?;
+            writer.write_all(&data[..len_cap as usize])?;
+            writer.flush()?;
+            written += 1;
+        }
+
+        Ok(written)
+    }
+}
+
+/// Capture the device traffic of `usbmon` for `cyme capture --output` to the `output` pcap file, stdout if '-', stopping after `count` packets; returns the number of packets captured
+pub fn run(usbmon: Usbmon, output: &str, count: Option<usize>) -> Result<usize> {
+    log::info!(
+        "Capturing bus {} device {} to {}",
+        usbmon.bus,
+        usbmon.address,
+        output
+    );
+    capture_to(usbmon, output, count)
+}
+
+/// Capture the device traffic of `usbmon` to the `output` pcap file, or stdout if '-'
+#[cfg(all(feature = "capture", target_os = "linux"))]
+fn capture_to(usbmon: Usbmon, output: &str, count: Option<usize>) -> Result<usize> {
+    if output == "-" {
+        usbmon.capture(std::io::stdout().lock(), count)
+    } else {
+        usbmon.capture(
+            std::io::BufWriter::new(std::fs::File::create(output)?),
+            count,
+        )
+    }
+}
+
+#[cfg(not(all(feature = "capture", target_os = "linux")))]
+fn capture_to(_usbmon: Usbmon, _output: &str, _count: Option<usize>) -> Result<usize> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "Capturing requires the 'capture' feature on Linux; run without --output for capture instructions",
+    ))
+}
+
+/// Length of the usbmon header returned by MON_IOCX_GETX
+#[cfg(all(feature = "capture", target_os = "linux"))]
+const MMAPPED_HEADER_LEN: usize = 64;
+/// Offset of devnum in the usbmon header
+#[cfg(all(feature = "capture", target_os = "linux"))]
+const HEADER_DEVNUM: usize = 11;
+/// Offset of ts_sec in the usbmon header
+#[cfg(all(feature = "capture", target_os = "linux"))]
+const HEADER_TS_SEC: usize = 16;
+/// Offset of ts_usec in the usbmon header
+#[cfg(all(feature = "capture", target_os = "linux"))]
+const HEADER_TS_USEC: usize = 24;
+/// Offset of len_cap in the usbmon header
+#[cfg(all(feature = "capture", target_os = "linux"))]
+const HEADER_LEN_CAP: usize = 36;
+/// Size of the buffer for event data; larger transfers are truncated
+#[cfg(all(feature = "capture", target_os = "linux"))]
+const DATA_LEN: usize = 0x10000;
+
+/// struct mon_get_arg of the usbmon binary API
+#[cfg(all(feature = "capture", target_os = "linux"))]
+#[repr(C)]
+struct MonGetArg {
+    hdr: *mut libc::c_void,
+    data: *mut libc::c_void,
+    alloc: libc::size_t,
+}
+
+/// _IOC_WRITE shifted to the direction bits of the architecture
+#[cfg(all(
+    feature = "capture",
+    target_os = "linux",
+    any(
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "sparc64"
+    )
+))]
+const IOC_WRITE: usize = 4 << 29;
+#[cfg(all(
+    feature = "capture",
+    target_os = "linux",
+    not(any(
+        target_arch = "mips",
+        target_arch = "mips64",
+        target_arch = "powerpc",
+        target_arch = "powerpc64",
+        target_arch = "sparc64"
+    ))
+))]
+const IOC_WRITE: usize = 1 << 30;
+
+/// _IOW(0x92, 10, struct mon_get_arg): get the next event with the 64 byte header, blocking until there is one
+#[cfg(all(feature = "capture", target_os = "linux"))]
+const MON_IOCX_GETX: libc::Ioctl =
+    (IOC_WRITE | (std::mem::size_of::<MonGetArg>() << 16) | (0x92 << 8) | 10) as libc::Ioctl;
+
+/// pcap global header in native byte order, as the usbmon headers are
+#[cfg(all(feature = "capture", target_os = "linux"))]
+fn pcap_header() -> Vec<u8> {
+    let mut ret = Vec::with_capacity(24);
+    ret.extend_from_slice(&0xa1b2c3d4u32.to_ne_bytes());
+    ret.extend_from_slice(&2u16.to_ne_bytes());
+    ret.extend_from_slice(&4u16.to_ne_bytes());
+    // thiszone and sigfigs
+    ret.extend_from_slice(&[0; 8]);
+    ret.extend_from_slice(&((MMAPPED_HEADER_LEN + DATA_LEN) as u32).to_ne_bytes());
+    ret.extend_from_slice(&LINKTYPE_USB_LINUX_MMAPPED.to_ne_bytes());
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usbmon_instructions() {
+        let usbmon = Usbmon { bus: 3, address: 7 };
+        assert_eq!(usbmon.device_node(), PathBuf::from("/dev/usbmon3"));
+        let instructions = usbmon.instructions();
+        assert!(instructions.contains("sudo grep ':3:007:' /sys/kernel/debug/usb/usbmon/3u"));
+        assert!(instructions.contains("tcpdump -i usbmon3"));
+        assert!(instructions.contains("-Y 'usb.bus_id == 3 && usb.device_address == 7'"));
+    }
+}
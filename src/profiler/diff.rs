@@ -0,0 +1,382 @@
+//! Compare two [`SystemProfile`]s, such as dumps taken before and after a firmware update.
+//!
+//! Devices are matched by port path. A device at the same port with a different VID:PID is reported as removed and added rather than changed.
+//!
+//! ```
+//! use cyme::profiler::diff::{DiffKind, ProfileDiff};
+//! use cyme::profiler::read_json_dump;
+//!
+//! let before = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+//! let mut after = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+//! let port_path = after
+//!     .flattened_devices()
+//!     .iter()
+//!     .find(|d| !d.is_root_hub())
+//!     .unwrap()
+//!     .port_path();
+//! after.get_node_mut(&port_path).unwrap().bcd_device = Some(cyme::usb::Version(9, 9, 0));
+//!
+//! let diff = ProfileDiff::new(&before, &after);
+//! assert_eq!(diff.devices.len(), 1);
+//! assert_eq!(diff.devices[0].kind, DiffKind::Changed);
+//! assert_eq!(diff.devices[0].changes[0].field, "bcd_device");
+//! ```
+use crate::colour::colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::{Device, SystemProfile};
+use crate::error::{Error, ErrorKind, Result};
+use crate::redact::Redaction;
+
+/// How a [`Device`] differs between profiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffKind {
+    /// Only in the new profile
+    Added,
+    /// Only in the old profile
+    Removed,
+    /// In both but with changed fields
+    Changed,
+}
+
+impl fmt::Display for DiffKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiffKind::Added => write!(f, "+"),
+            DiffKind::Removed => write!(f, "-"),
+            DiffKind::Changed => write!(f, "~"),
+        }
+    }
+}
+
+/// A field of a [`Device`] which changed; `None` if the field was not present
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Name of the field
+    pub field: String,
+    /// Value in the old profile
+    pub old: Option<String>,
+    /// Value in the new profile
+    pub new: Option<String>,
+}
+
+/// A [`Device`] which was added, removed or changed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceDiff {
+    /// Added, removed or changed
+    pub kind: DiffKind,
+    /// Port path of the device
+    pub port_path: String,
+    /// Depth of the device in the tree
+    pub depth: usize,
+    /// Vendor ID
+    pub vendor_id: Option<u16>,
+    /// Product ID
+    pub product_id: Option<u16>,
+    /// Device name
+    pub name: String,
+    /// Changed fields; empty if added or removed
+    pub changes: Vec<FieldChange>,
+}
+
+impl DeviceDiff {
+    fn new(kind: DiffKind, device: &Device, changes: Vec<FieldChange>) -> Self {
+        DeviceDiff {
+            kind,
+            port_path: device.port_path(),
+            depth: device.get_depth(),
+            vendor_id: device.vendor_id,
+            product_id: device.product_id,
+            name: device.name.clone(),
+            changes,
+        }
+    }
+}
+
+/// Differences between two [`SystemProfile`]s, sorted by port path so that they follow the tree
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileDiff {
+    /// Devices which differ
+    pub devices: Vec<DeviceDiff>,
+}
+
+impl ProfileDiff {
+    /// Compare `old` with `new`
+    pub fn new(old: &SystemProfile, new: &SystemProfile) -> Self {
+        let old_devices: BTreeMap<String, &Device> = old
+            .flattened_devices()
+            .into_iter()
+            .map(|d| (d.port_path(), d))
+            .collect();
+        let new_devices: BTreeMap<String, &Device> = new
+            .flattened_devices()
+            .into_iter()
+            .map(|d| (d.port_path(), d))
+            .collect();
+
+        let mut devices = Vec::new();
+        for (port_path, old_device) in old_devices.iter() {
+            match new_devices.get(port_path) {
+                Some(new_device)
+                    if new_device.vendor_id == old_device.vendor_id
+                        && new_device.product_id == old_device.product_id =>
+                {
+                    let changes = device_changes(old_device, new_device);
+                    if !changes.is_empty() {
+                        devices.push(DeviceDiff::new(DiffKind::Changed, new_device, changes));
+                    }
+                }
+                Some(new_device) => {
+                    devices.push(DeviceDiff::new(DiffKind::Removed, old_device, vec![]));
+                    devices.push(DeviceDiff::new(DiffKind::Added, new_device, vec![]));
+                }
+                None => devices.push(DeviceDiff::new(DiffKind::Removed, old_device, vec![])),
+            }
+        }
+        for (port_path, new_device) in new_devices.iter() {
+            if !old_devices.contains_key(port_path) {
+                devices.push(DeviceDiff::new(DiffKind::Added, new_device, vec![]));
+            }
+        }
+        // stable so removed before added at the same port
+        devices.sort_by(|a, b| a.port_path.cmp(&b.port_path));
+
+        ProfileDiff { devices }
+    }
+
+    /// Compare `actual` against an `expected` baseline, such as a provisioned test fixture
+    ///
+    /// Only fields present in the baseline are compared and those named in `ignore` are skipped, so a baseline can be a subset of a dump. Devices in `actual` which are not in the baseline are only reported if `exact`.
+    ///
+    /// ```
+    /// use cyme::profiler::diff::ProfileDiff;
+    /// use cyme::profiler::read_json_dump;
+    ///
+    /// let expected = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+    /// let mut actual = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+    /// let port_path = actual
+    ///     .flattened_devices()
+    ///     .iter()
+    ///     .find(|d| !d.is_root_hub())
+    ///     .unwrap()
+    ///     .port_path();
+    /// actual.get_node_mut(&port_path).unwrap().serial_num = Some("other".into());
+    ///
+    /// assert!(!ProfileDiff::expect(&expected, &actual, &[] as &[&str], true).is_empty());
+    /// assert!(ProfileDiff::expect(&expected, &actual, &["serial_num"], true).is_empty());
+    /// ```
+    pub fn expect<S: AsRef<str>>(
+        expected: &SystemProfile,
+        actual: &SystemProfile,
+        ignore: &[S],
+        exact: bool,
+    ) -> Self {
+        let mut diff = ProfileDiff::new(expected, actual);
+        diff.devices.retain_mut(|d| match d.kind {
+            DiffKind::Added => exact,
+            DiffKind::Removed => true,
+            DiffKind::Changed => {
+                d.changes
+                    .retain(|c| c.old.is_some() && !ignore.iter().any(|i| i.as_ref() == c.field));
+                !d.changes.is_empty()
+            }
+        });
+
+        diff
+    }
+
+    /// Whether the profiles are the same
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+}
+
+/// Alternate (#) is coloured
+impl fmt::Display for ProfileDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for d in self.devices.iter() {
+            let indent = d.depth.saturating_sub(1) * 2;
+            let line = format!(
+                "{} {:indent$}{} {:04x}:{:04x} {}",
+                d.kind,
+                "",
+                d.port_path,
+                d.vendor_id.unwrap_or(0),
+                d.product_id.unwrap_or(0),
+                d.name.trim(),
+            );
+            if f.alternate() {
+                match d.kind {
+                    DiffKind::Added => writeln!(f, "{}", line.green())?,
+                    DiffKind::Removed => writeln!(f, "{}", line.red())?,
+                    DiffKind::Changed => writeln!(f, "{}", line.yellow())?,
+                }
+            } else {
+                writeln!(f, "{}", line)?;
+            }
+            for c in d.changes.iter() {
+                writeln!(
+                    f,
+                    "  {:indent$}  {}: {} -> {}",
+                    "",
+                    c.field,
+                    c.old.as_deref().unwrap_or("-"),
+                    c.new.as_deref().unwrap_or("-"),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Names of the fields compared between devices, which can be ignored with [`ProfileDiff::expect`]
+pub const FIELDS: [&str; 11] = [
+    "name",
+    "manufacturer",
+    "serial_num",
+    "bcd_device",
+    "bcd_usb",
+    "class",
+    "device_speed",
+    "negotiated_speed",
+    "driver",
+    "configuration",
+    "interface",
+];
+
+/// [`ProfileDiff`] of `old` and `new` for `cyme diff`, both redacted with `redaction` first
+pub fn run(
+    mut old: SystemProfile,
+    mut new: SystemProfile,
+    redaction: Option<&Redaction>,
+) -> Result<ProfileDiff> {
+    if let Some(redaction) = redaction {
+        redaction.apply(&mut old)?;
+        redaction.apply(&mut new)?;
+    }
+
+    Ok(ProfileDiff::new(&old, &new))
+}
+
+/// Check `actual` against `expected` for `cyme assert`, both redacted with `redaction` first; see [`ProfileDiff::expect`]
+///
+/// The profiles match if the returned diff is empty. Errors with [`ErrorKind::InvalidArg`] if `ignore` has a field not in [`FIELDS`].
+pub fn run_assert<S: AsRef<str>>(
+    mut expected: SystemProfile,
+    mut actual: SystemProfile,
+    ignore: &[S],
+    exact: bool,
+    redaction: Option<&Redaction>,
+) -> Result<ProfileDiff> {
+    if let Some(field) = ignore.iter().find(|f| !FIELDS.contains(&f.as_ref())) {
+        return Err(Error::new(
+            ErrorKind::InvalidArg,
+            &format!(
+                "Unknown field '{}' to ignore, must be one of: {}",
+                field.as_ref(),
+                FIELDS.join(", ")
+            ),
+        ));
+    }
+    if let Some(redaction) = redaction {
+        redaction.apply(&mut expected)?;
+        redaction.apply(&mut actual)?;
+    }
+
+    Ok(ProfileDiff::expect(&expected, &actual, ignore, exact))
+}
+
+/// Fields compared between devices as name and value
+fn device_fields(d: &Device) -> Vec<(&'static str, Option<String>)> {
+    let extra = d.extra.as_ref();
+    vec![
+        ("name", Some(d.name.trim().to_string())),
+        ("manufacturer", d.manufacturer.clone()),
+        ("serial_num", d.serial_num.clone()),
+        ("bcd_device", d.bcd_device.map(|v| v.to_string())),
+        ("bcd_usb", d.bcd_usb.map(|v| v.to_string())),
+        ("class", d.class.map(|c| format!("{:?}", c))),
+        (
+            "device_speed",
+            d.device_speed.as_ref().map(|s| s.to_string()),
+        ),
+        (
+            "negotiated_speed",
+            d.negotiated_speed().map(|s| s.to_string()),
+        ),
+        ("driver", extra.and_then(|e| e.driver.clone())),
+    ]
+}
+
+/// Summary of each configuration and interface, compared as lists since they can be added or removed
+fn configuration_summaries(d: &Device) -> Vec<(&'static str, String)> {
+    let mut ret = Vec::new();
+    for c in d.extra.iter().flat_map(|e| e.configurations.iter()) {
+        ret.push((
+            "configuration",
+            format!(
+                "{} {} {}",
+                c.number,
+                c.max_power,
+                c.attributes
+                    .iter()
+                    .map(|a| a.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ),
+        ));
+        for i in c.interfaces.iter() {
+            ret.push((
+                "interface",
+                format!(
+                    "{}.{} {:?} {}",
+                    i.number,
+                    i.alt_setting,
+                    i.class,
+                    i.driver.as_deref().unwrap_or("-")
+                ),
+            ));
+        }
+    }
+
+    ret
+}
+
+fn device_changes(old: &Device, new: &Device) -> Vec<FieldChange> {
+    let mut changes: Vec<FieldChange> = device_fields(old)
+        .into_iter()
+        .zip(device_fields(new))
+        .filter(|((_, o), (_, n))| o != n)
+        .map(|((field, o), (_, n))| FieldChange {
+            field: field.to_string(),
+            old: o,
+            new: n,
+        })
+        .collect();
+
+    let old_summaries = configuration_summaries(old);
+    let new_summaries = configuration_summaries(new);
+    for (field, summary) in old_summaries.iter() {
+        if !new_summaries.contains(&(*field, summary.to_owned())) {
+            changes.push(FieldChange {
+                field: field.to_string(),
+                old: Some(summary.to_owned()),
+                new: None,
+            });
+        }
+    }
+    for (field, summary) in new_summaries.iter() {
+        if !old_summaries.contains(&(*field, summary.to_owned())) {
+            changes.push(FieldChange {
+                field: field.to_string(),
+                old: None,
+                new: Some(summary.to_owned()),
+            });
+        }
+    }
+
+    changes
+}
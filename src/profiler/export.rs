@@ -0,0 +1,291 @@
+//! Export the raw descriptors of a device as binary files, for replay in emulators and fuzzers or to attach to bug reports.
+//!
+//! The descriptors are read from the device with GET_DESCRIPTOR control requests, so they are as the device reports them rather than as parsed by cyme. The files written are:
+//!
+//! * `device.bin`: device descriptor
+//! * `config-<index>.bin`: configuration descriptor of wTotalLength including interface, endpoint and class descriptors
+//! * `bos.bin`: Binary Object Store descriptor, if the device has one
+//! * `hid-report-<interface>.bin`: report descriptor of each HID interface
+//! * `string-<index>.bin`: string descriptors referenced by the other descriptors in the first language; `string-0.bin` is the supported languages
+//!
+//! ```no_run
+//! use cyme::profiler::{export, ProfilerBuilder};
+//!
+//! let builder = ProfilerBuilder::new();
+//! let spusb = builder.profile().unwrap();
+//! let device = spusb.get_node("1-1").unwrap();
+//! for path in export::export_descriptors(device, "descriptors", &builder).unwrap() {
+//!     println!("{}", path.display());
+//! }
+//! ```
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{
+    ControlRequest, ControlType, Device, ProfilerBuilder, Recipient, UsbOperations,
+    REQUEST_GET_DESCRIPTOR,
+};
+use crate::error::{Error, ErrorKind, Result};
+use crate::redact::Redaction;
+use crate::usb::{iter_descriptors, DescriptorType};
+
+/// Read a descriptor of `length` bytes with a standard GET_DESCRIPTOR request to the device
+fn get_descriptor<T: UsbOperations>(
+    device: &T,
+    descriptor_type: DescriptorType,
+    index: u8,
+    language: u16,
+    length: usize,
+) -> Result<Vec<u8>> {
+    device.get_control_msg(ControlRequest {
+        control_type: ControlType::Standard,
+        recipient: Recipient::Device,
+        request: REQUEST_GET_DESCRIPTOR,
+        value: ((u8::from(descriptor_type) as u16) << 8) | index as u16,
+        index: language,
+        length,
+        claim_interface: false,
+    })
+}
+
+/// Read a descriptor with wTotalLength following bDescriptorType, such as configuration and BOS descriptors, by first reading the header
+fn get_descriptor_with_total_length<T: UsbOperations>(
+    device: &T,
+    descriptor_type: DescriptorType,
+    index: u8,
+    header_length: usize,
+) -> Result<Vec<u8>> {
+    let header = get_descriptor(device, descriptor_type.clone(), index, 0, header_length)?;
+    let total_length = header
+        .get(2..4)
+        .map(|l| u16::from_le_bytes([l[0], l[1]]))
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidDescriptor,
+                &format!("{:?} descriptor header too short", descriptor_type),
+            )
+        })?;
+    get_descriptor(device, descriptor_type, index, 0, total_length as usize)
+}
+
+/// Read a string descriptor, first reading bLength so that the request is not longer than the descriptor
+fn get_string_descriptor<T: UsbOperations>(
+    device: &T,
+    index: u8,
+    language: u16,
+) -> Result<Vec<u8>> {
+    let header = get_descriptor(device, DescriptorType::String, index, language, 2)?;
+    let length = header.first().copied().unwrap_or(0) as usize;
+    if length < 2 {
+        return Err(Error::new(
+            ErrorKind::InvalidDescriptor,
+            &format!("String descriptor {} has invalid bLength {}", index, length),
+        ));
+    }
+    get_descriptor(device, DescriptorType::String, index, language, length)
+}
+
+/// String descriptor indexes referenced by the device descriptor and configuration descriptors, excluding 0
+///
+/// ```
+/// use cyme::profiler::export::string_indexes;
+///
+/// let device = [0x12, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x40, 0x09, 0x12, 0x01, 0x00, 0x00, 0x01, 0x01, 0x02, 0x03, 0x01];
+/// let config = [
+///     0x09, 0x02, 0x12, 0x00, 0x01, 0x01, 0x04, 0xa0, 0x32,
+///     0x09, 0x04, 0x00, 0x00, 0x00, 0xff, 0x00, 0x00, 0x05,
+/// ];
+/// assert_eq!(string_indexes(&device, &[config.to_vec()]).into_iter().collect::<Vec<u8>>(), vec![1, 2, 3, 4, 5]);
+/// ```
+pub fn string_indexes(device: &[u8], configs: &[Vec<u8>]) -> BTreeSet<u8> {
+    let mut ret = BTreeSet::new();
+    // iManufacturer, iProduct, iSerialNumber
+    ret.extend(device.get(14..17).unwrap_or_default());
+
+    for config in configs {
+        for desc in iter_descriptors(config) {
+            let index = match desc.get(1) {
+                // iConfiguration
+                Some(0x02) => desc.get(6),
+                // iInterface
+                Some(0x04) => desc.get(8),
+                // iFunction
+                Some(0x0b) => desc.get(7),
+                _ => None,
+            };
+            ret.extend(index);
+        }
+    }
+    ret.remove(&0);
+
+    ret
+}
+
+/// Interface number and report descriptor length of each HID interface in a configuration descriptor
+///
+/// ```
+/// use cyme::profiler::export::hid_report_lengths;
+///
+/// let config = [
+///     0x09, 0x02, 0x22, 0x00, 0x01, 0x01, 0x00, 0xa0, 0x32,
+///     0x09, 0x04, 0x00, 0x00, 0x01, 0x03, 0x01, 0x02, 0x00,
+///     0x09, 0x21, 0x11, 0x01, 0x00, 0x01, 0x22, 0x34, 0x00,
+///     0x07, 0x05, 0x81, 0x03, 0x04, 0x00, 0x0a,
+/// ];
+/// assert_eq!(hid_report_lengths(&config), vec![(0, 0x34)]);
+/// ```
+pub fn hid_report_lengths(config: &[u8]) -> Vec<(u8, u16)> {
+    let mut ret = Vec::new();
+    // interface number if current interface is HID
+    let mut hid_interface: Option<u8> = None;
+
+    for desc in iter_descriptors(config) {
+        match desc.get(1) {
+            Some(0x04) if desc.len() >= 6 => {
+                hid_interface = (desc[5] == 0x03).then_some(desc[2]);
+            }
+            // HID descriptor with bNumDescriptors entries of (bDescriptorType, wDescriptorLength)
+            Some(0x21) => {
+                if let Some(interface) = hid_interface.take() {
+                    let report_length = desc
+                        .get(6..)
+                        .unwrap_or_default()
+                        .chunks_exact(3)
+                        .find(|c| c[0] == 0x22)
+                        .map(|c| u16::from_le_bytes([c[1], c[2]]));
+                    if let Some(length) = report_length {
+                        ret.push((interface, length));
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    ret
+}
+
+/// Read the raw descriptors from an open device as file name and bytes; optional descriptors which fail to read are logged and skipped
+pub(crate) fn read_descriptors<T: UsbOperations + std::fmt::Debug>(
+    device: &T,
+) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut ret = Vec::new();
+
+    let device_desc = get_descriptor(device, DescriptorType::Device, 0, 0, 18)?;
+    if device_desc.len() < 18 {
+        return Err(Error::new(
+            ErrorKind::InvalidDescriptor,
+            &format!("{:?} device descriptor too short", device),
+        ));
+    }
+    let num_configurations = device_desc[17];
+    let bcd_usb = u16::from_le_bytes([device_desc[2], device_desc[3]]);
+    ret.push((String::from("device.bin"), device_desc.clone()));
+
+    let mut configs = Vec::new();
+    for i in 0..num_configurations {
+        match get_descriptor_with_total_length(device, DescriptorType::Config, i, 9) {
+            Ok(config) => {
+                ret.push((format!("config-{}.bin", i), config.clone()));
+                configs.push(config);
+            }
+            Err(e) => log::warn!("{:?} Failed to read configuration {}: {}", device, i, e),
+        }
+    }
+
+    // BOS is only required from USB 2.01
+    if bcd_usb >= 0x0201 {
+        match get_descriptor_with_total_length(device, DescriptorType::Bos, 0, 5) {
+            Ok(bos) => ret.push((String::from("bos.bin"), bos)),
+            Err(e) => log::info!("{:?} Failed to read BOS descriptor: {}", device, e),
+        }
+    }
+
+    for (interface, length) in configs.iter().flat_map(|c| hid_report_lengths(c)) {
+        let request = ControlRequest {
+            control_type: ControlType::Standard,
+            recipient: Recipient::Interface,
+            request: REQUEST_GET_DESCRIPTOR,
+            value: (u8::from(DescriptorType::Report) as u16) << 8,
+            index: interface as u16,
+            length: length as usize,
+            // only claim interface on linux
+            claim_interface: cfg!(target_os = "linux") || cfg!(target_os = "android"),
+        };
+        match device.get_control_msg(request) {
+            Ok(report) => ret.push((format!("hid-report-{}.bin", interface), report)),
+            Err(e) => log::warn!(
+                "{:?} Failed to read HID report descriptor of interface {}: {}",
+                device,
+                interface,
+                e
+            ),
+        }
+    }
+
+    match get_string_descriptor(device, 0, 0) {
+        Ok(languages) => {
+            let language = languages
+                .get(2..4)
+                .map(|l| u16::from_le_bytes([l[0], l[1]]))
+                .unwrap_or(0x0409);
+            ret.push((String::from("string-0.bin"), languages));
+            for index in string_indexes(&device_desc, &configs) {
+                match get_string_descriptor(device, index, language) {
+                    Ok(string) => ret.push((format!("string-{}.bin", index), string)),
+                    Err(e) => log::warn!("{:?} Failed to read string {}: {}", device, index, e),
+                }
+            }
+        }
+        Err(e) => log::info!("{:?} Device has no string descriptors: {}", device, e),
+    }
+
+    Ok(ret)
+}
+
+/// Write `descriptors` as file name and bytes to `dir`, creating it if required
+pub fn write_descriptors<P: AsRef<Path>>(
+    descriptors: &[(String, Vec<u8>)],
+    dir: P,
+) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir.as_ref())?;
+    descriptors
+        .iter()
+        .map(|(name, data)| {
+            let path = dir.as_ref().join(name);
+            fs::write(&path, data)?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// Read the raw descriptors of `device` with the backend of `options` and write them to `dir` as binary files, returning the paths written
+///
+/// Requires permission to open the device.
+pub fn export_descriptors<P: AsRef<Path>>(
+    device: &Device,
+    dir: P,
+    options: &ProfilerBuilder,
+) -> Result<Vec<PathBuf>> {
+    let descriptors = options
+        .get_backend()?
+        .read_descriptors(&device.location_id)?;
+
+    write_descriptors(&descriptors, dir)
+}
+
+/// [`export_descriptors`] with the serial number and other string descriptors redacted by `redaction`; see [`Redaction::apply_descriptors`]
+pub fn export_redacted_descriptors<P: AsRef<Path>>(
+    device: &Device,
+    dir: P,
+    redaction: &Redaction,
+    options: &ProfilerBuilder,
+) -> Result<Vec<PathBuf>> {
+    let mut descriptors = options
+        .get_backend()?
+        .read_descriptors(&device.location_id)?;
+    redaction.apply_descriptors(&mut descriptors)?;
+
+    write_descriptors(&descriptors, dir)
+}
@@ -0,0 +1,499 @@
+//! Boolean filter expressions such as `class==HID && vid==0x046d || name~"Webcam"`, compiled into [`Filter::expression`](super::Filter::expression) by `--where` and `--highlight`.
+//!
+//! An expression is conditions of `field operator value` combined with `&&` (or `,`), `||`, `!` and parentheses; `&&` binds tighter than `||`.
+//!
+//! Fields are `vid`, `pid`, `bus`, `number`, `name`, `serial`, `manufacturer` and `class`. Operators:
+//!
+//! * `=` or `~` matches like the `--filter-*` flags: string fields contain the value, other fields are equal to it
+//! * `==` is equal to the value
+//! * `!=` and `!~` are the negations
+//!
+//! `vid` and `pid` are base16, `bus` and `number` base10 and `class` a [`BaseClass`] name, which is matched against the device and its interfaces. Values are quoted with `"` if they contain `&&`, `||`, `,` or `)`.
+//!
+//! ```
+//! use cyme::profiler::expression::FilterExpression;
+//! use cyme::profiler::read_json_dump;
+//!
+//! let sp = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+//! let expr: FilterExpression = "vid==0x1d50 || (name~J-Link && !serial~\"999\")".parse().unwrap();
+//! let matched: Vec<String> = sp.iter().filter(|d| expr.is_match(d)).map(|d| d.name.clone()).collect();
+//! assert_eq!(matched, vec!["J-Link", "Black Magic Probe  v1.8.2"]);
+//! ```
+use clap::ValueEnum;
+use serde_with::{DeserializeFromStr, SerializeDisplay};
+use std::fmt;
+use std::str::FromStr;
+
+use super::Device;
+use crate::error::{Error, ErrorKind, Result};
+use crate::usb::BaseClass;
+
+/// [`Device`] field compared by a [`Condition`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// Vendor ID
+    Vid,
+    /// Product ID
+    Pid,
+    /// Bus number
+    Bus,
+    /// Device number
+    Number,
+    /// Product name
+    Name,
+    /// Serial number
+    Serial,
+    /// Manufacturer name
+    Manufacturer,
+    /// Class of the device or any of its interfaces
+    Class,
+}
+
+impl FromStr for Field {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "vid" => Ok(Field::Vid),
+            "pid" => Ok(Field::Pid),
+            "bus" => Ok(Field::Bus),
+            "number" => Ok(Field::Number),
+            "name" => Ok(Field::Name),
+            "serial" => Ok(Field::Serial),
+            "manufacturer" => Ok(Field::Manufacturer),
+            "class" => Ok(Field::Class),
+            _ => Err(Error::new(
+                ErrorKind::Parsing,
+                &format!("Unknown filter expression field '{}'", s),
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Field::Vid => "vid",
+            Field::Pid => "pid",
+            Field::Bus => "bus",
+            Field::Number => "number",
+            Field::Name => "name",
+            Field::Serial => "serial",
+            Field::Manufacturer => "manufacturer",
+            Field::Class => "class",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// How a [`Condition`] compares the [`Field`] with its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    /// `=` or `~`: string contains the value, otherwise equal
+    Match,
+    /// `!~`: negation of [`Operator::Match`]
+    NotMatch,
+    /// `==`: equal to the value
+    Equal,
+    /// `!=`: not equal to the value
+    NotEqual,
+}
+
+impl Operator {
+    fn is_negated(&self) -> bool {
+        matches!(self, Operator::NotMatch | Operator::NotEqual)
+    }
+}
+
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Operator::Match => "~",
+            Operator::NotMatch => "!~",
+            Operator::Equal => "==",
+            Operator::NotEqual => "!=",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// Value of a [`Condition`], parsed for the type of its [`Field`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// Numerical ID or number
+    Number(u16),
+    /// String
+    Text(String),
+    /// USB class
+    Class(BaseClass),
+}
+
+impl Value {
+    fn parse(field: Field, s: &str) -> Result<Self> {
+        let parse_error = |e: &dyn fmt::Display| {
+            Error::new(
+                ErrorKind::Parsing,
+                &format!("Invalid value '{}' for {}: {}", s, field, e),
+            )
+        };
+        match field {
+            Field::Vid | Field::Pid => u16::from_str_radix(s.trim_start_matches("0x"), 16)
+                .map(Value::Number)
+                .map_err(|e| parse_error(&e)),
+            Field::Bus | Field::Number => s
+                .parse::<u8>()
+                .map(|v| Value::Number(v as u16))
+                .map_err(|e| parse_error(&e)),
+            Field::Name | Field::Serial | Field::Manufacturer => Ok(Value::Text(s.to_string())),
+            Field::Class => BaseClass::from_str(s, true)
+                .map(Value::Class)
+                .map_err(|e| parse_error(&e)),
+        }
+    }
+}
+
+/// A `field operator value` comparison of a [`Device`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Condition {
+    /// Field of the device compared
+    pub field: Field,
+    /// Comparison
+    pub operator: Operator,
+    /// Value compared with
+    pub value: Value,
+}
+
+impl Condition {
+    /// Whether `device` satisfies the condition
+    pub fn is_match(&self, device: &Device) -> bool {
+        let matched = match &self.value {
+            Value::Number(n) => {
+                let field = match self.field {
+                    Field::Vid => device.vendor_id,
+                    Field::Pid => device.product_id,
+                    Field::Bus => Some(device.location_id.bus as u16),
+                    Field::Number => Some(device.location_id.number as u16),
+                    _ => None,
+                };
+                field == Some(*n)
+            }
+            Value::Text(s) => {
+                let field = match self.field {
+                    Field::Name => Some(&device.name),
+                    Field::Serial => device.serial_num.as_ref(),
+                    Field::Manufacturer => device.manufacturer.as_ref(),
+                    _ => None,
+                };
+                field.is_some_and(|f| match self.operator {
+                    Operator::Match | Operator::NotMatch => f.contains(s.as_str()),
+                    Operator::Equal | Operator::NotEqual => f == s,
+                })
+            }
+            Value::Class(c) => device.class.as_ref() == Some(c) || device.has_interface_class(c),
+        };
+
+        matched != self.operator.is_negated()
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.value {
+            Value::Number(n) if matches!(self.field, Field::Vid | Field::Pid) => {
+                write!(f, "{}{}0x{:04x}", self.field, self.operator, n)
+            }
+            Value::Number(n) => write!(f, "{}{}{}", self.field, self.operator, n),
+            Value::Text(s) => write!(
+                f,
+                "{}{}\"{}\"",
+                self.field,
+                self.operator,
+                s.replace('\\', "\\\\").replace('"', "\\\"")
+            ),
+            Value::Class(c) => write!(
+                f,
+                "{}{}{}",
+                self.field,
+                self.operator,
+                c.to_possible_value()
+                    .map_or(String::new(), |v| v.get_name().to_string())
+            ),
+        }
+    }
+}
+
+/// Boolean combination of [`Condition`]s; parsed from and displayed as the expression string
+#[derive(Debug, Clone, PartialEq, Eq, SerializeDisplay, DeserializeFromStr)]
+pub enum FilterExpression {
+    /// Single comparison
+    Condition(Condition),
+    /// Negation of the expression
+    Not(Box<FilterExpression>),
+    /// Both expressions match
+    And(Box<FilterExpression>, Box<FilterExpression>),
+    /// Either expression matches
+    Or(Box<FilterExpression>, Box<FilterExpression>),
+}
+
+impl FilterExpression {
+    /// Whether `device` satisfies the expression
+    pub fn is_match(&self, device: &Device) -> bool {
+        match self {
+            FilterExpression::Condition(c) => c.is_match(device),
+            FilterExpression::Not(e) => !e.is_match(device),
+            FilterExpression::And(a, b) => a.is_match(device) && b.is_match(device),
+            FilterExpression::Or(a, b) => a.is_match(device) || b.is_match(device),
+        }
+    }
+
+    /// Whether any condition in the expression compares `field`; [`Field::Class`] requires the device extra to match interfaces
+    pub fn contains_field(&self, field: Field) -> bool {
+        match self {
+            FilterExpression::Condition(c) => c.field == field,
+            FilterExpression::Not(e) => e.contains_field(field),
+            FilterExpression::And(a, b) | FilterExpression::Or(a, b) => {
+                a.contains_field(field) || b.contains_field(field)
+            }
+        }
+    }
+}
+
+impl fmt::Display for FilterExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FilterExpression::Condition(c) => write!(f, "{}", c),
+            FilterExpression::Not(e) => match e.as_ref() {
+                FilterExpression::Condition(_) | FilterExpression::Not(_) => write!(f, "!{}", e),
+                _ => write!(f, "!({})", e),
+            },
+            FilterExpression::And(a, b) => {
+                for (i, e) in [a, b].into_iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " && ")?;
+                    }
+                    match e.as_ref() {
+                        FilterExpression::Or(_, _) => write!(f, "({})", e)?,
+                        _ => write!(f, "{}", e)?,
+                    }
+                }
+                Ok(())
+            }
+            FilterExpression::Or(a, b) => write!(f, "{} || {}", a, b),
+        }
+    }
+}
+
+impl FromStr for FilterExpression {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser { input: s, pos: 0 };
+        let expr = parser.parse_or()?;
+        parser.skip_whitespace();
+        if parser.pos < s.len() {
+            return Err(parser.error("unexpected trailing input"));
+        }
+        Ok(expr)
+    }
+}
+
+/// Recursive descent parser of the expression string
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn error(&self, msg: &str) -> Error {
+        Error::new(
+            ErrorKind::Parsing,
+            &format!(
+                "Invalid filter expression '{}' at {}: {}",
+                self.input, self.pos, msg
+            ),
+        )
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.pos = self.input.len() - self.rest().trim_start().len();
+    }
+
+    /// Consumes `token` after any whitespace if next
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<FilterExpression> {
+        let mut expr = self.parse_and()?;
+        while self.eat("||") {
+            expr = FilterExpression::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<FilterExpression> {
+        let mut expr = self.parse_unary()?;
+        while self.eat("&&") || self.eat(",") {
+            expr = FilterExpression::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<FilterExpression> {
+        if self.eat("!") {
+            Ok(FilterExpression::Not(Box::new(self.parse_unary()?)))
+        } else if self.eat("(") {
+            let expr = self.parse_or()?;
+            if !self.eat(")") {
+                return Err(self.error("expected ')'"));
+            }
+            Ok(expr)
+        } else {
+            self.parse_condition().map(FilterExpression::Condition)
+        }
+    }
+
+    fn parse_condition(&mut self) -> Result<Condition> {
+        self.skip_whitespace();
+        let len = self
+            .rest()
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(self.rest().len());
+        if len == 0 {
+            return Err(self.error("expected field"));
+        }
+        let field: Field = self.rest()[..len].parse()?;
+        self.pos += len;
+
+        let operator = if self.eat("==") {
+            Operator::Equal
+        } else if self.eat("!=") {
+            Operator::NotEqual
+        } else if self.eat("!~") {
+            Operator::NotMatch
+        } else if self.eat("=") || self.eat("~") {
+            Operator::Match
+        } else {
+            return Err(self.error("expected operator"));
+        };
+
+        let value = self.parse_value()?;
+        Ok(Condition {
+            field,
+            operator,
+            value: Value::parse(field, &value)?,
+        })
+    }
+
+    fn parse_value(&mut self) -> Result<String> {
+        if self.eat("\"") {
+            let mut value = String::new();
+            let mut chars = self.rest().char_indices();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '"' => {
+                        self.pos += i + 1;
+                        return Ok(value);
+                    }
+                    '\\' => match chars.next() {
+                        Some((_, e)) => value.push(e),
+                        None => break,
+                    },
+                    _ => value.push(c),
+                }
+            }
+            return Err(self.error("unterminated quoted value"));
+        }
+
+        let rest = self.rest();
+        let len = ["&&", "||", ",", ")"]
+            .iter()
+            .filter_map(|t| rest.find(t))
+            .min()
+            .unwrap_or(rest.len());
+        let value = rest[..len].trim();
+        if value.is_empty() {
+            return Err(self.error("expected value"));
+        }
+        self.pos += len;
+        Ok(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::DeviceLocation;
+
+    fn device(name: &str, vid: u16, serial: Option<&str>) -> Device {
+        Device {
+            name: name.into(),
+            vendor_id: Some(vid),
+            serial_num: serial.map(String::from),
+            location_id: DeviceLocation {
+                bus: 1,
+                number: 4,
+                tree_positions: vec![1],
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_precedence() {
+        let expr: FilterExpression = "vid==0x046d && bus=2 || name~\"Webcam\"".parse().unwrap();
+        assert!(expr.is_match(&device("HD Webcam", 0x1234, None)));
+        assert!(!expr.is_match(&device("Mouse", 0x046d, None)));
+
+        let expr: FilterExpression = "vid==0x046d && (bus=2 || name~Mouse)".parse().unwrap();
+        assert!(expr.is_match(&device("Mouse", 0x046d, None)));
+        assert!(!expr.is_match(&device("Keyboard", 0x046d, None)));
+    }
+
+    #[test]
+    fn test_operators() {
+        let d = device("Black Magic Probe", 0x1d50, Some("97B6A11D"));
+        let is_match = |s: &str| s.parse::<FilterExpression>().unwrap().is_match(&d);
+        assert!(is_match("name=Black Magic"));
+        assert!(!is_match("name==Black Magic"));
+        assert!(is_match("name==\"Black Magic Probe\""));
+        assert!(is_match("serial!~FFFF, number=4"));
+        assert!(is_match("!(vid=1d50 && pid=6018)"));
+        assert!(!is_match("manufacturer~Black"));
+        assert!(is_match("manufacturer!~Black"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for s in [
+            "",
+            "vid",
+            "vid==",
+            "foo=1",
+            "vid=zz",
+            "(vid=1",
+            "name=\"a",
+            "bus=1 bus",
+        ] {
+            assert!(s.parse::<FilterExpression>().is_err(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let s = "!(vid==0x1d50 || class~hid) && name~\"a \\\"b\\\"\" || bus!=2";
+        let expr: FilterExpression = s.parse().unwrap();
+        assert_eq!(expr.to_string(), s);
+        assert_eq!(expr.to_string().parse::<FilterExpression>().unwrap(), expr);
+    }
+}
@@ -0,0 +1,545 @@
+//! USB device controllers (UDC) and configfs gadgets from sysfs on Linux.
+//!
+//! On embedded targets the same system can be a USB host and a USB device. The device role is a device controller in `/sys/class/udc` with the gadget bound to it, which is normally composed in configfs at `/sys/kernel/config/usb_gadget` from functions such as 'acm.usb0' or 'ecm.usb0'; the functions in the configurations of a bound gadget are those active.
+//!
+//! ```no_run
+//! use cyme::profiler::gadget;
+//!
+//! let gadgets = gadget::get_gadgets().unwrap();
+//! for udc in gadgets.controllers {
+//!     println!("{}", udc);
+//! }
+//! ```
+//!
+//! A dual-role port with a USB role switch in `/sys/class/usb_role` reports whether it is currently the host or device; see [`get_role_switches`].
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::error::{Error, ErrorKind, Result};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SYSFS_UDC_PREFIX: &str = "/sys/class/udc/";
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const CONFIGFS_GADGET_PREFIX: &str = "/sys/kernel/config/usb_gadget/";
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SYSFS_USB_ROLE_PREFIX: &str = "/sys/class/usb_role/";
+
+/// Device controllers and configfs gadgets; see [`get_gadgets`]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GadgetProfile {
+    /// Device controllers with the gadget bound to each
+    #[serde(default)]
+    pub controllers: Vec<DeviceController>,
+    /// configfs gadgets not bound to a controller, so none of their functions are active
+    #[serde(default)]
+    pub unbound: Vec<Gadget>,
+}
+
+/// A USB device controller (UDC), the device role of a dual-role or peripheral-only USB port
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceController {
+    /// sysfs name such as 'fe980000.usb' or 'dummy_udc.0'
+    pub name: String,
+    /// USB device state such as 'not attached', 'configured' or 'suspended'
+    pub state: Option<String>,
+    /// Speed connected to the host at such as 'high-speed'; 'UNKNOWN' when not connected
+    pub current_speed: Option<String>,
+    /// Fastest speed the controller supports such as 'super-speed'
+    pub maximum_speed: Option<String>,
+    /// Name of the gadget driver bound to the controller; the gadget name for configfs gadgets
+    pub function: Option<String>,
+    /// Whether the controller is On-The-Go capable
+    pub is_otg: Option<bool>,
+    /// Current role of the port from its USB role switch if dual-role
+    pub role: Option<UsbRole>,
+    /// configfs gadget bound to the controller
+    pub gadget: Option<Gadget>,
+}
+
+impl fmt::Display for DeviceController {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(s) = self.state.as_ref() {
+            write!(f, " {}", s)?;
+        }
+        if let Some(s) = self.current_speed.as_ref().filter(|s| *s != "UNKNOWN") {
+            write!(f, " {}", s)?;
+        }
+        if let Some(s) = self.maximum_speed.as_ref() {
+            write!(f, " (max {})", s)?;
+        }
+        if self.is_otg == Some(true) {
+            write!(f, " OTG")?;
+        }
+        if let Some(role) = self.role {
+            write!(f, " {} role", role)?;
+        }
+        // legacy gadget drivers such as g_serial have no configfs gadget
+        if let (None, Some(function)) = (self.gadget.as_ref(), self.function.as_ref()) {
+            write!(f, " function: {}", function)?;
+        }
+        Ok(())
+    }
+}
+
+/// Current role of a dual-role port, as the USB role switch reports it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum UsbRole {
+    /// Neither role, normally nothing is connected
+    None,
+    /// USB host with the host controller bus active
+    Host,
+    /// USB device with the device controller active
+    Device,
+}
+
+impl fmt::Display for UsbRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UsbRole::None => write!(f, "none"),
+            UsbRole::Host => write!(f, "host"),
+            UsbRole::Device => write!(f, "device"),
+        }
+    }
+}
+
+impl FromStr for UsbRole {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "none" => Ok(UsbRole::None),
+            "host" => Ok(UsbRole::Host),
+            "device" => Ok(UsbRole::Device),
+            _ => Err(Error::new(
+                ErrorKind::Parsing,
+                &format!("Invalid USB role '{}'", s),
+            )),
+        }
+    }
+}
+
+/// A USB role switch from `/sys/class/usb_role` selecting the role of a dual-role port
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleSwitch {
+    /// sysfs name such as '11200000.usb-role-switch'
+    pub name: String,
+    /// Current role
+    pub role: UsbRole,
+    /// sysfs path of the device the switch belongs to, normally the dual-role controller
+    pub controller: PathBuf,
+}
+
+impl RoleSwitch {
+    /// Whether the canonical sysfs path `path` is the controller of the switch or below it, such as the root hub of the controller host role or its device controller
+    pub fn is_for(&self, path: &Path) -> bool {
+        path.starts_with(&self.controller)
+    }
+}
+
+/// A gadget composed in configfs from functions grouped into configurations
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Gadget {
+    /// configfs directory name such as 'g1'
+    pub name: String,
+    /// Vendor ID the gadget enumerates with
+    pub vendor_id: Option<u16>,
+    /// Product ID the gadget enumerates with
+    pub product_id: Option<u16>,
+    /// Manufacturer string
+    pub manufacturer: Option<String>,
+    /// Product string
+    pub product: Option<String>,
+    /// Serial number string
+    pub serial_num: Option<String>,
+    /// Name of the device controller the gadget is bound to
+    pub udc: Option<String>,
+    /// Configurations of the gadget
+    #[serde(default)]
+    pub configurations: Vec<GadgetConfiguration>,
+}
+
+impl fmt::Display for Gadget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let (Some(v), Some(p)) = (self.vendor_id, self.product_id) {
+            write!(f, " {:04x}:{:04x}", v, p)?;
+        }
+        if let Some(m) = self.manufacturer.as_ref() {
+            write!(f, " {}", m)?;
+        }
+        if let Some(p) = self.product.as_ref() {
+            write!(f, " {}", p)?;
+        }
+        if let Some(s) = self.serial_num.as_ref() {
+            write!(f, " {}", s)?;
+        }
+        Ok(())
+    }
+}
+
+impl Gadget {
+    /// Functions linked into any configuration, which are active if the gadget is bound to a controller; no duplicates
+    pub fn functions(&self) -> Vec<&str> {
+        let mut ret: Vec<&str> = Vec::new();
+        for function in self.configurations.iter().flat_map(|c| c.functions.iter()) {
+            if !ret.contains(&function.as_str()) {
+                ret.push(function);
+            }
+        }
+        ret
+    }
+}
+
+/// A configuration of a [`Gadget`]
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GadgetConfiguration {
+    /// configfs directory name as label.number such as 'c.1'
+    pub name: String,
+    /// Configuration string
+    pub description: Option<String>,
+    /// Maximum power in mA
+    pub max_power: Option<u16>,
+    /// Functions linked into the configuration as type.instance such as 'acm.usb0'
+    #[serde(default)]
+    pub functions: Vec<String>,
+}
+
+impl fmt::Display for GadgetConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(d) = self.description.as_ref() {
+            write!(f, " {}", d)?;
+        }
+        if let Some(p) = self.max_power {
+            write!(f, " {} mA", p)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn read_string(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path)
+        .ok()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+}
+
+/// Names of the entries in `path`, sorted; empty if it cannot be read
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn read_names(path: &Path) -> Vec<String> {
+    let mut ret: Vec<String> = std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    ret.sort();
+    ret
+}
+
+/// Strings of the first language in the 'strings' directory of a gadget or configuration, normally '0x409'
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn strings_path(path: &Path) -> Option<std::path::PathBuf> {
+    let strings = path.join("strings");
+    read_names(&strings).first().map(|l| strings.join(l))
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl DeviceController {
+    fn from_sysfs(path: &Path) -> Self {
+        DeviceController {
+            name: path
+                .file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            state: read_string(&path.join("state")),
+            current_speed: read_string(&path.join("current_speed")),
+            maximum_speed: read_string(&path.join("maximum_speed")),
+            function: read_string(&path.join("function")),
+            is_otg: read_string(&path.join("is_otg")).map(|s| s != "0"),
+            role: None,
+            gadget: None,
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl Gadget {
+    fn from_configfs(path: &Path) -> Self {
+        let hex = |attr: &str| {
+            read_string(&path.join(attr))
+                .and_then(|s| u16::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+        };
+        let strings = strings_path(path);
+        let string = |name: &str| strings.as_ref().and_then(|s| read_string(&s.join(name)));
+
+        Gadget {
+            name: path
+                .file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            vendor_id: hex("idVendor"),
+            product_id: hex("idProduct"),
+            manufacturer: string("manufacturer"),
+            product: string("product"),
+            serial_num: string("serialnumber"),
+            udc: read_string(&path.join("UDC")),
+            configurations: read_names(&path.join("configs"))
+                .iter()
+                .map(|c| GadgetConfiguration::from_configfs(&path.join("configs").join(c)))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl GadgetConfiguration {
+    fn from_configfs(path: &Path) -> Self {
+        // functions are linked into the configuration directory
+        let functions = read_names(path)
+            .into_iter()
+            .filter(|n| path.join(n).is_symlink())
+            .filter_map(|n| {
+                std::fs::read_link(path.join(n))
+                    .ok()
+                    .and_then(|p| p.file_name().map(|f| f.to_string_lossy().to_string()))
+            })
+            .collect();
+
+        GadgetConfiguration {
+            name: path
+                .file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            description: strings_path(path).and_then(|s| read_string(&s.join("configuration"))),
+            max_power: read_string(&path.join("MaxPower")).and_then(|s| s.parse().ok()),
+            functions,
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+impl RoleSwitch {
+    fn from_sysfs(path: &Path) -> Option<Self> {
+        // class devices are in a 'usb_role' directory of the device they belong to
+        let controller = std::fs::canonicalize(path)
+            .ok()?
+            .parent()?
+            .parent()?
+            .to_path_buf();
+        Some(RoleSwitch {
+            name: path.file_name()?.to_string_lossy().to_string(),
+            role: read_string(&path.join("role"))?.parse().ok()?,
+            controller,
+        })
+    }
+}
+
+/// Current role of the dual-role port the canonical sysfs path `path` belongs to from `switches`
+pub fn role_of(path: &Path, switches: &[RoleSwitch]) -> Option<UsbRole> {
+    switches.iter().find(|s| s.is_for(path)).map(|s| s.role)
+}
+
+/// Attach each gadget in `gadgets` to the controller in `controllers` it is bound to, returning the [`GadgetProfile`] with those not bound
+pub fn assemble(mut controllers: Vec<DeviceController>, gadgets: Vec<Gadget>) -> GadgetProfile {
+    let mut unbound = Vec::new();
+    for gadget in gadgets {
+        match controllers
+            .iter_mut()
+            .find(|c| gadget.udc.as_ref() == Some(&c.name) && c.gadget.is_none())
+        {
+            Some(controller) => controller.gadget = Some(gadget),
+            None => unbound.push(gadget),
+        }
+    }
+
+    GadgetProfile {
+        controllers,
+        unbound,
+    }
+}
+
+/// Get the device controllers and configfs gadgets from sysfs; Linux only
+///
+/// Returns an [`ErrorKind::NotFound`] error if there is no device controller class, which is the case on most hosts. Gadgets are empty if configfs is not mounted.
+pub fn get_gadgets() -> Result<GadgetProfile> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let entries = std::fs::read_dir(SYSFS_UDC_PREFIX).map_err(|e| {
+            Error::new(
+                ErrorKind::NotFound,
+                &format!(
+                    "Failed to read {}, does the system have a USB device controller? Error({})",
+                    SYSFS_UDC_PREFIX, e
+                ),
+            )
+        })?;
+        let switches = get_role_switches().unwrap_or_default();
+        let mut controllers: Vec<DeviceController> = entries
+            .flatten()
+            .map(|e| {
+                let mut controller = DeviceController::from_sysfs(&e.path());
+                controller.role = std::fs::canonicalize(e.path())
+                    .ok()
+                    .and_then(|p| role_of(&p, &switches));
+                controller
+            })
+            .collect();
+        controllers.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let configfs = Path::new(CONFIGFS_GADGET_PREFIX);
+        let gadgets = read_names(configfs)
+            .iter()
+            .map(|g| Gadget::from_configfs(&configfs.join(g)))
+            .collect();
+
+        Ok(assemble(controllers, gadgets))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    return Err(Error::new(
+        ErrorKind::Unsupported,
+        "USB device controllers are only available on Linux",
+    ));
+}
+
+/// Get the USB role switches of dual-role ports from sysfs; Linux only
+///
+/// Returns an [`ErrorKind::NotFound`] error if there is no role switch class, which is the case on systems without dual-role ports.
+pub fn get_role_switches() -> Result<Vec<RoleSwitch>> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let entries = std::fs::read_dir(SYSFS_USB_ROLE_PREFIX).map_err(|e| {
+            Error::new(
+                ErrorKind::NotFound,
+                &format!(
+                    "Failed to read {}, does the system have a dual-role port? Error({})",
+                    SYSFS_USB_ROLE_PREFIX, e
+                ),
+            )
+        })?;
+        let mut switches: Vec<RoleSwitch> = entries
+            .flatten()
+            .filter_map(|e| RoleSwitch::from_sysfs(&e.path()))
+            .collect();
+        switches.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(switches)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    return Err(Error::new(
+        ErrorKind::Unsupported,
+        "USB role switches are only available on Linux",
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assemble_and_display() {
+        let gadget = Gadget {
+            name: String::from("g1"),
+            vendor_id: Some(0x1d6b),
+            product_id: Some(0x0104),
+            product: Some(String::from("Composite Gadget")),
+            udc: Some(String::from("fe980000.usb")),
+            configurations: vec![GadgetConfiguration {
+                name: String::from("c.1"),
+                description: Some(String::from("CDC")),
+                max_power: Some(250),
+                functions: vec![String::from("acm.usb0"), String::from("ecm.usb0")],
+            }],
+            ..Default::default()
+        };
+        let unbound = Gadget {
+            name: String::from("g2"),
+            ..Default::default()
+        };
+        let udc = DeviceController {
+            name: String::from("fe980000.usb"),
+            state: Some(String::from("configured")),
+            current_speed: Some(String::from("high-speed")),
+            maximum_speed: Some(String::from("high-speed")),
+            function: Some(String::from("g1")),
+            ..Default::default()
+        };
+
+        let profile = assemble(vec![udc], vec![gadget, unbound]);
+        assert_eq!(profile.unbound.len(), 1);
+        let udc = &profile.controllers[0];
+        assert_eq!(
+            udc.to_string(),
+            "fe980000.usb configured high-speed (max high-speed)"
+        );
+        let gadget = udc.gadget.as_ref().unwrap();
+        assert_eq!(gadget.to_string(), "g1 1d6b:0104 Composite Gadget");
+        assert_eq!(gadget.configurations[0].to_string(), "c.1 CDC 250 mA");
+        assert_eq!(gadget.functions(), vec!["acm.usb0", "ecm.usb0"]);
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn test_gadget_from_configfs() {
+        let root = std::env::temp_dir().join(format!("cyme-gadget-{}", std::process::id()));
+        let gadget = root.join("g1");
+        let config = gadget.join("configs/c.1");
+        std::fs::create_dir_all(gadget.join("functions/acm.usb0")).unwrap();
+        std::fs::create_dir_all(gadget.join("strings/0x409")).unwrap();
+        std::fs::create_dir_all(config.join("strings/0x409")).unwrap();
+        std::fs::write(gadget.join("idVendor"), "0x1d6b\n").unwrap();
+        std::fs::write(gadget.join("idProduct"), "0x0104\n").unwrap();
+        std::fs::write(gadget.join("UDC"), "\n").unwrap();
+        std::fs::write(gadget.join("strings/0x409/product"), "Serial\n").unwrap();
+        std::fs::write(config.join("MaxPower"), "120\n").unwrap();
+        std::os::unix::fs::symlink(gadget.join("functions/acm.usb0"), config.join("acm.usb0"))
+            .unwrap();
+
+        let parsed = Gadget::from_configfs(&gadget);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(parsed.vendor_id, Some(0x1d6b));
+        assert_eq!(parsed.product.as_deref(), Some("Serial"));
+        // an empty UDC is unbound
+        assert_eq!(parsed.udc, None);
+        assert_eq!(parsed.configurations[0].max_power, Some(120));
+        assert_eq!(parsed.functions(), vec!["acm.usb0"]);
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn test_role_switch_from_sysfs() {
+        let root = std::env::temp_dir().join(format!("cyme-role-{}", std::process::id()));
+        let controller = root.join("devices/platform/11200000.usb");
+        let switch = controller.join("usb_role/11200000.usb-role-switch");
+        std::fs::create_dir_all(&switch).unwrap();
+        std::fs::create_dir_all(controller.join("xhci-hcd.0.auto/usb1")).unwrap();
+        std::fs::create_dir_all(root.join("class/usb_role")).unwrap();
+        std::fs::write(switch.join("role"), "host\n").unwrap();
+        let link = root.join("class/usb_role/11200000.usb-role-switch");
+        std::os::unix::fs::symlink(&switch, &link).unwrap();
+
+        let parsed = RoleSwitch::from_sysfs(&link).unwrap();
+        let root_hub = std::fs::canonicalize(controller.join("xhci-hcd.0.auto/usb1")).unwrap();
+        let other = std::fs::canonicalize(root.join("devices/platform")).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(parsed.name, "11200000.usb-role-switch");
+        assert_eq!(parsed.role, UsbRole::Host);
+        let switches = vec![parsed];
+        assert_eq!(role_of(&root_hub, &switches), Some(UsbRole::Host));
+        assert_eq!(role_of(&other, &switches), None);
+    }
+}
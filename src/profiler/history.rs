@@ -0,0 +1,425 @@
+//! Persistent history of devices connecting and disconnecting, recorded by `cyme watch --history` and queried with `cyme history`
+//!
+//! Each [`WatchEvent`] is appended to a JSON lines file as a [`HistoryRecord`] of the device identity and time, so the history survives reboots and can be read with other tools. [`summarize`] groups the records by device to show when each was first and last seen and how many times it connected; a device connecting many times on the same port is re-enumerating, which is often a sign of a flaky cable, hub or firmware.
+//!
+//! ```
+//! use cyme::profiler::history::{self, HistoryEvent, HistoryRecord};
+//! use cyme::profiler::watch::WatchEvent;
+//! use cyme::profiler::Device;
+//!
+//! let device = Device {
+//!     vendor_id: Some(0x1d50),
+//!     product_id: Some(0x6018),
+//!     serial_num: Some(String::from("ABC123")),
+//!     ..Default::default()
+//! };
+//! let records = [
+//!     HistoryRecord::new(&WatchEvent::Connected(device.clone()), 100),
+//!     HistoryRecord::new(&WatchEvent::Disconnected(device.clone()), 160),
+//!     HistoryRecord::new(&WatchEvent::Connected(device), 165),
+//! ];
+//! let summary = history::summarize(&records);
+//! assert_eq!(summary.len(), 1);
+//! assert_eq!((summary[0].first_seen, summary[0].last_seen), (100, 165));
+//! assert_eq!(summary[0].re_enumerations(), 1);
+//! assert_eq!(summary[0].last_event, HistoryEvent::Connected);
+//! ```
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::watch::WatchEvent;
+use super::{Device, DeviceLocation, Filter};
+use crate::error::{Error, ErrorKind, Result};
+use crate::redact::Redaction;
+use crate::usb::PortPath;
+
+/// Name of the history file within the cyme data directory
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// Event of a [`HistoryRecord`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HistoryEvent {
+    /// Device connected
+    Connected,
+    /// Device disconnected
+    Disconnected,
+    /// Device was already connected when recording started; not counted as a connection
+    Present,
+}
+
+impl fmt::Display for HistoryEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryEvent::Connected => write!(f, "connected"),
+            HistoryEvent::Disconnected => write!(f, "disconnected"),
+            HistoryEvent::Present => write!(f, "present"),
+        }
+    }
+}
+
+/// A device event in the history: the time and enough of the [`Device`] to identify it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    /// Unix time in seconds of the event
+    pub time: u64,
+    /// What happened
+    pub event: HistoryEvent,
+    /// Port path of the device
+    pub port_path: PortPath,
+    /// Device number on the bus
+    pub number: u8,
+    /// Vendor ID
+    pub vendor_id: Option<u16>,
+    /// Product ID
+    pub product_id: Option<u16>,
+    /// Device name
+    pub name: String,
+    /// Device manufacturer
+    pub manufacturer: Option<String>,
+    /// Device serial number
+    pub serial_num: Option<String>,
+}
+
+impl HistoryRecord {
+    /// Record of `event` at unix `time`
+    pub fn new(event: &WatchEvent, time: u64) -> Self {
+        let d = event.device();
+        HistoryRecord {
+            time,
+            event: if event.is_connected() {
+                HistoryEvent::Connected
+            } else {
+                HistoryEvent::Disconnected
+            },
+            port_path: d.path(),
+            number: d.location_id.number,
+            vendor_id: d.vendor_id,
+            product_id: d.product_id,
+            name: d.name.to_owned(),
+            manufacturer: d.manufacturer.to_owned(),
+            serial_num: d.serial_num.to_owned(),
+        }
+    }
+
+    /// [`Device`] with the values of the record so it can be matched with a [`super::Filter`]
+    pub fn to_device(&self) -> Device {
+        Device {
+            location_id: DeviceLocation {
+                bus: self.port_path.bus,
+                tree_positions: self.port_path.ports.clone(),
+                number: self.number,
+            },
+            vendor_id: self.vendor_id,
+            product_id: self.product_id,
+            name: self.name.to_owned(),
+            manufacturer: self.manufacturer.to_owned(),
+            serial_num: self.serial_num.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Key identifying the same device across records: VID:PID and serial, or the port path if it has no serial
+    fn key(&self) -> (Option<u16>, Option<u16>, String) {
+        let id = match self.serial_num.as_deref() {
+            Some(s) if !s.is_empty() => s.to_string(),
+            _ => format!("@{}", self.port_path),
+        };
+        (self.vendor_id, self.product_id, id)
+    }
+}
+
+impl fmt::Display for HistoryRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex = |v: Option<u16>| v.map_or(String::from("----"), |v| format!("{:04x}", v));
+        write!(
+            f,
+            "{} {} {} {}:{} '{}' serial {}",
+            utc_timestamp(self.time),
+            self.event,
+            self.port_path,
+            hex(self.vendor_id),
+            hex(self.product_id),
+            self.name,
+            self.serial_num.as_deref().unwrap_or("-")
+        )
+    }
+}
+
+/// History of one device summarized from its [`HistoryRecord`]s by [`summarize`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeviceHistory {
+    /// Vendor ID
+    pub vendor_id: Option<u16>,
+    /// Product ID
+    pub product_id: Option<u16>,
+    /// Device name when last seen
+    pub name: String,
+    /// Device serial number
+    pub serial_num: Option<String>,
+    /// Unix time in seconds of the first record
+    pub first_seen: u64,
+    /// Unix time in seconds of the last record
+    pub last_seen: u64,
+    /// Number of times the device connected
+    pub connects: usize,
+    /// Event of the first record
+    pub first_event: HistoryEvent,
+    /// Event of the last record; the device is probably still connected unless [`HistoryEvent::Disconnected`]
+    pub last_event: HistoryEvent,
+    /// Port paths the device has been seen at, in the order first seen
+    pub ports: Vec<String>,
+}
+
+impl DeviceHistory {
+    /// Number of times the device connected again after it was first seen
+    pub fn re_enumerations(&self) -> usize {
+        if self.first_event == HistoryEvent::Connected {
+            self.connects.saturating_sub(1)
+        } else {
+            self.connects
+        }
+    }
+}
+
+impl fmt::Display for DeviceHistory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex = |v: Option<u16>| v.map_or(String::from("----"), |v| format!("{:04x}", v));
+        write!(
+            f,
+            "{}:{} '{}' serial {}: first seen {}, last seen {} {}, re-enumerated {} time(s) on {}",
+            hex(self.vendor_id),
+            hex(self.product_id),
+            self.name,
+            self.serial_num.as_deref().unwrap_or("-"),
+            utc_timestamp(self.first_seen),
+            utc_timestamp(self.last_seen),
+            self.last_event,
+            self.re_enumerations(),
+            self.ports.join(", ")
+        )
+    }
+}
+
+/// Default history path in the user data directory: '$XDG_DATA_HOME/cyme/history.jsonl' on Linux
+///
+/// `None` without the 'config' feature so a history path must be supplied.
+pub fn default_path() -> Option<PathBuf> {
+    #[cfg(feature = "config")]
+    return dirs::data_dir().map(|d| d.join("cyme").join(HISTORY_FILE));
+    #[cfg(not(feature = "config"))]
+    return None;
+}
+
+/// Unix time now in seconds
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn utc_timestamp(time: u64) -> String {
+    crate::logger::utc_timestamp(UNIX_EPOCH + std::time::Duration::from_secs(time))
+}
+
+/// Append `records` to the history at `path`, creating it and parent directories if required
+pub fn append(path: &Path, records: &[HistoryRecord]) -> Result<()> {
+    if records.is_empty() {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut lines = String::new();
+    for record in records {
+        lines.push_str(&serde_json::to_string(record)?);
+        lines.push('\n');
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    // one write so concurrent watchers do not interleave lines
+    file.write_all(lines.as_bytes())?;
+    Ok(())
+}
+
+/// Read the history at `path` in the order recorded
+///
+/// Lines which do not parse, such as one partially written when cyme was killed, are skipped with a warning.
+pub fn read(path: &Path) -> Result<Vec<HistoryRecord>> {
+    let data = fs::read_to_string(path).map_err(|e| {
+        Error::new(
+            ErrorKind::Io,
+            &format!("Failed to read history at {:?}; Error({})", path, e),
+        )
+    })?;
+    Ok(data
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .filter_map(|(i, l)| match serde_json::from_str(l) {
+            Ok(r) => Some(r),
+            Err(e) => {
+                log::warn!("Skipping history {:?} line {}: {}", path, i + 1, e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Summarize `records` by device, identified by VID:PID and serial or port path if it has none, ordered by when each was last seen
+pub fn summarize<'a, I: IntoIterator<Item = &'a HistoryRecord>>(records: I) -> Vec<DeviceHistory> {
+    let mut devices: HashMap<(Option<u16>, Option<u16>, String), DeviceHistory> = HashMap::new();
+    for record in records {
+        let history = devices
+            .entry(record.key())
+            .or_insert_with(|| DeviceHistory {
+                vendor_id: record.vendor_id,
+                product_id: record.product_id,
+                name: record.name.to_owned(),
+                serial_num: record.serial_num.to_owned(),
+                first_seen: record.time,
+                last_seen: record.time,
+                connects: 0,
+                first_event: record.event,
+                last_event: record.event,
+                ports: Vec::new(),
+            });
+        history.first_seen = history.first_seen.min(record.time);
+        history.last_seen = history.last_seen.max(record.time);
+        history.name = record.name.to_owned();
+        history.last_event = record.event;
+        if record.event == HistoryEvent::Connected {
+            history.connects += 1;
+        }
+        let port = record.port_path.to_string();
+        if !history.ports.contains(&port) {
+            history.ports.push(port);
+        }
+    }
+    let mut ret: Vec<DeviceHistory> = devices.into_values().collect();
+    ret.sort_by_key(|h| (h.last_seen, h.first_seen));
+    ret
+}
+
+/// Records of the history at `path` of devices matching `filter` for `cyme history --events`, redacted with `redaction`
+pub fn run_events(
+    path: &Path,
+    filter: &Filter,
+    redaction: Option<&Redaction>,
+) -> Result<Vec<HistoryRecord>> {
+    let mut records = read_matching(path, filter)?;
+    if let Some(redaction) = redaction {
+        redaction.apply_history(&mut records)?;
+    }
+
+    Ok(records)
+}
+
+/// [`summarize`] of the history at `path` of devices matching `filter` for `cyme history`, redacted with `redaction`
+pub fn run(
+    path: &Path,
+    filter: &Filter,
+    redaction: Option<&Redaction>,
+) -> Result<Vec<DeviceHistory>> {
+    // summarized before redacting so masks do not merge devices
+    let mut summary = summarize(&read_matching(path, filter)?);
+    if let Some(redaction) = redaction {
+        redaction.apply_history_summary(&mut summary)?;
+    }
+
+    Ok(summary)
+}
+
+/// [`read`] the records at `path` of devices matching `filter`
+fn read_matching(path: &Path, filter: &Filter) -> Result<Vec<HistoryRecord>> {
+    Ok(read(path)?
+        .into_iter()
+        .filter(|r| filter.is_match(&r.to_device()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::read_json_dump;
+
+    #[test]
+    fn test_append_read() {
+        let path = std::env::temp_dir()
+            .join(format!("cyme-history-{}", std::process::id()))
+            .join(HISTORY_FILE);
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let records: Vec<HistoryRecord> = spusb
+            .iter()
+            .map(|d| HistoryRecord::new(&WatchEvent::Connected(d.clone()), 1))
+            .collect();
+        append(&path, &records).unwrap();
+        // partial line from being killed mid write
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"{\"time\":2,\"ev")
+            .unwrap();
+        assert_eq!(read(&path).unwrap(), records);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_summarize_keys() {
+        let device = Device {
+            vendor_id: Some(0x1d50),
+            product_id: Some(0x6018),
+            location_id: DeviceLocation {
+                bus: 1,
+                tree_positions: vec![2],
+                number: 3,
+            },
+            ..Default::default()
+        };
+        let moved = Device {
+            location_id: DeviceLocation {
+                bus: 1,
+                tree_positions: vec![4],
+                number: 5,
+            },
+            ..device.clone()
+        };
+        let present = HistoryRecord {
+            event: HistoryEvent::Present,
+            ..HistoryRecord::new(&WatchEvent::Connected(device.clone()), 10)
+        };
+        let records = [
+            present,
+            HistoryRecord::new(&WatchEvent::Disconnected(device.clone()), 20),
+            HistoryRecord::new(&WatchEvent::Connected(device), 30),
+            HistoryRecord::new(&WatchEvent::Connected(moved.clone()), 40),
+        ];
+        // no serial so different ports are different devices
+        let summary = summarize(&records);
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].re_enumerations(), 1);
+        assert_eq!(summary[0].ports, vec!["1-2"]);
+        assert_eq!(summary[1].re_enumerations(), 0);
+
+        let records: Vec<HistoryRecord> = records
+            .iter()
+            .map(|r| HistoryRecord {
+                serial_num: Some(String::from("ABC")),
+                ..r.clone()
+            })
+            .collect();
+        let summary = summarize(&records);
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].ports, vec!["1-2", "1-4"]);
+        assert_eq!(summary[0].re_enumerations(), 2);
+    }
+}
@@ -0,0 +1,300 @@
+//! Iterators over the [`Device`] tree of a [`SystemProfile`], [`Bus`] or [`Device`] without flattening into a `Vec`
+//!
+//! Traversal is depth first by default, the same order as the tree is displayed, or breadth first with [`Traversal::BreadthFirst`].
+//!
+//! ```
+//! use cyme::profiler::iter::Traversal;
+//! use cyme::profiler::read_json_dump;
+//!
+//! let mut sp = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+//! assert_eq!(sp.iter().count(), sp.len());
+//! assert!(sp.iter().any(|d| d.port_path() == "2-2.8"));
+//!
+//! // depth first visits the children of a hub before its next sibling
+//! let paths: Vec<String> = sp.get_bus(2).unwrap().iter().map(|d| d.port_path()).collect();
+//! assert_eq!(paths, vec!["2-0:1.0", "2-2", "2-2.1", "2-2.8"]);
+//!
+//! // a `&mut Device` iterator would alias the children of each yielded device so mutation is with a closure
+//! sp.for_each_mut(Traversal::BreadthFirst, |d| d.serial_num = None);
+//! assert!(sp.iter().all(|d| d.serial_num.is_none()));
+//! ```
+use std::collections::VecDeque;
+
+use super::{Bus, Device, SystemProfile};
+
+/// Order [`Device`]s in the tree are visited
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Traversal {
+    /// Each device followed by its children before its next sibling
+    #[default]
+    DepthFirst,
+    /// Each level of the tree before the next level
+    BreadthFirst,
+}
+
+/// Iterator of references to [`Device`]s in a tree; see [`Device::iter`]
+#[derive(Debug, Clone)]
+pub struct Iter<'a> {
+    queue: VecDeque<&'a Device>,
+    traversal: Traversal,
+}
+
+impl<'a> Iter<'a> {
+    fn new<I: IntoIterator<Item = &'a Device>>(devices: I, traversal: Traversal) -> Self {
+        Iter {
+            queue: devices.into_iter().collect(),
+            traversal,
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Device;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let device = self.queue.pop_front()?;
+        let children = device.devices.iter().flatten();
+        match self.traversal {
+            Traversal::DepthFirst => children.rev().for_each(|d| self.queue.push_front(d)),
+            Traversal::BreadthFirst => self.queue.extend(children),
+        }
+
+        Some(device)
+    }
+}
+
+/// Iterator of owned [`Device`]s in a tree, each with `devices` taken; see [`Device::into_iter`]
+#[derive(Debug)]
+pub struct IntoIter {
+    queue: VecDeque<Device>,
+    traversal: Traversal,
+}
+
+impl IntoIter {
+    fn new<I: IntoIterator<Item = Device>>(devices: I, traversal: Traversal) -> Self {
+        IntoIter {
+            queue: devices.into_iter().collect(),
+            traversal,
+        }
+    }
+}
+
+impl Iterator for IntoIter {
+    type Item = Device;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut device = self.queue.pop_front()?;
+        let children = device.devices.take().into_iter().flatten();
+        match self.traversal {
+            Traversal::DepthFirst => children.rev().for_each(|d| self.queue.push_front(d)),
+            Traversal::BreadthFirst => self.queue.extend(children),
+        }
+
+        Some(device)
+    }
+}
+
+fn for_each_mut<'a, I, F>(devices: I, traversal: Traversal, mut f: F)
+where
+    I: IntoIterator<Item = &'a mut Device>,
+    F: FnMut(&mut Device),
+{
+    let mut queue: VecDeque<&mut Device> = devices.into_iter().collect();
+    while let Some(device) = queue.pop_front() {
+        f(device);
+        let children = device.devices.iter_mut().flatten();
+        match traversal {
+            Traversal::DepthFirst => children.rev().for_each(|d| queue.push_front(d)),
+            Traversal::BreadthFirst => queue.extend(children),
+        }
+    }
+}
+
+impl Device {
+    /// Returns an iterator of self and all [`Device`]s below it in the tree, depth first
+    pub fn iter(&self) -> Iter<'_> {
+        self.iter_with(Traversal::DepthFirst)
+    }
+
+    /// Returns an iterator of self and all [`Device`]s below it in the tree in `traversal` order
+    pub fn iter_with(&self, traversal: Traversal) -> Iter<'_> {
+        Iter::new(Some(self), traversal)
+    }
+
+    /// Calls `f` on self and all [`Device`]s below it in the tree in `traversal` order
+    ///
+    /// Children are visited after `f` so it can modify or remove them.
+    pub fn for_each_mut<F: FnMut(&mut Device)>(&mut self, traversal: Traversal, f: F) {
+        for_each_mut(Some(self), traversal, f)
+    }
+}
+
+impl<'a> IntoIterator for &'a Device {
+    type Item = &'a Device;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Device {
+    type Item = Device;
+    type IntoIter = IntoIter;
+
+    /// Consumes the tree, yielding each [`Device`] depth first with `devices` taken
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(Some(self), Traversal::DepthFirst)
+    }
+}
+
+impl Bus {
+    /// Returns an iterator of all [`Device`]s in the bus tree, depth first
+    pub fn iter(&self) -> Iter<'_> {
+        self.iter_with(Traversal::DepthFirst)
+    }
+
+    /// Returns an iterator of all [`Device`]s in the bus tree in `traversal` order
+    pub fn iter_with(&self, traversal: Traversal) -> Iter<'_> {
+        Iter::new(self.devices.iter().flatten(), traversal)
+    }
+
+    /// Calls `f` on all [`Device`]s in the bus tree in `traversal` order; see [`Device::for_each_mut`]
+    pub fn for_each_mut<F: FnMut(&mut Device)>(&mut self, traversal: Traversal, f: F) {
+        for_each_mut(self.devices.iter_mut().flatten(), traversal, f)
+    }
+}
+
+impl<'a> IntoIterator for &'a Bus {
+    type Item = &'a Device;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Bus {
+    type Item = Device;
+    type IntoIter = IntoIter;
+
+    /// Consumes the bus, yielding each [`Device`] depth first with `devices` taken
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self.devices.into_iter().flatten(), Traversal::DepthFirst)
+    }
+}
+
+impl SystemProfile {
+    /// Returns an iterator of all [`Device`]s in all buses, depth first
+    pub fn iter(&self) -> Iter<'_> {
+        self.iter_with(Traversal::DepthFirst)
+    }
+
+    /// Returns an iterator of all [`Device`]s in all buses in `traversal` order
+    pub fn iter_with(&self, traversal: Traversal) -> Iter<'_> {
+        Iter::new(
+            self.buses.iter().flat_map(|b| b.devices.iter().flatten()),
+            traversal,
+        )
+    }
+
+    /// Calls `f` on all [`Device`]s in all buses in `traversal` order; see [`Device::for_each_mut`]
+    pub fn for_each_mut<F: FnMut(&mut Device)>(&mut self, traversal: Traversal, f: F) {
+        for_each_mut(
+            self.buses
+                .iter_mut()
+                .flat_map(|b| b.devices.iter_mut().flatten()),
+            traversal,
+            f,
+        )
+    }
+}
+
+impl<'a> IntoIterator for &'a SystemProfile {
+    type Item = &'a Device;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for SystemProfile {
+    type Item = Device;
+    type IntoIter = IntoIter;
+
+    /// Consumes the profile, yielding each [`Device`] in all buses depth first with `devices` taken
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(
+            self.buses
+                .into_iter()
+                .flat_map(|b| b.devices.into_iter().flatten()),
+            Traversal::DepthFirst,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::DeviceLocation;
+
+    fn device(tree_positions: Vec<u8>, devices: Vec<Device>) -> Device {
+        Device {
+            location_id: DeviceLocation {
+                bus: 1,
+                number: tree_positions.len() as u8,
+                tree_positions,
+            },
+            devices: Some(devices),
+            ..Default::default()
+        }
+    }
+
+    fn tree() -> Device {
+        device(
+            vec![1],
+            vec![
+                device(vec![1, 1], vec![device(vec![1, 1, 1], vec![])]),
+                device(vec![1, 2], vec![]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_traversal_order() {
+        let tree = tree();
+        let paths = |t| tree.iter_with(t).map(|d| d.port_path()).collect::<Vec<_>>();
+        assert_eq!(
+            paths(Traversal::DepthFirst),
+            vec!["1-1", "1-1.1", "1-1.1.1", "1-1.2"]
+        );
+        assert_eq!(
+            paths(Traversal::BreadthFirst),
+            vec!["1-1", "1-1.1", "1-1.2", "1-1.1.1"]
+        );
+    }
+
+    #[test]
+    fn test_into_iter_takes_devices() {
+        let devices: Vec<Device> = tree().into_iter().collect();
+        assert_eq!(devices.len(), 4);
+        assert_eq!(devices[2].port_path(), "1-1.1.1");
+        assert!(devices.iter().all(|d| d.devices.is_none()));
+    }
+
+    #[test]
+    fn test_for_each_mut() {
+        let mut tree = tree();
+        let mut visited = Vec::new();
+        tree.for_each_mut(Traversal::DepthFirst, |d| {
+            visited.push(d.port_path());
+            if d.port_path() == "1-1.1" {
+                d.devices = None;
+            }
+            d.name = "visited".into();
+        });
+        assert_eq!(visited, vec!["1-1", "1-1.1", "1-1.2"]);
+        assert!(tree.iter().all(|d| d.name == "visited"));
+    }
+}
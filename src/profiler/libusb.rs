@@ -1,13 +1,17 @@
 //! Uses rusb (upto date libusb fork) to get system USB information - same lib as lsusb. Requires 'libusb' feature. Uses [`crate::profiler::types`] types to hold data so that it is cross-compatible with macOS system_profiler command.
+use super::names::NameSource;
 use super::*;
 use crate::error::{Error, ErrorKind};
-use crate::lsusb::names;
 use crate::types::NumericalUnit;
 use rusb as libusb;
-use usb_ids::{self, FromId};
+use rusb::UsbContext;
 
 #[derive(Debug)]
-pub(crate) struct LibUsbProfiler;
+pub(crate) struct LibUsbProfiler {
+    options: ProfilerBuilder,
+    context: libusb::Context,
+    strings_cache: StringCache,
+}
 
 pub(crate) struct UsbDevice<T: libusb::UsbContext> {
     handle: libusb::DeviceHandle<T>,
@@ -15,6 +19,8 @@ pub(crate) struct UsbDevice<T: libusb::UsbContext> {
     vidpid: (u16, u16),
     location: DeviceLocation,
     timeout: std::time::Duration,
+    strings: bool,
+    strings_cache: StringCache,
 }
 
 impl<T: libusb::UsbContext> std::fmt::Debug for UsbDevice<T> {
@@ -29,16 +35,18 @@ impl<T: libusb::UsbContext> std::fmt::Debug for UsbDevice<T> {
     }
 }
 
-/// Set log level for rusb
-pub fn set_log_level(debug: u8) {
-    let log_level = match debug {
+/// New libusb context with the [`ProfilerBuilder::libusb_log_level`]; a context rather than the default so the level does not apply to the whole process
+fn new_context(options: &ProfilerBuilder) -> Result<libusb::Context> {
+    let log_level = match options.libusb_log_level {
         0 => rusb::LogLevel::None,
         1 => rusb::LogLevel::Warning,
         2 => rusb::LogLevel::Info,
         _ => rusb::LogLevel::Debug,
     };
 
-    rusb::set_log_level(log_level);
+    let mut context = libusb::Context::new()?;
+    context.set_log_level(log_level);
+    Ok(context)
 }
 
 impl ControlRequest {
@@ -142,8 +150,33 @@ impl From<libusb::SyncType> for usb::SyncType {
 
 impl From<libusb::Version> for usb::Version {
     fn from(libusb: libusb::Version) -> Self {
-        usb::Version(libusb.major(), libusb.minor(), libusb.sub_minor())
+        // rusb decodes the major BCD digits as decimal, whereas ours is the raw byte
+        let major = libusb.major();
+        usb::Version(
+            ((major / 10) << 4) | (major % 10),
+            libusb.minor(),
+            libusb.sub_minor(),
+        )
+    }
+}
+
+/// Base class codes of the device and the interfaces of all its configurations; from the descriptors libusb has cached so the device is not opened
+fn device_classes<T: libusb::UsbContext>(
+    device: &libusb::Device<T>,
+    device_desc: &libusb::DeviceDescriptor,
+) -> Vec<u8> {
+    let mut classes = vec![device_desc.class_code()];
+    for n in 0..device_desc.num_configurations() {
+        if let Ok(config_desc) = device.config_descriptor(n) {
+            classes.extend(
+                config_desc
+                    .interfaces()
+                    .flat_map(|i| i.descriptors())
+                    .map(|d| d.class_code()),
+            );
+        }
     }
+    classes
 }
 
 /// Attempt to retrieve the current bConfigurationValue and iConfiguration for a device
@@ -173,13 +206,21 @@ impl<T: libusb::UsbContext> UsbOperations for UsbDevice<T> {
     ///
     /// Returns None if string_index is 0 - reserved for language codes
     fn get_descriptor_string(&self, string_index: u8) -> Option<String> {
-        if string_index == 0 {
+        if string_index == 0 || !self.strings {
             return None;
         }
-        self.handle
-            .read_string_descriptor(self.language, string_index, self.timeout)
-            .map(|s| s.trim().trim_end_matches('\0').to_string())
-            .ok()
+        self.strings_cache.get_or_read(
+            &self.location,
+            self.vidpid,
+            self.language.lang_id(),
+            string_index,
+            || {
+                self.handle
+                    .read_string_descriptor(self.language, string_index, self.timeout)
+                    .map(|s| s.trim().trim_end_matches('\0').to_string())
+                    .ok()
+            },
+        )
     }
 
     /// Get control message from device, ensuring message of [`ControlRequest`] length is read
@@ -221,27 +262,36 @@ impl<T: libusb::UsbContext> UsbOperations for UsbDevice<T> {
 }
 
 impl LibUsbProfiler {
+    pub fn new(options: ProfilerBuilder) -> Result<Self> {
+        Ok(Self {
+            context: new_context(&options)?,
+            options,
+            strings_cache: StringCache::default(),
+        })
+    }
+
     fn build_endpoints<T: libusb::UsbContext>(
         &self,
         handle: &UsbDevice<T>,
         interface_desc: &libusb::InterfaceDescriptor,
-    ) -> Vec<usb::Endpoint> {
+    ) -> Result<Vec<usb::Endpoint>> {
         let mut ret: Vec<usb::Endpoint> = Vec::new();
 
         for endpoint_desc in interface_desc.endpoint_descriptors() {
             let extra_desc = if let Some(extra) = endpoint_desc.extra() {
-                self.build_endpoint_descriptor_extra(
-                    handle,
-                    (
-                        interface_desc.class_code(),
-                        interface_desc.sub_class_code(),
-                        interface_desc.protocol_code(),
-                    ),
-                    interface_desc.interface_number(),
-                    extra.to_vec(),
-                )
-                .ok()
-                .flatten()
+                self.options
+                    .parse_mode
+                    .tolerate(self.build_endpoint_descriptor_extra(
+                        handle,
+                        (
+                            interface_desc.class_code(),
+                            interface_desc.sub_class_code(),
+                            interface_desc.protocol_code(),
+                        ),
+                        interface_desc.interface_number(),
+                        extra.to_vec(),
+                    ))?
+                    .flatten()
             } else {
                 None
             };
@@ -259,10 +309,11 @@ impl LibUsbProfiler {
                 interval: endpoint_desc.interval(),
                 length: endpoint_desc.length(),
                 extra: extra_desc,
+                raw: None,
             });
         }
 
-        ret
+        Ok(ret)
     }
 
     fn build_interfaces<T: libusb::UsbContext>(
@@ -281,7 +332,7 @@ impl LibUsbProfiler {
                     interface_desc.interface_number(),
                 );
 
-                let interface = usb::Interface {
+                let mut interface = usb::Interface {
                     name: get_sysfs_string(&path, "interface").or_else(|| {
                         interface_desc
                             .description_string_index()
@@ -293,14 +344,14 @@ impl LibUsbProfiler {
                     sub_class: interface_desc.sub_class_code(),
                     protocol: interface_desc.protocol_code(),
                     alt_setting: interface_desc.setting_number(),
-                    driver: get_sysfs_readlink(&path, "driver")
-                        .or_else(|| get_udev_driver_name(&path).ok().flatten()),
-                    syspath: get_syspath(&path).or_else(|| get_udev_syspath(&path).ok().flatten()),
+                    driver: self.options.driver(&path),
+                    module: self.options.module(&path),
+                    syspath: self.options.syspath(&path),
                     path,
                     length: interface_desc.length(),
-                    endpoints: self.build_endpoints(handle, &interface_desc),
-                    extra: self
-                        .build_interface_descriptor_extra(
+                    endpoints: self.build_endpoints(handle, &interface_desc)?,
+                    extra: self.options.parse_mode.tolerate(
+                        self.build_interface_descriptor_extra(
                             handle,
                             (
                                 interface_desc.class_code(),
@@ -309,10 +360,17 @@ impl LibUsbProfiler {
                             ),
                             interface_desc.interface_number(),
                             interface_desc.extra().to_vec(),
-                        )
-                        .ok(),
+                        ),
+                    )?,
+                    bluetooth: None,
+                    vendor_fields: None,
+                    raw: None,
                 };
 
+                if interface.is_bluetooth_hci() {
+                    interface.bluetooth = get_sysfs_bluetooth(&interface.path, &self.options);
+                }
+
                 ret.push(interface);
             }
         }
@@ -358,7 +416,7 @@ impl LibUsbProfiler {
                 None
             };
 
-            ret.push(usb::Configuration {
+            let mut config = usb::Configuration {
                 name: config_desc
                     .description_string_index()
                     .and_then(|i| handle.get_descriptor_string(i))
@@ -375,10 +433,35 @@ impl LibUsbProfiler {
                 length: config_desc.length(),
                 total_length: config_desc.total_length(),
                 interfaces: self.build_interfaces(handle, &config_desc)?,
-                extra: self
-                    .build_config_descriptor_extra(handle, config_desc.extra().to_vec())
-                    .ok(),
-            });
+                extra: self.options.parse_mode.tolerate(
+                    self.build_config_descriptor_extra(handle, config_desc.extra().to_vec()),
+                )?,
+                raw: None,
+                active: false,
+                warnings: Vec::new(),
+            };
+
+            // libusb only has the parsed descriptors so read the raw bytes from the device
+            if self.options.raw_descriptors || self.options.validate_descriptors {
+                match Self::get_config_descriptor(handle, n, config_desc.total_length()) {
+                    Ok(data) => {
+                        if self.options.validate_descriptors {
+                            config.warnings = usb::descriptors::validate_configuration(&data);
+                        }
+                        if self.options.raw_descriptors {
+                            config.set_raw_descriptors(&data);
+                        }
+                    }
+                    Err(e) => log::warn!(
+                        "{:?} Failed to read raw configuration descriptor {}: {}",
+                        handle,
+                        n,
+                        e
+                    ),
+                }
+            }
+
+            ret.push(config);
         }
 
         Ok(ret)
@@ -392,21 +475,40 @@ impl LibUsbProfiler {
         device_desc: &libusb::DeviceDescriptor,
         sp_device: &mut Device,
     ) -> Result<usb::DeviceExtra> {
-        // attempt to get manufacturer and product strings from device itself
-        sp_device.manufacturer = device_desc
-            .manufacturer_string_index()
-            .and_then(|i| handle.get_descriptor_string(i));
-
-        if let Some(name) = device_desc
-            .product_string_index()
-            .and_then(|i| handle.get_descriptor_string(i))
+        // get strings from device itself only if not already cached by the OS or that is less preferred
+        if self
+            .options
+            .names
+            .prefers(NameSource::Descriptor, sp_device.manufacturer_source)
         {
-            sp_device.name = name;
+            if let Some(manufacturer) = device_desc
+                .manufacturer_string_index()
+                .and_then(|i| handle.get_descriptor_string(i))
+            {
+                sp_device.manufacturer = Some(manufacturer);
+                sp_device.manufacturer_source = Some(NameSource::Descriptor);
+            }
         }
 
-        sp_device.serial_num = device_desc
-            .serial_number_string_index()
-            .and_then(|i| handle.get_descriptor_string(i));
+        if self
+            .options
+            .names
+            .prefers(NameSource::Descriptor, sp_device.name_source)
+        {
+            if let Some(name) = device_desc
+                .product_string_index()
+                .and_then(|i| handle.get_descriptor_string(i))
+            {
+                sp_device.name = name;
+                sp_device.name_source = Some(NameSource::Descriptor);
+            }
+        }
+
+        if sp_device.serial_num.is_none() {
+            sp_device.serial_num = device_desc
+                .serial_number_string_index()
+                .and_then(|i| handle.get_descriptor_string(i));
+        }
         let sysfs_name = sp_device.sysfs_name();
 
         let mut extra = usb::DeviceExtra {
@@ -416,33 +518,49 @@ impl LibUsbProfiler {
                 device_desc.manufacturer_string_index().unwrap_or(0),
                 device_desc.serial_number_string_index().unwrap_or(0),
             ),
-            driver: get_sysfs_readlink(&sysfs_name, "driver")
-                .or_else(|| get_udev_driver_name(&sysfs_name).ok().flatten()),
-            syspath: get_syspath(&sysfs_name)
-                .or_else(|| get_udev_syspath(&sysfs_name).ok().flatten()),
+            driver: self.options.driver(&sysfs_name),
+            syspath: self.options.syspath(&sysfs_name),
             // These are idProduct, idVendor in lsusb - from udev_hwdb/usb-ids
-            vendor: names::vendor(device_desc.vendor_id()).or_else(|| {
-                usb_ids::Vendor::from_id(device_desc.vendor_id()).map(|v| v.name().to_owned())
-            }),
-            product_name: names::product(device_desc.vendor_id(), device_desc.product_id())
-                .or_else(|| {
-                    usb_ids::Device::from_vid_pid(device_desc.vendor_id(), device_desc.product_id())
-                        .map(|v| v.name().to_owned())
-                }),
+            vendor: self.options.vendor_name(device_desc.vendor_id()),
+            product_name: self
+                .options
+                .product_name(device_desc.vendor_id(), device_desc.product_id()),
             configurations: self.build_configurations(device, handle, device_desc, sp_device)?,
             status: Self::get_device_status(handle).ok(),
-            debug: Self::get_debug_descriptor(handle).ok(),
+            debug: self
+                .options
+                .parse_mode
+                .tolerate(Self::get_debug_descriptor(handle))?,
             binary_object_store: None,
             qualifier: None,
             hub: None,
+            vendor_fields: None,
         };
 
+        if let Some(value) = Self::get_active_configuration(handle, &sysfs_name) {
+            extra.set_active_configuration(value);
+        }
+
         // Get device specific stuff: bos, hub, dualspeed, debug and status
-        if device_desc.usb_version() >= rusb::Version::from_bcd(0x0201) {
-            extra.binary_object_store = Self::get_bos_descriptor(handle).ok();
+        let quirks = self
+            .options
+            .quirks
+            .quirks(device_desc.vendor_id(), device_desc.product_id());
+        if device_desc.usb_version() >= rusb::Version::from_bcd(0x0201)
+            && !quirks.contains(&quirks::Quirk::NoBos)
+        {
+            extra.binary_object_store = self
+                .options
+                .parse_mode
+                .tolerate(Self::get_bos_descriptor(handle))?;
         }
-        if device_desc.usb_version() >= rusb::Version::from_bcd(0x0200) {
-            extra.qualifier = Self::get_device_qualifier(handle).ok();
+        if device_desc.usb_version() >= rusb::Version::from_bcd(0x0200)
+            && !quirks.contains(&quirks::Quirk::NoQualifier)
+        {
+            extra.qualifier = self
+                .options
+                .parse_mode
+                .tolerate(Self::get_device_qualifier(handle))?;
         }
         if device_desc.class_code() == usb::BaseClass::Hub as u8 {
             let has_ssp = if let Some(bos) = &extra.binary_object_store {
@@ -453,10 +571,21 @@ impl LibUsbProfiler {
                 false
             };
             let bcd = sp_device.bcd_usb.map_or(0x0100, |v| v.into());
-            extra.hub =
-                Self::get_hub_descriptor(handle, device_desc.protocol_code(), bcd, has_ssp).ok();
+            extra.hub = self.options.parse_mode.tolerate(Self::get_hub_descriptor(
+                handle,
+                device_desc.protocol_code(),
+                bcd,
+                has_ssp,
+            ))?;
         }
 
+        extra.vendor_fields = bridges::get_bridge_fields(
+            handle,
+            device_desc.vendor_id(),
+            device_desc.product_id(),
+            sp_device.bcd_device.map_or(0, |v| v.into()),
+        );
+
         Ok(extra)
     }
 
@@ -501,6 +630,8 @@ impl LibUsbProfiler {
                 tree_positions: device.port_numbers()?,
             },
             timeout,
+            strings: self.options.strings,
+            strings_cache: self.strings_cache.clone(),
         })
     }
 
@@ -536,32 +667,37 @@ impl LibUsbProfiler {
             ..Default::default()
         };
 
-        // sysfs cache
-        sp_device.name = get_sysfs_string(&sp_device.sysfs_name(), "product")
-            // udev-hwdb
-            .or_else(|| names::product(device_desc.vendor_id(), device_desc.product_id()))
-            // usb-ids
-            .or_else(|| {
-                usb_ids::Device::from_vid_pid(device_desc.vendor_id(), device_desc.product_id())
-                    .map(|device| device.name().to_owned())
-            })
-            // empty
-            .unwrap_or_default();
-
-        // sysfs cache
-        sp_device.manufacturer = get_sysfs_string(&sp_device.sysfs_name(), "manufacturer")
-            // udev-hwdb
-            .or_else(|| names::vendor(device_desc.vendor_id())) // udev, usb-ids if error
-            // usb-ids
-            .or_else(|| {
-                usb_ids::Vendor::from_id(device_desc.vendor_id())
-                    .map(|vendor| vendor.name().to_owned())
-            });
+        if self.options.attributes {
+            fill_sysfs_attributes(&mut sp_device);
+        }
 
+        // sysfs cache; extra only requests those missing from the device
+        if let Some(name) = get_sysfs_string(&sp_device.sysfs_name(), "product") {
+            sp_device.name = name;
+            sp_device.name_source = Some(NameSource::Os);
+        }
+        sp_device.manufacturer = get_sysfs_string(&sp_device.sysfs_name(), "manufacturer");
+        sp_device.manufacturer_source = sp_device.manufacturer.as_ref().map(|_| NameSource::Os);
         sp_device.serial_num = get_sysfs_string(&sp_device.sysfs_name(), "serial");
 
         if with_extra {
-            if let Ok(handle) = self.open_device(device, &device_desc) {
+            let handle = if self.options.opens(device_classes(device, &device_desc)) {
+                let handle = self.open_device(device, &device_desc);
+                if handle.is_err() {
+                    log::warn!("Failed to open device {:?} for extra data", device);
+                    sp_device.profiler_error =
+                        Some("Failed to open device for extra data".to_string());
+                }
+                handle.ok()
+            } else {
+                log::debug!(
+                    "Not opening {:?} for extra data, device or interface class is set to not open",
+                    device
+                );
+                None
+            };
+
+            if let Some(handle) = handle {
                 sp_device.profiler_error = {
                     match self.build_spdevice_extra(
                         device,
@@ -573,6 +709,7 @@ impl LibUsbProfiler {
                             sp_device.extra = Some(extra);
                             None
                         }
+                        Err(e) if self.options.parse_mode.is_fatal(&e) => return Err(e),
                         Err(e) => {
                             Some(format!(
                                 "Failed to get some extra data for {}, probably requires elevated permissions: {}",
@@ -582,9 +719,7 @@ impl LibUsbProfiler {
                     }
                 }
             } else {
-                log::warn!("Failed to open device {:?} for extra data", device);
                 let sysfs_name = sp_device.sysfs_name();
-                sp_device.profiler_error = Some("Failed to open device for extra data".to_string());
                 sp_device.extra = Some(usb::DeviceExtra {
                     max_packet_size: device_desc.max_packet_size(),
                     string_indexes: (
@@ -592,78 +727,105 @@ impl LibUsbProfiler {
                         device_desc.manufacturer_string_index().unwrap_or(0),
                         device_desc.serial_number_string_index().unwrap_or(0),
                     ),
-                    driver: get_sysfs_readlink(&sysfs_name, "driver")
-                        .or_else(|| get_udev_driver_name(&sysfs_name).ok().flatten()),
-                    syspath: get_syspath(&sysfs_name)
-                        .or_else(|| get_udev_syspath(&sysfs_name).ok().flatten()),
-                    vendor: names::vendor(device_desc.vendor_id()).or_else(|| {
-                        usb_ids::Vendor::from_id(device_desc.vendor_id())
-                            .map(|v| v.name().to_owned())
-                    }),
-                    product_name: names::product(device_desc.vendor_id(), device_desc.product_id())
-                        .or_else(|| {
-                            usb_ids::Device::from_vid_pid(
-                                device_desc.vendor_id(),
-                                device_desc.product_id(),
-                            )
-                            .map(|v| v.name().to_owned())
-                        }),
+                    driver: self.options.driver(&sysfs_name),
+                    syspath: self.options.syspath(&sysfs_name),
+                    vendor: self.options.vendor_name(device_desc.vendor_id()),
+                    product_name: self
+                        .options
+                        .product_name(device_desc.vendor_id(), device_desc.product_id()),
                     configurations: Vec::new(),
                     status: None,
                     debug: None,
                     binary_object_store: None,
                     qualifier: None,
                     hub: None,
+                    vendor_fields: None,
                 });
             }
         }
 
+        // udev-hwdb, usb-ids if neither the OS nor device had them; re-resolved in the preferred order after profiling
+        if sp_device.name.is_empty() {
+            if let Some(name) = self
+                .options
+                .product_name(device_desc.vendor_id(), device_desc.product_id())
+            {
+                sp_device.name = name;
+                sp_device.name_source = Some(NameSource::Ids);
+            }
+        }
+        if sp_device.manufacturer.is_none() {
+            sp_device.manufacturer = self.options.vendor_name(device_desc.vendor_id());
+            sp_device.manufacturer_source =
+                sp_device.manufacturer.as_ref().map(|_| NameSource::Ids);
+        }
+
         Ok(sp_device)
     }
+
+    /// Build the [`Device`] with [`Self::build_spdevice`], printing any errors; `None` if it failed or an error if it is [`usb::ParseMode::is_fatal`]
+    fn profile_device<T: libusb::UsbContext>(
+        &self,
+        device: &libusb::Device<T>,
+        with_extra: bool,
+    ) -> Result<Option<Device>> {
+        match self.build_spdevice(device, with_extra) {
+            Ok(sp_device) => {
+                let print_stderr = self.options.print_errors;
+
+                // print any non-critical error during extra capture
+                sp_device.profiler_error.iter().for_each(|e| {
+                    if print_stderr {
+                        eprintln!("{}", e);
+                    } else {
+                        log::warn!("Non-critical error during profile: {}", e);
+                    }
+                });
+
+                Ok(Some(sp_device))
+            }
+            Err(e) if self.options.parse_mode.is_fatal(&e) => Err(e),
+            Err(e) => {
+                eprintln!("Failed to get data for {:?}: {}", device, e);
+                Ok(None)
+            }
+        }
+    }
 }
 
 impl<C: libusb::UsbContext> Profiler<UsbDevice<C>> for LibUsbProfiler {
+    fn options(&self) -> &ProfilerBuilder {
+        &self.options
+    }
+
     fn get_devices(&mut self, with_extra: bool) -> Result<Vec<Device>> {
-        let mut devices = Vec::new();
         // run through devices building Device types - not root_hubs (port number 0)
-        for device in libusb::DeviceList::new()?
+        self.context
+            .devices()?
             .iter()
             .filter(|d| d.port_number() != 0)
-        {
-            match self.build_spdevice(&device, with_extra) {
-                Ok(sp_device) => {
-                    devices.push(sp_device.to_owned());
-                    let print_stderr =
-                        std::env::var_os("CYME_PRINT_NON_CRITICAL_PROFILER_STDERR").is_some();
-
-                    // print any non-critical error during extra capture
-                    sp_device.profiler_error.iter().for_each(|e| {
-                        if print_stderr {
-                            eprintln!("{}", e);
-                        } else {
-                            log::warn!("Non-critical error during profile: {}", e);
-                        }
-                    });
-                }
-                Err(e) => eprintln!("Failed to get data for {:?}: {}", device, e),
-            }
-        }
-
-        Ok(devices)
+            .filter_map(|device| self.profile_device(&device, with_extra).transpose())
+            .collect()
     }
 
     #[cfg(target_os = "linux")]
     fn get_root_hubs(&mut self) -> Result<HashMap<u8, Device>> {
         let mut ret = HashMap::new();
 
-        for device in libusb::DeviceList::new()?
+        for device in self
+            .context
+            .devices()?
             .iter()
             .filter(|d| d.port_number() == 0)
         {
-            if let Ok(mut sp_device) = self.build_spdevice(&device, true) {
-                // put self in as first device; root_hubs included in list on Linux
-                sp_device.devices = Some(vec![sp_device.clone()]);
-                ret.insert(sp_device.location_id.bus, sp_device);
+            match self.build_spdevice(&device, true) {
+                Ok(mut sp_device) => {
+                    // put self in as first device; root_hubs included in list on Linux
+                    sp_device.devices = Some(vec![sp_device.clone()]);
+                    ret.insert(sp_device.location_id.bus, sp_device);
+                }
+                Err(e) if self.options.parse_mode.is_fatal(&e) => return Err(e),
+                Err(_) => (),
             }
         }
 
@@ -685,6 +847,81 @@ impl<C: libusb::UsbContext> Profiler<UsbDevice<C>> for LibUsbProfiler {
 }
 
 pub(crate) fn fill_spusb(spusb: &mut SystemProfile) -> Result<()> {
-    let mut profiler = LibUsbProfiler;
+    let mut profiler = LibUsbProfiler::new(ProfilerBuilder::default())?;
     <LibUsbProfiler as Profiler<UsbDevice<rusb::Context>>>::fill_spusb(&mut profiler, spusb)
 }
+
+pub(crate) fn devices_iter(options: ProfilerBuilder) -> Result<impl Iterator<Item = Device>> {
+    let with_extra = options.with_extra;
+    let profiler = LibUsbProfiler::new(options)?;
+    // the list is collected since iter borrows it, but the devices are only references so this is cheap
+    let devices: Vec<libusb::Device<libusb::Context>> = profiler
+        .context
+        .devices()?
+        .iter()
+        .filter(|d| d.port_number() != 0)
+        .collect();
+    Ok(devices.into_iter().filter_map(move |device| {
+        // the iterator cannot return strict parsing errors so skip the device
+        profiler
+            .profile_device(&device, with_extra)
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to get data for {:?}: {}", device, e);
+                None
+            })
+    }))
+}
+
+/// Profile only the device matching `selector`
+pub(crate) fn get_device(options: ProfilerBuilder, selector: &DeviceSelector) -> Result<Device> {
+    let profiler = LibUsbProfiler::new(options)?;
+    let device = profiler
+        .context
+        .devices()?
+        .iter()
+        .filter(|d| d.port_number() != 0)
+        .find(|d| {
+            selector.is_match(
+                d.bus_number(),
+                d.address(),
+                &d.port_numbers().unwrap_or_default(),
+            )
+        })
+        .ok_or_else(|| selector.not_found())?;
+
+    profiler.build_spdevice(&device, profiler.options.with_extra)
+}
+
+/// Find the [`libusb::Device`] at `location` in `context`
+fn find_device(
+    context: &libusb::Context,
+    location: &DeviceLocation,
+) -> Result<libusb::Device<libusb::Context>> {
+    context
+        .devices()?
+        .iter()
+        .find(|d| d.bus_number() == location.bus && d.address() == location.number)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                &format!("Unable to find device at {}", location.port_path()),
+            )
+        })
+}
+
+/// Open the device at `location` for control requests
+pub(crate) fn open_device(location: &DeviceLocation) -> Result<UsbDevice<libusb::Context>> {
+    let profiler = LibUsbProfiler::new(ProfilerBuilder::default())?;
+    let device = find_device(&profiler.context, location)?;
+    let device_desc = device.device_descriptor()?;
+    profiler.open_device(&device, &device_desc)
+}
+
+/// Issue a USB port reset to the device at `location`
+#[cfg(feature = "actions")]
+pub(crate) fn reset_device(location: &DeviceLocation) -> Result<()> {
+    let handle = find_device(&new_context(&ProfilerBuilder::default())?, location)?.open()?;
+    handle.reset()?;
+
+    Ok(())
+}
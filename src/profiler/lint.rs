@@ -0,0 +1,406 @@
+//! USB specification compliance checks of device descriptors, like shellcheck for device firmware; see [`lint`]
+//!
+//! Most checks require the [`crate::usb::DeviceExtra`] so the devices must be profiled with extra and be opened. Hosts are lenient with descriptors so a device with findings may work on one but not another.
+//!
+//! ```
+//! use cyme::profiler::lint::{lint, Severity};
+//! use cyme::profiler::read_json_dump;
+//!
+//! let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+//! for finding in lint(&spusb).iter().filter(|f| f.severity == Severity::Error) {
+//!     println!("{}", finding);
+//! }
+//! ```
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::{Device, Filter, SystemProfile};
+use crate::error::Result;
+use crate::redact::Redaction;
+use crate::usb::{BaseClass, ConfigAttributes, Speed, TransferType, Version};
+
+/// A check of [`lint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Check {
+    /// Mass storage devices must have a serial number
+    MissingSerial,
+    /// Bus-powered configurations must not request more current than the port can supply
+    BusPower,
+    /// Interrupt and isochronous endpoint bInterval must be in range for the speed
+    EndpointInterval,
+    /// bcdUSB must be consistent with the speed and descriptors of the device
+    BcdUsb,
+    /// String descriptors referenced by an index must be readable
+    StringDescriptor,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Check::MissingSerial => write!(f, "missing-serial"),
+            Check::BusPower => write!(f, "bus-power"),
+            Check::EndpointInterval => write!(f, "endpoint-interval"),
+            Check::BcdUsb => write!(f, "bcd-usb"),
+            Check::StringDescriptor => write!(f, "string-descriptor"),
+        }
+    }
+}
+
+/// How serious a [`Finding`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Allowed but likely to cause problems with some hosts
+    Warning,
+    /// Does not comply with the specification
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A problem found by a [`Check`] with the descriptors of a device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Finding {
+    /// Check which found the problem
+    pub check: Check,
+    /// How serious it is
+    pub severity: Severity,
+    /// Port path of the device
+    pub port_path: String,
+    /// Vendor and product ID as 'vid:pid'
+    pub vidpid: String,
+    /// Name of the device
+    pub name: String,
+    /// The problem
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} '{}': {} [{}]: {}",
+            self.port_path, self.vidpid, self.name, self.severity, self.check, self.message
+        )
+    }
+}
+
+/// Run every [`Check`] on the devices in `spusb`, other than root hubs which are provided by the host controller driver
+pub fn lint(spusb: &SystemProfile) -> Vec<Finding> {
+    spusb
+        .iter()
+        .filter(|d| !d.is_root_hub())
+        .flat_map(lint_device)
+        .collect()
+}
+
+/// Findings of [`run`] and the devices checked
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LintReport {
+    /// Findings of the devices, redacted if a [`Redaction`] was supplied
+    pub findings: Vec<Finding>,
+    /// Number of devices checked
+    pub devices: usize,
+    /// Number of devices which could not be opened so were only checked for bcd-usb
+    pub unopened: usize,
+}
+
+impl LintReport {
+    /// Number of findings with [`Severity::Error`]
+    pub fn errors(&self) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+            .count()
+    }
+}
+
+/// Check the devices in `spusb` matching `filter` for `cyme lint`, other than [`Check`]s in `allow`, redacting the findings with `redaction`
+pub fn run(
+    spusb: &SystemProfile,
+    filter: &Filter,
+    allow: &[Check],
+    redaction: Option<&Redaction>,
+) -> Result<LintReport> {
+    let devices: Vec<&Device> = spusb
+        .iter()
+        .filter(|d| !d.is_root_hub() && filter.is_match(d))
+        .collect();
+    let mut findings: Vec<Finding> = devices
+        .iter()
+        .flat_map(|d| lint_device(d))
+        .filter(|f| !allow.contains(&f.check))
+        .collect();
+    if let Some(redaction) = redaction {
+        redaction.apply_lint(&mut findings)?;
+    }
+
+    Ok(LintReport {
+        findings,
+        devices: devices.len(),
+        unopened: devices.iter().filter(|d| d.extra.is_none()).count(),
+    })
+}
+
+/// Run every [`Check`] on `device`, not including devices connected to it
+///
+/// ```
+/// use cyme::profiler::lint::{lint_device, Check};
+/// use cyme::profiler::read_json_dump;
+///
+/// let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+/// let device = spusb.get_node("2-2.8").unwrap();
+/// assert!(lint_device(device).iter().all(|f| f.check != Check::MissingSerial));
+/// ```
+pub fn lint_device(device: &Device) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let finding = |check: Check, severity: Severity, message: String| Finding {
+        check,
+        severity,
+        port_path: device.port_path(),
+        vidpid: format!(
+            "{:04x}:{:04x}",
+            device.vendor_id.unwrap_or(0),
+            device.product_id.unwrap_or(0)
+        ),
+        name: device.name.to_owned(),
+        message,
+    };
+    let speed = device.negotiated_speed();
+
+    // bcdUSB is known without opening the device
+    if let (Some(bcd_usb), Some(speed)) = (device.bcd_usb, speed.as_ref()) {
+        if *speed >= Speed::SuperSpeed && bcd_usb < Version(3, 0, 0) {
+            findings.push(finding(
+                Check::BcdUsb,
+                Severity::Error,
+                format!(
+                    "connected at {} but bcdUSB {} is below 3.00",
+                    speed, bcd_usb
+                ),
+            ));
+        } else if *speed == Speed::HighSpeed && bcd_usb < Version(2, 0, 0) {
+            findings.push(finding(
+                Check::BcdUsb,
+                Severity::Error,
+                format!(
+                    "connected at {} but bcdUSB {} is below 2.00",
+                    speed, bcd_usb
+                ),
+            ));
+        }
+    }
+
+    let extra = match device.extra.as_ref() {
+        Some(e) => e,
+        None => return findings,
+    };
+
+    if let Some(bcd_usb) = device.bcd_usb {
+        if bcd_usb >= Version(2, 0, 1) && extra.binary_object_store.is_none() {
+            findings.push(finding(
+                Check::BcdUsb,
+                Severity::Warning,
+                format!(
+                    "bcdUSB {} requires a BOS descriptor but none was read",
+                    bcd_usb
+                ),
+            ));
+        }
+    }
+
+    let (product_index, manufacturer_index, serial_index) = extra.string_indexes;
+    let serial = device.serial_num.as_ref().filter(|s| !s.is_empty());
+    if device.has_interface_class(&BaseClass::MassStorage) && serial.is_none() && serial_index == 0
+    {
+        findings.push(finding(
+            Check::MissingSerial,
+            Severity::Error,
+            String::from("mass storage devices must have a serial number (Mass Storage Bulk-Only Transport 4.1.1)"),
+        ));
+    }
+
+    let mut unreadable = Vec::new();
+    if product_index != 0 && device.name.is_empty() {
+        unreadable.push(format!("iProduct {}", product_index));
+    }
+    if manufacturer_index != 0 && device.manufacturer.as_ref().is_none_or(|m| m.is_empty()) {
+        unreadable.push(format!("iManufacturer {}", manufacturer_index));
+    }
+    if serial_index != 0 && serial.is_none() {
+        unreadable.push(format!("iSerialNumber {}", serial_index));
+    }
+
+    for config in extra.configurations.iter() {
+        if config.string_index != 0 && config.name.is_empty() {
+            unreadable.push(format!(
+                "iConfiguration {} of configuration {}",
+                config.string_index, config.number
+            ));
+        }
+
+        if !config.attributes.contains(&ConfigAttributes::SelfPowered) {
+            let (limit, port) = match speed.as_ref() {
+                Some(s) if *s >= Speed::SuperSpeed => (900, "SuperSpeed"),
+                _ => (500, "USB 2.0"),
+            };
+            if config.max_power.value > limit {
+                findings.push(finding(
+                    Check::BusPower,
+                    Severity::Error,
+                    format!(
+                        "bus-powered configuration {} requests {} mA but a {} port supplies at most {} mA",
+                        config.number, config.max_power.value, port, limit
+                    ),
+                ));
+            } else if config.max_power.value == 0 {
+                findings.push(finding(
+                    Check::BusPower,
+                    Severity::Warning,
+                    format!(
+                        "bus-powered configuration {} requests 0 mA; bMaxPower should be the maximum it draws",
+                        config.number
+                    ),
+                ));
+            }
+        }
+
+        for interface in config.interfaces.iter() {
+            if interface.string_index != 0 && interface.name.is_none() {
+                unreadable.push(format!(
+                    "iInterface {} of interface {}.{}",
+                    interface.string_index, interface.number, interface.alt_setting
+                ));
+            }
+
+            for endpoint in interface.endpoints.iter() {
+                let interval = endpoint.interval;
+                // high-speed and above are 2^(bInterval-1) microframes; full and low-speed frames
+                let valid = match (&endpoint.transfer_type, speed.as_ref()) {
+                    (TransferType::Interrupt | TransferType::Isochronous, Some(s))
+                        if *s >= Speed::HighSpeed =>
+                    {
+                        1..=16
+                    }
+                    (TransferType::Isochronous, Some(_)) => 1..=16,
+                    (TransferType::Interrupt, Some(_)) => 1..=255,
+                    _ => continue,
+                };
+                if !valid.contains(&interval) {
+                    findings.push(finding(
+                        Check::EndpointInterval,
+                        Severity::Error,
+                        format!(
+                            "{:?} endpoint {:#04x} of interface {}.{} bInterval {} is not within {}-{} at {}",
+                            endpoint.transfer_type,
+                            endpoint.address.address,
+                            interface.number,
+                            interface.alt_setting,
+                            interval,
+                            valid.start(),
+                            valid.end(),
+                            speed.as_ref().map(|s| s.to_string()).unwrap_or_default()
+                        ),
+                    ));
+                } else if matches!(endpoint.transfer_type, TransferType::Interrupt)
+                    && speed == Some(Speed::LowSpeed)
+                    && interval < 10
+                {
+                    findings.push(finding(
+                        Check::EndpointInterval,
+                        Severity::Warning,
+                        format!(
+                            "Interrupt endpoint {:#04x} of interface {}.{} bInterval {} is below the 10 ms low_speed hosts are required to support",
+                            endpoint.address.address, interface.number, interface.alt_setting, interval
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    if !unreadable.is_empty() {
+        findings.push(finding(
+            Check::StringDescriptor,
+            Severity::Warning,
+            format!(
+                "{} could not be read; check string descriptor 0 lists the LANGID the strings are provided in",
+                unreadable.join(", ")
+            ),
+        ));
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::read_json_dump;
+
+    #[test]
+    fn test_lint_device() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let mut device = spusb.get_node("2-2.8").unwrap().clone();
+        let extra = device.extra.as_mut().unwrap();
+        let config = &mut extra.configurations[0];
+        config
+            .attributes
+            .retain(|a| *a != ConfigAttributes::SelfPowered);
+        config.max_power.value = 600;
+        let endpoint = config
+            .interfaces
+            .iter_mut()
+            .flat_map(|i| i.endpoints.iter_mut())
+            .find(|e| matches!(e.transfer_type, TransferType::Interrupt))
+            .unwrap();
+        endpoint.interval = 0;
+        device.serial_num = None;
+
+        let findings = lint_device(&device);
+        for check in [
+            Check::BusPower,
+            Check::EndpointInterval,
+            Check::StringDescriptor,
+        ] {
+            assert!(
+                findings
+                    .iter()
+                    .any(|f| f.check == check && f.port_path == "2-2.8"),
+                "{:?} not in {:#?}",
+                check,
+                findings
+            );
+        }
+        assert!(findings
+            .iter()
+            .any(|f| f.message.starts_with("iSerialNumber")));
+    }
+
+    #[test]
+    fn test_run_lint() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let errors = lint(&spusb)
+            .iter()
+            .filter(|f| f.severity == Severity::Error)
+            .count();
+        let report = run(&spusb, &Filter::default(), &[], None).unwrap();
+        assert_eq!(report.errors(), errors);
+        assert!(report.devices > 0);
+        assert!(
+            run(&spusb, &Filter::default(), Check::value_variants(), None)
+                .unwrap()
+                .findings
+                .is_empty()
+        );
+    }
+}
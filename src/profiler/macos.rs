@@ -9,14 +9,17 @@ use std::process::Command;
 use core_foundation::{
     base::{CFType, TCFType},
     data::CFData,
+    dictionary::CFDictionary,
+    number::CFNumber,
     string::CFString,
     ConcreteCFType,
 };
 use io_kit_sys::{
     kIOMasterPortDefault, kIORegistryIterateParents, kIORegistryIterateRecursively,
     keys::kIOServicePlane, ret::kIOReturnSuccess, IOIteratorNext, IOObjectRelease,
-    IORegistryEntryGetRegistryEntryID, IORegistryEntrySearchCFProperty,
-    IOServiceGetMatchingServices, IOServiceNameMatching,
+    IORegistryEntryCreateCFProperty, IORegistryEntryGetRegistryEntryID, IORegistryEntryIDMatching,
+    IORegistryEntrySearchCFProperty, IOServiceGetMatchingService, IOServiceGetMatchingServices,
+    IOServiceNameMatching,
 };
 
 pub(crate) struct IoObject(u32);
@@ -227,6 +230,58 @@ pub(crate) fn get_controller(name: &str) -> Result<HostControllerInfo> {
     }
 }
 
+/// Get the [`PowerState`] of the USB device IOService with `registry_id` from its 'IOPowerManagement' 'CurrentPowerState'
+///
+/// IOUSBHostDevice power states are 0: off, 1: suspended, 2: active
+pub(crate) fn get_power_state(registry_id: u64) -> Option<PowerState> {
+    unsafe {
+        let dictionary = IORegistryEntryIDMatching(registry_id);
+        if dictionary.is_null() {
+            return None;
+        }
+
+        // consumes dictionary
+        let handle = IOServiceGetMatchingService(kIOMasterPortDefault, dictionary);
+        if handle == 0 {
+            log::debug!("No IOService found for registry id {registry_id:08x}");
+            return None;
+        }
+        let device = IoService::new(handle);
+
+        // not get_property as that searches parents and would return the hub state
+        let cf_property = CFString::from_static_string("IOPowerManagement");
+        let raw = IORegistryEntryCreateCFProperty(
+            device.get(),
+            cf_property.as_concrete_TypeRef(),
+            std::ptr::null(),
+            0,
+        );
+        if raw.is_null() {
+            log::debug!("Device {registry_id:08x} does not have property `IOPowerManagement`");
+            return None;
+        }
+
+        let pm: CFDictionary = CFType::wrap_under_create_rule(raw).downcast_into()?;
+        let key = CFString::from_static_string("CurrentPowerState");
+        let state = pm
+            .find(key.as_CFTypeRef())
+            .and_then(|v| CFType::wrap_under_get_rule(*v).downcast::<CFNumber>())
+            .and_then(|n| n.to_i64())?;
+
+        let status = match state {
+            0 => "off".to_string(),
+            1 => "suspended".to_string(),
+            2 => "active".to_string(),
+            s => format!("state {}", s),
+        };
+
+        Some(PowerState {
+            status,
+            autosuspend_delay_ms: None,
+        })
+    }
+}
+
 /// Runs the system_profiler command for SPUSBDataType and parses the json stdout into a [`SystemProfile`].
 ///
 /// Ok result not contain [`usb::DeviceExtra`] because system_profiler does not provide this. Use `get_spusb_with_extra` to combine with libusb output for [`Device`]s with `extra`
@@ -250,6 +305,7 @@ pub fn get_spusb() -> Result<SystemProfile> {
             .map(|mut sp: SystemProfile| {
                 for bus in sp.buses.iter_mut() {
                     bus.fill_host_controller_from_ids();
+                    bus.fill_location_kinds();
                 }
                 sp
             })
@@ -266,31 +322,12 @@ pub fn get_spusb() -> Result<SystemProfile> {
     }
 }
 
-/// Runs `get_spusb` and then adds in data obtained from libusb. Requires 'libusb' feature.
+/// Runs `get_spusb` and then adds in data obtained from the [`crate::profiler::backend::Backend`] of `options`. Requires 'nusb' or 'libusb' feature.
 ///
 /// `system_profiler` captures Apple buses (essentially root_hubs) that are not captured by libusb (but are captured by nusb); this method merges the two to so the bus information is kept.
-pub fn get_spusb_with_extra() -> Result<SystemProfile> {
-    #[cfg(all(feature = "libusb", not(feature = "nusb")))]
-    {
-        get_spusb().and_then(|mut spusb| {
-            crate::profiler::libusb::fill_spusb(&mut spusb)?;
-            Ok(spusb)
-        })
-    }
-
-    #[cfg(feature = "nusb")]
-    {
-        get_spusb().and_then(|mut spusb| {
-            crate::profiler::nusb::fill_spusb(&mut spusb)?;
-            Ok(spusb)
-        })
-    }
-
-    #[cfg(all(not(feature = "libusb"), not(feature = "nusb")))]
-    {
-        Err(Error::new(
-            ErrorKind::Unsupported,
-            "nusb or libusb feature is required to do this, install with `cargo install --features nusb/libusb`",
-        ))
-    }
+pub fn get_spusb_with_extra(options: &crate::profiler::ProfilerBuilder) -> Result<SystemProfile> {
+    get_spusb().and_then(|mut spusb| {
+        options.get_backend()?.fill_spusb(&mut spusb)?;
+        Ok(spusb)
+    })
 }
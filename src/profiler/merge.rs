@@ -0,0 +1,302 @@
+//! Merge two [`SystemProfile`]s from different sources, such as macOS `system_profiler` with nusb/libusb, sysfs with libusb or a remote dump with the local system.
+//!
+//! Buses are matched by bus number and devices by port path. How a bus or device present in both is resolved is set by [`MergeStrategy`]; those only in the other profile are added.
+//!
+//! ```
+//! use cyme::profiler::merge::MergeStrategy;
+//! use cyme::profiler::read_json_dump;
+//!
+//! let mut local = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+//! let mut remote = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+//! let port_path = remote
+//!     .flattened_devices()
+//!     .iter()
+//!     .find(|d| !d.is_root_hub())
+//!     .unwrap()
+//!     .port_path();
+//! remote.get_node_mut(&port_path).unwrap().serial_num = Some("remote".into());
+//! local.get_node_mut(&port_path).unwrap().serial_num = None;
+//!
+//! let len = local.len();
+//! local.merge(remote, MergeStrategy::KeepExisting);
+//! assert_eq!(local.len(), len);
+//! assert_eq!(
+//!     local.get_node(&port_path).unwrap().serial_num,
+//!     Some("remote".into())
+//! );
+//! ```
+use super::{Bus, Device, SystemProfile};
+
+/// How a [`Bus`] or [`Device`] in both profiles is resolved by [`SystemProfile::merge`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Keep existing values, only taking values from the other profile where there are none
+    #[default]
+    KeepExisting,
+    /// Take values from the other profile, keeping existing values only where it has none
+    PreferOther,
+    /// Take the device tree of each bus from the other profile, keeping existing values only where it has none. Devices not in the other profile are dropped but bus values are kept; this is how macOS `system_profiler` is merged with the more verbose nusb/libusb profile
+    ReplaceDevices,
+}
+
+impl MergeStrategy {
+    fn prefer_other(&self) -> bool {
+        !matches!(self, MergeStrategy::KeepExisting)
+    }
+}
+
+fn merge_option<T>(existing: &mut Option<T>, other: Option<T>, prefer_other: bool) {
+    if other.is_some() && (prefer_other || existing.is_none()) {
+        *existing = other;
+    }
+}
+
+fn merge_string(existing: &mut String, other: String, prefer_other: bool) {
+    if !other.is_empty() && (prefer_other || existing.is_empty()) {
+        *existing = other;
+    }
+}
+
+impl SystemProfile {
+    /// Merge `other` into this profile, resolving buses and devices in both with `strategy`
+    ///
+    /// Buses only in `other` are added and the buses sorted by number. Devices only in `other` are added under their parent if it exists, otherwise to the bus.
+    pub fn merge(&mut self, other: SystemProfile, strategy: MergeStrategy) {
+        let mut added = false;
+
+        for bus in other.buses {
+            match self
+                .buses
+                .iter_mut()
+                .find(|b| b.get_bus_number() == bus.get_bus_number())
+            {
+                Some(existing) => existing.merge(bus, strategy),
+                None => {
+                    self.buses.push(bus);
+                    added = true;
+                }
+            }
+        }
+
+        if added {
+            self.buses.sort_by_key(|b| b.usb_bus_number);
+        }
+
+        merge_option(
+            &mut self.thunderbolt,
+            other.thunderbolt,
+            strategy.prefer_other(),
+        );
+        merge_option(&mut self.gadget, other.gadget, strategy.prefer_other());
+    }
+}
+
+impl Bus {
+    /// Merge `other` into this bus with `strategy`; see [`SystemProfile::merge`]
+    pub fn merge(&mut self, mut other: Bus, strategy: MergeStrategy) {
+        let devices = other.devices.take();
+
+        if strategy == MergeStrategy::ReplaceDevices {
+            self.merge_fields(other, false);
+            let existing = std::mem::replace(&mut self.devices, devices);
+            for device in existing
+                .into_iter()
+                .flatten()
+                .flat_map(|d| d.into_flattened())
+            {
+                if let Some(d) = self.get_node_at_mut(&device.path()) {
+                    d.merge_fields(device, false);
+                }
+            }
+        } else {
+            self.merge_fields(other, strategy.prefer_other());
+            for device in devices
+                .into_iter()
+                .flatten()
+                .flat_map(|d| d.into_flattened())
+            {
+                self.merge_device(device, strategy.prefer_other());
+            }
+        }
+    }
+
+    /// Merge a flattened `device` into the bus tree
+    fn merge_device(&mut self, device: Device, prefer_other: bool) {
+        if let Some(existing) = self.get_node_at_mut(&device.path()) {
+            existing.merge_fields(device, prefer_other);
+            return;
+        }
+
+        let parent = if device.is_trunk_device() || device.is_root_hub() {
+            None
+        } else {
+            device
+                .location_id
+                .path()
+                .parent()
+                .and_then(|p| self.get_node_at_mut(&p))
+        };
+
+        match parent {
+            Some(p) => p.devices.get_or_insert_with(Vec::new).push(device),
+            None => self.devices.get_or_insert_with(Vec::new).push(device),
+        }
+    }
+
+    fn merge_fields(&mut self, other: Bus, prefer_other: bool) {
+        merge_string(&mut self.name, other.name, prefer_other);
+        merge_string(
+            &mut self.host_controller,
+            other.host_controller,
+            prefer_other,
+        );
+        merge_option(
+            &mut self.host_controller_vendor,
+            other.host_controller_vendor,
+            prefer_other,
+        );
+        merge_option(
+            &mut self.host_controller_device,
+            other.host_controller_device,
+            prefer_other,
+        );
+        merge_option(&mut self.pci_vendor, other.pci_vendor, prefer_other);
+        merge_option(&mut self.pci_device, other.pci_device, prefer_other);
+        merge_option(&mut self.pci_revision, other.pci_revision, prefer_other);
+        merge_option(&mut self.usb_bus_number, other.usb_bus_number, prefer_other);
+        merge_option(&mut self.usb4_tunnelled, other.usb4_tunnelled, prefer_other);
+        merge_option(&mut self.role, other.role, prefer_other);
+    }
+}
+
+impl Device {
+    /// Merge the values of `other`, the same device from another source, into this device; `devices` are not merged
+    ///
+    /// Values of `other` are taken if `prefer_other` or this device has none.
+    pub fn merge_fields(&mut self, other: Device, prefer_other: bool) {
+        merge_string(&mut self.name, other.name, prefer_other);
+        merge_option(&mut self.vendor_id, other.vendor_id, prefer_other);
+        merge_option(&mut self.product_id, other.product_id, prefer_other);
+        merge_option(&mut self.serial_num, other.serial_num, prefer_other);
+        merge_option(&mut self.manufacturer, other.manufacturer, prefer_other);
+        merge_option(&mut self.bcd_device, other.bcd_device, prefer_other);
+        merge_option(&mut self.bcd_usb, other.bcd_usb, prefer_other);
+        merge_option(&mut self.bus_power, other.bus_power, prefer_other);
+        merge_option(&mut self.bus_power_used, other.bus_power_used, prefer_other);
+        merge_option(&mut self.device_speed, other.device_speed, prefer_other);
+        merge_option(
+            &mut self.extra_current_used,
+            other.extra_current_used,
+            prefer_other,
+        );
+        merge_option(&mut self.built_in, other.built_in, prefer_other);
+        merge_option(&mut self.media, other.media, prefer_other);
+        merge_option(&mut self.class, other.class, prefer_other);
+        merge_option(&mut self.sub_class, other.sub_class, prefer_other);
+        merge_option(&mut self.protocol, other.protocol, prefer_other);
+        merge_option(&mut self.power_state, other.power_state, prefer_other);
+        merge_option(
+            &mut self.connected_since,
+            other.connected_since,
+            prefer_other,
+        );
+        merge_option(&mut self.rx_lanes, other.rx_lanes, prefer_other);
+        merge_option(&mut self.tx_lanes, other.tx_lanes, prefer_other);
+        merge_option(&mut self.link_state, other.link_state, prefer_other);
+        merge_option(
+            &mut self.physical_location,
+            other.physical_location,
+            prefer_other,
+        );
+        merge_option(&mut self.removable, other.removable, prefer_other);
+        merge_option(&mut self.location_kind, other.location_kind, prefer_other);
+        merge_option(&mut self.batteries, other.batteries, prefer_other);
+        merge_option(&mut self.quirks, other.quirks, prefer_other);
+        merge_option(&mut self.name_source, other.name_source, prefer_other);
+        merge_option(
+            &mut self.manufacturer_source,
+            other.manufacturer_source,
+            prefer_other,
+        );
+        merge_option(&mut self.extra, other.extra, prefer_other);
+        merge_option(&mut self.profiler_error, other.profiler_error, prefer_other);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::DeviceLocation;
+
+    fn device(name: &str, tree_positions: Vec<u8>) -> Device {
+        Device {
+            name: name.into(),
+            location_id: DeviceLocation {
+                bus: 1,
+                number: tree_positions.len() as u8,
+                tree_positions,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn profile(devices: Vec<Device>) -> SystemProfile {
+        let mut bus = Bus::from(1);
+        bus.devices = Some(devices);
+        SystemProfile {
+            buses: vec![bus],
+            thunderbolt: None,
+            gadget: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_adds_devices_under_parent() {
+        let mut hub = device("Hub", vec![1]);
+        hub.devices = Some(vec![device("Mouse", vec![1, 2])]);
+        let mut existing = profile(vec![hub]);
+
+        let mut other_hub = device("", vec![1]);
+        other_hub.serial_num = Some("hub".into());
+        other_hub.devices = Some(vec![device("Keyboard", vec![1, 3])]);
+        let mut other = profile(vec![other_hub, device("Disk", vec![2])]);
+        other.buses.push(Bus::from(2));
+
+        existing.merge(other, MergeStrategy::KeepExisting);
+
+        assert_eq!(existing.buses.len(), 2);
+        assert_eq!(existing.len(), 4);
+        let hub = existing.get_node("1-1").unwrap();
+        assert_eq!(hub.name, "Hub");
+        assert_eq!(hub.serial_num, Some("hub".into()));
+        assert_eq!(hub.devices.as_ref().unwrap().len(), 2);
+        assert!(existing.get_node("1-1.3").is_some());
+        assert!(existing.get_node("1-2").is_some());
+    }
+
+    #[test]
+    fn test_merge_strategies() {
+        let mut a = device("A", vec![1]);
+        a.manufacturer = Some("existing".into());
+        a.bus_power = Some(500);
+        let mut b = device("B", vec![1]);
+        b.manufacturer = Some("other".into());
+        b.vendor_id = Some(0x1d50);
+
+        let mut existing = profile(vec![a.clone(), device("Gone", vec![2])]);
+        existing.merge(profile(vec![b.clone()]), MergeStrategy::PreferOther);
+        let d = existing.get_node("1-1").unwrap();
+        assert_eq!(d.name, "B");
+        assert_eq!(d.manufacturer, Some("other".into()));
+        assert_eq!(d.bus_power, Some(500));
+        assert!(existing.get_node("1-2").is_some());
+
+        let mut existing = profile(vec![a, device("Gone", vec![2])]);
+        existing.merge(profile(vec![b]), MergeStrategy::ReplaceDevices);
+        let d = existing.get_node("1-1").unwrap();
+        assert_eq!(d.name, "B");
+        assert_eq!(d.vendor_id, Some(0x1d50));
+        assert_eq!(d.bus_power, Some(500));
+        assert!(existing.get_node("1-2").is_none());
+    }
+}
@@ -0,0 +1,199 @@
+//! Resolution of device product and manufacturer names from the [`NameSource`]s in a preferred order
+//!
+//! A name can come from the string descriptors of the device, the copy of them cached by the OS (sysfs on Linux, IOKit on macOS), the USB IDs repository/udev hwdb or a user override. lsusb prefers the USB IDs where cyme prefers the device, so the order of the [`Resolver`] can be changed with [`Resolver::set_order`] and it is used with [`ProfilerBuilder::names`]; [`Device::name_source`] records which was used.
+//!
+//! ```
+//! use cyme::profiler::names::{NameSource, Resolver};
+//! use cyme::profiler::ProfilerBuilder;
+//!
+//! let mut resolver = Resolver::new();
+//! // like lsusb
+//! resolver.set_order([NameSource::Ids, NameSource::Os, NameSource::Descriptor]);
+//! resolver.set_override(0x1d50, Some(0x6018), "My Probe");
+//! let builder = ProfilerBuilder::new().names(resolver);
+//! ```
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{Device, ProfilerBuilder};
+
+/// Where a device name came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum NameSource {
+    /// User name from the 'names' of the config, or set with [`Resolver::set_override`]
+    User,
+    /// String descriptor cached by the OS when the device was enumerated
+    Os,
+    /// String descriptor read from the device when profiling with extra
+    Descriptor,
+    /// USB IDs repository, or the udev hwdb on Linux if enabled
+    Ids,
+}
+
+impl fmt::Display for NameSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameSource::User => write!(f, "user"),
+            NameSource::Os => write!(f, "os"),
+            NameSource::Descriptor => write!(f, "descriptor"),
+            NameSource::Ids => write!(f, "ids"),
+        }
+    }
+}
+
+/// Default order of [`NameSource`]: user overrides, then the device strings and the USB IDs only if it has none
+pub const DEFAULT_ORDER: [NameSource; 4] = [
+    NameSource::User,
+    NameSource::Os,
+    NameSource::Descriptor,
+    NameSource::Ids,
+];
+
+/// Preferred [`NameSource`] order and user overrides used to resolve names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolver {
+    order: Vec<NameSource>,
+    products: HashMap<(u16, u16), String>,
+    vendors: HashMap<u16, String>,
+}
+
+impl Default for Resolver {
+    fn default() -> Self {
+        Resolver {
+            order: DEFAULT_ORDER.to_vec(),
+            products: HashMap::new(),
+            vendors: HashMap::new(),
+        }
+    }
+}
+
+impl Resolver {
+    /// New resolver with the [`DEFAULT_ORDER`] and no overrides
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the order sources are tried in; sources not in `order` are not used and repeats are ignored
+    pub fn set_order<I: IntoIterator<Item = NameSource>>(&mut self, order: I) {
+        self.order.clear();
+        for source in order {
+            if !self.order.contains(&source) {
+                self.order.push(source);
+            }
+        }
+    }
+
+    /// The order sources are tried in
+    pub fn order(&self) -> &[NameSource] {
+        &self.order
+    }
+
+    /// Override the product name of `vendor_id` and `product_id` with `name`, or the manufacturer of all products of the vendor if `product_id` is `None`
+    pub fn set_override(&mut self, vendor_id: u16, product_id: Option<u16>, name: &str) {
+        match product_id {
+            Some(pid) => self.products.insert((vendor_id, pid), name.to_owned()),
+            None => self.vendors.insert(vendor_id, name.to_owned()),
+        };
+    }
+
+    /// Whether `source` is preferred to `current`, the source of a name already profiled; always if there is no name and `source` is used
+    pub fn prefers(&self, source: NameSource, current: Option<NameSource>) -> bool {
+        let rank = |s: NameSource| self.order.iter().position(|o| *o == s);
+        match (rank(source), current.and_then(rank)) {
+            (Some(s), Some(c)) => s < c,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// Replace the name and manufacturer of `device` with that of the most preferred source, `options` for the USB IDs lookups
+    ///
+    /// A profiled name is a candidate for its [`Device::name_source`] only; those without a source are assumed to be from the OS.
+    pub fn resolve(&self, device: &mut Device, options: &ProfilerBuilder) {
+        let (Some(vid), Some(pid)) = (device.vendor_id, device.product_id) else {
+            return;
+        };
+
+        let profiled = Some(std::mem::take(&mut device.name))
+            .filter(|n| !n.is_empty())
+            .map(|n| (n, device.name_source.unwrap_or(NameSource::Os)));
+        let (name, source) = self.first(profiled, |s| match s {
+            NameSource::User => self.products.get(&(vid, pid)).cloned(),
+            NameSource::Ids => options.product_name(vid, pid),
+            _ => None,
+        });
+        device.name = name.unwrap_or_default();
+        device.name_source = source;
+
+        let profiled = device
+            .manufacturer
+            .take()
+            .filter(|n| !n.is_empty())
+            .map(|n| (n, device.manufacturer_source.unwrap_or(NameSource::Os)));
+        let (manufacturer, source) = self.first(profiled, |s| match s {
+            NameSource::User => self.vendors.get(&vid).cloned(),
+            NameSource::Ids => options.vendor_name(vid),
+            _ => None,
+        });
+        device.manufacturer = manufacturer;
+        device.manufacturer_source = source;
+    }
+
+    /// First name in order from the `profiled` name or `lookup`
+    fn first<F: Fn(NameSource) -> Option<String>>(
+        &self,
+        mut profiled: Option<(String, NameSource)>,
+        lookup: F,
+    ) -> (Option<String>, Option<NameSource>) {
+        self.order
+            .iter()
+            .find_map(|s| match profiled.take_if(|(_, p)| p == s) {
+                Some((name, _)) => Some((name, *s)),
+                None => lookup(*s).map(|name| (name, *s)),
+            })
+            .unzip()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_names() {
+        let options = ProfilerBuilder::new();
+        let mut device = Device {
+            name: String::from("Probe"),
+            manufacturer: None,
+            vendor_id: Some(0x1d6b),
+            product_id: Some(0x0003),
+            name_source: Some(NameSource::Descriptor),
+            ..Default::default()
+        };
+
+        let mut resolver = Resolver::new();
+        resolver.resolve(&mut device, &options);
+        assert_eq!(device.name, "Probe");
+        assert_eq!(device.name_source, Some(NameSource::Descriptor));
+        assert!(resolver.prefers(NameSource::Descriptor, Some(NameSource::Ids)));
+        assert!(!resolver.prefers(NameSource::Ids, Some(NameSource::Os)));
+
+        resolver.set_override(0x1d6b, Some(0x0003), "My Hub");
+        resolver.set_override(0x1d6b, None, "Linux");
+        resolver.resolve(&mut device, &options);
+        assert_eq!(device.name, "My Hub");
+        assert_eq!(device.name_source, Some(NameSource::User));
+        assert_eq!(device.manufacturer.as_deref(), Some("Linux"));
+        assert_eq!(device.manufacturer_source, Some(NameSource::User));
+
+        // the descriptor name is no longer known so nothing without the user override or USB IDs
+        resolver.set_order([NameSource::Descriptor, NameSource::Os]);
+        resolver.resolve(&mut device, &options);
+        assert_eq!(device.name, "");
+        assert_eq!(device.name_source, None);
+        assert!(!resolver.prefers(NameSource::Ids, None));
+    }
+}
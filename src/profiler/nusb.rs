@@ -1,13 +1,14 @@
 //! Uses nusb (pure Rust) to get system USB information. Requires 'nusb' feature. Uses [`crate::profiler::types`] types to hold data so that it is cross-compatible with macOS system_profiler command.
+use super::names::NameSource;
 use super::*;
 use crate::error::{Error, ErrorKind};
-use crate::lsusb::names;
 use crate::types::NumericalUnit;
 use ::nusb;
-use usb_ids::{self, FromId};
 
 #[derive(Debug)]
 pub(crate) struct NusbProfiler {
+    options: ProfilerBuilder,
+    strings_cache: StringCache,
     #[cfg(target_os = "windows")]
     bus_id_map: HashMap<String, u8>,
 }
@@ -18,6 +19,8 @@ pub(crate) struct UsbDevice {
     vidpid: (u16, u16),
     location: DeviceLocation,
     timeout: std::time::Duration,
+    strings: bool,
+    strings_cache: StringCache,
 }
 
 impl std::fmt::Debug for UsbDevice {
@@ -170,63 +173,76 @@ impl From<&nusb::BusInfo> for Bus {
 
 impl From<&nusb::DeviceInfo> for Device {
     fn from(device_info: &nusb::DeviceInfo) -> Self {
-        let device_speed = device_info.speed().map(|s| {
-            let s = usb::Speed::from(s);
-            DeviceSpeed::SpeedValue(s)
-        });
-
-        let manufacturer = device_info
-            .manufacturer_string()
-            .map(|s| s.to_string())
-            .or_else(|| names::vendor(device_info.vendor_id()))
-            .or_else(|| {
-                usb_ids::Vendor::from_id(device_info.vendor_id()).map(|v| v.name().to_string())
-            });
-        let name = device_info
-            .product_string()
-            .map(|s| s.to_string())
-            .or_else(|| names::product(device_info.vendor_id(), device_info.product_id()))
-            .or_else(|| {
-                usb_ids::Device::from_vid_pid(device_info.vendor_id(), device_info.product_id())
-                    .map(|d| d.name().to_string())
-            })
-            .unwrap_or_default();
-        let serial_num = device_info.serial_number().map(|s| s.to_string());
-
-        let bus_no = if cfg!(target_os = "macos") {
-            // macOS bus_id is a hex string
-            u8::from_str_radix(device_info.bus_id(), 16)
-                .expect("Failed to parse bus_id: macOS bus_id should be a hex string and not None")
-        } else if cfg!(target_os = "linux") || cfg!(target_os = "android") {
-            // Linux bus_id is a string decimal
-            device_info.bus_id().parse::<u8>().expect(
-                "Failed to parse bus_id: Linux bus_id should be a decimal string and not None",
-            )
-        } else {
-            // Windows bus_id is a string string so 0
-            0
-        };
+        device_from_info(device_info, &ProfilerBuilder::default())
+    }
+}
 
-        Device {
-            vendor_id: Some(device_info.vendor_id()),
-            product_id: Some(device_info.product_id()),
-            device_speed,
-            location_id: DeviceLocation {
-                bus: bus_no,
-                number: device_info.device_address(),
-                tree_positions: device_info.port_chain().to_vec(),
-            },
-            bcd_device: Some(usb::Version::from_bcd(device_info.device_version())),
-            // gets added on the extra read
-            bcd_usb: None,
-            class: Some(usb::BaseClass::from(device_info.class())),
-            sub_class: Some(device_info.subclass()),
-            protocol: Some(device_info.protocol()),
-            name,
-            manufacturer,
-            serial_num,
-            ..Default::default()
+/// Bus number of the device from the OS bus_id
+fn bus_number(device_info: &nusb::DeviceInfo) -> u8 {
+    if cfg!(target_os = "macos") {
+        // macOS bus_id is a hex string
+        u8::from_str_radix(device_info.bus_id(), 16)
+            .expect("Failed to parse bus_id: macOS bus_id should be a hex string and not None")
+    } else if cfg!(target_os = "linux") || cfg!(target_os = "android") {
+        // Linux bus_id is a string decimal
+        device_info
+            .bus_id()
+            .parse::<u8>()
+            .expect("Failed to parse bus_id: Linux bus_id should be a decimal string and not None")
+    } else {
+        // Windows bus_id is a string string so 0
+        0
+    }
+}
+
+/// Build a [`Device`] from the [`nusb::DeviceInfo`] cached by the OS, using `options` for name lookups
+fn device_from_info(device_info: &nusb::DeviceInfo, options: &ProfilerBuilder) -> Device {
+    let device_speed = device_info.speed().map(|s| {
+        let s = usb::Speed::from(s);
+        DeviceSpeed::SpeedValue(s)
+    });
+
+    // udev-hwdb, usb-ids if the OS did not have them; re-resolved in the preferred order after profiling
+    let (manufacturer, manufacturer_source) = match device_info.manufacturer_string() {
+        Some(s) => (Some(s.to_string()), Some(NameSource::Os)),
+        None => {
+            let manufacturer = options.vendor_name(device_info.vendor_id());
+            let source = manufacturer.as_ref().map(|_| NameSource::Ids);
+            (manufacturer, source)
         }
+    };
+    let (name, name_source) = match device_info.product_string() {
+        Some(s) => (s.to_string(), Some(NameSource::Os)),
+        None => match options.product_name(device_info.vendor_id(), device_info.product_id()) {
+            Some(name) => (name, Some(NameSource::Ids)),
+            None => (String::new(), None),
+        },
+    };
+    let serial_num = device_info.serial_number().map(|s| s.to_string());
+
+    let bus_no = bus_number(device_info);
+
+    Device {
+        vendor_id: Some(device_info.vendor_id()),
+        product_id: Some(device_info.product_id()),
+        device_speed,
+        location_id: DeviceLocation {
+            bus: bus_no,
+            number: device_info.device_address(),
+            tree_positions: device_info.port_chain().to_vec(),
+        },
+        bcd_device: Some(usb::Version::from_bcd(device_info.device_version())),
+        // gets added on the extra read
+        bcd_usb: None,
+        class: Some(usb::BaseClass::from(device_info.class())),
+        sub_class: Some(device_info.subclass()),
+        protocol: Some(device_info.protocol()),
+        name,
+        name_source,
+        manufacturer,
+        manufacturer_source,
+        serial_num,
+        ..Default::default()
     }
 }
 
@@ -305,13 +321,21 @@ impl UsbDevice {
 
 impl UsbOperations for UsbDevice {
     fn get_descriptor_string(&self, string_index: u8) -> Option<String> {
-        if string_index == 0 {
+        if string_index == 0 || !self.strings {
             return None;
         }
-        self.handle
-            .get_string_descriptor(string_index, self.language, self.timeout)
-            .map(|s| s.chars().filter(|c| !c.is_control()).collect())
-            .ok()
+        self.strings_cache.get_or_read(
+            &self.location,
+            self.vidpid,
+            self.language,
+            string_index,
+            || {
+                self.handle
+                    .get_string_descriptor(string_index, self.language, self.timeout)
+                    .map(|s| s.chars().filter(|c| !c.is_control()).collect())
+                    .ok()
+            },
+        )
     }
 
     fn get_control_msg(&self, control_request: ControlRequest) -> Result<Vec<u8>> {
@@ -340,7 +364,13 @@ impl UsbOperations for UsbDevice {
 
 impl NusbProfiler {
     pub fn new() -> Self {
+        Self::with_options(ProfilerBuilder::default())
+    }
+
+    pub fn with_options(options: ProfilerBuilder) -> Self {
         Self {
+            options,
+            strings_cache: StringCache::default(),
             #[cfg(target_os = "windows")]
             bus_id_map: HashMap::new(),
         }
@@ -350,7 +380,7 @@ impl NusbProfiler {
         &self,
         device: &UsbDevice,
         interface_desc: &nusb::descriptors::InterfaceAltSetting,
-    ) -> Vec<usb::Endpoint> {
+    ) -> Result<Vec<usb::Endpoint>> {
         let mut ret: Vec<usb::Endpoint> = Vec::new();
 
         for endpoint in interface_desc.endpoints() {
@@ -371,7 +401,9 @@ impl NusbProfiler {
                 interval: endpoint.interval(),
                 length: endpoint_desc[0],
                 extra: self
-                    .build_endpoint_descriptor_extra(
+                    .options
+                    .parse_mode
+                    .tolerate(self.build_endpoint_descriptor_extra(
                         device,
                         (
                             interface_desc.class(),
@@ -380,13 +412,13 @@ impl NusbProfiler {
                         ),
                         interface_desc.interface_number(),
                         endpoint_extra,
-                    )
-                    .ok()
+                    ))?
                     .flatten(),
+                raw: None,
             });
         }
 
-        ret
+        Ok(ret)
     }
 
     fn build_interfaces(
@@ -414,7 +446,7 @@ impl NusbProfiler {
                     .flat_map(|d| d.to_vec())
                     .collect::<Vec<u8>>();
 
-                let interface = usb::Interface {
+                let mut interface = usb::Interface {
                     name: get_sysfs_string(&path, "interface").or_else(|| {
                         interface_alt
                             .string_index()
@@ -426,13 +458,13 @@ impl NusbProfiler {
                     sub_class: interface_alt.subclass(),
                     protocol: interface_alt.protocol(),
                     alt_setting: interface_alt.alternate_setting(),
-                    driver: get_sysfs_readlink(&path, "driver")
-                        .or_else(|| get_udev_driver_name(&path).ok().flatten()),
-                    syspath: get_syspath(&path).or_else(|| get_udev_syspath(&path).ok().flatten()),
+                    driver: self.options.driver(&path),
+                    module: self.options.module(&path),
+                    syspath: self.options.syspath(&path),
                     length: interface_desc[0],
-                    endpoints: self.build_endpoints(device, &interface_alt),
-                    extra: self
-                        .build_interface_descriptor_extra(
+                    endpoints: self.build_endpoints(device, &interface_alt)?,
+                    extra: self.options.parse_mode.tolerate(
+                        self.build_interface_descriptor_extra(
                             device,
                             (
                                 interface_alt.class(),
@@ -441,11 +473,18 @@ impl NusbProfiler {
                             ),
                             interface_alt.interface_number(),
                             interface_extra,
-                        )
-                        .ok(),
+                        ),
+                    )?,
                     path,
+                    bluetooth: None,
+                    vendor_fields: None,
+                    raw: None,
                 };
 
+                if interface.is_bluetooth_hci() {
+                    interface.bluetooth = get_sysfs_bluetooth(&interface.path, &self.options);
+                }
+
                 ret.push(interface);
             }
         }
@@ -478,7 +517,7 @@ impl NusbProfiler {
                 .collect::<Vec<u8>>();
             let total_length = u16::from_le_bytes(config_desc[2..4].try_into().unwrap());
 
-            ret.push(usb::Configuration {
+            let mut config = usb::Configuration {
                 name: c
                     .string_index()
                     .and_then(|i| device.get_descriptor_string(i))
@@ -496,9 +535,29 @@ impl NusbProfiler {
                 total_length,
                 interfaces: self.build_interfaces(device, &c)?,
                 extra: self
-                    .build_config_descriptor_extra(device, config_extra)
-                    .ok(),
-            });
+                    .options
+                    .parse_mode
+                    .tolerate(self.build_config_descriptor_extra(device, config_extra))?,
+                raw: None,
+                active: false,
+                warnings: Vec::new(),
+            };
+
+            if self.options.raw_descriptors || self.options.validate_descriptors {
+                // nusb has the full configuration descriptor cached so no need to read it again
+                let data = c
+                    .descriptors()
+                    .flat_map(|d| d.to_vec())
+                    .collect::<Vec<u8>>();
+                if self.options.validate_descriptors {
+                    config.warnings = usb::descriptors::validate_configuration(&data);
+                }
+                if self.options.raw_descriptors {
+                    config.set_raw_descriptors(&data);
+                }
+            }
+
+            ret.push(config);
         }
 
         Ok(ret)
@@ -515,18 +574,28 @@ impl NusbProfiler {
         sp_device.bcd_usb = Some(device_desc.usb_version);
 
         // try to get strings from device descriptors
-        // if missing
-        if sp_device.name.is_empty() {
+        // if missing or those profiled are less preferred
+        if self
+            .options
+            .names
+            .prefers(NameSource::Descriptor, sp_device.name_source)
+        {
             if let Some(name) = device.get_descriptor_string(device_desc.product_string_index) {
                 sp_device.name = name;
+                sp_device.name_source = Some(NameSource::Descriptor);
             }
         }
 
-        if sp_device.manufacturer.is_none() {
+        if self
+            .options
+            .names
+            .prefers(NameSource::Descriptor, sp_device.manufacturer_source)
+        {
             if let Some(manufacturer) =
                 device.get_descriptor_string(device_desc.manufacturer_string_index)
             {
                 sp_device.manufacturer = Some(manufacturer);
+                sp_device.manufacturer_source = Some(NameSource::Descriptor);
             }
         }
 
@@ -546,34 +615,49 @@ impl NusbProfiler {
                 device_desc.manufacturer_string_index,
                 device_desc.serial_number_string_index,
             ),
-            driver: get_sysfs_readlink(&sysfs_name, "driver")
-                .or_else(|| get_udev_driver_name(&sysfs_name).ok().flatten()),
-            syspath: get_syspath(&sysfs_name)
-                .or_else(|| get_udev_syspath(&sysfs_name).ok().flatten()),
+            driver: self.options.driver(&sysfs_name),
+            syspath: self.options.syspath(&sysfs_name),
             // These are idProduct, idVendor in lsusb - from udev_hwdb/usb-ids - not device descriptor
-            vendor: names::vendor(device_desc.vendor_id).or_else(|| {
-                usb_ids::Vendor::from_id(device_desc.vendor_id).map(|v| v.name().to_owned())
-            }),
-            product_name: names::product(device_desc.vendor_id, device_desc.product_id).or_else(
-                || {
-                    usb_ids::Device::from_vid_pid(device_desc.vendor_id, device_desc.product_id)
-                        .map(|v| v.name().to_owned())
-                },
-            ),
+            vendor: self.options.vendor_name(device_desc.vendor_id),
+            product_name: self
+                .options
+                .product_name(device_desc.vendor_id, device_desc.product_id),
             configurations: self.build_configurations(device)?,
             status: Self::get_device_status(device).ok(),
-            debug: Self::get_debug_descriptor(device).ok(),
+            debug: self
+                .options
+                .parse_mode
+                .tolerate(Self::get_debug_descriptor(device))?,
             binary_object_store: None,
             qualifier: None,
             hub: None,
+            vendor_fields: None,
         };
 
+        if let Some(value) = Self::get_active_configuration(device, &sysfs_name) {
+            extra.set_active_configuration(value);
+        }
+
         // Get device specific stuff: bos, hub, dualspeed, debug and status
-        if device_desc.usb_version >= usb::Version::from_bcd(0x0201) {
-            extra.binary_object_store = Self::get_bos_descriptor(device).ok();
+        let quirks = self
+            .options
+            .quirks
+            .quirks(device_desc.vendor_id, device_desc.product_id);
+        if device_desc.usb_version >= usb::Version::from_bcd(0x0201)
+            && !quirks.contains(&quirks::Quirk::NoBos)
+        {
+            extra.binary_object_store = self
+                .options
+                .parse_mode
+                .tolerate(Self::get_bos_descriptor(device))?;
         }
-        if device_desc.usb_version >= usb::Version::from_bcd(0x0200) {
-            extra.qualifier = Self::get_device_qualifier(device).ok();
+        if device_desc.usb_version >= usb::Version::from_bcd(0x0200)
+            && !quirks.contains(&quirks::Quirk::NoQualifier)
+        {
+            extra.qualifier = self
+                .options
+                .parse_mode
+                .tolerate(Self::get_device_qualifier(device))?;
         }
 
         if device_desc.device_class == usb::BaseClass::Hub as u8 {
@@ -585,10 +669,21 @@ impl NusbProfiler {
                 false
             };
             let bcd = sp_device.bcd_usb.map_or(0x0100, |v| v.into());
-            extra.hub =
-                Self::get_hub_descriptor(device, device_desc.device_protocol, bcd, has_ssp).ok();
+            extra.hub = self.options.parse_mode.tolerate(Self::get_hub_descriptor(
+                device,
+                device_desc.device_protocol,
+                bcd,
+                has_ssp,
+            ))?;
         }
 
+        extra.vendor_fields = bridges::get_bridge_fields(
+            device,
+            device_desc.vendor_id,
+            device_desc.product_id,
+            device_desc.device_version.into(),
+        );
+
         Ok(extra)
     }
 
@@ -597,39 +692,50 @@ impl NusbProfiler {
         device_info: &nusb::DeviceInfo,
         with_extra: bool,
     ) -> Result<Device> {
-        let mut sp_device: Device = device_info.into();
+        let mut sp_device = device_from_info(device_info, &self.options);
+        let options = self.options.clone();
+
+        if options.attributes {
+            #[cfg(target_os = "macos")]
+            {
+                sp_device.power_state =
+                    super::macos::get_power_state(device_info.registry_entry_id());
+            }
+            #[cfg(not(target_os = "macos"))]
+            fill_sysfs_attributes(&mut sp_device);
+        }
 
         let generic_extra = |sysfs_name: &str| {
             usb::DeviceExtra {
                 max_packet_size: device_info.max_packet_size_0(),
                 // nusb doesn't have these cached
                 string_indexes: (0, 0, 0),
-                driver: get_sysfs_readlink(sysfs_name, "driver")
-                    .or_else(|| get_udev_driver_name(sysfs_name).ok().flatten()),
-                syspath: get_syspath(sysfs_name)
-                    .or_else(|| get_udev_syspath(sysfs_name).ok().flatten()),
-                vendor: names::vendor(device_info.vendor_id()).or_else(|| {
-                    usb_ids::Vendor::from_id(device_info.vendor_id()).map(|v| v.name().to_owned())
-                }),
-                product_name: names::product(device_info.vendor_id(), device_info.product_id())
-                    .or_else(|| {
-                        usb_ids::Device::from_vid_pid(
-                            device_info.vendor_id(),
-                            device_info.product_id(),
-                        )
-                        .map(|v| v.name().to_owned())
-                    }),
+                driver: options.driver(sysfs_name),
+                syspath: options.syspath(sysfs_name),
+                vendor: options.vendor_name(device_info.vendor_id()),
+                product_name: options
+                    .product_name(device_info.vendor_id(), device_info.product_id()),
                 configurations: vec![],
                 status: None,
                 debug: None,
                 binary_object_store: None,
                 qualifier: None,
                 hub: None,
+                vendor_fields: None,
             }
         };
 
         if with_extra {
-            if let Ok(device) = device_info.open() {
+            let classes = std::iter::once(device_info.class())
+                .chain(device_info.interfaces().map(|i| i.class()));
+            if !self.options.opens(classes) {
+                log::debug!(
+                    "Not opening {:04x}:{:04x} for extra data, device or interface class is set to not open",
+                    device_info.vendor_id(),
+                    device_info.product_id()
+                );
+                sp_device.extra = Some(generic_extra(&sp_device.sysfs_name()));
+            } else if let Ok(device) = device_info.open() {
                 // get the first language - probably US English
                 let languages: Vec<u16> = device
                     .get_string_descriptor_supported_languages(std::time::Duration::from_secs(1))
@@ -647,6 +753,8 @@ impl NusbProfiler {
                         vidpid: (device_info.vendor_id(), device_info.product_id()),
                         location: sp_device.location_id.clone(),
                         timeout: std::time::Duration::from_secs(1),
+                        strings: self.options.strings,
+                        strings_cache: self.strings_cache.clone(),
                     };
 
                     match self.build_spdevice_extra(&usb_device, &mut sp_device) {
@@ -654,6 +762,7 @@ impl NusbProfiler {
                             sp_device.extra = Some(extra);
                             None
                         }
+                        Err(e) if options.parse_mode.is_fatal(&e) => return Err(e),
                         Err(e) => {
                             sp_device.extra = Some(generic_extra(&sp_device.sysfs_name()));
                             Some(format!("Failed to get some extra data for {}, probably requires elevated permissions: {}", sp_device, e))
@@ -672,46 +781,60 @@ impl NusbProfiler {
 
         Ok(sp_device)
     }
-}
 
-impl Profiler<UsbDevice> for NusbProfiler {
-    fn get_devices(&mut self, with_extra: bool) -> Result<Vec<Device>> {
-        let mut devices = Vec::new();
-        for device in nusb::list_devices()? {
-            match self.build_spdevice(&device, with_extra) {
-                #[allow(unused_mut)]
-                Ok(mut sp_device) => {
-                    #[cfg(target_os = "windows")]
-                    {
-                        // Windows doesn't have a bus number for root hubs, so we use the index
-                        // and assign devices based on serial number
-                        if let Some(existing_no) = self.bus_id_map.get(device.bus_id()) {
-                            sp_device.location_id.bus = *existing_no;
-                        } else {
-                            let bus = self.bus_id_map.len() as u8;
-                            self.bus_id_map.insert(device.bus_id().to_owned(), bus);
-                            sp_device.location_id.bus = bus;
-                        }
+    /// Build the [`Device`] with [`Self::build_spdevice`], printing any errors; `None` if it failed or an error if it is [`usb::ParseMode::is_fatal`]
+    fn profile_device(
+        &mut self,
+        device: &nusb::DeviceInfo,
+        with_extra: bool,
+    ) -> Result<Option<Device>> {
+        match self.build_spdevice(device, with_extra) {
+            #[allow(unused_mut)]
+            Ok(mut sp_device) => {
+                #[cfg(target_os = "windows")]
+                {
+                    // Windows doesn't have a bus number for root hubs, so we use the index
+                    // and assign devices based on serial number
+                    if let Some(existing_no) = self.bus_id_map.get(device.bus_id()) {
+                        sp_device.location_id.bus = *existing_no;
+                    } else {
+                        let bus = self.bus_id_map.len() as u8;
+                        self.bus_id_map.insert(device.bus_id().to_owned(), bus);
+                        sp_device.location_id.bus = bus;
                     }
-                    devices.push(sp_device.to_owned());
+                }
 
-                    let print_stderr =
-                        std::env::var_os("CYME_PRINT_NON_CRITICAL_PROFILER_STDERR").is_some();
+                let print_stderr = self.options.print_errors;
 
-                    // print any non-critical error during extra capture
-                    sp_device.profiler_error.iter().for_each(|e| {
-                        if print_stderr {
-                            eprintln!("{}", e);
-                        } else {
-                            log::warn!("Non-critical error during profile of {:?}: {}", device, e);
-                        }
-                    });
-                }
-                Err(e) => eprintln!("Failed to get data for {:?}: {}", device, e),
+                // print any non-critical error during extra capture
+                sp_device.profiler_error.iter().for_each(|e| {
+                    if print_stderr {
+                        eprintln!("{}", e);
+                    } else {
+                        log::warn!("Non-critical error during profile of {:?}: {}", device, e);
+                    }
+                });
+
+                Ok(Some(sp_device))
+            }
+            Err(e) if self.options.parse_mode.is_fatal(&e) => Err(e),
+            Err(e) => {
+                eprintln!("Failed to get data for {:?}: {}", device, e);
+                Ok(None)
             }
         }
+    }
+}
+
+impl Profiler<UsbDevice> for NusbProfiler {
+    fn options(&self) -> &ProfilerBuilder {
+        &self.options
+    }
 
-        Ok(devices)
+    fn get_devices(&mut self, with_extra: bool) -> Result<Vec<Device>> {
+        nusb::list_devices()?
+            .filter_map(|device| self.profile_device(&device, with_extra).transpose())
+            .collect()
     }
 
     #[cfg(any(target_os = "linux", target_os = "android"))]
@@ -732,8 +855,7 @@ impl Profiler<UsbDevice> for NusbProfiler {
                             ),
                         ));
                     }
-                    let print_stderr =
-                        std::env::var_os("CYME_PRINT_NON_CRITICAL_PROFILER_STDERR").is_some();
+                    let print_stderr = self.options.print_errors;
 
                     // print any non-critical error during extra capture
                     sp_device.profiler_error.iter().for_each(|e| {
@@ -746,6 +868,7 @@ impl Profiler<UsbDevice> for NusbProfiler {
 
                     root_hubs.insert(sp_device.location_id.bus, sp_device);
                 }
+                Err(e) if self.options.parse_mode.is_fatal(&e) => return Err(e),
                 Err(e) => eprintln!("Failed to get data for {:?}: {}", device, e),
             }
         }
@@ -817,3 +940,86 @@ pub(crate) fn fill_spusb(spusb: &mut SystemProfile) -> Result<()> {
     let mut profiler = NusbProfiler::new();
     profiler.fill_spusb(spusb)
 }
+
+pub(crate) fn devices_iter(options: ProfilerBuilder) -> Result<impl Iterator<Item = Device>> {
+    let with_extra = options.with_extra;
+    let mut profiler = NusbProfiler::with_options(options);
+    Ok(nusb::list_devices()?.filter_map(move |device| {
+        // the iterator cannot return strict parsing errors so skip the device
+        profiler
+            .profile_device(&device, with_extra)
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to get data for {:?}: {}", device, e);
+                None
+            })
+    }))
+}
+
+/// Profile only the device matching `selector`
+pub(crate) fn get_device(options: ProfilerBuilder, selector: &DeviceSelector) -> Result<Device> {
+    let devices: Vec<nusb::DeviceInfo> = nusb::list_devices()?.collect();
+    // Windows bus numbers are assigned by the profiler in the order buses are first seen so assign them the same way as a full profile
+    let buses = if cfg!(target_os = "windows") {
+        super::assign_bus_numbers(devices.iter().map(|d| d.bus_id()))
+    } else {
+        devices.iter().map(bus_number).collect()
+    };
+    let (device_info, bus) = devices
+        .iter()
+        .zip(buses)
+        .find(|(d, bus)| selector.is_match(*bus, d.device_address(), d.port_chain()))
+        .ok_or_else(|| selector.not_found())?;
+    let with_extra = options.with_extra;
+    let mut profiler = NusbProfiler::with_options(options);
+    let mut device = profiler.build_spdevice(device_info, with_extra)?;
+    device.location_id.bus = bus;
+
+    Ok(device)
+}
+
+/// Find the [`nusb::DeviceInfo`] of the device at `location`
+fn find_device_info(location: &DeviceLocation) -> Result<nusb::DeviceInfo> {
+    nusb::list_devices()?
+        .find(|d| {
+            d.device_address() == location.number
+                && d.port_chain() == location.tree_positions.as_slice()
+                // Windows bus numbers are assigned by profiler so cannot compare
+                && (cfg!(target_os = "windows")
+                    || Device::from(d).location_id.bus == location.bus)
+        })
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                &format!("Unable to find device at {}", location.port_path()),
+            )
+        })
+}
+
+/// Open the device at `location` for control requests
+pub(crate) fn open_device(location: &DeviceLocation) -> Result<UsbDevice> {
+    let device_info = find_device_info(location)?;
+    let handle = device_info.open()?;
+    let language = handle
+        .get_string_descriptor_supported_languages(std::time::Duration::from_secs(1))
+        .ok()
+        .and_then(|mut i| i.next())
+        .unwrap_or(nusb::descriptors::language_id::US_ENGLISH);
+
+    Ok(UsbDevice {
+        handle,
+        language,
+        vidpid: (device_info.vendor_id(), device_info.product_id()),
+        location: location.clone(),
+        timeout: std::time::Duration::from_secs(1),
+        strings: true,
+        strings_cache: StringCache::default(),
+    })
+}
+
+/// Issue a USB port reset to the device at `location`
+#[cfg(feature = "actions")]
+pub(crate) fn reset_device(location: &DeviceLocation) -> Result<()> {
+    find_device_info(location)?.open()?.reset()?;
+
+    Ok(())
+}
@@ -0,0 +1,197 @@
+//! Database of devices with known-broken behaviour, similar to the Linux kernel usb quirks
+//!
+//! A [`Quirk`] adjusts what is read when profiling the device - such as not requesting a descriptor which stalls it - and annotates the [`Device`] so it is shown in the output. The [`Database`] of a [`ProfilerBuilder`](super::ProfilerBuilder) starts with the built-in entries and can be replaced with [`ProfilerBuilder::quirks`](super::ProfilerBuilder::quirks); cyme adds the 'quirks' of the config.
+//!
+//! ```
+//! use cyme::profiler::quirks::{Database, Quirk, QuirkEntry};
+//! use cyme::profiler::ProfilerBuilder;
+//!
+//! let mut database = Database::builtin();
+//! database.add(QuirkEntry::new(0x1234, Some(0x5678), vec![Quirk::FakeSerial]));
+//! assert_eq!(database.quirks(0x1234, 0x5678), vec![Quirk::FakeSerial]);
+//! // Elgato 4K X is built-in
+//! assert_eq!(database.quirks(0x0fd9, 0x009b), vec![Quirk::NoBos]);
+//!
+//! let builder = ProfilerBuilder::new().quirks(database);
+//! ```
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::Device;
+
+/// A known-broken behaviour of a device
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Quirk {
+    /// Stalls or hangs when the BOS descriptor is requested so it is not read
+    NoBos,
+    /// Stalls when the device qualifier descriptor is requested so it is not read
+    NoQualifier,
+    /// String descriptor bLength includes padding such as trailing NULs so strings are trimmed
+    BadStringLength,
+    /// Serial number is not unique to the device, such as being shared by every unit
+    FakeSerial,
+}
+
+impl fmt::Display for Quirk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Quirk::NoBos => write!(f, "no-bos"),
+            Quirk::NoQualifier => write!(f, "no-qualifier"),
+            Quirk::BadStringLength => write!(f, "bad-string-length"),
+            Quirk::FakeSerial => write!(f, "fake-serial"),
+        }
+    }
+}
+
+/// Devices the [`Quirk`]s apply to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuirkEntry {
+    /// Vendor ID of the devices
+    pub vendor_id: u16,
+    /// Product ID of the devices or `None` for all products of the vendor
+    pub product_id: Option<u16>,
+    /// Quirks of the devices
+    pub quirks: Vec<Quirk>,
+}
+
+impl QuirkEntry {
+    /// New entry for `quirks` of `vendor_id` and `product_id`, or all products of the vendor if `None`
+    pub fn new(vendor_id: u16, product_id: Option<u16>, quirks: Vec<Quirk>) -> Self {
+        QuirkEntry {
+            vendor_id,
+            product_id,
+            quirks,
+        }
+    }
+
+    /// Whether the entry applies to device `vendor_id` and `product_id`
+    pub fn matches(&self, vendor_id: u16, product_id: u16) -> bool {
+        self.vendor_id == vendor_id && self.product_id.is_none_or(|p| p == product_id)
+    }
+}
+
+/// Built-in entries, from the Linux kernel drivers/usb/core/quirks.c
+const BUILTIN: &[(u16, Option<u16>, &[Quirk])] = &[
+    // Elgato 4K X: BOS descriptor fetch hangs at SuperSpeed Plus
+    (0x0fd9, Some(0x009b), &[Quirk::NoBos]),
+    // AVerMedia Live Gamer Ultra 2.1 (GC553G2): BOS descriptor fetch hangs at SuperSpeed Plus
+    (0x07ca, Some(0x2553), &[Quirk::NoBos]),
+];
+
+/// Database of [`QuirkEntry`]
+#[derive(Debug, Default)]
+pub struct Database {
+    entries: Vec<QuirkEntry>,
+}
+
+impl Database {
+    /// New empty database
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// New database with the built-in entries
+    pub fn builtin() -> Self {
+        Database {
+            entries: BUILTIN
+                .iter()
+                .map(|(vid, pid, quirks)| QuirkEntry::new(*vid, *pid, quirks.to_vec()))
+                .collect(),
+        }
+    }
+
+    /// Add an entry; the quirks are in addition to those of any existing entries which match
+    pub fn add(&mut self, entry: QuirkEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Number of entries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Sorted [`Quirk`]s of all entries matching device `vendor_id` and `product_id`
+    pub fn quirks(&self, vendor_id: u16, product_id: u16) -> Vec<Quirk> {
+        let mut quirks: Vec<Quirk> = self
+            .entries
+            .iter()
+            .filter(|e| e.matches(vendor_id, product_id))
+            .flat_map(|e| e.quirks.iter().copied())
+            .collect();
+        quirks.sort();
+        quirks.dedup();
+        quirks
+    }
+}
+
+/// Annotate `device` with its [`Quirk`]s in `database` and apply those which adjust the profiled data
+pub(crate) fn apply(device: &mut Device, database: &Database) {
+    let (Some(vid), Some(pid)) = (device.vendor_id, device.product_id) else {
+        return;
+    };
+    let quirks = database.quirks(vid, pid);
+    if quirks.is_empty() {
+        return;
+    }
+
+    if quirks.contains(&Quirk::BadStringLength) {
+        device.name = trim_string(&device.name);
+        device.manufacturer = device.manufacturer.as_deref().map(trim_string);
+        device.serial_num = device.serial_num.as_deref().map(trim_string);
+        if let Some(extra) = device.extra.as_mut() {
+            for config in extra.configurations.iter_mut() {
+                config.name = trim_string(&config.name);
+                for interface in config.interfaces.iter_mut() {
+                    interface.name = interface.name.as_deref().map(trim_string);
+                }
+            }
+        }
+    }
+
+    device.quirks = Some(quirks);
+}
+
+/// Trim trailing padding read beyond the string: NULs, whitespace and the replacement character of invalid UTF-16
+fn trim_string(s: &str) -> String {
+    s.trim_end_matches(|c: char| {
+        c.is_control() || c.is_whitespace() || c == char::REPLACEMENT_CHARACTER
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quirk_database() {
+        let mut database = Database::builtin();
+        assert_eq!(database.quirks(0x07ca, 0x2553), vec![Quirk::NoBos]);
+        assert!(database.quirks(0x07ca, 0x2554).is_empty());
+
+        database.add(QuirkEntry::new(0x07ca, None, vec![Quirk::FakeSerial]));
+        database.add(QuirkEntry::new(
+            0x07ca,
+            Some(0x2553),
+            vec![Quirk::NoBos, Quirk::BadStringLength],
+        ));
+        assert_eq!(
+            database.quirks(0x07ca, 0x2553),
+            vec![Quirk::NoBos, Quirk::BadStringLength, Quirk::FakeSerial]
+        );
+        assert_eq!(database.quirks(0x07ca, 0x2554), vec![Quirk::FakeSerial]);
+    }
+
+    #[test]
+    fn test_trim_string() {
+        assert_eq!(trim_string("Device \0\0"), "Device");
+        assert_eq!(trim_string("Device\0\u{fffd}\u{fffd}"), "Device");
+        assert_eq!(trim_string("Dev\0ice"), "Dev\0ice");
+    }
+}
@@ -0,0 +1,496 @@
+//! Profile a device from raw descriptor files written by [`super::export`] rather than hardware.
+//!
+//! A [`ReplayDevice`] answers GET_DESCRIPTOR requests from the files so that the [`Device`] is built with the same descriptor parsing as a connected device. This allows parser development and reproducing fuzz findings offline. Requests which cannot be answered from the files, such as GET_STATUS or vendor requests, fail as they would with a device which does not support them.
+//!
+//! ```no_run
+//! use cyme::profiler::{replay, ProfilerBuilder};
+//!
+//! let spusb = replay::read_descriptors_to_phony_bus("descriptors", ProfilerBuilder::new()).unwrap();
+//! println!("{:#}", spusb);
+//! ```
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::*;
+use crate::types::NumericalUnit;
+
+/// Bus number the replayed device is placed on
+const REPLAY_BUS: u8 = 0;
+
+/// Device which answers control requests from raw descriptor files
+pub(crate) struct ReplayDevice {
+    /// Descriptor file name and bytes
+    files: HashMap<String, Vec<u8>>,
+    dir: PathBuf,
+    strings: bool,
+}
+
+impl std::fmt::Debug for ReplayDevice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ReplayDevice {{ dir: {} }}", self.dir.display())
+    }
+}
+
+impl ReplayDevice {
+    /// Read the `.bin` descriptor files in `dir`
+    pub(crate) fn read<P: AsRef<Path>>(dir: P, strings: bool) -> Result<Self> {
+        let mut files = HashMap::new();
+        for entry in fs::read_dir(dir.as_ref())? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "bin") {
+                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+                    files.insert(name.to_string(), fs::read(&path)?);
+                }
+            }
+        }
+
+        if !files.contains_key("device.bin") {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                &format!("No device.bin descriptor in {}", dir.as_ref().display()),
+            ));
+        }
+
+        Ok(ReplayDevice {
+            files,
+            dir: dir.as_ref().to_path_buf(),
+            strings,
+        })
+    }
+
+    fn file(&self, name: &str) -> Result<&[u8]> {
+        self.files.get(name).map(|f| f.as_slice()).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                &format!("{:?} has no {} descriptor", self, name),
+            )
+        })
+    }
+
+    /// Device descriptor
+    fn device_descriptor(&self) -> Result<&[u8]> {
+        self.file("device.bin")
+    }
+
+    /// Configuration descriptors in index order
+    fn config_descriptors(&self) -> Vec<&[u8]> {
+        (0..=u8::MAX)
+            .map_while(|i| self.file(&format!("config-{}.bin", i)).ok())
+            .collect()
+    }
+}
+
+impl UsbOperations for ReplayDevice {
+    fn get_descriptor_string(&self, string_index: u8) -> Option<String> {
+        if string_index == 0 || !self.strings {
+            return None;
+        }
+        let data = self.file(&format!("string-{}.bin", string_index)).ok()?;
+        let length = (*data.first()? as usize).min(data.len());
+        let utf16: Vec<u16> = data
+            .get(2..length)?
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+
+        Some(
+            String::from_utf16_lossy(&utf16)
+                .chars()
+                .filter(|c| !c.is_control())
+                .collect(),
+        )
+    }
+
+    fn get_control_msg(&self, control_request: ControlRequest) -> Result<Vec<u8>> {
+        if control_request.control_type != ControlType::Standard
+            || control_request.request != REQUEST_GET_DESCRIPTOR
+        {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                &format!(
+                    "{:?} Only standard GET_DESCRIPTOR requests can be replayed",
+                    self
+                ),
+            ));
+        }
+
+        let index = control_request.value as u8;
+        let name = match (
+            control_request.recipient,
+            usb::DescriptorType::from((control_request.value >> 8) as u8),
+        ) {
+            (Recipient::Device, usb::DescriptorType::Device) => String::from("device.bin"),
+            (Recipient::Device, usb::DescriptorType::Config) => format!("config-{}.bin", index),
+            (Recipient::Device, usb::DescriptorType::Bos) => String::from("bos.bin"),
+            (Recipient::Device, usb::DescriptorType::String) => format!("string-{}.bin", index),
+            (Recipient::Interface, usb::DescriptorType::Report) => {
+                format!("hid-report-{}.bin", control_request.index)
+            }
+            (recipient, descriptor_type) => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    &format!(
+                        "{:?} {:?} descriptor to {:?} is not exported so cannot be replayed",
+                        self, descriptor_type, recipient
+                    ),
+                ))
+            }
+        };
+
+        // like a device, return at most the requested length but fail if short
+        let data = self.file(&name)?;
+        if data.len() < control_request.length {
+            return Err(Error::new(
+                ErrorKind::InvalidDescriptor,
+                &format!(
+                    "{:?} {} is {} bytes but {} requested",
+                    self,
+                    name,
+                    data.len(),
+                    control_request.length
+                ),
+            ));
+        }
+
+        Ok(data[..control_request.length].to_vec())
+    }
+}
+
+/// Profiler building a [`Device`] from a [`ReplayDevice`]
+#[derive(Debug)]
+pub(crate) struct ReplayProfiler {
+    options: ProfilerBuilder,
+    dir: PathBuf,
+}
+
+impl ReplayProfiler {
+    pub fn new<P: AsRef<Path>>(dir: P, options: ProfilerBuilder) -> Self {
+        ReplayProfiler {
+            options,
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn location() -> DeviceLocation {
+        DeviceLocation {
+            bus: REPLAY_BUS,
+            tree_positions: vec![1],
+            number: 1,
+        }
+    }
+
+    fn build_endpoints(
+        &self,
+        device: &ReplayDevice,
+        interface: &usb::SplitInterface,
+    ) -> Result<Vec<usb::Endpoint>> {
+        let (number, class_code) = (
+            interface.interface[2],
+            (
+                interface.interface[5],
+                interface.interface[6],
+                interface.interface[7],
+            ),
+        );
+
+        interface
+            .endpoints
+            .iter()
+            .map(|(desc, extra)| {
+                Ok(usb::Endpoint {
+                    address: usb::EndpointAddress::from(desc[2]),
+                    transfer_type: usb::TransferType::from(desc[3]),
+                    sync_type: usb::SyncType::from(desc[3]),
+                    usage_type: usb::UsageType::from(desc[3]),
+                    max_packet_size: u16::from_le_bytes([desc[4], desc[5]]),
+                    interval: desc[6],
+                    length: desc[0],
+                    extra: self
+                        .options
+                        .parse_mode
+                        .tolerate(self.build_endpoint_descriptor_extra(
+                            device,
+                            class_code,
+                            number,
+                            extra.to_vec(),
+                        ))?
+                        .flatten(),
+                    raw: None,
+                })
+            })
+            .collect()
+    }
+
+    fn build_interfaces(
+        &self,
+        device: &ReplayDevice,
+        config: &usb::SplitConfiguration,
+    ) -> Result<Vec<usb::Interface>> {
+        let location = Self::location();
+
+        config
+            .interfaces
+            .iter()
+            .map(|i| {
+                let desc = i.interface;
+                let class_code = (desc[5], desc[6], desc[7]);
+                Ok(usb::Interface {
+                    name: device.get_descriptor_string(desc[8]),
+                    string_index: desc[8],
+                    number: desc[2],
+                    path: usb::get_interface_path(
+                        location.bus,
+                        &location.tree_positions,
+                        config.config[5],
+                        desc[2],
+                    ),
+                    class: usb::BaseClass::from(desc[5]),
+                    sub_class: desc[6],
+                    protocol: desc[7],
+                    alt_setting: desc[3],
+                    driver: None,
+                    syspath: None,
+                    endpoints: self.build_endpoints(device, i)?,
+                    length: desc[0],
+                    extra: self.options.parse_mode.tolerate(
+                        self.build_interface_descriptor_extra(
+                            device,
+                            class_code,
+                            desc[2],
+                            i.extra.to_vec(),
+                        ),
+                    )?,
+                    bluetooth: None,
+                    module: None,
+                    vendor_fields: None,
+                    raw: None,
+                })
+            })
+            .collect()
+    }
+
+    fn build_configurations(&self, device: &ReplayDevice) -> Result<Vec<usb::Configuration>> {
+        let mut ret = Vec::new();
+
+        for data in device.config_descriptors() {
+            let split = usb::split_configuration(data)?;
+            if !split.trailing.is_empty() {
+                let e = Error::new(
+                    ErrorKind::InvalidDescriptor,
+                    &format!(
+                        "Malformed descriptor in configuration, ignoring {} trailing bytes",
+                        split.trailing.len()
+                    ),
+                );
+                if self.options.parse_mode.is_fatal(&e) {
+                    return Err(e);
+                }
+                log::warn!("{:?} {:#}", device, e);
+            }
+            let desc = split.config;
+            let mut attributes = Vec::new();
+            if desc[7] & 0x10 != 0 {
+                attributes.push(usb::ConfigAttributes::BatteryPowered);
+            }
+            if desc[7] & 0x20 != 0 {
+                attributes.push(usb::ConfigAttributes::RemoteWakeup);
+            }
+            if desc[7] & 0x40 != 0 {
+                attributes.push(usb::ConfigAttributes::SelfPowered);
+            }
+
+            let mut config = usb::Configuration {
+                name: device.get_descriptor_string(desc[6]).unwrap_or_default(),
+                string_index: desc[6],
+                number: desc[5],
+                interfaces: self.build_interfaces(device, &split)?,
+                attributes,
+                max_power: NumericalUnit {
+                    // bMaxPower is in 2mA units
+                    value: desc[8] as u32 * 2,
+                    unit: String::from("mA"),
+                    description: None,
+                },
+                length: desc[0],
+                total_length: u16::from_le_bytes([desc[2], desc[3]]),
+                extra: self
+                    .options
+                    .parse_mode
+                    .tolerate(self.build_config_descriptor_extra(device, split.extra.to_vec()))?,
+                raw: None,
+                active: false,
+                warnings: Vec::new(),
+            };
+
+            if self.options.validate_descriptors {
+                config.warnings = usb::descriptors::validate_configuration(data);
+            }
+            if self.options.raw_descriptors {
+                config.set_raw_descriptors(data);
+            }
+
+            ret.push(config);
+        }
+
+        Ok(ret)
+    }
+
+    fn build_spdevice(&self, device: &ReplayDevice) -> Result<Device> {
+        let device_desc = usb::DeviceDescriptor::try_from(device.device_descriptor()?)?;
+
+        let mut sp_device = Device {
+            vendor_id: Some(device_desc.vendor_id),
+            product_id: Some(device_desc.product_id),
+            location_id: Self::location(),
+            bcd_device: Some(device_desc.device_version),
+            bcd_usb: Some(device_desc.usb_version),
+            class: Some(usb::BaseClass::from(device_desc.device_class)),
+            sub_class: Some(device_desc.device_sub_class),
+            protocol: Some(device_desc.device_protocol),
+            // USB IDs names are resolved after profiling if missing
+            name: device
+                .get_descriptor_string(device_desc.product_string_index)
+                .unwrap_or_default(),
+            manufacturer: device.get_descriptor_string(device_desc.manufacturer_string_index),
+            name_source: Some(names::NameSource::Descriptor),
+            manufacturer_source: Some(names::NameSource::Descriptor),
+            serial_num: device.get_descriptor_string(device_desc.serial_number_string_index),
+            ..Default::default()
+        };
+
+        let mut extra = usb::DeviceExtra {
+            max_packet_size: device_desc.max_packet_size,
+            string_indexes: (
+                device_desc.product_string_index,
+                device_desc.manufacturer_string_index,
+                device_desc.serial_number_string_index,
+            ),
+            driver: None,
+            syspath: None,
+            vendor: self.options.vendor_name(device_desc.vendor_id),
+            product_name: self
+                .options
+                .product_name(device_desc.vendor_id, device_desc.product_id),
+            configurations: self.build_configurations(device)?,
+            status: None,
+            debug: None,
+            binary_object_store: None,
+            qualifier: None,
+            hub: None,
+            vendor_fields: None,
+        };
+
+        if device_desc.usb_version >= usb::Version::from_bcd(0x0201) {
+            extra.binary_object_store = self
+                .options
+                .parse_mode
+                .tolerate(Self::get_bos_descriptor(device))?;
+        }
+
+        sp_device.extra = Some(extra);
+
+        Ok(sp_device)
+    }
+}
+
+impl Profiler<ReplayDevice> for ReplayProfiler {
+    fn options(&self) -> &ProfilerBuilder {
+        &self.options
+    }
+
+    fn get_devices(&mut self, _with_extra: bool) -> Result<Vec<Device>> {
+        let device = ReplayDevice::read(&self.dir, self.options.strings)?;
+        Ok(vec![self.build_spdevice(&device)?])
+    }
+
+    fn get_root_hubs(&mut self) -> Result<HashMap<u8, Device>> {
+        Ok(HashMap::new())
+    }
+
+    fn get_buses(&mut self) -> Result<HashMap<u8, Bus>> {
+        let bus = Bus {
+            name: String::from("Phony Replayed Descriptors Bus"),
+            host_controller: String::from("Phony Host Controller"),
+            host_controller_vendor: None,
+            host_controller_device: None,
+            pci_device: None,
+            pci_vendor: None,
+            pci_revision: None,
+            usb_bus_number: Some(REPLAY_BUS),
+            devices: None,
+            usb4_tunnelled: None,
+            role: None,
+        };
+
+        Ok(HashMap::from([(REPLAY_BUS, bus)]))
+    }
+}
+
+/// Build the [`Device`] from the raw descriptor files in `dir`, as written by [`super::export::export_descriptors`]
+///
+/// The device is placed at port path '0-1'. Data which is not in the descriptors, such as the driver and device speed, is not available.
+pub fn read_descriptors<P: AsRef<Path>>(dir: P, options: ProfilerBuilder) -> Result<Device> {
+    let mut profiler = ReplayProfiler::new(dir, options);
+    let mut devices = profiler.get_devices(true)?;
+    for device in devices.iter_mut() {
+        profiler.options.apply(device, true);
+    }
+    devices
+        .pop()
+        .ok_or_else(|| Error::new(ErrorKind::NotFound, "No device replayed"))
+}
+
+/// Build a [`SystemProfile`] with the [`Device`] from the raw descriptor files in `dir` on a phony bus
+pub fn read_descriptors_to_phony_bus<P: AsRef<Path>>(
+    dir: P,
+    options: ProfilerBuilder,
+) -> Result<SystemProfile> {
+    let mut profiler = ReplayProfiler::new(dir, options);
+    profiler.get_spusb(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mode() {
+        let dir = std::env::temp_dir().join(format!("cyme-replay-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("device.bin"),
+            [
+                0x12, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x40, 0x09, 0x12, 0x01, 0x00, 0x00, 0x01,
+                0x00, 0x00, 0x00, 0x01,
+            ],
+        )
+        .unwrap();
+        // HID interface with a HID descriptor declaring a report descriptor it does not have
+        fs::write(
+            dir.join("config-0.bin"),
+            [
+                0x09, 0x02, 0x18, 0x00, 0x01, 0x01, 0x00, 0xa0, 0x32, 0x09, 0x04, 0x00, 0x00, 0x00,
+                0x03, 0x00, 0x00, 0x00, 0x06, 0x21, 0x11, 0x01, 0x00, 0x01,
+            ],
+        )
+        .unwrap();
+
+        let lenient = read_descriptors(&dir, ProfilerBuilder::new().ids(false));
+        let strict = read_descriptors(
+            &dir,
+            ProfilerBuilder::new()
+                .ids(false)
+                .parse_mode(usb::ParseMode::Strict),
+        );
+        fs::remove_dir_all(&dir).unwrap();
+
+        let device = lenient.unwrap();
+        let interface = &device.extra.unwrap().configurations[0].interfaces[0];
+        assert!(matches!(
+            interface.extra.as_ref().unwrap()[0],
+            usb::Descriptor::Device(usb::ClassDescriptor::Generic(..))
+        ));
+        assert_eq!(strict.unwrap_err().kind(), ErrorKind::InvalidDescriptor);
+    }
+}
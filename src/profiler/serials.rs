@@ -0,0 +1,206 @@
+//! Devices sharing a serial number or without one; see [`report`]
+//!
+//! udev rules and provisioning scripts commonly key on the serial number to tell devices of the same model apart. Cheap devices often have the same serial in every unit or none at all so the rule matches the wrong device, or every one.
+//!
+//! ```
+//! use cyme::profiler::read_json_dump;
+//! use cyme::profiler::serials;
+//!
+//! let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+//! // Virtual Mouse and Virtual USB1.1 HUB share a serial but hubs are not included
+//! let report = serials::report(spusb.iter());
+//! assert!(report.is_empty());
+//! ```
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::{Device, Filter, SystemProfile};
+use crate::error::Result;
+use crate::redact::Redaction;
+
+/// Identity of a [`Device`] in a [`SerialReport`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerialDevice {
+    /// Port path of the device
+    pub port_path: String,
+    /// Vendor and product ID as 'vid:pid'
+    pub vidpid: String,
+    /// Name of the device
+    pub name: String,
+}
+
+impl From<&Device> for SerialDevice {
+    fn from(device: &Device) -> Self {
+        SerialDevice {
+            port_path: device.port_path(),
+            vidpid: format!(
+                "{:04x}:{:04x}",
+                device.vendor_id.unwrap_or(0),
+                device.product_id.unwrap_or(0)
+            ),
+            name: device.name.to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for SerialDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} '{}'", self.port_path, self.vidpid, self.name)
+    }
+}
+
+/// A serial number shared by more than one device
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateSerial {
+    /// The serial number
+    pub serial: String,
+    /// Devices with the serial number
+    pub devices: Vec<SerialDevice>,
+}
+
+impl fmt::Display for DuplicateSerial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "serial '{}' shared by {}",
+            self.serial,
+            self.devices
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+/// Devices sharing a serial number or without one from [`report`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerialReport {
+    /// Serial numbers shared by more than one device, ordered by serial
+    pub duplicates: Vec<DuplicateSerial>,
+    /// Devices without a serial number
+    pub missing: Vec<SerialDevice>,
+}
+
+impl SerialReport {
+    /// Whether no devices share a serial number or are without one
+    pub fn is_empty(&self) -> bool {
+        self.duplicates.is_empty() && self.missing.is_empty()
+    }
+
+    /// Port paths of all devices in the report
+    pub fn port_paths(&self) -> HashSet<String> {
+        self.duplicates
+            .iter()
+            .flat_map(|d| d.devices.iter())
+            .chain(self.missing.iter())
+            .map(|d| d.port_path.to_owned())
+            .collect()
+    }
+}
+
+/// Whether `device` has no serial number or one which is only whitespace
+pub fn is_missing(device: &Device) -> bool {
+    device
+        .serial_num
+        .as_deref()
+        .is_none_or(|s| s.trim().is_empty())
+}
+
+/// Report `devices` sharing a serial number, regardless of VID:PID, or without one
+///
+/// Hubs are not included as they rarely have a serial number and are not usually what rules match.
+pub fn report<'a, I: IntoIterator<Item = &'a Device>>(devices: I) -> SerialReport {
+    let mut report = SerialReport::default();
+    let mut serials: HashMap<&str, Vec<&Device>> = HashMap::new();
+    for device in devices.into_iter().filter(|d| !d.is_hub()) {
+        match device.serial_num.as_deref() {
+            Some(s) if !is_missing(device) => serials.entry(s).or_default().push(device),
+            _ => report.missing.push(SerialDevice::from(device)),
+        }
+    }
+    report.duplicates = serials
+        .into_iter()
+        .filter(|(_, d)| d.len() > 1)
+        .map(|(serial, devices)| DuplicateSerial {
+            serial: serial.to_string(),
+            devices: devices.into_iter().map(SerialDevice::from).collect(),
+        })
+        .collect();
+    report.duplicates.sort_by(|a, b| a.serial.cmp(&b.serial));
+    report
+}
+
+/// [`report`] of devices in `spusb` matching `filter` for `cyme serials`, redacted with `redaction`
+pub fn run(
+    spusb: &SystemProfile,
+    filter: &Filter,
+    redaction: Option<&Redaction>,
+) -> Result<SerialReport> {
+    let mut report = report(spusb.iter().filter(|d| filter.is_match(d)));
+    if let Some(redaction) = redaction {
+        redaction.apply_serials(&mut report)?;
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::read_json_dump;
+
+    #[test]
+    fn test_report() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let mut devices: Vec<Device> = spusb.iter().cloned().collect();
+        // J-Link with the serial of the Black Magic Probe and the Virtual Printer with a blank serial
+        for device in devices.iter_mut() {
+            match device.port_path().as_str() {
+                "2-2.1" => device.serial_num = Some(String::from("97B6A11D")),
+                "1-6" => device.serial_num = Some(String::from("  ")),
+                _ => (),
+            }
+        }
+        let report = report(devices.iter());
+        assert_eq!(report.duplicates.len(), 1);
+        assert_eq!(report.duplicates[0].serial, "97B6A11D");
+        assert_eq!(
+            report.duplicates[0]
+                .devices
+                .iter()
+                .map(|d| d.port_path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["2-2.1", "2-2.8"]
+        );
+        assert_eq!(
+            report
+                .missing
+                .iter()
+                .map(|d| d.port_path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["1-6"]
+        );
+        assert_eq!(report.port_paths().len(), 3);
+    }
+
+    #[test]
+    fn test_run_serials() {
+        let mut spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        assert!(run(&spusb, &Filter::default(), None)
+            .unwrap()
+            .duplicates
+            .is_empty());
+
+        spusb.get_node_mut("2-2.1").unwrap().serial_num = Some(String::from("97B6A11D"));
+        let report = run(&spusb, &Filter::default(), None).unwrap();
+        assert_eq!(report.duplicates.len(), 1);
+        // the other device is not included
+        let filter = Filter {
+            vid: Some(0x1d50),
+            ..Default::default()
+        };
+        assert!(run(&spusb, &filter, None).unwrap().duplicates.is_empty());
+    }
+}
@@ -0,0 +1,300 @@
+//! [`SystemProfile`] shared between threads and updated incrementally as devices connect and disconnect; see [`SharedProfile`]
+//!
+//! Rather than profiling every device again, [`SharedProfile::refresh`] lists the connected devices without opening them and only profiles those that are new, removing those that have gone. Subscribers receive a [`WatchEvent`] for each change so a dashboard or the HTTP server can follow the profile without polling it.
+//!
+//! ```no_run
+//! use cyme::profiler::{ProfilerBuilder, SharedProfile};
+//!
+//! let builder = ProfilerBuilder::new();
+//! let shared = SharedProfile::new(builder.profile().unwrap());
+//! let events = shared.subscribe();
+//!
+//! let updater = shared.clone();
+//! std::thread::spawn(move || loop {
+//!     std::thread::sleep(std::time::Duration::from_millis(500));
+//!     updater.refresh(&builder).unwrap();
+//! });
+//!
+//! for event in events {
+//!     println!("{} {}", event.name(), event.device().port_path());
+//!     println!("{} devices", shared.read().len());
+//! }
+//! ```
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{mpsc, Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use super::watch::WatchEvent;
+use super::{Device, ProfilerBuilder, SystemProfile};
+use crate::error::{Error, ErrorKind, Result};
+use crate::usb::PortPath;
+
+/// Devices of `profile`, not including root hubs, by port path and device number
+fn device_keys(profile: &SystemProfile) -> BTreeMap<(PortPath, u8), &Device> {
+    profile
+        .iter()
+        .filter(|d| !d.is_root_hub())
+        .map(|d| ((d.path(), d.location_id.number), d))
+        .collect()
+}
+
+/// `device` and the devices connected to it as a flat list without children, as reported in [`WatchEvent`]s
+fn flattened(device: &Device) -> Vec<Device> {
+    device
+        .iter()
+        .map(|d| Device {
+            devices: None,
+            ..d.clone()
+        })
+        .collect()
+}
+
+/// Devices connected to the same hub or bus as the device at `path`; `None` if the hub or bus is not in `profile`
+fn siblings_mut<'a>(
+    profile: &'a mut SystemProfile,
+    path: &PortPath,
+) -> Option<&'a mut Vec<Device>> {
+    let devices = match path.parent() {
+        Some(parent) if !parent.is_root() => &mut profile.get_node_at_mut(&parent)?.devices,
+        _ => &mut profile.get_bus_mut(path.bus)?.devices,
+    };
+    Some(devices.get_or_insert_with(Vec::new))
+}
+
+/// Profile and subscribers to changes behind an [`Arc`] so clones share the same profile; see the [module docs](self)
+#[derive(Debug, Clone)]
+pub struct SharedProfile {
+    profile: Arc<RwLock<SystemProfile>>,
+    subscribers: Arc<Mutex<Vec<mpsc::Sender<WatchEvent>>>>,
+}
+
+impl SharedProfile {
+    /// Share `profile`
+    pub fn new(profile: SystemProfile) -> Self {
+        SharedProfile {
+            profile: Arc::new(RwLock::new(profile)),
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Read the current profile; updates wait until the guard is dropped so hold it briefly
+    pub fn read(&self) -> RwLockReadGuard<'_, SystemProfile> {
+        self.profile.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, SystemProfile> {
+        self.profile.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Receive a [`WatchEvent`] for each device connecting or disconnecting from now on; dropping the receiver unsubscribes
+    pub fn subscribe(&self) -> mpsc::Receiver<WatchEvent> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(tx);
+        rx
+    }
+
+    /// Send `events` to the subscribers, dropping those which have unsubscribed
+    fn notify(&self, events: &[WatchEvent]) {
+        if events.is_empty() {
+            return;
+        }
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|s| events.iter().all(|e| s.send(e.clone()).is_ok()));
+    }
+
+    /// Add `device`, profiled elsewhere, under its hub or bus, returning the [`WatchEvent::Connected`] for it and any devices connected to it
+    ///
+    /// A device already at the port path is replaced. Errors with [`ErrorKind::NotFound`] if its hub or bus is not in the profile.
+    pub fn insert(&self, device: Device) -> Result<Vec<WatchEvent>> {
+        let mut events: Vec<WatchEvent> = Vec::new();
+        let path = device.path();
+        {
+            let mut profile = self.write();
+            let siblings = siblings_mut(&mut profile, &path).ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    &format!("No hub or bus for {} in the shared profile", path),
+                )
+            })?;
+            if let Some(i) = siblings.iter().position(|d| d.path() == path) {
+                events.extend(
+                    flattened(&siblings.remove(i))
+                        .into_iter()
+                        .map(WatchEvent::Disconnected),
+                );
+            }
+            events.extend(flattened(&device).into_iter().map(WatchEvent::Connected));
+            siblings.push(device);
+            siblings.sort_by_key(|d| d.get_branch_position());
+        }
+        self.notify(&events);
+        Ok(events)
+    }
+
+    /// Remove the device at `path` and those connected to it, returning the [`WatchEvent::Disconnected`] for each; empty if there is no device at `path`
+    pub fn remove(&self, path: &PortPath) -> Vec<WatchEvent> {
+        let removed = {
+            let mut profile = self.write();
+            siblings_mut(&mut profile, path).and_then(|siblings| {
+                siblings
+                    .iter()
+                    .position(|d| d.path() == *path)
+                    .map(|i| siblings.remove(i))
+            })
+        };
+        let events: Vec<WatchEvent> = removed
+            .iter()
+            .flat_map(flattened)
+            .map(WatchEvent::Disconnected)
+            .collect();
+        self.notify(&events);
+        events
+    }
+
+    /// Replace the profile with `profile`, such as one profiled again or read from a json dump, returning the events for the devices which differ
+    pub fn replace(&self, profile: SystemProfile) -> Vec<WatchEvent> {
+        let events = {
+            let mut current = self.write();
+            let mut new = device_keys(&profile);
+            let mut events: Vec<WatchEvent> = Vec::new();
+            for (key, device) in device_keys(&current) {
+                if new.remove(&key).is_none() {
+                    events.push(WatchEvent::Disconnected(Device {
+                        devices: None,
+                        ..device.clone()
+                    }));
+                }
+            }
+            events.extend(new.into_values().map(|d| {
+                WatchEvent::Connected(Device {
+                    devices: None,
+                    ..d.clone()
+                })
+            }));
+            *current = profile;
+            events
+        };
+        self.notify(&events);
+        events
+    }
+
+    /// Update the profile with the devices which have connected or disconnected since it was profiled, returning the events
+    ///
+    /// Devices are listed with `builder` but without opening them, then only new devices are profiled with `builder`. Falls back to profiling the system again if a device connects to a hub or bus not in the profile. Only the device tree is updated; buses and other sections of the profile are kept as they were.
+    pub fn refresh(&self, builder: &ProfilerBuilder) -> Result<Vec<WatchEvent>> {
+        let mut listed: BTreeSet<(PortPath, u8)> = builder
+            .clone()
+            .with_extra(false)
+            .strings(false)
+            .ids(false)
+            .udev(false)
+            .devices_iter()?
+            .map(|d| (d.path(), d.location_id.number))
+            .collect();
+
+        let mut removed: Vec<PortPath> = Vec::new();
+        for key in device_keys(&self.read()).into_keys() {
+            if !listed.remove(&key) {
+                removed.push(key.0);
+            }
+        }
+        if removed.is_empty() && listed.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // hubs before the devices connected to them
+        removed.sort_by_key(|p| p.depth());
+        let mut added: Vec<(PortPath, u8)> = listed.into_iter().collect();
+        added.sort_by_key(|(p, _)| p.depth());
+        log::debug!(
+            "Refresh shared profile {} removed {} added",
+            removed.len(),
+            added.len()
+        );
+
+        let mut events: Vec<WatchEvent> = Vec::new();
+        for path in removed.iter() {
+            events.extend(self.remove(path));
+        }
+        for (path, number) in added {
+            // profiled outside the lock so readers are not blocked by opening the device
+            let device = match builder.get_device(path.bus, number) {
+                Ok(d) => d,
+                Err(e) => {
+                    // probably disconnected already; added again next refresh if not
+                    log::debug!("Failed to profile {} for shared profile: {}", path, e);
+                    continue;
+                }
+            };
+            match self.insert(device) {
+                Ok(e) => events.extend(e),
+                Err(e) => {
+                    log::debug!("{}, profiling the system", e);
+                    events.extend(self.replace(builder.profile()?));
+                    break;
+                }
+            }
+        }
+
+        Ok(events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::read_json_dump;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_insert_remove() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let shared =
+            SharedProfile::new(read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap());
+        let events = shared.subscribe();
+        let path = PortPath::from_str("2-2.8").unwrap();
+        let device = spusb.get_node_at(&path).unwrap().clone();
+        let len = spusb.len();
+
+        let removed = shared.remove(&path);
+        assert_eq!(removed.len(), 1 + device.descendants().count());
+        assert!(removed.iter().all(|e| !e.is_connected()));
+        assert!(shared.read().get_node_at(&path).is_none());
+        assert_eq!(shared.read().len(), len - removed.len());
+        assert!(shared.remove(&path).is_empty());
+
+        let added = shared.insert(device).unwrap();
+        assert_eq!(added.len(), removed.len());
+        assert_eq!(shared.read().len(), len);
+        assert!(shared.read().get_node_at(&path).is_some());
+        assert_eq!(events.try_iter().count(), removed.len() + added.len());
+
+        let orphan = Device {
+            location_id: crate::profiler::DeviceLocation {
+                bus: 99,
+                number: 1,
+                tree_positions: vec![1],
+            },
+            ..Default::default()
+        };
+        assert!(shared.insert(orphan).is_err());
+    }
+
+    #[test]
+    fn test_replace() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let shared = SharedProfile::new(SystemProfile {
+            buses: Vec::new(),
+            thunderbolt: None,
+            gadget: None,
+        });
+        let events = shared.replace(spusb);
+        assert_eq!(events.len(), device_keys(&shared.read()).len());
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        assert!(shared.replace(spusb).is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+//! Thunderbolt/USB4 domain and router topology from sysfs on Linux.
+//!
+//! USB4 hosts and docks are routers on a Thunderbolt bus, separate from the USB bus that they tunnel. The routers are enumerated from `/sys/bus/thunderbolt/devices` so that the topology of docks can be shown alongside the USB devices they host.
+//!
+//! ```no_run
+//! use cyme::profiler::thunderbolt;
+//!
+//! for domain in thunderbolt::get_domains().unwrap() {
+//!     println!("{}", domain);
+//! }
+//! ```
+use serde::{Deserialize, Serialize};
+use serde_with::skip_serializing_none;
+use std::fmt;
+
+use crate::error::{Error, ErrorKind, Result};
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SYSFS_THUNDERBOLT_PREFIX: &str = "/sys/bus/thunderbolt/devices/";
+
+/// A Thunderbolt domain; one per host controller (NHI)
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThunderboltDomain {
+    /// sysfs name such as 'domain0'
+    pub name: String,
+    /// Security level of the domain: 'none', 'user', 'secure', 'dponly' or 'usbonly'
+    pub security: Option<String>,
+    /// Routers directly connected to the domain; normally just the host router
+    #[serde(default)]
+    pub routers: Vec<ThunderboltRouter>,
+}
+
+impl fmt::Display for ThunderboltDomain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.security.as_ref() {
+            Some(s) => write!(f, "{} (security: {})", self.name, s),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+impl ThunderboltDomain {
+    /// Total number of routers in the domain including the host router
+    pub fn len(&self) -> usize {
+        self.routers.iter().map(|r| r.len()).sum()
+    }
+
+    /// Whether the domain has no routers
+    pub fn is_empty(&self) -> bool {
+        self.routers.is_empty()
+    }
+}
+
+/// A Thunderbolt/USB4 router; the host or a connected device such as a dock
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThunderboltRouter {
+    /// sysfs name as domain-route such as '0-0' for the host router or '0-1'
+    pub name: String,
+    /// Vendor name reported by the router
+    pub vendor_name: Option<String>,
+    /// Device name reported by the router
+    pub device_name: Option<String>,
+    /// Vendor ID
+    pub vendor_id: Option<u16>,
+    /// Device ID
+    pub device_id: Option<u16>,
+    /// Thunderbolt generation; 4 is USB4
+    pub generation: Option<u8>,
+    /// UUID of the router
+    pub unique_id: Option<String>,
+    /// Whether the router has been authorized; None for the host router
+    pub authorized: Option<bool>,
+    /// Receive link speed such as '20.0 Gb/s'
+    pub rx_speed: Option<String>,
+    /// Number of receive lanes
+    pub rx_lanes: Option<u8>,
+    /// Transmit link speed such as '20.0 Gb/s'
+    pub tx_speed: Option<String>,
+    /// Number of transmit lanes
+    pub tx_lanes: Option<u8>,
+    /// Routers connected downstream of this one
+    #[serde(default)]
+    pub routers: Vec<ThunderboltRouter>,
+}
+
+impl fmt::Display for ThunderboltRouter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(v) = self.vendor_name.as_ref() {
+            write!(f, " {}", v)?;
+        }
+        if let Some(d) = self.device_name.as_ref() {
+            write!(f, " {}", d)?;
+        }
+        if let Some(g) = self.generation {
+            write!(f, " Gen {}", g)?;
+        }
+        if let Some(s) = self.rx_speed.as_ref() {
+            write!(f, " {}", s)?;
+            if let Some(l) = self.rx_lanes {
+                write!(f, " x{}", l)?;
+            }
+        }
+        if self.authorized == Some(false) {
+            write!(f, " (unauthorized)")?;
+        }
+        Ok(())
+    }
+}
+
+impl ThunderboltRouter {
+    /// Number of routers including this one and all downstream
+    pub fn len(&self) -> usize {
+        1 + self.routers.iter().map(|r| r.len()).sum::<usize>()
+    }
+
+    /// Always false since the router itself is counted
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Is this the host router of the domain; route 0
+    pub fn is_host(&self) -> bool {
+        self.name.ends_with("-0")
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn from_sysfs(name: &str) -> Self {
+        let hex = |attr: &str| {
+            get_sysfs_string(name, attr)
+                .and_then(|s| u16::from_str_radix(s.trim_start_matches("0x"), 16).ok())
+        };
+        ThunderboltRouter {
+            name: name.to_string(),
+            vendor_name: get_sysfs_string(name, "vendor_name"),
+            device_name: get_sysfs_string(name, "device_name"),
+            vendor_id: hex("vendor"),
+            device_id: hex("device"),
+            generation: get_sysfs_string(name, "generation").and_then(|s| s.parse().ok()),
+            unique_id: get_sysfs_string(name, "unique_id"),
+            authorized: get_sysfs_string(name, "authorized").map(|s| s != "0"),
+            rx_speed: get_sysfs_string(name, "rx_speed"),
+            rx_lanes: get_sysfs_string(name, "rx_lanes").and_then(|s| s.parse().ok()),
+            tx_speed: get_sysfs_string(name, "tx_speed"),
+            tx_lanes: get_sysfs_string(name, "tx_lanes").and_then(|s| s.parse().ok()),
+            routers: Vec::new(),
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn get_sysfs_string(name: &str, attr: &str) -> Option<String> {
+    std::fs::read_to_string(format!("{}{}/{}", SYSFS_THUNDERBOLT_PREFIX, name, attr))
+        .ok()
+        .map(|s| s.trim().to_string())
+}
+
+/// Routers are named domain-route, with route in hex; retimers and XDomain connections have a ':' or '.' suffix
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn is_router_name(name: &str) -> bool {
+    name.split_once('-').is_some_and(|(domain, route)| {
+        domain.parse::<u8>().is_ok() && u64::from_str_radix(route, 16).is_ok()
+    })
+}
+
+/// Get the Thunderbolt domains and their router topology from sysfs; Linux only
+pub fn get_domains() -> Result<Vec<ThunderboltDomain>> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use std::collections::HashMap;
+
+        let entries = std::fs::read_dir(SYSFS_THUNDERBOLT_PREFIX).map_err(|e| {
+            Error::new(
+                ErrorKind::NotFound,
+                &format!(
+                    "Failed to read {}, is the thunderbolt driver loaded? Error({})",
+                    SYSFS_THUNDERBOLT_PREFIX, e
+                ),
+            )
+        })?;
+
+        let mut domains: Vec<ThunderboltDomain> = Vec::new();
+        // routers keyed by the sysfs name of their parent, which is the domain or upstream router
+        let mut children: HashMap<String, Vec<ThunderboltRouter>> = HashMap::new();
+
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("domain") {
+                domains.push(ThunderboltDomain {
+                    security: get_sysfs_string(&name, "security"),
+                    name,
+                    routers: Vec::new(),
+                });
+            } else if is_router_name(&name) {
+                let parent = std::fs::read_link(entry.path())
+                    .ok()
+                    .and_then(|p| {
+                        p.parent()
+                            .and_then(|pp| pp.file_name())
+                            .map(|f| f.to_string_lossy().to_string())
+                    })
+                    .unwrap_or_default();
+                log::trace!("Thunderbolt router {} with parent {}", name, parent);
+                children
+                    .entry(parent)
+                    .or_default()
+                    .push(ThunderboltRouter::from_sysfs(&name));
+            }
+        }
+
+        fn attach(
+            routers: &mut [ThunderboltRouter],
+            children: &mut HashMap<String, Vec<ThunderboltRouter>>,
+        ) {
+            routers.sort_by(|a, b| a.name.cmp(&b.name));
+            for router in routers.iter_mut() {
+                if let Some(mut downstream) = children.remove(&router.name) {
+                    attach(&mut downstream, children);
+                    router.routers = downstream;
+                }
+            }
+        }
+
+        for domain in domains.iter_mut() {
+            if let Some(mut routers) = children.remove(&domain.name) {
+                attach(&mut routers, &mut children);
+                domain.routers = routers;
+            }
+        }
+        domains.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(domains)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    return Err(Error::new(
+        ErrorKind::Unsupported,
+        "Thunderbolt topology is only available on Linux",
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_router_display_and_len() {
+        let dock = ThunderboltRouter {
+            name: String::from("0-1"),
+            vendor_name: Some(String::from("CalDigit, Inc.")),
+            device_name: Some(String::from("TS4")),
+            generation: Some(3),
+            authorized: Some(false),
+            rx_speed: Some(String::from("20.0 Gb/s")),
+            rx_lanes: Some(2),
+            ..Default::default()
+        };
+        let host = ThunderboltRouter {
+            name: String::from("0-0"),
+            routers: vec![dock],
+            ..Default::default()
+        };
+
+        assert!(host.is_host());
+        assert_eq!(host.len(), 2);
+        assert_eq!(
+            host.routers[0].to_string(),
+            "0-1 CalDigit, Inc. TS4 Gen 3 20.0 Gb/s x2 (unauthorized)"
+        );
+    }
+
+    #[test]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn test_is_router_name() {
+        assert!(is_router_name("0-0"));
+        assert!(is_router_name("1-301"));
+        assert!(!is_router_name("domain0"));
+        assert!(!is_router_name("0-0:1.1"));
+        assert!(!is_router_name("0-1.1"));
+    }
+}
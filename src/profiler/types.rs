@@ -1,7 +1,7 @@
 //! USB data structures for system profiling of USB devices and their descriptors.
 //!
 //! Originally based on serde deserialization of `system_profiler -json` output but now used as data structures for all platforms. Not all fields are used on all platforms or are completely logically in hindsight but it works. Naming is also based on `system_profiler` (SP..) and not very Rustian...
-use colored::*;
+use crate::colour::colored::*;
 use serde::de::{self, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_with::{skip_serializing_none, DeserializeFromStr, SerializeDisplay};
@@ -17,11 +17,17 @@ use crate::types::NumericalUnit;
 use crate::usb::*;
 
 /// Root JSON returned from system_profiler and used as holder for all static USB bus data
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SystemProfile {
     /// system buses
     #[serde(rename(deserialize = "SPUSBDataType"), alias = "buses")]
     pub buses: Vec<Bus>,
+    /// Thunderbolt/USB4 domains if profiled; see [`thunderbolt::get_domains`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thunderbolt: Option<Vec<thunderbolt::ThunderboltDomain>>,
+    /// USB device controllers and configfs gadgets if profiled; see [`gadget::get_gadgets`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gadget: Option<gadget::GadgetProfile>,
 }
 
 impl SystemProfile {
@@ -44,14 +50,24 @@ impl SystemProfile {
         }
     }
 
-    /// Returns a flattened Vec of references to all [`Device`]s in each of the `buses`
-    pub fn flattened_devices(&self) -> Vec<&Device> {
-        let mut ret = Vec::with_capacity(self.len());
-        for bus in &self.buses {
-            ret.extend(bus.flattened_devices());
+    /// Fill fields of all [`Device`]s missing from json dumps of older versions; see [`Device::fill_legacy_fields`] and [`Bus::fill_location_kinds`]
+    pub fn fill_legacy_fields(&mut self) {
+        for bus in self.buses.iter_mut() {
+            bus.fill_location_kinds();
         }
+        for device in self
+            .buses
+            .iter_mut()
+            .filter_map(|b| b.devices.as_mut())
+            .flatten()
+        {
+            device.fill_legacy_fields();
+        }
+    }
 
-        ret
+    /// Returns a flattened Vec of references to all [`Device`]s in each of the `buses`
+    pub fn flattened_devices(&self) -> Vec<&Device> {
+        self.iter().collect()
     }
 
     /// Returns reference to [`Bus`] `number` if it exists in data
@@ -68,8 +84,14 @@ impl SystemProfile {
 
     /// Search for reference to [`Device`] at `port_path` in all buses
     pub fn get_node(&self, port_path: &str) -> Option<&Device> {
+        let path = PortPath::from_str(port_path).ok()?;
+        self.get_node_at(&path)
+    }
+
+    /// Search for reference to [`Device`] at `path` in all buses
+    pub fn get_node_at(&self, path: &PortPath) -> Option<&Device> {
         for bus in self.buses.iter() {
-            if let Some(node) = bus.get_node(port_path) {
+            if let Some(node) = bus.get_node_at(path) {
                 return Some(node);
             }
         }
@@ -78,13 +100,71 @@ impl SystemProfile {
 
     /// Search for mutable reference to [`Device`] at `port_path` in all buses
     pub fn get_node_mut(&mut self, port_path: &str) -> Option<&mut Device> {
+        let path = PortPath::from_str(port_path).ok()?;
+        self.get_node_at_mut(&path)
+    }
+
+    /// Search for mutable reference to [`Device`] at `path` in all buses
+    pub fn get_node_at_mut(&mut self, path: &PortPath) -> Option<&mut Device> {
         for bus in self.buses.iter_mut() {
-            if let Some(node) = bus.get_node_mut(port_path) {
+            if let Some(node) = bus.get_node_at_mut(path) {
                 return Some(node);
             }
         }
         None
     }
+
+    /// Returns the first [`Device`] in all buses with `serial`
+    pub fn find_by_serial(&self, serial: &str) -> Option<&Device> {
+        self.buses
+            .iter()
+            .filter_map(|b| b.devices.as_ref())
+            .flatten()
+            .find_map(|d| d.find_by_serial(serial))
+    }
+
+    /// Returns an iterator of all [`Device`]s in all buses with `vid` and `pid`; any product if `pid` is `None`
+    pub fn find_all_vidpid(&self, vid: u16, pid: Option<u16>) -> impl Iterator<Item = &Device> {
+        self.buses
+            .iter()
+            .filter_map(|b| b.devices.as_ref())
+            .flatten()
+            .flat_map(move |d| d.find_all_vidpid(vid, pid))
+    }
+
+    /// Returns an iterator of the hubs `device` is connected through, from its parent up to the trunk device
+    ///
+    /// The root hub is not included since it is not a parent in the tree. Ends early if a parent is missing from the profile.
+    pub fn ancestors<'a>(&'a self, device: &Device) -> impl Iterator<Item = &'a Device> {
+        self.hub_chain(device).rev()
+    }
+
+    /// Returns an iterator of the hubs `device` is connected through, from the trunk device down to its parent; the reverse of [`SystemProfile::ancestors`]
+    pub fn hub_chain<'a>(&'a self, device: &Device) -> impl DoubleEndedIterator<Item = &'a Device> {
+        let location = &device.location_id;
+        let mut chain = Vec::with_capacity(location.tree_positions.len());
+        let mut devices = self
+            .buses
+            .iter()
+            .find(|b| b.get_bus_number() == Some(location.bus))
+            .and_then(|b| b.devices.as_ref());
+
+        for depth in 1..location.tree_positions.len() {
+            let positions = &location.tree_positions[..depth];
+            match devices.and_then(|ds| {
+                ds.iter()
+                    .find(|d| d.location_id.tree_positions == positions)
+            }) {
+                Some(hub) => {
+                    chain.push(hub);
+                    devices = hub.devices.as_ref();
+                }
+                None => break,
+            }
+        }
+
+        chain.into_iter()
+    }
 }
 
 impl fmt::Display for SystemProfile {
@@ -129,6 +209,7 @@ pub struct Bus {
     #[serde(rename(deserialize = "_name"), alias = "name")]
     pub name: String,
     /// System internal bus provider name
+    #[serde(default)] // default for legacy json
     pub host_controller: String,
     /// Vendor name of PCI Host Controller from pci.ids
     pub host_controller_vendor: Option<String>,
@@ -151,6 +232,12 @@ pub struct Bus {
     /// On Linux, the root hub is also included in this list
     #[serde(rename(deserialize = "_items"), alias = "devices")]
     pub devices: Option<Vec<Device>>,
+    /// Bus is tunnelled over USB4/Thunderbolt. Linux only
+    #[serde(default)]
+    pub usb4_tunnelled: Option<bool>,
+    /// Current role of the dual-role port of the host controller from its USB role switch. Linux only
+    #[serde(default)]
+    pub role: Option<gadget::UsbRole>,
 }
 
 /// Deprecated alias for [`Bus`]
@@ -198,6 +285,8 @@ impl TryFrom<Device> for Bus {
             pci_revision: pci_revision.filter(|v| *v != 0xffff && *v != 0),
             usb_bus_number: Some(device.location_id.bus),
             devices: device.devices,
+            usb4_tunnelled: None,
+            role: None,
         })
     }
 }
@@ -216,6 +305,15 @@ impl From<u8> for Bus {
 
 /// Returns of Vec of devices in the Bus as a reference
 impl Bus {
+    /// Name of the bus with ' (USB4)' appended if it is tunnelled over USB4/Thunderbolt so that it stands out
+    pub fn display_name(&self) -> String {
+        if self.usb4_tunnelled == Some(true) {
+            format!("{} (USB4)", self.name)
+        } else {
+            self.name.to_owned()
+        }
+    }
+
     /// Returns total number of devices in the bus
     pub fn len(&self) -> usize {
         self.devices
@@ -243,11 +341,7 @@ impl Bus {
     ///
     /// Note that whilst `Vec` of references is flat, the `Device`s still contain a `devices` `Vec` where the references point; recursive functions on the returned `Vec` will produce weird results
     pub fn flattened_devices(&self) -> Vec<&Device> {
-        if let Some(devices) = &self.devices {
-            devices.iter().flat_map(|d| d.flatten()).collect()
-        } else {
-            Vec::new()
-        }
+        self.iter().collect()
     }
 
     /// Whether the bus has [`Device`]s
@@ -303,11 +397,64 @@ impl Bus {
         self.interface().and_then(|i| self.get_node_mut(&i))
     }
 
+    /// Set [`Device::removable`] where unknown from the DeviceRemovable bitmap of the hub descriptor of the hub each device is connected to, including the root hub for trunk devices
+    ///
+    /// Requires the hubs to have been profiled with [`DeviceExtra`].
+    pub fn fill_removable_from_hubs(&mut self) {
+        fn fill(hub: Option<&usb::HubDescriptor>, devices: &mut [Device]) {
+            for device in devices.iter_mut() {
+                if device.removable.is_none() {
+                    device.removable =
+                        hub.and_then(|h| h.is_port_removable(device.get_branch_position()));
+                }
+                let hub = device.extra.as_ref().and_then(|e| e.hub.as_ref());
+                if let Some(devices) = device.devices.as_mut() {
+                    fill(hub, devices);
+                }
+            }
+        }
+
+        let root_hub = self
+            .get_root_hub_device()
+            .and_then(|d| d.extra.as_ref())
+            .and_then(|e| e.hub.clone());
+        if let Some(devices) = self.devices.as_mut() {
+            fill(root_hub.as_ref(), devices);
+        }
+    }
+
+    /// Set [`Device::location_kind`] where unknown from whether each device and the hubs it is connected through are removable, their [`PhysicalLocation`] and whether the bus is tunnelled over USB4
+    ///
+    /// Devices hard-wired to an external hub are taken to be part of a dock and everything connected through a dock is [`LocationKind::Dock`]. Devices not known to be removable or fixed are left unknown.
+    pub fn fill_location_kinds(&mut self) {
+        fn fill(parent: Option<LocationKind>, devices: &mut [Device]) {
+            for device in devices.iter_mut().filter(|d| !d.is_root_hub()) {
+                if device.location_kind.is_none() {
+                    device.location_kind = device.classify_location(parent);
+                }
+                if let Some(devices) = device.devices.as_mut() {
+                    fill(device.location_kind, devices);
+                }
+            }
+        }
+
+        let bus = (self.usb4_tunnelled == Some(true)).then_some(LocationKind::Dock);
+        if let Some(devices) = self.devices.as_mut() {
+            fill(bus, devices);
+        }
+    }
+
     /// Search for [`Device`] in branches of bus and return reference
     pub fn get_node(&self, port_path: &str) -> Option<&Device> {
+        let path = PortPath::from_str(port_path).ok()?;
+        self.get_node_at(&path)
+    }
+
+    /// Search for [`Device`] at `path` in branches of bus and return reference
+    pub fn get_node_at(&self, path: &PortPath) -> Option<&Device> {
         if let Some(devices) = self.devices.as_ref() {
             for dev in devices {
-                if let Some(node) = dev.get_node(port_path) {
+                if let Some(node) = dev.get_node_at(path) {
                     log::debug!("Found {}", node);
                     return Some(node);
                 }
@@ -319,9 +466,15 @@ impl Bus {
 
     /// Search for [`Device`] in branches of bus and return mutable if found
     pub fn get_node_mut(&mut self, port_path: &str) -> Option<&mut Device> {
+        let path = PortPath::from_str(port_path).ok()?;
+        self.get_node_at_mut(&path)
+    }
+
+    /// Search for [`Device`] at `path` in branches of bus and return mutable if found
+    pub fn get_node_at_mut(&mut self, path: &PortPath) -> Option<&mut Device> {
         if let Some(devices) = self.devices.as_mut() {
             for dev in devices {
-                if let Some(node) = dev.get_node_mut(port_path) {
+                if let Some(node) = dev.get_node_at_mut(path) {
                     log::debug!("Found {}", node);
                     return Some(node);
                 }
@@ -347,14 +500,20 @@ impl Bus {
     ///
     /// Only Linux systems with a root_hub will contain accurate data, others are mainly for styling
     pub fn to_lsusb_tree_string(&self) -> Vec<(String, String, String)> {
+        self.to_lsusb_tree_string_with_units(SpeedUnits::Lsusb)
+    }
+
+    /// [`Bus::to_lsusb_tree_string`] with the speed in `units` rather than lsusb style
+    pub fn to_lsusb_tree_string_with_units(
+        &self,
+        units: SpeedUnits,
+    ) -> Vec<(String, String, String)> {
         if let Some(root_device) = self.get_root_hub_device() {
-            let speed = match &root_device.device_speed {
-                Some(v) => match v {
-                    DeviceSpeed::SpeedValue(v) => v.to_lsusb_speed(),
-                    DeviceSpeed::Description(_) => String::new(),
-                },
-                None => String::from(""),
-            };
+            let speed = root_device
+                .device_speed
+                .as_ref()
+                .and_then(|v| v.to_units_string(units))
+                .unwrap_or_default();
 
             // no fallback for lsusb tree mode
             let (driver, vendor, product, ports) = match &root_device.extra {
@@ -510,8 +669,18 @@ impl fmt::Display for Bus {
 ///
 ///   0x  -- always
 ///   bb  -- bus number in hexadecimal
-///   dddddd -- up to six levels for the tree, each digit represents its
+///   dddddd -- up to six levels for the tree, each hexadecimal digit represents its
 ///             position on that level
+///
+/// ```
+/// use std::str::FromStr;
+/// use cyme::profiler::DeviceLocation;
+///
+/// let location = DeviceLocation::from_str("0x14a20000 / 9").unwrap();
+/// assert_eq!(location.bus, 0x14);
+/// assert_eq!(location.tree_positions, vec![10, 2]);
+/// assert_eq!(location.port_path(), "20-10.2");
+/// ```
 #[derive(Debug, Default, Clone, PartialEq, Serialize)]
 pub struct DeviceLocation {
     /// Number of bus attached too
@@ -539,7 +708,7 @@ impl FromStr for DeviceLocation {
             .unwrap_or("0")
             .trim_end_matches('0')
             .chars()
-            .map(|v| v.to_digit(10).unwrap_or(0) as u8)
+            .map(|v| v.to_digit(16).unwrap_or(0) as u8)
             .collect();
         // bus no is msb
         let bus = (u32::from_str_radix(reg, 16)
@@ -561,6 +730,11 @@ impl FromStr for DeviceLocation {
 }
 
 impl DeviceLocation {
+    /// [`PortPath`] of the location
+    pub fn path(&self) -> PortPath {
+        PortPath::new(self.bus, self.tree_positions.clone())
+    }
+
     /// Linux style port path where it can be found on system device path - normally /sys/bus/usb/devices
     ///
     /// A wrapper for [`get_port_path`]
@@ -586,6 +760,25 @@ impl DeviceLocation {
     pub fn sysfs_name(&self) -> String {
         get_sysfs_name(self.bus, &self.tree_positions)
     }
+
+    /// macOS IOKit locationID register (0xbbdddddd) - the reverse of the system_profiler parsing
+    ///
+    /// ```
+    /// use cyme::profiler::DeviceLocation;
+    /// use std::str::FromStr;
+    ///
+    /// let location = DeviceLocation::from_str("0x02110000 / 3").unwrap();
+    /// assert_eq!(location.location_reg(), 0x02110000);
+    /// ```
+    pub fn location_reg(&self) -> u32 {
+        self.tree_positions
+            .iter()
+            .take(6)
+            .enumerate()
+            .fold((self.bus as u32) << 24, |reg, (i, p)| {
+                reg | ((*p as u32 & 0x0f) << (20 - 4 * i))
+            })
+    }
 }
 
 impl<'de> Deserialize<'de> for DeviceLocation {
@@ -688,6 +881,137 @@ impl<'de> Deserialize<'de> for DeviceLocation {
     }
 }
 
+/// Runtime power management state of a [`Device`]
+///
+/// On Linux this is the sysfs 'power/runtime_status' and 'power/autosuspend_delay_ms'; on macOS the IOKit 'CurrentPowerState'.
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PowerState {
+    /// Power state such as 'active', 'suspended' or 'unsupported'
+    pub status: String,
+    /// Delay in milliseconds before the device is autosuspended when idle; negative means autosuspend is disabled. Linux only
+    pub autosuspend_delay_ms: Option<i32>,
+}
+
+impl fmt::Display for PowerState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.autosuspend_delay_ms {
+            Some(d) if d >= 0 => write!(f, "{} ({} ms)", self.status, d),
+            _ => write!(f, "{}", self.status),
+        }
+    }
+}
+
+impl PowerState {
+    /// Is the device suspended; runtime suspended on Linux or in a low power state on macOS
+    pub fn is_suspended(&self) -> bool {
+        self.status == "suspended"
+    }
+}
+
+/// Physical location of the port a [`Device`] is connected to, from the ACPI _PLD (Physical Location of Device) of the port
+///
+/// On Linux this is the sysfs 'physical_location' of the hub port the device is connected to; requires ACPI and kernel 6.0 or newer.
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PhysicalLocation {
+    /// Surface of the system housing the port such as 'front', 'back' or 'left'
+    pub panel: String,
+    /// Vertical position on the panel: 'upper', 'center' or 'lower'
+    pub vertical_position: Option<String>,
+    /// Horizontal position on the panel: 'left', 'center' or 'right'
+    pub horizontal_position: Option<String>,
+    /// Port is on a docking station
+    #[serde(default)]
+    pub dock: bool,
+    /// Port is on the lid of a laptop
+    #[serde(default)]
+    pub lid: bool,
+}
+
+impl fmt::Display for PhysicalLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.panel)?;
+        for position in [&self.vertical_position, &self.horizontal_position]
+            .into_iter()
+            .flatten()
+        {
+            write!(f, " {}", position)?;
+        }
+        if self.dock {
+            write!(f, " dock")?;
+        }
+        if self.lid {
+            write!(f, " lid")?;
+        }
+        Ok(())
+    }
+}
+
+/// Where a [`Device`] is relative to the machine; a heuristic from [`Device::removable`], [`PhysicalLocation`] and the hubs it is connected through, see [`Bus::fill_location_kinds`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LocationKind {
+    /// Hard-wired inside the machine such as a laptop webcam, fingerprint reader or Bluetooth adapter
+    Internal,
+    /// Plugged into a port of the machine or an external hub
+    External,
+    /// Part of or connected through a dock: hard-wired to an external hub, on a port ACPI reports as docking or on a bus tunnelled over USB4/Thunderbolt
+    Dock,
+}
+
+impl fmt::Display for LocationKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LocationKind::Internal => write!(f, "internal"),
+            LocationKind::External => write!(f, "external"),
+            LocationKind::Dock => write!(f, "dock"),
+        }
+    }
+}
+
+/// Battery of a [`Device`] such as a wireless mouse receiver, headset or UPS
+///
+/// On Linux this is the power supply the kernel registers for HID devices with battery strength reports, as read by upower.
+///
+/// ```
+/// use cyme::profiler::Battery;
+///
+/// let battery = Battery {
+///     name: "hid-0003:046D:C52B.0004-battery".into(),
+///     capacity: Some(85),
+///     status: Some("Discharging".into()),
+///     ..Default::default()
+/// };
+/// assert_eq!(battery.to_string(), "85% Discharging");
+/// ```
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Battery {
+    /// Name of the power supply such as 'hid-0003:046D:C52B.0004-battery'
+    pub name: String,
+    /// Charge in percent
+    pub capacity: Option<u8>,
+    /// Coarse charge such as 'Normal' or 'Low', for batteries which do not report a percentage
+    pub capacity_level: Option<String>,
+    /// Charging state such as 'Charging', 'Discharging', 'Full' or 'Not charging'
+    pub status: Option<String>,
+}
+
+impl fmt::Display for Battery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.capacity, self.capacity_level.as_ref()) {
+            (Some(c), _) => write!(f, "{}%", c)?,
+            (None, Some(l)) => write!(f, "{}", l)?,
+            (None, None) => write!(f, "-")?,
+        }
+        if let Some(status) = self.status.as_ref().filter(|s| *s != "Unknown") {
+            write!(f, " {}", status)?;
+        }
+        Ok(())
+    }
+}
+
 /// Used for macOS system_profiler dump. Speed is a snake_case string and in case we can't match to a [`Speed`], this allows the String to be stored and not panic
 #[derive(Debug, Clone, PartialEq, DeserializeFromStr, SerializeDisplay)]
 pub enum DeviceSpeed {
@@ -720,6 +1044,16 @@ impl fmt::Display for DeviceSpeed {
     }
 }
 
+impl DeviceSpeed {
+    /// Speed formatted in `units` with [`Speed::to_units_string`]; `None` if it is only a [`DeviceSpeed::Description`]
+    pub fn to_units_string(&self, units: SpeedUnits) -> Option<String> {
+        match self {
+            DeviceSpeed::SpeedValue(v) => Some(v.to_units_string(units)),
+            DeviceSpeed::Description(_) => None,
+        }
+    }
+}
+
 impl FromStr for DeviceSpeed {
     type Err = Error;
 
@@ -778,6 +1112,17 @@ pub struct Device {
     #[serde(default, deserialize_with = "deserialize_option_number_from_string")]
     /// macOS system_profiler only - actually bus current used in mA not power!
     pub extra_current_used: Option<u16>,
+    #[serde(
+        default,
+        alias = "Built-in_Device",
+        alias = "built_in_device",
+        deserialize_with = "deserialize_option_bool_from_yes_no"
+    )]
+    /// macOS system_profiler only - device is built into the machine such as an internal keyboard or card reader
+    pub built_in: Option<bool>,
+    #[serde(default, rename(deserialize = "Media"), alias = "media")]
+    /// macOS system_profiler only - storage [`Media`] of a mass storage device
+    pub media: Option<Vec<Media>>,
     /// Devices can be hub and have devices attached so need to walk each device's devices...
     #[serde(rename(deserialize = "_items"), alias = "devices")]
     pub devices: Option<Vec<Device>>,
@@ -788,6 +1133,42 @@ pub struct Device {
     pub sub_class: Option<u8>,
     /// USB protocol
     pub protocol: Option<u8>,
+    /// Runtime power management state
+    #[serde(default)]
+    pub power_state: Option<PowerState>,
+    /// Unix time in seconds when the device was connected; Linux only from sysfs 'power/connected_duration'
+    #[serde(default)]
+    pub connected_since: Option<u64>,
+    /// Number of receive lanes in use; 2 for USB 3.2 Gen 2x2. Linux only
+    #[serde(default)]
+    pub rx_lanes: Option<u8>,
+    /// Number of transmit lanes in use; 2 for USB 3.2 Gen 2x2. Linux only
+    #[serde(default)]
+    pub tx_lanes: Option<u8>,
+    /// State of the hub port the device is connected to such as 'configured' or 'suspended'. Linux only
+    #[serde(default)]
+    pub link_state: Option<String>,
+    /// Physical location of the port the device is connected to from ACPI. Linux only
+    #[serde(default)]
+    pub physical_location: Option<PhysicalLocation>,
+    /// Whether the device is on a removable port rather than hard-wired to it like an internal webcam; sysfs 'removable' on Linux, otherwise the DeviceRemovable of its hub descriptor
+    #[serde(default)]
+    pub removable: Option<bool>,
+    /// Whether the device is internal, external or on a dock; see [`LocationKind`]
+    #[serde(default)]
+    pub location_kind: Option<LocationKind>,
+    /// Batteries of the device such as that of a wireless mouse reported by its receiver. Linux only
+    #[serde(default)]
+    pub batteries: Option<Vec<Battery>>,
+    /// Known-broken behaviours of the device in the [`quirks::Database`] used to profile it
+    #[serde(default)]
+    pub quirks: Option<Vec<quirks::Quirk>>,
+    /// Where [`Device::name`] came from; see [`names::Resolver`]
+    #[serde(default)]
+    pub name_source: Option<names::NameSource>,
+    /// Where [`Device::manufacturer`] came from
+    #[serde(default)]
+    pub manufacturer_source: Option<names::NameSource>,
     /// Extra data obtained by libusb/udev exploration
     #[serde(default)]
     pub extra: Option<DeviceExtra>,
@@ -796,6 +1177,106 @@ pub struct Device {
     pub profiler_error: Option<String>,
 }
 
+/// Storage media of a mass storage [`Device`] from macOS system_profiler, or the SCSI disks of its mass storage interfaces from sysfs on Linux
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Media {
+    /// Name of the media, normally the product name
+    #[serde(rename(deserialize = "_name"), alias = "name")]
+    pub name: String,
+    /// BSD disk name such as 'disk2', or block device name such as 'sdb' on Linux
+    pub bsd_name: Option<String>,
+    /// SCSI logical unit number of the media
+    #[serde(rename(deserialize = "Logical Unit"), alias = "logical_unit")]
+    pub logical_unit: Option<u8>,
+    /// Interface number of the mass storage interface
+    #[serde(rename(deserialize = "USB Interface"), alias = "usb_interface")]
+    pub usb_interface: Option<u8>,
+    /// Partition map such as 'guid_partition_map_type'
+    pub partition_map_type: Option<String>,
+    /// Whether the media is removable from the device, such as a card in a reader
+    #[serde(
+        default,
+        rename(deserialize = "removable_media"),
+        alias = "removable",
+        deserialize_with = "deserialize_option_bool_from_yes_no"
+    )]
+    pub removable: Option<bool>,
+    /// Size as a human readable string such as '1 TB'
+    pub size: Option<String>,
+    /// Size in bytes
+    pub size_in_bytes: Option<u64>,
+    /// S.M.A.R.T. status such as 'Verified'
+    pub smart_status: Option<String>,
+    /// Vendor identification of the SCSI INQUIRY data; Linux only
+    pub scsi_vendor: Option<String>,
+    /// Product identification of the SCSI INQUIRY data; Linux only
+    pub scsi_model: Option<String>,
+    /// Product revision level of the SCSI INQUIRY data; Linux only
+    pub scsi_revision: Option<String>,
+    /// Volumes on the media
+    #[serde(default)]
+    pub volumes: Vec<Volume>,
+}
+
+impl Media {
+    /// SCSI INQUIRY vendor, model and revision such as 'SanDisk Cruzer Blade 1.00'; `None` if not known
+    ///
+    /// ```
+    /// use cyme::profiler::Media;
+    ///
+    /// let media = Media {
+    ///     scsi_vendor: Some(String::from("SanDisk")),
+    ///     scsi_model: Some(String::from("Cruzer Blade")),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(media.scsi_inquiry().as_deref(), Some("SanDisk Cruzer Blade"));
+    /// assert_eq!(Media::default().scsi_inquiry(), None);
+    /// ```
+    pub fn scsi_inquiry(&self) -> Option<String> {
+        let fields: Vec<&str> = [&self.scsi_vendor, &self.scsi_model, &self.scsi_revision]
+            .into_iter()
+            .flatten()
+            .map(|s| s.as_str())
+            .collect();
+        (!fields.is_empty()).then(|| fields.join(" "))
+    }
+}
+
+impl fmt::Display for Media {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.bsd_name.as_ref().unwrap_or(&self.name))?;
+        if let Some(size) = self.size.as_ref() {
+            write!(f, " {}", size)?;
+        }
+        if self.removable == Some(true) {
+            write!(f, " (removable)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Volume on [`Media`] from macOS system_profiler
+#[skip_serializing_none]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Volume {
+    /// Volume name
+    #[serde(rename(deserialize = "_name"), alias = "name")]
+    pub name: String,
+    /// BSD disk name such as 'disk2s1'
+    pub bsd_name: Option<String>,
+    /// File system such as 'MS-DOS FAT32'; not present for containers
+    pub file_system: Option<String>,
+    /// Partition content type such as 'Apple_APFS'
+    pub iocontent: Option<String>,
+    /// Size as a human readable string
+    pub size: Option<String>,
+    /// Size in bytes
+    pub size_in_bytes: Option<u64>,
+    /// Volume UUID
+    pub volume_uuid: Option<String>,
+}
+
 /// Deprecated alias for [`Device`]
 #[deprecated(since = "2.0.0", note = "Use Device instead")]
 pub type USBDevice = Device;
@@ -829,6 +1310,168 @@ impl Device {
         }
     }
 
+    /// Summary of the frame sizes, frame rates and formats of all UVC streaming [`usb::Interface`]s such as '3840x2160@30fps, MJPEG/H264, raw YUY2'; requires [`DeviceExtra`]
+    pub fn video_format_summary(&self) -> Option<usb::video::VideoFormatSummary> {
+        let descriptors = self
+            .extra
+            .iter()
+            .flat_map(|e| e.configurations.iter())
+            .flat_map(|c| c.interfaces.iter())
+            .flat_map(|i| i.extra.iter().flatten())
+            .filter_map(|d| match d {
+                usb::Descriptor::Interface(usb::ClassDescriptor::Video(uvc, _)) => {
+                    Some(&uvc.interface)
+                }
+                _ => None,
+            });
+        usb::video::VideoFormatSummary::from_descriptors(descriptors)
+    }
+
+    /// Whether the device has interfaces no driver is bound to; see [`usb::DeviceExtra::unclaimed_interfaces`]
+    ///
+    /// ```
+    /// use cyme::profiler::read_json_dump;
+    ///
+    /// let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+    /// // DFU and trace interfaces of the Black Magic Probe are not bound to a driver
+    /// let bmp = spusb.get_node("2-2.8").unwrap();
+    /// assert!(bmp.has_unclaimed_interfaces());
+    /// assert_eq!(bmp.extra.as_ref().unwrap().unclaimed_interfaces().len(), 2);
+    /// ```
+    pub fn has_unclaimed_interfaces(&self) -> bool {
+        self.extra
+            .as_ref()
+            .is_some_and(|e| !e.unclaimed_interfaces().is_empty())
+    }
+
+    /// Gets the Bluetooth HCI adapter of the first [`usb::Interface`] that has one; requires [`DeviceExtra`] and is only populated on Linux
+    pub fn bluetooth_adapter(&self) -> Option<&BluetoothAdapter> {
+        self.extra.as_ref().and_then(|extra| {
+            extra
+                .configurations
+                .iter()
+                .flat_map(|conf| conf.interfaces.iter())
+                .find_map(|i| i.bluetooth.as_ref())
+        })
+    }
+
+    /// Gets the On-The-Go descriptor of the first [`usb::Configuration`] that has one; only devices which support OTG have one and it requires [`DeviceExtra`]
+    pub fn otg(&self) -> Option<&usb::descriptors::OnTheGoDescriptor> {
+        self.extra
+            .iter()
+            .flat_map(|e| e.configurations.iter())
+            .flat_map(|c| c.extra.iter().flatten())
+            .find_map(|d| match d {
+                usb::Descriptor::Otg(otg) => Some(otg),
+                _ => None,
+            })
+    }
+
+    /// Speed the device is connected at; the [`DeviceSpeed`] if it is a known [`Speed`]
+    pub fn negotiated_speed(&self) -> Option<Speed> {
+        match self.device_speed.as_ref()? {
+            DeviceSpeed::SpeedValue(s) => Some(s.to_owned()),
+            DeviceSpeed::Description(d) => Speed::from_str(d).ok(),
+        }
+        .filter(|s| *s != Speed::Unknown)
+    }
+
+    /// Maximum speed the device is capable of from the BOS SuperSpeed capabilities, bcdUSB or presence of a device qualifier
+    ///
+    /// Requires [`DeviceExtra`] for all but bcdUSB. None if it cannot be determined
+    pub fn capable_speed(&self) -> Option<Speed> {
+        let extra = self.extra.as_ref();
+        if let Some(bos) = extra.and_then(|e| e.binary_object_store.as_ref()) {
+            for cap in bos.capabilities.iter() {
+                if let bos::BosCapability::SuperSpeedPlus(_) = cap {
+                    return Some(Speed::SuperSpeedPlus);
+                }
+            }
+            for cap in bos.capabilities.iter() {
+                if let bos::BosCapability::SuperSpeed(ss) = cap {
+                    // wSpeedsSupported bitmap: low, full, high, 5 Gb/s
+                    return Some(match ss.speed_supported {
+                        s if s & 0x08 != 0 => Speed::SuperSpeed,
+                        s if s & 0x04 != 0 => Speed::HighSpeed,
+                        s if s & 0x02 != 0 => Speed::FullSpeed,
+                        _ => Speed::LowSpeed,
+                    });
+                }
+            }
+        }
+
+        match self.bcd_usb {
+            // USB 3.x devices must support SuperSpeed
+            Some(v) if v.major() >= 3 => Some(Speed::SuperSpeed),
+            // only high speed capable devices have a device qualifier
+            _ if extra.is_some_and(|e| e.qualifier.is_some()) => Some(Speed::HighSpeed),
+            _ => None,
+        }
+    }
+
+    /// Is the device connected at a slower speed than it is capable of, for example a SuperSpeed device on a USB 2.0 port or cable
+    pub fn is_below_capable_speed(&self) -> bool {
+        matches!((self.negotiated_speed(), self.capable_speed()), (Some(n), Some(c)) if n < c)
+    }
+
+    /// Time in seconds since the device was connected based on [`Device::connected_since`] and the current system time
+    pub fn connected_duration(&self) -> Option<u64> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?;
+        self.connected_since
+            .map(|since| now.as_secs().saturating_sub(since))
+    }
+
+    /// Decode vendor-specific class descriptors of all interfaces with the decoders in `registry` matching the device VID:PID
+    pub fn decode_vendor_descriptors(&mut self, registry: &vendor::Registry) {
+        if let (Some(vid), Some(pid), Some(extra)) =
+            (self.vendor_id, self.product_id, self.extra.as_mut())
+        {
+            for interface in extra
+                .configurations
+                .iter_mut()
+                .flat_map(|c| c.interfaces.iter_mut())
+            {
+                interface.decode_vendor_descriptors(vid, pid, registry);
+            }
+        }
+    }
+
+    /// Copy the fields only available from macOS system_profiler from `other`, which should be the same device
+    ///
+    /// Used when merging system_profiler with nusb/libusb since only the latter has [`DeviceExtra`]
+    pub fn merge_system_profiler(&mut self, other: &Device) {
+        self.bus_power = self.bus_power.or(other.bus_power);
+        self.bus_power_used = self.bus_power_used.or(other.bus_power_used);
+        self.extra_current_used = self.extra_current_used.or(other.extra_current_used);
+        self.built_in = self.built_in.or(other.built_in);
+        if self.media.is_none() {
+            self.media.clone_from(&other.media);
+        }
+    }
+
+    /// Fill fields missing from json dumps of older versions, which are otherwise set when profiling, for this device and its devices
+    ///
+    /// Interface paths were not in early dumps so are built from the device location. Called by [`read_json_dump`] and [`read_flat_json_dump`]; use it if deserializing a dump directly.
+    pub fn fill_legacy_fields(&mut self) {
+        let location = &self.location_id;
+        if let Some(extra) = self.extra.as_mut() {
+            for config in extra.configurations.iter_mut() {
+                for interface in config.interfaces.iter_mut() {
+                    if interface.path.is_empty() {
+                        interface.path =
+                            interface.path(location.bus, &location.tree_positions, config.number);
+                    }
+                }
+            }
+        }
+
+        for device in self.devices.iter_mut().flatten() {
+            device.fill_legacy_fields();
+        }
+    }
+
     /// Gets root_hub [`Device`] if it is one
     ///
     /// root_hub returns `Some(Self)`
@@ -854,34 +1497,42 @@ impl Device {
     ///
     /// Will panic if `port_path` is not a child device or if it sits shallower than self
     pub fn get_node(&self, port_path: &str) -> Option<&Device> {
-        // special case for root_hub, it ends with :1.0
-        if port_path.ends_with(":1.0") {
-            return self.get_root_hub();
-        }
-        let node_depth = port_path
-            .split('-')
-            .last()
-            .expect("Invalid port path")
-            .split('.')
-            .count();
-        let current_depth = self.get_depth();
+        let path = PortPath::from_str(port_path).ok()?;
+        self.get_node_at(&path)
+    }
+
+    /// Recursively walk all [`Device`] from self, looking for the one at `path` and returning reference
+    ///
+    /// An interface path returns the device of the interface. Will panic if `path` sits shallower than self
+    pub fn get_node_at(&self, path: &PortPath) -> Option<&Device> {
+        // special case for root_hub, it's the bus interface 1-0:1.0
+        if path.is_root() {
+            return if self.is_root_hub() && self.location_id.bus == path.bus {
+                Some(self)
+            } else {
+                None
+            };
+        }
         log::debug!(
             "Get node at {} with {} ({}); depth {}/{}",
-            port_path,
+            path,
             self.port_path(),
             self,
-            current_depth,
-            node_depth
+            self.get_depth(),
+            path.depth()
         );
 
         // should not be looking for nodes below us unless root
-        match current_depth.cmp(&node_depth) {
+        match self.get_depth().cmp(&path.depth()) {
             Ordering::Greater => panic!(
                 "Trying to find node at {}/{} shallower than current position {}!",
-                &port_path, node_depth, current_depth
+                path,
+                path.depth(),
+                self.get_depth()
             ),
             Ordering::Equal => {
-                if self.port_path() == port_path {
+                if self.location_id.bus == path.bus && self.location_id.tree_positions == path.ports
+                {
                     return Some(self);
                 } else {
                     return None;
@@ -890,53 +1541,54 @@ impl Device {
             Ordering::Less => {}
         }
 
-        // else walk through devices recursively running function and returning if found
-        if let Some(devices) = self.devices.as_ref() {
-            for dev in devices {
-                if let Some(node) = dev.get_node(port_path) {
-                    return Some(node);
-                }
-            }
-        }
-
-        None
+        // else walk through the branch containing the path
+        self.devices
+            .iter()
+            .flatten()
+            .find(|d| path.ports.starts_with(&d.location_id.tree_positions))
+            .and_then(|d| d.get_node_at(path))
     }
 
     /// Recursively walk all [`Device`] from self, looking for the one with `port_path` and returning mutable
     ///
     /// Will panic if `port_path` is not a child device or if it sits shallower than self
     pub fn get_node_mut(&mut self, port_path: &str) -> Option<&mut Device> {
-        if port_path.ends_with(":1.0") {
-            if self.is_root_hub() {
-                return Some(self);
+        let path = PortPath::from_str(port_path).ok()?;
+        self.get_node_at_mut(&path)
+    }
+
+    /// Recursively walk all [`Device`] from self, looking for the one at `path` and returning mutable
+    ///
+    /// An interface path returns the device of the interface. Will panic if `path` sits shallower than self
+    pub fn get_node_at_mut(&mut self, path: &PortPath) -> Option<&mut Device> {
+        // special case for root_hub, it's the bus interface 1-0:1.0
+        if path.is_root() {
+            return if self.is_root_hub() && self.location_id.bus == path.bus {
+                Some(self)
             } else {
-                return None;
-            }
+                None
+            };
         }
-        let node_depth = port_path
-            .split('-')
-            .last()
-            .expect("Invalid port path")
-            .split('.')
-            .count();
-        let current_depth = self.get_depth();
         log::debug!(
             "Get node at {} with {} ({}); depth {}/{}",
-            port_path,
+            path,
             self.port_path(),
             self,
-            current_depth,
-            node_depth
+            self.get_depth(),
+            path.depth()
         );
 
         // should not be looking for nodes below us
-        match current_depth.cmp(&node_depth) {
+        match self.get_depth().cmp(&path.depth()) {
             Ordering::Greater => panic!(
                 "Trying to find node at {}/{} shallower than current position {}!",
-                &port_path, node_depth, current_depth
+                path,
+                path.depth(),
+                self.get_depth()
             ),
             Ordering::Equal => {
-                if self.port_path() == port_path {
+                if self.location_id.bus == path.bus && self.location_id.tree_positions == path.ports
+                {
                     return Some(self);
                 } else {
                     return None;
@@ -945,16 +1597,12 @@ impl Device {
             Ordering::Less => {}
         }
 
-        // else walk through devices recursively running function and returning if found
-        if let Some(devices) = self.devices.as_mut() {
-            for dev in devices {
-                if let Some(node) = dev.get_node_mut(port_path) {
-                    return Some(node);
-                }
-            }
-        }
-
-        None
+        // else walk through the branch containing the path
+        self.devices
+            .iter_mut()
+            .flatten()
+            .find(|d| path.ports.starts_with(&d.location_id.tree_positions))
+            .and_then(|d| d.get_node_at_mut(path))
     }
 
     /// Returns position on branch (parent), which is the last number in `tree_positions` also sometimes referred to as port
@@ -967,6 +1615,32 @@ impl Device {
         self.location_id.tree_positions.len()
     }
 
+    /// Classify where the device is given the [`LocationKind`] of the hub or bus it is connected to; `parent` is `None` for trunk devices
+    fn classify_location(&self, parent: Option<LocationKind>) -> Option<LocationKind> {
+        let removable = self.is_removable();
+        match (parent, self.physical_location.as_ref()) {
+            (Some(LocationKind::Dock), _) => Some(LocationKind::Dock),
+            (_, Some(p)) if p.dock => Some(LocationKind::Dock),
+            (Some(LocationKind::External), _) => match removable {
+                Some(false) => Some(LocationKind::Dock),
+                _ => Some(LocationKind::External),
+            },
+            (_, Some(p)) if p.lid => Some(LocationKind::Internal),
+            _ => removable.map(|r| {
+                if r {
+                    LocationKind::External
+                } else {
+                    LocationKind::Internal
+                }
+            }),
+        }
+    }
+
+    /// Whether the device is on a removable port; [`Device::removable`] or, from macOS system_profiler, not [`Device::built_in`]
+    pub fn is_removable(&self) -> Option<bool> {
+        self.removable.or(self.built_in.map(|b| !b))
+    }
+
     /// Returns `true` if device is a hub based on device name - not perfect but most hubs advertise as a hub in name - or class code if it has one
     ///
     /// ```
@@ -1001,11 +1675,16 @@ impl Device {
     /// assert_eq!(d.port_path(), "1-0:1.0");
     /// ```
     pub fn port_path(&self) -> String {
+        self.path().to_string()
+    }
+
+    /// [`PortPath`] of the device; see [`Device::port_path`]
+    pub fn path(&self) -> PortPath {
         // special case for root_hub, it's the interface 0 on config 1
         if self.is_root_hub() {
-            get_interface_path(self.location_id.bus, &self.location_id.tree_positions, 1, 0)
+            self.location_id.path().with_interface(1, 0)
         } else {
-            self.location_id.port_path()
+            self.location_id.path()
         }
     }
 
@@ -1047,6 +1726,19 @@ impl Device {
         get_dev_path(self.location_id.bus, Some(self.location_id.number))
     }
 
+    /// Path that can be used to open the [`Device`] with OS tools; Linux devpath, macOS IOKit locationID or `None` on platforms without an equivalent
+    ///
+    /// The path is for the host cyme is running on so is only meaningful for a [`Device`] profiled on it, not one read from a dump of another system.
+    pub fn platform_dev_path(&self) -> Option<String> {
+        if cfg!(target_os = "macos") {
+            Some(format!("0x{:08x}", self.location_id.location_reg()))
+        } else if cfg!(target_os = "windows") {
+            None
+        } else {
+            Some(self.dev_path())
+        }
+    }
+
     /// Linux sysfs name of [`Device`]
     pub fn sysfs_name(&self) -> String {
         self.location_id.sysfs_name()
@@ -1127,15 +1819,21 @@ impl Device {
 
     /// Generate a tuple (String, String, String) of the lsusb tree output at all three verbosity levels
     pub fn to_lsusb_tree_string(&self) -> Vec<(String, String, String)> {
+        self.to_lsusb_tree_string_with_units(SpeedUnits::Lsusb)
+    }
+
+    /// [`Device::to_lsusb_tree_string`] with the speed in `units` rather than lsusb style
+    pub fn to_lsusb_tree_string_with_units(
+        &self,
+        units: SpeedUnits,
+    ) -> Vec<(String, String, String)> {
         let mut format_strs = Vec::new();
 
-        let speed = match &self.device_speed {
-            Some(v) => match v {
-                DeviceSpeed::SpeedValue(v) => v.to_lsusb_speed(),
-                DeviceSpeed::Description(_) => String::new(),
-            },
-            None => String::from(""),
-        };
+        let speed = self
+            .device_speed
+            .as_ref()
+            .and_then(|v| v.to_units_string(units))
+            .unwrap_or_default();
 
         // no fallback for lsusb tree mode
         let (driver, vendor, product) = match &self.extra {
@@ -1270,17 +1968,34 @@ impl Device {
             .map(|c| (c, self.sub_class.unwrap_or(0), self.protocol.unwrap_or(0)).into())
     }
 
-    /// Recursively gets all devices in a [`Device`] and flattens them into a Vec of references, including self
-    pub fn flatten(&self) -> Vec<&Device> {
-        let mut ret: Vec<&Device> = Vec::with_capacity(self.len());
-        ret.push(self);
-        if let Some(d) = self.devices.as_ref() {
-            for child in d {
-                ret.extend(child.flatten());
-            }
+    /// Converts the [`Device`] into a slim stub with only location and identifying fields - retains child `devices` but drops everything else including [`DeviceExtra`]
+    ///
+    /// Used to keep the topology context of a tree without the bulk of data for devices that are not of interest
+    ///
+    /// ```
+    /// let d = cyme::profiler::Device{ name: String::from("Test hub"), serial_num: Some(String::from("1234")), location_id: cyme::profiler::DeviceLocation { bus: 1, number: 2, tree_positions: vec![1] }, ..Default::default() };
+    /// let stub = d.into_stub();
+    /// assert_eq!(stub.name, "Test hub");
+    /// assert_eq!(stub.location_id.number, 2);
+    /// assert_eq!(stub.serial_num, None);
+    /// ```
+    pub fn into_stub(self) -> Self {
+        Device {
+            name: self.name,
+            vendor_id: self.vendor_id,
+            product_id: self.product_id,
+            location_id: self.location_id,
+            class: self.class,
+            sub_class: self.sub_class,
+            protocol: self.protocol,
+            devices: self.devices,
+            ..Default::default()
         }
+    }
 
-        ret
+    /// Recursively gets all devices in a [`Device`] and flattens them into a Vec of references, including self
+    pub fn flatten(&self) -> Vec<&Device> {
+        self.iter().collect()
     }
 
     /// Recursively gets all devices in a [`Device`] and flattens them into a Vec, including self
@@ -1298,6 +2013,34 @@ impl Device {
 
         ret
     }
+
+    /// Returns an iterator of all [`Device`]s below self in the tree, depth first and not including self
+    ///
+    /// ```
+    /// let mut hub = cyme::profiler::Device{ name: String::from("Hub"), location_id: cyme::profiler::DeviceLocation { bus: 1, number: 1, tree_positions: vec![1] }, ..Default::default() };
+    /// let child = cyme::profiler::Device{ name: String::from("Child"), location_id: cyme::profiler::DeviceLocation { bus: 1, number: 2, tree_positions: vec![1, 2] }, ..Default::default() };
+    /// hub.devices = Some(vec![child]);
+    /// assert_eq!(hub.descendants().map(|d| d.port_path()).collect::<Vec<_>>(), vec!["1-1.2"]);
+    /// ```
+    pub fn descendants(&self) -> impl Iterator<Item = &Device> {
+        self.iter().skip(1)
+    }
+
+    /// Returns the first [`Device`] with `serial`, including self and all [`Device::descendants`]
+    pub fn find_by_serial(&self, serial: &str) -> Option<&Device> {
+        std::iter::once(self)
+            .chain(self.descendants())
+            .find(|d| d.serial_num.as_deref() == Some(serial))
+    }
+
+    /// Returns an iterator of all [`Device`]s with `vid` and `pid`, including self and all [`Device::descendants`]; any product if `pid` is `None`
+    pub fn find_all_vidpid(&self, vid: u16, pid: Option<u16>) -> impl Iterator<Item = &Device> {
+        std::iter::once(self)
+            .chain(self.descendants())
+            .filter(move |d| {
+                d.vendor_id == Some(vid) && pid.is_none_or(|p| d.product_id == Some(p))
+            })
+    }
 }
 
 impl fmt::Display for Device {
@@ -1366,7 +2109,7 @@ impl fmt::Display for Device {
 /// Used to filter devices within buses
 ///
 /// The tree to a [`Device`] is kept even if parent branches are not matches. To avoid this, one must flatten the devices first.
-#[derive(Debug, Default, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Filter {
     /// Retain only devices with vendor id matching this
     pub vid: Option<u16>,
@@ -1386,6 +2129,14 @@ pub struct Filter {
     pub exclude_empty_hub: bool,
     /// Don't exclude Linux root_hub devices - this is inverse because they are pseudo [`Bus`]'s in the tree
     pub no_exclude_root_hub: bool,
+    /// Retain only devices with interfaces no driver is bound to; see [`usb::DeviceExtra::unclaimed_interfaces`]
+    pub unclaimed: bool,
+    /// Retain only devices of this [`LocationKind`]
+    pub location: Option<LocationKind>,
+    /// Reduce non-matching parents retained in the tree to slim stubs with [`Device::into_stub`]
+    pub stub_parents: bool,
+    /// Retain only devices satisfying this boolean expression of conditions, in addition to the fields above
+    pub expression: Option<expression::FilterExpression>,
 }
 
 /// Deprecated alias for [`Filter`]
@@ -1490,6 +2241,9 @@ impl Filter {
             && (self.class.as_ref().map_or(true, |fc| {
                 device.class.as_ref() == Some(fc) || device.has_interface_class(fc)
             }))
+            && (self.expression.as_ref().is_none_or(|e| e.is_match(device)))
+            && (!self.unclaimed || device.has_unclaimed_interfaces())
+            && (self.location.is_none() || device.location_kind == self.location)
             && !(self.exclude_empty_hub && device.is_hub() && !device.has_devices())
             && (!device.is_root_hub() || self.no_exclude_root_hub)
     }
@@ -1508,10 +2262,15 @@ impl Filter {
     /// Recursively retain only `Device` in `devices` matching filter
     ///
     /// Note that non-matching parents will still be retained if they have a matching `Device` within their branches
+    ///
+    /// If `stub_parents` is set, these non-matching parents are reduced to a slim stub with [`Device::into_stub`]
     pub fn retain_devices(&self, devices: &mut Vec<Device>) {
         devices.retain(|d| self.exists_in_tree(d));
 
         for d in devices {
+            if self.stub_parents && !self.is_match(d) {
+                *d = std::mem::take(d).into_stub();
+            }
             d.devices.iter_mut().for_each(|d| self.retain_devices(d));
         }
     }
@@ -1539,6 +2298,29 @@ impl Filter {
     }
 }
 
+impl FromStr for Filter {
+    type Err = Error;
+
+    /// Parse a [`expression::FilterExpression`] string into a filter matching only devices satisfying it
+    ///
+    /// ```
+    /// use cyme::profiler::{read_json_dump, Filter};
+    ///
+    /// let sp = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+    /// let filter: Filter = "vid=0x1d50,name=Black Magic || class==hub".parse().unwrap();
+    /// let mut flattened = sp.flattened_devices();
+    /// filter.retain_flattened_devices_ref(&mut flattened);
+    /// assert_eq!(flattened.len(), 2);
+    /// assert!("vid".parse::<Filter>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(Filter {
+            expression: Some(s.parse()?),
+            ..Default::default()
+        })
+    }
+}
+
 /// Reads a json dump at `file_path` with serde deserializer - either from `system_profiler` or from `cyme --json`
 ///
 /// Must be a full tree including buses. Use `read_flat_json_dump` for devices only
@@ -1548,12 +2330,13 @@ pub fn read_json_dump(file_path: &str) -> Result<SystemProfile> {
     let mut data = String::new();
     file.read_to_string(&mut data)?;
 
-    let json_dump: SystemProfile = serde_json::from_str(&data).map_err(|e| {
+    let mut json_dump: SystemProfile = serde_json::from_str(&data).map_err(|e| {
         Error::new(
             ErrorKind::Parsing,
             &format!("Failed to parse dump at {:?}; Error({})", file_path, e),
         )
     })?;
+    json_dump.fill_legacy_fields();
 
     Ok(json_dump)
 }
@@ -1565,12 +2348,13 @@ pub fn read_flat_json_dump(file_path: &str) -> Result<Vec<Device>> {
     let mut data = String::new();
     file.read_to_string(&mut data)?;
 
-    let json_dump: Vec<Device> = serde_json::from_str(&data).map_err(|e| {
+    let mut json_dump: Vec<Device> = serde_json::from_str(&data).map_err(|e| {
         Error::new(
             ErrorKind::Parsing,
             &format!("Failed to parse dump at {:?}; Error({})", file_path, e),
         )
     })?;
+    json_dump.iter_mut().for_each(|d| d.fill_legacy_fields());
 
     Ok(json_dump)
 }
@@ -1590,9 +2374,15 @@ pub fn read_flat_json_to_phony_bus(file_path: &str) -> Result<SystemProfile> {
         pci_revision: None,
         usb_bus_number: None,
         devices: Some(devices),
+        usb4_tunnelled: None,
+        role: None,
     };
 
-    Ok(SystemProfile { buses: vec![bus] })
+    Ok(SystemProfile {
+        buses: vec![bus],
+        thunderbolt: None,
+        gadget: None,
+    })
 }
 
 /// Deserializes an option number from String (base10 or base16 encoding) or a number
@@ -1645,6 +2435,36 @@ where
     }
 }
 
+/// Deserializes an option bool from system_profiler 'Yes'/'No' String or a bool
+fn deserialize_option_bool_from_yes_no<'de, D>(
+    deserializer: D,
+) -> core::result::Result<Option<bool>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum BoolOrNull<'a> {
+        Str(&'a str),
+        Bool(bool),
+        Null,
+    }
+
+    match BoolOrNull::deserialize(deserializer)? {
+        BoolOrNull::Str(s) => match s.trim().to_lowercase().as_str() {
+            "yes" | "true" => Ok(Some(true)),
+            "no" | "false" => Ok(Some(false)),
+            "" => Ok(None),
+            _ => Err(serde::de::Error::custom(format!(
+                "invalid yes/no value '{}'",
+                s
+            ))),
+        },
+        BoolOrNull::Bool(b) => Ok(Some(b)),
+        BoolOrNull::Null => Ok(None),
+    }
+}
+
 fn deserialize_option_version_from_string<'de, D>(
     deserializer: D,
 ) -> core::result::Result<Option<Version>, D::Error>
@@ -1751,6 +2571,51 @@ mod tests {
         assert_eq!(device.vendor_id, Some(0x2341));
     }
 
+    #[test]
+    fn test_deserialize_device_media() {
+        let device_json = "{
+              \"_name\" : \"Card Reader\",
+              \"Built-in_Device\" : \"Yes\",
+              \"location_id\" : \"0x001b0000 / 3\",
+              \"Media\" : [
+                {
+                  \"_name\" : \"SD Card\",
+                  \"bsd_name\" : \"disk4\",
+                  \"Logical Unit\" : 0,
+                  \"removable_media\" : \"yes\",
+                  \"size\" : \"64 GB\",
+                  \"size_in_bytes\" : 63864569856,
+                  \"USB Interface\" : 0,
+                  \"volumes\" : [
+                    {
+                      \"_name\" : \"NO NAME\",
+                      \"bsd_name\" : \"disk4s1\",
+                      \"file_system\" : \"ExFAT\",
+                      \"size\" : \"64 GB\",
+                      \"size_in_bytes\" : 63863521280
+                    }
+                  ]
+                }
+              ]
+            }";
+
+        let device: Device = serde_json::from_str(device_json).unwrap();
+
+        assert_eq!(device.built_in, Some(true));
+        assert_eq!(device.location_id.tree_positions, vec![1, 11]);
+        let media = device.media.as_ref().unwrap();
+        assert_eq!(media[0].removable, Some(true));
+        assert_eq!(media[0].logical_unit, Some(0));
+        assert_eq!(media[0].volumes[0].file_system, Some(String::from("ExFAT")));
+        assert_eq!(media[0].to_string(), "disk4 64 GB (removable)");
+
+        // round trip through cyme JSON
+        let json = serde_json::to_string(&device).unwrap();
+        let device: Device = serde_json::from_str(&json).unwrap();
+        assert_eq!(device.built_in, Some(true));
+        assert_eq!(device.media.as_ref(), Some(media));
+    }
+
     #[test]
     fn test_deserialize_bus() {
         let device_json = "{
@@ -1772,8 +2637,255 @@ mod tests {
         assert_eq!(device.usb_bus_number, Some(0x00));
     }
 
+    #[test]
+    fn test_deserialize_legacy_device() {
+        // dump from before interface paths and with kebab-case acronym variant names
+        let device_json = "{
+              \"name\" : \"Keyboard\",
+              \"vendor_id\" : 4617,
+              \"product_id\" : 1,
+              \"location_id\" : { \"bus\" : 3, \"tree_positions\" : [1, 4], \"number\" : 9 },
+              \"extra\" : {
+                \"max_packet_size\" : 64,
+                \"configurations\" : [
+                  {
+                    \"name\" : \"\",
+                    \"number\" : 1,
+                    \"attributes\" : [\"RemoteWakeup\"],
+                    \"max_power\" : { \"value\" : 100, \"unit\" : \"mA\", \"description\" : null },
+                    \"interfaces\" : [
+                      {
+                        \"name\" : null,
+                        \"number\" : 0,
+                        \"class\" : \"h-i-d\",
+                        \"sub_class\" : 1,
+                        \"protocol\" : 1,
+                        \"alt_setting\" : 0,
+                        \"endpoints\" : [],
+                        \"extra\" : [
+                          {
+                            \"device\" : {
+                              \"h-i-d\" : {
+                                \"length\" : 9,
+                                \"descriptor_type\" : 33,
+                                \"bcd_hid\" : [1, 1, 1],
+                                \"country_code\" : 0,
+                                \"descriptors\" : []
+                              }
+                            }
+                          }
+                        ]
+                      }
+                    ]
+                  }
+                ]
+              }
+            }";
+
+        let mut device: Device = serde_json::from_str(device_json).unwrap();
+        device.fill_legacy_fields();
+
+        let interface = &device.extra.as_ref().unwrap().configurations[0].interfaces[0];
+        assert_eq!(interface.class, BaseClass::Hid);
+        assert_eq!(interface.path, "3-1.4:1.0");
+        assert!(matches!(
+            interface.extra.as_ref().unwrap()[0],
+            Descriptor::Device(ClassDescriptor::Hid(_))
+        ));
+    }
+
+    #[test]
+    fn test_tree_navigation() {
+        let sp = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+
+        let probe = sp.find_by_serial("97B6A11D").unwrap();
+        assert_eq!(probe.port_path(), "2-2.8");
+        assert!(sp.find_by_serial("nope").is_none());
+
+        assert_eq!(sp.find_all_vidpid(0x1d6b, None).count(), 4);
+        assert_eq!(sp.find_all_vidpid(0x1d6b, Some(0x0003)).count(), 1);
+
+        let hubs: Vec<String> = sp.hub_chain(probe).map(|d| d.port_path()).collect();
+        assert_eq!(hubs, vec!["2-2"]);
+        assert_eq!(sp.ancestors(probe).count(), 1);
+
+        let hub = sp.get_node("2-2").unwrap();
+        assert_eq!(sp.ancestors(hub).count(), 0);
+        assert_eq!(hub.descendants().count(), 2);
+        assert_eq!(
+            hub.find_by_serial("001050027328").unwrap().port_path(),
+            "2-2.1"
+        );
+    }
+
     #[test]
     fn test_json_dump_read_not_panic() {
         read_json_dump("./tests/data/system_profiler_dump.json").unwrap();
     }
+
+    #[test]
+    fn test_device_below_capable_speed() {
+        let mut device = Device {
+            device_speed: Some(DeviceSpeed::SpeedValue(Speed::HighSpeed)),
+            bcd_usb: Some(Version(3, 2, 0)),
+            ..Default::default()
+        };
+
+        assert_eq!(device.negotiated_speed(), Some(Speed::HighSpeed));
+        assert_eq!(device.capable_speed(), Some(Speed::SuperSpeed));
+        assert!(device.is_below_capable_speed());
+
+        device.bcd_usb = Some(Version(2, 0, 0));
+        assert_eq!(device.capable_speed(), None);
+        assert!(!device.is_below_capable_speed());
+    }
+
+    #[test]
+    fn test_physical_location_display() {
+        let mut location = PhysicalLocation {
+            panel: "front".into(),
+            vertical_position: Some("upper".into()),
+            horizontal_position: Some("left".into()),
+            ..Default::default()
+        };
+        assert_eq!(location.to_string(), "front upper left");
+        location.horizontal_position = None;
+        location.dock = true;
+        assert_eq!(location.to_string(), "front upper dock");
+    }
+
+    #[test]
+    fn test_fill_removable_from_hubs() {
+        let hub_extra = |descriptor: &[u8]| usb::DeviceExtra {
+            max_packet_size: 64,
+            driver: None,
+            syspath: None,
+            vendor: None,
+            product_name: None,
+            string_indexes: (0, 0, 0),
+            configurations: Vec::new(),
+            status: None,
+            debug: None,
+            binary_object_store: None,
+            qualifier: None,
+            hub: Some(usb::HubDescriptor::try_from(descriptor).unwrap()),
+            vendor_fields: None,
+        };
+        let device = |tree_positions: Vec<u8>| Device {
+            location_id: DeviceLocation {
+                bus: 1,
+                number: tree_positions.iter().sum::<u8>() + 1,
+                tree_positions,
+            },
+            ..Default::default()
+        };
+
+        // root hub with 4 ports, port 2 hard-wired
+        let root_hub = Device {
+            extra: Some(hub_extra(&[
+                0x09, 0x29, 0x04, 0x00, 0x00, 0x00, 0x00, 0x04, 0xff,
+            ])),
+            ..device(vec![])
+        };
+        // SuperSpeed hub with 4 ports, port 1 hard-wired
+        let hub = Device {
+            extra: Some(hub_extra(&[
+                0x0c, 0x2a, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00,
+            ])),
+            devices: Some(vec![device(vec![1, 1]), device(vec![1, 3])]),
+            ..device(vec![1])
+        };
+        let sysfs = Device {
+            removable: Some(true),
+            ..device(vec![2])
+        };
+        let mut bus = Bus {
+            devices: Some(vec![root_hub, hub, sysfs]),
+            ..Bus::from(1)
+        };
+        bus.fill_removable_from_hubs();
+
+        let removable = |path: &str| bus.get_node(path).unwrap().removable;
+        assert_eq!(removable("1-0:1.0"), None);
+        assert_eq!(removable("1-1"), Some(true));
+        assert_eq!(removable("1-2"), Some(true));
+        assert_eq!(removable("1-1.1"), Some(false));
+        assert_eq!(removable("1-1.3"), Some(true));
+
+        let built_in = Device {
+            built_in: Some(true),
+            ..Default::default()
+        };
+        assert_eq!(built_in.is_removable(), Some(false));
+    }
+
+    #[test]
+    fn test_fill_location_kinds() {
+        let device = |tree_positions: Vec<u8>, removable: Option<bool>| Device {
+            location_id: DeviceLocation {
+                bus: 1,
+                number: tree_positions.iter().sum::<u8>() + 1,
+                tree_positions,
+            },
+            removable,
+            ..Default::default()
+        };
+
+        let dock = Device {
+            devices: Some(vec![
+                device(vec![1, 1], Some(false)),
+                device(vec![1, 2], None),
+            ]),
+            ..device(vec![1], Some(true))
+        };
+        let internal_hub = Device {
+            devices: Some(vec![
+                device(vec![2, 1], Some(false)),
+                device(vec![2, 2], Some(true)),
+            ]),
+            ..device(vec![2], Some(false))
+        };
+        let lid = Device {
+            physical_location: Some(PhysicalLocation {
+                panel: "top".into(),
+                lid: true,
+                ..Default::default()
+            }),
+            ..device(vec![3], None)
+        };
+        let mut bus = Bus {
+            devices: Some(vec![dock, internal_hub, lid, device(vec![4], None)]),
+            ..Bus::from(1)
+        };
+        bus.fill_location_kinds();
+
+        let kind = |path: &str| bus.get_node(path).unwrap().location_kind;
+        assert_eq!(kind("1-1"), Some(LocationKind::External));
+        assert_eq!(kind("1-1.1"), Some(LocationKind::Dock));
+        assert_eq!(kind("1-1.2"), Some(LocationKind::External));
+        assert_eq!(kind("1-2"), Some(LocationKind::Internal));
+        assert_eq!(kind("1-2.1"), Some(LocationKind::Internal));
+        assert_eq!(kind("1-2.2"), Some(LocationKind::External));
+        assert_eq!(kind("1-3"), Some(LocationKind::Internal));
+        assert_eq!(kind("1-4"), None);
+
+        let filter = Filter {
+            location: Some(LocationKind::Dock),
+            ..Default::default()
+        };
+        let mut flattened = bus.flattened_devices();
+        filter.retain_flattened_devices_ref(&mut flattened);
+        assert_eq!(flattened.len(), 1);
+
+        let mut tunnelled = Bus {
+            usb4_tunnelled: Some(true),
+            devices: Some(vec![device(vec![1], Some(true))]),
+            ..Bus::from(2)
+        };
+        tunnelled.fill_location_kinds();
+        assert_eq!(
+            tunnelled.devices.unwrap()[0].location_kind,
+            Some(LocationKind::Dock)
+        );
+    }
 }
@@ -0,0 +1,784 @@
+//! Import of reports saved by the Windows USBView tool, so reports collected on Windows machines can be filtered and displayed with cyme; see [`read_report`]
+//!
+//! Both the text report and XML are read. XML is that of [`crate::display::xml`], which is modeled on the USBView export, with devices nested in their host controller and hubs; elements named as the descriptor fields can be attributes or child elements. The text report has no host controllers or hubs so its devices are listed on bus 1 at the port in their '[PortN]' heading.
+//!
+//! Only the standard descriptors are imported; class descriptors, hub and power details in the reports are ignored.
+//!
+//! ```
+//! use cyme::profiler::usbview;
+//!
+//! let spusb = usbview::read_report("./tests/data/usbview_report.txt").unwrap();
+//! let device = spusb.get_node("1-3").unwrap();
+//! assert_eq!(device.vendor_id, Some(0x0781));
+//! assert_eq!(device.serial_num.as_deref(), Some("0401b5f6c3b2"));
+//! ```
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::*;
+use crate::types::NumericalUnit;
+
+/// Bus devices of a text report are placed on
+const TEXT_BUS: u8 = 1;
+
+/// Descriptor fields and details of a report element by name
+type Fields = HashMap<String, String>;
+
+#[derive(Debug, Default)]
+struct ReportInterface {
+    fields: Fields,
+    endpoints: Vec<Fields>,
+}
+
+#[derive(Debug, Default)]
+struct ReportConfiguration {
+    fields: Fields,
+    interfaces: Vec<ReportInterface>,
+}
+
+#[derive(Debug, Default)]
+struct ReportDevice {
+    fields: Fields,
+    configurations: Vec<ReportConfiguration>,
+    devices: Vec<ReportDevice>,
+}
+
+#[derive(Debug, Default)]
+struct ReportBus {
+    fields: Fields,
+    devices: Vec<ReportDevice>,
+}
+
+/// Read the USBView text or XML report at `file_path`
+pub fn read_report<P: AsRef<Path>>(file_path: P) -> Result<SystemProfile> {
+    let data = fs::read_to_string(&file_path)?;
+    parse_report(&data).map_err(|e| {
+        Error::new(
+            ErrorKind::Parsing,
+            &format!(
+                "Failed to parse USBView report {:?}; {}",
+                file_path.as_ref(),
+                e.message()
+            ),
+        )
+    })
+}
+
+/// Parse a USBView text or XML report
+pub fn parse_report(report: &str) -> Result<SystemProfile> {
+    let buses = if report.trim_start().starts_with('<') {
+        xml_buses(&parse_xml(report)?)
+    } else {
+        text_buses(report)
+    };
+
+    if buses.iter().all(|b| b.devices.is_empty()) {
+        return Err(Error::new(
+            ErrorKind::Parsing,
+            "No devices found in USBView report",
+        ));
+    }
+
+    Ok(SystemProfile {
+        buses: buses
+            .into_iter()
+            .enumerate()
+            .map(|(i, b)| build_bus(b, i as u8 + 1))
+            .collect(),
+        thunderbolt: None,
+        gadget: None,
+    })
+}
+
+/// Parse the text report: '[PortN]' headings start a device with '===>Name<===' descriptor sections of 'field: value' lines
+fn text_buses(report: &str) -> Vec<ReportBus> {
+    #[derive(PartialEq)]
+    enum Section {
+        Info,
+        Device,
+        Configuration,
+        Interface,
+        Endpoint,
+        Other,
+    }
+
+    let mut devices: Vec<ReportDevice> = Vec::new();
+    let mut section = Section::Other;
+    // field the next quoted string line is the string descriptor of
+    let mut string_field: Option<&str> = None;
+
+    for line in report.lines() {
+        let line = line.trim();
+
+        if let Some((port, description)) =
+            line.strip_prefix("[Port").and_then(|l| l.split_once(']'))
+        {
+            let mut device = ReportDevice::default();
+            device.fields.insert("Port".into(), port.trim().into());
+            if let Some(d) = description.trim().strip_prefix(':') {
+                device.fields.insert("Description".into(), d.trim().into());
+            }
+            devices.push(device);
+            section = Section::Info;
+            string_field = None;
+            continue;
+        }
+        let Some(device) = devices.last_mut() else {
+            continue;
+        };
+
+        if let Some(name) = line
+            .split_once("===>")
+            .and_then(|(_, l)| l.split_once("<==="))
+            .map(|(n, _)| n.trim())
+        {
+            string_field = None;
+            section = match name {
+                "Device Information" => Section::Info,
+                "Device Descriptor" => Section::Device,
+                "Configuration Descriptor" => {
+                    device.configurations.push(ReportConfiguration::default());
+                    Section::Configuration
+                }
+                "Interface Descriptor" => match device.configurations.last_mut() {
+                    Some(c) => {
+                        c.interfaces.push(ReportInterface::default());
+                        Section::Interface
+                    }
+                    None => Section::Other,
+                },
+                // the open pipes are listed before the configuration so only add those of an interface
+                "Endpoint Descriptor" => match device
+                    .configurations
+                    .last_mut()
+                    .and_then(|c| c.interfaces.last_mut())
+                {
+                    Some(i) => {
+                        i.endpoints.push(Fields::new());
+                        Section::Endpoint
+                    }
+                    None => Section::Other,
+                },
+                _ => Section::Other,
+            };
+            continue;
+        }
+
+        let fields = match section {
+            Section::Info | Section::Device => Some(&mut device.fields),
+            Section::Configuration => device.configurations.last_mut().map(|c| &mut c.fields),
+            Section::Interface => device
+                .configurations
+                .last_mut()
+                .and_then(|c| c.interfaces.last_mut())
+                .map(|i| &mut i.fields),
+            Section::Endpoint => device
+                .configurations
+                .last_mut()
+                .and_then(|c| c.interfaces.last_mut())
+                .and_then(|i| i.endpoints.last_mut()),
+            Section::Other => None,
+        };
+        let Some(fields) = fields else {
+            continue;
+        };
+
+        // string descriptor on the line after its index: 'English (United States)  "String"'
+        if let (Some(field), Some((_, quoted))) = (string_field, line.split_once('"')) {
+            if let Some((string, _)) = quoted.rsplit_once('"') {
+                fields.insert(field.into(), string.trim().into());
+            }
+            string_field = None;
+            continue;
+        }
+
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        string_field = match key {
+            "iManufacturer" => Some("Manufacturer"),
+            "iProduct" => Some("Product"),
+            "iSerialNumber" => Some("SerialNumber"),
+            "iConfiguration" => Some("Configuration"),
+            "iInterface" => Some("Interface"),
+            _ => None,
+        };
+        match key {
+            "Device Address" => {
+                fields.insert("DeviceAddress".into(), value.into());
+            }
+            // 'Current Config Value: 0x01  -> Device Bus Speed: High (is not SuperSpeed or higher capable)'
+            "Current Config Value" => {
+                if let Some((_, speed)) = value.split_once("Device Bus Speed:") {
+                    let speed = speed.split_whitespace().next().unwrap_or_default();
+                    fields.insert("Speed".into(), speed.into());
+                }
+            }
+            "English product name" => {
+                fields.insert("ProductName".into(), value.trim_matches('"').into());
+            }
+            // a descriptor field has no spaces in the name
+            _ if !key.is_empty() && !key.contains(' ') && section != Section::Info => {
+                fields.insert(key.into(), value.into());
+            }
+            _ => (),
+        }
+    }
+
+    vec![ReportBus {
+        fields: Fields::from([("BusNumber".into(), TEXT_BUS.to_string())]),
+        devices,
+    }]
+}
+
+/// XML element with the text of it
+#[derive(Debug, Default)]
+struct XmlElement {
+    name: String,
+    attributes: Vec<(String, String)>,
+    text: String,
+    children: Vec<XmlElement>,
+}
+
+impl XmlElement {
+    /// Attributes and the text of children without children of their own by name
+    fn fields(&self) -> Fields {
+        let mut fields: Fields = self.attributes.iter().cloned().collect();
+        for child in self.children.iter().filter(|c| c.children.is_empty()) {
+            if !child.text.is_empty() {
+                fields.insert(child.name.to_owned(), child.text.to_owned());
+            }
+        }
+        fields
+    }
+
+    fn is_device(&self) -> bool {
+        matches!(
+            self.name.as_str(),
+            "UsbDevice" | "Device" | "RootHub" | "ExternalHub"
+        )
+    }
+}
+
+/// Parse `xml` into a root element containing the elements of the document
+///
+/// Only what reports use is supported: elements, attributes, text, entities and CDATA; processing instructions, comments and declarations are skipped.
+fn parse_xml(xml: &str) -> Result<XmlElement> {
+    let invalid =
+        |message: &str| Error::new(ErrorKind::Parsing, &format!("Invalid XML: {}", message));
+    let mut stack = vec![XmlElement::default()];
+    let mut rest = xml;
+
+    while let Some(start) = rest.find('<') {
+        let text = unescape(rest[..start].trim());
+        if let Some(e) = stack.last_mut() {
+            e.text.push_str(&text);
+        }
+        rest = &rest[start..];
+
+        let skip_to = |rest: &str, end: &str| {
+            rest.find(end)
+                .map(|i| i + end.len())
+                .ok_or_else(|| invalid(&format!("'{}' is not closed", end)))
+        };
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = skip_to(cdata, "]]>")?;
+            if let Some(e) = stack.last_mut() {
+                e.text.push_str(&cdata[..end - 3]);
+            }
+            rest = &cdata[end..];
+        } else if rest.starts_with("<!--") {
+            rest = &rest[skip_to(rest, "-->")?..];
+        } else if rest.starts_with("<?") {
+            rest = &rest[skip_to(rest, "?>")?..];
+        } else if rest.starts_with("<!") {
+            rest = &rest[skip_to(rest, ">")?..];
+        } else if let Some(close) = rest.strip_prefix("</") {
+            let end = skip_to(close, ">")?;
+            let name = close[..end - 1].trim();
+            let element = stack
+                .pop()
+                .filter(|e| e.name == name && !stack.is_empty())
+                .ok_or_else(|| invalid(&format!("unexpected </{}>", name)))?;
+            if let Some(parent) = stack.last_mut() {
+                parent.children.push(element);
+            }
+            rest = &close[end..];
+        } else {
+            let end = skip_to(rest, ">")?;
+            let tag = &rest[1..end - 1];
+            let (tag, empty) = match tag.strip_suffix('/') {
+                Some(t) => (t, true),
+                None => (tag, false),
+            };
+            let (name, mut attributes) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+            let mut element = XmlElement {
+                name: name.to_string(),
+                ..Default::default()
+            };
+            // name="value" or name='value'
+            while let Some((name, value)) = attributes.split_once('=') {
+                let value = value.trim_start();
+                let quote = value
+                    .chars()
+                    .next()
+                    .filter(|c| *c == '"' || *c == '\'')
+                    .ok_or_else(|| invalid(&format!("attribute {} is not quoted", name)))?;
+                let (value, remaining) = value[1..]
+                    .split_once(quote)
+                    .ok_or_else(|| invalid(&format!("attribute {} is not closed", name)))?;
+                element
+                    .attributes
+                    .push((name.trim().to_string(), unescape(value)));
+                attributes = remaining;
+            }
+
+            if empty {
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(element);
+                }
+            } else {
+                stack.push(element);
+            }
+            rest = &rest[end..];
+        }
+    }
+
+    match stack.pop() {
+        Some(root) if stack.is_empty() => Ok(root),
+        Some(e) => Err(invalid(&format!("<{}> is not closed", e.name))),
+        None => Err(invalid("no root")),
+    }
+}
+
+/// Replace the XML entities in `s`
+fn unescape(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find('&') {
+        ret.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|h| u32::from_str_radix(h, 16))
+                .or_else(|| entity.strip_prefix('#').map(|d| d.parse::<u32>()))
+                .and_then(|n| n.ok())
+                .and_then(char::from_u32),
+        };
+        match c {
+            Some(c) => {
+                ret.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                ret.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    ret.push_str(rest);
+    ret
+}
+
+/// Host controllers and their devices in `root`; devices outside of a host controller are placed on a bus of their own
+fn xml_buses(root: &XmlElement) -> Vec<ReportBus> {
+    fn collect(element: &XmlElement, buses: &mut Vec<ReportBus>, orphans: &mut Vec<ReportDevice>) {
+        for child in element.children.iter() {
+            if child.name == "HostController" {
+                buses.push(ReportBus {
+                    fields: child.fields(),
+                    devices: xml_devices(child),
+                });
+            } else if child.is_device() {
+                orphans.push(xml_device(child));
+            } else {
+                collect(child, buses, orphans);
+            }
+        }
+    }
+
+    let mut buses = Vec::new();
+    let mut orphans = Vec::new();
+    collect(root, &mut buses, &mut orphans);
+    if !orphans.is_empty() {
+        buses.push(ReportBus {
+            fields: Fields::new(),
+            devices: orphans,
+        });
+    }
+    buses
+}
+
+/// Devices in `element`, including those in elements such as ports which are not devices
+fn xml_devices(element: &XmlElement) -> Vec<ReportDevice> {
+    element
+        .children
+        .iter()
+        .flat_map(|c| {
+            if c.is_device() {
+                vec![xml_device(c)]
+            } else if c.name.ends_with("Descriptor") {
+                Vec::new()
+            } else {
+                xml_devices(c)
+            }
+        })
+        .collect()
+}
+
+fn xml_device(element: &XmlElement) -> ReportDevice {
+    let mut fields = element.fields();
+    let descriptors = |name: &'static str| element.children.iter().filter(move |c| c.name == name);
+    for descriptor in descriptors("DeviceDescriptor") {
+        fields.extend(descriptor.fields());
+    }
+
+    ReportDevice {
+        fields,
+        configurations: descriptors("ConfigurationDescriptor")
+            .map(|c| ReportConfiguration {
+                fields: c.fields(),
+                interfaces: c
+                    .children
+                    .iter()
+                    .filter(|i| i.name == "InterfaceDescriptor")
+                    .map(|i| ReportInterface {
+                        fields: i.fields(),
+                        endpoints: i
+                            .children
+                            .iter()
+                            .filter(|e| e.name == "EndpointDescriptor")
+                            .map(|e| e.fields())
+                            .collect(),
+                    })
+                    .collect(),
+            })
+            .collect(),
+        devices: xml_devices(element),
+    }
+}
+
+/// Number at the start of the field `key`, hex if prefixed with '0x' such as '0x046D = Logitech Inc.'
+fn number(fields: &Fields, key: &str) -> Option<u32> {
+    let value = fields.get(key)?.split_whitespace().next()?;
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+fn byte(fields: &Fields, key: &str) -> Option<u8> {
+    number(fields, key).and_then(|n| u8::try_from(n).ok())
+}
+
+fn word(fields: &Fields, key: &str) -> Option<u16> {
+    number(fields, key).and_then(|n| u16::try_from(n).ok())
+}
+
+fn string(fields: &Fields, key: &str) -> Option<String> {
+    fields.get(key).filter(|s| !s.is_empty()).cloned()
+}
+
+fn build_bus(report: ReportBus, index: u8) -> Bus {
+    let fields = &report.fields;
+    let number = byte(fields, "BusNumber").unwrap_or(index);
+    let mut bus = Bus {
+        pci_vendor: word(fields, "VendorId"),
+        pci_device: word(fields, "DeviceId"),
+        pci_revision: word(fields, "Revision"),
+        ..Bus::from(number)
+    };
+    if let Some(name) = string(fields, "Name") {
+        bus.name = name;
+    }
+    if let Some(controller) = string(fields, "Controller") {
+        bus.host_controller = controller;
+    }
+    bus.devices = Some(
+        report
+            .devices
+            .into_iter()
+            .map(|d| build_device(d, number, &[]))
+            .collect(),
+    );
+    bus
+}
+
+fn build_device(report: ReportDevice, bus: u8, parent_ports: &[u8]) -> Device {
+    let fields = &report.fields;
+    let location = match fields.get("Port").map(|p| p.parse::<usb::PortPath>()) {
+        Some(Ok(path)) => DeviceLocation {
+            bus: path.bus,
+            tree_positions: path.ports,
+            number: 0,
+        },
+        port => {
+            let mut tree_positions = parent_ports.to_vec();
+            if let Some(port) = port.and_then(|_| byte(fields, "Port")) {
+                tree_positions.push(port);
+            }
+            DeviceLocation {
+                bus,
+                tree_positions,
+                number: 0,
+            }
+        }
+    };
+    let location = DeviceLocation {
+        number: byte(fields, "DeviceAddress").unwrap_or_default(),
+        ..location
+    };
+
+    let (name, name_source) = match string(fields, "Product") {
+        Some(name) => (name, Some(names::NameSource::Descriptor)),
+        // the device description from the Windows driver
+        None => (
+            string(fields, "ProductName")
+                .or_else(|| string(fields, "Description"))
+                .unwrap_or_default(),
+            Some(names::NameSource::Os),
+        ),
+    };
+    let manufacturer = string(fields, "Manufacturer");
+
+    let configurations: Vec<usb::Configuration> = report
+        .configurations
+        .into_iter()
+        .map(|c| build_configuration(c, &location))
+        .collect();
+    let extra =
+        (byte(fields, "bMaxPacketSize0").is_some() || !configurations.is_empty()).then(|| {
+            usb::DeviceExtra {
+                max_packet_size: byte(fields, "bMaxPacketSize0").unwrap_or_default(),
+                driver: string(fields, "Driver"),
+                syspath: None,
+                vendor: None,
+                product_name: None,
+                string_indexes: (
+                    byte(fields, "iProduct").unwrap_or_default(),
+                    byte(fields, "iManufacturer").unwrap_or_default(),
+                    byte(fields, "iSerialNumber").unwrap_or_default(),
+                ),
+                configurations,
+                status: None,
+                debug: None,
+                binary_object_store: None,
+                qualifier: None,
+                hub: None,
+                vendor_fields: None,
+            }
+        });
+
+    let ports = location.tree_positions.clone();
+    let devices: Vec<Device> = report
+        .devices
+        .into_iter()
+        .map(|d| build_device(d, location.bus, &ports))
+        .collect();
+
+    Device {
+        name,
+        vendor_id: word(fields, "idVendor"),
+        product_id: word(fields, "idProduct"),
+        serial_num: string(fields, "SerialNumber"),
+        manufacturer_source: manufacturer.as_ref().map(|_| names::NameSource::Descriptor),
+        manufacturer,
+        name_source,
+        bcd_device: word(fields, "bcdDevice").map(usb::Version::from_bcd),
+        bcd_usb: word(fields, "bcdUSB").map(usb::Version::from_bcd),
+        class: byte(fields, "bDeviceClass").map(usb::BaseClass::from),
+        sub_class: byte(fields, "bDeviceSubClass"),
+        protocol: byte(fields, "bDeviceProtocol"),
+        device_speed: fields.get("Speed").map(|s| {
+            let speed = match s.to_lowercase().as_str() {
+                "superspeedplus" | "superplus" => usb::Speed::SuperSpeedPlus,
+                "superspeed" => usb::Speed::SuperSpeed,
+                s => s.parse().unwrap_or(usb::Speed::Unknown),
+            };
+            DeviceSpeed::SpeedValue(speed)
+        }),
+        devices: (!devices.is_empty()).then_some(devices),
+        extra,
+        location_id: location,
+        ..Default::default()
+    }
+}
+
+fn build_configuration(
+    report: ReportConfiguration,
+    location: &DeviceLocation,
+) -> usb::Configuration {
+    let fields = &report.fields;
+    let number = byte(fields, "bConfigurationValue").unwrap_or_default();
+    let attributes_value = byte(fields, "bmAttributes").unwrap_or_default();
+    let mut attributes = Vec::new();
+    if attributes_value & 0x10 != 0 {
+        attributes.push(usb::ConfigAttributes::BatteryPowered);
+    }
+    if attributes_value & 0x20 != 0 {
+        attributes.push(usb::ConfigAttributes::RemoteWakeup);
+    }
+    if attributes_value & 0x40 != 0 {
+        attributes.push(usb::ConfigAttributes::SelfPowered);
+    }
+    // '0x31 = 98 mA' in the text report, '98 mA' in XML
+    let max_power = fields
+        .get("MaxPower")
+        .map(|p| p.rsplit_once('=').map_or(p.as_str(), |(_, power)| power))
+        .and_then(|p| p.trim().parse::<NumericalUnit<u32>>().ok())
+        .unwrap_or(NumericalUnit {
+            value: 0,
+            unit: String::from("mA"),
+            description: None,
+        });
+
+    usb::Configuration {
+        name: string(fields, "Configuration").unwrap_or_default(),
+        string_index: byte(fields, "iConfiguration").unwrap_or_default(),
+        number,
+        interfaces: report
+            .interfaces
+            .into_iter()
+            .map(|i| build_interface(i, location, number))
+            .collect(),
+        attributes,
+        max_power,
+        length: byte(fields, "bLength").unwrap_or(9),
+        total_length: word(fields, "wTotalLength").unwrap_or_default(),
+        extra: None,
+        raw: None,
+        active: fields.get("Active").is_some_and(|a| a == "true"),
+        warnings: Vec::new(),
+    }
+}
+
+fn build_interface(
+    report: ReportInterface,
+    location: &DeviceLocation,
+    config: u8,
+) -> usb::Interface {
+    let fields = &report.fields;
+    let number = byte(fields, "bInterfaceNumber").unwrap_or_default();
+
+    usb::Interface {
+        name: string(fields, "Interface"),
+        string_index: byte(fields, "iInterface").unwrap_or_default(),
+        number,
+        path: usb::get_interface_path(location.bus, &location.tree_positions, config, number),
+        class: usb::BaseClass::from(byte(fields, "bInterfaceClass").unwrap_or_default()),
+        sub_class: byte(fields, "bInterfaceSubClass").unwrap_or_default(),
+        protocol: byte(fields, "bInterfaceProtocol").unwrap_or_default(),
+        alt_setting: byte(fields, "bAlternateSetting").unwrap_or_default(),
+        driver: string(fields, "Driver"),
+        syspath: None,
+        endpoints: report
+            .endpoints
+            .iter()
+            .map(|fields| {
+                let attributes = byte(fields, "bmAttributes").unwrap_or_default();
+                usb::Endpoint {
+                    address: usb::EndpointAddress::from(
+                        byte(fields, "bEndpointAddress").unwrap_or_default(),
+                    ),
+                    transfer_type: usb::TransferType::from(attributes),
+                    sync_type: usb::SyncType::from(attributes),
+                    usage_type: usb::UsageType::from(attributes),
+                    max_packet_size: word(fields, "wMaxPacketSize").unwrap_or_default(),
+                    interval: byte(fields, "bInterval").unwrap_or_default(),
+                    length: byte(fields, "bLength").unwrap_or(7),
+                    extra: None,
+                    raw: None,
+                }
+            })
+            .collect(),
+        length: byte(fields, "bLength").unwrap_or(9),
+        extra: None,
+        bluetooth: None,
+        module: None,
+        vendor_fields: None,
+        raw: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::display::xml::to_xml;
+
+    #[test]
+    fn test_read_text_report() {
+        let spusb = read_report("./tests/data/usbview_report.txt").unwrap();
+        let device = spusb.get_node("1-1").unwrap();
+        assert_eq!(device.name, "USB Receiver");
+        assert_eq!(device.manufacturer.as_deref(), Some("Logitech"));
+        assert_eq!(device.location_id.number, 2);
+        assert_eq!(
+            device.device_speed,
+            Some(DeviceSpeed::SpeedValue(usb::Speed::FullSpeed))
+        );
+        let config = &device.extra.as_ref().unwrap().configurations[0];
+        assert_eq!(config.name, "RQR12.11_B0032");
+        assert_eq!(config.max_power.value, 98);
+        assert_eq!(config.attributes, vec![usb::ConfigAttributes::RemoteWakeup]);
+        // only the endpoint of the interface, not the open pipe
+        assert_eq!(config.interfaces[0].endpoints.len(), 1);
+        assert_eq!(config.interfaces[0].class, usb::BaseClass::Hid);
+
+        let device = spusb.get_node("1-3").unwrap();
+        assert_eq!(device.name, "SanDisk 3.2Gen1");
+        assert!(device.has_interface_class(&usb::BaseClass::MassStorage));
+    }
+
+    #[test]
+    fn test_read_xml_report() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let imported = parse_report(&to_xml(&spusb)).unwrap();
+        for device in spusb.iter() {
+            let i = imported.get_node(&device.port_path()).unwrap();
+            assert_eq!(i.name, device.name);
+            assert_eq!(
+                (i.vendor_id, i.product_id),
+                (device.vendor_id, device.product_id)
+            );
+            assert_eq!(i.serial_num, device.serial_num);
+            assert_eq!(i.location_id, device.location_id);
+            let (ie, de) = (i.extra.as_ref().unwrap(), device.extra.as_ref().unwrap());
+            assert_eq!(ie.configurations.len(), de.configurations.len());
+            assert_eq!(
+                ie.configurations[0].interfaces[0].endpoints.len(),
+                de.configurations[0].interfaces[0].endpoints.len()
+            );
+        }
+
+        assert!(parse_report("<UsbTree><UsbDevice></UsbTree>").is_err());
+        assert_eq!(unescape("a &amp; &#x42;&#67; &bad"), "a & BC &bad");
+    }
+
+    #[test]
+    fn test_xml_control_characters() {
+        let mut spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        spusb.get_node_mut("2-2.8").unwrap().name = String::from("Probe\x01\x1b[31m\tv1");
+        let xml = to_xml(&spusb);
+        assert!(!xml.contains(['\x01', '\x1b']));
+        let imported = parse_report(&xml).unwrap();
+        assert_eq!(
+            imported.get_node("2-2.8").unwrap().name,
+            "Probe\u{fffd}\u{fffd}[31m\tv1"
+        );
+    }
+}
@@ -0,0 +1,648 @@
+//! Watch for [`Device`]s connecting and disconnecting by profiling the system at an interval
+//!
+//! Polling works with every profiler and platform at the cost of missing a device that connects and disconnects between polls. A device is identified by its port path and number so one that re-enumerates on the same port between polls is reported as disconnected then connected.
+//!
+//! With [`Watcher::with_shared`] each poll lists the devices without opening them and only profiles those which have connected, updating a [`SharedProfile`] that other threads can read and subscribe to.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use cyme::profiler::watch::{WatchEvent, Watcher};
+//!
+//! let mut watcher = Watcher::new(Duration::from_millis(250));
+//! // the first poll reports devices already connected
+//! watcher.poll().unwrap();
+//! for event in watcher {
+//!     match event.unwrap() {
+//!         WatchEvent::Connected(d) => println!("+ {}", d.port_path()),
+//!         WatchEvent::Disconnected(d) => println!("- {}", d.port_path()),
+//!     }
+//! }
+//! ```
+use crate::colour::colored::*;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, VecDeque};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use super::audit::{self, Policy};
+use super::history::{self, HistoryEvent, HistoryRecord};
+use super::{cache, Device, Filter, ProfilerBuilder, SharedProfile, SystemProfile};
+use crate::display::{self, outputln};
+use crate::error::{Error, ErrorKind, Result};
+use crate::redact::Redaction;
+
+/// Fields substituted by [`WatchEvent::expand`] when in braces, `{port_path}` for example
+pub const TEMPLATE_FIELDS: [&str; 12] = [
+    "event",
+    "port_path",
+    "vidpid",
+    "vid",
+    "pid",
+    "name",
+    "serial",
+    "manufacturer",
+    "bus",
+    "number",
+    "dev_path",
+    "class",
+];
+
+/// [`TEMPLATE_FIELDS`] with strings reported by the device rather than formatted by cyme; a `--exec` command gets these from their [`env_var`] rather than in the command so the shell never parses them
+pub const DEVICE_STRING_FIELDS: [&str; 3] = ["name", "serial", "manufacturer"];
+
+/// Environment variable of a [`TEMPLATE_FIELDS`] `field` for a `--exec` command, `CYME_PORT_PATH` for example
+pub fn env_var(field: &str) -> String {
+    format!("CYME_{}", field.to_uppercase())
+}
+
+/// A [`Device`] connecting or disconnecting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", content = "device", rename_all = "kebab-case")]
+pub enum WatchEvent {
+    /// Device was connected
+    Connected(Device),
+    /// Device was disconnected; values are those when it was last seen
+    Disconnected(Device),
+}
+
+impl WatchEvent {
+    /// The [`Device`] of the event
+    pub fn device(&self) -> &Device {
+        match self {
+            WatchEvent::Connected(d) | WatchEvent::Disconnected(d) => d,
+        }
+    }
+
+    /// Mutable [`Device`] of the event, such as to redact it
+    pub fn device_mut(&mut self) -> &mut Device {
+        match self {
+            WatchEvent::Connected(d) | WatchEvent::Disconnected(d) => d,
+        }
+    }
+
+    /// Whether the event is a [`WatchEvent::Connected`]
+    pub fn is_connected(&self) -> bool {
+        matches!(self, WatchEvent::Connected(_))
+    }
+
+    /// Name of the event: 'connected' or 'disconnected'
+    pub fn name(&self) -> &'static str {
+        if self.is_connected() {
+            "connected"
+        } else {
+            "disconnected"
+        }
+    }
+
+    /// Value of a [`TEMPLATE_FIELDS`] `field` for the event; empty if the device has no value
+    pub fn field(&self, field: &str) -> Option<String> {
+        let d = self.device();
+        let hex = |v: Option<u16>| v.map_or(String::new(), |v| format!("{:04x}", v));
+        Some(match field {
+            "event" => self.name().to_string(),
+            "port_path" => d.port_path(),
+            "vidpid" => format!("{}:{}", hex(d.vendor_id), hex(d.product_id)),
+            "vid" => hex(d.vendor_id),
+            "pid" => hex(d.product_id),
+            "name" => d.name.to_owned(),
+            "serial" => d.serial_num.to_owned().unwrap_or_default(),
+            "manufacturer" => d.manufacturer.to_owned().unwrap_or_default(),
+            "bus" => d.location_id.bus.to_string(),
+            "number" => d.location_id.number.to_string(),
+            "dev_path" => d.dev_path(),
+            "class" => d.class.map_or(String::new(), |c| c.to_string()),
+            _ => return None,
+        })
+    }
+
+    /// Substitute each `{field}` of [`TEMPLATE_FIELDS`] in `template` with its value passed through `escape`, such as shell quoting; unknown fields are left as is
+    ///
+    /// ```
+    /// use cyme::profiler::watch::WatchEvent;
+    /// use cyme::profiler::Device;
+    ///
+    /// let device = Device {
+    ///     vendor_id: Some(0x1d50),
+    ///     product_id: Some(0x6018),
+    ///     ..Default::default()
+    /// };
+    /// let event = WatchEvent::Connected(device);
+    /// assert_eq!(
+    ///     event.expand("{event} {vidpid} {unknown}", |s| s.to_string()),
+    ///     "connected 1d50:6018 {unknown}"
+    /// );
+    /// ```
+    pub fn expand<F: Fn(&str) -> String>(&self, template: &str, escape: F) -> String {
+        self.expand_fields(template, |_, value| escape(&value))
+    }
+
+    /// The shell command of a `--exec` `template` for the event: [`DEVICE_STRING_FIELDS`] are replaced with a reference to their [`env_var`] and other fields with their shell quoted value
+    ///
+    /// ```
+    /// use cyme::profiler::watch::WatchEvent;
+    /// use cyme::profiler::Device;
+    ///
+    /// let device = Device {
+    ///     name: "Probe'; rm -rf ~".into(),
+    ///     vendor_id: Some(0x1d50),
+    ///     product_id: Some(0x6018),
+    ///     ..Default::default()
+    /// };
+    /// let event = WatchEvent::Connected(device);
+    /// if cfg!(unix) {
+    ///     assert_eq!(
+    ///         event.shell_command("notify-send {vidpid} {name}"),
+    ///         "notify-send '1d50:6018' \"$CYME_NAME\""
+    ///     );
+    /// }
+    /// ```
+    pub fn shell_command(&self, template: &str) -> String {
+        self.expand_fields(template, |field, value| {
+            if DEVICE_STRING_FIELDS.contains(&field) {
+                shell_var(&env_var(field))
+            } else {
+                shell_quote(&value)
+            }
+        })
+    }
+
+    /// Each of [`TEMPLATE_FIELDS`] as its [`env_var`] and value, the environment of a `--exec` command
+    pub fn env_vars(&self) -> Vec<(String, String)> {
+        TEMPLATE_FIELDS
+            .iter()
+            .filter_map(|f| self.field(f).map(|v| (env_var(f), v)))
+            .collect()
+    }
+
+    /// Substitute each `{field}` of [`TEMPLATE_FIELDS`] in `template` with `f` of the field name and its value
+    fn expand_fields<F: Fn(&str, String) -> String>(&self, template: &str, f: F) -> String {
+        let mut ret = String::with_capacity(template.len());
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            ret.push_str(&rest[..start]);
+            rest = &rest[start..];
+            match rest
+                .find('}')
+                .and_then(|end| self.field(&rest[1..end]).map(|v| (end, v)))
+            {
+                Some((end, value)) => {
+                    ret.push_str(&f(&rest[1..end], value));
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    ret.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        ret.push_str(rest);
+        ret
+    }
+}
+
+/// Send a desktop notification of `event` with the device name, VID:PID and port path
+#[cfg(feature = "notify")]
+pub fn notify(event: &WatchEvent) -> Result<()> {
+    notify_rust::Notification::new()
+        .appname("cyme")
+        .summary(&format!("USB device {}", event.name()))
+        .body(&event.expand("{name} {vidpid} on {port_path}", |s| s.to_string()))
+        .show()
+        .map(|_| ())
+        .map_err(|e| Error::new(ErrorKind::Other("notify-rust"), &e.to_string()))
+}
+
+/// Send a desktop notification of `event`; requires the 'notify' feature
+#[cfg(not(feature = "notify"))]
+pub fn notify(_event: &WatchEvent) -> Result<()> {
+    Err(Error::new(
+        ErrorKind::Unsupported,
+        "notify feature is required for desktop notifications, install with `cargo install --features notify`",
+    ))
+}
+
+/// Profiles the system each `interval` reporting [`WatchEvent`]s for devices matching an optional [`Filter`]
+///
+/// Use [`Watcher::poll`] to profile once or iterate, which blocks until the next event.
+#[derive(Debug)]
+pub struct Watcher {
+    builder: ProfilerBuilder,
+    filter: Option<Filter>,
+    interval: Duration,
+    devices: BTreeMap<(String, u8), Device>,
+    pending: VecDeque<WatchEvent>,
+    shared: Option<SharedProfile>,
+}
+
+impl Watcher {
+    /// New watcher profiling each `interval` without extra data
+    pub fn new(interval: Duration) -> Self {
+        Watcher {
+            builder: ProfilerBuilder::new(),
+            filter: None,
+            interval,
+            devices: BTreeMap::new(),
+            pending: VecDeque::new(),
+            shared: None,
+        }
+    }
+
+    /// Only report devices matching `filter`
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Profile with `builder`, such as to include extra data required by a class filter
+    pub fn with_builder(mut self, builder: ProfilerBuilder) -> Self {
+        self.builder = builder;
+        self
+    }
+
+    /// Update `shared` with [`SharedProfile::refresh`] each poll rather than profiling the system again, so only devices which have connected are profiled and other users of `shared` see the changes
+    pub fn with_shared(mut self, shared: SharedProfile) -> Self {
+        self.shared = Some(shared);
+        self
+    }
+
+    /// Time between each profile when iterating
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Connected devices matching the filter as of the last poll, sorted by port path
+    pub fn devices(&self) -> impl Iterator<Item = &Device> {
+        self.devices.values()
+    }
+
+    /// Profile the system once returning the events since the last poll; the first poll reports all connected devices
+    pub fn poll(&mut self) -> Result<Vec<WatchEvent>> {
+        match self.shared.clone() {
+            Some(shared) => {
+                shared.refresh(&self.builder)?;
+                let spusb = shared.read();
+                Ok(self.update(&spusb))
+            }
+            None => {
+                let spusb = self.builder.profile()?;
+                Ok(self.update(&spusb))
+            }
+        }
+    }
+
+    /// Update the connected devices from `spusb`, profiled elsewhere, returning the events since the last update
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use cyme::profiler::read_json_dump;
+    /// use cyme::profiler::watch::Watcher;
+    ///
+    /// let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+    /// let mut watcher = Watcher::new(Duration::from_secs(1));
+    /// assert_eq!(watcher.update(&spusb).len(), spusb.len());
+    /// assert!(watcher.update(&spusb).is_empty());
+    /// ```
+    pub fn update(&mut self, spusb: &SystemProfile) -> Vec<WatchEvent> {
+        let mut current: BTreeMap<(String, u8), Device> = spusb
+            .iter()
+            .filter(|d| self.filter.as_ref().is_none_or(|f| f.is_match(d)))
+            .map(|d| {
+                let mut d = d.clone();
+                d.devices = None;
+                ((d.port_path(), d.location_id.number), d)
+            })
+            .collect();
+
+        let mut events: Vec<WatchEvent> = Vec::new();
+        for (key, device) in std::mem::take(&mut self.devices) {
+            match current.remove(&key) {
+                Some(d) => {
+                    self.devices.insert(key, d);
+                }
+                None => events.push(WatchEvent::Disconnected(device)),
+            }
+        }
+        for (key, device) in current {
+            events.push(WatchEvent::Connected(device.clone()));
+            self.devices.insert(key, device);
+        }
+        log::debug!("Watch update {} events", events.len());
+
+        events
+    }
+}
+
+impl Iterator for Watcher {
+    type Item = Result<WatchEvent>;
+
+    /// Blocks polling each [`Watcher::interval`] until there is an event
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            std::thread::sleep(self.interval);
+            match self.poll() {
+                Ok(events) => self.pending.extend(events),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+/// Poll `watcher` until a device is connected, or none are if `gone`, returning the connected devices or those that disconnected
+///
+/// Errors with [`ErrorKind::NotFound`] if `timeout` passes first.
+pub fn wait(mut watcher: Watcher, gone: bool, timeout: Option<Duration>) -> Result<Vec<Device>> {
+    let start = Instant::now();
+    let mut disconnected = Vec::new();
+
+    loop {
+        for event in watcher.poll()? {
+            if let WatchEvent::Disconnected(d) = event {
+                disconnected.push(d);
+            }
+        }
+
+        match (gone, watcher.devices().next().is_none()) {
+            (false, false) => return Ok(watcher.devices().cloned().collect()),
+            (true, true) => return Ok(disconnected),
+            _ => (),
+        }
+
+        if let Some(t) = timeout {
+            if start.elapsed() >= t {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    &format!(
+                        "Timed out after {}s waiting for device to {}",
+                        t.as_secs(),
+                        if gone { "disconnect" } else { "connect" }
+                    ),
+                ));
+            }
+        }
+
+        std::thread::sleep(watcher.interval());
+    }
+}
+
+/// [`wait`] for `cyme wait`, with the devices redacted with `redaction`
+pub fn run_wait(
+    watcher: Watcher,
+    gone: bool,
+    timeout: Option<Duration>,
+    redaction: Option<&Redaction>,
+) -> Result<Vec<Device>> {
+    let mut devices = wait(watcher, gone, timeout)?;
+    if let Some(redaction) = redaction {
+        for device in devices.iter_mut() {
+            redaction.apply_device(device, true)?;
+        }
+    }
+
+    Ok(devices)
+}
+
+/// Settings of [`run`] for `cyme watch`
+#[derive(Debug, Default, Clone)]
+pub struct WatchSettings {
+    /// Command spawned with the shell for each event; see [`WatchEvent::shell_command`] and [`WatchEvent::env_vars`]
+    pub exec: Option<String>,
+    /// Report devices connected before starting as events rather than only those after
+    pub existing: bool,
+    /// Send a desktop [`notify`] of each event
+    pub notify: bool,
+    /// Print events as JSON lines
+    pub json: bool,
+    /// Colour connected events green and disconnected red when not `json`
+    pub colours: bool,
+    /// Record the events in the [`history`] at the path
+    pub history: Option<PathBuf>,
+    /// Print the [`audit::enforce`] findings of connected devices with the policy
+    pub policy: Option<Policy>,
+    /// Deauthorize connected devices which violate `policy`
+    pub deauthorize: bool,
+}
+
+/// Print the events of `watcher` for `cyme watch` to the [`display::set_output`] writer until it errors, redacted with `redaction`, applying the `settings` to each
+///
+/// The history, policy and exec command have the real values rather than the redacted ones. The profile cache is invalidated on each event since it no longer matches.
+pub fn run(
+    mut watcher: Watcher,
+    settings: &WatchSettings,
+    redaction: Option<&Redaction>,
+) -> Result<()> {
+    let initial = watcher.poll()?;
+    // devices connected before starting are reported with the events if existing
+    if let (Some(policy), false) = (settings.policy.as_ref(), settings.existing) {
+        for event in initial.iter() {
+            enforce(event.device(), policy, settings, redaction)?;
+        }
+    }
+    if let Some(path) = settings.history.as_ref() {
+        let now = history::now();
+        let records: Vec<HistoryRecord> = initial
+            .iter()
+            .map(|e| HistoryRecord {
+                event: HistoryEvent::Present,
+                ..HistoryRecord::new(e, now)
+            })
+            .collect();
+        history::append(path, &records)?;
+    }
+    let events = initial
+        .into_iter()
+        .filter(|_| settings.existing)
+        .map(Ok)
+        .chain(watcher);
+
+    for event in events {
+        let event = event?;
+        let shown = match redaction {
+            Some(redaction) => {
+                let mut shown = event.clone();
+                redaction.apply_device(shown.device_mut(), true)?;
+                Cow::Owned(shown)
+            }
+            None => Cow::Borrowed(&event),
+        };
+        if settings.json {
+            outputln!("{}", serde_json::to_string(&shown)?);
+        } else {
+            let line = format!(
+                "{} {}",
+                if event.is_connected() { "+" } else { "-" },
+                shown.device().to_lsusb_string()
+            );
+            match (settings.colours, event.is_connected()) {
+                (false, _) => outputln!("{}", line),
+                (true, true) => outputln!("{}", line.green()),
+                (true, false) => outputln!("{}", line.red()),
+            }
+        }
+        if let Some(path) = settings.history.as_ref() {
+            let record = HistoryRecord::new(&event, history::now());
+            if let Err(e) = history::append(path, &[record]) {
+                log::warn!("Failed to record history: {}", e);
+            }
+        }
+        // the profile cached for --cached no longer matches
+        if let Some(path) = cache::default_path() {
+            if let Err(e) = cache::invalidate(&path) {
+                log::warn!("Failed to invalidate profile cache: {}", e);
+            }
+        }
+        if let (Some(policy), true) = (settings.policy.as_ref(), event.is_connected()) {
+            enforce(event.device(), policy, settings, redaction)?;
+        }
+        if let Some(exec) = settings.exec.as_ref() {
+            run_shell(&event, exec);
+        }
+        if settings.notify {
+            if let Err(e) = notify(&event) {
+                log::warn!("Failed to send notification: {}", e);
+            }
+        }
+        // each event is written as it happens rather than when buffered output is full
+        if let Err(e) = display::flush_output() {
+            log::warn!("Failed to flush output: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the [`audit::enforce`] findings of `device` with `policy` as JSON lines if [`WatchSettings::json`], otherwise coloured if [`WatchSettings::colours`]
+fn enforce(
+    device: &Device,
+    policy: &Policy,
+    settings: &WatchSettings,
+    redaction: Option<&Redaction>,
+) -> Result<()> {
+    let report = audit::enforce(device, policy, settings.deauthorize, redaction)?;
+    for finding in &report.findings {
+        if settings.json {
+            outputln!("{}", serde_json::to_string(finding)?);
+        } else if settings.colours {
+            outputln!("! {}", finding.to_coloured_string());
+        } else {
+            outputln!("! {}", finding);
+        }
+    }
+    for port_path in &report.deauthorized {
+        log::info!("Deauthorized {}", port_path);
+        if !settings.json {
+            outputln!("! deauthorized {}", port_path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Quote `s` as a single shell word
+fn shell_quote(s: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", s.replace('"', ""))
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+}
+
+/// Reference to environment variable `name` as a single shell word; delayed expansion on Windows since cmd parses the value of a '%' reference
+fn shell_var(name: &str) -> String {
+    if cfg!(windows) {
+        format!("\"!{}!\"", name)
+    } else {
+        format!("\"${}\"", name)
+    }
+}
+
+/// Spawn the `--exec` `template` for `event` with the shell and [`WatchEvent::env_vars`], without waiting so watching continues; a failure is logged rather than returned
+fn run_shell(event: &WatchEvent, template: &str) {
+    let command = event.shell_command(template);
+    let mut shell = if cfg!(windows) {
+        let mut shell = std::process::Command::new("cmd");
+        shell.args(["/V:ON", "/C", &command]);
+        shell
+    } else {
+        let mut shell = std::process::Command::new("sh");
+        shell.args(["-c", &command]);
+        shell
+    };
+    shell.envs(event.env_vars());
+    match shell.spawn() {
+        // reaped by a thread so the exit status is still logged
+        Ok(mut child) => {
+            std::thread::spawn(move || match child.wait() {
+                Ok(s) if !s.success() => log::warn!("'{}' exited with {}", command, s),
+                Ok(_) => (),
+                Err(e) => log::error!("Failed to wait for '{}': {}", command, e),
+            });
+        }
+        Err(e) => log::error!("Failed to run '{}': {}", command, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::DeviceLocation;
+
+    #[test]
+    fn test_expand_template() {
+        let device = Device {
+            name: "Black Magic Probe".into(),
+            vendor_id: Some(0x1d50),
+            product_id: Some(0x6018),
+            location_id: DeviceLocation {
+                bus: 2,
+                number: 24,
+                tree_positions: vec![2, 8],
+            },
+            ..Default::default()
+        };
+        let event = WatchEvent::Disconnected(device);
+        assert_eq!(
+            event.expand("{event}: {port_path} {vid}:{pid} '{name}' {serial}", |s| s
+                .to_string()),
+            "disconnected: 2-2.8 1d50:6018 'Black Magic Probe' "
+        );
+        assert_eq!(
+            event.expand("{{name}} {name", |s| s.replace(' ', "_")),
+            "{Black_Magic_Probe} {name"
+        );
+        assert!(TEMPLATE_FIELDS.iter().all(|f| event.field(f).is_some()));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("Black Magic"), "'Black Magic'");
+        assert_eq!(shell_quote("it's; rm -rf"), "'it'\\''s; rm -rf'");
+        assert_eq!(shell_var("CYME_NAME"), "\"$CYME_NAME\"");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_shell_command_env() {
+        let device = Device {
+            name: "$(touch pwned) `id` ; echo".into(),
+            serial_num: Some("'; exit 1 #".into()),
+            vendor_id: Some(0x1d50),
+            product_id: Some(0x6018),
+            ..Default::default()
+        };
+        let event = WatchEvent::Connected(device);
+        let command = event.shell_command("printf '%s|%s|%s' {name} {serial} {vid}");
+        assert!(!command.contains("pwned") && !command.contains("exit"));
+        let output = std::process::Command::new("sh")
+            .args(["-c", &command])
+            .envs(event.env_vars())
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "$(touch pwned) `id` ; echo|'; exit 1 #|1d50"
+        );
+    }
+}
@@ -0,0 +1,605 @@
+//! Redaction of identifying data from a [`SystemProfile`] so dumps are safe to attach to public issues; see [`Redaction`]
+//!
+//! ```
+//! use cyme::redact::Redaction;
+//! use cyme::profiler::read_json_dump;
+//!
+//! let mut spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+//! Redaction::default().apply(&mut spusb).unwrap();
+//! assert!(spusb.get_node("2-2.8").unwrap().serial_num.as_ref().unwrap() != "97B6A11D");
+//! ```
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::display::MaskSerial;
+use crate::error::{Error, ErrorKind, Result};
+use crate::profiler::gadget::Gadget;
+#[cfg(feature = "watch")]
+use crate::profiler::history::{DeviceHistory, HistoryRecord};
+use crate::profiler::serials::SerialReport;
+use crate::profiler::thunderbolt::ThunderboltRouter;
+use crate::profiler::{audit, lint, Bus, Device, SystemProfile};
+
+/// Replacement of matches of [`Redaction::patterns`]
+pub const REDACTED: &str = "[redacted]";
+
+/// What to redact and how; the 'redact' object of the config
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields, default)]
+pub struct Redaction {
+    /// How serials, addresses and volume UUIDs are masked
+    pub method: MaskSerial,
+    /// Salt for [`MaskSerial::Hash`]; without one a random salt is used for the run, since the hash is public and a short serial could otherwise be found by hashing guesses, so masks only match within one run
+    pub salt: Option<String>,
+    /// Mask serial numbers, paths containing them and Thunderbolt router UUIDs
+    pub serials: bool,
+    /// Mask the device half of Bluetooth adapter addresses, keeping the OUI
+    pub addresses: bool,
+    /// Replace the hostname and user name of this system, which appear in paths such as the names of virtual machine devices
+    pub hostnames: bool,
+    /// Replace the names of storage media volumes and mask their UUIDs
+    pub volumes: bool,
+    /// Regular expressions whose matches in any name, path or description are replaced with [`REDACTED`]; requires the 'regex_icon' feature
+    pub patterns: Vec<String>,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Redaction {
+            method: MaskSerial::Hash,
+            salt: None,
+            serials: true,
+            addresses: true,
+            hostnames: true,
+            volumes: true,
+            patterns: Vec::new(),
+        }
+    }
+}
+
+impl Redaction {
+    /// Only mask serials and addresses with `method`, as `--mask-serials` does
+    pub fn mask_serials(method: MaskSerial, salt: Option<String>) -> Self {
+        Redaction {
+            method,
+            salt,
+            serials: true,
+            addresses: true,
+            hostnames: false,
+            volumes: false,
+            patterns: Vec::new(),
+        }
+    }
+
+    /// Check the patterns are valid regular expressions, so later redactions will not error
+    ///
+    /// Returns the same errors as [`Redaction::apply`].
+    pub fn validate(&self) -> Result<()> {
+        Redactor::new(self).map(|_| ())
+    }
+
+    /// Redact the buses and devices of `spusb`
+    ///
+    /// Returns an [`ErrorKind::Parsing`] error if a pattern is not a valid regular expression or [`ErrorKind::Unsupported`] without the 'regex_icon' feature.
+    pub fn apply(&self, spusb: &mut SystemProfile) -> Result<()> {
+        let redactor = Redactor::new(self)?;
+        for bus in spusb.buses.iter_mut() {
+            redactor.bus(bus);
+        }
+        for domain in spusb.thunderbolt.iter_mut().flatten() {
+            domain.routers.iter_mut().for_each(|r| redactor.router(r));
+        }
+        if let Some(profile) = spusb.gadget.as_mut() {
+            profile
+                .controllers
+                .iter_mut()
+                .flat_map(|c| c.gadget.iter_mut())
+                .chain(profile.unbound.iter_mut())
+                .for_each(|g| redactor.gadget(g));
+        }
+        Ok(())
+    }
+
+    /// Redact `device` and recursively the devices connected to it if `recursive`
+    pub fn apply_device(&self, device: &mut Device, recursive: bool) -> Result<()> {
+        Redactor::new(self)?.device(device, recursive);
+        Ok(())
+    }
+
+    /// Redact the names and serials of history `records`, as `cyme history --events` outputs
+    #[cfg(feature = "watch")]
+    pub fn apply_history(&self, records: &mut [HistoryRecord]) -> Result<()> {
+        let redactor = Redactor::new(self)?;
+        for record in records.iter_mut() {
+            redactor.serial(&mut record.serial_num);
+            redactor.text(&mut record.name);
+            record
+                .manufacturer
+                .iter_mut()
+                .for_each(|s| redactor.text(s));
+        }
+        Ok(())
+    }
+
+    /// Redact the names and serials of the history `summary`, as `cyme history` outputs
+    #[cfg(feature = "watch")]
+    pub fn apply_history_summary(&self, summary: &mut [DeviceHistory]) -> Result<()> {
+        let redactor = Redactor::new(self)?;
+        for device in summary.iter_mut() {
+            redactor.serial(&mut device.serial_num);
+            redactor.text(&mut device.name);
+        }
+        Ok(())
+    }
+
+    /// Redact the names and messages of audit `findings` of `devices`; the serials of the devices are masked in the messages, such as those of cloned serials
+    pub fn apply_audit(&self, findings: &mut [audit::Finding], devices: &[&Device]) -> Result<()> {
+        let redactor = Redactor::new(self)?;
+        for finding in findings.iter_mut() {
+            for device in devices {
+                redactor.serial_in(&mut finding.message, device);
+            }
+            redactor.text(&mut finding.name);
+            redactor.text(&mut finding.message);
+        }
+        Ok(())
+    }
+
+    /// Redact the names and messages of lint `findings`
+    pub fn apply_lint(&self, findings: &mut [lint::Finding]) -> Result<()> {
+        let redactor = Redactor::new(self)?;
+        for finding in findings.iter_mut() {
+            redactor.text(&mut finding.name);
+            redactor.text(&mut finding.message);
+        }
+        Ok(())
+    }
+
+    /// Redact the serials and names of the devices in `report`
+    ///
+    /// The report should be made before redacting since masks other than [`MaskSerial::Hash`] do not keep serials which differ apart.
+    pub fn apply_serials(&self, report: &mut SerialReport) -> Result<()> {
+        let redactor = Redactor::new(self)?;
+        for duplicate in report.duplicates.iter_mut() {
+            if self.serials {
+                duplicate.serial = self.method.mask(&duplicate.serial, redactor.salt());
+            }
+        }
+        let devices = report
+            .duplicates
+            .iter_mut()
+            .flat_map(|d| d.devices.iter_mut())
+            .chain(report.missing.iter_mut());
+        for device in devices {
+            redactor.text(&mut device.name);
+        }
+        Ok(())
+    }
+
+    /// Redact the string descriptors of `descriptors` read by [`crate::profiler::export`] as file name and bytes
+    ///
+    /// The serial number string, the iSerialNumber of 'device.bin', is masked and the other strings have the hostname and user name and the patterns replaced.
+    pub fn apply_descriptors(&self, descriptors: &mut [(String, Vec<u8>)]) -> Result<()> {
+        let redactor = Redactor::new(self)?;
+        let serial_index = descriptors
+            .iter()
+            .find(|(name, _)| name == "device.bin")
+            .and_then(|(_, data)| data.get(16).copied())
+            .filter(|i| *i != 0);
+        for (name, data) in descriptors.iter_mut() {
+            let index = match name
+                .strip_prefix("string-")
+                .and_then(|n| n.strip_suffix(".bin"))
+                .and_then(|n| n.parse::<u8>().ok())
+            {
+                // string 0 is the languages
+                Some(i) if i != 0 => i,
+                _ => continue,
+            };
+            let utf16: Vec<u16> = data
+                .get(2..)
+                .unwrap_or_default()
+                .chunks_exact(2)
+                .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                .collect();
+            let mut string = Some(String::from_utf16_lossy(&utf16));
+            if Some(index) == serial_index {
+                redactor.serial(&mut string);
+            }
+            let mut string = string.unwrap_or_default();
+            redactor.text(&mut string);
+            // bLength is a byte so at most 126 UTF-16 code units
+            let encoded: Vec<u16> = string.encode_utf16().take(126).collect();
+            if encoded != utf16 {
+                *data = vec![2 + encoded.len() as u8 * 2, 0x03];
+                data.extend(encoded.iter().flat_map(|c| c.to_le_bytes()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// [`Redaction`] with the patterns compiled and the words of this system to replace
+struct Redactor<'a> {
+    redaction: &'a Redaction,
+    #[cfg(feature = "regex_icon")]
+    patterns: Vec<regex::Regex>,
+    words: Vec<(String, &'static str)>,
+}
+
+impl<'a> Redactor<'a> {
+    fn new(redaction: &'a Redaction) -> Result<Self> {
+        #[cfg(feature = "regex_icon")]
+        let patterns = redaction
+            .patterns
+            .iter()
+            .map(|p| {
+                regex::Regex::new(p).map_err(|e| {
+                    Error::new(
+                        ErrorKind::Parsing,
+                        &format!("Invalid redact pattern '{}': {}", p, e),
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        #[cfg(not(feature = "regex_icon"))]
+        if !redaction.patterns.is_empty() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "Redact patterns require the 'regex_icon' feature",
+            ));
+        }
+
+        let words = if redaction.hostnames {
+            system_words()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Redactor {
+            redaction,
+            #[cfg(feature = "regex_icon")]
+            patterns,
+            words,
+        })
+    }
+
+    fn salt(&self) -> &str {
+        self.redaction.salt.as_deref().unwrap_or_else(|| run_salt())
+    }
+
+    /// Mask `serial` if serials are redacted
+    fn serial(&self, serial: &mut Option<String>) {
+        if let Some(serial) = serial.as_mut().filter(|_| self.redaction.serials) {
+            *serial = self.redaction.method.mask(serial, self.salt());
+        }
+    }
+
+    /// Mask the serial of `device` where it appears in `s`; short serials such as '1' are not since they would replace unrelated parts
+    fn serial_in(&self, s: &mut String, device: &Device) {
+        if let Some(serial) = device
+            .serial_num
+            .as_ref()
+            .filter(|s| self.redaction.serials && s.len() >= 4)
+        {
+            *s = s.replace(
+                serial.as_str(),
+                &self.redaction.method.mask(serial, self.salt()),
+            );
+        }
+    }
+
+    /// Replace the system words and pattern matches in `s`
+    fn text(&self, s: &mut String) {
+        for (word, with) in self.words.iter() {
+            *s = replace_word(s, word, with);
+        }
+        #[cfg(feature = "regex_icon")]
+        for pattern in self.patterns.iter() {
+            if let std::borrow::Cow::Owned(r) = pattern.replace_all(s, REDACTED) {
+                *s = r;
+            }
+        }
+    }
+
+    fn bus(&self, bus: &mut Bus) {
+        self.text(&mut bus.name);
+        self.text(&mut bus.host_controller);
+        bus.host_controller_vendor
+            .iter_mut()
+            .chain(bus.host_controller_device.iter_mut())
+            .for_each(|s| self.text(s));
+        for device in bus.devices.iter_mut().flatten() {
+            self.device(device, true);
+        }
+    }
+
+    fn router(&self, router: &mut ThunderboltRouter) {
+        if let Some(uuid) = router.unique_id.as_mut().filter(|_| self.redaction.serials) {
+            *uuid = self.redaction.method.mask(uuid, self.salt());
+        }
+        router
+            .vendor_name
+            .iter_mut()
+            .chain(router.device_name.iter_mut())
+            .for_each(|s| self.text(s));
+        router.routers.iter_mut().for_each(|r| self.router(r));
+    }
+
+    fn gadget(&self, gadget: &mut Gadget) {
+        if let Some(serial) = gadget
+            .serial_num
+            .as_mut()
+            .filter(|_| self.redaction.serials)
+        {
+            *serial = self.redaction.method.mask(serial, self.salt());
+        }
+        gadget
+            .manufacturer
+            .iter_mut()
+            .chain(gadget.product.iter_mut())
+            .for_each(|s| self.text(s));
+    }
+
+    fn device(&self, device: &mut Device, recursive: bool) {
+        let salt = self.salt();
+        let method = &self.redaction.method;
+
+        // root hub serials are the host controller PCI address which is also in the path
+        let in_paths = !device.is_root_hub();
+        if let Some(serial) = device
+            .serial_num
+            .as_mut()
+            .filter(|_| self.redaction.serials)
+        {
+            let masked = method.mask(serial, salt);
+            // short serials such as '1' would replace unrelated parts of paths
+            if in_paths && serial.len() >= 4 {
+                if let Some(extra) = device.extra.as_mut() {
+                    let paths = extra.syspath.iter_mut().chain(
+                        extra
+                            .configurations
+                            .iter_mut()
+                            .flat_map(|c| c.interfaces.iter_mut())
+                            .flat_map(|i| i.syspath.as_mut()),
+                    );
+                    for path in paths {
+                        *path = path.replace(serial.as_str(), &masked);
+                    }
+                }
+            }
+            *serial = masked;
+        }
+
+        self.text(&mut device.name);
+        device
+            .manufacturer
+            .iter_mut()
+            .chain(device.serial_num.iter_mut())
+            .for_each(|s| self.text(s));
+
+        if let Some(extra) = device.extra.as_mut() {
+            extra
+                .syspath
+                .iter_mut()
+                .chain(extra.vendor.iter_mut())
+                .chain(extra.product_name.iter_mut())
+                .for_each(|s| self.text(s));
+            for config in extra.configurations.iter_mut() {
+                self.text(&mut config.name);
+                for interface in config.interfaces.iter_mut() {
+                    interface
+                        .name
+                        .iter_mut()
+                        .chain(interface.syspath.iter_mut())
+                        .for_each(|s| self.text(s));
+                    if let Some(bluetooth) = interface.bluetooth.as_mut() {
+                        if let Some(address) = bluetooth
+                            .address
+                            .as_mut()
+                            .filter(|_| self.redaction.addresses)
+                        {
+                            *address = method.mask_address(address, salt);
+                        }
+                        self.text(&mut bluetooth.name);
+                    }
+                }
+            }
+        }
+
+        for media in device.media.iter_mut().flatten() {
+            self.text(&mut media.name);
+            for volume in media.volumes.iter_mut() {
+                if self.redaction.volumes {
+                    volume.name = REDACTED.to_string();
+                    if let Some(uuid) = volume.volume_uuid.as_mut() {
+                        *uuid = method.mask(uuid, salt);
+                    }
+                }
+                self.text(&mut volume.name);
+            }
+        }
+
+        if recursive {
+            for d in device.devices.iter_mut().flatten() {
+                self.device(d, recursive);
+            }
+        }
+    }
+}
+
+/// Hostname and user name of this system with their replacements
+fn system_words() -> Vec<(String, &'static str)> {
+    let hostname = std::fs::read_to_string("/etc/hostname")
+        .ok()
+        .map(|h| h.trim().to_string())
+        .or_else(|| std::env::var("HOSTNAME").ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok());
+    let user = std::env::var("USER")
+        .ok()
+        .or_else(|| std::env::var("USERNAME").ok());
+
+    hostname
+        .map(|h| (h, "[hostname]"))
+        .into_iter()
+        .chain(user.map(|u| (u, "[user]")))
+        // very short words would replace parts of unrelated words
+        .filter(|(w, _)| w.len() >= 2)
+        .collect()
+}
+
+/// Random salt for [`MaskSerial::Hash`] when [`Redaction::salt`] is not set; the same for every redaction of the run so devices still match between outputs
+fn run_salt() -> &'static str {
+    static SALT: OnceLock<String> = OnceLock::new();
+    SALT.get_or_init(|| {
+        rand::thread_rng()
+            .sample_iter(Alphanumeric)
+            .take(32)
+            .map(char::from)
+            .collect()
+    })
+}
+
+/// Replace `word` in `s` with `with` where it is not part of a longer word
+fn replace_word(s: &str, word: &str, with: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find(word) {
+        let before = rest[..i].chars().next_back();
+        let after = rest[i + word.len()..].chars().next();
+        ret.push_str(&rest[..i]);
+        if before.is_some_and(char::is_alphanumeric) || after.is_some_and(char::is_alphanumeric) {
+            ret.push_str(word);
+        } else {
+            ret.push_str(with);
+        }
+        rest = &rest[i + word.len()..];
+    }
+    ret.push_str(rest);
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_word() {
+        assert_eq!(
+            replace_word("Printer (/Users/john/Parallels/john.pvm)", "john", "[user]"),
+            "Printer (/Users/[user]/Parallels/[user].pvm)"
+        );
+        assert_eq!(
+            replace_word("johnny john", "john", "[user]"),
+            "johnny [user]"
+        );
+    }
+
+    #[test]
+    fn test_redact_descriptors() {
+        // iSerialNumber 3
+        let mut device = vec![0u8; 18];
+        device[16] = 3;
+        let string = |s: &str| {
+            let mut ret = vec![2 + s.len() as u8 * 2, 0x03];
+            ret.extend(s.encode_utf16().flat_map(|c| c.to_le_bytes()));
+            ret
+        };
+        let mut descriptors = vec![
+            (String::from("device.bin"), device),
+            (String::from("string-0.bin"), vec![0x04, 0x03, 0x09, 0x04]),
+            (String::from("string-2.bin"), string("Black Magic Probe")),
+            (String::from("string-3.bin"), string("97B6A11D")),
+        ];
+        let redaction = Redaction {
+            salt: Some(String::from("salt")),
+            hostnames: false,
+            ..Default::default()
+        };
+        redaction.apply_descriptors(&mut descriptors).unwrap();
+        assert_eq!(descriptors[1].1, vec![0x04, 0x03, 0x09, 0x04]);
+        assert_eq!(descriptors[2].1, string("Black Magic Probe"));
+        assert_eq!(
+            descriptors[3].1,
+            string(&MaskSerial::Hash.mask("97B6A11D", "salt"))
+        );
+    }
+
+    #[test]
+    fn test_redact_serial_report() {
+        let mut spusb =
+            crate::profiler::read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let probe = spusb.get_node("2-2.8").unwrap().clone();
+        // a clone of the probe on another port
+        spusb.buses[0]
+            .devices
+            .get_or_insert_with(Vec::new)
+            .push(probe);
+        let mut report = crate::profiler::serials::report(spusb.iter());
+        let redaction = Redaction {
+            salt: Some(String::from("salt")),
+            hostnames: false,
+            ..Default::default()
+        };
+        redaction.apply_serials(&mut report).unwrap();
+        assert_eq!(
+            report.duplicates[0].serial,
+            MaskSerial::Hash.mask("97B6A11D", "salt")
+        );
+    }
+
+    #[test]
+    fn test_redact_profile() {
+        let mut spusb =
+            crate::profiler::read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let serial = spusb.get_node("2-2.8").unwrap().serial_num.clone().unwrap();
+        let redaction = Redaction {
+            salt: Some(String::from("salt")),
+            hostnames: false,
+            ..Default::default()
+        };
+        redaction.apply(&mut spusb).unwrap();
+        let device = spusb.get_node("2-2.8").unwrap();
+        assert_eq!(
+            device.serial_num,
+            Some(MaskSerial::Hash.mask(&serial, "salt"))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "regex_icon")]
+    fn test_redact_patterns() {
+        let mut spusb =
+            crate::profiler::read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let redaction = Redaction {
+            hostnames: false,
+            patterns: vec![String::from("Black Magic")],
+            ..Default::default()
+        };
+        redaction.apply(&mut spusb).unwrap();
+        assert!(spusb.get_node("2-2.8").unwrap().name.starts_with(REDACTED));
+    }
+
+    #[test]
+    fn test_redact_default_salt() {
+        let redaction = Redaction {
+            hostnames: false,
+            ..Default::default()
+        };
+        let mask = |serial: &str| {
+            let mut device = Device {
+                serial_num: Some(serial.to_string()),
+                ..Default::default()
+            };
+            redaction.apply_device(&mut device, false).unwrap();
+            device.serial_num.unwrap()
+        };
+        let masked = mask("97B6A11D");
+        // an unsalted hash of guesses would find the serial
+        assert_ne!(masked, MaskSerial::Hash.mask("97B6A11D", ""));
+        // but the device still has the same mask within the run
+        assert_eq!(masked, mask("97B6A11D"));
+    }
+}
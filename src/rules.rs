@@ -0,0 +1,401 @@
+//! Generate udev rules granting users access to USB devices without root on Linux; see [`UdevRule`]
+//!
+//! ```
+//! use cyme::rules::UdevRule;
+//!
+//! let rule = UdevRule::new(0x1d50, Some(0x6018)).with_group("plugdev");
+//! assert_eq!(
+//!     rule.to_string(),
+//!     "SUBSYSTEM==\"usb\", ATTR{idVendor}==\"1d50\", ATTR{idProduct}==\"6018\", MODE=\"0660\", GROUP=\"plugdev\"\n"
+//! );
+//! ```
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, ErrorKind, Result};
+use crate::profiler::{Device, Filter, SystemProfile};
+use crate::usb::BaseClass;
+
+/// Directory system udev rules are installed to
+pub const RULES_DIR: &str = "/etc/udev/rules.d";
+
+/// Drivers of usb-serial adapters which create a ttyUSB node rather than ttyACM
+const USB_SERIAL_DRIVERS: [&str; 6] = [
+    "ftdi_sio",
+    "cp210x",
+    "ch341",
+    "pl2303",
+    "cdc_acm",
+    "usb_serial_simple",
+];
+
+/// A udev rule for a USB device and the device nodes of its interfaces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UdevRule {
+    /// Vendor ID to match
+    pub vendor_id: u16,
+    /// Product ID to match; all products of the vendor if `None`
+    pub product_id: Option<u16>,
+    /// Serial number to match so only one device is given access
+    pub serial: Option<String>,
+    /// Name of the device for the comment
+    pub name: Option<String>,
+    /// Subsystems of interface device nodes to include besides usb: 'tty' for serial ports and 'hidraw' for HID
+    pub subsystems: Vec<&'static str>,
+    /// File mode of the device nodes
+    pub mode: String,
+    /// Group given access; the user at the seat is given access with the uaccess tag if `None`
+    pub group: Option<String>,
+}
+
+impl UdevRule {
+    /// Rule for the usb device node of `vendor_id` and `product_id` with mode 0660 and uaccess
+    pub fn new(vendor_id: u16, product_id: Option<u16>) -> Self {
+        UdevRule {
+            vendor_id,
+            product_id,
+            serial: None,
+            name: None,
+            subsystems: Vec::new(),
+            mode: String::from("0660"),
+            group: None,
+        }
+    }
+
+    /// Rule for `device` including the tty and hidraw subsystems if it has serial or HID interfaces; `None` if it has no vendor ID
+    ///
+    /// Interfaces are only known if the device was profiled with extra.
+    pub fn from_device(device: &Device) -> Option<Self> {
+        let mut rule = UdevRule::new(device.vendor_id?, device.product_id);
+        rule.name = Some(device.name.to_owned()).filter(|n| !n.is_empty());
+
+        let interfaces = device
+            .extra
+            .iter()
+            .flat_map(|e| e.configurations.iter())
+            .flat_map(|c| c.interfaces.iter());
+        for interface in interfaces {
+            let subsystem = match interface.class {
+                BaseClass::CdcCommunications | BaseClass::CdcData => Some("tty"),
+                BaseClass::Hid => Some("hidraw"),
+                _ if interface
+                    .driver
+                    .as_deref()
+                    .is_some_and(|d| USB_SERIAL_DRIVERS.contains(&d)) =>
+                {
+                    Some("tty")
+                }
+                _ => None,
+            };
+            if let Some(s) = subsystem.filter(|s| !rule.subsystems.contains(s)) {
+                rule.subsystems.push(s);
+            }
+        }
+
+        Some(rule)
+    }
+
+    /// Give `group` access rather than the user at the seat
+    pub fn with_group(mut self, group: &str) -> Self {
+        self.group = Some(group.to_string());
+        self
+    }
+
+    /// Set the file `mode` of the device nodes, 0660 by default
+    pub fn with_mode(mut self, mode: &str) -> Self {
+        self.mode = mode.to_string();
+        self
+    }
+
+    /// Only match the device with `serial`
+    pub fn with_serial(mut self, serial: &str) -> Self {
+        self.serial = Some(serial.to_string());
+        self
+    }
+
+    /// File name for the rule in [`RULES_DIR`]; numbered before 73-seat-late.rules, which applies the uaccess tag
+    ///
+    /// The serial is included so that installing a rule for one device does not replace that of another with the same VID:PID; characters other than ASCII alphanumerics, '-' and '.' are replaced with '_' to keep it a file name.
+    ///
+    /// ```
+    /// use cyme::rules::UdevRule;
+    ///
+    /// let rule = UdevRule::new(0x1d50, Some(0x6018));
+    /// assert_eq!(rule.file_name(), "70-cyme-1d50-6018.rules");
+    /// assert_eq!(rule.with_serial("97B6/A11D").file_name(), "70-cyme-1d50-6018-97B6_A11D.rules");
+    /// ```
+    pub fn file_name(&self) -> String {
+        let mut name = format!("70-cyme-{:04x}", self.vendor_id);
+        if let Some(pid) = self.product_id {
+            name.push_str(&format!("-{:04x}", pid));
+        }
+        if let Some(serial) = self.serial.as_ref() {
+            name.push('-');
+            name.extend(serial.chars().map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '.' => c,
+                _ => '_',
+            }));
+        }
+        name.push_str(".rules");
+        name
+    }
+
+    /// Check the values can be written to a rule: udev has no escaping within the quotes of a value so a '"' or '\\' would end or change it and a control character such as a newline would start a new rule
+    ///
+    /// The serial is also matched as a udev glob so '*', '?', '[', ']' and '|' would match other devices' serials; they are rejected rather than letting the rule grant access to more than the device.
+    ///
+    /// The serial and name come from the device so must be checked before the rule is installed. The name is only a comment so control characters are replaced when displayed rather than rejected.
+    ///
+    /// ```
+    /// use cyme::rules::UdevRule;
+    ///
+    /// assert!(UdevRule::new(0x1d50, Some(0x6018)).with_serial("97B6A11D").validate().is_ok());
+    /// assert!(UdevRule::new(0x1d50, Some(0x6018)).with_serial("\", RUN+=\"/bin/sh").validate().is_err());
+    /// assert!(UdevRule::new(0x1d50, Some(0x6018)).with_serial("*").validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        for (key, value) in [
+            ("serial", self.serial.as_deref()),
+            ("group", self.group.as_deref()),
+            ("mode", Some(self.mode.as_str())),
+        ] {
+            if let Some(v) =
+                value.filter(|v| v.chars().any(|c| c == '"' || c == '\\' || c.is_control()))
+            {
+                return Err(Error::new(
+                    ErrorKind::InvalidArg,
+                    &format!(
+                        "{} {:?} cannot be written to a udev rule as it has a quote, backslash or control character",
+                        key, v
+                    ),
+                ));
+            }
+        }
+        if let Some(serial) = self
+            .serial
+            .as_deref()
+            .filter(|s| s.contains(['*', '?', '[', ']', '|']))
+        {
+            return Err(Error::new(
+                ErrorKind::InvalidArg,
+                &format!(
+                    "serial {:?} cannot be written to a udev rule as it has a glob character: '*', '?', '[', ']' or '|'",
+                    serial
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Match and assignment keys of a line; `attr` is ATTR for the usb device itself or ATTRS for a child node
+    fn keys(&self, subsystem: &str, attr: &str) -> String {
+        let mut keys = vec![
+            format!("SUBSYSTEM==\"{}\"", subsystem),
+            format!("{}{{idVendor}}==\"{:04x}\"", attr, self.vendor_id),
+        ];
+        if let Some(pid) = self.product_id {
+            keys.push(format!("{}{{idProduct}}==\"{:04x}\"", attr, pid));
+        }
+        if let Some(serial) = self.serial.as_ref() {
+            keys.push(format!("{}{{serial}}==\"{}\"", attr, serial));
+        }
+        keys.push(format!("MODE=\"{}\"", self.mode));
+        match self.group.as_ref() {
+            Some(group) => keys.push(format!("GROUP=\"{}\"", group)),
+            None => keys.push(String::from("TAG+=\"uaccess\"")),
+        }
+        keys.join(", ")
+    }
+}
+
+impl fmt::Display for UdevRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(name) = self.name.as_ref() {
+            // a newline would end the comment
+            let name: String = name
+                .chars()
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .collect();
+            writeln!(f, "# {}; generated by cyme", name)?;
+        }
+        writeln!(f, "{}", self.keys("usb", "ATTR"))?;
+        for subsystem in &self.subsystems {
+            writeln!(f, "{}", self.keys(subsystem, "ATTRS"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Rules for devices in `spusb` matching `filter` for `cyme rules`, checked with [`UdevRule::validate`] so they can be written and installed with [`install_rules`]
+///
+/// Each rule has `mode` and is for `group` if supplied, matching the device serial if `match_serial`. If no device matches, the rule is for the usb device node of the VID:PID of `filter` only.
+pub fn run(
+    spusb: &SystemProfile,
+    filter: &Filter,
+    mode: &str,
+    group: Option<&str>,
+    match_serial: bool,
+) -> Result<Vec<UdevRule>> {
+    let configure = |rule: UdevRule| {
+        let rule = rule.with_mode(mode);
+        match group {
+            Some(group) => rule.with_group(group),
+            None => rule,
+        }
+    };
+    let mut rules: Vec<UdevRule> = Vec::new();
+    for device in spusb.iter().filter(|d| filter.is_match(d)) {
+        let mut rule = match UdevRule::from_device(device) {
+            Some(r) => configure(r),
+            None => continue,
+        };
+        if match_serial {
+            match device.serial_num.as_ref() {
+                Some(serial) => rule = rule.with_serial(serial),
+                None => log::warn!("{} has no serial number to match", device),
+            }
+        }
+        if !rules.iter().any(|r| r.file_name() == rule.file_name()) {
+            rules.push(rule);
+        }
+    }
+
+    // not connected so only the usb node from the vidpid
+    if rules.is_empty() {
+        match (filter.vid, filter.pid) {
+            (Some(vid), pid) => {
+                log::info!("No matching device connected; rule is for the usb device node only");
+                rules.push(configure(UdevRule::new(vid, pid)));
+            }
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    "No matching devices to generate rules for; use --vidpid for a device not connected",
+                ))
+            }
+        }
+    }
+
+    rules.iter().try_for_each(UdevRule::validate)?;
+
+    Ok(rules)
+}
+
+/// Write `rules` to [`RULES_DIR`] and reload udev, returning the paths written
+///
+/// Every rule is checked with [`UdevRule::validate`] before any are written. Requires root.
+pub fn install_rules(rules: &[UdevRule]) -> Result<Vec<PathBuf>> {
+    rules.iter().try_for_each(UdevRule::validate)?;
+
+    let mut paths = Vec::new();
+    for rule in rules {
+        let path = Path::new(RULES_DIR).join(rule.file_name());
+        std::fs::write(&path, rule.to_string()).map_err(|e| {
+            Error::new(
+                ErrorKind::Io,
+                &format!("Failed to write {}, requires root: {}", path.display(), e),
+            )
+        })?;
+        paths.push(path);
+    }
+
+    // failing to reload is logged since the rules are installed and apply on the next boot
+    for args in [
+        ["control", "--reload-rules"].as_slice(),
+        ["trigger"].as_slice(),
+    ] {
+        match std::process::Command::new("udevadm").args(args).status() {
+            Ok(s) if !s.success() => log::warn!("udevadm {} exited with {}", args.join(" "), s),
+            Ok(_) => (),
+            Err(e) => {
+                log::error!("Failed to run udevadm {}: {}", args.join(" "), e);
+                break;
+            }
+        }
+    }
+
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::read_json_dump;
+
+    #[test]
+    fn test_rule_from_device() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let device = spusb.get_node("2-2.8").unwrap();
+        let rule = UdevRule::from_device(device)
+            .unwrap()
+            .with_serial("97B6A11D");
+        assert_eq!(rule.subsystems, vec!["tty"]);
+        assert_eq!(rule.file_name(), "70-cyme-1d50-6018-97B6A11D.rules");
+        assert_eq!(
+            rule.to_string(),
+            "# Black Magic Probe  v1.8.2; generated by cyme\n\
+            SUBSYSTEM==\"usb\", ATTR{idVendor}==\"1d50\", ATTR{idProduct}==\"6018\", ATTR{serial}==\"97B6A11D\", MODE=\"0660\", TAG+=\"uaccess\"\n\
+            SUBSYSTEM==\"tty\", ATTRS{idVendor}==\"1d50\", ATTRS{idProduct}==\"6018\", ATTRS{serial}==\"97B6A11D\", MODE=\"0660\", TAG+=\"uaccess\"\n"
+        );
+    }
+
+    #[test]
+    fn test_rule_from_untrusted_device() {
+        let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let mut device = spusb.get_node("2-2.8").unwrap().clone();
+        device.name = String::from("Probe\nSUBSYSTEM==\"usb\", MODE=\"0666\"");
+        let rule = UdevRule::from_device(&device).unwrap();
+        assert!(rule.validate().is_ok());
+        // the name stays in the comment line
+        assert_eq!(rule.to_string().lines().count(), 2 + rule.subsystems.len());
+        assert!(rule.to_string().starts_with("# Probe SUBSYSTEM"));
+
+        for serial in [
+            "97B6\"A11D",
+            "97B6\\A11D",
+            "97B6\nA11D",
+            "*",
+            "97B6?11D",
+            "97B6[A-Z]11D",
+            "97B6A11D|*",
+        ] {
+            assert!(rule.clone().with_serial(serial).validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_run_rules() {
+        let mut spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+        let filter = |vid, name: Option<&str>| Filter {
+            vid: Some(vid),
+            name: name.map(String::from),
+            ..Default::default()
+        };
+        let rules = run(&spusb, &filter(0x1d50, None), "0660", None, true).unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].serial.as_deref(), Some("97B6A11D"));
+        // not connected is for the usb node of the vidpid
+        let rules = run(&spusb, &filter(0xffff, None), "0660", None, false).unwrap();
+        assert_eq!(rules, vec![UdevRule::new(0xffff, None)]);
+        let err = run(
+            &spusb,
+            &Filter {
+                name: Some(String::from("Not Connected")),
+                ..Default::default()
+            },
+            "0660",
+            None,
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        // checked before returning so they are never printed or installed
+        spusb.get_node_mut("2-2.8").unwrap().serial_num = Some(String::from("\", RUN+=\"x"));
+        let err = run(&spusb, &filter(0x1d50, None), "0660", None, true).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidArg);
+        let rule = UdevRule::new(0x1d50, Some(0x6018)).with_serial("\", RUN+=\"x");
+        let err = install_rules(&[rule]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidArg);
+    }
+}
@@ -0,0 +1,497 @@
+//! HTTP server of the live [`SystemProfile`] as JSON for dashboards and remote tooling without SSH; see [`Server`]
+//!
+//! | Endpoint | Response |
+//! | --- | --- |
+//! | `GET /devices` | All devices as a flattened list, without children |
+//! | `GET /devices/{port_path}` | Device at the port path with the devices connected to it |
+//! | `GET /buses` | Buses with their device tree, like `cyme --json --tree` |
+//! | `GET /events` | [Server-sent events](https://html.spec.whatwg.org/multipage/server-sent-events.html) of devices connecting and disconnecting: `event:` is 'connected' or 'disconnected' and `data:` the device |
+//!
+//! It is a minimal HTTP/1.1 server on std networking with a thread per connection rather than pulling in an async runtime. Each connection is closed after the response; clients must send the request within [`READ_TIMEOUT`] and the request line and headers are limited in size so an idle or endless client cannot hold a thread. Connections over [`MAX_CONNECTIONS`], or `/events` streams over [`MAX_EVENT_STREAMS`], get a 503 response so clients cannot exhaust the threads.
+//!
+//! The profile includes serial numbers and names, so by default the server only listens on localhost and sends no CORS header. Listening on all interfaces with '0.0.0.0:8080' exposes the devices to the network and [`Server::with_cors`] to other web origins; there is no authentication.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use cyme::serve::Server;
+//!
+//! Server::bind(":8080")
+//!     .unwrap()
+//!     .with_interval(Duration::from_secs(2))
+//!     .run()
+//!     .unwrap();
+//! ```
+use serde::Serialize;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::profiler::{Device, ProfilerBuilder, SharedProfile, SystemProfile};
+use crate::redact::Redaction;
+
+/// Endpoints listed by `GET /`
+const ENDPOINTS: [&str; 4] = ["/devices", "/devices/{port_path}", "/buses", "/events"];
+/// Interval of comments sent to idle `/events` clients, which also detects those disconnected
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+/// Time a client has to send each read of the request and to accept each write of the response
+pub const READ_TIMEOUT: Duration = Duration::from_secs(10);
+/// Maximum length of the request line and of each header line in bytes
+const MAX_LINE: usize = 8 * 1024;
+/// Maximum number of header lines
+const MAX_HEADERS: usize = 100;
+/// Maximum number of connections handled at once, including `/events` streams
+pub const MAX_CONNECTIONS: usize = 64;
+/// Maximum number of `/events` streams at once, which stay open until the client disconnects
+pub const MAX_EVENT_STREAMS: usize = 16;
+
+/// Response to a request; see [`response`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    /// HTTP status code
+    pub status: u16,
+    /// JSON body
+    pub body: String,
+}
+
+impl Response {
+    fn json<T: Serialize + ?Sized>(value: &T) -> Self {
+        match serde_json::to_string_pretty(value) {
+            Ok(body) => Response { status: 200, body },
+            Err(e) => Response::error(500, &e.to_string()),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Response {
+            status,
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W, cors: Option<&str>) -> io::Result<()> {
+        write!(
+            w,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+            self.status,
+            self.reason(),
+            self.body.len(),
+            cors_header(cors),
+            self.body
+        )?;
+        w.flush()
+    }
+}
+
+/// Access-Control-Allow-Origin header line for `cors` or nothing
+fn cors_header(cors: Option<&str>) -> String {
+    cors.map(|origin| format!("Access-Control-Allow-Origin: {}\r\n", origin))
+        .unwrap_or_default()
+}
+
+/// [`Response`] of `profile` to a `method` request of `path`, other than the `/events` stream
+///
+/// ```
+/// use cyme::profiler::read_json_dump;
+/// use cyme::serve::response;
+///
+/// let spusb = read_json_dump("./tests/data/cyme_libusb_linux_tree.json").unwrap();
+/// assert_eq!(response(&spusb, "GET", "/devices/2-2.8").status, 200);
+/// assert_eq!(response(&spusb, "GET", "/devices/9-9").status, 404);
+/// assert_eq!(response(&spusb, "POST", "/devices").status, 405);
+/// ```
+pub fn response(profile: &SystemProfile, method: &str, path: &str) -> Response {
+    if method != "GET" {
+        return Response::error(405, &format!("{} not allowed, only GET", method));
+    }
+
+    match path.trim_end_matches('/') {
+        "" => Response::json(&serde_json::json!({ "endpoints": ENDPOINTS })),
+        "/devices" => Response::json(
+            &profile
+                .iter()
+                .map(|d| Device {
+                    devices: None,
+                    ..d.clone()
+                })
+                .collect::<Vec<Device>>(),
+        ),
+        "/buses" => Response::json(&profile.buses),
+        p => match p.strip_prefix("/devices/") {
+            Some(port_path) => match profile.get_node(port_path) {
+                Some(d) => Response::json(d),
+                None => Response::error(404, &format!("No device at port path {}", port_path)),
+            },
+            None => Response::error(404, &format!("No endpoint {}", p)),
+        },
+    }
+}
+
+/// Serves the [`SystemProfile`] over HTTP, profiling it again each interval; see the [module docs](self)
+#[derive(Debug)]
+pub struct Server {
+    listener: TcpListener,
+    builder: ProfilerBuilder,
+    interval: Duration,
+    options: Options,
+}
+
+/// How responses are written, shared by the connection threads
+#[derive(Debug, Clone, Default)]
+struct Options {
+    cors: Option<String>,
+    redaction: Option<Redaction>,
+    /// Number of open `/events` streams
+    event_streams: Arc<AtomicUsize>,
+}
+
+/// Taken count of a limited resource such as connections, given back when dropped
+struct Slot(Arc<AtomicUsize>);
+
+impl Slot {
+    /// Take one of `count` if fewer than `max` are taken
+    fn take(count: &Arc<AtomicUsize>, max: usize) -> Option<Self> {
+        count
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < max).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| Slot(count.clone()))
+    }
+}
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Server {
+    /// Listen on `addr` such as '127.0.0.1:8080'; ':8080' listens on localhost and '0.0.0.0:8080' on all interfaces
+    pub fn bind(addr: &str) -> Result<Self> {
+        let addr = if addr.starts_with(':') {
+            format!("127.0.0.1{}", addr)
+        } else {
+            addr.to_string()
+        };
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+            builder: ProfilerBuilder::new(),
+            interval: Duration::from_secs(1),
+            options: Options::default(),
+        })
+    }
+
+    /// Allow web pages from `origin`, or '*' for any, to read the responses with the Access-Control-Allow-Origin header; none are by default
+    pub fn with_cors(mut self, origin: &str) -> Self {
+        self.options.cors = Some(origin.to_string());
+        self
+    }
+
+    /// Redact the devices of every response and event with `redaction`, as `--redact` does
+    ///
+    /// Errors if the patterns of `redaction` are not valid; see [`Redaction::validate`].
+    pub fn with_redaction(mut self, redaction: Redaction) -> Result<Self> {
+        redaction.validate()?;
+        self.options.redaction = Some(redaction);
+        Ok(self)
+    }
+
+    /// Profile with `builder`, such as to include extra data
+    pub fn with_builder(mut self, builder: ProfilerBuilder) -> Self {
+        self.builder = builder;
+        self
+    }
+
+    /// Time between each profile of the system
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Address the server is listening on
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    /// Serve the profile of the system, only returning if the first profile fails
+    ///
+    /// The profile is updated each interval with [`SharedProfile::refresh`] so only devices which have connected are profiled.
+    pub fn run(self) -> Result<()> {
+        let builder = self.builder.clone();
+        let shared = SharedProfile::new(builder.profile()?);
+        let updater = shared.clone();
+        let interval = self.interval;
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            if let Err(e) = updater.refresh(&builder) {
+                log::warn!("Failed to profile system: {}", e);
+            }
+        });
+        self.run_shared(shared)
+    }
+
+    /// Serve the [`SystemProfile`] returned by `profile` each interval, such as a json dump, only returning if the first profile fails
+    pub fn run_with<F>(self, mut profile: F) -> Result<()>
+    where
+        F: FnMut() -> Result<SystemProfile> + Send + 'static,
+    {
+        let shared = SharedProfile::new(profile()?);
+        let updater = shared.clone();
+        let interval = self.interval;
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            match profile() {
+                Ok(p) => {
+                    updater.replace(p);
+                }
+                Err(e) => log::warn!("Failed to profile system: {}", e),
+            }
+        });
+        self.run_shared(shared)
+    }
+
+    /// Serve `shared` as it is updated elsewhere, with `/events` following its changes
+    ///
+    /// Errors accepting a connection, such as the client resetting it, are logged and the server carries on.
+    pub fn run_shared(self, shared: SharedProfile) -> Result<()> {
+        let connections = Arc::new(AtomicUsize::new(0));
+        for stream in self.listener.incoming() {
+            let mut stream = match stream {
+                Ok(s) => s,
+                Err(e) => {
+                    log::warn!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            };
+            let Some(slot) = Slot::take(&connections, MAX_CONNECTIONS) else {
+                log::warn!("Rejecting connection, {} already open", MAX_CONNECTIONS);
+                reject(&mut stream, self.options.cors.as_deref());
+                continue;
+            };
+            let shared = shared.clone();
+            let options = self.options.clone();
+            std::thread::spawn(move || {
+                let _slot = slot;
+                if let Err(e) = handle(stream, &shared, &options) {
+                    log::debug!("Connection closed: {}", e);
+                }
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Write a 503 response to `stream` over a connection limit, ignoring errors since it is closed after
+fn reject(stream: &mut TcpStream, cors: Option<&str>) {
+    let _ = stream.set_write_timeout(Some(READ_TIMEOUT));
+    let _ = Response::error(503, "Too many connections, try again later").write_to(stream, cors);
+}
+
+/// Read a line of `reader` into `buf`, erroring with [`io::ErrorKind::InvalidData`] if it is longer than [`MAX_LINE`]
+fn read_line<R: BufRead>(reader: &mut R, buf: &mut String) -> io::Result<usize> {
+    let n = reader.take(MAX_LINE as u64).read_line(buf)?;
+    if n == MAX_LINE && !buf.ends_with('\n') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "line too long"));
+    }
+    Ok(n)
+}
+
+/// Read the request line of `reader` and skip its headers, which are not used but must be read before responding
+fn read_request<R: BufRead>(reader: &mut R) -> io::Result<String> {
+    let mut request = String::new();
+    read_line(reader, &mut request)?;
+    let mut header = String::new();
+    for _ in 0..MAX_HEADERS {
+        header.clear();
+        if read_line(reader, &mut header)? == 0 || header.trim().is_empty() {
+            return Ok(request);
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "too many headers",
+    ))
+}
+
+/// Read the request line and headers of `stream` then write the response
+fn handle(mut stream: TcpStream, shared: &SharedProfile, options: &Options) -> io::Result<()> {
+    let cors = options.cors.as_deref();
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    stream.set_write_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let request = match read_request(&mut reader) {
+        Ok(r) => r,
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            Response::error(400, &format!("Invalid request: {}", e)).write_to(&mut stream, cors)?;
+            return Err(e);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let mut parts = request.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let path = parts
+        .next()
+        .unwrap_or_default()
+        .split('?')
+        .next()
+        .unwrap_or_default();
+    log::info!("{} {}", method, path);
+
+    if method == "GET" && path.trim_end_matches('/') == "/events" {
+        match Slot::take(&options.event_streams, MAX_EVENT_STREAMS) {
+            Some(_slot) => stream_events(stream, shared, options),
+            None => {
+                log::warn!(
+                    "Rejecting /events, {} streams already open",
+                    MAX_EVENT_STREAMS
+                );
+                Response::error(503, "Too many /events streams, try again later")
+                    .write_to(&mut stream, cors)
+            }
+        }
+    } else {
+        let response = match options.redaction.as_ref() {
+            Some(redaction) => {
+                let mut profile = shared.read().clone();
+                redaction.apply(&mut profile).map_err(io::Error::other)?;
+                response(&profile, method, path)
+            }
+            None => response(&shared.read(), method, path),
+        };
+        response.write_to(&mut stream, cors)
+    }
+}
+
+/// Write each [`crate::profiler::watch::WatchEvent`] of `shared` to `stream` as a server-sent event until the client disconnects
+fn stream_events(
+    mut stream: TcpStream,
+    shared: &SharedProfile,
+    options: &Options,
+) -> io::Result<()> {
+    let rx = shared.subscribe();
+
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n{}Connection: keep-alive\r\n\r\n",
+        cors_header(options.cors.as_deref())
+    )?;
+    stream.flush()?;
+
+    loop {
+        match rx.recv_timeout(KEEP_ALIVE) {
+            Ok(mut event) => {
+                if let Some(redaction) = options.redaction.as_ref() {
+                    redaction
+                        .apply_device(event.device_mut(), true)
+                        .map_err(io::Error::other)?;
+                }
+                let data = serde_json::to_string(event.device()).map_err(io::Error::other)?;
+                write!(stream, "event: {}\ndata: {}\n\n", event.name(), data)?;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => write!(stream, ": keep-alive\n\n")?,
+            Err(mpsc::RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+        stream.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profiler::read_json_dump;
+
+    const DUMP: &str = "./tests/data/cyme_libusb_linux_tree.json";
+
+    #[test]
+    fn test_response() {
+        let spusb = read_json_dump(DUMP).unwrap();
+
+        let devices: Vec<Device> =
+            serde_json::from_str(&response(&spusb, "GET", "/devices").body).unwrap();
+        assert_eq!(devices.len(), spusb.len());
+        assert!(devices.iter().all(|d| d.devices.is_none()));
+
+        let hub: Device =
+            serde_json::from_str(&response(&spusb, "GET", "/devices/2-2/").body).unwrap();
+        assert_eq!(hub.devices.map(|d| d.len()), Some(2));
+
+        let missing = response(&spusb, "GET", "/nothing");
+        assert_eq!(missing.status, 404);
+        assert!(missing.body.contains("/nothing"));
+    }
+
+    #[test]
+    fn test_server_request() {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        std::thread::spawn(move || server.run_with(|| read_json_dump(DUMP)));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET /buses?pretty HTTP/1.1\r\nHost: localhost\r\n\r\n"
+        )
+        .unwrap();
+        let mut ret = String::new();
+        stream.read_to_string(&mut ret).unwrap();
+
+        let (head, body) = ret.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK"));
+        assert!(!head.contains("Access-Control-Allow-Origin"));
+        let buses: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(
+            buses.as_array().map(|b| b.len()),
+            Some(read_json_dump(DUMP).unwrap().buses.len())
+        );
+    }
+
+    #[test]
+    fn test_server_event_streams_limit() {
+        let server = Server::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        server
+            .options
+            .event_streams
+            .store(MAX_EVENT_STREAMS, Ordering::SeqCst);
+        std::thread::spawn(move || server.run_with(|| read_json_dump(DUMP)));
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(stream, "GET /events HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut ret = String::new();
+        stream.read_to_string(&mut ret).unwrap();
+        assert!(ret.starts_with("HTTP/1.1 503 Service Unavailable"));
+    }
+
+    #[test]
+    fn test_read_request_limits() {
+        let request = "GET /devices HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        assert_eq!(
+            read_request(&mut request.as_bytes()).unwrap(),
+            "GET /devices HTTP/1.1\r\n"
+        );
+
+        let endless = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_LINE));
+        let err = read_request(&mut endless.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let headers = format!("GET / HTTP/1.1\r\n{}\r\n", "X: y\r\n".repeat(MAX_HEADERS));
+        let err = read_request(&mut headers.as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
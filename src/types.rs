@@ -1,6 +1,12 @@
 //! Types used in crate non-specific to a module
-use std::fmt;
-use std::str::FromStr;
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+use core::str::FromStr;
 
 use serde::de::{self, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize};
@@ -12,7 +18,7 @@ use crate::error::{self, Error, ErrorKind};
 /// Serialized string is of format "\[value\] \[unit\]" where u32 of f32 is supported
 ///
 /// ```
-/// use std::str::FromStr;
+/// use core::str::FromStr;
 /// use cyme::types::NumericalUnit;
 ///
 /// let s: &'static str = "100.0 W";
@@ -316,3 +322,31 @@ impl<'de> Deserialize<'de> for NumericalUnit<f32> {
         deserializer.deserialize_any(DeviceNumericalUnitF32Visitor)
     }
 }
+
+/// Format a duration in seconds as a short human-readable String with the two most significant units
+///
+/// ```
+/// use cyme::types::format_duration;
+///
+/// assert_eq!(format_duration(42), "42s");
+/// assert_eq!(format_duration(312), "5m 12s");
+/// assert_eq!(format_duration(3 * 3600 + 5 * 60 + 9), "3h 05m");
+/// assert_eq!(format_duration(2 * 86400 + 4 * 3600), "2d 04h");
+/// ```
+pub fn format_duration(secs: u64) -> String {
+    let (days, hours, mins, secs) = (
+        secs / 86400,
+        (secs % 86400) / 3600,
+        (secs % 3600) / 60,
+        secs % 60,
+    );
+    if days > 0 {
+        format!("{}d {:02}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {:02}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m {:02}s", mins, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
@@ -82,6 +82,21 @@ pub fn get_udev_syspath(port_path: &str) -> Result<Option<String>, Error> {
     Ok(device.syspath().to_str().map(|s| s.to_string()))
 }
 
+/// Lookup the kernel module of the driver bound to a device or interface given the `port_path`; `None` if no driver is bound or it is built into the kernel without a module entry.
+///
+/// ```no_run
+/// use cyme::udev::get_udev_module_name;
+/// let module = get_udev_module_name("1-0:1.0").unwrap();
+/// assert_eq!(module, Some("usbcore".into()));
+/// ```
+pub fn get_udev_module_name(port_path: &str) -> Result<Option<String>, Error> {
+    let device = get_device(port_path)?;
+
+    Ok(std::fs::read_link(device.syspath().join("driver/module"))
+        .ok()
+        .and_then(|p| p.file_name().map(|f| f.to_string_lossy().to_string())))
+}
+
 /// Lookup a udev attribute given the `port_path` and `attribute`.
 ///
 /// This only works on Linux and not all devices have all attributes.
@@ -3,13 +3,24 @@
 //! Also referring to [beyondlogic](https://beyondlogic.org/usbnutshell/usb5.shtml)
 //!
 //! There are some repeated/copied Enum defines from rusb in order to control Serialize/Deserialize and add impl
+use alloc::collections::BTreeMap;
+#[cfg(not(feature = "std"))]
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+#[cfg(feature = "std")]
 use clap::ValueEnum;
+use core::convert::TryFrom;
+use core::fmt;
+use core::str::FromStr;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
-use serde_with::skip_serializing_none;
-use std::convert::TryFrom;
-use std::fmt;
-use std::str::FromStr;
+use serde_with::{skip_serializing_none, DeserializeFromStr, SerializeDisplay};
 
 pub mod descriptors;
 pub use descriptors::*;
@@ -41,19 +52,20 @@ impl Version {
     /// descriptors as 16-bit integers encoding a version as `0xJJMN`, where `JJ` is the major
     /// version, `M` is the minor version, and `N` is the sub minor version. For example, 2.0 is
     /// encoded as `0x0200` and 1.1 is encoded as `0x0110`.
-    pub fn from_bcd(mut raw: u16) -> Self {
-        let sub_minor: u8 = (raw & 0x000F) as u8;
-        raw >>= 4;
-
-        let minor: u8 = (raw & 0x000F) as u8;
-        raw >>= 4;
-
-        let mut major: u8 = (raw & 0x000F) as u8;
-        raw >>= 4;
-
-        major += (10 * raw) as u8;
-
-        Version(major, minor, sub_minor)
+    ///
+    /// The major version is kept as the byte `JJ` so that it is displayed as in the descriptor and [`u16::from`] is the inverse:
+    ///
+    /// ```
+    /// use cyme::usb::Version;
+    /// assert_eq!(Version::from_bcd(0x1234).to_string(), "12.34");
+    /// assert_eq!(u16::from(Version::from_bcd(0x1234)), 0x1234);
+    /// ```
+    pub fn from_bcd(raw: u16) -> Self {
+        Version(
+            (raw >> 8) as u8,
+            ((raw >> 4) & 0x000F) as u8,
+            (raw & 0x000F) as u8,
+        )
     }
 
     /// Returns the major version.
@@ -75,7 +87,7 @@ impl Version {
     }
 }
 
-impl std::fmt::Display for Version {
+impl core::fmt::Display for Version {
     /// Output is a base16 encoding of Major.MinorSub
     ///
     /// ```
@@ -83,7 +95,7 @@ impl std::fmt::Display for Version {
     /// assert_eq!(cyme::usb::Version(2, 0, 1).to_string(), "2.01");
     /// ```
     ///
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "{:x}.{:x}{:x}",
@@ -188,7 +200,8 @@ pub enum DescriptorUsage {
 /// USB class code defines [ref](https://www.usb.org/defined-class-codes)
 ///
 /// Technically this is the 'Base Class' - the 'Class Code' is the full triplet of (Base Class, Sub Class, Protocol).
-#[derive(Debug, ValueEnum, Default, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(ValueEnum))]
 #[serde(rename_all = "kebab-case")]
 #[non_exhaustive]
 #[repr(u8)]
@@ -632,7 +645,9 @@ impl ClassCode {
 }
 
 /// USB Speed is also defined in libusb but this one allows us to provide updates and custom impl
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+///
+/// Ordered from slowest to fastest so that speeds can be compared
+#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
 #[non_exhaustive]
 #[serde(untagged, rename_all = "snake_case")]
 #[allow(missing_docs)]
@@ -748,6 +763,76 @@ impl Speed {
             _ => format!("{:.0}{}", dv.value, prefix),
         }
     }
+
+    /// Data rate in Mb/s
+    pub fn to_mbps(&self) -> f32 {
+        let dv = NumericalUnit::<f32>::from(self);
+        if dv.unit.starts_with('G') {
+            dv.value * 1000.0
+        } else {
+            dv.value
+        }
+    }
+
+    /// Bytes per second of the signalling rate hosts can reserve for periodic endpoints: 90% at full and low speed, 80% at high speed and 90% at SuperSpeed; `None` if unknown
+    ///
+    /// ```
+    /// # use cyme::usb::Speed;
+    ///
+    /// assert_eq!(Speed::HighSpeed.periodic_capacity(), Some(48_000_000));
+    /// assert_eq!(Speed::FullSpeed.periodic_capacity(), Some(1_350_000));
+    /// ```
+    pub fn periodic_capacity(&self) -> Option<u64> {
+        let percent = match self {
+            Speed::Unknown => return None,
+            Speed::HighSpeed | Speed::HighBandwidth => 80,
+            _ => 90,
+        };
+        Some((self.to_mbps() * 1_000_000.0 / 8.0) as u64 * percent / 100)
+    }
+
+    /// Speed formatted in `units`
+    ///
+    /// ```
+    /// # use cyme::usb::{Speed, SpeedUnits};
+    ///
+    /// assert_eq!(Speed::HighSpeed.to_units_string(SpeedUnits::Decimal), "480.0 Mb/s");
+    /// assert_eq!(Speed::SuperSpeed.to_units_string(SpeedUnits::Bits), "5 Gbps");
+    /// assert_eq!(Speed::SuperSpeedPlus.to_units_string(SpeedUnits::Bytes), "1.25 GB/s");
+    /// assert_eq!(Speed::LowSpeed.to_units_string(SpeedUnits::Bytes), "187.5 kB/s");
+    /// assert_eq!(Speed::LowSpeed.to_units_string(SpeedUnits::Raw), "1.5");
+    /// assert_eq!(Speed::FullSpeed.to_units_string(SpeedUnits::Lsusb), "12M");
+    /// ```
+    pub fn to_units_string(&self, units: SpeedUnits) -> String {
+        let mbps = self.to_mbps();
+        match units {
+            SpeedUnits::Decimal => format!("{:.1}", NumericalUnit::<f32>::from(self)),
+            SpeedUnits::Bits if mbps >= 1000.0 => format!("{} Gbps", mbps / 1000.0),
+            SpeedUnits::Bits => format!("{} Mbps", mbps),
+            SpeedUnits::Bytes if mbps >= 8000.0 => format!("{} GB/s", mbps / 8000.0),
+            SpeedUnits::Bytes if mbps >= 8.0 || mbps == 0.0 => format!("{} MB/s", mbps / 8.0),
+            SpeedUnits::Bytes => format!("{} kB/s", mbps * 1000.0 / 8.0),
+            SpeedUnits::Raw => format!("{}", mbps),
+            SpeedUnits::Lsusb => self.to_lsusb_speed(),
+        }
+    }
+}
+
+/// Units to display a [`Speed`] in; see [`Speed::to_units_string`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "std", derive(ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum SpeedUnits {
+    /// Bits per second to one decimal place: '480.0 Mb/s'; default of blocks
+    Decimal,
+    /// Bits per second in Mbps or Gbps: '480 Mbps'
+    Bits,
+    /// Bytes per second: '60 MB/s'
+    Bytes,
+    /// Mb/s without units as in Linux sysfs: '480'
+    Raw,
+    /// Mb/s with an M suffix like lsusb: '480M'; default of the lsusb tree
+    Lsusb,
 }
 
 /// Transfer and [`Endpoint`] direction
@@ -950,6 +1035,9 @@ pub struct Endpoint {
     /// Extra descriptors data based on type
     #[serde(default)] // default for legacy json
     pub extra: Option<Vec<Descriptor>>,
+    /// Raw endpoint descriptor followed by any companion or class-specific endpoint descriptors; only when profiled with raw descriptors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Vec<u8>>,
 }
 
 /// Deprecated alias for [`Endpoint`]
@@ -975,6 +1063,7 @@ impl Endpoint {
     ///     max_packet_size: 0xfff1,
     ///     interval: 3,
     ///     extra: None,
+    ///     raw: None,
     /// };
     /// assert_eq!(ep.max_packet_string(), "4x 2033");
     /// ep.max_packet_size = 0x0064;
@@ -988,6 +1077,75 @@ impl Endpoint {
         )
     }
 
+    /// Microseconds between each service of a periodic endpoint at `speed`; `None` for control and bulk endpoints
+    ///
+    /// bInterval is 2^(bInterval-1) 125 µs microframes at high speed and above, otherwise 1 ms frames for interrupt and 2^(bInterval-1) frames for isochronous endpoints.
+    ///
+    /// ```
+    /// # use cyme::usb::*;
+    ///
+    /// let ep = Endpoint {
+    ///     length: 7,
+    ///     address: EndpointAddress {
+    ///         address: 0x81,
+    ///         number: 1,
+    ///         direction: Direction::In
+    ///     },
+    ///     transfer_type: TransferType::Interrupt,
+    ///     sync_type: SyncType::None,
+    ///     usage_type: UsageType::Data,
+    ///     max_packet_size: 0x0040,
+    ///     interval: 4,
+    ///     extra: None,
+    ///     raw: None,
+    /// };
+    /// assert_eq!(ep.service_interval_us(&Speed::FullSpeed), Some(4000));
+    /// assert_eq!(ep.service_interval_us(&Speed::HighSpeed), Some(1000));
+    /// assert_eq!(ep.periodic_bandwidth(&Speed::HighSpeed), Some(64000));
+    /// ```
+    pub fn service_interval_us(&self, speed: &Speed) -> Option<u32> {
+        let exponent = self.interval.clamp(1, 16) as u32 - 1;
+        match self.transfer_type {
+            TransferType::Control | TransferType::Bulk => None,
+            _ if *speed >= Speed::HighSpeed => Some(125 << exponent),
+            TransferType::Interrupt => Some(1000 * self.interval.max(1) as u32),
+            TransferType::Isochronous => Some(1000 << exponent),
+        }
+    }
+
+    /// Bytes transferred each service interval at `speed`
+    ///
+    /// wMaxPacketSize multiplied by the additional transactions at high speed, or by the bMaxBurst and isochronous Mult of the SuperSpeed Endpoint Companion in `extra` at SuperSpeed.
+    pub fn bytes_per_interval(&self, speed: &Speed) -> u32 {
+        let size = (self.max_packet_size & 0x7ff) as u32;
+        if *speed >= Speed::SuperSpeed {
+            let companion = self.extra.iter().flatten().find_map(|d| match d {
+                Descriptor::SsEndpointCompanion(c) => Some(c),
+                _ => None,
+            });
+            match companion {
+                Some(c) => {
+                    let mult = match self.transfer_type {
+                        TransferType::Isochronous => (c.attributes & 0x03) as u32 + 1,
+                        _ => 1,
+                    };
+                    size * (c.max_burst as u32 + 1) * mult
+                }
+                None => size,
+            }
+        } else if *speed >= Speed::HighSpeed {
+            size * (((self.max_packet_size >> 11) & 3) as u32 + 1)
+        } else {
+            size
+        }
+    }
+
+    /// Bytes per second reserved for a periodic endpoint at `speed`, excluding protocol overhead; `None` for control and bulk endpoints
+    pub fn periodic_bandwidth(&self, speed: &Speed) -> Option<u64> {
+        self.service_interval_us(speed)
+            .map(|us| self.bytes_per_interval(speed) as u64 * 1_000_000 / us as u64)
+    }
+
     /// Returns the attributes byte for the endpoint
     pub fn attributes(&self) -> u8 {
         self.transfer_type.to_owned() as u8
@@ -1007,6 +1165,7 @@ pub struct Interface {
     /// Interface number
     pub number: u8,
     /// Interface port path - could be generated from device but stored here for ease
+    #[serde(default)] // default for legacy json, filled from device location when read
     pub path: String,
     /// Class of interface provided by USB IF
     pub class: BaseClass,
@@ -1028,6 +1187,18 @@ pub struct Interface {
     /// Extra descriptors for interface based on type
     #[serde(default)] // default for legacy json
     pub extra: Option<Vec<Descriptor>>,
+    /// Bluetooth HCI adapter bound to interface if it is a [`ClassCode::BluetoothProgrammingInterface`]; Linux only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bluetooth: Option<BluetoothAdapter>,
+    /// Kernel module of the bound `driver`, obtained from sysfs or udev on Linux only
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module: Option<KernelModule>,
+    /// Fields decoded from vendor-specific class descriptors by a registered [`vendor::Decoder`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor_fields: Option<Vec<vendor::VendorField>>,
+    /// Raw interface descriptor followed by any class-specific interface descriptors; only when profiled with raw descriptors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Vec<u8>>,
 }
 
 /// Deprecated alias for [`Interface`]
@@ -1041,6 +1212,7 @@ impl Interface {
     }
 
     /// Name of class from Linux USB IDs repository
+    #[cfg(feature = "std")]
     pub fn class_name(&self) -> Option<&str> {
         usb_ids::Classes::iter()
             .find(|c| c.id() == u8::from(self.class))
@@ -1048,11 +1220,13 @@ impl Interface {
     }
 
     /// Name of sub class from Linux USB IDs repository
+    #[cfg(feature = "std")]
     pub fn sub_class_name(&self) -> Option<&str> {
         usb_ids::SubClass::from_cid_scid(u8::from(self.class), self.sub_class).map(|sc| sc.name())
     }
 
     /// Name of protocol from Linux USB IDs repository
+    #[cfg(feature = "std")]
     pub fn protocol_name(&self) -> Option<&str> {
         usb_ids::Protocol::from_cid_scid_pid(u8::from(self.class), self.sub_class, self.protocol)
             .map(|p| p.name())
@@ -1062,6 +1236,128 @@ impl Interface {
     pub fn fully_defined_class(&self) -> ClassCode {
         (self.class, self.sub_class, self.protocol).into()
     }
+
+    /// Whether no driver is bound to the interface though the host reports bound drivers, which is only Linux
+    ///
+    /// Only meaningful for interfaces of the active [`Configuration`] since no others can be claimed; see [`DeviceExtra::unclaimed_interfaces`].
+    pub fn is_unclaimed(&self) -> bool {
+        self.syspath.is_some() && self.driver.is_none()
+    }
+
+    /// Whether the interface is a Bluetooth HCI; class triplet E0/01/01
+    pub fn is_bluetooth_hci(&self) -> bool {
+        self.fully_defined_class() == ClassCode::BluetoothProgrammingInterface
+    }
+
+    /// Summary of the audio formats supported if the interface is a UAC streaming interface with format descriptors
+    pub fn audio_format_summary(&self) -> Option<audio::AudioFormatSummary> {
+        let descriptors = self.extra.as_ref()?.iter().filter_map(|d| match d {
+            Descriptor::Interface(ClassDescriptor::Audio(uac, _)) => Some(&uac.interface),
+            _ => None,
+        });
+        audio::AudioFormatSummary::from_descriptors(descriptors)
+    }
+
+    /// Summary of the frame sizes, frame rates and formats supported if the interface is a UVC streaming interface with format descriptors
+    pub fn video_format_summary(&self) -> Option<video::VideoFormatSummary> {
+        let descriptors = self.extra.as_ref()?.iter().filter_map(|d| match d {
+            Descriptor::Interface(ClassDescriptor::Video(uvc, _)) => Some(&uvc.interface),
+            _ => None,
+        });
+        video::VideoFormatSummary::from_descriptors(descriptors)
+    }
+
+    /// UAC2/UAC3 controls present in the class descriptors of the interface; duplicates across units are removed
+    pub fn audio_controls(&self) -> Vec<audio::Control> {
+        let mut controls: Vec<audio::Control> = Vec::new();
+        for d in self.extra.iter().flatten() {
+            if let Descriptor::Interface(ClassDescriptor::Audio(uac, _)) = d {
+                for c in uac.interface.controls() {
+                    if !controls.contains(&c) {
+                        controls.push(c);
+                    }
+                }
+            }
+        }
+        controls
+    }
+
+    /// Decode any class descriptors not otherwise parsed with the matching decoders in `registry` and store them in `vendor_fields`
+    pub fn decode_vendor_descriptors(
+        &mut self,
+        vendor_id: u16,
+        product_id: u16,
+        registry: &vendor::Registry,
+    ) {
+        let fields: Vec<vendor::VendorField> = self
+            .extra
+            .iter()
+            .flatten()
+            .filter_map(|d| match d {
+                Descriptor::Interface(ClassDescriptor::Generic(Some(class), gd))
+                | Descriptor::Endpoint(ClassDescriptor::Generic(Some(class), gd)) => {
+                    Some(registry.decode(vendor_id, product_id, class, gd))
+                }
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        if !fields.is_empty() {
+            self.vendor_fields = Some(fields);
+        }
+    }
+}
+
+/// Bluetooth Host Controller Interface (HCI) adapter registered by the host for a [`ClassCode::BluetoothProgrammingInterface`] [`Interface`]
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BluetoothAdapter {
+    /// HCI device name assigned by the host, e.g. 'hci0'
+    pub name: String,
+    /// Bluetooth device address (BD_ADDR) if exposed by the host; on Linux kernels without it in sysfs this requires root to read from debugfs
+    pub address: Option<String>,
+    /// Organisation the address OUI is assigned to, from the udev hwdb
+    #[serde(default)]
+    pub vendor: Option<String>,
+}
+
+/// Kernel module providing the driver bound to an [`Interface`]; Linux only
+#[skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KernelModule {
+    /// Module name such as 'usbhid' or 'cdc_acm'
+    pub name: String,
+    /// Module parameters and their current values; only when profiled with module parameters and those readable
+    #[serde(default)]
+    pub parameters: Option<BTreeMap<String, String>>,
+}
+
+impl fmt::Display for KernelModule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl KernelModule {
+    /// Parameters in the form 'name=value' as passed to modprobe, space separated
+    ///
+    /// ```
+    /// use cyme::usb::KernelModule;
+    ///
+    /// let module = KernelModule {
+    ///     name: "usbhid".into(),
+    ///     parameters: Some([("mousepoll".into(), "0".into()), ("jspoll".into(), "0".into())].into()),
+    /// };
+    /// assert_eq!(module.parameters_string(), "jspoll=0 mousepoll=0");
+    /// ```
+    pub fn parameters_string(&self) -> String {
+        self.parameters
+            .iter()
+            .flatten()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .join(" ")
+    }
 }
 
 /// Devices can have multiple configurations, each with different attributes and interfaces
@@ -1089,6 +1385,15 @@ pub struct Configuration {
     /// Extra descriptors for configuration based on type
     #[serde(default)] // default for legacy json
     pub extra: Option<Vec<Descriptor>>,
+    /// Raw configuration descriptor followed by any descriptors before the first interface, such as interface associations; only when profiled with raw descriptors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<Vec<u8>>,
+    /// Configuration is the one currently set on the device; from sysfs bConfigurationValue or GET_CONFIGURATION, false if unknown
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub active: bool,
+    /// Mismatches between the lengths and counts the configuration descriptor declares and its descriptors; only when profiled with validation, see [`descriptors::validate_configuration`]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<DescriptorWarning>,
 }
 
 /// Deprecated alias for [`Configuration`]
@@ -1114,6 +1419,63 @@ impl Configuration {
 
         ret
     }
+
+    /// Set the `raw` bytes of the configuration, its interfaces and their endpoints by splitting `data`, the full configuration descriptor of wTotalLength
+    ///
+    /// Each descriptor belongs to the standard descriptor it follows, so class descriptors after an interface are part of that interface's raw bytes. If `data` is malformed, the remaining bytes are added to the level being split so none are lost.
+    pub fn set_raw_descriptors(&mut self, data: &[u8]) {
+        let mut config: Vec<u8> = Vec::new();
+        let mut interfaces: BTreeMap<(u8, u8), Vec<u8>> = BTreeMap::new();
+        let mut endpoints: BTreeMap<(u8, u8, u8), Vec<u8>> = BTreeMap::new();
+        // (interface number, alt setting, endpoint address) of the descriptor being split
+        let mut current: Option<(u8, u8, Option<u8>)> = None;
+
+        let mut i = 0;
+        while i < data.len() {
+            let length = data[i] as usize;
+            let desc = if length < 2 || i + length > data.len() {
+                log::warn!(
+                    "Malformed descriptor at offset {} of configuration {}",
+                    i,
+                    self.number
+                );
+                &data[i..]
+            } else {
+                &data[i..i + length]
+            };
+
+            match (desc.get(1), current) {
+                (Some(0x04), _) if desc.len() >= 4 => current = Some((desc[2], desc[3], None)),
+                (Some(0x05), Some((n, a, _))) if desc.len() >= 3 => {
+                    current = Some((n, a, Some(desc[2])))
+                }
+                _ => (),
+            }
+
+            match current {
+                None => config.extend_from_slice(desc),
+                Some((n, a, None)) => interfaces
+                    .entry((n, a))
+                    .or_default()
+                    .extend_from_slice(desc),
+                Some((n, a, Some(e))) => endpoints
+                    .entry((n, a, e))
+                    .or_default()
+                    .extend_from_slice(desc),
+            }
+
+            i += desc.len();
+        }
+
+        self.raw = Some(config);
+        for interface in self.interfaces.iter_mut() {
+            let key = (interface.number, interface.alt_setting);
+            interface.raw = interfaces.remove(&key);
+            for endpoint in interface.endpoints.iter_mut() {
+                endpoint.raw = endpoints.remove(&(key.0, key.1, endpoint.address.address));
+            }
+        }
+    }
 }
 
 /// Extra USB device data for verbose printing
@@ -1134,6 +1496,7 @@ pub struct DeviceExtra {
     #[serde(default)]
     pub string_indexes: (u8, u8, u8),
     /// USB devices can be have a number of configurations
+    #[serde(default)] // default for legacy json
     pub configurations: Vec<Configuration>,
     /// Device status
     pub status: Option<u16>,
@@ -1145,12 +1508,247 @@ pub struct DeviceExtra {
     pub qualifier: Option<DeviceQualifierDescriptor>,
     /// Hub descriptor if present (is a hub)
     pub hub: Option<HubDescriptor>,
+    /// Vendor specific details such as the chip of USB-UART bridges
+    pub vendor_fields: Option<Vec<vendor::VendorField>>,
 }
 
 /// Deprecated alias for [`DeviceExtra`]
 #[deprecated(since = "2.0.0", note = "Use DeviceExtra instead")]
 pub type USBDeviceExtra = DeviceExtra;
 
+impl DeviceExtra {
+    /// The [`Configuration`] currently set on the device if known
+    pub fn active_configuration(&self) -> Option<&Configuration> {
+        self.configurations.iter().find(|c| c.active)
+    }
+
+    /// [`Interface`]s of the active [`Configuration`] with no driver bound, one per interface number; the only configuration is used if none is known to be active
+    ///
+    /// See [`Interface::is_unclaimed`].
+    pub fn unclaimed_interfaces(&self) -> Vec<&Interface> {
+        let config = self
+            .active_configuration()
+            .or(match self.configurations.as_slice() {
+                [only] => Some(only),
+                _ => None,
+            });
+        let mut ret: Vec<&Interface> = config
+            .iter()
+            .flat_map(|c| c.interfaces.iter())
+            .filter(|i| i.is_unclaimed())
+            .collect();
+        // alternate settings follow each other
+        ret.dedup_by_key(|i| i.number);
+        ret
+    }
+
+    /// Mark the [`Configuration`] with bConfigurationValue `value` as active; 0 is unconfigured so none will be
+    pub fn set_active_configuration(&mut self, value: u8) {
+        for config in self.configurations.iter_mut() {
+            config.active = config.number == value;
+        }
+    }
+}
+
+/// Linux style port path of a device, or one of its interfaces, where it can be found on system device path - normally /sys/bus/usb/devices
+///
+/// The bus number followed by the port number at each hub from the root hub: 'bus-port.port.port', with an optional ':config.interface' suffix for an interface. A path with no ports is the bus/root hub, 'bus-0'.
+///
+/// ```
+/// use cyme::usb::PortPath;
+///
+/// let path: PortPath = "1-10.2.3".parse().unwrap();
+/// assert_eq!(path.bus, 1);
+/// assert_eq!(path.ports, vec![10, 2, 3]);
+/// assert_eq!(path.depth(), 3);
+/// assert_eq!(path.parent().unwrap().to_string(), "1-10.2");
+/// assert_eq!(path.trunk().to_string(), "1-10");
+/// assert_eq!(path.child(4).to_string(), "1-10.2.3.4");
+/// assert_eq!(path.with_interface(1, 0).to_string(), "1-10.2.3:1.0");
+///
+/// // root hub interface
+/// let root: PortPath = "2-0:1.0".parse().unwrap();
+/// assert!(root.is_root());
+/// assert_eq!(root.interface, Some((1, 0)));
+/// assert_eq!(root.parent().unwrap().sysfs_name(), "usb2");
+/// assert_eq!("usb2".parse::<PortPath>().unwrap(), PortPath::new(2, vec![]));
+/// ```
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    SerializeDisplay,
+    DeserializeFromStr,
+)]
+pub struct PortPath {
+    /// Number of bus
+    pub bus: u8,
+    /// Port at each hub from the root hub, empty for the bus/root hub itself
+    pub ports: Vec<u8>,
+    /// Configuration and interface number if path to an interface
+    pub interface: Option<(u8, u8)>,
+}
+
+impl PortPath {
+    /// New path to device on `bus` at `ports`
+    pub fn new(bus: u8, ports: Vec<u8>) -> Self {
+        PortPath {
+            bus,
+            ports,
+            interface: None,
+        }
+    }
+
+    /// Path to `interface` in `config` of this device
+    pub fn with_interface(&self, config: u8, interface: u8) -> Self {
+        PortPath {
+            bus: self.bus,
+            ports: self.ports.clone(),
+            interface: Some((config, interface)),
+        }
+    }
+
+    /// Path to the device of an interface path, or self if not an interface
+    pub fn device(&self) -> Self {
+        PortPath::new(self.bus, self.ports.clone())
+    }
+
+    /// Number of hubs deep; 0 for the bus/root hub
+    pub fn depth(&self) -> usize {
+        self.ports.len()
+    }
+
+    /// Whether the path is the bus/root hub itself, rather than a device attached to it
+    pub fn is_root(&self) -> bool {
+        self.ports.is_empty()
+    }
+
+    /// Path to the parent: the device of an interface, hub of a device or bus of a trunk device. `None` for the bus/root hub
+    pub fn parent(&self) -> Option<Self> {
+        if self.interface.is_some() {
+            Some(self.device())
+        } else if self.is_root() {
+            None
+        } else {
+            Some(PortPath::new(
+                self.bus,
+                self.ports[..self.ports.len() - 1].to_vec(),
+            ))
+        }
+    }
+
+    /// Path to device at `port` of this hub
+    pub fn child(&self, port: u8) -> Self {
+        let mut ports = self.ports.clone();
+        ports.push(port);
+        PortPath::new(self.bus, ports)
+    }
+
+    /// Path to the trunk device on the bus; first in tree. The bus if the path is the bus/root hub
+    pub fn trunk(&self) -> Self {
+        PortPath::new(self.bus, self.ports.iter().take(1).copied().collect())
+    }
+
+    /// Whether `other` is below this path in the tree, including interfaces of this device
+    pub fn is_ancestor_of(&self, other: &PortPath) -> bool {
+        self.interface.is_none()
+            && self.bus == other.bus
+            && other.ports.starts_with(&self.ports)
+            && (other.ports.len() > self.ports.len() || other.interface.is_some())
+    }
+
+    /// Linux sysfs name, like the path but the bus/root hub device uses the USB controller name (usbX)
+    pub fn sysfs_name(&self) -> String {
+        if self.is_root() && self.interface.is_none() {
+            format!("usb{}", self.bus)
+        } else {
+            self.to_string()
+        }
+    }
+
+    /// Linux sysfs path of the device or interface
+    pub fn sysfs_path(&self) -> String {
+        format!("/sys/bus/usb/devices/{}", self.sysfs_name())
+    }
+}
+
+impl fmt::Display for PortPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_root() {
+            write!(f, "{}-0", self.bus)?;
+        } else {
+            write!(f, "{}-{}", self.bus, self.ports.iter().format("."))?;
+        }
+
+        if let Some((config, interface)) = self.interface {
+            write!(f, ":{}.{}", config, interface)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for PortPath {
+    type Err = Error;
+
+    fn from_str(s: &str) -> error::Result<Self> {
+        let invalid = || {
+            Error::new(
+                ErrorKind::Parsing,
+                &format!(
+                    "Invalid port path '{}', expected bus-port.port[:config.interface]",
+                    s
+                ),
+            )
+        };
+
+        let s = s.trim();
+        // sysfs name of root hub
+        if let Some(bus) = s.strip_prefix("usb") {
+            return Ok(PortPath::new(bus.parse().map_err(|_| invalid())?, vec![]));
+        }
+
+        let (device, interface) = match s.split_once(':') {
+            Some((device, interface)) => {
+                let (config, interface) = interface.split_once('.').ok_or_else(invalid)?;
+                (
+                    device,
+                    Some((
+                        config.parse().map_err(|_| invalid())?,
+                        interface.parse().map_err(|_| invalid())?,
+                    )),
+                )
+            }
+            None => (s, None),
+        };
+
+        let (bus, ports) = device.split_once('-').ok_or_else(invalid)?;
+        let bus = bus.parse().map_err(|_| invalid())?;
+        let ports = if ports == "0" {
+            Vec::new()
+        } else {
+            ports
+                .split('.')
+                .map(|p| match p.parse::<u8>() {
+                    Ok(p) if p != 0 => Ok(p),
+                    _ => Err(invalid()),
+                })
+                .collect::<error::Result<Vec<u8>>>()?
+        };
+
+        Ok(PortPath {
+            bus,
+            ports,
+            interface,
+        })
+    }
+}
+
 /// Builds a replica of sysfs path; excludes config.interface
 ///
 /// ```
@@ -1170,11 +1768,7 @@ pub type USBDeviceExtra = DeviceExtra;
 ///
 ///  bus-port.port.port ...
 pub fn get_port_path(bus: u8, ports: &[u8]) -> String {
-    if ports.len() <= 1 {
-        get_trunk_path(bus, ports)
-    } else {
-        format!("{:}-{}", bus, ports.iter().format("."))
-    }
+    PortPath::new(bus, ports.to_vec()).to_string()
 }
 
 /// Parent path is path to parent device
@@ -1184,14 +1778,15 @@ pub fn get_port_path(bus: u8, ports: &[u8]) -> String {
 /// assert_eq!(get_parent_path(1, &[1, 3, 4, 5]).unwrap(), String::from("1-1.3.4"));
 /// ```
 pub fn get_parent_path(bus: u8, ports: &[u8]) -> error::Result<String> {
-    if ports.is_empty() {
-        Err(Error::new(
-            ErrorKind::InvalidArg,
-            "Cannot get parent path for root device",
-        ))
-    } else {
-        Ok(get_port_path(bus, &ports[..ports.len() - 1]))
-    }
+    PortPath::new(bus, ports.to_vec())
+        .parent()
+        .map(|p| p.to_string())
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidArg,
+                "Cannot get parent path for root device",
+            )
+        })
 }
 
 /// Trunk path is path to trunk device on bus
@@ -1203,12 +1798,7 @@ pub fn get_parent_path(bus: u8, ports: &[u8]) -> error::Result<String> {
 /// assert_eq!(get_trunk_path(1, &[]), String::from("1-0"));
 /// ```
 pub fn get_trunk_path(bus: u8, ports: &[u8]) -> String {
-    if ports.is_empty() {
-        // special case for root_hub
-        format!("{:}-{}", bus, 0)
-    } else {
-        format!("{:}-{}", bus, ports[0])
-    }
+    PortPath::new(bus, ports.to_vec()).trunk().to_string()
 }
 
 /// Build replica of sysfs path with interface
@@ -1221,7 +1811,9 @@ pub fn get_trunk_path(bus: u8, ports: &[u8]) -> String {
 /// assert_eq!(get_interface_path(1, &[], 1, 0), String::from("1-0:1.0"));
 /// ```
 pub fn get_interface_path(bus: u8, ports: &[u8], config: u8, interface: u8) -> String {
-    format!("{}:{}.{}", get_port_path(bus, ports), config, interface)
+    PortPath::new(bus, ports.to_vec())
+        .with_interface(config, interface)
+        .to_string()
 }
 
 /// Build replica of Linux dev path from libusb.c *devbususb for getting device with -D
@@ -1259,12 +1851,7 @@ pub fn get_dev_path(bus: u8, device_no: Option<u8>) -> String {
 /// assert_eq!(get_sysfs_name(2, &vec![]), String::from("usb2"));
 /// ```
 pub fn get_sysfs_name(bus: u8, ports: &[u8]) -> String {
-    if ports.is_empty() {
-        // special cae for root_hub
-        format!("usb{}", bus)
-    } else {
-        get_port_path(bus, ports)
-    }
+    PortPath::new(bus, ports.to_vec()).sysfs_name()
 }
 
 #[cfg(test)]
@@ -1286,4 +1873,213 @@ mod tests {
         assert_eq!(Version::try_from(2.01).unwrap(), Version(2, 0, 1));
         assert_eq!(Version::try_from(2.31).unwrap(), Version(2, 1, 15));
     }
+
+    #[test]
+    fn test_set_raw_descriptors() {
+        let data: Vec<u8> = vec![
+            // configuration
+            0x09, 0x02, 0x22, 0x00, 0x01, 0x01, 0x00, 0xa0, 0x32, //
+            // interface 0 alt 0
+            0x09, 0x04, 0x00, 0x00, 0x01, 0x03, 0x01, 0x02, 0x00, //
+            // HID
+            0x09, 0x21, 0x11, 0x01, 0x00, 0x01, 0x22, 0x34, 0x00, //
+            // endpoint 0x81
+            0x07, 0x05, 0x81, 0x03, 0x04, 0x00, 0x0a,
+        ];
+        let mut config = Configuration {
+            name: String::new(),
+            string_index: 0,
+            number: 1,
+            interfaces: vec![Interface {
+                name: None,
+                string_index: 0,
+                number: 0,
+                path: String::from("1-1:1.0"),
+                class: BaseClass::Hid,
+                sub_class: 1,
+                protocol: 2,
+                alt_setting: 0,
+                driver: None,
+                syspath: None,
+                endpoints: vec![Endpoint {
+                    length: 7,
+                    address: EndpointAddress::from(0x81),
+                    transfer_type: TransferType::Interrupt,
+                    sync_type: SyncType::None,
+                    usage_type: UsageType::Data,
+                    max_packet_size: 4,
+                    interval: 10,
+                    extra: None,
+                    raw: None,
+                }],
+                length: 9,
+                extra: None,
+                bluetooth: None,
+                module: None,
+                vendor_fields: None,
+                raw: None,
+            }],
+            attributes: vec![ConfigAttributes::RemoteWakeup],
+            max_power: NumericalUnit {
+                value: 100,
+                unit: String::from("mA"),
+                description: None,
+            },
+            length: 9,
+            total_length: 0x22,
+            extra: None,
+            raw: None,
+            active: false,
+            warnings: Vec::new(),
+        };
+
+        config.set_raw_descriptors(&data);
+        assert_eq!(config.raw.as_deref(), Some(&data[..9]));
+        assert_eq!(config.interfaces[0].raw.as_deref(), Some(&data[9..27]));
+        assert_eq!(
+            config.interfaces[0].endpoints[0].raw.as_deref(),
+            Some(&data[27..])
+        );
+    }
+
+    #[test]
+    fn test_split_configuration() {
+        let config = [
+            0x09, 0x02, 0x29, 0x00, 0x01, 0x01, 0x00, 0xa0, 0x32, // config
+            0x08, 0x0b, 0x00, 0x01, 0x03, 0x00, 0x00, 0x00, // iad
+            0x09, 0x04, 0x00, 0x00, 0x01, 0x03, 0x01, 0x02, 0x00, // interface
+            0x09, 0x21, 0x11, 0x01, 0x00, 0x01, 0x22, 0x34, 0x00, // hid
+            0x07, 0x05, 0x81, 0x03, 0x04, 0x00, 0x0a, // endpoint
+        ];
+        let split = split_configuration(&config).unwrap();
+
+        assert_eq!(split.config, &config[..9]);
+        assert_eq!(split.extra, &config[9..17]);
+        assert_eq!(split.interfaces.len(), 1);
+        assert_eq!(split.interfaces[0].interface, &config[17..26]);
+        assert_eq!(split.interfaces[0].extra, &config[26..35]);
+        assert_eq!(split.interfaces[0].endpoints, vec![(&config[35..], vec![])]);
+        assert!(split.trailing.is_empty());
+        assert!(split_configuration(&config[9..]).is_err());
+    }
+
+    #[test]
+    fn test_parse_configuration_malformed() {
+        let mut data: Vec<u8> = vec![
+            // configuration
+            0x09, 0x02, 0x3e, 0x00, 0x02, 0x01, 0x00, 0xa0, 0x32, //
+            // interface 0 CDC ACM with header and endpoint
+            0x09, 0x04, 0x00, 0x00, 0x01, 0x02, 0x02, 0x01, 0x00, //
+            0x05, 0x24, 0x00, 0x10, 0x01, //
+            0x07, 0x05, 0x82, 0x03, 0x08, 0x00, 0x10, //
+            // interface 1 HID with endpoint
+            0x09, 0x04, 0x01, 0x00, 0x01, 0x03, 0x01, 0x01, 0x00, //
+            0x09, 0x21, 0x11, 0x01, 0x00, 0x01, 0x22, 0x3f, 0x00, //
+            0x07, 0x05, 0x81, 0x03, 0x08, 0x00, 0x0a,
+        ];
+        data[2] = data.len() as u8;
+        let parsed = parse_configuration(&data).unwrap();
+        assert!(parsed.is_ok(), "{:?}", parsed.errors);
+        assert_eq!(parsed.configuration.interfaces.len(), 2);
+
+        // every truncation parses without panic, erroring only without the configuration header
+        for n in 0..data.len() {
+            match parse_configuration(&data[..n]) {
+                Ok(parsed) => assert!(!parsed.is_ok()),
+                Err(_) => assert!(n < 9),
+            }
+        }
+
+        // zero and overlong lengths end parsing rather than loop or read past the end
+        for length in [0x00, 0x01, 0xff] {
+            let mut data = data.clone();
+            data[18] = length;
+            let parsed = parse_configuration(&data).unwrap();
+            assert!(!parsed.is_ok());
+            assert_eq!(parsed.configuration.interfaces.len(), 1);
+            assert!(parsed.configuration.interfaces[0].endpoints.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_validate_configuration() {
+        let data: Vec<u8> = vec![
+            // configuration with 2 interfaces
+            0x09, 0x02, 0x2b, 0x00, 0x02, 0x01, 0x00, 0xa0, 0x32, //
+            // interface 0 declaring 2 endpoints with 1
+            0x09, 0x04, 0x00, 0x00, 0x02, 0x03, 0x01, 0x02, 0x00, //
+            0x07, 0x05, 0x81, 0x03, 0x04, 0x00, 0x0a, //
+            // interface 1 with short bLength
+            0x08, 0x04, 0x01, 0x00, 0x01, 0xff, 0x00, 0x00, //
+            0x07, 0x05, 0x02, 0x02, 0x40, 0x00, 0x00, //
+            // audio endpoint
+            0x09, 0x05, 0x83, 0x01, 0x40, 0x00, 0x01, 0x00, 0x00,
+        ];
+        let warnings = validate_configuration(&data);
+        let messages: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Interface at offset 25: bLength 8 but should be 9",
+                "Interface at offset 9: interface 0.0 bNumEndpoints 2 but 1 endpoint descriptors follow",
+                "Interface at offset 25: interface 1.0 bNumEndpoints 1 but 2 endpoint descriptors follow",
+                "Config at offset 0: wTotalLength is 43 but 49 bytes were returned",
+            ]
+        );
+
+        // zero bLength ends parsing with the bytes remaining
+        let mut data = data;
+        data[9] = 0;
+        let warnings = validate_configuration(&data);
+        assert_eq!(warnings[0].offset, 9);
+        assert!(warnings[0].message.contains("40 bytes remaining"));
+        assert!(validate_configuration(&data[..4]).len() == 1);
+    }
+
+    #[test]
+    fn test_validate_configuration_many_endpoints() {
+        // more endpoint descriptors after an interface than fit in a u8 count
+        let mut data: Vec<u8> = vec![
+            0x09, 0x02, 0x00, 0x00, 0x01, 0x01, 0x00, 0xa0, 0x32, //
+            0x09, 0x04, 0x00, 0x00, 0x01, 0xff, 0x00, 0x00, 0x00, //
+        ];
+        for _ in 0..300 {
+            data.extend_from_slice(&[0x07, 0x05, 0x81, 0x02, 0x40, 0x00, 0x00]);
+        }
+        let total_length = (data.len() as u16).to_le_bytes();
+        data[2..4].copy_from_slice(&total_length);
+
+        let warnings = validate_configuration(&data);
+        let messages: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec!["Interface at offset 9: interface 0.0 bNumEndpoints 1 but 300 endpoint descriptors follow"]
+        );
+        assert!(parse_configuration(&data).is_ok());
+    }
+
+    #[test]
+    fn test_port_path() {
+        for path in ["1-0", "1-0:1.0", "3-10.2.12", "3-10.2.12:2.1"] {
+            assert_eq!(path.parse::<PortPath>().unwrap().to_string(), path);
+        }
+        for path in [
+            "", "1", "1-", "x-1", "1-1..2", "1-0.1", "1-1:1", "1-1:1.x", "usb",
+        ] {
+            assert!(path.parse::<PortPath>().is_err(), "{}", path);
+        }
+
+        // deep and wide hubs where string prefixes mislead
+        let hub: PortPath = "1-1.1".parse().unwrap();
+        assert!(hub.is_ancestor_of(&"1-1.1.10".parse().unwrap()));
+        assert!(hub.is_ancestor_of(&"1-1.1:1.0".parse().unwrap()));
+        assert!(!hub.is_ancestor_of(&"1-1.10".parse().unwrap()));
+        assert!(!hub.is_ancestor_of(&hub));
+        assert_eq!(
+            hub.with_interface(1, 0).parent().unwrap().parent().unwrap(),
+            "1-1".parse().unwrap()
+        );
+        assert_eq!(hub.trunk().parent().unwrap().to_string(), "1-0");
+        assert_eq!(hub.trunk().parent().unwrap().parent(), None);
+    }
 }
@@ -1,6 +1,6 @@
 //! Defines for USB parsed device descriptors; extends the `usb` module.
+use core::convert::TryFrom;
 use serde::{Deserialize, Serialize};
-use std::convert::TryFrom;
 
 use super::*;
 use crate::error::{self, Error, ErrorKind};
@@ -8,6 +8,9 @@ use crate::error::{self, Error, ErrorKind};
 pub mod audio;
 pub mod bos;
 pub mod cdc;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod round_trip;
+pub mod vendor;
 pub mod video;
 
 /// USB descriptor types
@@ -196,15 +199,18 @@ pub enum Descriptor {
     Interface(ClassDescriptor),
     Endpoint(ClassDescriptor),
     DeviceQualifier(DeviceQualifierDescriptor),
+    #[serde(alias = "o-t-g")]
     Otg(OnTheGoDescriptor),
     Debug(DebugDescriptor),
     InterfaceAssociation(InterfaceAssociationDescriptor),
     Security(SecurityDescriptor),
     Encrypted(EncryptionDescriptor),
+    #[serde(alias = "b-o-s")]
     Bos(bos::BinaryObjectStoreDescriptor),
     Report(HidReportDescriptor),
     Hub(HubDescriptor),
     SuperSpeedHub(HubDescriptor),
+    #[serde(alias = "s-s-endpoint-companion")]
     SsEndpointCompanion(SsEndpointCompanionDescriptor),
     // these are internal
     Unknown(Vec<u8>),
@@ -253,9 +259,10 @@ impl TryFrom<&[u8]> for Descriptor {
         match v[1].into() {
             DescriptorType::Device => Ok(Descriptor::Device(ClassDescriptor::try_from(v)?)),
             DescriptorType::Config => Ok(Descriptor::Config(ClassDescriptor::try_from(v)?)),
-            DescriptorType::String => {
-                Ok(Descriptor::String(String::from_utf8_lossy(v).to_string()))
-            }
+            // kept as bytes if not UTF-8 rather than replacing characters
+            DescriptorType::String => Ok(String::from_utf8(v.to_vec())
+                .map(Descriptor::String)
+                .unwrap_or_else(|e| Descriptor::Unknown(e.into_bytes()))),
             DescriptorType::Interface => Ok(Descriptor::Interface(ClassDescriptor::try_from(v)?)),
             DescriptorType::Endpoint => Ok(Descriptor::Endpoint(ClassDescriptor::try_from(v)?)),
             DescriptorType::DeviceQualifier => Ok(Descriptor::DeviceQualifier(
@@ -552,10 +559,18 @@ impl TryFrom<&[u8]> for EncryptionDescriptor {
             ));
         }
 
+        let encryption_type = EncryptionType::from(value[2]);
+        if encryption_type == EncryptionType::Reserved {
+            return Err(Error::new(
+                ErrorKind::InvalidDescriptor,
+                &format!("Reserved EncryptionDescriptor type {:#04x}", value[2]),
+            ));
+        }
+
         Ok(EncryptionDescriptor {
             length: value[0],
             descriptor_type: value[1],
-            encryption_type: EncryptionType::from(value[2]),
+            encryption_type,
             encryption_value: value[3],
             auth_key_index: value[4],
         })
@@ -579,22 +594,26 @@ impl From<EncryptionDescriptor> for Vec<u8> {
 #[serde(rename_all = "kebab-case")]
 pub enum ClassDescriptor {
     /// USB HID extra descriptor
+    #[serde(alias = "h-i-d")]
     Hid(HidDescriptor),
     /// USB Communication extra descriptor
     Communication(cdc::CommunicationDescriptor),
     /// USB CCID (Smart Card) extra descriptor
+    #[serde(alias = "c-c-i-d")]
     Ccid(CcidDescriptor),
     /// USB Printer extra descriptor
     Printer(PrinterDescriptor),
     /// USB MIDI extra descriptor
     ///
     /// For legacy purposes, MIDI is defined as a SubClass (3) of Audio Class [1](https://www.usb.org/sites/default/files/USB%20MIDI%20v2_0.pdf) but we define at as a separate ClassDescriptor
+    #[serde(alias = "m-i-d-i")]
     Midi(audio::MidiDescriptor, u8),
     /// USB Audio extra descriptor
     Audio(audio::UacDescriptor, audio::UacProtocol),
     /// USB Video extra descriptor
     Video(video::UvcDescriptor, u8),
     /// Device Firmware Upgrade (DFU) descriptor
+    #[serde(alias = "d-f-u")]
     Dfu(DfuDescriptor),
     /// Generic descriptor with `Option<BaseClass>`
     ///
@@ -882,8 +901,11 @@ impl From<HidDescriptor> for Vec<u8> {
         ret.push(hd.descriptor_type);
         ret.extend(u16::from(hd.bcd_hid).to_le_bytes());
         ret.push(hd.country_code);
+        ret.push(hd.descriptors.len() as u8);
+        // only the type and length are in the HID descriptor; report data is read separately
         for desc in hd.descriptors {
-            ret.extend(Vec::<u8>::from(desc));
+            ret.push(desc.descriptor_type);
+            ret.extend(desc.length.to_le_bytes());
         }
 
         ret
@@ -1002,6 +1024,9 @@ pub struct PrinterDescriptor {
     pub length: u8,
     pub descriptor_type: u8,
     pub release_number: u8,
+    /// bcdNumDescriptors, which may be more than `descriptors` if the descriptor is too short for them
+    #[serde(default)]
+    pub num_descriptors: u8,
     pub descriptors: Vec<PrinterReportDescriptor>,
 }
 
@@ -1009,10 +1034,10 @@ impl TryFrom<&[u8]> for PrinterDescriptor {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> error::Result<Self> {
-        if value.len() < 5 {
+        if value.len() < 4 {
             return Err(Error::new_descriptor_len(
                 "PrinterDescriptor",
-                5,
+                4,
                 value.len(),
             ));
         }
@@ -1044,6 +1069,7 @@ impl TryFrom<&[u8]> for PrinterDescriptor {
             length: value[0],
             descriptor_type: value[1],
             release_number: value[2],
+            num_descriptors: value[3],
             descriptors,
         })
     }
@@ -1060,10 +1086,12 @@ impl TryFrom<GenericDescriptor> for PrinterDescriptor {
 
 impl From<PrinterDescriptor> for Vec<u8> {
     fn from(pd: PrinterDescriptor) -> Self {
-        let mut ret = Vec::new();
-        ret.push(pd.length);
-        ret.push(pd.descriptor_type);
-        ret.push(pd.release_number);
+        let mut ret = vec![
+            pd.length,
+            pd.descriptor_type,
+            pd.release_number,
+            pd.num_descriptors,
+        ];
         for desc in pd.descriptors {
             ret.extend(Vec::<u8>::from(desc));
         }
@@ -1097,9 +1125,10 @@ impl TryFrom<&[u8]> for PrinterReportDescriptor {
             ));
         }
 
+        // type before length, unlike standard descriptors
         Ok(PrinterReportDescriptor {
-            length: value[0],
-            descriptor_type: value[1],
+            descriptor_type: value[0],
+            length: value[1],
             capabilities: u16::from_le_bytes([value[2], value[3]]),
             versions_supported: value[4],
             uuid_string_index: value[5],
@@ -1117,6 +1146,9 @@ impl From<PrinterReportDescriptor> for Vec<u8> {
         ret.extend(prd.capabilities.to_le_bytes());
         ret.push(prd.versions_supported);
         ret.push(prd.uuid_string_index);
+        if let Some(data) = prd.data {
+            ret.extend(data);
+        }
 
         ret
     }
@@ -1184,6 +1216,31 @@ impl HubDescriptor {
     pub fn latency(&self) -> Option<u8> {
         self.data.first().copied()
     }
+
+    /// DeviceRemovable bitmap with a bit for each port from bit 1; bit 0 is reserved
+    ///
+    /// Follows the latency and delay fields for SuperSpeed hubs, otherwise one byte for every 8 ports. Shorter if the hub did not return the whole bitmap.
+    pub fn device_removable(&self) -> &[u8] {
+        let (offset, len) = if self.descriptor_type == u8::from(DescriptorType::SuperSpeedHub) {
+            (3, 2)
+        } else {
+            (0, (self.num_ports as usize >> 3) + 1)
+        };
+        let start = offset.min(self.data.len());
+        &self.data[start..(start + len).min(self.data.len())]
+    }
+
+    /// Whether the device on `port` can be removed from the hub according to the DeviceRemovable bitmap; a set bit is a non-removable, hard-wired device
+    ///
+    /// `None` if `port` is not a port of the hub or not in the bitmap.
+    pub fn is_port_removable(&self, port: u8) -> Option<bool> {
+        if port == 0 || port > self.num_ports {
+            return None;
+        }
+        self.device_removable()
+            .get(port as usize >> 3)
+            .map(|b| b & (1 << (port & 7)) == 0)
+    }
 }
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
@@ -1324,11 +1381,22 @@ impl TryFrom<&[u8]> for DeviceQualifierDescriptor {
             ));
         }
 
+        let device_class = BaseClass::from(value[4]);
+        if u8::from(device_class) != value[4] {
+            return Err(Error::new(
+                ErrorKind::InvalidDescriptor,
+                &format!(
+                    "Device Qualifier descriptor unknown class {:#04x}",
+                    value[4]
+                ),
+            ));
+        }
+
         Ok(DeviceQualifierDescriptor {
             length: value[0],
             descriptor_type: value[1],
             version: Version::from_bcd(u16::from_le_bytes([value[2], value[3]])),
-            device_class: BaseClass::from(value[4]),
+            device_class,
             device_subclass: value[5],
             device_protocol: value[6],
             max_packet_size: value[7],
@@ -1353,19 +1421,65 @@ impl From<DeviceQualifierDescriptor> for Vec<u8> {
     }
 }
 
+/// On-The-Go descriptor of a device which supports OTG, in the configuration extra bytes
+///
+/// OTG 1.3 descriptors are 3 bytes; OTG 2.0 adds `bcdOTG` and an ADP bit to `bmAttributes`.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 #[allow(missing_docs)]
 pub struct OnTheGoDescriptor {
     pub length: u8,
     pub descriptor_type: u8,
     pub attributes: u8,
+    /// OTG and EH supplement release number of OTG 2.0 descriptors
+    #[serde(default)]
+    pub otg_version: Option<Version>,
+}
+
+impl OnTheGoDescriptor {
+    /// Supports Session Request Protocol (SRP)
+    pub fn srp(&self) -> bool {
+        self.attributes & 0x01 != 0
+    }
+
+    /// Supports Host Negotiation Protocol (HNP)
+    pub fn hnp(&self) -> bool {
+        self.attributes & 0x02 != 0
+    }
+
+    /// Supports Attach Detection Protocol (ADP); OTG 2.0 only
+    pub fn adp(&self) -> bool {
+        self.otg_version.is_some() && self.attributes & 0x04 != 0
+    }
+
+    /// Names of the supported protocols
+    pub fn protocols(&self) -> Vec<&'static str> {
+        [
+            (self.srp(), "SRP"),
+            (self.hnp(), "HNP"),
+            (self.adp(), "ADP"),
+        ]
+        .into_iter()
+        .filter_map(|(supported, name)| supported.then_some(name))
+        .collect()
+    }
+}
+
+impl fmt::Display for OnTheGoDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let protocols = self.protocols();
+        if protocols.is_empty() {
+            write!(f, "none")
+        } else {
+            write!(f, "{}", protocols.join(" "))
+        }
+    }
 }
 
 impl TryFrom<&[u8]> for OnTheGoDescriptor {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> error::Result<Self> {
-        if value.len() != 3 {
+        if value.len() != 3 && value.len() != 5 {
             return Err(Error::new_descriptor_len(
                 "OnTheGoDescriptor",
                 3,
@@ -1384,12 +1498,553 @@ impl TryFrom<&[u8]> for OnTheGoDescriptor {
             length: value[0],
             descriptor_type: value[1],
             attributes: value[2],
+            otg_version: (value.len() == 5)
+                .then(|| Version::from_bcd(u16::from_le_bytes([value[3], value[4]]))),
         })
     }
 }
 
 impl From<OnTheGoDescriptor> for Vec<u8> {
     fn from(otg: OnTheGoDescriptor) -> Self {
-        vec![otg.length, otg.descriptor_type, otg.attributes]
+        let mut ret = vec![otg.length, otg.descriptor_type, otg.attributes];
+        if let Some(v) = otg.otg_version {
+            ret.extend(u16::from(v).to_le_bytes());
+        }
+        ret
+    }
+}
+
+/// Descriptor type of a class descriptor in the extra bytes of an interface
+///
+/// The class bit of CS_INTERFACE is masked so that it parses as an Interface descriptor; types other than Device or Interface are misplaced so forced to Interface like lsusb.
+pub(crate) fn interface_extra_descriptor_type(descriptor_type: u8) -> u8 {
+    match descriptor_type & !(0x01 << 5) {
+        t @ (0x01 | 0x04) => t,
+        _ => 0x04,
+    }
+}
+
+/// Descriptor type of a class descriptor in the extra bytes of an endpoint; CS_ENDPOINT is masked so that it parses as an Endpoint descriptor
+pub(crate) fn endpoint_extra_descriptor_type(descriptor_type: u8) -> u8 {
+    if descriptor_type == 0x25 {
+        descriptor_type & !(0x01 << 5)
+    } else {
+        descriptor_type
+    }
+}
+
+/// Iterate the descriptors in `data` by bLength, stopping at a bLength which is less than 2 or past the end
+pub(crate) fn iter_descriptors(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut offset = 0;
+    core::iter::from_fn(move || {
+        let length = *data.get(offset)? as usize;
+        if length < 2 || offset + length > data.len() {
+            return None;
+        }
+        let desc = &data[offset..offset + length];
+        offset += length;
+        Some(desc)
+    })
+}
+
+/// Configuration descriptor blob split into the descriptor of each level and the extra descriptors following it
+#[derive(Debug, Default)]
+pub(crate) struct SplitConfiguration<'a> {
+    pub config: &'a [u8],
+    pub extra: Vec<u8>,
+    pub interfaces: Vec<SplitInterface<'a>>,
+    /// Bytes which could not be split as a descriptor
+    pub trailing: &'a [u8],
+}
+
+/// Interface descriptor with its extra descriptors and endpoints split from a configuration descriptor blob
+#[derive(Debug, Default)]
+pub(crate) struct SplitInterface<'a> {
+    pub interface: &'a [u8],
+    pub extra: Vec<u8>,
+    /// Endpoint descriptor and the extra descriptors following it
+    pub endpoints: Vec<(&'a [u8], Vec<u8>)>,
+}
+
+/// Split a configuration descriptor blob by level
+///
+/// Descriptors which are not interface or endpoint descriptors are extra descriptors of the preceding descriptor, as with libusb. Every descriptor slice is at least the standard length so can be indexed without checks.
+pub(crate) fn split_configuration(data: &[u8]) -> error::Result<SplitConfiguration<'_>> {
+    let mut iter = iter_descriptors(data);
+    let config = iter
+        .next()
+        .filter(|d| d.len() >= 9 && d[1] == 0x02)
+        .ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidDescriptor,
+                "Configuration descriptor header missing or invalid",
+            )
+        })?;
+    let mut split = SplitConfiguration {
+        config,
+        ..Default::default()
+    };
+    let mut taken = config.len();
+
+    for desc in iter {
+        taken += desc.len();
+        match (desc[1], split.interfaces.last_mut()) {
+            (0x04, _) if desc.len() >= 9 => split.interfaces.push(SplitInterface {
+                interface: desc,
+                ..Default::default()
+            }),
+            (0x05, Some(i)) if desc.len() >= 7 => i.endpoints.push((desc, Vec::new())),
+            (_, Some(i)) => match i.endpoints.last_mut() {
+                Some((_, extra)) => extra.extend_from_slice(desc),
+                None => i.extra.extend_from_slice(desc),
+            },
+            (_, None) => split.extra.extend_from_slice(desc),
+        }
+    }
+    split.trailing = &data[taken..];
+
+    Ok(split)
+}
+
+/// A length or count declared in a configuration descriptor blob which does not match the descriptors it contains; see [`validate_configuration`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DescriptorWarning {
+    /// Byte offset in the configuration descriptor blob of the descriptor with the mismatch
+    pub offset: usize,
+    /// Type of the descriptor; `None` if there are too few bytes for one
+    pub descriptor_type: Option<DescriptorType>,
+    /// What does not match
+    pub message: String,
+}
+
+impl fmt::Display for DescriptorWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.descriptor_type.as_ref() {
+            Some(t) => write!(f, "{:?} at offset {}: {}", t, self.offset, self.message),
+            None => write!(f, "offset {}: {}", self.offset, self.message),
+        }
+    }
+}
+
+/// bLength of standard descriptors in a configuration with a fixed length; endpoints of audio class interfaces are 9 so also allowed
+fn standard_length(descriptor_type: u8) -> Option<usize> {
+    match descriptor_type {
+        0x02 | 0x04 => Some(9),
+        0x05 => Some(7),
+        0x0b => Some(8),
+        0x30 => Some(6),
+        _ => None,
+    }
+}
+
+/// Check the configuration descriptor blob `data`, as returned by GET_DESCRIPTOR with wTotalLength, against the lengths and counts it declares
+///
+/// The bLengths of the descriptors must sum to wTotalLength and equal the standard length for their type, the number of interfaces must match bNumInterfaces and the endpoints following each interface its bNumEndpoints. Hosts are lenient so a device with mismatches may still work but each parses them differently, so they are worth fixing in firmware.
+///
+/// ```
+/// use cyme::usb::descriptors::validate_configuration;
+///
+/// let mut config = vec![
+///     0x09, 0x02, 0x22, 0x00, 0x01, 0x01, 0x00, 0xa0, 0x32,
+///     0x09, 0x04, 0x00, 0x00, 0x01, 0x03, 0x01, 0x01, 0x00,
+///     0x09, 0x21, 0x11, 0x01, 0x00, 0x01, 0x22, 0x3f, 0x00,
+///     0x07, 0x05, 0x81, 0x03, 0x08, 0x00, 0x0a,
+/// ];
+/// assert!(validate_configuration(&config).is_empty());
+///
+/// // wTotalLength includes a descriptor the device did not return
+/// config[2] = 0x29;
+/// let warnings = validate_configuration(&config);
+/// assert_eq!(
+///     warnings[0].to_string(),
+///     "Config at offset 0: wTotalLength is 41 but 34 bytes were returned"
+/// );
+/// ```
+pub fn validate_configuration(data: &[u8]) -> Vec<DescriptorWarning> {
+    fn warning(offset: usize, descriptor_type: u8, message: String) -> DescriptorWarning {
+        DescriptorWarning {
+            offset,
+            descriptor_type: Some(DescriptorType::from(descriptor_type)),
+            message,
+        }
+    }
+
+    let config = match data.get(..9).filter(|d| d[1] == 0x02) {
+        Some(c) => c,
+        None => {
+            return vec![DescriptorWarning {
+                offset: 0,
+                descriptor_type: data.get(1).map(|&t| DescriptorType::from(t)),
+                message: String::from("Configuration descriptor header missing or invalid"),
+            }]
+        }
+    };
+    let total_length = u16::from_le_bytes([config[2], config[3]]) as usize;
+    let mut warnings = Vec::new();
+    let mut interfaces: Vec<u8> = Vec::new();
+    // offset, number, alt setting, bNumEndpoints and endpoints found of the interface being counted; found is a usize since a device can return any number of endpoint descriptors
+    let mut current: Option<(usize, u8, u8, u8, usize)> = None;
+    fn check_endpoints(
+        current: Option<(usize, u8, u8, u8, usize)>,
+        warnings: &mut Vec<DescriptorWarning>,
+    ) {
+        if let Some((offset, number, alt, declared, found)) =
+            current.filter(|c| c.3 as usize != c.4)
+        {
+            warnings.push(warning(
+                offset,
+                0x04,
+                format!(
+                    "interface {}.{} bNumEndpoints {} but {} endpoint descriptors follow",
+                    number, alt, declared, found
+                ),
+            ));
+        }
+    }
+
+    let mut offset = 0;
+    for desc in iter_descriptors(data) {
+        let descriptor_type = desc[1];
+        if let Some(expected) = standard_length(descriptor_type) {
+            if desc.len() != expected && !(descriptor_type == 0x05 && desc.len() == 9) {
+                warnings.push(warning(
+                    offset,
+                    descriptor_type,
+                    format!("bLength {} but should be {}", desc.len(), expected),
+                ));
+            }
+        }
+        match descriptor_type {
+            0x02 if offset > 0 => warnings.push(warning(
+                offset,
+                descriptor_type,
+                String::from("Configuration descriptor within the configuration"),
+            )),
+            0x04 if desc.len() >= 5 => {
+                check_endpoints(current.take(), &mut warnings);
+                if !interfaces.contains(&desc[2]) {
+                    interfaces.push(desc[2]);
+                }
+                current = Some((offset, desc[2], desc[3], desc[4], 0));
+            }
+            0x05 => match current.as_mut() {
+                Some(c) => c.4 += 1,
+                None => warnings.push(warning(
+                    offset,
+                    descriptor_type,
+                    String::from("endpoint before any interface"),
+                )),
+            },
+            _ => (),
+        }
+        offset += desc.len();
+    }
+    check_endpoints(current, &mut warnings);
+
+    if offset < data.len() {
+        warnings.push(DescriptorWarning {
+            offset,
+            descriptor_type: data.get(offset + 1).map(|&t| DescriptorType::from(t)),
+            message: format!(
+                "bLength {} is invalid with {} bytes remaining so they cannot be parsed",
+                data[offset],
+                data.len() - offset
+            ),
+        });
+    }
+    if data.len() != total_length {
+        warnings.push(warning(
+            0,
+            0x02,
+            format!(
+                "wTotalLength is {} but {} bytes were returned",
+                total_length,
+                data.len()
+            ),
+        ));
+    }
+    if interfaces.len() != config[4] as usize {
+        warnings.push(warning(
+            0,
+            0x02,
+            format!(
+                "bNumInterfaces {} but {} interfaces",
+                config[4],
+                interfaces.len()
+            ),
+        ));
+    }
+
+    warnings
+}
+
+/// [`Configuration`] parsed by [`parse_configuration`] with the errors found in its descriptors
+#[derive(Debug)]
+pub struct ParsedConfiguration {
+    /// Configuration with the descriptors which could be parsed
+    pub configuration: Configuration,
+    /// Errors with descriptors which were skipped or could not be fully parsed
+    pub errors: Vec<Error>,
+}
+
+impl ParsedConfiguration {
+    /// Whether every descriptor was parsed without error
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// How descriptors which fail to parse are handled
+///
+/// Lenient parsing records the error and continues with the descriptors which could be parsed, so a device with a malformed descriptor is still profiled. Strict parsing returns the error instead; for validating device firmware, where tolerating malformed descriptors hides bugs.
+#[derive(Debug, Default, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParseMode {
+    /// Record errors and continue; the default
+    #[default]
+    Lenient,
+    /// Return the first error
+    Strict,
+}
+
+impl ParseMode {
+    /// Whether `error` should be returned rather than recorded: strict and an error with the descriptor itself, not one reading it such as a stall
+    pub fn is_fatal(self, error: &Error) -> bool {
+        self == ParseMode::Strict
+            && matches!(
+                error.kind,
+                ErrorKind::InvalidDescriptor | ErrorKind::DescriptorLength(_)
+            )
+    }
+
+    /// `result` as an `Option`, returning the error if [`Self::is_fatal`]; other errors are `None` and should already have been recorded or logged
+    ///
+    /// ```
+    /// use cyme::error::{Error, ErrorKind};
+    /// use cyme::usb::descriptors::ParseMode;
+    ///
+    /// let malformed = || Err::<(), _>(Error::new(ErrorKind::InvalidDescriptor, "bad bLength"));
+    /// assert_eq!(ParseMode::Lenient.tolerate(malformed()), Ok(None));
+    /// assert!(ParseMode::Strict.tolerate(malformed()).is_err());
+    /// let stall = Err::<(), _>(Error::new(ErrorKind::TransferStall, "stall"));
+    /// assert_eq!(ParseMode::Strict.tolerate(stall), Ok(None));
+    /// ```
+    pub fn tolerate<T>(self, result: error::Result<T>) -> error::Result<Option<T>> {
+        match result {
+            Ok(v) => Ok(Some(v)),
+            Err(e) if self.is_fatal(&e) => Err(e),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// Parse the extra descriptors in `data`, all of which are well formed from [`split_configuration`], with `descriptor_type` mapping the type before parsing
+fn parse_extra_descriptors(
+    data: &[u8],
+    class_code: Option<ClassCodeTriplet<u8>>,
+    descriptor_type: fn(u8) -> u8,
+    context: &str,
+    errors: &mut Vec<Error>,
+) -> Vec<Descriptor> {
+    let mut ret = Vec::new();
+
+    for desc in iter_descriptors(data) {
+        let mut desc = desc.to_vec();
+        desc[1] = descriptor_type(desc[1]);
+        match Descriptor::try_from(desc.as_slice()) {
+            Ok(mut d) => {
+                if let Some(class_code) = class_code {
+                    if let Err(e) = d.update_with_class_context(class_code) {
+                        errors.push(Error::new(
+                            e.kind(),
+                            &format!("{} class descriptor {:02x?}: {}", context, desc, e),
+                        ));
+                    }
+                }
+                ret.push(d);
+            }
+            Err(e) => errors.push(Error::new(
+                e.kind(),
+                &format!("{} descriptor {:02x?}: {}", context, desc, e),
+            )),
+        }
+    }
+
+    ret
+}
+
+/// Parse a whole configuration descriptor blob, as returned by GET_DESCRIPTOR with wTotalLength, into a [`Configuration`]
+///
+/// Parsing is defensive so that descriptors captured from untrusted devices can be parsed: only a missing or invalid configuration descriptor header is an error. Malformed lengths end parsing of the blob and descriptors which fail to parse are skipped, with the errors accumulated in [`ParsedConfiguration::errors`].
+///
+/// Strings are not available without the device so names are empty, only the string indexes are set. The interface paths are relative to a device at bus 0 with no ports; use [`Interface::path`] with the device location to set them.
+///
+/// ```
+/// use cyme::usb::{descriptors::parse_configuration, BaseClass, ClassDescriptor, Descriptor};
+///
+/// let config = [
+///     0x09, 0x02, 0x22, 0x00, 0x01, 0x01, 0x00, 0xa0, 0x32,
+///     0x09, 0x04, 0x00, 0x00, 0x01, 0x03, 0x01, 0x01, 0x00,
+///     0x09, 0x21, 0x11, 0x01, 0x00, 0x01, 0x22, 0x3f, 0x00,
+///     0x07, 0x05, 0x81, 0x03, 0x08, 0x00, 0x0a,
+/// ];
+/// let parsed = parse_configuration(&config).unwrap();
+/// assert!(parsed.is_ok());
+/// let interface = &parsed.configuration.interfaces[0];
+/// assert_eq!(interface.class, BaseClass::Hid);
+/// assert!(matches!(
+///     interface.extra.as_ref().unwrap()[0],
+///     Descriptor::Device(ClassDescriptor::Hid(_))
+/// ));
+/// assert_eq!(interface.endpoints[0].max_packet_size, 8);
+///
+/// // truncated blob is parsed up to the malformed descriptor
+/// let parsed = parse_configuration(&config[..30]).unwrap();
+/// assert!(!parsed.is_ok());
+/// assert!(parsed.configuration.interfaces[0].endpoints.is_empty());
+/// ```
+pub fn parse_configuration(data: &[u8]) -> error::Result<ParsedConfiguration> {
+    let split = split_configuration(data)?;
+    let mut errors = Vec::new();
+    let desc = split.config;
+
+    let total_length = u16::from_le_bytes([desc[2], desc[3]]);
+    if total_length as usize != data.len() {
+        errors.push(Error::new(
+            ErrorKind::InvalidDescriptor,
+            &format!(
+                "Configuration wTotalLength {} does not match {} bytes",
+                total_length,
+                data.len()
+            ),
+        ));
+    }
+    if !split.trailing.is_empty() {
+        errors.push(Error::new(
+            ErrorKind::InvalidDescriptor,
+            &format!(
+                "Malformed descriptor at offset {}, ignoring {} trailing bytes",
+                data.len() - split.trailing.len(),
+                split.trailing.len()
+            ),
+        ));
+    }
+
+    let mut attributes = Vec::new();
+    if desc[7] & 0x10 != 0 {
+        attributes.push(ConfigAttributes::BatteryPowered);
+    }
+    if desc[7] & 0x20 != 0 {
+        attributes.push(ConfigAttributes::RemoteWakeup);
+    }
+    if desc[7] & 0x40 != 0 {
+        attributes.push(ConfigAttributes::SelfPowered);
+    }
+
+    let extra = parse_extra_descriptors(
+        &split.extra,
+        None,
+        core::convert::identity,
+        "Configuration",
+        &mut errors,
+    );
+
+    let mut interfaces = Vec::with_capacity(split.interfaces.len());
+    for i in split.interfaces.iter() {
+        let d = i.interface;
+        let class_code = (d[5], d[6], d[7]);
+        let context = format!("Interface {}.{}", d[2], d[3]);
+
+        let endpoints = i
+            .endpoints
+            .iter()
+            .map(|(e, extra)| Endpoint {
+                address: EndpointAddress::from(e[2]),
+                transfer_type: TransferType::from(e[3]),
+                sync_type: SyncType::from(e[3]),
+                usage_type: UsageType::from(e[3]),
+                max_packet_size: u16::from_le_bytes([e[4], e[5]]),
+                interval: e[6],
+                length: e[0],
+                extra: Some(parse_extra_descriptors(
+                    extra,
+                    Some(class_code),
+                    endpoint_extra_descriptor_type,
+                    &format!("{} endpoint {:#04x}", context, e[2]),
+                    &mut errors,
+                )),
+                raw: None,
+            })
+            .collect();
+
+        interfaces.push(Interface {
+            name: None,
+            string_index: d[8],
+            number: d[2],
+            path: get_interface_path(0, &[], desc[5], d[2]),
+            class: BaseClass::from(d[5]),
+            sub_class: d[6],
+            protocol: d[7],
+            alt_setting: d[3],
+            driver: None,
+            syspath: None,
+            endpoints,
+            length: d[0],
+            extra: Some(parse_extra_descriptors(
+                &i.extra,
+                Some(class_code),
+                interface_extra_descriptor_type,
+                &context,
+                &mut errors,
+            )),
+            bluetooth: None,
+            module: None,
+            vendor_fields: None,
+            raw: None,
+        });
+    }
+
+    Ok(ParsedConfiguration {
+        configuration: Configuration {
+            name: String::new(),
+            string_index: desc[6],
+            number: desc[5],
+            interfaces,
+            attributes,
+            max_power: NumericalUnit {
+                // bMaxPower is in 2mA units
+                value: desc[8] as u32 * 2,
+                unit: String::from("mA"),
+                description: None,
+            },
+            length: desc[0],
+            total_length,
+            extra: Some(extra),
+            raw: None,
+            active: false,
+            warnings: validate_configuration(data),
+        },
+        errors,
+    })
+}
+
+/// [`parse_configuration`] with `mode`: strict returns the first error in [`ParsedConfiguration::errors`], so a parsed configuration has none
+///
+/// ```
+/// use cyme::usb::descriptors::{parse_configuration_with, ParseMode};
+///
+/// // wTotalLength of 0x22 but only the configuration descriptor
+/// let config = [0x09, 0x02, 0x22, 0x00, 0x00, 0x01, 0x00, 0xa0, 0x32];
+/// assert!(parse_configuration_with(&config, ParseMode::Lenient).is_ok());
+/// assert!(parse_configuration_with(&config, ParseMode::Strict).is_err());
+/// ```
+pub fn parse_configuration_with(
+    data: &[u8],
+    mode: ParseMode,
+) -> error::Result<ParsedConfiguration> {
+    let mut parsed = parse_configuration(data)?;
+    if mode == ParseMode::Strict && !parsed.errors.is_empty() {
+        return Err(parsed.errors.remove(0));
     }
+    Ok(parsed)
 }
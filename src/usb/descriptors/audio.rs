@@ -1,7 +1,7 @@
 //! Defines for the USB Audio Class (UAC) interface descriptors and MIDI
+use core::convert::TryFrom;
+use core::fmt;
 use serde::{Deserialize, Serialize};
-use std::convert::TryFrom;
-use std::fmt;
 use strum::VariantArray;
 use strum_macros::VariantArray;
 
@@ -92,6 +92,13 @@ impl TryFrom<&[u8]> for MidiDescriptor {
 
         let descriptor_type = DescriptorType::from(value[1]);
         let descriptor_subtype = MidiSubtype::from(value[2]);
+        // left as a GenericDescriptor rather than losing the subtype
+        if u8::from(descriptor_subtype.to_owned()) != value[2] {
+            return Err(Error::new(
+                ErrorKind::InvalidDescriptor,
+                &format!("Unknown MidiDescriptor subtype {:#04x}", value[2]),
+            ));
+        }
         let interface = MidiInterfaceDescriptor::from_midi_descriptor(
             &descriptor_type,
             &descriptor_subtype,
@@ -287,8 +294,8 @@ impl TryFrom<&[u8]> for Element {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        if value.len() < 8 {
-            return Err(Error::new_descriptor_len("Element", 8, value.len()));
+        if value.len() < 7 {
+            return Err(Error::new_descriptor_len("Element", 7, value.len()));
         }
 
         let element_id = value[0];
@@ -320,6 +327,13 @@ impl TryFrom<&[u8]> for Element {
                 "Element descriptor reported caps size too long for buffer",
             ));
         }
+        // element_caps is u16, which covers the capabilities defined by the spec
+        if capsize > 2 {
+            return Err(Error::new(
+                ErrorKind::InvalidDescriptor,
+                "Element descriptor reported caps size larger than bmElementCaps",
+            ));
+        }
 
         let mut element_caps: u16 = 0;
         for i in 0..capsize {
@@ -1088,6 +1102,89 @@ impl UacInterfaceDescriptor {
         }
     }
 
+    /// Get the UAC2/UAC3 controls present in the descriptor bmControls, decoded with [`decode_bm_controls`]
+    ///
+    /// UAC1 descriptors use one bit per control without a setting so return an empty list, as do descriptors without bmControls. Only the master channel is decoded for Feature Units.
+    pub fn controls(&self) -> Vec<Control> {
+        match self {
+            UacInterfaceDescriptor::Header2(d) => {
+                decode_bm_controls(d.controls, &UAC2_INTERFACE_HEADER_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::Header3(d) => {
+                decode_bm_controls(d.controls, &UAC2_INTERFACE_HEADER_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::InputTerminal2(d) => {
+                decode_bm_controls(d.controls, &UAC2_INPUT_TERMINAL_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::InputTerminal3(d) => {
+                decode_bm_controls(d.controls, &UAC3_INPUT_TERMINAL_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::OutputTerminal2(d) => {
+                decode_bm_controls(d.controls, &UAC2_OUTPUT_TERMINAL_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::OutputTerminal3(d) => {
+                decode_bm_controls(d.controls, &UAC3_OUTPUT_TERMINAL_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::MixerUnit2(d) => {
+                decode_bm_controls(d.controls, &UAC2_MIXER_UNIT_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::MixerUnit3(d) => {
+                decode_bm_controls(d.controls, &UAC3_MIXER_UNIT_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::SelectorUnit2(d) => {
+                decode_bm_controls(d.controls, &UAC2_SELECTOR_UNIT_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::SelectorUnit3(d) => {
+                decode_bm_controls(d.controls, &UAC2_SELECTOR_UNIT_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::FeatureUnit2(d) => decode_bm_controls(
+                u32::from_le_bytes(d.controls),
+                &UAC2_FEATURE_UNIT_BMCONTROLS,
+            ),
+            UacInterfaceDescriptor::FeatureUnit3(d) => decode_bm_controls(
+                u32::from_le_bytes(d.controls),
+                &UAC2_FEATURE_UNIT_BMCONTROLS,
+            ),
+            UacInterfaceDescriptor::ExtensionUnit2(d) => {
+                decode_bm_controls(d.controls, &UAC2_EXTENSION_UNIT_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::ExtensionUnit3(d) => {
+                decode_bm_controls(d.controls, &UAC3_EXTENSION_UNIT_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::ClockSource2(d) => {
+                decode_bm_controls(d.controls, &UAC2_CLOCK_SOURCE_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::ClockSource3(d) => {
+                decode_bm_controls(d.controls, &UAC2_CLOCK_SOURCE_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::ClockSelector2(d) => {
+                decode_bm_controls(d.controls, &UAC2_CLOCK_SELECTOR_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::ClockSelector3(d) => {
+                decode_bm_controls(d.controls, &UAC2_CLOCK_SELECTOR_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::ClockMultiplier2(d) => {
+                decode_bm_controls(d.controls, &UAC2_CLOCK_MULTIPLIER_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::ClockMultiplier3(d) => {
+                decode_bm_controls(d.controls, &UAC2_CLOCK_MULTIPLIER_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::StreamingInterface2(d) => {
+                decode_bm_controls(d.controls, &UAC2_AS_INTERFACE_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::StreamingInterface3(d) => {
+                decode_bm_controls(d.controls, &UAC3_AS_INTERFACE_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::DatastreamingEndpoint2(d) => {
+                decode_bm_controls(d.controls, &UAC2_AS_ISO_ENDPOINT_BMCONTROLS)
+            }
+            UacInterfaceDescriptor::DataStreamingEndpoint3(d) => {
+                decode_bm_controls(d.controls, &UAC2_AS_ISO_ENDPOINT_BMCONTROLS)
+            }
+            _ => Vec::new(),
+        }
+    }
+
     /// Get USB Audio Device Class channel names from the descriptor "wChannelConfig" field bitmap string based on the protocol
     pub fn get_channel_name_strings<T: Into<u32> + Copy>(
         protocol: &UacProtocol,
@@ -1204,8 +1301,8 @@ impl From<UacProtocol> for u8 {
     }
 }
 
-impl std::fmt::Display for UacProtocol {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for UacProtocol {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         match self {
             UacProtocol::Uac1 => write!(f, "UAC1"),
             UacProtocol::Uac2 => write!(f, "UAC2"),
@@ -1333,8 +1430,8 @@ pub enum ControlSubtype {
     PowerDomain = 0x10,
 }
 
-impl std::fmt::Display for ControlSubtype {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for ControlSubtype {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         if f.alternate() {
             // uppercase with _ instead of space for lsusb dump
             match self {
@@ -1556,6 +1653,7 @@ pub enum StreamingFormatInterface {
     FormatTypeI2(FormatTypeI2),
     FormatTypeII2(FormatTypeII2),
     FormatTypeIII2(FormatTypeIII2),
+    FormatTypeIV2,
     FormatSpecificMpeg(FormatSpecificMpeg),
     FormatSpecificAc3(FormatSpecificAc3),
     Invalid(Vec<u8>),
@@ -1571,6 +1669,7 @@ impl From<StreamingFormatInterface> for Vec<u8> {
             StreamingFormatInterface::FormatTypeI2(ft) => ft.into(),
             StreamingFormatInterface::FormatTypeII2(ft) => ft.into(),
             StreamingFormatInterface::FormatTypeIII2(ft) => ft.into(),
+            StreamingFormatInterface::FormatTypeIV2 => Vec::new(),
             StreamingFormatInterface::FormatSpecificMpeg(ft) => ft.into(),
             StreamingFormatInterface::FormatSpecificAc3(ft) => ft.into(),
             StreamingFormatInterface::Invalid(data) => data,
@@ -1638,6 +1737,11 @@ impl StreamingFormat {
                         interface: StreamingFormatInterface::FormatTypeIII2(ft),
                     })
                 }
+                // Type IV is used for the USB side of an external (analogue/digital) connection and has no fields beyond the format type
+                StreamingFormatType::TypeIV => Ok(StreamingFormat {
+                    format_type,
+                    interface: StreamingFormatInterface::FormatTypeIV2,
+                }),
                 _ => Ok(StreamingFormat {
                     format_type,
                     interface: StreamingFormatInterface::Undefined(data[1..].to_vec()),
@@ -1712,6 +1816,19 @@ impl From<StreamingFormatSpecific> for Vec<u8> {
     }
 }
 
+/// UAC1 Type I format descriptor with discrete or continuous sample frequencies
+///
+/// Sample frequencies are 3 bytes each; continuous is a lower and upper bound:
+///
+/// ```
+/// use cyme::usb::descriptors::audio::*;
+///
+/// let data = [0x02, 0x02, 0x10, 0x02, 0x44, 0xac, 0x00, 0x80, 0xbb, 0x00];
+/// let ft = FormatTypeI1::try_from(&data[..]).unwrap();
+/// assert_eq!(ft.sample_frequency_type, SampleFrequencyType::Discrete(2));
+/// assert_eq!(ft.sample_frequencies, vec![44100, 48000]);
+/// assert_eq!(Vec::<u8>::from(ft), data);
+/// ```
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 #[allow(missing_docs)]
 pub struct FormatTypeI1 {
@@ -1781,7 +1898,7 @@ impl From<FormatTypeI1> for Vec<u8> {
         let mut data = vec![ft.num_channels, ft.subframe_size, ft.bit_resolution];
         data.push(ft.sample_frequency_type.into());
         for sf in ft.sample_frequencies {
-            data.extend_from_slice(&sf.to_le_bytes());
+            data.extend_from_slice(&sf.to_le_bytes()[..3]);
         }
         data
     }
@@ -1855,7 +1972,7 @@ impl From<FormatTypeII1> for Vec<u8> {
         data.extend_from_slice(&ft.samples_per_frame.to_le_bytes());
         data.push(ft.sample_frequency_type.into());
         for sf in ft.sample_frequencies {
-            data.extend_from_slice(&sf.to_le_bytes());
+            data.extend_from_slice(&sf.to_le_bytes()[..3]);
         }
         data
     }
@@ -1875,7 +1992,7 @@ impl TryFrom<&[u8]> for FormatTypeIII1 {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        let sam_freq_type: SampleFrequencyType = match value.get(4) {
+        let sam_freq_type: SampleFrequencyType = match value.get(3) {
             Some(&sam_freq_type) => sam_freq_type.into(),
             None => return Err(Error::new_descriptor_len("FormatTypeIII", 4, value.len())),
         };
@@ -1930,7 +2047,7 @@ impl From<FormatTypeIII1> for Vec<u8> {
         let mut data = vec![ft.num_channels, ft.subframe_size, ft.bit_resolution];
         data.push(ft.sample_frequency_type.into());
         for sf in ft.sample_frequencies {
-            data.extend_from_slice(&sf.to_le_bytes());
+            data.extend_from_slice(&sf.to_le_bytes()[..3]);
         }
         data
     }
@@ -2027,8 +2144,8 @@ impl TryFrom<&[u8]> for FormatSpecificMpeg {
 impl From<FormatSpecificMpeg> for Vec<u8> {
     fn from(ft: FormatSpecificMpeg) -> Vec<u8> {
         let mut data = Vec::new();
-        data.push(ft.mpeg_features);
         data.extend_from_slice(&ft.mpeg_capabilities.to_le_bytes());
+        data.push(ft.mpeg_features);
         data
     }
 }
@@ -2068,6 +2185,170 @@ impl From<FormatSpecificAc3> for Vec<u8> {
     }
 }
 
+/// Summary of the sample rates, bit depths and channel counts supported by a UAC streaming interface
+///
+/// Built from the AS interface and format type descriptors with [`AudioFormatSummary::from_descriptors`]. UAC2/3 sample rates are provided by the clock entity at runtime rather than descriptors so will normally be empty.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AudioFormatSummary {
+    /// Discrete sample frequencies in Hz
+    pub sample_rates: Vec<u32>,
+    /// Continuous sample frequency range (min, max) in Hz
+    pub sample_rate_range: Option<(u32, u32)>,
+    /// Bit resolutions
+    pub bit_depths: Vec<u8>,
+    /// Number of channels
+    pub channels: Vec<u8>,
+}
+
+fn format_sample_rate(hz: u32) -> String {
+    if hz.is_multiple_of(1000) {
+        format!("{}kHz", hz / 1000)
+    } else if hz >= 1000 {
+        format!("{:.1}kHz", hz as f32 / 1000.0)
+    } else {
+        format!("{}Hz", hz)
+    }
+}
+
+/// Displayed in the form '48kHz/96kHz 24-bit 2ch'
+impl fmt::Display for AudioFormatSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = Vec::new();
+        if let Some((min, max)) = self.sample_rate_range {
+            parts.push(format!(
+                "{}-{}",
+                format_sample_rate(min),
+                format_sample_rate(max)
+            ));
+        } else if !self.sample_rates.is_empty() {
+            parts.push(
+                self.sample_rates
+                    .iter()
+                    .map(|r| format_sample_rate(*r))
+                    .collect::<Vec<String>>()
+                    .join("/"),
+            );
+        }
+        if !self.bit_depths.is_empty() {
+            parts.push(format!(
+                "{}-bit",
+                self.bit_depths
+                    .iter()
+                    .map(|b| b.to_string())
+                    .collect::<Vec<String>>()
+                    .join("/")
+            ));
+        }
+        if !self.channels.is_empty() {
+            parts.push(format!(
+                "{}ch",
+                self.channels
+                    .iter()
+                    .map(|c| c.to_string())
+                    .collect::<Vec<String>>()
+                    .join("/")
+            ));
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl AudioFormatSummary {
+    /// Build the summary from the UAC interface descriptors of a streaming interface; None if there is no format information
+    ///
+    /// ```
+    /// use cyme::usb::audio::*;
+    ///
+    /// let format = UacInterfaceDescriptor::StreamingFormat(StreamingFormat {
+    ///     format_type: StreamingFormatType::TypeI,
+    ///     interface: StreamingFormatInterface::FormatTypeI1(FormatTypeI1 {
+    ///         num_channels: 2,
+    ///         subframe_size: 3,
+    ///         bit_resolution: 24,
+    ///         sample_frequency_type: SampleFrequencyType::Discrete(3),
+    ///         sample_frequencies: vec![44100, 48000, 96000],
+    ///     }),
+    /// });
+    /// let summary = AudioFormatSummary::from_descriptors([&format]).unwrap();
+    /// assert_eq!(summary.to_string(), "44.1kHz/48kHz/96kHz 24-bit 2ch");
+    /// ```
+    pub fn from_descriptors<'a>(
+        descriptors: impl IntoIterator<Item = &'a UacInterfaceDescriptor>,
+    ) -> Option<Self> {
+        let mut summary = AudioFormatSummary::default();
+
+        fn add_frequencies(
+            summary: &mut AudioFormatSummary,
+            sft: &SampleFrequencyType,
+            freqs: &[u32],
+        ) {
+            match sft {
+                SampleFrequencyType::Continuous if freqs.len() >= 2 => {
+                    summary.sample_rate_range = Some((freqs[0], freqs[1]))
+                }
+                SampleFrequencyType::Continuous => (),
+                SampleFrequencyType::Discrete(_) => summary.sample_rates.extend(freqs),
+            }
+        }
+
+        for d in descriptors {
+            match d {
+                UacInterfaceDescriptor::StreamingInterface2(si) => {
+                    summary.channels.push(si.nr_channels)
+                }
+                UacInterfaceDescriptor::StreamingInterface3(si) => {
+                    summary.bit_depths.push(si.bit_resolution)
+                }
+                UacInterfaceDescriptor::StreamingFormat(sf) => match &sf.interface {
+                    StreamingFormatInterface::FormatTypeI1(ft) => {
+                        summary.channels.push(ft.num_channels);
+                        summary.bit_depths.push(ft.bit_resolution);
+                        add_frequencies(
+                            &mut summary,
+                            &ft.sample_frequency_type,
+                            &ft.sample_frequencies,
+                        );
+                    }
+                    StreamingFormatInterface::FormatTypeIII1(ft) => {
+                        summary.channels.push(ft.num_channels);
+                        summary.bit_depths.push(ft.bit_resolution);
+                        add_frequencies(
+                            &mut summary,
+                            &ft.sample_frequency_type,
+                            &ft.sample_frequencies,
+                        );
+                    }
+                    StreamingFormatInterface::FormatTypeII1(ft) => add_frequencies(
+                        &mut summary,
+                        &ft.sample_frequency_type,
+                        &ft.sample_frequencies,
+                    ),
+                    StreamingFormatInterface::FormatTypeI2(ft)
+                    | StreamingFormatInterface::FormatTypeIII2(ft) => {
+                        summary.bit_depths.push(ft.bit_resolution)
+                    }
+                    _ => (),
+                },
+                _ => (),
+            }
+        }
+
+        for v in [&mut summary.bit_depths, &mut summary.channels] {
+            v.sort_unstable();
+            v.dedup();
+        }
+        summary.sample_rates.sort_unstable();
+        summary.sample_rates.dedup();
+
+        if summary == AudioFormatSummary::default() {
+            None
+        } else {
+            Some(summary)
+        }
+    }
+}
+
 /// The control setting for a UAC bmControls byte
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 #[repr(u8)]
@@ -2109,6 +2390,150 @@ pub enum ControlType {
     BmControl2,
 }
 
+/// A named control and its [`ControlSetting`] decoded from a UAC2/UAC3 bmControls bitmap
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct Control {
+    /// Name of the control such as 'Mute'
+    pub name: &'static str,
+    /// Whether the control is read-only or read/write
+    pub setting: ControlSetting,
+}
+
+impl fmt::Display for Control {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.setting)
+    }
+}
+
+/// Decode a UAC2/UAC3 bmControls bitmap with two bits per control into the controls that are present
+///
+/// `names` are the control names in bit pair order, such as [`UAC2_FEATURE_UNIT_BMCONTROLS`]. Controls with setting 0b00 are not present so are skipped.
+///
+/// ```
+/// use cyme::usb::descriptors::audio::*;
+///
+/// let controls = decode_bm_controls(0b0111u32, &UAC2_FEATURE_UNIT_BMCONTROLS);
+/// assert_eq!(
+///     controls.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(", "),
+///     "Mute: read/write, Volume: read-only"
+/// );
+/// ```
+pub fn decode_bm_controls<T: Into<u32>>(controls: T, names: &[&'static str]) -> Vec<Control> {
+    let controls: u32 = controls.into();
+    names
+        .iter()
+        .enumerate()
+        .filter_map(|(index, &name)| {
+            let c = ((controls >> (index * 2)) & 0x3) as u8;
+            (c != 0).then(|| Control {
+                name,
+                setting: ControlSetting::from(c),
+            })
+        })
+        .collect()
+}
+
+/// UAC2/UAC3 AC interface header bmControls names
+pub const UAC2_INTERFACE_HEADER_BMCONTROLS: [&str; 1] = ["Legacy"];
+/// UAC2 Input Terminal bmControls names
+pub const UAC2_INPUT_TERMINAL_BMCONTROLS: [&str; 6] = [
+    "Copy Protect",
+    "Connector",
+    "Overload",
+    "Cluster",
+    "Underflow",
+    "Overflow",
+];
+/// UAC3 Input Terminal bmControls names
+pub const UAC3_INPUT_TERMINAL_BMCONTROLS: [&str; 5] = [
+    "Insertion",
+    "Overload",
+    "Underflow",
+    "Overflow",
+    "Underflow",
+];
+/// UAC2 Output Terminal bmControls names
+pub const UAC2_OUTPUT_TERMINAL_BMCONTROLS: [&str; 5] = [
+    "Copy Protect",
+    "Connector",
+    "Overload",
+    "Underflow",
+    "Overflow",
+];
+/// UAC3 Output Terminal bmControls names
+pub const UAC3_OUTPUT_TERMINAL_BMCONTROLS: [&str; 4] =
+    ["Insertion", "Overload", "Underflow", "Overflow"];
+/// UAC2 AS interface bmControls names
+pub const UAC2_AS_INTERFACE_BMCONTROLS: [&str; 2] =
+    ["Active Alternate Setting", "Valid Alternate Setting"];
+/// UAC3 AS interface bmControls names
+pub const UAC3_AS_INTERFACE_BMCONTROLS: [&str; 3] = [
+    "Active Alternate Setting",
+    "Valid Alternate Setting",
+    "Audio Data Format Control",
+];
+/// UAC2/UAC3 AS isochronous data endpoint bmControls names
+pub const UAC2_AS_ISO_ENDPOINT_BMCONTROLS: [&str; 3] = ["Pitch", "Data Overrun", "Data Underrun"];
+/// UAC2 Mixer Unit bmControls names
+pub const UAC2_MIXER_UNIT_BMCONTROLS: [&str; 4] = ["Cluster", "Underflow", "Overflow", "Overflow"];
+/// UAC3 Mixer Unit bmControls names
+pub const UAC3_MIXER_UNIT_BMCONTROLS: [&str; 2] = ["Underflow", "Overflow"];
+/// UAC2/UAC3 Selector Unit bmControls names
+pub const UAC2_SELECTOR_UNIT_BMCONTROLS: [&str; 1] = ["Selector"];
+/// UAC1 Feature Unit; one bit per control bmControls names
+pub const UAC1_FEATURE_UNIT_BMCONTROLS: [&str; 13] = [
+    "Mute",
+    "Volume",
+    "Bass",
+    "Mid",
+    "Treble",
+    "Graphic Equalizer",
+    "Automatic Gain",
+    "Delay",
+    "Bass Boost",
+    "Loudness",
+    "Input gain",
+    "Input gain pad",
+    "Phase invert",
+];
+/// UAC2/UAC3 Feature Unit bmaControls names
+pub const UAC2_FEATURE_UNIT_BMCONTROLS: [&str; 15] = [
+    "Mute",
+    "Volume",
+    "Bass",
+    "Mid",
+    "Treble",
+    "Graphic Equalizer",
+    "Automatic Gain",
+    "Delay",
+    "Bass Boost",
+    "Loudness",
+    "Input gain",
+    "Input gain pad",
+    "Phase invert",
+    "Underflow",
+    "Overflow",
+];
+/// UAC2 Extension Unit bmControls names
+pub const UAC2_EXTENSION_UNIT_BMCONTROLS: [&str; 4] =
+    ["Enable", "Cluster", "Underflow", "Overflow"];
+/// UAC3 Extension Unit bmControls names
+pub const UAC3_EXTENSION_UNIT_BMCONTROLS: [&str; 2] = ["Underflow", "Overflow"];
+/// UAC2/UAC3 Clock Source bmControls names
+pub const UAC2_CLOCK_SOURCE_BMCONTROLS: [&str; 2] = ["Clock Frequency", "Clock Validity"];
+/// UAC2/UAC3 Clock Selector bmControls names
+pub const UAC2_CLOCK_SELECTOR_BMCONTROLS: [&str; 1] = ["Clock Selector"];
+/// UAC2/UAC3 Clock Multiplier bmControls names
+pub const UAC2_CLOCK_MULTIPLIER_BMCONTROLS: [&str; 2] = ["Clock Numerator", "Clock Denominator"];
+/// UAC3 Up/Down-mix Processing Unit bmControls names
+pub const UAC3_PROCESSING_UNIT_UP_DOWN_BMCONTROLS: [&str; 3] =
+    ["Mode Select", "Underflow", "Overflow"];
+/// UAC3 Stereo Extender Processing Unit bmControls names
+pub const UAC3_PROCESSING_UNIT_STEREO_EXTENDER_BMCONTROLS: [&str; 3] =
+    ["Width", "Underflow", "Overflow"];
+/// UAC3 Multi-Function Processing Unit bmControls names
+pub const UAC3_PROCESSING_UNIT_MULTI_FUNC_BMCONTROLS: [&str; 2] = ["Underflow", "Overflow"];
+
 /// UAC1: 4.3.2 Class-Specific AC Interface Descriptor; Table 4-2.
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 #[allow(missing_docs)]
@@ -2792,19 +3217,17 @@ impl TryFrom<&[u8]> for MixerUnit3 {
             ));
         }
 
+        // bmMixerControls is variable length, filling the descriptor up to bmControls and wMixerDescrStr
+        let n = value.len() - 6;
+
         Ok(MixerUnit3 {
             unit_id: value[0],
             nr_in_pins: value[1],
             source_ids: value[2..2 + nr_in_pins].to_vec(),
             cluster_descr_id: u16::from_le_bytes([value[2 + nr_in_pins], value[3 + nr_in_pins]]),
-            mixer_controls: value[4 + nr_in_pins..4 + nr_in_pins + 1].to_vec(),
-            controls: u32::from_le_bytes([
-                value[5 + nr_in_pins],
-                value[6 + nr_in_pins],
-                value[7 + nr_in_pins],
-                value[8 + nr_in_pins],
-            ]),
-            mixer_descr_str: u16::from_le_bytes([value[9 + nr_in_pins], value[10 + nr_in_pins]]),
+            mixer_controls: value[4 + nr_in_pins..n].to_vec(),
+            controls: u32::from_le_bytes([value[n], value[n + 1], value[n + 2], value[n + 3]]),
+            mixer_descr_str: u16::from_le_bytes([value[n + 4], value[n + 5]]),
         })
     }
 }
@@ -3398,18 +3821,25 @@ impl TryFrom<&[u8]> for AudioProcessingUnitExtended1 {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> error::Result<Self> {
-        if value.len() < 3 {
+        if value.is_empty() {
             return Err(Error::new_descriptor_len(
                 "AudioProcessingUnitExtended1",
-                3,
+                1,
                 value.len(),
             ));
         }
 
         let nr_modes = value[0];
-        let modes = (1..value.len())
-            .step_by(2)
-            .map(|i| u16::from_le_bytes([value[i], value[i + 1]]))
+        let expected_len = 1 + nr_modes as usize * 2;
+        if value.len() < expected_len {
+            return Err(Error::new(
+                ErrorKind::InvalidDescriptor,
+                "AudioProcessingUnitExtended1 descriptor too short for the number of modes",
+            ));
+        }
+        let modes = value[1..expected_len]
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
             .collect();
 
         Ok(AudioProcessingUnitExtended1 { nr_modes, modes })
@@ -3512,11 +3942,11 @@ impl From<ProcessingUnit1> for Vec<u8> {
         data.push(val.channel_names_index);
         data.push(val.control_size);
         data.extend_from_slice(&val.controls);
+        data.push(val.processing_index);
         if let Some(specific) = val.specific {
             let specific_data: Vec<u8> = specific.into();
             data.extend_from_slice(&specific_data);
         }
-        data.push(val.processing_index);
         data
     }
 }
@@ -3540,18 +3970,25 @@ impl TryFrom<&[u8]> for AudioProcessingUnit2UpDownMix {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> error::Result<Self> {
-        if value.len() < 5 {
+        if value.is_empty() {
             return Err(Error::new_descriptor_len(
                 "AudioProcessingUnit2UpDownMix",
-                5,
+                1,
                 value.len(),
             ));
         }
 
         let nr_modes = value[0];
-        let modes = (1..value.len())
-            .step_by(4)
-            .map(|i| u32::from_le_bytes([value[i], value[i + 1], value[i + 2], value[i + 3]]))
+        let expected_len = 1 + nr_modes as usize * 4;
+        if value.len() < expected_len {
+            return Err(Error::new(
+                ErrorKind::InvalidDescriptor,
+                "AudioProcessingUnit2UpDownMix descriptor too short for the number of modes",
+            ));
+        }
+        let modes = value[1..expected_len]
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
             .collect();
 
         Ok(AudioProcessingUnit2UpDownMix { nr_modes, modes })
@@ -3581,18 +4018,25 @@ impl TryFrom<&[u8]> for AudioProcessingUnit2DolbyPrologic {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> error::Result<Self> {
-        if value.len() < 5 {
+        if value.is_empty() {
             return Err(Error::new_descriptor_len(
                 "AudioProcessingUnit2DolbyPrologic",
-                5,
+                1,
                 value.len(),
             ));
         }
 
         let nr_modes = value[0];
-        let modes = (1..value.len())
-            .step_by(4)
-            .map(|i| u32::from_le_bytes([value[i], value[i + 1], value[i + 2], value[i + 3]]))
+        let expected_len = 1 + nr_modes as usize * 4;
+        if value.len() < expected_len {
+            return Err(Error::new(
+                ErrorKind::InvalidDescriptor,
+                "AudioProcessingUnit2DolbyPrologic descriptor too short for the number of modes",
+            ));
+        }
+        let modes = value[1..expected_len]
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
             .collect();
 
         Ok(AudioProcessingUnit2DolbyPrologic { nr_modes, modes })
@@ -3640,18 +4084,25 @@ impl TryFrom<&[u8]> for AudioProcessingUnit3UpDownMix {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> error::Result<Self> {
-        if value.len() < 7 {
+        if value.len() < 5 {
             return Err(Error::new_descriptor_len(
                 "AudioProcessingUnit3UpDownMix",
-                7,
+                5,
                 value.len(),
             ));
         }
 
         let nr_modes = value[4];
-        let cluster_descr_ids = (5..value.len())
-            .step_by(2)
-            .map(|i| u16::from_le_bytes([value[i], value[i + 1]]))
+        let expected_len = 5 + nr_modes as usize * 2;
+        if value.len() < expected_len {
+            return Err(Error::new(
+                ErrorKind::InvalidDescriptor,
+                "AudioProcessingUnit3UpDownMix descriptor too short for the number of modes",
+            ));
+        }
+        let cluster_descr_ids = value[5..expected_len]
+            .chunks_exact(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
             .collect();
 
         Ok(AudioProcessingUnit3UpDownMix {
@@ -3845,11 +4296,11 @@ impl From<ProcessingUnit2> for Vec<u8> {
         data.extend_from_slice(&val.channel_config.to_le_bytes());
         data.push(val.channel_names_index);
         data.extend_from_slice(&val.controls.to_le_bytes());
+        data.push(val.processing_index);
         if let Some(specific) = val.specific {
             let specific_data: Vec<u8> = specific.into();
             data.extend_from_slice(&specific_data);
         }
-        data.push(val.processing_index);
         data
     }
 }
@@ -3873,8 +4324,8 @@ pub enum AudioProcessingMultiFunction {
     NoiseSuppression,
 }
 
-impl std::fmt::Display for AudioProcessingMultiFunction {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl core::fmt::Display for AudioProcessingMultiFunction {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         if f.alternate() {
             match self {
                 AudioProcessingMultiFunction::AlgorithmUndefined => {
@@ -3959,12 +4410,12 @@ impl TryFrom<&[u8]> for ProcessingUnit3 {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> error::Result<Self> {
-        if value.len() < 7 {
-            return Err(Error::new_descriptor_len("ProcessingUnit3", 7, value.len()));
+        if value.len() < 6 {
+            return Err(Error::new_descriptor_len("ProcessingUnit3", 6, value.len()));
         }
 
         let nr_in_pins = value[3];
-        let expected_length = 7 + nr_in_pins as usize;
+        let expected_length = 6 + nr_in_pins as usize;
         if value.len() < expected_length {
             return Err(Error::new(
                 ErrorKind::InvalidArg,
@@ -3991,8 +4442,8 @@ impl TryFrom<&[u8]> for ProcessingUnit3 {
             nr_in_pins,
             source_ids: value[4..4 + nr_in_pins as usize].to_vec(),
             processing_descr_str: u16::from_le_bytes([
+                value[4 + nr_in_pins as usize],
                 value[5 + nr_in_pins as usize],
-                value[6 + nr_in_pins as usize],
             ]),
             specific,
         })
@@ -4054,9 +4505,11 @@ impl TryFrom<&[u8]> for EffectUnit2 {
             return Err(Error::new_descriptor_len("EffectUnit2", 9, value.len()));
         }
 
-        let controls = (4..value.len() - 1)
-            .step_by(4)
-            .map(|i| u32::from_le_bytes([value[i], value[i + 1], value[i + 2], value[i + 3]]))
+        // controls for each channel fill the descriptor up to the effect index
+        let controls_end = 4 + (value.len() - 5) / 4 * 4;
+        let controls = value[4..controls_end]
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
             .collect();
 
         Ok(EffectUnit2 {
@@ -4064,7 +4517,7 @@ impl TryFrom<&[u8]> for EffectUnit2 {
             effect_type: u16::from_le_bytes([value[1], value[2]]),
             source_id: value[3],
             controls,
-            effect_index: value[value.len() - 1],
+            effect_index: value[controls_end],
             effect: None,
         })
     }
@@ -4103,9 +4556,11 @@ impl TryFrom<&[u8]> for EffectUnit3 {
             return Err(Error::new_descriptor_len("EffectUnit3", 10, value.len()));
         }
 
-        let controls = (4..value.len() - 2)
-            .step_by(4)
-            .map(|i| u32::from_le_bytes([value[i], value[i + 1], value[i + 2], value[i + 3]]))
+        // controls for each channel fill the descriptor up to the effect string
+        let controls_end = 4 + (value.len() - 6) / 4 * 4;
+        let controls = value[4..controls_end]
+            .chunks_exact(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
             .collect();
 
         Ok(EffectUnit3 {
@@ -4113,7 +4568,7 @@ impl TryFrom<&[u8]> for EffectUnit3 {
             effect_type: u16::from_le_bytes([value[1], value[2]]),
             source_id: value[3],
             controls,
-            effect_descr_str: u16::from_le_bytes([value[value.len() - 2], value[value.len() - 1]]),
+            effect_descr_str: u16::from_le_bytes([value[controls_end], value[controls_end + 1]]),
         })
     }
 }
@@ -4192,24 +4647,39 @@ impl From<FeatureUnit1> for Vec<u8> {
 pub struct FeatureUnit2 {
     pub unit_id: u8,
     pub source_id: u8,
+    /// Master channel bmaControls(0)
     pub controls: [u8; 4],
+    /// bmaControls of each logical channel after the master channel
+    #[serde(default)]
+    pub channel_controls: Vec<[u8; 4]>,
     pub feature_index: u8,
     pub feature: Option<String>,
 }
 
+/// bmaControls of the channels after the master channel in `value`, which are followed by `trailing` bytes
+fn feature_channel_controls(value: &[u8], trailing: usize) -> Vec<[u8; 4]> {
+    value[6..value.len() - trailing]
+        .chunks_exact(4)
+        .map(|c| [c[0], c[1], c[2], c[3]])
+        .collect()
+}
+
 impl TryFrom<&[u8]> for FeatureUnit2 {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> error::Result<Self> {
-        if value.len() < 8 {
-            return Err(Error::new_descriptor_len("FeatureUnit2", 8, value.len()));
+        if value.len() < 7 {
+            return Err(Error::new_descriptor_len("FeatureUnit2", 7, value.len()));
         }
 
+        let channel_controls = feature_channel_controls(value, 1);
+
         Ok(FeatureUnit2 {
             unit_id: value[0],
             source_id: value[1],
-            controls: value[2..6].try_into().unwrap(),
-            feature_index: value[7],
+            controls: [value[2], value[3], value[4], value[5]],
+            feature_index: value[6 + channel_controls.len() * 4],
+            channel_controls,
             feature: None,
         })
     }
@@ -4221,6 +4691,9 @@ impl From<FeatureUnit2> for Vec<u8> {
         data.push(val.unit_id);
         data.push(val.source_id);
         data.extend_from_slice(&val.controls);
+        for controls in val.channel_controls {
+            data.extend_from_slice(&controls);
+        }
         data.push(val.feature_index);
         data
     }
@@ -4232,7 +4705,11 @@ impl From<FeatureUnit2> for Vec<u8> {
 pub struct FeatureUnit3 {
     pub unit_id: u8,
     pub source_id: u8,
+    /// Master channel bmaControls(0)
     pub controls: [u8; 4],
+    /// bmaControls of each logical channel after the master channel
+    #[serde(default)]
+    pub channel_controls: Vec<[u8; 4]>,
     pub feature_descr_str: u16,
 }
 
@@ -4244,11 +4721,15 @@ impl TryFrom<&[u8]> for FeatureUnit3 {
             return Err(Error::new_descriptor_len("FeatureUnit3", 8, value.len()));
         }
 
+        let channel_controls = feature_channel_controls(value, 2);
+        let n = 6 + channel_controls.len() * 4;
+
         Ok(FeatureUnit3 {
             unit_id: value[0],
             source_id: value[1],
-            controls: value[2..6].try_into().unwrap(),
-            feature_descr_str: u16::from_le_bytes([value[6], value[7]]),
+            controls: [value[2], value[3], value[4], value[5]],
+            channel_controls,
+            feature_descr_str: u16::from_le_bytes([value[n], value[n + 1]]),
         })
     }
 }
@@ -4259,6 +4740,9 @@ impl From<FeatureUnit3> for Vec<u8> {
         data.push(val.unit_id);
         data.push(val.source_id);
         data.extend_from_slice(&val.controls);
+        for controls in val.channel_controls {
+            data.extend_from_slice(&controls);
+        }
         data.extend_from_slice(&val.feature_descr_str.to_le_bytes());
         data
     }
@@ -4291,7 +4775,9 @@ impl TryFrom<&[u8]> for ExtensionUnit1 {
         }
 
         let nr_in_pins = value[3] as usize;
-        let control_size = value[8 + nr_in_pins];
+        let control_size = *value.get(8 + nr_in_pins).ok_or_else(|| {
+            Error::new_descriptor_len("ExtensionUnit1", 10 + nr_in_pins, value.len())
+        })?;
         let expected_length = 10 + nr_in_pins + control_size as usize;
         if value.len() < expected_length {
             return Err(Error::new(
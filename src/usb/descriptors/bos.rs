@@ -1,6 +1,6 @@
 //! Binary Object Store (BOS) descriptor types and capabilities parsing
+use core::convert::TryFrom;
 use serde::{Deserialize, Serialize};
-use std::convert::TryFrom;
 use uuid::{uuid, Uuid};
 
 use super::*;
@@ -190,7 +190,8 @@ impl TryFrom<&[u8]> for BinaryObjectStoreDescriptor {
         // already checked that the total length is correct
         while offset < total_length as usize {
             let cd_len = value[offset] as usize;
-            if value.len() < offset + cd_len {
+            // a length shorter than the capability header would never advance
+            if cd_len < 3 || value.len() < offset + cd_len {
                 // break if we're going to read past the end of the buffer rather than Err so all is not lost...
                 log::warn!("BOS capability has invalid length, breaking");
                 break;
@@ -468,8 +469,13 @@ pub struct SuperSpeedPlusCapability {
     pub length: u8,
     pub descriptor_type: u8,
     pub capability_type: BosType,
+    #[serde(default)]
+    pub reserved: u8,
     pub attributes: u32,
     pub functionality_supported: u16,
+    /// wReserved after wFunctionalitySupport
+    #[serde(default)]
+    pub reserved_functionality: u16,
     pub sublink_attributes: Vec<u32>,
 }
 
@@ -496,7 +502,7 @@ impl TryFrom<&[u8]> for SuperSpeedPlusCapability {
             ));
         }
 
-        for chunk in value[12..12 + sublink_speed_attr_count].chunks_exact(4) {
+        for chunk in value[12..12 + sublink_speed_attr_count * 4].chunks_exact(4) {
             sublink_attributes.push(u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]));
         }
 
@@ -504,8 +510,10 @@ impl TryFrom<&[u8]> for SuperSpeedPlusCapability {
             length: value[0],
             descriptor_type: value[1],
             capability_type: value[2].into(),
+            reserved: value[3],
             attributes: u32::from_le_bytes([value[4], value[5], value[6], value[7]]),
             functionality_supported: u16::from_le_bytes([value[8], value[9]]),
+            reserved_functionality: u16::from_le_bytes([value[10], value[11]]),
             sublink_attributes,
         })
     }
@@ -513,12 +521,15 @@ impl TryFrom<&[u8]> for SuperSpeedPlusCapability {
 
 impl From<SuperSpeedPlusCapability> for Vec<u8> {
     fn from(sspc: SuperSpeedPlusCapability) -> Self {
-        let mut ret = Vec::new();
-        ret.push(sspc.length);
-        ret.push(sspc.descriptor_type);
-        ret.push(u8::from(sspc.capability_type));
+        let mut ret = vec![
+            sspc.length,
+            sspc.descriptor_type,
+            u8::from(sspc.capability_type),
+            sspc.reserved,
+        ];
         ret.extend(sspc.attributes.to_le_bytes());
         ret.extend(sspc.functionality_supported.to_le_bytes());
+        ret.extend(sspc.reserved_functionality.to_le_bytes());
         for attr in sspc.sublink_attributes {
             ret.extend(attr.to_le_bytes());
         }
@@ -620,12 +631,6 @@ impl TryFrom<&[u8]> for BillboardCapability {
             i += 4;
         }
 
-        let version = if value[41] == 0 {
-            Version::from_bcd(u16::from_le_bytes([value[40], 1]))
-        } else {
-            Version::from_bcd(u16::from_le_bytes([value[40], value[41]]))
-        };
-
         Ok(BillboardCapability {
             length: value[0],
             descriptor_type: value[1],
@@ -636,7 +641,7 @@ impl TryFrom<&[u8]> for BillboardCapability {
             preferred_alternate_mode: value[5],
             vconn_power: u16::from_le_bytes([value[6], value[7]]),
             configured: value[8..40].try_into().expect("bmConfigured slice error"),
-            version,
+            version: Version::from_bcd(u16::from_le_bytes([value[40], value[41]])),
             additional_failure_info: value[42],
             reserved: value[43],
             alternate_modes,
@@ -778,7 +783,7 @@ pub struct ConfigurationSummaryCapability {
     pub sub_class: u8,
     pub protocol: u8,
     pub configuration_count: u8,
-    pub configurations: u8,
+    /// bConfigurationIndex of each configuration with the function
     pub configured: Vec<u8>,
 }
 
@@ -786,19 +791,19 @@ impl TryFrom<&[u8]> for ConfigurationSummaryCapability {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> error::Result<Self> {
-        if value.len() < 10 {
+        if value.len() < 9 {
             return Err(Error::new_descriptor_len(
                 "ConfigurationSummaryCapability",
-                10,
+                9,
                 value.len(),
             ));
         }
 
-        let configured_count = value[7];
-        if value.len() < 10 + configured_count as usize {
+        let configured_count = value[8];
+        if value.len() < 9 + configured_count as usize {
             return Err(Error::new_descriptor_len(
                 "ConfigurationSummaryCapability configured_count",
-                10 + configured_count as usize,
+                9 + configured_count as usize,
                 value.len(),
             ));
         }
@@ -812,8 +817,7 @@ impl TryFrom<&[u8]> for ConfigurationSummaryCapability {
             sub_class: value[6],
             protocol: value[7],
             configuration_count: configured_count,
-            configurations: value[8],
-            configured: value[9..].to_vec(),
+            configured: value[9..9 + configured_count as usize].to_vec(),
         })
     }
 }
@@ -829,7 +833,6 @@ impl From<ConfigurationSummaryCapability> for Vec<u8> {
         ret.push(ucs.sub_class);
         ret.push(ucs.protocol);
         ret.push(ucs.configuration_count);
-        ret.push(ucs.configurations);
         ret.extend(ucs.configured);
 
         ret
@@ -1,6 +1,6 @@
 //! Defines for the USB Communication Device Class (CDC) descriptors
+use core::convert::TryFrom;
 use serde::{Deserialize, Serialize};
-use std::convert::TryFrom;
 use uuid::Uuid;
 
 use super::*;
@@ -47,8 +47,8 @@ pub enum CdcType {
     Unknown = 0xff,
 }
 
-impl std::fmt::Display for CdcType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for CdcType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         // lsusb style
         if f.alternate() {
             match self {
@@ -659,8 +659,8 @@ impl TryFrom<&[u8]> for MbimExtended {
     type Error = Error;
 
     fn try_from(value: &[u8]) -> error::Result<Self> {
-        if value.len() < 6 {
-            return Err(Error::new_descriptor_len("MBIM Extended", 6, value.len()));
+        if value.len() < 5 {
+            return Err(Error::new_descriptor_len("MBIM Extended", 5, value.len()));
         }
 
         let version = Version::from_bcd(u16::from_le_bytes([value[0], value[1]]));
@@ -814,6 +814,13 @@ impl TryFrom<&[u8]> for CommunicationDescriptor {
         }
 
         let communication_type = CdcType::from(value[2]);
+        // left as a GenericDescriptor rather than losing the subtype
+        if communication_type == CdcType::Unknown {
+            return Err(Error::new(
+                ErrorKind::InvalidDescriptor,
+                &format!("Unknown CommunicationDescriptor subtype {:#04x}", value[2]),
+            ));
+        }
         let interface = CdcInterfaceDescriptor::from_cdc_descriptor(
             &DescriptorType::Interface,
             &communication_type,
@@ -0,0 +1,378 @@
+//! Checks that descriptors round-trip through their bytes, exported with the 'test-utils' feature so custom descriptor extensions can be checked the same way
+//!
+//! Every descriptor struct implements `TryFrom<&[u8]>` and `From<T> for Vec<u8>`. The invariant is that for any bytes which parse, the encoded descriptor is the same bytes, up to any trailing bytes the descriptor does not cover, and parses again to an equal descriptor if it parses at all; encoded bytes shorter than a reported length may not. A parser which reads a field from the wrong offset or drops part of a variable length field breaks one of these.
+//!
+//! [`ByteGen`] generates random descriptor bytes from a seed so failures can be reproduced, and [`check_round_trip`] checks the invariant for one input:
+//!
+//! ```
+//! use cyme::usb::descriptors::round_trip::{fuzz_round_trip, ByteGen};
+//! use cyme::usb::descriptors::audio::MixerUnit3;
+//!
+//! let mut gen = ByteGen::new(0xc0ffee);
+//! let parsed = fuzz_round_trip::<MixerUnit3>(&mut gen, 1000, 32).unwrap();
+//! assert!(parsed > 0);
+//! ```
+use core::convert::TryFrom;
+use core::fmt;
+
+use super::*;
+
+/// Mixed into the seed so that small seeds do not start with mostly zero bits
+const GOLDEN_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Xorshift random bytes generator; not for cryptographic use
+#[derive(Debug, Clone)]
+pub struct ByteGen {
+    state: u64,
+}
+
+impl ByteGen {
+    /// New generator from `seed`; the same seed generates the same bytes
+    pub fn new(seed: u64) -> Self {
+        // xorshift state must not be zero, which the xor makes it for that one seed
+        let state = match seed ^ GOLDEN_GAMMA {
+            0 => GOLDEN_GAMMA,
+            s => s,
+        };
+        ByteGen { state }
+    }
+
+    /// Next random u64
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    /// Next random byte
+    pub fn next_u8(&mut self) -> u8 {
+        (self.next_u64() >> 56) as u8
+    }
+
+    /// `len` random bytes
+    pub fn bytes(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next_u8()).collect()
+    }
+
+    /// Random bytes with a random length up to and including `max_len`
+    ///
+    /// Small values are more likely in the bytes since they are used as counts and lengths by descriptors; uniformly random counts are nearly always too large for the bytes to parse.
+    pub fn descriptor(&mut self, max_len: usize) -> Vec<u8> {
+        let len = (self.next_u64() % (max_len as u64 + 1)) as usize;
+        (0..len)
+            .map(|_| match self.next_u8() % 4 {
+                0 => self.next_u8() % 8,
+                1 => len as u8,
+                _ => self.next_u8(),
+            })
+            .collect()
+    }
+}
+
+/// Check the round-trip invariant of `T` for `bytes`
+///
+/// Returns `Ok(None)` if `bytes` do not parse as `T` and `Ok(Some(encoded))` if they round-trip. Errors with [`ErrorKind::InvalidDescriptor`] describing the violation if they parse but the encoded bytes are not the start of `bytes` or parse to a different `T`.
+///
+/// ```
+/// use cyme::usb::descriptors::round_trip::check_round_trip;
+/// use cyme::usb::descriptors::audio::MixerUnit3;
+///
+/// // three pins and two bytes of mixer controls
+/// let bytes = [0x05, 0x03, 0x01, 0x02, 0x03, 0x10, 0x00, 0xaa, 0xbb, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00];
+/// assert_eq!(check_round_trip::<MixerUnit3>(&bytes).unwrap().unwrap(), bytes);
+/// ```
+pub fn check_round_trip<T>(bytes: &[u8]) -> error::Result<Option<Vec<u8>>>
+where
+    T: for<'a> TryFrom<&'a [u8]> + Into<Vec<u8>> + Clone + PartialEq + fmt::Debug,
+{
+    let parsed = match T::try_from(bytes) {
+        Ok(t) => t,
+        Err(_) => return Ok(None),
+    };
+    let encoded: Vec<u8> = parsed.clone().into();
+    if !bytes.starts_with(&encoded) {
+        return Err(Error::new(
+            ErrorKind::InvalidDescriptor,
+            &format!(
+                "{:?} encoded as {:02x?} which is not the parsed bytes {:02x?}",
+                parsed, encoded, bytes
+            ),
+        ));
+    }
+    // encoded bytes shorter than a reported length, such as when the descriptor has fields cyme does not know, need not parse but must not parse differently
+    match T::try_from(encoded.as_slice()).ok() {
+        Some(reparsed) if reparsed != parsed => Err(Error::new(
+            ErrorKind::InvalidDescriptor,
+            &format!(
+                "{:?} encoded as {:02x?} parses to {:?}",
+                parsed, encoded, reparsed
+            ),
+        )),
+        _ => Ok(Some(encoded)),
+    }
+}
+
+/// [`check_round_trip`] for `iterations` of [`ByteGen::descriptor`] bytes up to `max_len`, returning the number which parsed
+///
+/// Errors with the first violation.
+pub fn fuzz_round_trip<T>(
+    gen: &mut ByteGen,
+    iterations: usize,
+    max_len: usize,
+) -> error::Result<usize>
+where
+    T: for<'a> TryFrom<&'a [u8]> + Into<Vec<u8>> + Clone + PartialEq + fmt::Debug,
+{
+    let mut parsed = 0;
+    for _ in 0..iterations {
+        if check_round_trip::<T>(&gen.descriptor(max_len))?.is_some() {
+            parsed += 1;
+        }
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fuzz each type with the same seed, panicking with the type and violation
+    macro_rules! fuzz_all {
+        ($($t:ty),+ $(,)?) => {
+            $(
+                let mut gen = ByteGen::new(1);
+                if let Err(e) = fuzz_round_trip::<$t>(&mut gen, 5000, 64) {
+                    panic!("{}: {}", stringify!($t), e);
+                }
+            )+
+        };
+    }
+
+    #[test]
+    fn test_byte_gen_seed() {
+        // the seed xor'd to zero would otherwise only generate zeros
+        let mut gen = ByteGen::new(GOLDEN_GAMMA);
+        assert_ne!(gen.next_u64(), 0);
+        assert_eq!(ByteGen::new(7).bytes(16), ByteGen::new(7).bytes(16));
+    }
+
+    #[test]
+    fn test_fuzz_audio() {
+        fuzz_all!(
+            audio::MidiDescriptor,
+            audio::Header,
+            audio::InputJack,
+            audio::OutputJack,
+            audio::Element,
+            audio::MidiEndpointDescriptor,
+            audio::StreamingFormatSpecific,
+            audio::FormatTypeI1,
+            audio::FormatTypeII1,
+            audio::FormatTypeIII1,
+            audio::FormatTypeI2,
+            audio::FormatTypeII2,
+            audio::FormatSpecificMpeg,
+            audio::FormatSpecificAc3,
+            audio::Header1,
+            audio::Header2,
+            audio::Header3,
+            audio::InputTerminal1,
+            audio::InputTerminal2,
+            audio::InputTerminal3,
+            audio::OutputTerminal1,
+            audio::OutputTerminal2,
+            audio::OutputTerminal3,
+            audio::ExtendedTerminalHeader,
+            audio::PowerDomain,
+            audio::MixerUnit1,
+            audio::MixerUnit2,
+            audio::MixerUnit3,
+            audio::StreamingInterface1,
+            audio::StreamingInterface2,
+            audio::StreamingInterface3,
+            audio::DataStreamingEndpoint1,
+            audio::DataStreamingEndpoint2,
+            audio::DataStreamingEndpoint3,
+            audio::SelectorUnit1,
+            audio::SelectorUnit2,
+            audio::SelectorUnit3,
+            audio::AudioProcessingUnitExtended1,
+            audio::ProcessingUnit1,
+            audio::AudioProcessingUnit2UpDownMix,
+            audio::AudioProcessingUnit2DolbyPrologic,
+            audio::AudioProcessingUnit3UpDownMix,
+            audio::AudioProcessingUnit3StereoExtender,
+            audio::AudioProcessingUnit3MultiFunction,
+            audio::ProcessingUnit2,
+            audio::ProcessingUnit3,
+            audio::EffectUnit2,
+            audio::EffectUnit3,
+            audio::FeatureUnit1,
+            audio::FeatureUnit2,
+            audio::FeatureUnit3,
+            audio::ExtensionUnit1,
+            audio::ExtensionUnit2,
+            audio::ExtensionUnit3,
+            audio::ClockSource2,
+            audio::ClockSource3,
+            audio::ClockSelector2,
+            audio::ClockSelector3,
+            audio::ClockMultiplier2,
+            audio::ClockMultiplier3,
+            audio::SampleRateConverter2,
+            audio::SampleRateConverter3,
+        );
+    }
+
+    #[test]
+    fn test_fuzz_bos() {
+        fuzz_all!(
+            bos::BosCapability,
+            bos::BinaryObjectStoreDescriptor,
+            bos::GenericCapability,
+            bos::PlatformDeviceCompatibility,
+            bos::WebUsbPlatformCapability,
+            bos::ExtensionCapability,
+            bos::SuperSpeedCapability,
+            bos::SuperSpeedPlusCapability,
+            bos::BillboardCapability,
+            bos::BillboardAltModeCapability,
+            bos::ContainerIdCapability,
+            bos::ConfigurationSummaryCapability,
+        );
+    }
+
+    #[test]
+    fn test_fuzz_cdc() {
+        fuzz_all!(
+            cdc::Header,
+            cdc::CallManagement,
+            cdc::AbstractControlManagement,
+            cdc::Union,
+            cdc::CountrySelection,
+            cdc::TelephoneOperations,
+            cdc::NetworkChannel,
+            cdc::EthernetNetworking,
+            cdc::MobileDirectLineModelFunctional,
+            cdc::MobileDirectLineModelDetail,
+            cdc::DeviceManagement,
+            cdc::CommandSet,
+            cdc::Ncm,
+            cdc::Mbim,
+            cdc::MbimExtended,
+            cdc::CommunicationDescriptor,
+        );
+    }
+
+    #[test]
+    fn test_fuzz_video() {
+        fuzz_all!(
+            video::Header,
+            video::TerminalExtra,
+            video::InputTerminal,
+            video::ProcessingUnit,
+            video::ExtensionUnit,
+            video::EncodingUnit,
+            video::InputHeader,
+            video::OutputHeader,
+            video::StillImageFrame,
+            video::ColorFormat,
+            video::FormatStreamBased,
+            video::FormatMPEG2TS,
+            video::FormatMJPEG,
+            video::FormatFrame,
+            video::FrameCommon,
+            video::FrameUncompressed,
+            video::FrameFrameBased,
+        );
+    }
+
+    #[test]
+    fn test_fuzz_descriptors() {
+        fuzz_all!(
+            DeviceDescriptor,
+            Descriptor,
+            InterfaceAssociationDescriptor,
+            SsEndpointCompanionDescriptor,
+            SecurityDescriptor,
+            EncryptionDescriptor,
+            ClassDescriptor,
+            HidReportDescriptor,
+            GenericDescriptor,
+            HidDescriptor,
+            CcidDescriptor,
+            PrinterDescriptor,
+            PrinterReportDescriptor,
+            HubDescriptor,
+            DfuDescriptor,
+            DebugDescriptor,
+            DeviceQualifierDescriptor,
+            OnTheGoDescriptor,
+        );
+    }
+
+    #[test]
+    fn test_printer_basic_capabilities() {
+        // one basic capabilities descriptor, which has its type before its length
+        let bytes = [0x0a, 0x21, 0x00, 0x01, 0x00, 0x04, 0x01, 0x00, 0x01, 0x00];
+        let printer = PrinterDescriptor::try_from(&bytes[..]).unwrap();
+        assert_eq!(printer.descriptors[0].descriptor_type, 0x00);
+        assert_eq!(printer.descriptors[0].length, 0x04);
+        assert_eq!(
+            check_round_trip::<PrinterDescriptor>(&bytes)
+                .unwrap()
+                .unwrap(),
+            bytes
+        );
+    }
+
+    #[test]
+    fn test_otg_descriptor() {
+        // OTG 1.3 with SRP and HNP
+        let otg = OnTheGoDescriptor::try_from(&[0x03, 0x09, 0x03][..]).unwrap();
+        assert!(otg.srp() && otg.hnp() && !otg.adp());
+        assert_eq!(otg.to_string(), "SRP HNP");
+        // OTG 2.0 with HNP and ADP
+        let bytes = [0x05, 0x09, 0x06, 0x00, 0x02];
+        let otg = OnTheGoDescriptor::try_from(&bytes[..]).unwrap();
+        assert_eq!(otg.otg_version, Some(Version(2, 0, 0)));
+        assert_eq!(otg.to_string(), "HNP ADP");
+        assert_eq!(
+            check_round_trip::<OnTheGoDescriptor>(&bytes)
+                .unwrap()
+                .unwrap(),
+            bytes
+        );
+        assert!(OnTheGoDescriptor::try_from(&[0x04, 0x09, 0x03, 0x00][..]).is_err());
+    }
+
+    #[test]
+    fn test_feature_unit_channel_controls() {
+        // master and two logical channels
+        let bytes = [
+            0x06, 0x04, 0x01, 0x00, 0x00, 0x00, 0x0c, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00,
+            0x05,
+        ];
+        let unit = audio::FeatureUnit2::try_from(&bytes[..]).unwrap();
+        assert_eq!(
+            unit.channel_controls,
+            vec![[0x0c, 0, 0, 0], [0x03, 0, 0, 0]]
+        );
+        assert_eq!(unit.feature_index, 0x05);
+        assert_eq!(
+            check_round_trip::<audio::FeatureUnit2>(&bytes)
+                .unwrap()
+                .unwrap(),
+            bytes
+        );
+    }
+
+    #[test]
+    fn test_gen_reproducible() {
+        assert_eq!(ByteGen::new(7).bytes(16), ByteGen::new(7).bytes(16));
+        assert_ne!(ByteGen::new(7).bytes(16), ByteGen::new(8).bytes(16));
+    }
+}
@@ -0,0 +1,254 @@
+//! Decoding of vendor-specific class descriptors with registered [`Decoder`]s; extends the `usb` module.
+//!
+//! Class descriptors which cyme does not know how to parse are left as [`GenericDescriptor`] bytes. A [`Decoder`] registered in a [`Registry`] for a [`DecoderKey`] is given these descriptors when profiling with the registry, set with `ProfilerBuilder::vendor_decoders`, and the returned [`VendorField`]s are added to the [`Interface`](crate::usb::Interface) for display and JSON.
+//!
+//! ```
+//! use cyme::usb::descriptors::vendor::*;
+//! use cyme::usb::descriptors::GenericDescriptor;
+//!
+//! struct FirmwareDecoder;
+//!
+//! impl Decoder for FirmwareDecoder {
+//!     fn decode(&self, descriptor: &GenericDescriptor) -> Option<Vec<VendorField>> {
+//!         let data = descriptor.data.as_ref()?;
+//!         Some(vec![VendorField::new("firmware", format!("{}.{}", data.first()?, data.get(1)?))])
+//!     }
+//! }
+//!
+//! let mut registry = Registry::new();
+//! registry.register(DecoderKey::device(0x1234, 0x5678), FirmwareDecoder);
+//! ```
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+/// Decoded key/value pair from a vendor-specific descriptor
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VendorField {
+    /// Name of the field
+    pub name: String,
+    /// Decoded value
+    pub value: String,
+}
+
+impl VendorField {
+    /// Create a new [`VendorField`] from anything that can be displayed
+    pub fn new<N: Into<String>, V: fmt::Display>(name: N, value: V) -> Self {
+        VendorField {
+            name: name.into(),
+            value: value.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for VendorField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.name, self.value)
+    }
+}
+
+/// Which descriptors a [`Decoder`] is used for; `None` matches any
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DecoderKey {
+    /// Device vendor ID
+    pub vendor_id: Option<u16>,
+    /// Device product ID
+    pub product_id: Option<u16>,
+    /// Interface (Base Class, Sub Class, Protocol) triplet the descriptor belongs to
+    pub class: Option<ClassCodeTriplet<BaseClass>>,
+}
+
+impl DecoderKey {
+    /// Key matching all descriptors of a VID:PID device
+    pub fn device(vendor_id: u16, product_id: u16) -> Self {
+        DecoderKey {
+            vendor_id: Some(vendor_id),
+            product_id: Some(product_id),
+            class: None,
+        }
+    }
+
+    /// Key matching descriptors of interfaces with the class triplet on any device from the vendor
+    pub fn vendor_class(vendor_id: u16, class: ClassCodeTriplet<BaseClass>) -> Self {
+        DecoderKey {
+            vendor_id: Some(vendor_id),
+            product_id: None,
+            class: Some(class),
+        }
+    }
+
+    /// Whether the key matches the device and interface class
+    pub fn matches(
+        &self,
+        vendor_id: u16,
+        product_id: u16,
+        class: &ClassCodeTriplet<BaseClass>,
+    ) -> bool {
+        self.vendor_id.is_none_or(|v| v == vendor_id)
+            && self.product_id.is_none_or(|p| p == product_id)
+            && self.class.as_ref().is_none_or(|c| c == class)
+    }
+}
+
+/// Decodes a vendor-specific class descriptor into [`VendorField`]s
+pub trait Decoder: Send + Sync {
+    /// Decode the descriptor; `None` if it is not one the decoder understands
+    fn decode(&self, descriptor: &GenericDescriptor) -> Option<Vec<VendorField>>;
+}
+
+/// Field of a [`TableDecoder`] at a byte offset into the descriptor
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableField {
+    /// Name of the field
+    pub name: String,
+    /// Offset from the start of the descriptor including bLength, bDescriptorType and bDescriptorSubtype
+    pub offset: usize,
+    /// Size in bytes; 1, 2 and 4 are shown as little-endian hex values, otherwise as a hex string
+    pub size: usize,
+}
+
+/// [`Decoder`] from a lookup table of fields so that decoding can be configured without code
+#[derive(Debug, Default, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableDecoder {
+    /// Only decode descriptors with this bDescriptorSubtype
+    pub descriptor_subtype: Option<u8>,
+    /// Fields to decode; those beyond the end of the descriptor are skipped
+    pub fields: Vec<TableField>,
+}
+
+impl Decoder for TableDecoder {
+    fn decode(&self, descriptor: &GenericDescriptor) -> Option<Vec<VendorField>> {
+        if self
+            .descriptor_subtype
+            .is_some_and(|s| s != descriptor.descriptor_subtype)
+        {
+            return None;
+        }
+
+        let bytes = descriptor.to_vec();
+        let fields: Vec<VendorField> = self
+            .fields
+            .iter()
+            .filter_map(|f| {
+                let b = bytes.get(f.offset..f.offset.checked_add(f.size)?)?;
+                let value = match b.len() {
+                    1 => format!("0x{:02x}", b[0]),
+                    2 => format!("0x{:04x}", u16::from_le_bytes([b[0], b[1]])),
+                    4 => format!("0x{:08x}", u32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+                    _ => b.iter().map(|b| format!("{:02x}", b)).collect(),
+                };
+                Some(VendorField::new(f.name.as_str(), value))
+            })
+            .collect();
+
+        if fields.is_empty() {
+            None
+        } else {
+            Some(fields)
+        }
+    }
+}
+
+/// Collection of [`Decoder`]s by [`DecoderKey`]
+#[derive(Default)]
+pub struct Registry {
+    decoders: Vec<(DecoderKey, Box<dyn Decoder>)>,
+}
+
+impl fmt::Debug for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Registry")
+            .field(
+                "keys",
+                &self.decoders.iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Registry {
+    /// New empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a decoder for descriptors matching `key`
+    pub fn register<D: Decoder + 'static>(&mut self, key: DecoderKey, decoder: D) {
+        self.decoders.push((key, Box::new(decoder)));
+    }
+
+    /// Number of registered decoders
+    pub fn len(&self) -> usize {
+        self.decoders.len()
+    }
+
+    /// Whether there are no registered decoders
+    pub fn is_empty(&self) -> bool {
+        self.decoders.is_empty()
+    }
+
+    /// Decode the descriptor with all matching decoders in the order they were registered
+    pub fn decode(
+        &self,
+        vendor_id: u16,
+        product_id: u16,
+        class: &ClassCodeTriplet<BaseClass>,
+        descriptor: &GenericDescriptor,
+    ) -> Vec<VendorField> {
+        self.decoders
+            .iter()
+            .filter(|(k, _)| k.matches(vendor_id, product_id, class))
+            .filter_map(|(_, d)| d.decode(descriptor))
+            .flatten()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_decoder_registry() {
+        let descriptor =
+            GenericDescriptor::try_from(&[0x07, 0x24, 0x01, 0x02, 0x10, 0xaa, 0xbb][..]).unwrap();
+        let class = (BaseClass::VendorSpecificClass, 0xff, 0x00);
+        let mut registry = Registry::new();
+        registry.register(
+            DecoderKey::vendor_class(0x1234, class),
+            TableDecoder {
+                descriptor_subtype: Some(0x01),
+                fields: vec![
+                    TableField {
+                        name: String::from("version"),
+                        offset: 3,
+                        size: 2,
+                    },
+                    TableField {
+                        name: String::from("serial"),
+                        offset: 5,
+                        size: 2,
+                    },
+                    TableField {
+                        name: String::from("missing"),
+                        offset: 6,
+                        size: 4,
+                    },
+                ],
+            },
+        );
+
+        let fields = registry.decode(0x1234, 0x0001, &class, &descriptor);
+        assert_eq!(
+            fields
+                .iter()
+                .map(|f| f.to_string())
+                .collect::<Vec<String>>(),
+            vec!["version: 0x1002", "serial: 0xbbaa"]
+        );
+        assert!(registry
+            .decode(0x4321, 0x0001, &class, &descriptor)
+            .is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 //! Defines for the USB Video Class (UVC) interface descriptors
+use core::convert::TryFrom;
 use serde::{Deserialize, Serialize};
-use std::convert::TryFrom;
 use uuid::Uuid;
 
 use super::audio;
@@ -23,8 +23,8 @@ pub enum ControlSubtype {
     EncodingUnit = 0x07,
 }
 
-impl std::fmt::Display for ControlSubtype {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for ControlSubtype {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         // lsusb style
         if f.alternate() {
             match self {
@@ -74,8 +74,8 @@ pub enum StreamingSubtype {
     ColorFormat = 0x0d,
 }
 
-impl std::fmt::Display for StreamingSubtype {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for StreamingSubtype {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         // lsusb style
         if f.alternate() {
             match self {
@@ -119,8 +119,8 @@ pub enum UvcType {
     Streaming(StreamingSubtype),
 }
 
-impl std::fmt::Display for UvcType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for UvcType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         if f.alternate() {
             match self {
                 UvcType::Control(c) => write!(f, "{:#}", c),
@@ -404,6 +404,19 @@ impl From<Header> for Vec<u8> {
     }
 }
 
+/// bmControls bitmap of `control_size` bytes at the start of `value`, which must be long enough; errors if larger than the u32 it is kept in
+fn bitmap_controls(value: &[u8], control_size: usize, name: &str) -> error::Result<u32> {
+    if control_size > 4 {
+        return Err(Error::new(
+            ErrorKind::InvalidDescriptor,
+            &format!("{} descriptor control size {} > 4", name, control_size),
+        ));
+    }
+    let mut bytes = [0; 4];
+    bytes[..control_size].copy_from_slice(&value[..control_size]);
+    Ok(u32::from_le_bytes(bytes))
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 #[allow(missing_docs)]
 pub struct TerminalExtra {
@@ -433,15 +446,12 @@ impl TryFrom<&[u8]> for TerminalExtra {
                 &format!(
                     "Terminal Extra descriptor too short for control size {} < {}",
                     value.len(),
-                    7 + control_size
+                    7 + control_size as usize
                 ),
             ));
         }
 
-        let mut controls: u32 = 0;
-        for i in 0..control_size.min(3) as usize {
-            controls |= (value[7 + i] as u32) << (i * 8);
-        }
+        let controls = bitmap_controls(&value[7..], control_size as usize, "Terminal Extra")?;
 
         Ok(TerminalExtra {
             objective_focal_length_min,
@@ -461,9 +471,7 @@ impl From<TerminalExtra> for Vec<u8> {
         ret.extend_from_slice(&te.ocular_focal_length.to_le_bytes());
         ret.push(te.control_size);
 
-        for i in 0..te.control_size.min(3) {
-            ret.push((te.controls >> (i * 8)) as u8);
-        }
+        ret.extend_from_slice(&te.controls.to_le_bytes()[..te.control_size as usize]);
 
         ret
     }
@@ -566,15 +574,12 @@ impl TryFrom<&[u8]> for ProcessingUnit {
                 &format!(
                     "Processing Unit descriptor too short for control size {} < {}",
                     value.len(),
-                    7 + control_size
+                    7 + control_size as usize
                 ),
             ));
         }
 
-        let mut controls: u32 = 0;
-        for i in 0..control_size.min(3) as usize {
-            controls |= (value[5 + i] as u32) << (i * 8);
-        }
+        let controls = bitmap_controls(&value[5..], control_size as usize, "Processing Unit")?;
 
         let processing_string_index = value[5 + control_size as usize];
         let video_standards = value[6 + control_size as usize];
@@ -600,9 +605,7 @@ impl From<ProcessingUnit> for Vec<u8> {
         ret.extend_from_slice(&pu.max_multiplier.to_le_bytes());
         ret.push(pu.control_size);
 
-        for i in 0..pu.control_size.min(3) {
-            ret.push((pu.controls >> (i * 8)) as u8);
-        }
+        ret.extend_from_slice(&pu.controls.to_le_bytes()[..pu.control_size as usize]);
 
         ret.push(pu.processing_index);
         ret.push(pu.video_standards);
@@ -727,26 +730,20 @@ impl TryFrom<&[u8]> for EncodingUnit {
         let encoding_string_index = value[2];
         let control_size = value[3] as usize;
 
-        if value.len() < 3 + 2 * control_size {
+        if value.len() < 4 + 2 * control_size {
             return Err(Error::new(
                 ErrorKind::InvalidDescriptor,
                 &format!(
                     "Encoding Unit descriptor too short for control size {} < {}",
                     value.len(),
-                    3 + 2 * control_size
+                    4 + 2 * control_size
                 ),
             ));
         }
 
-        let mut controls: u32 = 0;
-        for i in 0..control_size.min(3) {
-            controls |= (value[4 + i] as u32) << (i * 8);
-        }
-
-        let mut controls_runtime: u32 = 0;
-        for i in 0..control_size.min(3) {
-            controls_runtime |= (value[4 + i + control_size] as u32) << (i * 8);
-        }
+        let controls = bitmap_controls(&value[4..], control_size, "Encoding Unit")?;
+        let controls_runtime =
+            bitmap_controls(&value[4 + control_size..], control_size, "Encoding Unit")?;
 
         Ok(EncodingUnit {
             unit_id,
@@ -764,13 +761,8 @@ impl From<EncodingUnit> for Vec<u8> {
     fn from(eu: EncodingUnit) -> Self {
         let mut ret = vec![eu.unit_id, eu.source_id, eu.encoding_index, eu.control_size];
 
-        for i in 0..eu.control_size.min(3) {
-            ret.push((eu.controls >> (i * 8)) as u8);
-        }
-
-        for i in 0..eu.control_size.min(3) {
-            ret.push((eu.controls_runtime >> (i * 8)) as u8);
-        }
+        ret.extend_from_slice(&eu.controls.to_le_bytes()[..eu.control_size as usize]);
+        ret.extend_from_slice(&eu.controls_runtime.to_le_bytes()[..eu.control_size as usize]);
 
         ret
     }
@@ -939,14 +931,18 @@ impl TryFrom<&[u8]> for StillImageFrame {
         let mut image_size_patterns = Vec::new();
         let mut offset = 2;
 
-        if offset + num_image_size_patterns as usize * 4 > value.len() {
+        // +1 for bNumCompressionPattern
+        if offset + num_image_size_patterns as usize * 4 + 1 > value.len() {
             return Err(Error::new(
                 ErrorKind::InvalidDescriptor,
                 "Still Image Frame descriptor too short for image size patterns",
             ));
         }
 
-        for b in value[offset..].chunks_exact(4) {
+        for b in value[offset..]
+            .chunks_exact(4)
+            .take(num_image_size_patterns as usize)
+        {
             let width = u16::from_le_bytes([b[0], b[1]]);
             let height = u16::from_le_bytes([b[2], b[3]]);
             image_size_patterns.push((width, height));
@@ -1470,3 +1466,186 @@ impl From<FrameFrameBased> for Vec<u8> {
         ret
     }
 }
+
+/// Summary of the largest frame size, frame rates and formats supported by the UVC streaming interfaces of a camera
+///
+/// Built from the VS format and frame descriptors with [`VideoFormatSummary::from_descriptors`]. Frame rates are the highest of the frame intervals rounded to whole frames per second.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VideoFormatSummary {
+    /// Largest frame (width, height) of any format
+    pub max_resolution: Option<(u16, u16)>,
+    /// Highest frame rate at `max_resolution`
+    pub max_resolution_fps: Option<u32>,
+    /// Highest frame rate of any frame size
+    pub max_fps: Option<u32>,
+    /// Compressed formats such as 'MJPEG' or 'H264'
+    pub compressed_formats: Vec<String>,
+    /// Uncompressed formats such as 'YUY2' or 'NV12'
+    pub uncompressed_formats: Vec<String>,
+}
+
+/// Displayed in the form '3840x2160@30fps, up to 60fps, MJPEG/H264, raw YUY2'
+impl core::fmt::Display for VideoFormatSummary {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut parts: Vec<String> = Vec::new();
+        if let Some((w, h)) = self.max_resolution {
+            match self.max_resolution_fps {
+                Some(fps) => parts.push(format!("{}x{}@{}fps", w, h, fps)),
+                None => parts.push(format!("{}x{}", w, h)),
+            }
+        }
+        if let Some(fps) = self
+            .max_fps
+            .filter(|fps| self.max_resolution_fps.is_none_or(|r| r < *fps))
+        {
+            parts.push(format!("up to {}fps", fps));
+        }
+        if !self.compressed_formats.is_empty() {
+            parts.push(self.compressed_formats.join("/"));
+        }
+        if !self.uncompressed_formats.is_empty() {
+            parts.push(format!("raw {}", self.uncompressed_formats.join("/")));
+        }
+
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// FourCC of a UVC format GUID such as 'YUY2' or the GUID if it is not one
+fn format_fourcc(guid: &Uuid) -> String {
+    let bytes = guid.to_bytes_le();
+    let fourcc = &bytes[..4];
+    if fourcc
+        .iter()
+        .all(|b| b.is_ascii_alphanumeric() || *b == b' ')
+    {
+        String::from_utf8_lossy(fourcc).trim_end().to_string()
+    } else {
+        guid.to_string()
+    }
+}
+
+/// Highest frame rate of the frame intervals in 100 ns units; continuous intervals are (min, max, step)
+fn max_frame_rate(frame_interval_type: u8, frame_intervals: &[u32]) -> Option<u32> {
+    let min_interval = if frame_interval_type == 0 {
+        frame_intervals.first().copied()
+    } else {
+        frame_intervals.iter().copied().min()
+    };
+    min_interval
+        .filter(|i| *i > 0)
+        .map(|i| (10_000_000 + i / 2) / i)
+}
+
+impl VideoFormatSummary {
+    /// Build the summary from the UVC interface descriptors of streaming interfaces; None if there are no format or frame descriptors
+    ///
+    /// ```
+    /// use cyme::usb::video::*;
+    ///
+    /// let mjpeg = UvcInterfaceDescriptor::FormatMJPEG(FormatMJPEG {
+    ///     format_index: 1,
+    ///     num_frame_descriptors: 1,
+    ///     flags: 0,
+    ///     default_frame_index: 1,
+    ///     aspect_ratio_x: 0,
+    ///     aspect_ratio_y: 0,
+    ///     interlace_flags: 0,
+    ///     copy_protect: 0,
+    /// });
+    /// let frame = UvcInterfaceDescriptor::FrameMJPEG(FrameMJPEG {
+    ///     common: FrameCommon {
+    ///         frame_index: 1,
+    ///         capabilities: 0,
+    ///         width: 3840,
+    ///         height: 2160,
+    ///         min_bit_rate: 0,
+    ///         max_bit_rate: 0,
+    ///     },
+    ///     max_video_frame_buffer_size: 0,
+    ///     default_frame_interval: 333333,
+    ///     frame_interval_type: 2,
+    ///     frame_intervals: vec![333333, 666666],
+    /// });
+    /// let summary = VideoFormatSummary::from_descriptors([&mjpeg, &frame]).unwrap();
+    /// assert_eq!(summary.to_string(), "3840x2160@30fps, MJPEG");
+    /// ```
+    pub fn from_descriptors<'a>(
+        descriptors: impl IntoIterator<Item = &'a UvcInterfaceDescriptor>,
+    ) -> Option<Self> {
+        let mut summary = VideoFormatSummary::default();
+
+        for d in descriptors {
+            let (frame, fps) = match d {
+                UvcInterfaceDescriptor::FormatMJPEG(_) => {
+                    summary.compressed_formats.push("MJPEG".into());
+                    continue;
+                }
+                UvcInterfaceDescriptor::FormatUncompressed(ff) => {
+                    summary
+                        .uncompressed_formats
+                        .push(format_fourcc(&ff.guid_format));
+                    continue;
+                }
+                UvcInterfaceDescriptor::FormatFrameBased(ff) => {
+                    summary
+                        .compressed_formats
+                        .push(format_fourcc(&ff.guid_format));
+                    continue;
+                }
+                UvcInterfaceDescriptor::FormatStreamBased(fsb) => {
+                    summary
+                        .compressed_formats
+                        .push(format_fourcc(&fsb.guid_format));
+                    continue;
+                }
+                UvcInterfaceDescriptor::FormatMPEG2TS(_) => {
+                    summary.compressed_formats.push("MPEG2-TS".into());
+                    continue;
+                }
+                UvcInterfaceDescriptor::FrameUncompressed(fu)
+                | UvcInterfaceDescriptor::FrameMJPEG(fu) => (
+                    &fu.common,
+                    max_frame_rate(fu.frame_interval_type, &fu.frame_intervals),
+                ),
+                UvcInterfaceDescriptor::FrameFrameBased(ffb) => (
+                    &ffb.common,
+                    max_frame_rate(ffb.frame_interval_type, &ffb.frame_intervals),
+                ),
+                _ => continue,
+            };
+
+            let pixels = |r: (u16, u16)| r.0 as u32 * r.1 as u32;
+            let resolution = (frame.width, frame.height);
+            match summary.max_resolution {
+                Some(max) if pixels(max) > pixels(resolution) => (),
+                Some(max) if max == resolution => {
+                    summary.max_resolution_fps = summary.max_resolution_fps.max(fps)
+                }
+                _ => {
+                    summary.max_resolution = Some(resolution);
+                    summary.max_resolution_fps = fps;
+                }
+            }
+            summary.max_fps = summary.max_fps.max(fps);
+        }
+
+        for v in [
+            &mut summary.compressed_formats,
+            &mut summary.uncompressed_formats,
+        ] {
+            let mut seen = Vec::new();
+            v.retain(|f| {
+                let new = !seen.contains(f);
+                seen.push(f.to_owned());
+                new
+            });
+        }
+
+        if summary == VideoFormatSummary::default() {
+            None
+        } else {
+            Some(summary)
+        }
+    }
+}
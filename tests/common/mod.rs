@@ -21,6 +21,8 @@ pub const CYME_LIBUSB_MERGE_MACOS_TREE_DUMP: &str =
 pub const CYME_LIBUSB_MACOS_TREE_DUMP: &str = "./tests/data/cyme_libusb_macos_tree.json";
 /// Dump using Linux with libusb so with [`DeviceExtra`]
 pub const CYME_LIBUSB_LINUX_TREE_DUMP: &str = "./tests/data/cyme_libusb_linux_tree.json";
+/// Raw descriptors of a HID keyboard as written by `cyme export-descriptors`
+pub const DESCRIPTORS_DIR: &str = "./tests/data/descriptors";
 /// Output of lsusb --tree
 pub const LSUSB_TREE_OUTPUT: &str = "./tests/data/lsusb_tree.txt";
 /// Output of lsusb --tree -vvv
@@ -160,3 +160,212 @@ fn test_tree_filtering() {
         false,
     );
 }
+
+#[test]
+fn test_tree_filtering_stub_parents() {
+    let te = common::TestEnv::new();
+
+    let mut comp_sp = common::sp_data_from_libusb_linux();
+    // same filter as the args: --vidpid 1d50 --stub-parents
+    let filter = cyme::profiler::Filter {
+        vid: Some(0x1d50),
+        stub_parents: true,
+        ..Default::default()
+    };
+    filter.retain_buses(&mut comp_sp.buses);
+    let comp = serde_json::to_string_pretty(&comp_sp).unwrap();
+
+    te.assert_output(
+        Some(common::CYME_LIBUSB_LINUX_TREE_DUMP),
+        &["--json", "--tree", "--vidpid", "1d50", "--stub-parents"],
+        &comp,
+        false,
+    );
+}
+
+#[test]
+// windows line ending messes this up
+#[cfg(not(target_os = "windows"))]
+fn test_tree_inline_descriptors() {
+    let te = common::TestEnv::new();
+
+    // tree line continues down to the children of the hub
+    te.assert_output(
+        Some(common::CYME_LIBUSB_LINUX_TREE_DUMP),
+        &[
+            "--tree",
+            "-v",
+            "--inline-descriptors",
+            "--color",
+            "never",
+            "--encoding",
+            "utf8",
+        ],
+        "   │  Device Descriptor:\n   │    bLength               18\n",
+        true,
+    );
+}
+
+#[test]
+fn test_list_json_fields() {
+    let te = common::TestEnv::new();
+
+    let mut comp_sp = common::sp_data_from_libusb_linux();
+    comp_sp.into_flattened();
+    let devices = comp_sp.flattened_devices();
+    let fields = [
+        cyme::display::DeviceBlocks::BusNumber,
+        cyme::display::DeviceBlocks::VendorId,
+        cyme::display::DeviceBlocks::Serial,
+    ];
+    let comp = serde_json::to_string_pretty(&cyme::display::devices_to_json_fields(
+        &devices,
+        &fields,
+        &Default::default(),
+    ))
+    .unwrap();
+
+    te.assert_output(
+        Some(common::CYME_LIBUSB_LINUX_TREE_DUMP),
+        &["--json-fields", "bus-number,vendor-id,serial"],
+        &comp,
+        false,
+    );
+}
+
+#[test]
+fn test_fail_if_empty() {
+    let te = common::TestEnv::new();
+
+    te.assert_success_and_get_output(
+        Some(common::CYME_LIBUSB_LINUX_TREE_DUMP),
+        &["--json", "--vidpid", "1d50", "--fail-if-empty"],
+    );
+
+    te.assert_failure(
+        Some(common::CYME_LIBUSB_LINUX_TREE_DUMP),
+        &["--json", "--vidpid", "dead:beef", "--fail-if-empty"],
+    );
+
+    // like grep: 1 and no output for no match, only the count with --count, and 2 for errors
+    let run = |args: &[&str]| {
+        std::process::Command::new(te.test_exe())
+            .args(args)
+            .output()
+            .unwrap()
+    };
+    let output = run(&[
+        "--from-json",
+        common::CYME_LIBUSB_LINUX_TREE_DUMP,
+        "--vidpid",
+        "dead:beef",
+        "--fail-if-empty",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty() && output.stderr.is_empty());
+    let output = run(&[
+        "--from-json",
+        common::CYME_LIBUSB_LINUX_TREE_DUMP,
+        "--vidpid",
+        "dead:beef",
+        "--fail-if-empty",
+        "--count",
+    ]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "0");
+    let output = run(&[
+        "--from-json",
+        "./tests/data/missing.json",
+        "--fail-if-empty",
+    ]);
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_count() {
+    let te = common::TestEnv::new();
+
+    let mut comp_sp = common::sp_data_from_libusb_linux();
+    comp_sp.into_flattened();
+    let count = comp_sp.flattened_devices().len();
+
+    te.assert_output(
+        Some(common::CYME_LIBUSB_LINUX_TREE_DUMP),
+        &["--json", "--count"],
+        &count.to_string(),
+        false,
+    );
+}
+
+#[test]
+fn test_get_block() {
+    let te = common::TestEnv::new();
+
+    te.assert_output(
+        Some(common::CYME_LIBUSB_LINUX_TREE_DUMP),
+        &["--get", "serial", "--filter-serial", "97B6A11D"],
+        "97B6A11D",
+        true,
+    );
+}
+
+#[test]
+fn test_diff_same_dump() {
+    let te = common::TestEnv::new();
+
+    te.assert_output(
+        None,
+        &[
+            "diff",
+            common::CYME_LIBUSB_LINUX_TREE_DUMP,
+            common::CYME_LIBUSB_LINUX_TREE_DUMP,
+        ],
+        "",
+        false,
+    );
+}
+
+#[test]
+fn test_from_descriptors() {
+    let te = common::TestEnv::new();
+
+    te.assert_output(
+        None,
+        &["--from-descriptors", common::DESCRIPTORS_DIR, "--tree"],
+        "\"name\": \"Replay Keyboard\"",
+        true,
+    );
+    te.assert_output(
+        None,
+        &["--from-descriptors", common::DESCRIPTORS_DIR, "--tree"],
+        "\"serial_num\": \"0001\"",
+        true,
+    );
+}
+
+#[test]
+fn test_assert_expect() {
+    let te = common::TestEnv::new();
+
+    te.assert_success_and_get_output(
+        None,
+        &[
+            "assert",
+            "--expect",
+            common::CYME_LIBUSB_LINUX_TREE_DUMP,
+            "--actual",
+            common::CYME_LIBUSB_LINUX_TREE_DUMP,
+        ],
+    );
+
+    te.assert_failure(
+        None,
+        &[
+            "assert",
+            "--expect",
+            common::CYME_LIBUSB_LINUX_TREE_DUMP,
+            "--actual",
+            common::CYME_LIBUSB_MACOS_TREE_DUMP,
+        ],
+    );
+}
@@ -141,3 +141,33 @@ fn test_lsusb_device() {
         &["--lsusb", "--device", "/dev/bus/usb/002"],
     );
 }
+
+/// Tests lsusb short flag combinations and error behaviour
+#[test]
+fn test_lsusb_compat_flags() {
+    let te = common::TestEnv::new();
+
+    te.assert_output(
+        Some(common::CYME_LIBUSB_LINUX_TREE_DUMP),
+        &["-l", "-s", ":24"],
+        "Bus 002 Device 024: ID 1d50:6018 OpenMoko, Inc. Black Magic Debug Probe (Application)",
+        false,
+    );
+    let comp = common::read_dump_to_string(common::LSUSB_TREE_OUTPUT_VERBOSE);
+    te.assert_output(
+        Some(common::CYME_LIBUSB_LINUX_TREE_DUMP),
+        &["-ltvvv"],
+        comp.as_str(),
+        false,
+    );
+    // no match exits with error like lsusb
+    te.assert_failure(
+        Some(common::CYME_LIBUSB_LINUX_TREE_DUMP),
+        &["--lsusb", "-s", "2:99"],
+    );
+    te.assert_failure_with_error(
+        Some(common::CYME_LIBUSB_LINUX_TREE_DUMP),
+        &["--lsusb", "--device", "/dev/blah/002/001"],
+        "Cannot open /dev/blah/002/001",
+    );
+}